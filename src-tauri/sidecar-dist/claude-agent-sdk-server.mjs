@@ -1,7 +1,7 @@
 #!/usr/bin/env node
 // Bridges the Claude Agent SDK to a stdio-based JSON-line protocol for Panes.
 
-import { readFile } from "node:fs/promises";
+import { readFile, stat } from "node:fs/promises";
 import { ChildProcess, execFile } from "node:child_process";
 import path from "node:path";
 import { createInterface } from "node:readline";
@@ -203,6 +203,12 @@ function classifyAttachment(attachment) {
   return null;
 }
 
+class AttachmentTooLargeError extends Error {
+  constructor(fileName) {
+    super(`Attachment "${fileName}" exceeds the 10 MB per-file limit.`);
+  }
+}
+
 async function buildAttachmentContentBlock(attachment, cwd) {
   const resolvedPath = normalizePath(cwd, attachment?.filePath ?? attachment?.path);
   const fileName =
@@ -220,18 +226,25 @@ async function buildAttachmentContentBlock(attachment, cwd) {
     );
   }
 
+  // Check the size on disk before reading so an oversized file is never
+  // loaded into memory.
   let bytes;
   try {
+    const stats = await stat(resolvedPath);
+    if (Math.max(stats.size, Number(attachment?.sizeBytes) || 0) > MAX_ATTACHMENT_BYTES) {
+      throw new AttachmentTooLargeError(fileName);
+    }
     bytes = await readFile(resolvedPath);
   } catch (err) {
+    if (err instanceof AttachmentTooLargeError) {
+      throw err;
+    }
     throw new Error(
       `Attachment "${fileName}" could not be read at "${resolvedPath}": ${err.message || String(err)}`,
     );
   }
-
-  const sizeBytes = Math.max(bytes.byteLength, Number(attachment?.sizeBytes) || 0);
-  if (sizeBytes > MAX_ATTACHMENT_BYTES) {
-    throw new Error(`Attachment "${fileName}" exceeds the 10 MB per-file limit.`);
+  if (bytes.byteLength > MAX_ATTACHMENT_BYTES) {
+    throw new AttachmentTooLargeError(fileName);
   }
 
   if (attachmentType.kind === "image") {
@@ -261,7 +274,13 @@ async function buildAttachmentContentBlock(attachment, cwd) {
   };
 }
 
-function buildPromptInput(prompt, attachments, cwd, sessionIdHint) {
+/**
+ * Attachment blocks are built before the query starts so read failures
+ * surface here instead of inside the SDK's prompt stream. An attachment that
+ * cannot be sent is reported through `onSkippedAttachment` and left out; the
+ * turn only fails when nothing is left to send.
+ */
+async function buildPromptInput(prompt, attachments, cwd, sessionIdHint, onSkippedAttachment) {
   if (!Array.isArray(attachments) || attachments.length === 0) {
     return prompt;
   }
@@ -272,22 +291,26 @@ function buildPromptInput(prompt, attachments, cwd, sessionIdHint) {
     );
   }
 
-  return (async function* promptWithAttachments() {
-    const content = [];
-    if (typeof prompt === "string" && prompt.length > 0) {
-      content.push({ type: "text", text: prompt });
-    }
+  const content = [];
+  if (typeof prompt === "string" && prompt.length > 0) {
+    content.push({ type: "text", text: prompt });
+  }
 
-    for (const attachment of attachments) {
+  for (const attachment of attachments) {
+    try {
       content.push(await buildAttachmentContentBlock(attachment, cwd));
+    } catch (err) {
+      onSkippedAttachment(err.message || String(err));
     }
+  }
 
-    if (content.length === 0) {
-      throw new Error(
-        "Claude turn must include either a prompt or at least one supported attachment.",
-      );
-    }
+  if (content.length === 0) {
+    throw new Error(
+      "Claude turn must include either a prompt or at least one supported attachment.",
+    );
+  }
 
+  return (async function* promptWithAttachments() {
     yield {
       type: "user",
       message: {
@@ -1551,11 +1574,12 @@ async function handleQuery(req) {
 
     let sawTextDelta = false;
     let terminalStatus = "completed";
-    const promptInput = buildPromptInput(
+    const promptInput = await buildPromptInput(
       prompt,
       attachments,
       sessionCwd,
       sessionId || resume || "",
+      (message) => emit({ id, type: "error", message, recoverable: true }),
     );
     const query = queryFn({ prompt: promptInput, options });
     context.query = query;
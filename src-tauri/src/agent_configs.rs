@@ -0,0 +1,408 @@
+use std::{
+    fs,
+    io::Read,
+    path::{Component, Path},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::models::{AgentConfigKindDto, DetectedAgentConfigDto};
+
+const EXCERPT_MAX_CHARS: usize = 280;
+const CONFIG_EXCERPT_MAX_KEYS: usize = 8;
+// Instruction files past this size are still detected, but only this much
+// is read for the excerpt and fingerprint.
+const CONFIG_READ_MAX_BYTES: u64 = 256 * 1024;
+
+struct KnownAgentConfig {
+    path: &'static str,
+    engine_id: &'static str,
+    kind: AgentConfigKindDto,
+}
+
+/// Project files the Codex and Claude CLIs read on their own when started
+/// in the repo, relative to the repo root.
+const KNOWN_AGENT_CONFIGS: &[KnownAgentConfig] = &[
+    KnownAgentConfig {
+        path: "AGENTS.md",
+        engine_id: "codex",
+        kind: AgentConfigKindDto::Instructions,
+    },
+    KnownAgentConfig {
+        path: "AGENTS.override.md",
+        engine_id: "codex",
+        kind: AgentConfigKindDto::Instructions,
+    },
+    KnownAgentConfig {
+        path: ".codex/config.toml",
+        engine_id: "codex",
+        kind: AgentConfigKindDto::Config,
+    },
+    KnownAgentConfig {
+        path: "CLAUDE.md",
+        engine_id: "claude",
+        kind: AgentConfigKindDto::Instructions,
+    },
+    KnownAgentConfig {
+        path: "CLAUDE.local.md",
+        engine_id: "claude",
+        kind: AgentConfigKindDto::Instructions,
+    },
+    KnownAgentConfig {
+        path: ".claude/CLAUDE.md",
+        engine_id: "claude",
+        kind: AgentConfigKindDto::Instructions,
+    },
+    KnownAgentConfig {
+        path: ".claude/settings.json",
+        engine_id: "claude",
+        kind: AgentConfigKindDto::Config,
+    },
+];
+
+/// Directories under the repo root that hold known configs; the watcher
+/// follows these in addition to the root itself.
+pub const AGENT_CONFIG_DIRS: &[&str] = &[".codex", ".claude"];
+
+/// A config the user adopted for a repo, with the content hash it had then.
+/// Panes has no repo-level instructions of its own, so adopting records the
+/// file as in effect: repo-scoped threads start in the repo root, where the
+/// engine reads it natively.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdoptedAgentConfig {
+    pub path: String,
+    pub fingerprint: String,
+    pub adopted_at: String,
+}
+
+/// True when `relative` (to the repo root) names a known agent config.
+pub fn is_agent_config_path(relative: &Path) -> bool {
+    known_config(&relative_key(relative)).is_some()
+}
+
+/// Known configs present in the repo, plus adopted ones that were removed
+/// since. An adopted config is stale once its content no longer matches the
+/// fingerprint taken at adoption.
+pub fn detect_agent_configs(
+    repo_path: &Path,
+    adopted: &[AdoptedAgentConfig],
+) -> Vec<DetectedAgentConfigDto> {
+    let mut detected = Vec::new();
+    for known in KNOWN_AGENT_CONFIGS {
+        let adoption = adopted.iter().find(|item| item.path == known.path);
+        let Some(content) = read_config(&repo_path.join(known.path)) else {
+            if adoption.is_some() {
+                detected.push(DetectedAgentConfigDto {
+                    path: known.path.to_string(),
+                    engine_id: known.engine_id.to_string(),
+                    kind: known.kind,
+                    excerpt: String::new(),
+                    adopted: true,
+                    stale: true,
+                    note: Some("Removed since it was adopted.".to_string()),
+                });
+            }
+            continue;
+        };
+
+        let stale = adoption.is_some_and(|item| item.fingerprint != fingerprint(&content));
+        let note = match adoption {
+            Some(_) if stale => Some("Changed since it was adopted; review it again.".to_string()),
+            Some(_) => Some(format!(
+                "In effect: {} reads this file in threads scoped to this repo.",
+                engine_label(known.engine_id)
+            )),
+            None => None,
+        };
+        detected.push(DetectedAgentConfigDto {
+            path: known.path.to_string(),
+            engine_id: known.engine_id.to_string(),
+            kind: known.kind,
+            excerpt: excerpt(known, &content),
+            adopted: adoption.is_some(),
+            stale,
+            note,
+        });
+    }
+    detected
+}
+
+/// Records `relative` as adopted with its current content, replacing an
+/// earlier adoption of the same file.
+pub fn adopt_agent_config(
+    repo_path: &Path,
+    relative: &str,
+    adopted: &mut Vec<AdoptedAgentConfig>,
+) -> anyhow::Result<()> {
+    let known = known_config(&relative_key(Path::new(relative)))
+        .ok_or_else(|| anyhow::anyhow!("not a recognized agent config: {relative}"))?;
+    let content = read_config(&repo_path.join(known.path))
+        .ok_or_else(|| anyhow::anyhow!("agent config not found: {}", known.path))?;
+
+    adopted.retain(|item| item.path != known.path);
+    adopted.push(AdoptedAgentConfig {
+        path: known.path.to_string(),
+        fingerprint: fingerprint(&content),
+        adopted_at: Utc::now().to_rfc3339(),
+    });
+    Ok(())
+}
+
+fn known_config(key: &str) -> Option<&'static KnownAgentConfig> {
+    KNOWN_AGENT_CONFIGS.iter().find(|known| known.path == key)
+}
+
+fn relative_key(relative: &Path) -> String {
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn read_config(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    if !file.metadata().ok()?.is_file() {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    file.take(CONFIG_READ_MAX_BYTES)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn fingerprint(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn engine_label(engine_id: &str) -> &'static str {
+    match engine_id {
+        "claude" => "Claude",
+        _ => "Codex",
+    }
+}
+
+fn excerpt(known: &KnownAgentConfig, content: &str) -> String {
+    match known.kind {
+        AgentConfigKindDto::Instructions => markdown_excerpt(content),
+        AgentConfigKindDto::Config => {
+            let settings = if known.path.ends_with(".toml") {
+                toml_settings(content)
+            } else {
+                json_settings(content)
+            };
+            settings
+                .unwrap_or_else(|| "Could not be parsed; the engine reads it as-is.".to_string())
+        }
+    }
+}
+
+/// Leading prose of an instructions file on one line, with heading markers
+/// and HTML comments dropped.
+fn markdown_excerpt(content: &str) -> String {
+    let mut text = String::new();
+    let mut in_comment = false;
+    for line in content.lines() {
+        let mut line = line.trim();
+        if in_comment {
+            match line.find("-->") {
+                Some(end) => {
+                    in_comment = false;
+                    line = line[end + 3..].trim();
+                }
+                None => continue,
+            }
+        }
+        if line.starts_with("<!--") {
+            match line.find("-->") {
+                Some(end) => line = line[end + 3..].trim(),
+                None => {
+                    in_comment = true;
+                    continue;
+                }
+            }
+        }
+        let line = line.trim_start_matches('#').trim();
+        if line.is_empty() {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+        if text.chars().count() > EXCERPT_MAX_CHARS {
+            break;
+        }
+    }
+
+    if text.chars().count() > EXCERPT_MAX_CHARS {
+        let mut truncated = text.chars().take(EXCERPT_MAX_CHARS).collect::<String>();
+        truncated.push('…');
+        return truncated;
+    }
+    text
+}
+
+/// Top-level scalar keys of a TOML config, one `key = value` per line.
+fn toml_settings(content: &str) -> Option<String> {
+    let table = content.parse::<toml::Table>().ok()?;
+    let settings = table
+        .iter()
+        .filter_map(|(key, value)| match value {
+            toml::Value::String(value) => Some(format!("{key} = {value}")),
+            toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                Some(format!("{key} = {value}"))
+            }
+            _ => None,
+        })
+        .take(CONFIG_EXCERPT_MAX_KEYS)
+        .collect::<Vec<_>>();
+    Some(settings.join("\n"))
+}
+
+/// Top-level scalar keys of a JSON config, one `key = value` per line.
+fn json_settings(content: &str) -> Option<String> {
+    let Value::Object(object) = serde_json::from_str::<Value>(content).ok()? else {
+        return None;
+    };
+    let settings = object
+        .iter()
+        .filter_map(|(key, value)| match value {
+            Value::String(value) => Some(format!("{key} = {value}")),
+            Value::Number(_) | Value::Bool(_) => Some(format!("{key} = {value}")),
+            _ => None,
+        })
+        .take(CONFIG_EXCERPT_MAX_KEYS)
+        .collect::<Vec<_>>();
+    Some(settings.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn fixture_repo() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("panes-agent-configs-{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join(".codex")).expect("create .codex");
+        fs::create_dir_all(root.join(".claude")).expect("create .claude");
+        fs::write(
+            root.join("AGENTS.md"),
+            "<!-- generated -->\n# Agents\n\nRun `cargo test` before committing.\n",
+        )
+        .expect("write AGENTS.md");
+        fs::write(root.join("CLAUDE.md"), "## Style\nPrefer small diffs.\n")
+            .expect("write CLAUDE.md");
+        fs::write(
+            root.join(".codex/config.toml"),
+            "model = \"gpt-5\"\napproval_policy = \"on-request\"\n\n[mcp_servers.docs]\ncommand = \"docs\"\n",
+        )
+        .expect("write config.toml");
+        fs::write(
+            root.join(".claude/settings.json"),
+            r#"{"model": "opus", "permissions": {"allow": []}, "cleanupPeriodDays": 7}"#,
+        )
+        .expect("write settings.json");
+        root
+    }
+
+    fn find<'a>(detected: &'a [DetectedAgentConfigDto], path: &str) -> &'a DetectedAgentConfigDto {
+        detected
+            .iter()
+            .find(|config| config.path == path)
+            .unwrap_or_else(|| panic!("{path} should be detected"))
+    }
+
+    fn sorted_lines(value: &str) -> Vec<&str> {
+        let mut lines = value.lines().collect::<Vec<_>>();
+        lines.sort_unstable();
+        lines
+    }
+
+    #[test]
+    fn detects_each_known_file_type_with_an_excerpt() {
+        let root = fixture_repo();
+        let detected = detect_agent_configs(&root, &[]);
+
+        assert_eq!(detected.len(), 4);
+        let agents = find(&detected, "AGENTS.md");
+        assert_eq!(agents.engine_id, "codex");
+        assert_eq!(agents.kind, AgentConfigKindDto::Instructions);
+        assert_eq!(agents.excerpt, "Agents Run `cargo test` before committing.");
+        assert_eq!(
+            find(&detected, "CLAUDE.md").excerpt,
+            "Style Prefer small diffs."
+        );
+        assert_eq!(
+            sorted_lines(&find(&detected, ".codex/config.toml").excerpt),
+            vec!["approval_policy = on-request", "model = gpt-5"]
+        );
+        let settings = find(&detected, ".claude/settings.json");
+        assert_eq!(settings.kind, AgentConfigKindDto::Config);
+        assert_eq!(
+            sorted_lines(&settings.excerpt),
+            vec!["cleanupPeriodDays = 7", "model = opus"]
+        );
+        assert!(detected
+            .iter()
+            .all(|config| !config.adopted && !config.stale));
+
+        fs::write(root.join(".codex/config.toml"), "model = ").expect("break config");
+        let detected = detect_agent_configs(&root, &[]);
+        assert!(find(&detected, ".codex/config.toml")
+            .excerpt
+            .starts_with("Could not be parsed"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn adopted_config_turns_stale_when_modified_or_removed() {
+        let root = fixture_repo();
+        let mut adopted = Vec::new();
+        adopt_agent_config(&root, "AGENTS.md", &mut adopted).expect("adopt AGENTS.md");
+        assert!(adopt_agent_config(&root, "README.md", &mut adopted).is_err());
+        assert!(adopt_agent_config(&root, "AGENTS.override.md", &mut adopted).is_err());
+
+        let agents = find(&detect_agent_configs(&root, &adopted), "AGENTS.md").clone();
+        assert!(agents.adopted);
+        assert!(!agents.stale);
+        assert!(agents
+            .note
+            .is_some_and(|note| note.starts_with("In effect")));
+
+        fs::write(root.join("AGENTS.md"), "# Agents\nNew rules.\n").expect("modify AGENTS.md");
+        assert!(find(&detect_agent_configs(&root, &adopted), "AGENTS.md").stale);
+
+        adopt_agent_config(&root, "./AGENTS.md", &mut adopted).expect("adopt again");
+        assert_eq!(adopted.len(), 1);
+        assert!(!find(&detect_agent_configs(&root, &adopted), "AGENTS.md").stale);
+
+        fs::remove_file(root.join("AGENTS.md")).expect("remove AGENTS.md");
+        let removed = find(&detect_agent_configs(&root, &adopted), "AGENTS.md").clone();
+        assert!(removed.stale);
+        assert!(removed.excerpt.is_empty());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn recognizes_config_paths_relative_to_the_repo_root() {
+        assert!(is_agent_config_path(Path::new("CLAUDE.md")));
+        assert!(is_agent_config_path(Path::new(".claude/settings.json")));
+        assert!(!is_agent_config_path(Path::new("docs/AGENTS.md")));
+        assert!(!is_agent_config_path(Path::new(".codex/sessions/log.json")));
+    }
+}
@@ -0,0 +1,228 @@
+//! Content blocks: the document format of a message. They are persisted in
+//! `messages.blocks_json`, streamed to the frontend, and read back by
+//! regeneration and search.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{value::RawValue, Value};
+
+/// Version of the block format below, stored in `messages.schema_version`.
+/// Bump it when a change would make older readers misinterpret new rows.
+pub const BLOCK_SCHEMA_VERSION: i64 = 1;
+
+pub(crate) fn value_to_raw(value: &Value) -> Box<RawValue> {
+    serde_json::value::to_raw_value(value).unwrap_or_else(|_| empty_raw_value())
+}
+
+fn empty_raw_value() -> Box<RawValue> {
+    RawValue::from_string("null".to_string()).expect("\"null\" is a valid JSON literal")
+}
+
+/// `RawValue` cannot be read back through an internally tagged enum, so
+/// details go through `Value` on the way in.
+fn deserialize_raw_details<'de, D>(deserializer: D) -> Result<Box<RawValue>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(|value| value_to_raw(&value))
+}
+
+/// Reading is tolerant: a block whose type is unknown, or whose shape does not
+/// match its type, becomes `Unknown` and is written back unchanged, so one
+/// unexpected block never costs the rest of the message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self", tag = "type")]
+pub(crate) enum ContentBlock {
+    #[serde(rename = "text")]
+    Text {
+        content: String,
+        #[serde(rename = "planMode", skip_serializing_if = "Option::is_none")]
+        plan_mode: Option<bool>,
+        #[serde(rename = "isSteer", skip_serializing_if = "Option::is_none")]
+        is_steer: Option<bool>,
+    },
+
+    #[serde(rename = "diff")]
+    Diff { diff: String, scope: String },
+
+    #[serde(rename = "action")]
+    Action {
+        #[serde(rename = "actionId")]
+        action_id: String,
+        #[serde(rename = "engineActionId", skip_serializing_if = "Option::is_none")]
+        engine_action_id: Option<String>,
+        #[serde(rename = "actionType")]
+        action_type: String,
+        summary: String,
+        #[serde(deserialize_with = "deserialize_raw_details")]
+        details: Box<RawValue>,
+        #[serde(rename = "outputChunks")]
+        output_chunks: Vec<ActionOutputChunk>,
+        status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<ActionBlockResult>,
+    },
+
+    #[serde(rename = "approval")]
+    Approval {
+        #[serde(rename = "approvalId")]
+        approval_id: String,
+        #[serde(rename = "actionType")]
+        action_type: String,
+        summary: String,
+        #[serde(deserialize_with = "deserialize_raw_details")]
+        details: Box<RawValue>,
+        status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        decision: Option<String>,
+    },
+
+    #[serde(rename = "thinking")]
+    Thinking {
+        content: String,
+        #[serde(rename = "startedAt", skip_serializing_if = "Option::is_none")]
+        started_at: Option<f64>,
+        #[serde(rename = "durationMs", skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<f64>,
+    },
+
+    #[serde(rename = "notice")]
+    Notice {
+        kind: String,
+        level: String,
+        title: String,
+        message: String,
+    },
+
+    #[serde(rename = "error")]
+    Error { message: String },
+
+    #[serde(rename = "attachment")]
+    Attachment {
+        #[serde(rename = "fileName")]
+        file_name: String,
+        #[serde(rename = "filePath")]
+        file_path: String,
+        #[serde(rename = "sizeBytes")]
+        size_bytes: u64,
+        #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+    },
+
+    #[serde(rename = "skill")]
+    Skill { name: String, path: String },
+
+    #[serde(rename = "mention")]
+    Mention { name: String, path: String },
+
+    #[serde(skip)]
+    Unknown { raw: Value },
+}
+
+impl Serialize for ContentBlock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Unknown { raw } => raw.serialize(serializer),
+            known => Self::serialize(known, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentBlock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Value::deserialize(deserializer)?;
+        match Self::deserialize(&raw) {
+            Ok(block) => Ok(block),
+            Err(_) => Ok(Self::Unknown { raw }),
+        }
+    }
+}
+
+impl ContentBlock {
+    /// Parses a stored `blocks_json` array; anything but an array yields no
+    /// blocks.
+    pub(crate) fn parse_list(blocks: &Value) -> Vec<Self> {
+        Vec::<Self>::deserialize(blocks).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ActionOutputChunk {
+    pub(crate) stream: String,
+    pub(crate) content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActionBlockResult {
+    pub(crate) success: bool,
+    pub(crate) output: Option<String>,
+    pub(crate) error: Option<String>,
+    pub(crate) diff: Option<String>,
+    pub(crate) duration_ms: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// One block of every variant as the app writes it today. Changing any of
+    /// these means existing `blocks_json` rows change meaning.
+    const WIRE_FORMAT: &str = r#"[
+      {"type":"text","content":"Hello","planMode":true,"isSteer":false},
+      {"type":"text","content":"Plain"},
+      {"type":"diff","diff":"--- a\n+++ b\n","scope":"turn"},
+      {"type":"action","actionId":"a1","engineActionId":"e1","actionType":"command","summary":"Run tests","details":{"command":"cargo test","cwd":"/repo"},"outputChunks":[{"stream":"stdout","content":"ok"}],"status":"done","result":{"success":true,"output":"ok","error":null,"diff":null,"durationMs":42}},
+      {"type":"action","actionId":"a2","actionType":"file_read","summary":"Read","details":null,"outputChunks":[],"status":"running"},
+      {"type":"approval","approvalId":"p1","actionType":"command","summary":"Run rm","details":{"command":"rm -rf target"},"status":"answered","decision":"accept"},
+      {"type":"approval","approvalId":"p2","actionType":"command","summary":"Run ls","details":{},"status":"pending"},
+      {"type":"thinking","content":"Considering","startedAt":1700000000000.0,"durationMs":1500.0},
+      {"type":"notice","kind":"model_changed","level":"info","title":"Model","message":"Switched"},
+      {"type":"error","message":"Engine error: boom"},
+      {"type":"attachment","fileName":"a b.png","filePath":"/tmp/a b.png","sizeBytes":12,"mimeType":"image/png"},
+      {"type":"skill","name":"review","path":"/skills/review.md"},
+      {"type":"mention","name":"lib.rs","path":"src/lib.rs"}
+    ]"#;
+
+    #[test]
+    fn every_variant_round_trips_through_the_wire_format() {
+        let stored: Value = serde_json::from_str(WIRE_FORMAT).expect("fixture");
+        let blocks = ContentBlock::parse_list(&stored);
+
+        assert_eq!(blocks.len(), 13);
+        assert!(
+            !blocks
+                .iter()
+                .any(|block| matches!(block, ContentBlock::Unknown { .. })),
+            "{blocks:?}"
+        );
+        assert!(matches!(
+            &blocks[3],
+            ContentBlock::Action { details, result: Some(result), .. }
+                if details.get().contains("cargo test") && result.duration_ms == 42
+        ));
+        assert_eq!(serde_json::to_value(&blocks).expect("serialize"), stored);
+    }
+
+    #[test]
+    fn unknown_or_malformed_blocks_keep_the_rest_of_the_message() {
+        let stored = json!([
+            {"type":"text","content":"before"},
+            {"type":"chart","series":[1, 2, 3]},
+            {"type":"diff","diff":"missing scope"},
+            "not a block",
+            {"type":"text","content":"after"}
+        ]);
+        let blocks = ContentBlock::parse_list(&stored);
+
+        assert_eq!(blocks.len(), 5);
+        assert!(matches!(&blocks[0], ContentBlock::Text { content, .. } if content == "before"));
+        assert!(matches!(&blocks[1], ContentBlock::Unknown { .. }));
+        assert!(matches!(&blocks[2], ContentBlock::Unknown { .. }));
+        assert!(matches!(&blocks[3], ContentBlock::Unknown { .. }));
+        assert!(matches!(&blocks[4], ContentBlock::Text { content, .. } if content == "after"));
+        assert_eq!(serde_json::to_value(&blocks).expect("serialize"), stored);
+        assert!(ContentBlock::parse_list(&json!({"type":"text"})).is_empty());
+    }
+}
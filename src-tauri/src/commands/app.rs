@@ -8,6 +8,8 @@ use std::{
 use crate::{
     config::app_config::{clamp_terminal_font_size, AppConfig},
     locale::{normalize_app_locale, resolve_app_locale},
+    metrics,
+    models::{DatabasePoolMetricsDto, LocalMetricsDto, LocalMetricsPeriodDto},
     state::AppState,
     terminal_notifications::{
         agent_notification_settings_status, install_terminal_notification_integration,
@@ -182,6 +184,55 @@ pub async fn set_app_theme(state: State<'_, AppState>, theme: String) -> Result<
     .map_err(err_to_string)?
 }
 
+#[tauri::command]
+pub async fn get_database_pool_diagnostics(
+    state: State<'_, AppState>,
+) -> Result<DatabasePoolMetricsDto, String> {
+    Ok(state.db.pool_metrics())
+}
+
+/// Local usage counters for the window ending today. Never leaves the machine.
+#[tauri::command]
+pub async fn get_local_metrics(
+    state: State<'_, AppState>,
+    period: LocalMetricsPeriodDto,
+) -> Result<LocalMetricsDto, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        metrics::get_local_metrics(&db, period).map_err(err_to_string)
+    })
+    .await
+    .map_err(err_to_string)?
+}
+
+#[tauri::command]
+pub async fn get_weekly_usage_summary_enabled() -> Result<bool, String> {
+    tokio::task::spawn_blocking(move || {
+        let config = AppConfig::load_or_create().map_err(err_to_string)?;
+        Ok(config.weekly_usage_summary_enabled())
+    })
+    .await
+    .map_err(err_to_string)?
+}
+
+#[tauri::command]
+pub async fn set_weekly_usage_summary_enabled(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let config_write_lock = state.config_write_lock.clone();
+    let _guard = config_write_lock.lock_owned().await;
+
+    tokio::task::spawn_blocking(move || -> Result<bool, String> {
+        let mut config = AppConfig::load_or_create().map_err(err_to_string)?;
+        config.general.weekly_usage_summary = if enabled { Some(true) } else { None };
+        config.save().map_err(err_to_string)?;
+        Ok(enabled)
+    })
+    .await
+    .map_err(err_to_string)?
+}
+
 #[tauri::command]
 pub async fn get_terminal_accelerated_rendering() -> Result<bool, String> {
     tokio::task::spawn_blocking(move || {
@@ -322,6 +373,34 @@ pub async fn set_terminal_notifications_enabled(
     .map_err(err_to_string)?
 }
 
+#[tauri::command]
+pub async fn get_terminal_scrollback_persistence() -> Result<bool, String> {
+    tokio::task::spawn_blocking(move || {
+        let config = AppConfig::load_or_create().map_err(err_to_string)?;
+        Ok(config.terminal_scrollback_persistence_enabled())
+    })
+    .await
+    .map_err(err_to_string)?
+}
+
+#[tauri::command]
+pub async fn set_terminal_scrollback_persistence(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let config_write_lock = state.config_write_lock.clone();
+    let _guard = config_write_lock.lock_owned().await;
+
+    tokio::task::spawn_blocking(move || -> Result<bool, String> {
+        let mut config = AppConfig::load_or_create().map_err(err_to_string)?;
+        config.general.terminal_persist_scrollback = if enabled { Some(true) } else { None };
+        config.save().map_err(err_to_string)?;
+        Ok(enabled)
+    })
+    .await
+    .map_err(err_to_string)?
+}
+
 #[tauri::command]
 pub async fn install_terminal_notification_integration_command(
     integration: String,
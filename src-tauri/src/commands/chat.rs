@@ -1,9 +1,10 @@
 use std::{
     collections::{HashMap, HashSet},
-    path::Path,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
 
+use anyhow::Context;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::{Deserialize, Serialize};
 use serde_json::{value::RawValue, Value};
@@ -13,31 +14,50 @@ use tokio::sync::{mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use super::threads::emit_thread_updated;
 use crate::{
-    db,
+    blocks::{value_to_raw, ActionBlockResult, ActionOutputChunk, ContentBlock},
+    config::app_config::AppConfig,
+    db::{
+        self,
+        turn_writes::{TurnWrite, TurnWriteBatcher},
+    },
     engines::{
-        approval_response_route_for_engine, normalize_approval_response_for_engine,
-        trim_action_output_delta_content, validate_engine_sandbox_mode, ApprovalRequestRoute,
+        approval_response_route_for_engine, capabilities_for_engine, capabilities_for_thread,
+        context_budget::{
+            check_context_budget, context_rollover_due, context_used_percent,
+            estimate_turn_context, resolve_context_budget, rollover_seed_prompt,
+            ThreadContextUsage,
+        },
+        normalize_approval_response_for_engine, trim_action_output_delta_content,
+        turn_diagnostics::TurnDiagnostics,
+        turn_event_log::{
+            read_turn_event_log_tail, turn_event_log_dir, turn_event_log_path, TurnEventLog,
+        },
+        validate_engine_sandbox_mode, validate_turn_capabilities, ActionType, ApprovalRequestRoute,
         EngineEvent, OutputStream, SandboxPolicy, ThreadScope, TurnAttachment,
         TurnCompletionStatus, TurnInput, TurnInputItem, STREAMED_DIFF_MAX_CHARS,
     },
+    metrics,
     models::{
-        ActionOutputDto, EngineInfoDto, EngineModelDto, MessageDto, MessageStatusDto,
-        MessageWindowCursorDto, MessageWindowDto, RepoDto, SearchResultDto, ThreadDto,
-        ThreadStatusDto, TrustLevelDto,
+        ActionOutputDto, BlobStorageReportDto, EngineInfoDto, EngineModelDto, MessageDto,
+        MessageStatusDto, MessageWindowCursorDto, MessageWindowDto, MultiSendResultDto,
+        QueuedMessageDto, RepoDto, SearchResultDto, SendMessageErrorDto, ThreadDto,
+        ThreadModelHistoryDto, ThreadModelHistoryEntryDto, ThreadStatusDto, TrustLevelDto,
+        TurnDiagnosticDto, TurnEventLogDto,
     },
     runtime_env,
     state::AppState,
 };
 
 const MAX_THREAD_TITLE_CHARS: usize = 72;
-const STREAM_EVENT_COALESCE_MAX_CHARS: usize = 8_192;
 const STREAM_EVENT_COALESCE_IDLE_FLUSH_INTERVAL: Duration = Duration::from_millis(24);
-const STREAM_DB_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
 const STREAM_DB_BLOCKS_FLUSH_INTERVAL: Duration = Duration::from_millis(900);
+const TURN_INTERRUPT_SETTLE_TIMEOUT: Duration = Duration::from_secs(5);
 const ENGINE_EVENT_QUEUE_CAPACITY: usize = 128;
 const ACTION_OUTPUT_MAX_CHUNKS: usize = 240;
 const ENGINE_EVENT_LOG_ACTION_OUTPUT_MAX_CHARS: usize = 4_096;
+const TURN_EVENT_LOG_MAX_TAIL_LINES: usize = 1_000;
 const TRUNCATED_SUFFIX: &str = "\n... [truncated]";
 const MAX_ATTACHMENTS_PER_TURN: usize = 10;
 const MAX_PASTED_IMAGE_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
@@ -50,115 +70,9 @@ const IMAGE_ATTACHMENT_EXTENSIONS: &[&str] = &[
 ];
 const MESSAGE_WINDOW_DEFAULT_LIMIT: usize = 120;
 const MESSAGE_WINDOW_MAX_LIMIT: usize = 400;
+const THREAD_MESSAGES_PAGE_DEFAULT_LIMIT: usize = 50;
 const MAX_CHAT_NOTIFICATION_PREVIEW_CHARS: usize = 240;
-
-fn value_to_raw(value: &Value) -> Box<RawValue> {
-    serde_json::value::to_raw_value(value).unwrap_or_else(|_| empty_raw_value())
-}
-
-fn empty_raw_value() -> Box<RawValue> {
-    RawValue::from_string("null".to_string()).expect("\"null\" is a valid JSON literal")
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-enum ContentBlock {
-    #[serde(rename = "text")]
-    Text {
-        content: String,
-        #[serde(rename = "planMode", skip_serializing_if = "Option::is_none")]
-        plan_mode: Option<bool>,
-        #[serde(rename = "isSteer", skip_serializing_if = "Option::is_none")]
-        is_steer: Option<bool>,
-    },
-
-    #[serde(rename = "diff")]
-    Diff { diff: String, scope: String },
-
-    #[serde(rename = "action")]
-    Action {
-        #[serde(rename = "actionId")]
-        action_id: String,
-        #[serde(rename = "engineActionId", skip_serializing_if = "Option::is_none")]
-        engine_action_id: Option<String>,
-        #[serde(rename = "actionType")]
-        action_type: String,
-        summary: String,
-        details: Box<RawValue>,
-        #[serde(rename = "outputChunks")]
-        output_chunks: Vec<ActionOutputChunk>,
-        status: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        result: Option<ActionBlockResult>,
-    },
-
-    #[serde(rename = "approval")]
-    Approval {
-        #[serde(rename = "approvalId")]
-        approval_id: String,
-        #[serde(rename = "actionType")]
-        action_type: String,
-        summary: String,
-        details: Box<RawValue>,
-        status: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        decision: Option<String>,
-    },
-
-    #[serde(rename = "thinking")]
-    Thinking {
-        content: String,
-        #[serde(rename = "startedAt", skip_serializing_if = "Option::is_none")]
-        started_at: Option<f64>,
-        #[serde(rename = "durationMs", skip_serializing_if = "Option::is_none")]
-        duration_ms: Option<f64>,
-    },
-
-    #[serde(rename = "notice")]
-    Notice {
-        kind: String,
-        level: String,
-        title: String,
-        message: String,
-    },
-
-    #[serde(rename = "error")]
-    Error { message: String },
-
-    #[serde(rename = "attachment")]
-    Attachment {
-        #[serde(rename = "fileName")]
-        file_name: String,
-        #[serde(rename = "filePath")]
-        file_path: String,
-        #[serde(rename = "sizeBytes")]
-        size_bytes: u64,
-        #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
-        mime_type: Option<String>,
-    },
-
-    #[serde(rename = "skill")]
-    Skill { name: String, path: String },
-
-    #[serde(rename = "mention")]
-    Mention { name: String, path: String },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ActionOutputChunk {
-    stream: String,
-    content: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct ActionBlockResult {
-    success: bool,
-    output: Option<String>,
-    error: Option<String>,
-    diff: Option<String>,
-    duration_ms: u64,
-}
+const MAX_MULTI_SEND_THREADS: usize = 5;
 
 #[derive(Default)]
 struct EventProgress {
@@ -172,11 +86,11 @@ struct EventProgress {
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct ThreadUpdatedEvent {
-    thread_id: String,
-    workspace_id: String,
+pub(crate) struct ThreadUpdatedEvent {
+    pub(crate) thread_id: String,
+    pub(crate) workspace_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    thread: Option<ThreadDto>,
+    pub(crate) thread: Option<ThreadDto>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -191,6 +105,13 @@ struct ChatTurnFinishedEvent {
     preview: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QueuedMessagesUpdatedEvent {
+    thread_id: String,
+    messages: Vec<QueuedMessageDto>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ChatApprovalRequestedEvent {
@@ -384,6 +305,7 @@ where
     T: Send + 'static,
     F: FnOnce(&crate::db::Database) -> anyhow::Result<T> + Send + 'static,
 {
+    let db = db.acquire_scope().await.map_err(err_to_string)?;
     tokio::task::spawn_blocking(move || operation(&db))
         .await
         .map_err(|error| error.to_string())?
@@ -391,6 +313,7 @@ where
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn send_message(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
@@ -402,12 +325,172 @@ pub async fn send_message(
     input_items: Option<Vec<ChatInputItemPayload>>,
     plan_mode: Option<bool>,
     client_turn_id: Option<String>,
-) -> Result<String, String> {
-    let already_running = state.turns.get(&thread_id).await.is_some();
+    force: Option<bool>,
+) -> Result<String, SendMessageErrorDto> {
+    send_message_inner(
+        app,
+        state.inner(),
+        thread_id,
+        message,
+        model_id,
+        reasoning_effort,
+        attachments,
+        input_items,
+        plan_mode,
+        client_turn_id,
+        force,
+        TurnOrigin::NewMessage,
+    )
+    .await
+}
+
+/// Sends the same prompt to several threads at once, each on its own model
+/// and engine, e.g. to compare answers. Turns start concurrently and are
+/// registered per thread, so cancelling one leaves the others running. A
+/// thread that fails to start is reported in `errors` without stopping the
+/// rest.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn send_message_multi(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_ids: Vec<String>,
+    message: String,
+    attachments: Option<Vec<ChatAttachmentPayload>>,
+    input_items: Option<Vec<ChatInputItemPayload>>,
+    plan_mode: Option<bool>,
+    force: Option<bool>,
+) -> Result<MultiSendResultDto, String> {
+    validate_multi_send_threads(&thread_ids, |thread_id| state.turns.is_active(thread_id))?;
+
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut thread_ids_by_task = HashMap::new();
+    for thread_id in thread_ids {
+        let app = app.clone();
+        let state = state.inner().clone();
+        let message = message.clone();
+        let attachments = attachments.clone();
+        let input_items = input_items.clone();
+        let task_thread_id = thread_id.clone();
+        let handle = tasks.spawn(async move {
+            send_message_inner(
+                app,
+                &state,
+                task_thread_id,
+                message,
+                None,
+                None,
+                attachments,
+                input_items,
+                plan_mode,
+                None,
+                force,
+                TurnOrigin::NewMessage,
+            )
+            .await
+            .map_err(SendMessageErrorDto::into_message)
+        });
+        thread_ids_by_task.insert(handle.id(), thread_id);
+    }
+
+    let mut result = MultiSendResultDto::default();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (task_id, outcome) = match joined {
+            Ok((task_id, outcome)) => (task_id, outcome),
+            Err(error) => (error.id(), Err(format!("failed to start turn: {error}"))),
+        };
+        let Some(thread_id) = thread_ids_by_task.remove(&task_id) else {
+            continue;
+        };
+        match outcome {
+            Ok(assistant_message_id) => {
+                result
+                    .assistant_message_ids
+                    .insert(thread_id, assistant_message_id);
+            }
+            Err(error) => {
+                result.errors.insert(thread_id, error);
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn validate_multi_send_threads(
+    thread_ids: &[String],
+    is_active: impl Fn(&str) -> bool,
+) -> Result<(), String> {
+    if thread_ids.is_empty() {
+        return Err("Select at least one thread to send to.".to_string());
+    }
+    if thread_ids.len() > MAX_MULTI_SEND_THREADS {
+        return Err(format!(
+            "A prompt can be sent to at most {MAX_MULTI_SEND_THREADS} threads at once."
+        ));
+    }
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = thread_ids.iter().find(|id| !seen.insert(id.as_str())) {
+        return Err(format!("thread {duplicate} was selected more than once"));
+    }
+    if let Some(busy) = thread_ids.iter().find(|id| is_active(id)) {
+        return Err(format!(
+            "A turn is already running for thread {busy}. Cancel it before sending to it."
+        ));
+    }
+    Ok(())
+}
+
+/// How a turn started by [`send_message_inner`] relates to stored history.
+enum TurnOrigin {
+    /// A new prompt, stored as a user message.
+    NewMessage,
+    /// Re-answers an earlier prompt; the placeholder supersedes this reply.
+    Regenerate { superseded_message_id: String },
+    /// Re-runs the latest prompt after deleting its failed reply.
+    Retry {
+        discarded_message_ids: Vec<String>,
+        superseded_message_id: Option<String>,
+    },
+}
+
+impl TurnOrigin {
+    fn superseded_message_id(&self) -> Option<&str> {
+        match self {
+            Self::NewMessage => None,
+            Self::Regenerate {
+                superseded_message_id,
+            } => Some(superseded_message_id),
+            Self::Retry {
+                superseded_message_id,
+                ..
+            } => superseded_message_id.as_deref(),
+        }
+    }
+}
+
+/// Starts a turn for `message`. Only a [`TurnOrigin::NewMessage`] stores a
+/// user message; the others re-answer one that is already in the thread.
+#[allow(clippy::too_many_arguments)]
+async fn send_message_inner(
+    app: tauri::AppHandle,
+    state: &AppState,
+    thread_id: String,
+    message: String,
+    model_id: Option<String>,
+    reasoning_effort: Option<String>,
+    attachments: Option<Vec<ChatAttachmentPayload>>,
+    input_items: Option<Vec<ChatInputItemPayload>>,
+    plan_mode: Option<bool>,
+    client_turn_id: Option<String>,
+    force: Option<bool>,
+    origin: TurnOrigin,
+) -> Result<String, SendMessageErrorDto> {
+    let already_running = state.turns.is_active(&thread_id);
     if already_running {
         return Err(
             "A turn is already running for this thread. Cancel it before sending another message."
-                .to_string(),
+                .to_string()
+                .into(),
         );
     }
 
@@ -422,10 +505,11 @@ pub async fn send_message(
         .as_deref()
         .map(str::trim)
         .filter(|value| !value.is_empty());
-    let attachments = normalize_attachments(attachments)?;
+    let attachments =
+        normalize_attachments(attachments, AttachmentLimits::from_config(&state.config)).await?;
     let input_items = normalize_input_items(message.as_str(), input_items)?;
     let plan_mode = plan_mode.unwrap_or(false);
-    let turn_input = TurnInput {
+    let mut turn_input = TurnInput {
         message: message.clone(),
         attachments: attachments.clone(),
         plan_mode,
@@ -450,12 +534,31 @@ pub async fn send_message(
     } else {
         Some(state.engines.list_engines().await.map_err(err_to_string)?)
     };
+    validate_turn_capabilities(
+        &thread.engine_id,
+        &capabilities_for_thread(&thread),
+        plan_mode,
+        !attachments.is_empty(),
+    )?;
     validate_attachments_for_engine_model(
         &attachments,
         &thread.engine_id,
         &effective_model_id,
         attachment_catalog.as_deref(),
     )?;
+    let rollover = if matches!(origin, TurnOrigin::NewMessage) {
+        plan_context_rollover(state, &thread, &mut turn_input).await?
+    } else {
+        None
+    };
+    let context_budget_notice = check_turn_context_budget(
+        state,
+        &thread,
+        &turn_input,
+        rollover.is_some(),
+        force.unwrap_or(false),
+    )
+    .await?;
 
     let (workspace, repos, selected_repo) = run_db(db.clone(), {
         let workspace_id = thread.workspace_id.clone();
@@ -530,7 +633,7 @@ pub async fn send_message(
     };
     let scope = if let Some(repo) = selected_repo.as_ref() {
         ThreadScope::Repo {
-            repo_path: repo.path.clone(),
+            repo_path: thread_checkout_path(&repo.path, thread.engine_metadata.as_ref())?,
         }
     } else {
         ThreadScope::Workspace {
@@ -564,7 +667,7 @@ pub async fn send_message(
                 codex_external_sandbox_active,
             ) {
                 return Err(
-                "Codex read-only and workspace-write sandbox overrides are unavailable while Panes is using external sandbox mode. Clear the override or restore local Codex sandboxing first.".to_string(),
+                "Codex read-only and workspace-write sandbox overrides are unavailable while Panes is using external sandbox mode. Clear the override or restore local Codex sandboxing first.".to_string().into(),
             );
             }
 
@@ -576,7 +679,7 @@ pub async fn send_message(
                 workspace_write_opt_in_enabled(thread.engine_metadata.as_ref()),
             ) {
                 return Err(
-                "Workspace thread with multiple writable repositories requires explicit confirmation before execution.".to_string(),
+                "Workspace thread with multiple writable repositories requires explicit confirmation before execution.".to_string().into(),
             );
             }
         }
@@ -613,6 +716,7 @@ pub async fn send_message(
         })
         .await?;
         thread.engine_metadata = Some(metadata);
+        thread.reasoning_effort = reasoning_effort.clone();
     }
 
     let writable_roots = match &scope {
@@ -637,7 +741,7 @@ pub async fn send_message(
                 .unwrap_or_else(|| allow_network_for_trust_level(&trust_level))
         };
     let personality = if thread.engine_id == "codex"
-        && model_supports_personality(state.inner(), &thread.engine_id, &effective_model_id).await
+        && model_supports_personality(state, &thread.engine_id, &effective_model_id).await
     {
         thread_personality(thread.engine_metadata.as_ref())
     } else {
@@ -670,6 +774,17 @@ pub async fn send_message(
         personality,
         output_schema: thread_output_schema(thread.engine_metadata.as_ref()),
         opencode_agent: thread_opencode_agent(thread.engine_metadata.as_ref()),
+        executable_override: if thread.engine_id == "codex" {
+            thread_executable_override(thread.engine_metadata.as_ref())
+        } else {
+            None
+        },
+        custom_instructions: thread_custom_instructions(thread.engine_metadata.as_ref()),
+    };
+
+    let rollover_notice = match rollover.as_ref() {
+        Some(rollover) => Some(apply_context_rollover(state, &mut thread, rollover).await?),
+        None => None,
     };
 
     let engine_thread_id = state
@@ -696,11 +811,12 @@ pub async fn send_message(
     {
         return Err(
             "A turn is already running for this thread. Cancel it before sending another message."
-                .to_string(),
+                .to_string()
+                .into(),
         );
     }
 
-    let assistant_message = match run_db(db.clone(), {
+    let (assistant_message, initial_notices) = match run_db(db.clone(), {
         let thread_id = thread.id.clone();
         let message = message.clone();
         let attachments = attachments.clone();
@@ -710,43 +826,93 @@ pub async fn send_message(
         let model_id = effective_model_id.clone();
         let reasoning_effort = reasoning_effort.clone();
         move |db| {
-            let user_blocks = build_user_blocks(
-                &message,
-                &input_items,
-                &attachments,
-                plan_mode_enabled,
-                false,
-            );
-            db::messages::insert_user_message(
-                db,
-                &thread_id,
-                &message,
-                Some(serde_json::to_value(&user_blocks)?),
-                Some(engine_id.as_str()),
-                Some(model_id.as_str()),
-                reasoning_effort.as_deref(),
-            )?;
-            let assistant_message = db::messages::insert_assistant_placeholder(
-                db,
-                &thread_id,
-                Some(engine_id.as_str()),
-                Some(model_id.as_str()),
-                reasoning_effort.as_deref(),
-            )?;
+            match &origin {
+                TurnOrigin::NewMessage => {
+                    let user_blocks = build_user_blocks(
+                        &message,
+                        &input_items,
+                        &attachments,
+                        plan_mode_enabled,
+                        false,
+                    );
+                    db::messages::insert_user_message(
+                        db,
+                        &thread_id,
+                        &message,
+                        Some(serde_json::to_value(&user_blocks)?),
+                        Some(engine_id.as_str()),
+                        Some(model_id.as_str()),
+                        reasoning_effort.as_deref(),
+                    )?;
+                }
+                // The command looked its target up before this turn claimed
+                // the thread; make sure no turn ran in between.
+                TurnOrigin::Regenerate {
+                    superseded_message_id,
+                } => {
+                    db::messages::find_regeneration_pair(db, superseded_message_id)?;
+                }
+                TurnOrigin::Retry {
+                    discarded_message_ids,
+                    superseded_message_id,
+                } => {
+                    let current = db::messages::find_retry_target(db, &thread_id)?;
+                    if current.discarded_message_ids != *discarded_message_ids
+                        || current.superseded_message_id != *superseded_message_id
+                    {
+                        anyhow::bail!("the thread changed since the retry was requested");
+                    }
+                    for message_id in discarded_message_ids {
+                        db::messages::delete_message(db, &thread_id, message_id, false)?;
+                    }
+                    db::threads::refresh_thread_message_stats(db, &thread_id)?;
+                }
+            }
+            let previous_turn = db::messages::latest_assistant_model_entry(db, &thread_id)?;
+            let assistant_message = match origin.superseded_message_id() {
+                Some(superseded_message_id) => db::messages::insert_regenerated_placeholder(
+                    db,
+                    &thread_id,
+                    superseded_message_id,
+                    Some(engine_id.as_str()),
+                    Some(model_id.as_str()),
+                    reasoning_effort.as_deref(),
+                )?,
+                None => db::messages::insert_assistant_placeholder(
+                    db,
+                    &thread_id,
+                    Some(engine_id.as_str()),
+                    Some(model_id.as_str()),
+                    reasoning_effort.as_deref(),
+                )?,
+            };
             db::threads::update_thread_status(db, &thread_id, ThreadStatusDto::Streaming)?;
-            Ok(assistant_message)
+            Ok((assistant_message, previous_turn))
         }
     })
     .await
     {
-        Ok(assistant_message) => assistant_message,
+        Ok((assistant_message, previous_turn)) => {
+            let notices = model_change_notice(
+                previous_turn.as_ref(),
+                &thread.engine_id,
+                &effective_model_id,
+                reasoning_effort.as_deref(),
+            )
+            .into_iter()
+            .chain(context_budget_notice)
+            .chain(rollover_notice)
+            .chain(external_cli_session_notice(thread.engine_metadata.as_ref()))
+            .collect::<Vec<_>>();
+            (assistant_message, notices)
+        }
         Err(error) => {
             state.turns.finish(&thread.id).await;
-            return Err(error);
+            return Err(error.into());
         }
     };
 
-    let state_cloned = state.inner().clone();
+    let state_cloned = state.clone();
     let app_handle = app.clone();
     let assistant_message_id = assistant_message.id.clone();
     let turn_input_for_task = turn_input.clone();
@@ -763,6 +929,7 @@ pub async fn send_message(
             initial_turn_model_id,
             turn_input_for_task,
             client_turn_id,
+            initial_notices,
             cancellation,
         )
         .await;
@@ -771,6 +938,471 @@ pub async fn send_message(
     Ok(assistant_message.id)
 }
 
+/// Queues `message` to be sent when the thread's running turn ends. With no
+/// turn running it is sent right away.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn queue_message(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    message: String,
+    model_id: Option<String>,
+    reasoning_effort: Option<String>,
+    attachments: Option<Vec<ChatAttachmentPayload>>,
+    input_items: Option<Vec<ChatInputItemPayload>>,
+    plan_mode: Option<bool>,
+    run_after_error: Option<bool>,
+) -> Result<QueuedMessageDto, String> {
+    let new_message = db::pending_messages::NewQueuedMessage {
+        message,
+        model_id,
+        reasoning_effort,
+        attachments: attachments
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(err_to_string)?,
+        input_items: input_items
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(err_to_string)?,
+        plan_mode: plan_mode.unwrap_or(false),
+        run_after_error: run_after_error.unwrap_or(false),
+    };
+    let queued = run_db(state.db.clone(), {
+        let thread_id = thread_id.clone();
+        move |db| {
+            if db::threads::get_thread(db, &thread_id)?.is_none() {
+                anyhow::bail!("thread not found: {thread_id}");
+            }
+            db::pending_messages::queue_message(db, &thread_id, &new_message)
+        }
+    })
+    .await?;
+    emit_queued_messages_updated(&app, state.inner(), &thread_id).await;
+
+    if !state.turns.is_active(&thread_id) {
+        spawn_queued_message_dispatch(app, state.inner().clone(), thread_id, false);
+    }
+    Ok(queued)
+}
+
+#[tauri::command]
+pub async fn list_queued_messages(
+    state: State<'_, AppState>,
+    thread_id: String,
+) -> Result<Vec<QueuedMessageDto>, String> {
+    run_db(state.db.clone(), move |db| {
+        db::pending_messages::list_queued_messages(db, &thread_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn cancel_queued_message(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    queued_message_id: String,
+) -> Result<(), String> {
+    run_db(state.db.clone(), {
+        let thread_id = thread_id.clone();
+        move |db| db::pending_messages::cancel_queued_message(db, &thread_id, &queued_message_id)
+    })
+    .await?;
+    emit_queued_messages_updated(&app, state.inner(), &thread_id).await;
+    Ok(())
+}
+
+async fn emit_queued_messages_updated(app: &tauri::AppHandle, state: &AppState, thread_id: &str) {
+    match run_db(state.db.clone(), {
+        let thread_id = thread_id.to_string();
+        move |db| db::pending_messages::list_queued_messages(db, &thread_id)
+    })
+    .await
+    {
+        Ok(messages) => {
+            let _ = app.emit(
+                "queued-messages-updated",
+                QueuedMessagesUpdatedEvent {
+                    thread_id: thread_id.to_string(),
+                    messages,
+                },
+            );
+        }
+        Err(error) => log::warn!("failed to load queued messages for {thread_id}: {error}"),
+    }
+}
+
+/// Sends the thread's next queued message in the background. `after_error`
+/// is set when the turn that just ended failed.
+fn spawn_queued_message_dispatch(
+    app: tauri::AppHandle,
+    state: AppState,
+    thread_id: String,
+    after_error: bool,
+) {
+    // Boxed: the dispatch starts a turn whose end dispatches again.
+    let dispatch: std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> =
+        Box::pin(send_next_queued_message(app, state, thread_id, after_error));
+    tokio::spawn(dispatch);
+}
+
+async fn send_next_queued_message(
+    app: tauri::AppHandle,
+    state: AppState,
+    thread_id: String,
+    after_error: bool,
+) {
+    let taken = match run_db(state.db.clone(), {
+        let thread_id = thread_id.clone();
+        move |db| db::pending_messages::take_next_queued_message(db, &thread_id, after_error)
+    })
+    .await
+    {
+        Ok(Some(taken)) => taken,
+        Ok(None) => return,
+        Err(error) => {
+            log::warn!("failed to take queued message for thread {thread_id}: {error}");
+            return;
+        }
+    };
+    let queued = taken.message.clone();
+    let payloads = queued
+        .attachments
+        .map(serde_json::from_value::<Vec<ChatAttachmentPayload>>)
+        .transpose()
+        .and_then(|attachments| {
+            let input_items = queued
+                .input_items
+                .map(serde_json::from_value::<Vec<ChatInputItemPayload>>)
+                .transpose()?;
+            Ok((attachments, input_items))
+        })
+        .map_err(err_to_string);
+    let sent = match payloads {
+        Ok((attachments, input_items)) => send_message_inner(
+            app.clone(),
+            &state,
+            thread_id.clone(),
+            queued.message,
+            queued.model_id,
+            queued.reasoning_effort,
+            attachments,
+            input_items,
+            Some(queued.plan_mode),
+            None,
+            None,
+            TurnOrigin::NewMessage,
+        )
+        .await
+        .map_err(SendMessageErrorDto::into_message),
+        Err(error) => Err(error),
+    };
+
+    if let Err(error) = sent {
+        // Another turn won the race: keep the message for when that one
+        // ends. Anything else would fail again, so hold it for the user.
+        let held = !state.turns.is_active(&thread_id);
+        log::warn!(
+            "failed to send queued message {} for thread {thread_id}: {error}",
+            queued.id
+        );
+        if let Err(error) = run_db(state.db.clone(), move |db| {
+            db::pending_messages::restore_queued_message(db, &taken, held)
+        })
+        .await
+        {
+            log::warn!("failed to restore queued message for thread {thread_id}: {error}");
+        }
+    }
+    emit_queued_messages_updated(&app, &state, &thread_id).await;
+}
+
+/// Re-answers the prompt behind assistant `message_id`, optionally with
+/// another model of the same engine. Without `model_id` the thread's
+/// current model is used.
+#[tauri::command]
+pub async fn regenerate_message(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    message_id: String,
+    model_id: Option<String>,
+) -> Result<String, SendMessageErrorDto> {
+    let (pair, thread) = run_db(state.db.clone(), {
+        let turns = state.turns.clone();
+        move |db| {
+            let mut conn = db.connect()?;
+            let tx = conn
+                .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+                .context("failed to start message regeneration transaction")?;
+            let pair = db::messages::find_regeneration_pair_on(&tx, &message_id)?;
+            // Checked with the write lock held, as for an edit, so the pair
+            // cannot change under a turn that is starting right now.
+            if turns.is_active(&pair.assistant.thread_id) {
+                anyhow::bail!(
+                    "A turn is already running for this thread. Wait for it to finish before regenerating a response."
+                );
+            }
+            let thread = db::threads::get_thread_on(&tx, &pair.assistant.thread_id)?
+                .ok_or_else(|| anyhow::anyhow!("thread not found: {}", pair.assistant.thread_id))?;
+            tx.commit()
+                .context("failed to commit message regeneration transaction")?;
+            Ok((pair, thread))
+        }
+    })
+    .await?;
+
+    let resent = ResentUserMessage::from_message(&pair.user);
+    let message = if thread.engine_id == "codex" {
+        state.config.codex_regenerate_prompt(&resent.message)
+    } else {
+        resent.message
+    };
+    let input_items = if resent.input_items.is_empty() {
+        None
+    } else {
+        let mut items = resent.input_items;
+        items.push(ChatInputItemPayload::Text {
+            text: message.clone(),
+        });
+        Some(items)
+    };
+
+    send_message_inner(
+        app,
+        state.inner(),
+        thread.id,
+        message,
+        model_id,
+        None,
+        Some(resent.attachments),
+        input_items,
+        Some(resent.plan_mode),
+        None,
+        None,
+        TurnOrigin::Regenerate {
+            superseded_message_id: pair.assistant.id,
+        },
+    )
+    .await
+}
+
+/// Re-sends the latest prompt of a thread whose last turn failed or was
+/// interrupted, replacing the failed reply. The prompt's attachments, skills
+/// and mentions are sent again; `model_id` and `reasoning_effort` override
+/// the thread's current settings like they do for `send_message`.
+#[tauri::command]
+pub async fn retry_last_turn(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    model_id: Option<String>,
+    reasoning_effort: Option<String>,
+) -> Result<String, SendMessageErrorDto> {
+    let (target, thread) = run_db(state.db.clone(), {
+        let turns = state.turns.clone();
+        let thread_id = thread_id.clone();
+        move |db| {
+            let mut conn = db.connect()?;
+            let tx = conn
+                .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+                .context("failed to start turn retry transaction")?;
+            // Checked with the write lock held, as for an edit, so the
+            // messages picked for discarding cannot belong to a newer turn.
+            if turns.is_active(&thread_id) {
+                anyhow::bail!(
+                    "A turn is already running for this thread. Wait for it to finish before retrying."
+                );
+            }
+            let thread = db::threads::get_thread_on(&tx, &thread_id)?
+                .ok_or_else(|| anyhow::anyhow!("thread not found: {thread_id}"))?;
+            let target = db::messages::find_retry_target_on(&tx, &thread_id)?;
+            tx.commit()
+                .context("failed to commit turn retry transaction")?;
+            Ok((target, thread))
+        }
+    })
+    .await?;
+
+    let resent = ResentUserMessage::from_message(&target.user);
+    // Retrying a failed regeneration asks again on top of the original answer.
+    let message = if thread.engine_id == "codex" && target.superseded_message_id.is_some() {
+        state.config.codex_regenerate_prompt(&resent.message)
+    } else {
+        resent.message
+    };
+    let input_items = if resent.input_items.is_empty() {
+        None
+    } else {
+        let mut items = resent.input_items;
+        items.push(ChatInputItemPayload::Text {
+            text: message.clone(),
+        });
+        Some(items)
+    };
+
+    send_message_inner(
+        app,
+        state.inner(),
+        thread.id,
+        message,
+        model_id,
+        reasoning_effort,
+        Some(resent.attachments),
+        input_items,
+        Some(resent.plan_mode),
+        None,
+        None,
+        TurnOrigin::Retry {
+            discarded_message_ids: target.discarded_message_ids,
+            superseded_message_id: target.superseded_message_id,
+        },
+    )
+    .await
+}
+
+/// What a stored user message sent, rebuilt from its blocks.
+struct ResentUserMessage {
+    message: String,
+    attachments: Vec<ChatAttachmentPayload>,
+    /// Skills and mentions, in their original order.
+    input_items: Vec<ChatInputItemPayload>,
+    plan_mode: bool,
+}
+
+impl ResentUserMessage {
+    fn from_message(message: &MessageDto) -> Self {
+        let blocks = message
+            .blocks
+            .as_ref()
+            .map(ContentBlock::parse_list)
+            .unwrap_or_default();
+        let mut resent = Self {
+            message: message.content.clone().unwrap_or_default(),
+            attachments: Vec::new(),
+            input_items: Vec::new(),
+            plan_mode: false,
+        };
+        for block in blocks {
+            match block {
+                ContentBlock::Text {
+                    content, plan_mode, ..
+                } => {
+                    resent.message = content;
+                    resent.plan_mode = plan_mode.unwrap_or(false);
+                }
+                ContentBlock::Attachment {
+                    file_name,
+                    file_path,
+                    size_bytes,
+                    mime_type,
+                } => resent.attachments.push(ChatAttachmentPayload {
+                    file_name,
+                    file_path,
+                    size_bytes,
+                    mime_type,
+                }),
+                ContentBlock::Skill { name, path } => resent
+                    .input_items
+                    .push(ChatInputItemPayload::Skill { name, path }),
+                ContentBlock::Mention { name, path } => resent
+                    .input_items
+                    .push(ChatInputItemPayload::Mention { name, path }),
+                _ => {}
+            }
+        }
+        resent
+    }
+}
+
+/// Rewrites user message `message_id` and drops every message after it.
+/// The engine thread is detached as well, so the next turn starts a new
+/// engine-side conversation instead of resuming one that still holds the
+/// dropped turns, and its context usage is forgotten. All of it commits
+/// together or not at all. Returns the thread's remaining messages.
+#[tauri::command]
+pub async fn edit_user_message(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    message_id: String,
+    new_text: String,
+) -> Result<Vec<MessageDto>, String> {
+    if new_text.trim().is_empty() {
+        return Err("message text cannot be empty".to_string());
+    }
+
+    let messages = run_db(state.db.clone(), {
+        let turns = state.turns.clone();
+        let thread_id = thread_id.clone();
+        move |db| {
+            let mut conn = db.connect()?;
+            let tx = conn
+                .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+                .context("failed to start message edit transaction")?;
+            // Checked with the write lock held: a turn that started earlier
+            // refuses the edit, and one starting now writes after it commits.
+            if turns.is_active(&thread_id) {
+                anyhow::bail!(
+                    "A turn is already running for this thread. Cancel it before editing a message."
+                );
+            }
+            db::messages::rewrite_user_message_on(&tx, &thread_id, &message_id, &new_text)?;
+            db::threads::clear_engine_thread_id_on(&tx, &thread_id)?;
+            db::threads::reset_context_usage_on(&tx, &thread_id)?;
+            db::threads::refresh_thread_message_stats_on(&tx, &thread_id)?;
+            tx.commit()
+                .context("failed to commit message edit transaction")?;
+            db::messages::get_thread_messages_on(&conn, &thread_id)
+        }
+    })
+    .await?;
+    state.turns.forget_context_usage(&thread_id).await;
+    emit_thread_updated(&app, &state.db, &thread_id).await;
+    Ok(messages)
+}
+
+/// Removes one message, with its actions and approvals, from the thread's
+/// history. Like an edit, this detaches the engine thread so the removed
+/// message does not linger in the engine-side conversation; while a turn is
+/// running the engine thread is left attached for that turn to finish on.
+/// The delete, the detach and the refreshed thread stats commit together.
+#[tauri::command]
+pub async fn delete_message(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    message_id: String,
+) -> Result<(), String> {
+    let detached = run_db(state.db.clone(), {
+        let turns = state.turns.clone();
+        let thread_id = thread_id.clone();
+        move |db| {
+            let mut conn = db.connect()?;
+            let tx = conn
+                .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+                .context("failed to start message delete transaction")?;
+            let turn_active = turns.is_active(&thread_id);
+            db::messages::delete_message_on(&tx, &thread_id, &message_id, turn_active)?;
+            if !turn_active {
+                db::threads::clear_engine_thread_id_on(&tx, &thread_id)?;
+                db::threads::reset_context_usage_on(&tx, &thread_id)?;
+            }
+            db::threads::refresh_thread_message_stats_on(&tx, &thread_id)?;
+            tx.commit()
+                .context("failed to commit message delete transaction")?;
+            Ok(!turn_active)
+        }
+    })
+    .await?;
+    if detached {
+        state.turns.forget_context_usage(&thread_id).await;
+    }
+    emit_thread_updated(&app, &state.db, &thread_id).await;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn start_codex_review(
     app: tauri::AppHandle,
@@ -779,7 +1411,7 @@ pub async fn start_codex_review(
     target: CodexReviewTargetPayload,
     delivery: Option<CodexReviewDeliveryPayload>,
 ) -> Result<ThreadDto, String> {
-    if state.turns.get(&thread_id).await.is_some() {
+    if state.turns.is_active(&thread_id) {
         return Err(
             "A turn is already running for this thread. Cancel it before starting a review."
                 .to_string(),
@@ -932,7 +1564,7 @@ pub async fn steer_message(
     input_items: Option<Vec<ChatInputItemPayload>>,
     plan_mode: Option<bool>,
 ) -> Result<(), String> {
-    if state.turns.get(&thread_id).await.is_none() {
+    if !state.turns.is_active(&thread_id) {
         return Err(
             "No active turn is running for this thread yet. Wait for Codex to start the turn before steering."
                 .to_string(),
@@ -955,7 +1587,8 @@ pub async fn steer_message(
         .engine_thread_id
         .clone()
         .ok_or_else(|| format!("thread `{thread_id}` has no active engine thread id"))?;
-    let attachments = normalize_attachments(attachments)?;
+    let attachments =
+        normalize_attachments(attachments, AttachmentLimits::from_config(&state.config)).await?;
     let input_items = normalize_input_items(message.as_str(), input_items)?;
     let plan_mode = plan_mode.unwrap_or(false);
     let turn_input = TurnInput {
@@ -996,8 +1629,9 @@ pub async fn steer_message(
         .await
     {
         let rollback_result = run_db(db, {
+            let thread_id = thread.id.clone();
             let message_id = user_message.id.clone();
-            move |db| db::messages::delete_message(db, &message_id)
+            move |db| db::messages::delete_message(db, &thread_id, &message_id, true)
         })
         .await;
         if let Err(rollback_error) = rollback_result {
@@ -1145,10 +1779,42 @@ fn normalize_input_items(
     Ok(merged)
 }
 
-fn normalize_attachments(
+#[derive(Debug, Clone, Copy)]
+struct AttachmentLimits {
+    max_file_bytes: u64,
+    max_turn_bytes: u64,
+}
+
+impl AttachmentLimits {
+    fn from_config(config: &AppConfig) -> Self {
+        Self {
+            max_file_bytes: config.attachment_max_file_bytes(),
+            max_turn_bytes: config.attachment_max_turn_bytes(),
+        }
+    }
+}
+
+/// Checks every attachment against the file on disk before anything is
+/// written for the turn, so a stale path fails here rather than mid-turn.
+async fn normalize_attachments(
     attachments: Option<Vec<ChatAttachmentPayload>>,
+    limits: AttachmentLimits,
 ) -> Result<Vec<TurnAttachment>, String> {
     let attachments = attachments.unwrap_or_default();
+    if attachments.is_empty() {
+        return Ok(Vec::new());
+    }
+    tokio::task::spawn_blocking(move || stat_attachments(attachments, limits))
+        .await
+        .map_err(err_to_string)?
+}
+
+/// Sizes come from the file, not the client, which may have read them
+/// before the file changed.
+fn stat_attachments(
+    attachments: Vec<ChatAttachmentPayload>,
+    limits: AttachmentLimits,
+) -> Result<Vec<TurnAttachment>, String> {
     if attachments.len() > MAX_ATTACHMENTS_PER_TURN {
         return Err(format!(
             "You can attach at most {MAX_ATTACHMENTS_PER_TURN} files per turn."
@@ -1156,6 +1822,7 @@ fn normalize_attachments(
     }
 
     let mut normalized = Vec::with_capacity(attachments.len());
+    let mut total_bytes = 0u64;
     for attachment in attachments {
         let file_path = attachment.file_path.trim().to_string();
         if file_path.is_empty() {
@@ -1172,10 +1839,34 @@ fn normalize_attachments(
             attachment.file_name.trim().to_string()
         };
 
+        let metadata = std::fs::metadata(&file_path).map_err(|error| {
+            format!("Attachment `{file_name}` could not be read at `{file_path}`: {error}")
+        })?;
+        if !metadata.is_file() {
+            return Err(format!("Attachment `{file_name}` is not a file."));
+        }
+        std::fs::File::open(&file_path)
+            .map_err(|error| format!("Attachment `{file_name}` could not be opened: {error}"))?;
+        let size_bytes = metadata.len();
+        if size_bytes > limits.max_file_bytes {
+            return Err(format!(
+                "Attachment `{file_name}` is {}; the limit is {} per file.",
+                format_megabytes(size_bytes),
+                format_megabytes(limits.max_file_bytes)
+            ));
+        }
+        total_bytes = total_bytes.saturating_add(size_bytes);
+        if total_bytes > limits.max_turn_bytes {
+            return Err(format!(
+                "Attachments add up to more than {}; send fewer or smaller files.",
+                format_megabytes(limits.max_turn_bytes)
+            ));
+        }
+
         normalized.push(TurnAttachment {
             file_name,
             file_path,
-            size_bytes: attachment.size_bytes,
+            size_bytes,
             mime_type: attachment.mime_type,
         });
     }
@@ -1183,6 +1874,15 @@ fn normalize_attachments(
     Ok(normalized)
 }
 
+fn format_megabytes(bytes: u64) -> String {
+    let megabytes = bytes as f64 / (1024.0 * 1024.0);
+    if megabytes.fract() == 0.0 {
+        format!("{megabytes:.0} MB")
+    } else {
+        format!("{megabytes:.1} MB")
+    }
+}
+
 fn validate_attachments_for_engine_model(
     attachments: &[TurnAttachment],
     engine_id: &str,
@@ -1300,8 +2000,65 @@ fn attachment_modality_label(modality: &str) -> &'static str {
     }
 }
 
+/// A turn cancelled while still streaming ends as interrupted and leaves
+/// its thread idle. True when the statuses changed.
+fn settle_cancelled_turn(
+    cancelled: bool,
+    message_status: &mut MessageStatusDto,
+    thread_status: &mut ThreadStatusDto,
+) -> bool {
+    if !cancelled || *message_status != MessageStatusDto::Streaming {
+        return false;
+    }
+    *message_status = MessageStatusDto::Interrupted;
+    *thread_status = ThreadStatusDto::Idle;
+    true
+}
+
+/// Cancels the thread's running turn, if any, and waits for it to settle.
+/// True when a turn was running.
+pub(crate) async fn interrupt_running_turn(state: &AppState, thread: &ThreadDto) -> bool {
+    if !state.turns.is_active(&thread.id) {
+        return false;
+    }
+    state.turns.cancel(&thread.id).await;
+    if let Err(error) = state.engines.interrupt(thread).await {
+        log::warn!(
+            "failed to interrupt engine turn for thread {}: {error}",
+            thread.id
+        );
+    }
+    if !state
+        .turns
+        .wait_until_finished(&thread.id, TURN_INTERRUPT_SETTLE_TIMEOUT)
+        .await
+    {
+        log::warn!(
+            "turn for thread {} was still running {:?} after it was cancelled",
+            thread.id,
+            TURN_INTERRUPT_SETTLE_TIMEOUT
+        );
+    }
+    true
+}
+
+/// Stops the running turn. With `clear_queue` the thread's queued messages
+/// are dropped too; either way none are sent because of this turn.
 #[tauri::command]
-pub async fn cancel_turn(state: State<'_, AppState>, thread_id: String) -> Result<(), String> {
+pub async fn cancel_turn(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    clear_queue: Option<bool>,
+) -> Result<(), String> {
+    if clear_queue.unwrap_or(false) {
+        run_db(state.db.clone(), {
+            let thread_id = thread_id.clone();
+            move |db| db::pending_messages::clear_queued_messages(db, &thread_id)
+        })
+        .await?;
+        emit_queued_messages_updated(&app, state.inner(), &thread_id).await;
+    }
     state.turns.cancel(&thread_id).await;
 
     let db = state.db.clone();
@@ -1370,6 +2127,7 @@ async fn respond_to_approval_inner(
         let decision = decision.to_string();
         move |db| {
             db::actions::answer_approval(db, &approval_id, &decision)?;
+            metrics::incr(db, metrics::APPROVALS_ANSWERED, 1);
             if let Some(message_id) = db::actions::find_approval_message_id(db, &approval_id)? {
                 let _ = db::messages::mark_approval_block_answered(
                     db,
@@ -1467,6 +2225,25 @@ pub async fn get_thread_messages(
     .await
 }
 
+/// Pages backwards through a thread: the newest `limit` messages, or those
+/// before `before_message_id`, oldest first within the page.
+#[tauri::command]
+pub async fn get_thread_messages_page(
+    state: State<'_, AppState>,
+    thread_id: String,
+    before_message_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<MessageWindowDto, String> {
+    let limit = limit
+        .map(|limit| limit as usize)
+        .unwrap_or(THREAD_MESSAGES_PAGE_DEFAULT_LIMIT)
+        .clamp(1, MESSAGE_WINDOW_MAX_LIMIT);
+    run_db(state.db.clone(), move |db| {
+        db::messages::get_thread_messages_page(db, &thread_id, before_message_id.as_deref(), limit)
+    })
+    .await
+}
+
 #[tauri::command]
 pub async fn get_thread_messages_window(
     state: State<'_, AppState>,
@@ -1483,6 +2260,72 @@ pub async fn get_thread_messages_window(
     .await
 }
 
+#[tauri::command]
+pub async fn get_message_storage_report(
+    state: State<'_, AppState>,
+) -> Result<BlobStorageReportDto, String> {
+    run_db(state.db.clone(), db::blobs::storage_report).await
+}
+
+#[tauri::command]
+pub async fn get_turn_diagnostics(
+    state: State<'_, AppState>,
+    message_id: String,
+) -> Result<Vec<TurnDiagnosticDto>, String> {
+    run_db(state.db.clone(), move |db| {
+        db::actions::get_turn_diagnostics(db, &message_id)
+    })
+    .await
+}
+
+/// Where the JSONL event log of the turn behind `assistant_message_id` is
+/// written, with its last `tail_lines` entries. The file only exists when
+/// `debug.record_turn_event_logs` was on for that turn.
+#[tauri::command]
+pub async fn get_turn_event_log(
+    state: State<'_, AppState>,
+    assistant_message_id: String,
+    tail_lines: Option<usize>,
+) -> Result<TurnEventLogDto, String> {
+    let thread_id = run_db(state.db.clone(), {
+        let message_id = assistant_message_id.clone();
+        move |db| db::messages::message_thread_id(db, &message_id)
+    })
+    .await?
+    .ok_or_else(|| format!("message not found: {assistant_message_id}"))?;
+    let path = turn_event_log_path(&turn_event_log_dir(), &thread_id, &assistant_message_id)
+        .map_err(err_to_string)?;
+    let tail_lines = tail_lines
+        .unwrap_or_default()
+        .min(TURN_EVENT_LOG_MAX_TAIL_LINES);
+    tokio::task::spawn_blocking(move || {
+        let exists = path.is_file();
+        let tail = if exists {
+            read_turn_event_log_tail(&path, tail_lines).map_err(err_to_string)?
+        } else {
+            Vec::new()
+        };
+        Ok(TurnEventLogDto {
+            path: path.to_string_lossy().to_string(),
+            exists,
+            tail,
+        })
+    })
+    .await
+    .map_err(err_to_string)?
+}
+
+#[tauri::command]
+pub async fn get_thread_model_history(
+    state: State<'_, AppState>,
+    thread_id: String,
+) -> Result<ThreadModelHistoryDto, String> {
+    run_db(state.db.clone(), move |db| {
+        db::messages::get_thread_model_history(db, &thread_id)
+    })
+    .await
+}
+
 #[tauri::command]
 pub async fn get_message_blocks(
     state: State<'_, AppState>,
@@ -1511,9 +2354,15 @@ pub async fn search_messages(
     state: State<'_, AppState>,
     workspace_id: String,
     query: String,
+    context_before: Option<usize>,
+    context_after: Option<usize>,
+    limit: Option<usize>,
+    offset: Option<usize>,
 ) -> Result<Vec<SearchResultDto>, String> {
+    let context = db::messages::SnippetContext::new(context_before, context_after);
+    let page = db::messages::SearchPage::new(limit, offset);
     run_db(state.db.clone(), move |db| {
-        db::messages::search_messages(db, &workspace_id, &query)
+        db::messages::search_messages(db, &workspace_id, &query, context, page)
     })
     .await
 }
@@ -1613,6 +2462,7 @@ fn clone_codex_review_metadata(existing: Option<&Value>, model_id: &str) -> Opti
     Some(metadata)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_turn(
     app: tauri::AppHandle,
     state: AppState,
@@ -1622,9 +2472,11 @@ async fn run_turn(
     initial_turn_model_id: String,
     turn_input: TurnInput,
     client_turn_id: Option<String>,
+    initial_notices: Vec<EngineEvent>,
     cancellation: CancellationToken,
 ) {
     let max_output_chars = state.config.debug.max_action_output_chars;
+    let stream_tuning = StreamTuning::from_config(&state.config);
     let (event_tx, mut event_rx) = mpsc::channel::<EngineEvent>(ENGINE_EVENT_QUEUE_CAPACITY);
 
     let engines = state.engines.clone();
@@ -1633,6 +2485,7 @@ async fn run_turn(
     let engine_thread_for_engine = engine_thread_id.clone();
     let cancellation_for_engine = cancellation.clone();
 
+    state.engines.turn_diagnostics().begin(&engine_thread_id);
     let engine_task = tokio::spawn(async move {
         engines
             .send_message(
@@ -1662,35 +2515,44 @@ async fn run_turn(
     let stream_event_topic = format!("stream-event-{}", thread.id);
     let approval_event_topic = format!("approval-request-{}", thread.id);
     let mut pending_event: Option<EngineEvent> = None;
-
-    let initial_turn_started_event = EngineEvent::TurnStarted { client_turn_id };
-    let initial_progress = process_stream_event(
-        &app,
-        &state,
-        &thread,
-        &assistant_message_id,
-        &stream_event_topic,
-        &approval_event_topic,
-        &initial_turn_started_event,
-        &mut blocks,
-        &mut action_index,
-        &mut approval_index,
-        max_output_chars,
-    )
-    .await;
-    let initial_force_persist = apply_stream_progress(
-        initial_progress,
-        &mut message_status,
-        &mut thread_status,
-        &mut turn_model_id,
-        &mut token_usage,
-        &mut blocks_dirty,
-        &mut message_state_dirty,
-        &mut thread_status_dirty,
-        &mut turn_model_dirty,
-    );
+    let writes = TurnWriteBatcher::start(&state.db);
+    let event_log = start_turn_event_log(&state, &thread.id, &assistant_message_id);
+
+    let initial_events = std::iter::once(EngineEvent::TurnStarted { client_turn_id })
+        .chain(initial_notices)
+        .collect::<Vec<_>>();
+    let mut initial_force_persist = false;
+    for initial_event in &initial_events {
+        let initial_progress = process_stream_event(
+            &app,
+            &state,
+            &writes,
+            event_log.as_ref(),
+            &thread,
+            &assistant_message_id,
+            &stream_event_topic,
+            &approval_event_topic,
+            initial_event,
+            &mut blocks,
+            &mut action_index,
+            &mut approval_index,
+            max_output_chars,
+        )
+        .await;
+        initial_force_persist |= apply_stream_progress(
+            initial_progress,
+            &mut message_status,
+            &mut thread_status,
+            &mut turn_model_id,
+            &mut token_usage,
+            &mut blocks_dirty,
+            &mut message_state_dirty,
+            &mut thread_status_dirty,
+            &mut turn_model_dirty,
+        );
+    }
     flush_stream_state(
-        &state,
+        &writes,
         &thread,
         &assistant_message_id,
         &blocks,
@@ -1704,6 +2566,7 @@ async fn run_turn(
         &mut last_persisted_thread_status,
         &mut last_persist_at,
         &mut last_blocks_persist_at,
+        &stream_tuning,
         initial_force_persist,
     )
     .await;
@@ -1719,6 +2582,8 @@ async fn run_turn(
                         let progress = process_stream_event(
                             &app,
                             &state,
+                            &writes,
+                            event_log.as_ref(),
                             &thread,
                             &assistant_message_id,
                             &stream_event_topic,
@@ -1742,7 +2607,7 @@ async fn run_turn(
                             &mut turn_model_dirty,
                         );
                         flush_stream_state(
-                            &state,
+                            &writes,
                             &thread,
                             &assistant_message_id,
                             &blocks,
@@ -1756,6 +2621,7 @@ async fn run_turn(
                             &mut last_persisted_thread_status,
                             &mut last_persist_at,
                             &mut last_blocks_persist_at,
+                            &stream_tuning,
                             force_persist,
                         )
                         .await;
@@ -1778,11 +2644,13 @@ async fn run_turn(
                 match try_coalesce_stream_events(previous_event, current_event) {
                     Ok(merged_event) => {
                         if coalesced_event_content_len(&merged_event)
-                            >= STREAM_EVENT_COALESCE_MAX_CHARS
+                            >= stream_tuning.coalesce_max_chars
                         {
                             let progress = process_stream_event(
                                 &app,
                                 &state,
+                                &writes,
+                                event_log.as_ref(),
                                 &thread,
                                 &assistant_message_id,
                                 &stream_event_topic,
@@ -1806,7 +2674,7 @@ async fn run_turn(
                                 &mut turn_model_dirty,
                             );
                             flush_stream_state(
-                                &state,
+                                &writes,
                                 &thread,
                                 &assistant_message_id,
                                 &blocks,
@@ -1820,6 +2688,7 @@ async fn run_turn(
                                 &mut last_persisted_thread_status,
                                 &mut last_persist_at,
                                 &mut last_blocks_persist_at,
+                                &stream_tuning,
                                 force_persist,
                             )
                             .await;
@@ -1832,6 +2701,8 @@ async fn run_turn(
                         let progress = process_stream_event(
                             &app,
                             &state,
+                            &writes,
+                            event_log.as_ref(),
                             &thread,
                             &assistant_message_id,
                             &stream_event_topic,
@@ -1855,7 +2726,7 @@ async fn run_turn(
                             &mut turn_model_dirty,
                         );
                         flush_stream_state(
-                            &state,
+                            &writes,
                             &thread,
                             &assistant_message_id,
                             &blocks,
@@ -1869,6 +2740,7 @@ async fn run_turn(
                             &mut last_persisted_thread_status,
                             &mut last_persist_at,
                             &mut last_blocks_persist_at,
+                            &stream_tuning,
                             force_persist,
                         )
                         .await;
@@ -1882,6 +2754,8 @@ async fn run_turn(
                 let progress = process_stream_event(
                     &app,
                     &state,
+                    &writes,
+                    event_log.as_ref(),
                     &thread,
                     &assistant_message_id,
                     &stream_event_topic,
@@ -1905,7 +2779,7 @@ async fn run_turn(
                     &mut turn_model_dirty,
                 );
                 flush_stream_state(
-                    &state,
+                    &writes,
                     &thread,
                     &assistant_message_id,
                     &blocks,
@@ -1919,6 +2793,7 @@ async fn run_turn(
                     &mut last_persisted_thread_status,
                     &mut last_persist_at,
                     &mut last_blocks_persist_at,
+                    &stream_tuning,
                     force_persist,
                 )
                 .await;
@@ -1931,6 +2806,8 @@ async fn run_turn(
         let progress = process_stream_event(
             &app,
             &state,
+            &writes,
+            event_log.as_ref(),
             &thread,
             &assistant_message_id,
             &stream_event_topic,
@@ -1954,7 +2831,7 @@ async fn run_turn(
             &mut turn_model_dirty,
         );
         flush_stream_state(
-            &state,
+            &writes,
             &thread,
             &assistant_message_id,
             &blocks,
@@ -1968,6 +2845,7 @@ async fn run_turn(
             &mut last_persisted_thread_status,
             &mut last_persist_at,
             &mut last_blocks_persist_at,
+            &stream_tuning,
             force_persist,
         )
         .await;
@@ -2012,15 +2890,49 @@ async fn run_turn(
         }
     }
 
-    if cancellation.is_cancelled() && matches!(message_status, MessageStatusDto::Streaming) {
-        message_status = MessageStatusDto::Interrupted;
+    if settle_cancelled_turn(
+        cancellation.is_cancelled(),
+        &mut message_status,
+        &mut thread_status,
+    ) {
         message_state_dirty = true;
-        thread_status = ThreadStatusDto::Idle;
         thread_status_dirty = true;
     }
 
+    if message_status == MessageStatusDto::Completed {
+        if let Some(notice) = context_rollover_warning(&state, &thread).await {
+            let progress = process_stream_event(
+                &app,
+                &state,
+                &writes,
+                event_log.as_ref(),
+                &thread,
+                &assistant_message_id,
+                &stream_event_topic,
+                &approval_event_topic,
+                &notice,
+                &mut blocks,
+                &mut action_index,
+                &mut approval_index,
+                max_output_chars,
+            )
+            .await;
+            apply_stream_progress(
+                progress,
+                &mut message_status,
+                &mut thread_status,
+                &mut turn_model_id,
+                &mut token_usage,
+                &mut blocks_dirty,
+                &mut message_state_dirty,
+                &mut thread_status_dirty,
+                &mut turn_model_dirty,
+            );
+        }
+    }
+
     flush_stream_state(
-        &state,
+        &writes,
         &thread,
         &assistant_message_id,
         &blocks,
@@ -2034,9 +2946,15 @@ async fn run_turn(
         &mut last_persisted_thread_status,
         &mut last_persist_at,
         &mut last_blocks_persist_at,
+        &stream_tuning,
         true,
     )
     .await;
+    writes.finish().await;
+    metrics::spawn_incr(
+        &state.db,
+        metrics::turn_counters(&thread.engine_id, &turn_model_id, token_usage),
+    );
 
     state.turns.finish(&thread.id).await;
 
@@ -2059,6 +2977,19 @@ async fn run_turn(
         log::warn!("failed to complete assistant message: {error}");
     }
 
+    if capabilities_for_engine(&thread.engine_id).approvals_end_with_turn {
+        resolve_abandoned_approvals(&app, &state, &assistant_message_id, &stream_event_topic).await;
+    }
+
+    persist_turn_diagnostics(
+        &state,
+        &thread.id,
+        &engine_thread_id,
+        &assistant_message_id,
+        &message_status,
+    )
+    .await;
+
     if matches!(message_status, MessageStatusDto::Completed) {
         if let Err(error) = run_db(state.db.clone(), {
             let thread_id = thread.id.clone();
@@ -2089,6 +3020,54 @@ async fn run_turn(
     if let Some(final_thread) = final_thread.as_ref() {
         emit_chat_turn_finished(&app, final_thread, &message_status, &blocks);
     }
+
+    // Interrupted turns leave the queue alone; the user stopped the agent.
+    match message_status {
+        MessageStatusDto::Completed => spawn_queued_message_dispatch(app, state, thread.id, false),
+        MessageStatusDto::Error => spawn_queued_message_dispatch(app, state, thread.id, true),
+        _ => {}
+    }
+}
+
+async fn resolve_abandoned_approvals(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    assistant_message_id: &str,
+    stream_event_topic: &str,
+) {
+    let resolved = match run_db(state.db.clone(), {
+        let assistant_message_id = assistant_message_id.to_string();
+        move |db| {
+            let resolved =
+                db::actions::resolve_pending_approvals_for_message(db, &assistant_message_id)?;
+            for approval_id in &resolved {
+                db::messages::mark_approval_block_resolved(
+                    db,
+                    &assistant_message_id,
+                    approval_id,
+                    None,
+                )?;
+            }
+            Ok(resolved)
+        }
+    })
+    .await
+    {
+        Ok(resolved) => resolved,
+        Err(error) => {
+            log::warn!("failed to resolve abandoned approvals: {error}");
+            return;
+        }
+    };
+    for approval_id in resolved {
+        let _ = app.emit(
+            stream_event_topic,
+            serde_json::json!({
+                "type": "ApprovalResolved",
+                "approval_id": approval_id,
+            }),
+        );
+    }
 }
 
 async fn run_codex_review_turn(
@@ -2104,6 +3083,7 @@ async fn run_codex_review_turn(
     cancellation: CancellationToken,
 ) {
     let max_output_chars = state.config.debug.max_action_output_chars;
+    let stream_tuning = StreamTuning::from_config(&state.config);
     let (event_tx, mut event_rx) = mpsc::channel::<EngineEvent>(ENGINE_EVENT_QUEUE_CAPACITY);
     let (started_tx, started_rx) = oneshot::channel();
 
@@ -2113,6 +3093,10 @@ async fn run_codex_review_turn(
     let delivery_for_engine = delivery.clone();
     let cancellation_for_engine = cancellation.clone();
 
+    state
+        .engines
+        .turn_diagnostics()
+        .begin(&source_engine_thread_id);
     let engine_task = tokio::spawn(async move {
         engines
             .start_codex_review(
@@ -2207,6 +3191,8 @@ async fn run_codex_review_turn(
     let stream_event_topic = format!("stream-event-{}", review_thread.id);
     let approval_event_topic = format!("approval-request-{}", review_thread.id);
     let mut pending_event: Option<EngineEvent> = None;
+    let writes = TurnWriteBatcher::start(&state.db);
+    let event_log = start_turn_event_log(&state, &review_thread.id, &assistant_message_id);
 
     let initial_turn_started_event = EngineEvent::TurnStarted {
         client_turn_id: None,
@@ -2214,6 +3200,8 @@ async fn run_codex_review_turn(
     let initial_progress = process_stream_event(
         &app,
         &state,
+        &writes,
+        event_log.as_ref(),
         &review_thread,
         &assistant_message_id,
         &stream_event_topic,
@@ -2237,7 +3225,7 @@ async fn run_codex_review_turn(
         &mut turn_model_dirty,
     );
     flush_stream_state(
-        &state,
+        &writes,
         &review_thread,
         &assistant_message_id,
         &blocks,
@@ -2251,6 +3239,7 @@ async fn run_codex_review_turn(
         &mut last_persisted_thread_status,
         &mut last_persist_at,
         &mut last_blocks_persist_at,
+        &stream_tuning,
         initial_force_persist,
     )
     .await;
@@ -2270,6 +3259,8 @@ async fn run_codex_review_turn(
                         let progress = process_stream_event(
                             &app,
                             &state,
+                            &writes,
+                            event_log.as_ref(),
                             &review_thread,
                             &assistant_message_id,
                             &stream_event_topic,
@@ -2293,7 +3284,7 @@ async fn run_codex_review_turn(
                             &mut turn_model_dirty,
                         );
                         flush_stream_state(
-                            &state,
+                            &writes,
                             &review_thread,
                             &assistant_message_id,
                             &blocks,
@@ -2307,6 +3298,7 @@ async fn run_codex_review_turn(
                             &mut last_persisted_thread_status,
                             &mut last_persist_at,
                             &mut last_blocks_persist_at,
+                            &stream_tuning,
                             force_persist,
                         )
                         .await;
@@ -2329,11 +3321,13 @@ async fn run_codex_review_turn(
                 match try_coalesce_stream_events(previous_event, current_event) {
                     Ok(merged_event) => {
                         if coalesced_event_content_len(&merged_event)
-                            >= STREAM_EVENT_COALESCE_MAX_CHARS
+                            >= stream_tuning.coalesce_max_chars
                         {
                             let progress = process_stream_event(
                                 &app,
                                 &state,
+                                &writes,
+                                event_log.as_ref(),
                                 &review_thread,
                                 &assistant_message_id,
                                 &stream_event_topic,
@@ -2357,7 +3351,7 @@ async fn run_codex_review_turn(
                                 &mut turn_model_dirty,
                             );
                             flush_stream_state(
-                                &state,
+                                &writes,
                                 &review_thread,
                                 &assistant_message_id,
                                 &blocks,
@@ -2371,6 +3365,7 @@ async fn run_codex_review_turn(
                                 &mut last_persisted_thread_status,
                                 &mut last_persist_at,
                                 &mut last_blocks_persist_at,
+                                &stream_tuning,
                                 force_persist,
                             )
                             .await;
@@ -2383,6 +3378,8 @@ async fn run_codex_review_turn(
                         let progress = process_stream_event(
                             &app,
                             &state,
+                            &writes,
+                            event_log.as_ref(),
                             &review_thread,
                             &assistant_message_id,
                             &stream_event_topic,
@@ -2406,7 +3403,7 @@ async fn run_codex_review_turn(
                             &mut turn_model_dirty,
                         );
                         flush_stream_state(
-                            &state,
+                            &writes,
                             &review_thread,
                             &assistant_message_id,
                             &blocks,
@@ -2420,6 +3417,7 @@ async fn run_codex_review_turn(
                             &mut last_persisted_thread_status,
                             &mut last_persist_at,
                             &mut last_blocks_persist_at,
+                            &stream_tuning,
                             force_persist,
                         )
                         .await;
@@ -2433,6 +3431,8 @@ async fn run_codex_review_turn(
                 let progress = process_stream_event(
                     &app,
                     &state,
+                    &writes,
+                    event_log.as_ref(),
                     &review_thread,
                     &assistant_message_id,
                     &stream_event_topic,
@@ -2456,7 +3456,7 @@ async fn run_codex_review_turn(
                     &mut turn_model_dirty,
                 );
                 flush_stream_state(
-                    &state,
+                    &writes,
                     &review_thread,
                     &assistant_message_id,
                     &blocks,
@@ -2470,6 +3470,7 @@ async fn run_codex_review_turn(
                     &mut last_persisted_thread_status,
                     &mut last_persist_at,
                     &mut last_blocks_persist_at,
+                    &stream_tuning,
                     force_persist,
                 )
                 .await;
@@ -2482,6 +3483,8 @@ async fn run_codex_review_turn(
         let progress = process_stream_event(
             &app,
             &state,
+            &writes,
+            event_log.as_ref(),
             &review_thread,
             &assistant_message_id,
             &stream_event_topic,
@@ -2505,7 +3508,7 @@ async fn run_codex_review_turn(
             &mut turn_model_dirty,
         );
         flush_stream_state(
-            &state,
+            &writes,
             &review_thread,
             &assistant_message_id,
             &blocks,
@@ -2519,6 +3522,7 @@ async fn run_codex_review_turn(
             &mut last_persisted_thread_status,
             &mut last_persist_at,
             &mut last_blocks_persist_at,
+            &stream_tuning,
             force_persist,
         )
         .await;
@@ -2563,15 +3567,17 @@ async fn run_codex_review_turn(
         }
     }
 
-    if cancellation.is_cancelled() && matches!(message_status, MessageStatusDto::Streaming) {
-        message_status = MessageStatusDto::Interrupted;
+    if settle_cancelled_turn(
+        cancellation.is_cancelled(),
+        &mut message_status,
+        &mut thread_status,
+    ) {
         message_state_dirty = true;
-        thread_status = ThreadStatusDto::Idle;
         thread_status_dirty = true;
     }
 
     flush_stream_state(
-        &state,
+        &writes,
         &review_thread,
         &assistant_message_id,
         &blocks,
@@ -2585,9 +3591,15 @@ async fn run_codex_review_turn(
         &mut last_persisted_thread_status,
         &mut last_persist_at,
         &mut last_blocks_persist_at,
+        &stream_tuning,
         true,
     )
     .await;
+    writes.finish().await;
+    metrics::spawn_incr(
+        &state.db,
+        metrics::turn_counters(&review_thread.engine_id, &turn_model_id, token_usage),
+    );
 
     state.turns.finish(&source_thread.id).await;
     state.turns.finish(&review_thread.id).await;
@@ -2611,6 +3623,15 @@ async fn run_codex_review_turn(
         log::warn!("failed to complete review assistant message: {error}");
     }
 
+    persist_turn_diagnostics(
+        &state,
+        &review_thread.id,
+        &source_engine_thread_id,
+        &assistant_message_id,
+        &message_status,
+    )
+    .await;
+
     if matches!(message_status, MessageStatusDto::Completed) {
         if let Err(error) = run_db(state.db.clone(), {
             let thread_id = review_thread.id.clone();
@@ -2640,6 +3661,47 @@ async fn run_codex_review_turn(
     }
 }
 
+/// Closes the turn's diagnostics buffer; only failed turns keep what it
+/// collected.
+async fn persist_turn_diagnostics(
+    state: &AppState,
+    thread_id: &str,
+    engine_thread_id: &str,
+    message_id: &str,
+    message_status: &MessageStatusDto,
+) {
+    let diagnostics = state.engines.turn_diagnostics().finish(engine_thread_id);
+    let Some(entries) = turn_diagnostics_to_persist(message_status, diagnostics) else {
+        return;
+    };
+
+    if let Err(error) = run_db(state.db.clone(), {
+        let thread_id = thread_id.to_string();
+        let message_id = message_id.to_string();
+        move |db| db::actions::append_turn_diagnostics(db, &thread_id, &message_id, &entries)
+    })
+    .await
+    {
+        log::warn!("failed to persist turn diagnostics: {error}");
+    }
+}
+
+fn turn_diagnostics_to_persist(
+    message_status: &MessageStatusDto,
+    diagnostics: TurnDiagnostics,
+) -> Option<Vec<TurnDiagnosticDto>> {
+    if !matches!(message_status, MessageStatusDto::Error) || diagnostics.entries.is_empty() {
+        return None;
+    }
+    if diagnostics.evicted > 0 {
+        log::warn!(
+            "turn diagnostics dropped {} older entries before the turn failed",
+            diagnostics.evicted
+        );
+    }
+    Some(diagnostics.entries)
+}
+
 fn is_coalescable_stream_event(event: &EngineEvent) -> bool {
     matches!(
         event,
@@ -2748,6 +3810,8 @@ fn try_coalesce_stream_events(
 async fn process_stream_event(
     app: &tauri::AppHandle,
     state: &AppState,
+    writes: &TurnWriteBatcher,
+    event_log: Option<&TurnEventLog>,
     thread: &ThreadDto,
     assistant_message_id: &str,
     stream_event_topic: &str,
@@ -2789,20 +3853,18 @@ async fn process_stream_event(
         );
     }
 
-    if state.config.debug.persist_engine_event_logs {
+    if state.config.debug.persist_engine_event_logs || event_log.is_some() {
         let log_event = engine_event_for_debug_log(&normalized_event);
-        if let Ok(value) = serde_json::to_value(&log_event) {
-            if let Err(error) = run_db(state.db.clone(), {
-                let thread_id = thread.id.clone();
-                let assistant_message_id = assistant_message_id.to_string();
-                let value = value.clone();
-                move |db| {
-                    db::actions::append_event_log(db, &thread_id, &assistant_message_id, &value)
-                }
-            })
-            .await
-            {
-                log::warn!("failed to append engine event log: {error}");
+        if let Some(event_log) = event_log {
+            event_log.record(&log_event);
+        }
+        if state.config.debug.persist_engine_event_logs {
+            if let Ok(value) = serde_json::to_value(&log_event) {
+                writes.enqueue(TurnWrite::EventLog {
+                    thread_id: thread.id.clone(),
+                    message_id: assistant_message_id.to_string(),
+                    event: value,
+                });
             }
         }
     }
@@ -2815,42 +3877,21 @@ async fn process_stream_event(
             summary,
             details,
         } => {
-            if let Err(error) = run_db(state.db.clone(), {
-                let action_id = action_id.clone();
-                let thread_id = thread.id.clone();
-                let assistant_message_id = assistant_message_id.to_string();
-                let engine_action_id = engine_action_id.clone();
-                let action_type = action_type.clone();
-                let summary = summary.clone();
-                let details = details.clone();
-                move |db| {
-                    db::actions::insert_action_started(
-                        db,
-                        &action_id,
-                        &thread_id,
-                        &assistant_message_id,
-                        engine_action_id.as_deref(),
-                        &action_type,
-                        &summary,
-                        &details,
-                    )
-                }
-            })
-            .await
-            {
-                log::warn!("failed to persist action start: {error}");
-            }
+            writes.enqueue(TurnWrite::ActionStarted {
+                action_id: action_id.clone(),
+                thread_id: thread.id.clone(),
+                message_id: assistant_message_id.to_string(),
+                engine_action_id: engine_action_id.clone(),
+                action_type: action_type.clone(),
+                summary: summary.clone(),
+                details: details.clone(),
+            });
         }
         EngineEvent::ActionCompleted { action_id, result } => {
-            if let Err(error) = run_db(state.db.clone(), {
-                let action_id = action_id.clone();
-                let result = result.clone();
-                move |db| db::actions::update_action_completed(db, &action_id, &result)
-            })
-            .await
-            {
-                log::warn!("failed to persist action completion: {error}");
-            }
+            writes.enqueue(TurnWrite::ActionCompleted {
+                action_id: action_id.clone(),
+                result: result.clone(),
+            });
         }
         EngineEvent::ApprovalRequested {
             approval_id,
@@ -2858,28 +3899,34 @@ async fn process_stream_event(
             summary,
             details,
         } => {
-            if let Err(error) = run_db(state.db.clone(), {
-                let approval_id = approval_id.clone();
-                let thread_id = thread.id.clone();
-                let assistant_message_id = assistant_message_id.to_string();
-                let action_type = action_type.clone();
-                let summary = summary.clone();
-                let details = details.clone();
-                move |db| {
-                    db::actions::insert_approval(
-                        db,
-                        &approval_id,
-                        &thread_id,
-                        &assistant_message_id,
-                        &action_type,
-                        &summary,
-                        &details,
+            // Answering reads this row back, so it is committed right away
+            // rather than left for the next batch.
+            writes.enqueue(TurnWrite::Approval {
+                approval_id: approval_id.clone(),
+                thread_id: thread.id.clone(),
+                message_id: assistant_message_id.to_string(),
+                action_type: action_type.clone(),
+                summary: summary.clone(),
+                details: details.clone(),
+            });
+            writes.flush().await;
+        }
+        EngineEvent::UsageLimitsUpdated { usage } => {
+            writes.enqueue(TurnWrite::UsageLimits {
+                thread_id: thread.id.clone(),
+                usage: usage.clone(),
+            });
+            if let Some(current_tokens) = usage.current_tokens {
+                state
+                    .turns
+                    .record_context_usage(
+                        &thread.id,
+                        ThreadContextUsage {
+                            current_tokens,
+                            max_context_tokens: usage.max_context_tokens,
+                        },
                     )
-                }
-            })
-            .await
-            {
-                log::warn!("failed to persist approval: {error}");
+                    .await;
             }
         }
         _ => {}
@@ -2938,9 +3985,31 @@ fn apply_stream_progress(
     progress.force_persist
 }
 
+/// How a streaming turn batches events for the UI and writes for the
+/// database, from the `[debug]` config.
+#[derive(Debug, Clone, Copy)]
+struct StreamTuning {
+    coalesce_max_chars: usize,
+    db_flush_interval: Duration,
+    db_blocks_flush_interval: Duration,
+}
+
+impl StreamTuning {
+    fn from_config(config: &AppConfig) -> Self {
+        let db_flush_interval = config.stream_db_flush_interval();
+        Self {
+            coalesce_max_chars: config.stream_coalesce_max_chars(),
+            db_flush_interval,
+            // Block snapshots are the large writes, so they never go out more
+            // often than the status updates.
+            db_blocks_flush_interval: STREAM_DB_BLOCKS_FLUSH_INTERVAL.max(db_flush_interval),
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn flush_stream_state(
-    state: &AppState,
+    writes: &TurnWriteBatcher,
     thread: &ThreadDto,
     assistant_message_id: &str,
     blocks: &[ContentBlock],
@@ -2954,9 +4023,13 @@ async fn flush_stream_state(
     last_persisted_thread_status: &mut ThreadStatusDto,
     last_persist_at: &mut Instant,
     last_blocks_persist_at: &mut Instant,
+    tuning: &StreamTuning,
     force: bool,
 ) {
     if !*blocks_dirty && !*message_state_dirty && !*thread_status_dirty && !*turn_model_dirty {
+        if force {
+            writes.flush().await;
+        }
         return;
     }
 
@@ -2967,9 +4040,9 @@ async fn flush_stream_state(
     }
 
     let should_flush_state =
-        force || now.duration_since(*last_persist_at) >= STREAM_DB_FLUSH_INTERVAL;
+        force || now.duration_since(*last_persist_at) >= tuning.db_flush_interval;
     let should_flush_blocks =
-        force || now.duration_since(*last_blocks_persist_at) >= STREAM_DB_BLOCKS_FLUSH_INTERVAL;
+        force || now.duration_since(*last_blocks_persist_at) >= tuning.db_blocks_flush_interval;
 
     if !should_flush_blocks && !should_flush_state {
         return;
@@ -2978,91 +4051,58 @@ async fn flush_stream_state(
     let mut did_flush_state = false;
     let mut did_flush_blocks = false;
 
+    // Queued writes are retried one by one if their batch fails; a blocks
+    // write that is still lost is superseded by the next snapshot.
     if *blocks_dirty && should_flush_blocks {
         match serde_json::to_string(blocks) {
             Ok(blocks_json) => {
-                if let Err(error) = run_db(state.db.clone(), {
-                    let assistant_message_id = assistant_message_id.to_string();
-                    let message_status = message_status.clone();
-                    let turn_model_id = turn_model_id.to_string();
-                    move |db| {
-                        db::messages::update_assistant_blocks_json(
-                            db,
-                            &assistant_message_id,
-                            &blocks_json,
-                            message_status,
-                            Some(turn_model_id.as_str()),
-                        )
-                    }
-                })
-                .await
-                {
-                    log::warn!("failed to persist assistant stream blocks: {error}");
-                } else {
-                    *blocks_dirty = false;
-                    *message_state_dirty = false;
-                    *turn_model_dirty = false;
-                    did_flush_blocks = true;
-                    did_flush_state = true;
-                }
+                writes.enqueue(TurnWrite::Blocks {
+                    message_id: assistant_message_id.to_string(),
+                    blocks_json,
+                    status: message_status.clone(),
+                    turn_model_id: Some(turn_model_id.to_string()),
+                });
+                *blocks_dirty = false;
+                *message_state_dirty = false;
+                *turn_model_dirty = false;
+                did_flush_blocks = true;
+                did_flush_state = true;
             }
             Err(error) => {
                 log::warn!("failed to serialize assistant stream blocks: {error}");
             }
         }
     } else if *message_state_dirty && should_flush_state {
-        if let Err(error) = run_db(state.db.clone(), {
-            let assistant_message_id = assistant_message_id.to_string();
-            let message_status = message_status.clone();
-            move |db| {
-                db::messages::update_assistant_status(db, &assistant_message_id, message_status)
-            }
-        })
-        .await
-        {
-            log::warn!("failed to persist assistant stream status: {error}");
-        } else {
-            *message_state_dirty = false;
-            did_flush_state = true;
-        }
+        writes.enqueue(TurnWrite::MessageStatus {
+            message_id: assistant_message_id.to_string(),
+            status: message_status.clone(),
+        });
+        *message_state_dirty = false;
+        did_flush_state = true;
     }
 
     if *turn_model_dirty && should_flush_state {
-        if let Err(error) = run_db(state.db.clone(), {
-            let assistant_message_id = assistant_message_id.to_string();
-            let turn_model_id = turn_model_id.to_string();
-            move |db| {
-                db::messages::update_assistant_turn_model_id(
-                    db,
-                    &assistant_message_id,
-                    &turn_model_id,
-                )
-            }
-        })
-        .await
-        {
-            log::warn!("failed to persist assistant turn model id during stream: {error}");
-        } else {
-            *turn_model_dirty = false;
-            did_flush_state = true;
-        }
+        writes.enqueue(TurnWrite::TurnModel {
+            message_id: assistant_message_id.to_string(),
+            turn_model_id: turn_model_id.to_string(),
+        });
+        *turn_model_dirty = false;
+        did_flush_state = true;
     }
 
     if *thread_status_dirty && should_flush_state && *last_persisted_thread_status != *thread_status
     {
-        if let Err(error) = run_db(state.db.clone(), {
-            let thread_id = thread.id.clone();
-            let thread_status = thread_status.clone();
-            move |db| db::threads::update_thread_status(db, &thread_id, thread_status)
-        })
-        .await
-        {
-            log::warn!("failed to persist thread status during stream: {error}");
-        } else {
-            *last_persisted_thread_status = thread_status.clone();
-            *thread_status_dirty = false;
-            did_flush_state = true;
-        }
+        writes.enqueue(TurnWrite::ThreadStatus {
+            thread_id: thread.id.clone(),
+            status: thread_status.clone(),
+        });
+        *last_persisted_thread_status = thread_status.clone();
+        *thread_status_dirty = false;
+        did_flush_state = true;
+    }
+
+    if force {
+        writes.flush().await;
     }
 
     if did_flush_blocks {
@@ -3312,7 +4352,7 @@ fn apply_event_to_blocks(
                 engine_action_id: engine_action_id.clone(),
                 action_type: action_type.as_str().to_string(),
                 summary: summary.to_string(),
-                details: value_to_raw(details),
+                details: action_block_details(action_type, details),
                 output_chunks: Vec::new(),
                 status: "running".to_string(),
                 result: None,
@@ -3488,7 +4528,7 @@ fn apply_event_to_blocks(
                 approval_id: approval_id.to_string(),
                 action_type: action_type.as_str().to_string(),
                 summary: summary.to_string(),
-                details: value_to_raw(details),
+                details: action_block_details(action_type, details),
                 status: "pending".to_string(),
                 decision: None,
             };
@@ -3559,6 +4599,37 @@ fn append_thinking_delta(blocks: &mut Vec<ContentBlock>, content: &str) -> bool
     true
 }
 
+/// MCP tool blocks carry `server` and `tool` as separate fields. Engines that
+/// only report the qualified `mcp__<server>__<tool>` name get it split here.
+fn action_block_details(action_type: &ActionType, details: &Value) -> Box<RawValue> {
+    if !matches!(action_type, ActionType::McpTool) {
+        return value_to_raw(details);
+    }
+    let Some(object) = details.as_object() else {
+        return value_to_raw(details);
+    };
+    if object.get("server").and_then(Value::as_str).is_some() {
+        return value_to_raw(details);
+    }
+    let Some((server, tool)) = ["tool", "name", "toolName"]
+        .iter()
+        .find_map(|key| object.get(*key).and_then(Value::as_str))
+        .and_then(split_qualified_mcp_tool_name)
+    else {
+        return value_to_raw(details);
+    };
+
+    let mut object = object.clone();
+    object.insert("server".to_string(), Value::String(server.to_string()));
+    object.insert("tool".to_string(), Value::String(tool.to_string()));
+    value_to_raw(&Value::Object(object))
+}
+
+fn split_qualified_mcp_tool_name(name: &str) -> Option<(&str, &str)> {
+    let (server, tool) = name.strip_prefix("mcp__")?.split_once("__")?;
+    (!server.is_empty() && !tool.is_empty()).then_some((server, tool))
+}
+
 fn update_action_progress(details: &mut Box<RawValue>, message: &str) -> bool {
     let mut value: Value = serde_json::from_str(details.get())
         .unwrap_or_else(|_| Value::Object(serde_json::Map::new()));
@@ -3625,58 +4696,309 @@ fn upsert_approval_block(
         }
     }
 
-    let index = blocks.len();
-    blocks.push(block);
-    approval_index.insert(approval_id.to_string(), index);
-    true
+    let index = blocks.len();
+    blocks.push(block);
+    approval_index.insert(approval_id.to_string(), index);
+    true
+}
+
+fn upsert_notice_block(
+    blocks: &mut Vec<ContentBlock>,
+    action_index: &mut HashMap<String, usize>,
+    approval_index: &mut HashMap<String, usize>,
+    kind: &str,
+    block: ContentBlock,
+) -> bool {
+    if let Some(index) = blocks.iter().position(|existing| {
+        matches!(
+            existing,
+            ContentBlock::Notice {
+                kind: existing_kind,
+                ..
+            } if existing_kind == kind
+        )
+    }) {
+        if let Some(existing) = blocks.get_mut(index) {
+            *existing = block;
+            return true;
+        }
+    }
+
+    blocks.insert(0, block);
+    rebuild_block_indexes(blocks, action_index, approval_index);
+    true
+}
+
+fn rebuild_block_indexes(
+    blocks: &[ContentBlock],
+    action_index: &mut HashMap<String, usize>,
+    approval_index: &mut HashMap<String, usize>,
+) {
+    action_index.clear();
+    approval_index.clear();
+
+    for (index, block) in blocks.iter().enumerate() {
+        match block {
+            ContentBlock::Action { action_id, .. } => {
+                action_index.insert(action_id.clone(), index);
+            }
+            ContentBlock::Approval { approval_id, .. } => {
+                approval_index.insert(approval_id.clone(), index);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Context usage saved with the thread, for threads that have not reported
+/// any since the app started. Cached in the turn manager once read.
+async fn persisted_context_usage(state: &AppState, thread_id: &str) -> Option<ThreadContextUsage> {
+    let snapshot = run_db(state.db.clone(), {
+        let thread_id = thread_id.to_string();
+        move |db| db::threads::get_usage_limits(db, &thread_id)
+    })
+    .await
+    .ok()??;
+    let usage = ThreadContextUsage {
+        current_tokens: snapshot.current_tokens?,
+        max_context_tokens: snapshot.max_context_tokens,
+    };
+    state.turns.record_context_usage(thread_id, usage).await;
+    Some(usage)
+}
+
+/// How full, in percent, the thread's engine session last reported its
+/// context window to be.
+async fn context_used_percent_for_thread(state: &AppState, thread: &ThreadDto) -> Option<u64> {
+    let usage = match state.turns.context_usage(&thread.id).await {
+        Some(usage) => usage,
+        None => persisted_context_usage(state, &thread.id).await?,
+    };
+    let budget = resolve_context_budget(
+        &thread.engine_id,
+        &state.config.context_budget,
+        Some(&usage),
+    )?;
+    Some(context_used_percent(&usage, &budget))
+}
+
+/// A switch to a fresh engine session, decided before the turn is validated
+/// and applied by [`apply_context_rollover`] once it is about to start.
+struct ContextRollover {
+    used_percent: u64,
+    seeded_messages: usize,
+}
+
+/// Decides whether the thread's engine session is too full to continue and,
+/// if so, puts the thread's title and recent messages ahead of `turn_input`.
+/// Nothing is stored yet, so a turn refused later leaves the thread as it was.
+async fn plan_context_rollover(
+    state: &AppState,
+    thread: &ThreadDto,
+    turn_input: &mut TurnInput,
+) -> Result<Option<ContextRollover>, String> {
+    if thread.engine_thread_id.is_none() || state.turns.is_active(&thread.id) {
+        return Ok(None);
+    }
+    let Some(used_percent) = context_used_percent_for_thread(state, thread).await else {
+        return Ok(None);
+    };
+    if !context_rollover_due(&state.config.context_budget, used_percent) {
+        return Ok(None);
+    }
+    let seed_messages = state.config.context_budget.rollover_seed_messages;
+    let history = run_db(state.db.clone(), {
+        let thread_id = thread.id.clone();
+        move |db| db::messages::recent_message_texts(db, &thread_id, seed_messages)
+    })
+    .await?;
+
+    let seed = rollover_seed_prompt(&thread.title, &history);
+    turn_input.message = format!("{seed}{}", turn_input.message);
+    turn_input
+        .input_items
+        .insert(0, TurnInputItem::Text { text: seed });
+    Ok(Some(ContextRollover {
+        used_percent,
+        seeded_messages: history.len(),
+    }))
+}
+
+/// Detaches the thread from its old engine session so the turn starts a
+/// fresh one. The switch is recorded in the thread's engine metadata as
+/// `rolloverFrom` and `rolloverAt`.
+async fn apply_context_rollover(
+    state: &AppState,
+    thread: &mut ThreadDto,
+    rollover: &ContextRollover,
+) -> Result<EngineEvent, String> {
+    let previous_engine_thread_id = thread.engine_thread_id.clone().unwrap_or_default();
+    let mut metadata = thread
+        .engine_metadata
+        .clone()
+        .filter(Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+    if let Some(object) = metadata.as_object_mut() {
+        object.insert(
+            "rolloverFrom".to_string(),
+            Value::String(previous_engine_thread_id),
+        );
+        object.insert(
+            "rolloverAt".to_string(),
+            Value::String(chrono::Utc::now().to_rfc3339()),
+        );
+    }
+    run_db(state.db.clone(), {
+        let thread_id = thread.id.clone();
+        let metadata = metadata.clone();
+        move |db| {
+            db::threads::clear_engine_thread_id(db, &thread_id)?;
+            db::threads::update_engine_metadata(db, &thread_id, &metadata)?;
+            db::threads::reset_context_usage(db, &thread_id)
+        }
+    })
+    .await?;
+    state.turns.forget_context_usage(&thread.id).await;
+    thread.engine_thread_id = None;
+    thread.engine_metadata = Some(metadata);
+
+    Ok(EngineEvent::Notice {
+        kind: "context_rollover".to_string(),
+        level: "info".to_string(),
+        title: "Started a fresh context".to_string(),
+        message: format!(
+            "The previous session had used about {}% of its context window, so this turn starts a new one seeded with the last {} message(s).",
+            rollover.used_percent, rollover.seeded_messages
+        ),
+    })
+}
+
+/// Warns, once a turn has finished, that the thread's next message will
+/// start a fresh engine session.
+async fn context_rollover_warning(state: &AppState, thread: &ThreadDto) -> Option<EngineEvent> {
+    if !state.config.context_budget.rollover_enabled {
+        return None;
+    }
+    let used_percent = context_used_percent_for_thread(state, thread).await?;
+    if !context_rollover_due(&state.config.context_budget, used_percent) {
+        return None;
+    }
+    Some(EngineEvent::Notice {
+        kind: "context_rollover_pending".to_string(),
+        level: "warning".to_string(),
+        title: "Context window almost full".to_string(),
+        message: format!(
+            "This thread has used about {used_percent}% of its context window. The next message starts a fresh session seeded with recent messages."
+        ),
+    })
 }
 
-fn upsert_notice_block(
-    blocks: &mut Vec<ContentBlock>,
-    action_index: &mut HashMap<String, usize>,
-    approval_index: &mut HashMap<String, usize>,
-    kind: &str,
-    block: ContentBlock,
-) -> bool {
-    if let Some(index) = blocks.iter().position(|existing| {
-        matches!(
-            existing,
-            ContentBlock::Notice {
-                kind: existing_kind,
-                ..
-            } if existing_kind == kind
-        )
-    }) {
-        if let Some(existing) = blocks.get_mut(index) {
-            *existing = block;
-            return true;
+/// Refuses a turn whose estimated size would overflow the engine's input
+/// budget. With `force` the turn goes ahead and the returned notice is shown
+/// on the assistant message instead. A turn that starts a fresh engine
+/// session is measured without the old session's usage.
+async fn check_turn_context_budget(
+    state: &AppState,
+    thread: &ThreadDto,
+    turn_input: &TurnInput,
+    fresh_session: bool,
+    force: bool,
+) -> Result<Option<EngineEvent>, SendMessageErrorDto> {
+    let usage = if fresh_session {
+        None
+    } else {
+        match state.turns.context_usage(&thread.id).await {
+            Some(usage) => Some(usage),
+            None => persisted_context_usage(state, &thread.id).await,
         }
-    }
+    };
+    let Some(budget) = resolve_context_budget(
+        &thread.engine_id,
+        &state.config.context_budget,
+        usage.as_ref(),
+    ) else {
+        return Ok(None);
+    };
+    let mut instructions_chars = thread_output_schema(thread.engine_metadata.as_ref())
+        .map(|schema| schema.to_string().len())
+        .unwrap_or_default();
+    if turn_input.plan_mode && thread.engine_id == "codex" {
+        instructions_chars += crate::engines::codex::PLAN_MODE_PROMPT_PREFIX.len();
+    }
+    instructions_chars += thread_custom_instructions(thread.engine_metadata.as_ref())
+        .map(|instructions| instructions.len())
+        .unwrap_or_default();
+    let input = turn_input.clone();
+    let estimate = tokio::task::spawn_blocking(move || {
+        estimate_turn_context(&input, instructions_chars, usage.as_ref(), |path| {
+            std::fs::metadata(path)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len())
+        })
+    })
+    .await
+    .map_err(err_to_string)?;
 
-    blocks.insert(0, block);
-    rebuild_block_indexes(blocks, action_index, approval_index);
-    true
+    match check_context_budget(&thread.engine_id, &estimate, &budget) {
+        Ok(()) => Ok(None),
+        Err(error) if force => Ok(Some(error.forced_notice())),
+        Err(error) => Err(error.into()),
+    }
 }
 
-fn rebuild_block_indexes(
-    blocks: &[ContentBlock],
-    action_index: &mut HashMap<String, usize>,
-    approval_index: &mut HashMap<String, usize>,
-) {
-    action_index.clear();
-    approval_index.clear();
+/// Notice for a turn whose engine, model or effort differs from the previous
+/// assistant turn. The first turn of a thread has nothing to compare against.
+/// Warns when the thread was handed to the engine's CLI and that terminal
+/// may still be running it.
+fn external_cli_session_notice(metadata: Option<&Value>) -> Option<EngineEvent> {
+    db::threads::external_cli_session_id(metadata)?;
+    Some(EngineEvent::Notice {
+        kind: "external_cli_session".to_string(),
+        level: "warning".to_string(),
+        title: "Thread open in the CLI".to_string(),
+        message: "This thread was continued in a terminal. If that CLI session is still running, both may write to the thread; close it or detach the thread first.".to_string(),
+    })
+}
 
-    for (index, block) in blocks.iter().enumerate() {
-        match block {
-            ContentBlock::Action { action_id, .. } => {
-                action_index.insert(action_id.clone(), index);
-            }
-            ContentBlock::Approval { approval_id, .. } => {
-                approval_index.insert(approval_id.clone(), index);
-            }
-            _ => {}
-        }
+fn model_change_notice(
+    previous: Option<&ThreadModelHistoryEntryDto>,
+    engine_id: &str,
+    model_id: &str,
+    reasoning_effort: Option<&str>,
+) -> Option<EngineEvent> {
+    let previous = previous?;
+    if previous.engine_id == engine_id
+        && previous.model_id == model_id
+        && previous.reasoning_effort.as_deref() == reasoning_effort
+    {
+        return None;
     }
+    let describe = |engine: &str, model: &str, effort: Option<&str>| {
+        let mut label = if engine == engine_id && engine == previous.engine_id {
+            model.to_string()
+        } else {
+            format!("{engine}/{model}")
+        };
+        if let Some(effort) = effort {
+            label.push_str(&format!(" ({effort} effort)"));
+        }
+        label
+    };
+    Some(EngineEvent::Notice {
+        kind: "model_changed".to_string(),
+        level: "info".to_string(),
+        title: "Model changed".to_string(),
+        message: format!(
+            "Switched from {} to {}.",
+            describe(
+                &previous.engine_id,
+                &previous.model_id,
+                previous.reasoning_effort.as_deref()
+            ),
+            describe(engine_id, model_id, reasoning_effort)
+        ),
+    })
 }
 
 fn format_model_reroute_notice(from_model: &str, to_model: &str, reason: &str) -> String {
@@ -3733,6 +5055,28 @@ fn trim_action_output_chunks(
     truncated
 }
 
+/// Opens the turn's JSONL event log when `debug.record_turn_event_logs` is
+/// set. Failing to open it only costs the log.
+fn start_turn_event_log(
+    state: &AppState,
+    thread_id: &str,
+    assistant_message_id: &str,
+) -> Option<TurnEventLog> {
+    if !state.config.debug.record_turn_event_logs {
+        return None;
+    }
+    match TurnEventLog::start(&turn_event_log_dir(), thread_id, assistant_message_id) {
+        Ok(event_log) => {
+            log::debug!("recording turn events to {}", event_log.path().display());
+            Some(event_log)
+        }
+        Err(error) => {
+            log::warn!("failed to open turn event log for message {assistant_message_id}: {error}");
+            None
+        }
+    }
+}
+
 fn engine_event_for_debug_log(event: &EngineEvent) -> EngineEvent {
     match event {
         EngineEvent::ActionOutputDelta {
@@ -4109,6 +5453,46 @@ fn thread_opencode_agent(metadata: Option<&Value>) -> Option<String> {
         .map(ToOwned::to_owned)
 }
 
+fn thread_executable_override(metadata: Option<&Value>) -> Option<PathBuf> {
+    metadata
+        .and_then(|value| value.get("engineExecutableOverride"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The checkout a repo thread runs in: the worktree picked with
+/// `set_thread_worktree`, or the repo itself.
+pub(crate) fn thread_checkout_path(
+    repo_path: &str,
+    metadata: Option<&Value>,
+) -> Result<String, String> {
+    let Some(worktree_path) = metadata
+        .and_then(|value| value.get(db::threads::WORKTREE_PATH_KEY))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(repo_path.to_string());
+    };
+    if !Path::new(worktree_path).is_dir() {
+        return Err(format!(
+            "worktree `{worktree_path}` for this thread no longer exists; pick another worktree or clear it"
+        ));
+    }
+    Ok(worktree_path.to_string())
+}
+
+fn thread_custom_instructions(metadata: Option<&Value>) -> Option<String> {
+    metadata
+        .and_then(|value| value.get("customInstructions"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToOwned::to_owned)
+}
+
 fn normalize_reasoning_effort_value(value: Option<&str>) -> Option<String> {
     value
         .map(str::trim)
@@ -4237,6 +5621,7 @@ mod tests {
         config::app_config::AppConfig,
         db,
         engines::EngineManager,
+        file_watcher::FileWatcherManager,
         git::{repo::FileTreeCache, watcher::GitWatcherManager},
         models::{EngineCapabilitiesDto, ReasoningEffortOptionDto},
         power::KeepAwakeManager,
@@ -4250,14 +5635,16 @@ mod tests {
     fn test_app_state() -> AppState {
         let root = std::env::temp_dir().join(format!("panes-chat-cmd-{}", Uuid::new_v4()));
         fs::create_dir_all(&root).expect("failed to create temp root");
-        let db = crate::db::Database::open(root.join("workspaces.db"))
-            .expect("failed to create test database");
+        let db =
+            crate::db::Database::open(root.join("workspaces.db"), crate::db::PoolLimits::default())
+                .expect("failed to create test database");
         AppState {
             db,
             config: Arc::new(AppConfig::default()),
             config_write_lock: Arc::new(tokio::sync::Mutex::new(())),
-            engines: Arc::new(EngineManager::new()),
+            engines: Arc::new(EngineManager::default()),
             git_watchers: Arc::new(GitWatcherManager::default()),
+            file_watchers: Arc::new(FileWatcherManager::default()),
             terminals: Arc::new(TerminalManager::default()),
             notifications: Arc::new(TerminalNotificationManager::default()),
             keep_awake: Arc::new(KeepAwakeManager::new()),
@@ -4314,6 +5701,7 @@ mod tests {
                 permission_modes: Vec::new(),
                 sandbox_modes: Vec::new(),
                 approval_decisions: Vec::new(),
+                ..EngineCapabilitiesDto::default()
             },
         }]
     }
@@ -4327,6 +5715,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn attachments_are_sized_from_disk_and_checked_against_the_caps() {
+        let dir = std::env::temp_dir().join(format!("panes-attachments-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let payload = |file_name: &str, bytes: Option<&[u8]>| {
+            let path = dir.join(file_name);
+            if let Some(bytes) = bytes {
+                std::fs::write(&path, bytes).unwrap();
+            }
+            ChatAttachmentPayload {
+                file_name: file_name.to_string(),
+                file_path: path.to_string_lossy().into_owned(),
+                size_bytes: 1,
+                mime_type: Some("image/png".to_string()),
+            }
+        };
+        let limits = AttachmentLimits {
+            max_file_bytes: 8,
+            max_turn_bytes: 12,
+        };
+
+        let attachments = stat_attachments(
+            vec![
+                payload("a.png", Some(b"123456")),
+                payload("b.png", Some(b"abcdef")),
+            ],
+            limits,
+        )
+        .unwrap();
+        assert_eq!(
+            attachments
+                .iter()
+                .map(|attachment| attachment.size_bytes)
+                .collect::<Vec<_>>(),
+            [6, 6]
+        );
+
+        let missing = stat_attachments(vec![payload("gone.png", None)], limits).unwrap_err();
+        assert!(
+            missing.starts_with("Attachment `gone.png` could not be read"),
+            "{missing}"
+        );
+        let too_big =
+            stat_attachments(vec![payload("big.png", Some(b"123456789"))], limits).unwrap_err();
+        assert!(too_big.starts_with("Attachment `big.png` is"), "{too_big}");
+        let over_turn = stat_attachments(
+            vec![
+                payload("a.png", Some(b"123456")),
+                payload("b.png", Some(b"abcdef")),
+                payload("c.png", Some(b"x")),
+            ],
+            limits,
+        )
+        .unwrap_err();
+        assert!(
+            over_turn.starts_with("Attachments add up to more than"),
+            "{over_turn}"
+        );
+        assert_eq!(format_megabytes(25 * 1024 * 1024), "25 MB");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
     #[test]
     fn validates_attachments_against_model_attachment_modalities() {
         let catalog = attachment_validation_catalog(vec!["text", "image"]);
@@ -4376,6 +5826,104 @@ mod tests {
         assert!(error.contains("Image attachments are not supported"));
     }
 
+    #[tokio::test]
+    async fn turn_diagnostics_persist_only_for_failed_turns() {
+        use crate::models::TurnDiagnosticSourceDto;
+
+        let state = test_app_state();
+        let thread = test_thread(&state, "codex", "gpt-5.3-codex");
+        let sink = state.engines.turn_diagnostics();
+        let mut message_ids = Vec::new();
+
+        for status in [MessageStatusDto::Completed, MessageStatusDto::Error] {
+            let message = db::messages::insert_assistant_placeholder(
+                &state.db,
+                &thread.id,
+                Some("codex"),
+                Some("gpt-5.3-codex"),
+                None,
+            )
+            .expect("failed to create assistant message");
+            sink.begin("engine-thread");
+            sink.record(
+                "engine-thread",
+                TurnDiagnosticSourceDto::UnmappedNotification,
+                "item/unknownThing params_keys=None",
+            );
+            sink.record_at(
+                "engine-thread",
+                TurnDiagnosticSourceDto::Stderr,
+                "2026-01-01T00:00:00.000Z".to_string(),
+                "rate limited",
+            );
+            persist_turn_diagnostics(&state, &thread.id, "engine-thread", &message.id, &status)
+                .await;
+            message_ids.push(message.id);
+        }
+
+        let completed = db::actions::get_turn_diagnostics(&state.db, &message_ids[0])
+            .expect("failed to read diagnostics");
+        assert!(completed.is_empty());
+
+        let failed = db::actions::get_turn_diagnostics(&state.db, &message_ids[1])
+            .expect("failed to read diagnostics");
+        assert_eq!(failed.len(), 2);
+        assert_eq!(
+            failed[0].source,
+            TurnDiagnosticSourceDto::UnmappedNotification
+        );
+        assert_eq!(failed[1].source, TurnDiagnosticSourceDto::Stderr);
+        assert_eq!(failed[1].at, "2026-01-01T00:00:00.000Z");
+        assert_eq!(failed[1].message, "rate limited");
+    }
+
+    #[test]
+    fn model_change_notice_fires_only_when_the_turn_config_differs() {
+        let state = test_app_state();
+        let thread = test_thread(&state, "codex", "gpt-5.3-codex");
+        assert!(
+            db::messages::latest_assistant_model_entry(&state.db, &thread.id)
+                .expect("failed to read history")
+                .is_none()
+        );
+        assert!(model_change_notice(None, "codex", "gpt-5.3-codex", Some("high")).is_none());
+
+        db::messages::insert_assistant_placeholder(
+            &state.db,
+            &thread.id,
+            Some("codex"),
+            Some("gpt-5.3-codex"),
+            Some("high"),
+        )
+        .expect("failed to create assistant message");
+        let previous = db::messages::latest_assistant_model_entry(&state.db, &thread.id)
+            .expect("failed to read history");
+
+        assert!(
+            model_change_notice(previous.as_ref(), "codex", "gpt-5.3-codex", Some("high"))
+                .is_none()
+        );
+        match model_change_notice(previous.as_ref(), "codex", "gpt-5.4", None) {
+            Some(EngineEvent::Notice {
+                kind,
+                level,
+                message,
+                ..
+            }) => {
+                assert_eq!(kind, "model_changed");
+                assert_eq!(level, "info");
+                assert_eq!(
+                    message,
+                    "Switched from gpt-5.3-codex (high effort) to gpt-5.4."
+                );
+            }
+            other => panic!("expected a model change notice, got {other:?}"),
+        }
+        assert!(
+            model_change_notice(previous.as_ref(), "codex", "gpt-5.3-codex", Some("low")).is_some()
+        );
+    }
+
     fn insert_pending_approval_with_details(
         state: &AppState,
         thread: &ThreadDto,
@@ -4390,8 +5938,8 @@ mod tests {
             None,
         )
         .expect("failed to create assistant message");
-        db::actions::insert_approval(
-            &state.db,
+        db::actions::insert_approval_on(
+            &state.db.connect().unwrap(),
             approval_id,
             &thread.id,
             &assistant_message.id,
@@ -4859,6 +6407,57 @@ mod tests {
         assert_eq!(chunks[0].content, "6789");
     }
 
+    #[test]
+    fn mcp_tool_blocks_split_qualified_tool_names() {
+        let mut blocks = Vec::new();
+        let mut action_index = HashMap::new();
+        let mut approval_index = HashMap::new();
+
+        for (action_id, details) in [
+            (
+                "action-1",
+                serde_json::json!({ "name": "mcp__github__create_issue" }),
+            ),
+            (
+                "action-2",
+                serde_json::json!({ "server": "docs", "tool": "search" }),
+            ),
+        ] {
+            apply_event_to_blocks(
+                &mut blocks,
+                &mut action_index,
+                &mut approval_index,
+                &EngineEvent::ActionStarted {
+                    action_id: action_id.to_string(),
+                    engine_action_id: None,
+                    action_type: ActionType::McpTool,
+                    summary: "tool".to_string(),
+                    details,
+                },
+                1000,
+            );
+        }
+
+        let details: Vec<Value> = blocks
+            .iter()
+            .map(|block| match block {
+                ContentBlock::Action {
+                    action_type,
+                    details,
+                    ..
+                } => {
+                    assert_eq!(action_type, "mcp_tool");
+                    serde_json::from_str(details.get()).expect("details should parse")
+                }
+                other => panic!("expected action block, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(details[0]["server"], "github");
+        assert_eq!(details[0]["tool"], "create_issue");
+        assert_eq!(details[1]["server"], "docs");
+        assert_eq!(details[1]["tool"], "search");
+    }
+
     #[test]
     fn model_reroute_notice_reindexes_action_blocks() {
         let mut blocks = Vec::new();
@@ -4872,7 +6471,7 @@ mod tests {
             &EngineEvent::ActionStarted {
                 action_id: "action-1".to_string(),
                 engine_action_id: Some("item-1".to_string()),
-                action_type: crate::engines::events::ActionType::Other,
+                action_type: ActionType::Other,
                 summary: "search_docs".to_string(),
                 details: serde_json::json!({}),
             },
@@ -5264,6 +6863,7 @@ mod tests {
                 permission_modes: Vec::new(),
                 sandbox_modes: Vec::new(),
                 approval_decisions: Vec::new(),
+                ..EngineCapabilitiesDto::default()
             },
         }];
 
@@ -5312,6 +6912,7 @@ mod tests {
                 permission_modes: Vec::new(),
                 sandbox_modes: Vec::new(),
                 approval_decisions: Vec::new(),
+                ..EngineCapabilitiesDto::default()
             },
         }];
 
@@ -5365,6 +6966,198 @@ mod tests {
             "gpt-5.1-codex-mini"
         );
     }
+
+    #[test]
+    fn only_a_cancelled_streaming_turn_is_settled_as_interrupted() {
+        let mut message_status = MessageStatusDto::Streaming;
+        let mut thread_status = ThreadStatusDto::Streaming;
+        assert!(!settle_cancelled_turn(
+            false,
+            &mut message_status,
+            &mut thread_status
+        ));
+        assert!(settle_cancelled_turn(
+            true,
+            &mut message_status,
+            &mut thread_status
+        ));
+        assert_eq!(message_status, MessageStatusDto::Interrupted);
+        assert_eq!(thread_status, ThreadStatusDto::Idle);
+
+        let mut message_status = MessageStatusDto::Completed;
+        assert!(!settle_cancelled_turn(
+            true,
+            &mut message_status,
+            &mut thread_status
+        ));
+        assert_eq!(message_status, MessageStatusDto::Completed);
+    }
+
+    #[tokio::test]
+    async fn interrupting_a_streaming_turn_waits_for_it_to_settle_as_interrupted() {
+        let state = test_app_state();
+        let thread = test_thread(&state, "codex", "gpt-5.5-codex");
+        assert!(!interrupt_running_turn(&state, &thread).await);
+
+        let token = CancellationToken::new();
+        assert!(state.turns.try_register(&thread.id, token.clone()).await);
+        let turns = state.turns.clone();
+        let thread_id = thread.id.clone();
+        let turn = tokio::spawn(async move {
+            let mut message_status = MessageStatusDto::Streaming;
+            let mut thread_status = ThreadStatusDto::Streaming;
+            token.cancelled().await;
+            settle_cancelled_turn(
+                token.is_cancelled(),
+                &mut message_status,
+                &mut thread_status,
+            );
+            turns.finish(&thread_id).await;
+            (message_status, thread_status)
+        });
+
+        assert!(interrupt_running_turn(&state, &thread).await);
+        assert!(!state.turns.is_active(&thread.id));
+        let (message_status, thread_status) = turn.await.expect("turn task");
+        assert_eq!(message_status, MessageStatusDto::Interrupted);
+        assert_eq!(thread_status, ThreadStatusDto::Idle);
+    }
+
+    #[test]
+    fn stream_tuning_keeps_block_snapshots_at_or_above_the_state_interval() {
+        let mut config = AppConfig::default();
+        let tuning = StreamTuning::from_config(&config);
+        assert_eq!(tuning.coalesce_max_chars, 8_192);
+        assert_eq!(tuning.db_flush_interval, Duration::from_millis(250));
+        assert_eq!(
+            tuning.db_blocks_flush_interval,
+            STREAM_DB_BLOCKS_FLUSH_INTERVAL
+        );
+
+        config.debug.stream_coalesce_max_chars = 1;
+        config.debug.stream_db_flush_interval_ms = 5_000;
+        let tuning = StreamTuning::from_config(&config);
+        assert_eq!(tuning.coalesce_max_chars, 64);
+        assert_eq!(tuning.db_blocks_flush_interval, Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn tiny_text_deltas_are_persisted_at_the_flush_interval() {
+        let state = test_app_state();
+        let thread = test_thread(&state, "codex", "gpt-5.3-codex");
+        let message = db::messages::insert_assistant_placeholder(
+            &state.db,
+            &thread.id,
+            Some("codex"),
+            Some("gpt-5.3-codex"),
+            None,
+        )
+        .expect("failed to create assistant message");
+        let tuning = StreamTuning {
+            coalesce_max_chars: 64,
+            db_flush_interval: Duration::from_millis(20),
+            db_blocks_flush_interval: Duration::from_millis(20),
+        };
+        let writes = TurnWriteBatcher::start(&state.db);
+        let mut blocks = Vec::new();
+        let mut blocks_dirty = false;
+        let mut message_state_dirty = false;
+        let mut thread_status_dirty = false;
+        let mut turn_model_dirty = false;
+        let mut last_persisted_thread_status = ThreadStatusDto::Streaming;
+        let started_at = Instant::now();
+        let mut last_persist_at = started_at;
+        let mut last_blocks_persist_at = started_at;
+        let deltas = 200;
+
+        for index in 0..deltas {
+            blocks_dirty |= append_text_delta(&mut blocks, &index.to_string());
+            flush_stream_state(
+                &writes,
+                &thread,
+                &message.id,
+                &blocks,
+                &MessageStatusDto::Streaming,
+                &ThreadStatusDto::Streaming,
+                "gpt-5.3-codex",
+                &mut blocks_dirty,
+                &mut message_state_dirty,
+                &mut thread_status_dirty,
+                &mut turn_model_dirty,
+                &mut last_persisted_thread_status,
+                &mut last_persist_at,
+                &mut last_blocks_persist_at,
+                &tuning,
+                false,
+            )
+            .await;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        flush_stream_state(
+            &writes,
+            &thread,
+            &message.id,
+            &blocks,
+            &MessageStatusDto::Streaming,
+            &ThreadStatusDto::Streaming,
+            "gpt-5.3-codex",
+            &mut blocks_dirty,
+            &mut message_state_dirty,
+            &mut thread_status_dirty,
+            &mut turn_model_dirty,
+            &mut last_persisted_thread_status,
+            &mut last_persist_at,
+            &mut last_blocks_persist_at,
+            &tuning,
+            true,
+        )
+        .await;
+        let elapsed = started_at.elapsed();
+        let stats = writes.finish().await;
+
+        let interval_budget =
+            (elapsed.as_millis() / tuning.db_blocks_flush_interval.as_millis()) as usize + 1;
+        assert!(
+            stats.writes >= 2,
+            "expected periodic flushes, got {}",
+            stats.writes
+        );
+        assert!(
+            stats.writes <= interval_budget,
+            "{} writes in {elapsed:?} exceeds one per {:?}",
+            stats.writes,
+            tuning.db_blocks_flush_interval
+        );
+        assert!(stats.writes < deltas);
+
+        let blocks_json: String = state
+            .db
+            .connect()
+            .expect("connect")
+            .query_row(
+                "SELECT blocks_json FROM messages WHERE id = ?1",
+                params![message.id],
+                |row| row.get(0),
+            )
+            .expect("failed to load message blocks");
+        let expected: String = (0..deltas).map(|index| index.to_string()).collect();
+        assert!(blocks_json.contains(&expected));
+    }
+
+    #[test]
+    fn multi_send_rejects_duplicate_busy_or_too_many_threads() {
+        let ids = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let idle = |_: &str| false;
+
+        assert!(validate_multi_send_threads(&ids(&["a", "b"]), idle).is_ok());
+        assert!(validate_multi_send_threads(&[], idle).is_err());
+        assert!(validate_multi_send_threads(&ids(&["a", "b", "a"]), idle)
+            .unwrap_err()
+            .contains("more than once"));
+        assert!(validate_multi_send_threads(&ids(&["a", "b", "c", "d", "e", "f"]), idle).is_err());
+        let error = validate_multi_send_threads(&ids(&["a", "b"]), |id| id == "b").unwrap_err();
+        assert!(error.contains("thread b"));
+    }
 }
 
 fn resolve_turn_model_id(
@@ -107,9 +107,20 @@ pub async fn run_engine_check(
         return Err("command is not allowed for this engine check".to_string());
     }
 
-    execute_engine_check_command(&command)
+    let result = execute_engine_check_command(&command)
         .await
-        .map_err(err_to_string)
+        .map_err(err_to_string)?;
+
+    // A check may be a fix that changed the install; the cached catalog
+    // could be stale.
+    if engine_id == "codex" && result.success {
+        let engines = state.engines.clone();
+        tokio::spawn(async move {
+            engines.refresh_codex_models().await;
+        });
+    }
+
+    Ok(result)
 }
 
 async fn execute_engine_check_command(command: &str) -> anyhow::Result<EngineCheckResultDto> {
@@ -3,14 +3,21 @@ use std::{
     fs,
     path::{Path, PathBuf},
     process::Command,
+    sync::Arc,
 };
 
 use anyhow::Context;
-use tauri::State;
+use serde::Serialize;
+use tauri::{Emitter, State};
 
 use crate::{
-    db, fs_ops,
-    models::{FileTreeEntryDto, ReadFileResultDto, ResolvedEditorFileReferenceDto, TrustLevelDto},
+    db,
+    file_watcher::{FileChange, FILE_CHANGE_DEBOUNCE},
+    fs_ops,
+    models::{
+        FileTreeEntryDto, FileWriteErrorDto, ReadFileResultDto, ResolvedEditorFileReferenceDto,
+        TrustLevelDto,
+    },
     path_utils,
     state::AppState,
 };
@@ -19,18 +26,32 @@ use crate::{
 pub async fn list_dir(
     repo_path: String,
     dir_path: String,
+    show_ignored: Option<bool>,
 ) -> Result<Vec<FileTreeEntryDto>, String> {
     tokio::task::spawn_blocking(move || {
-        fs_ops::list_dir(&repo_path, &dir_path).map_err(err_to_string)
+        fs_ops::list_dir(&repo_path, &dir_path, show_ignored.unwrap_or(false))
+            .map_err(err_to_string)
     })
     .await
     .map_err(|error| error.to_string())?
 }
 
 #[tauri::command]
-pub async fn read_file(repo_path: String, file_path: String) -> Result<ReadFileResultDto, String> {
+pub async fn read_file(
+    state: State<'_, AppState>,
+    repo_path: String,
+    file_path: String,
+    force: Option<bool>,
+) -> Result<ReadFileResultDto, String> {
+    let preview_threshold = state.config.editor_preview_threshold_bytes();
     tokio::task::spawn_blocking(move || {
-        fs_ops::read_file(&repo_path, &file_path).map_err(err_to_string)
+        fs_ops::read_file(
+            &repo_path,
+            &file_path,
+            preview_threshold,
+            force.unwrap_or(false),
+        )
+        .map_err(err_to_string)
     })
     .await
     .map_err(|error| error.to_string())?
@@ -70,15 +91,14 @@ pub async fn write_file(
     file_path: String,
     content: String,
     workspace_id: Option<String>,
-) -> Result<(), String> {
+    create_backup: Option<bool>,
+) -> Result<(), FileWriteErrorDto> {
     let db = state.db.clone();
     let cache = state.file_tree_cache.clone();
-    tokio::task::spawn_blocking(move || {
-        let access_root = PathBuf::from(&repo_path)
-            .canonicalize()
-            .map_err(err_to_string)?;
-        let target_for_repo_lookup =
-            resolve_target_path_for_repo_lookup(&access_root, &file_path).map_err(err_to_string)?;
+    let requested_path = file_path.clone();
+    let written = tokio::task::spawn_blocking(move || -> anyhow::Result<PathBuf> {
+        let access_root = PathBuf::from(&repo_path).canonicalize()?;
+        let target_for_repo_lookup = resolve_target_path_for_repo_lookup(&access_root, &file_path)?;
 
         // Trust level check for user-initiated writes from the editor:
         // - Restricted: blocked — explicit opt-in required (must change trust level first)
@@ -88,22 +108,63 @@ pub async fn write_file(
             &db,
             target_for_repo_lookup.to_string_lossy().as_ref(),
             workspace_id.as_deref(),
-        )
-        .map_err(err_to_string)?
-        {
+        )? {
             if matches!(repo.trust_level, TrustLevelDto::Restricted) {
-                return Err(
+                anyhow::bail!(
                     "cannot write to a restricted repository; change the trust level first"
-                        .to_string(),
                 );
             }
         }
-        fs_ops::write_file(&repo_path, &file_path, &content).map_err(err_to_string)?;
+        fs_ops::write_file(
+            &repo_path,
+            &file_path,
+            &content,
+            create_backup.unwrap_or(false),
+        )?;
         cache.invalidate_containing_path(target_for_repo_lookup.to_string_lossy().as_ref());
-        Ok(())
+        Ok(target_for_repo_lookup)
     })
     .await
-    .map_err(|error| error.to_string())?
+    .map_err(|error| file_write_error(error.into(), &requested_path))?
+    .map_err(|error| file_write_error(error, &requested_path))?;
+    // The editor already has this content; don't report it back as an
+    // external change.
+    state.file_watchers.suppress_own_write(&written).await;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileChangedEvent {
+    path: String,
+    mtime_ms: Option<i64>,
+}
+
+/// Watches an absolute file path open in the editor, forwarding changes made
+/// outside the editor as `file-changed`.
+#[tauri::command]
+pub async fn watch_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<(), String> {
+    let callback = Arc::new(move |change: FileChange| {
+        let payload = FileChangedEvent {
+            path: change.path,
+            mtime_ms: change.mtime_ms,
+        };
+        let _ = app.emit("file-changed", payload);
+    });
+    state
+        .file_watchers
+        .watch_file(path, FILE_CHANGE_DEBOUNCE, callback)
+        .await
+        .map_err(err_to_string)
+}
+
+#[tauri::command]
+pub async fn unwatch_file(state: State<'_, AppState>, path: String) -> Result<bool, String> {
+    Ok(state.file_watchers.unwatch_file(&path).await)
 }
 
 #[tauri::command]
@@ -670,14 +731,31 @@ fn err_to_string(error: impl std::fmt::Display) -> String {
     error.to_string()
 }
 
+fn file_write_error(error: anyhow::Error, file_path: &str) -> FileWriteErrorDto {
+    let message = err_to_string(&error);
+    match error.downcast::<fs_ops::FileWriteError>() {
+        Ok(fs_ops::FileWriteError::ParentMissing { path }) => {
+            FileWriteErrorDto::ParentMissing { path, message }
+        }
+        Ok(fs_ops::FileWriteError::PermissionDenied { path }) => {
+            FileWriteErrorDto::PermissionDenied { path, message }
+        }
+        Err(_) => FileWriteErrorDto::Other {
+            path: file_path.to_string(),
+            message,
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, path::PathBuf};
 
     use super::{
-        build_open_command_plan, build_reveal_command_plan, resolve_target_path_for_repo_lookup,
-        RevealPlatform,
+        build_open_command_plan, build_reveal_command_plan, file_write_error,
+        resolve_target_path_for_repo_lookup, RevealPlatform,
     };
+    use crate::fs_ops;
     use uuid::Uuid;
 
     #[cfg(unix)]
@@ -694,6 +772,29 @@ mod tests {
         result
     }
 
+    #[test]
+    fn write_errors_are_tagged_for_the_editor() {
+        with_temp_path(|dir, _file| {
+            let root = dir.to_string_lossy().to_string();
+            let missing = fs_ops::write_file(&root, "new/dir/file.txt", "hello", false)
+                .expect_err("missing parent should fail");
+            assert_eq!(
+                serde_json::to_value(file_write_error(missing, "new/dir/file.txt")).unwrap(),
+                serde_json::json!({
+                    "kind": "parent_missing",
+                    "path": "new/dir",
+                    "message": "parent directory not found: new/dir",
+                })
+            );
+
+            let other = file_write_error(anyhow::anyhow!("disk full"), "file.txt");
+            assert_eq!(
+                serde_json::to_value(other).unwrap(),
+                serde_json::json!({ "kind": "other", "path": "file.txt", "message": "disk full" })
+            );
+        });
+    }
+
     #[test]
     fn windows_files_use_explorer_select_args() {
         with_temp_path(|_dir, file| {
@@ -3,15 +3,55 @@ use tauri::Emitter;
 use tauri::State;
 
 use crate::{
-    git::{repo, worktree},
+    db::{self, git_operations::GitOperationRecord},
+    engines::EphemeralPromptError,
+    git::{
+        commit_message, overview,
+        preflight::GitPreflightCache,
+        repo::{self, ForcePushMode, StaleHunkError},
+        safety::{
+            DirtyWorktreeError, ForcePushLeaseError, ProtectedBranchError, PublishedHistoryError,
+            PushedCommitError, UnmergedBranchError,
+        },
+        watcher::GitRepoChange,
+        worktree,
+    },
+    metrics,
     models::{
-        FileTreeEntryDto, FileTreePageDto, GitBranchPageDto, GitBranchScopeDto, GitCommitPageDto,
-        GitCompareSourceDto, GitDiffPreviewDto, GitFileCompareDto, GitInitRepoStatusDto,
-        GitRemoteDto, GitStashDto, GitStatusDto, GitWorktreeDto,
+        FileTreeEntryDto, FileTreePageDto, GitBranchPageDto, GitBranchScopeDto,
+        GitCommitMessageDto, GitCommitMessageErrorDto, GitCommitPageDto, GitCommitResultDto,
+        GitCompareSourceDto, GitConflictDto, GitConflictResolutionDto, GitDiffPreviewDto,
+        GitFileCompareDto, GitHunkErrorDto, GitInitRepoStatusDto, GitMultiRepoResultDto,
+        GitOperationLogDto, GitPreflightDto, GitRemoteDto, GitRepoFilesDto, GitStashApplyResultDto,
+        GitStashDto, GitStatusDto, GitWorktreeDto, WorkspaceGitOverviewDto,
     },
     state::AppState,
 };
 
+async fn run_db<T, F>(db: crate::db::Database, operation: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&crate::db::Database) -> anyhow::Result<T> + Send + 'static,
+{
+    let db = db.acquire_scope().await.map_err(err_to_string)?;
+    tokio::task::spawn_blocking(move || operation(&db))
+        .await
+        .map_err(|error| error.to_string())?
+        .map_err(err_to_string)
+}
+
+#[tauri::command]
+pub async fn git_preflight(
+    _state: State<'_, AppState>,
+    repo_path: String,
+    refresh: Option<bool>,
+) -> Result<GitPreflightDto, String> {
+    let refresh = refresh.unwrap_or(false);
+    tokio::task::spawn_blocking(move || GitPreflightCache::global().report(&repo_path, refresh))
+        .await
+        .map_err(|error| error.to_string())
+}
+
 #[tauri::command]
 pub async fn get_git_status(
     _state: State<'_, AppState>,
@@ -22,20 +62,54 @@ pub async fn get_git_status(
         .map_err(|error| error.to_string())?
 }
 
+/// With `conflict_markers`, a conflicted file comes back as its three-way
+/// merge with diff3-style markers instead of a diff.
 #[tauri::command]
 pub async fn get_file_diff(
     _state: State<'_, AppState>,
     repo_path: String,
     file_path: String,
     staged: bool,
+    conflict_markers: Option<bool>,
 ) -> Result<GitDiffPreviewDto, String> {
     tokio::task::spawn_blocking(move || {
+        if conflict_markers.unwrap_or(false) {
+            if let Some(preview) =
+                repo::get_conflict_merge_view(&repo_path, &file_path).map_err(err_to_string)?
+            {
+                return Ok(preview);
+            }
+        }
         repo::get_file_diff(&repo_path, &file_path, staged).map_err(err_to_string)
     })
     .await
     .map_err(|error| error.to_string())?
 }
 
+#[tauri::command]
+pub async fn list_conflicts(
+    _state: State<'_, AppState>,
+    repo_path: String,
+) -> Result<Vec<GitConflictDto>, String> {
+    tokio::task::spawn_blocking(move || repo::list_conflicts(&repo_path).map_err(err_to_string))
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+#[tauri::command]
+pub async fn resolve_conflict(
+    _state: State<'_, AppState>,
+    repo_path: String,
+    file_path: String,
+    resolution: GitConflictResolutionDto,
+) -> Result<GitStatusDto, String> {
+    tokio::task::spawn_blocking(move || {
+        repo::resolve_conflict(&repo_path, &file_path, resolution).map_err(err_to_string)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
 #[tauri::command]
 pub async fn get_git_file_compare(
     _state: State<'_, AppState>,
@@ -77,6 +151,72 @@ pub async fn unstage_files(
     .map_err(|error| error.to_string())?
 }
 
+/// `hunk_header` is the hunk's `@@ -a,b +c,d @@` line from `get_file_diff`.
+#[tauri::command]
+pub async fn stage_hunk(
+    _state: State<'_, AppState>,
+    repo_path: String,
+    file_path: String,
+    hunk_header: String,
+) -> Result<GitStatusDto, String> {
+    tokio::task::spawn_blocking(move || {
+        repo::stage_hunk(&repo_path, &file_path, &hunk_header).map_err(err_to_string)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+#[tauri::command]
+pub async fn unstage_hunk(
+    _state: State<'_, AppState>,
+    repo_path: String,
+    file_path: String,
+    hunk_header: String,
+) -> Result<GitStatusDto, String> {
+    tokio::task::spawn_blocking(move || {
+        repo::unstage_hunk(&repo_path, &file_path, &hunk_header).map_err(err_to_string)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+/// `hunk_ids` are ids from the `hunks` of `get_file_diff`'s unstaged diff.
+#[tauri::command]
+pub async fn stage_hunks(
+    _state: State<'_, AppState>,
+    repo_path: String,
+    file_path: String,
+    hunk_ids: Vec<String>,
+) -> Result<GitStatusDto, GitHunkErrorDto> {
+    tokio::task::spawn_blocking(move || {
+        repo::stage_hunks(&repo_path, &file_path, &hunk_ids).map_err(hunk_error)
+    })
+    .await
+    .map_err(|error| hunk_error(error.into()))?
+}
+
+#[tauri::command]
+pub async fn unstage_hunks(
+    _state: State<'_, AppState>,
+    repo_path: String,
+    file_path: String,
+    hunk_ids: Vec<String>,
+) -> Result<GitStatusDto, GitHunkErrorDto> {
+    tokio::task::spawn_blocking(move || {
+        repo::unstage_hunks(&repo_path, &file_path, &hunk_ids).map_err(hunk_error)
+    })
+    .await
+    .map_err(|error| hunk_error(error.into()))?
+}
+
+fn hunk_error(error: anyhow::Error) -> GitHunkErrorDto {
+    let message = err_to_string(&error);
+    match error.downcast::<StaleHunkError>() {
+        Ok(StaleHunkError { path }) => GitHunkErrorDto::StaleDiff { path, message },
+        Err(_) => GitHunkErrorDto::Other { message },
+    }
+}
+
 #[tauri::command]
 pub async fn discard_files(
     _state: State<'_, AppState>,
@@ -92,13 +232,194 @@ pub async fn discard_files(
 
 #[tauri::command]
 pub async fn commit(
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
     repo_path: String,
     message: String,
-) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || repo::commit(&repo_path, &message).map_err(err_to_string))
+    sign: Option<bool>,
+    amend: Option<bool>,
+    signoff: Option<bool>,
+    force: Option<bool>,
+    thread_id: Option<String>,
+) -> Result<GitCommitResultDto, String> {
+    let options = repo::CommitOptions {
+        sign,
+        amend: amend.unwrap_or(false),
+        signoff: signoff.unwrap_or(false),
+        force: force.unwrap_or(false),
+    };
+    let db = state.db.clone();
+    let result = tokio::task::spawn_blocking({
+        let repo_path = repo_path.clone();
+        move || {
+            let result = repo::commit(&repo_path, &message, options);
+            if result.is_ok() && !options.amend {
+                metrics::incr(&db, metrics::COMMITS_MADE, 1);
+            }
+            result
+        }
+    })
+    .await
+    .map_err(|error| error.to_string())?;
+
+    if options.amend {
+        let (outcome, detail) = match &result {
+            Ok(commit) => ("completed", Some(format!("new HEAD {}", commit.hash))),
+            Err(error) => (safety_outcome(error), Some(error.to_string())),
+        };
+        record_git_operation(
+            &state,
+            GitOperationRecord {
+                repo_path: repo_path.clone(),
+                thread_id,
+                operation: "amend_commit",
+                target: format!("{} (HEAD)", current_branch_label(&repo_path)),
+                force: options.force,
+                force_with_lease: false,
+                outcome,
+                detail,
+            },
+        )
+        .await;
+    }
+
+    result.map_err(err_to_string)
+}
+
+/// Drafts a commit message for the staged changes with the given engine.
+/// Nothing is written to git or to the thread database.
+#[tauri::command]
+pub async fn generate_commit_message(
+    state: State<'_, AppState>,
+    repo_path: String,
+    engine_id: String,
+    model_id: String,
+) -> Result<GitCommitMessageDto, GitCommitMessageErrorDto> {
+    let budget = state.config.commit_message_diff_budget_bytes();
+    let diff = tokio::task::spawn_blocking({
+        let repo_path = repo_path.clone();
+        move || commit_message::staged_diff(&repo_path, budget)
+    })
+    .await
+    .map_err(|error| commit_message_error(error.into()))?
+    .map_err(commit_message_error)?;
+    if diff.is_empty() {
+        return Err(GitCommitMessageErrorDto::NothingStaged {
+            message: "nothing is staged; stage changes to describe them".to_string(),
+        });
+    }
+
+    let reply = state
+        .engines
+        .run_ephemeral_prompt(
+            &engine_id,
+            &model_id,
+            &repo_path,
+            commit_message::build_prompt(&diff),
+            state.config.commit_message_timeout(),
+        )
         .await
-        .map_err(|error| error.to_string())?
+        .map_err(commit_message_error)?;
+    commit_message::parse_reply(&reply).ok_or_else(|| {
+        commit_message_error(
+            EphemeralPromptError::Failed {
+                engine_id,
+                message: "the reply held no commit message".to_string(),
+            }
+            .into(),
+        )
+    })
+}
+
+fn commit_message_error(error: anyhow::Error) -> GitCommitMessageErrorDto {
+    let message = err_to_string(&error);
+    match error.downcast::<EphemeralPromptError>() {
+        Ok(EphemeralPromptError::EngineUnavailable { engine_id }) => {
+            GitCommitMessageErrorDto::EngineUnavailable { engine_id, message }
+        }
+        Ok(EphemeralPromptError::TimedOut {
+            engine_id,
+            timeout_ms,
+        }) => GitCommitMessageErrorDto::TimedOut {
+            engine_id,
+            timeout_ms,
+            message,
+        },
+        Ok(EphemeralPromptError::Failed { engine_id, .. }) => {
+            GitCommitMessageErrorDto::Failed { engine_id, message }
+        }
+        Err(_) => GitCommitMessageErrorDto::Other { message },
+    }
+}
+
+/// Status of all git-active repos in a workspace. The frontend refreshes it on
+/// the same watcher events that drive the single-repo panel.
+#[tauri::command]
+pub async fn get_workspace_git_overview(
+    state: State<'_, AppState>,
+    workspace_id: String,
+) -> Result<WorkspaceGitOverviewDto, String> {
+    let repos = run_db(state.db.clone(), {
+        let workspace_id = workspace_id.clone();
+        move |db| db::repos::get_repos(db, &workspace_id)
+    })
+    .await?
+    .into_iter()
+    .filter(|repo| repo.is_active)
+    .collect();
+    Ok(overview::workspace_git_overview(&workspace_id, repos).await)
+}
+
+#[tauri::command]
+pub async fn stage_files_multi(
+    _state: State<'_, AppState>,
+    entries: Vec<GitRepoFilesDto>,
+) -> Result<Vec<GitMultiRepoResultDto>, String> {
+    tokio::task::spawn_blocking(move || overview::stage_files_multi(&entries))
+        .await
+        .map_err(|error| error.to_string())
+}
+
+/// Commits each selected repo with the same message. Paths that are not repos
+/// of the workspace are reported as failures without touching them.
+#[tauri::command]
+pub async fn commit_multi(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    message: String,
+    repo_paths: Vec<String>,
+) -> Result<Vec<GitMultiRepoResultDto>, String> {
+    let known_paths = run_db(state.db.clone(), move |db| {
+        db::repos::get_repos(db, &workspace_id)
+    })
+    .await?
+    .into_iter()
+    .map(|repo| repo.path)
+    .collect::<std::collections::HashSet<_>>();
+
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let (known, unknown): (Vec<String>, Vec<String>) = repo_paths
+            .iter()
+            .cloned()
+            .partition(|repo_path| known_paths.contains(repo_path));
+        let mut results = overview::commit_multi(&known, &message);
+        let committed = results.iter().filter(|result| result.ok).count();
+        metrics::incr(&db, metrics::COMMITS_MADE, committed as i64);
+        results.extend(unknown.into_iter().map(|repo_path| GitMultiRepoResultDto {
+            repo_path,
+            ok: false,
+            commit_hash: None,
+            error: Some("repo is not part of this workspace".to_string()),
+        }));
+        results.sort_by_key(|result| {
+            repo_paths
+                .iter()
+                .position(|repo_path| *repo_path == result.repo_path)
+        });
+        results
+    })
+    .await
+    .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
@@ -127,11 +448,87 @@ pub async fn pull_git(_state: State<'_, AppState>, repo_path: String) -> Result<
         .map_err(|error| error.to_string())?
 }
 
+/// Plain pushes go straight through. Force pushes need `force_with_lease` or
+/// the explicit `force` acknowledgement for an unconditional overwrite, and
+/// every attempt is recorded in the git operation log, under `thread_id`
+/// when the push was run from a thread.
 #[tauri::command]
-pub async fn push_git(_state: State<'_, AppState>, repo_path: String) -> Result<(), String> {
-    tokio::task::spawn_blocking(move || repo::push_repo(&repo_path).map_err(err_to_string))
+pub async fn push_git(
+    state: State<'_, AppState>,
+    repo_path: String,
+    force_with_lease: Option<bool>,
+    force: Option<bool>,
+    thread_id: Option<String>,
+) -> Result<(), String> {
+    let force_with_lease = force_with_lease.unwrap_or(false);
+    let force = force.unwrap_or(false);
+    let mode = if force {
+        ForcePushMode::Unconditional
+    } else if force_with_lease {
+        ForcePushMode::WithLease
+    } else {
+        return tokio::task::spawn_blocking(move || {
+            repo::push_repo(&repo_path).map_err(err_to_string)
+        })
         .await
-        .map_err(|error| error.to_string())?
+        .map_err(|error| error.to_string())?;
+    };
+
+    let protected = run_db(state.db.clone(), {
+        let repo_path = repo_path.clone();
+        move |db| db::repos::get_protected_branches(db, &repo_path)
+    })
+    .await?;
+    let result = tokio::task::spawn_blocking({
+        let repo_path = repo_path.clone();
+        move || repo::force_push_repo(&repo_path, mode, &protected)
+    })
+    .await
+    .map_err(|error| error.to_string())?;
+
+    let (target, outcome, detail) = match &result {
+        Ok(pushed) => (
+            format!("{}/{}", pushed.remote, pushed.branch),
+            "completed",
+            pushed
+                .previous_remote_tip
+                .as_ref()
+                .map(|tip| format!("replaced {tip}")),
+        ),
+        Err(error) => (
+            current_branch_label(&repo_path),
+            safety_outcome(error),
+            Some(error.to_string()),
+        ),
+    };
+    record_git_operation(
+        &state,
+        GitOperationRecord {
+            repo_path: repo_path.clone(),
+            thread_id,
+            operation: "force_push",
+            target,
+            force,
+            force_with_lease,
+            outcome,
+            detail,
+        },
+    )
+    .await;
+
+    result.map(|_| ()).map_err(err_to_string)
+}
+
+#[tauri::command]
+pub async fn list_git_operation_logs(
+    state: State<'_, AppState>,
+    repo_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<GitOperationLogDto>, String> {
+    run_db(state.db.clone(), move |db| {
+        db::git_operations::list_git_operations(db, &repo_path, limit.unwrap_or(100))
+    })
+    .await
 }
 
 #[tauri::command]
@@ -200,16 +597,149 @@ pub async fn rename_git_branch(
 
 #[tauri::command]
 pub async fn delete_git_branch(
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
     repo_path: String,
     branch_name: String,
     force: bool,
+    thread_id: Option<String>,
 ) -> Result<(), String> {
-    tokio::task::spawn_blocking(move || {
-        repo::delete_git_branch(&repo_path, &branch_name, force).map_err(err_to_string)
+    let protected = run_db(state.db.clone(), {
+        let repo_path = repo_path.clone();
+        move |db| db::repos::get_protected_branches(db, &repo_path)
+    })
+    .await?;
+    let result = tokio::task::spawn_blocking({
+        let repo_path = repo_path.clone();
+        let branch_name = branch_name.clone();
+        move || repo::delete_git_branch(&repo_path, &branch_name, force, &protected)
     })
     .await
-    .map_err(|error| error.to_string())?
+    .map_err(|error| error.to_string())?;
+
+    let (outcome, detail) = match &result {
+        Ok(0) => ("completed", None),
+        Ok(dropped) => (
+            "completed",
+            Some(format!("dropped {dropped} unmerged commit(s)")),
+        ),
+        Err(error) => (safety_outcome(error), Some(error.to_string())),
+    };
+    record_git_operation(
+        &state,
+        GitOperationRecord {
+            repo_path: repo_path.clone(),
+            thread_id,
+            operation: "delete_branch",
+            target: branch_name,
+            force,
+            force_with_lease: false,
+            outcome,
+            detail,
+        },
+    )
+    .await;
+
+    result.map(|_| ()).map_err(err_to_string)
+}
+
+/// Squashes the last `count` commits into one with `message`. Refuses to
+/// run over uncommitted changes unless `force` is set, and always refuses
+/// when the commits are already pushed on a protected branch. Returns the
+/// new HEAD commit; every attempt is recorded in the git operation log.
+#[tauri::command]
+pub async fn squash_commits(
+    state: State<'_, AppState>,
+    repo_path: String,
+    count: usize,
+    message: String,
+    force: Option<bool>,
+    thread_id: Option<String>,
+) -> Result<String, String> {
+    let force = force.unwrap_or(false);
+    rewrite_history(
+        &state,
+        repo_path,
+        thread_id,
+        "squash_commits",
+        format!("{count} commits"),
+        force,
+        move |repo_path, protected| {
+            repo::squash_commits(repo_path, count, &message, force, protected)
+        },
+    )
+    .await
+}
+
+/// Rewords the HEAD commit `sha`, under the same checks as
+/// [`squash_commits`].
+#[tauri::command]
+pub async fn reword_commit(
+    state: State<'_, AppState>,
+    repo_path: String,
+    sha: String,
+    message: String,
+    force: Option<bool>,
+    thread_id: Option<String>,
+) -> Result<String, String> {
+    let force = force.unwrap_or(false);
+    rewrite_history(
+        &state,
+        repo_path,
+        thread_id,
+        "reword_commit",
+        sha.clone(),
+        force,
+        move |repo_path, protected| {
+            repo::reword_commit(repo_path, &sha, &message, force, protected)
+        },
+    )
+    .await
+}
+
+async fn rewrite_history<F>(
+    state: &State<'_, AppState>,
+    repo_path: String,
+    thread_id: Option<String>,
+    operation: &'static str,
+    target: String,
+    force: bool,
+    rewrite: F,
+) -> Result<String, String>
+where
+    F: FnOnce(&str, &[String]) -> anyhow::Result<String> + Send + 'static,
+{
+    let protected = run_db(state.db.clone(), {
+        let repo_path = repo_path.clone();
+        move |db| db::repos::get_protected_branches(db, &repo_path)
+    })
+    .await?;
+    let result = tokio::task::spawn_blocking({
+        let repo_path = repo_path.clone();
+        move || rewrite(&repo_path, &protected)
+    })
+    .await
+    .map_err(|error| error.to_string())?;
+
+    let (outcome, detail) = match &result {
+        Ok(new_head) => ("completed", Some(format!("new HEAD {new_head}"))),
+        Err(error) => (safety_outcome(error), Some(error.to_string())),
+    };
+    record_git_operation(
+        state,
+        GitOperationRecord {
+            repo_path: repo_path.clone(),
+            thread_id,
+            operation,
+            target: format!("{} ({target})", current_branch_label(&repo_path)),
+            force,
+            force_with_lease: false,
+            outcome,
+            detail,
+        },
+    )
+    .await;
+
+    result.map_err(err_to_string)
 }
 
 #[tauri::command]
@@ -244,9 +774,17 @@ pub async fn push_git_stash(
     _state: State<'_, AppState>,
     repo_path: String,
     message: Option<String>,
+    include_untracked: Option<bool>,
+    paths: Option<Vec<String>>,
 ) -> Result<(), String> {
     tokio::task::spawn_blocking(move || {
-        repo::push_git_stash(&repo_path, message.as_deref()).map_err(err_to_string)
+        repo::push_git_stash(
+            &repo_path,
+            message.as_deref(),
+            include_untracked.unwrap_or(false),
+            paths.as_deref().unwrap_or_default(),
+        )
+        .map_err(err_to_string)
     })
     .await
     .map_err(|error| error.to_string())?
@@ -257,7 +795,7 @@ pub async fn apply_git_stash(
     _state: State<'_, AppState>,
     repo_path: String,
     stash_index: usize,
-) -> Result<(), String> {
+) -> Result<GitStashApplyResultDto, String> {
     tokio::task::spawn_blocking(move || {
         repo::apply_git_stash(&repo_path, stash_index).map_err(err_to_string)
     })
@@ -270,7 +808,7 @@ pub async fn pop_git_stash(
     _state: State<'_, AppState>,
     repo_path: String,
     stash_index: usize,
-) -> Result<(), String> {
+) -> Result<GitStashApplyResultDto, String> {
     tokio::task::spawn_blocking(move || {
         repo::pop_git_stash(&repo_path, stash_index).map_err(err_to_string)
     })
@@ -310,12 +848,24 @@ pub async fn get_file_tree_page(
     repo_path: String,
     offset: Option<usize>,
     limit: Option<usize>,
+    dir_path: Option<String>,
+    show_ignored: Option<bool>,
 ) -> Result<FileTreePageDto, String> {
     let offset = offset.unwrap_or(0);
     let limit = limit.unwrap_or(2000);
     let cache = state.file_tree_cache.clone();
     tokio::task::spawn_blocking(move || {
-        repo::get_file_tree_page(&repo_path, offset, limit, &cache).map_err(err_to_string)
+        match dir_path {
+            Some(dir_path) => repo::get_file_tree_dir_page(
+                &repo_path,
+                &dir_path,
+                offset,
+                limit,
+                show_ignored.unwrap_or(false),
+            ),
+            None => repo::get_file_tree_page(&repo_path, offset, limit, &cache),
+        }
+        .map_err(err_to_string)
     })
     .await
     .map_err(|error| error.to_string())?
@@ -325,6 +875,24 @@ pub async fn get_file_tree_page(
 #[serde(rename_all = "camelCase")]
 struct GitRepoChangedEvent {
     repo_path: String,
+    head: bool,
+    index: bool,
+    refs: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RepoAgentConfigChangedEvent {
+    repo_path: String,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GitRepoReadyEvent {
+    workspace_id: String,
+    repo_path: String,
+    status: GitStatusDto,
 }
 
 #[tauri::command]
@@ -333,20 +901,73 @@ pub async fn watch_git_repo(
     state: State<'_, AppState>,
     repo_path: String,
 ) -> Result<(), String> {
+    register_repo_watchers(&app, state.inner(), repo_path)
+        .await
+        .map_err(err_to_string)
+}
+
+/// Starts the git and agent-config watchers for a repo, forwarding their
+/// changes as `git-repo-changed` and `repo-agent-config-changed`.
+pub(crate) async fn register_repo_watchers(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    repo_path: String,
+) -> anyhow::Result<()> {
+    let git_app = app.clone();
+    let config_app = app.clone();
     let cache = state.file_tree_cache.clone();
-    let callback = std::sync::Arc::new(move |changed_repo_path: String| {
-        cache.invalidate_containing_path(&changed_repo_path);
+    let callback = std::sync::Arc::new(move |change: GitRepoChange| {
+        cache.invalidate_containing_path(&change.repo_path);
         let payload = GitRepoChangedEvent {
-            repo_path: changed_repo_path,
+            repo_path: change.repo_path,
+            head: change.head,
+            index: change.index,
+            refs: change.refs,
         };
-        let _ = app.emit("git-repo-changed", payload);
+        let _ = git_app.emit("git-repo-changed", payload);
+    });
+
+    let config_callback = std::sync::Arc::new(move |repo_path: String, path: String| {
+        let payload = RepoAgentConfigChangedEvent { repo_path, path };
+        let _ = config_app.emit("repo-agent-config-changed", payload);
     });
+    if let Err(error) = state
+        .git_watchers
+        .watch_agent_configs(repo_path.clone(), config_callback)
+        .await
+    {
+        log::warn!("failed to watch agent configs in {repo_path}: {error}");
+    }
 
     state
         .git_watchers
-        .watch_repo(repo_path, callback)
+        .watch_repo(repo_path, state.config.git_watcher_debounce(), callback)
         .await
-        .map_err(err_to_string)
+}
+
+/// Loads the repo's status in the background and emits it as
+/// `git-repo-ready` once it is back.
+pub(crate) fn spawn_repo_ready(app: &tauri::AppHandle, workspace_id: String, repo_path: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let status = tokio::task::spawn_blocking({
+            let repo_path = repo_path.clone();
+            move || repo::get_git_status(&repo_path)
+        })
+        .await;
+        match status {
+            Ok(Ok(status)) => {
+                let payload = GitRepoReadyEvent {
+                    workspace_id,
+                    repo_path,
+                    status,
+                };
+                let _ = app.emit("git-repo-ready", payload);
+            }
+            Ok(Err(error)) => log::warn!("failed to load git status for {repo_path}: {error}"),
+            Err(error) => log::warn!("git status task for {repo_path} failed: {error}"),
+        }
+    });
 }
 
 // ── Git Worktrees ──────────────────────────────────────────────
@@ -408,16 +1029,25 @@ pub async fn list_git_worktrees(
         .map_err(|error| error.to_string())?
 }
 
+/// Removes a linked worktree and clears it as the target of every thread
+/// that ran there, so they fall back to the main checkout.
 #[tauri::command]
 pub async fn remove_git_worktree(
-    _state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
     repo_path: String,
     worktree_path: String,
     force: bool,
     branch_name: Option<String>,
     delete_branch: bool,
 ) -> Result<(), String> {
-    tokio::task::spawn_blocking(move || {
+    let removed_paths = tokio::task::spawn_blocking(move || {
+        // Threads store the path as git lists it, which may differ from the
+        // one the caller passed.
+        let listed = worktree::find_worktree(&repo_path, &worktree_path)
+            .ok()
+            .flatten()
+            .map(|found| found.path);
         worktree::remove_worktree(
             &repo_path,
             &worktree_path,
@@ -425,10 +1055,28 @@ pub async fn remove_git_worktree(
             branch_name.as_deref(),
             delete_branch,
         )
-        .map_err(err_to_string)
+        .map_err(err_to_string)?;
+        Ok::<_, String>(
+            std::iter::once(worktree_path)
+                .chain(listed)
+                .collect::<std::collections::BTreeSet<_>>(),
+        )
     })
     .await
-    .map_err(|error| error.to_string())?
+    .map_err(|error| error.to_string())??;
+
+    let cleared = run_db(state.db.clone(), move |db| {
+        let mut cleared = Vec::new();
+        for path in &removed_paths {
+            cleared.extend(db::threads::clear_worktree_path(db, path)?);
+        }
+        Ok(cleared)
+    })
+    .await?;
+    for thread_id in cleared {
+        super::threads::emit_thread_updated(&app, &state.db, &thread_id).await;
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -553,6 +1201,39 @@ pub async fn rename_git_remote(
     .map_err(|error| error.to_string())?
 }
 
+/// Failing to write the audit row must not mask the git result.
+async fn record_git_operation(state: &State<'_, AppState>, record: GitOperationRecord) {
+    let result = run_db(state.db.clone(), move |db| {
+        db::git_operations::record_git_operation(db, &record)
+    })
+    .await;
+    if let Err(error) = result {
+        log::warn!("failed to record git operation: {error}");
+    }
+}
+
+/// Safety refusals are logged apart from ordinary git failures.
+fn safety_outcome(error: &anyhow::Error) -> &'static str {
+    if error.is::<ForcePushLeaseError>()
+        || error.is::<UnmergedBranchError>()
+        || error.is::<ProtectedBranchError>()
+        || error.is::<DirtyWorktreeError>()
+        || error.is::<PublishedHistoryError>()
+        || error.is::<PushedCommitError>()
+    {
+        "refused"
+    } else {
+        "failed"
+    }
+}
+
+fn current_branch_label(repo_path: &str) -> String {
+    git2::Repository::open(repo_path)
+        .ok()
+        .and_then(|repo| repo.head().ok()?.shorthand().map(ToOwned::to_owned))
+        .unwrap_or_else(|| "HEAD".to_string())
+}
+
 fn err_to_string(error: impl std::fmt::Display) -> String {
     error.to_string()
 }
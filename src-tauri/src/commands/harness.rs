@@ -507,7 +507,11 @@ async fn run_harness_install(
 
     emit(message.clone(), "status".to_string(), true);
 
-    Ok(InstallResult { success, message })
+    Ok(InstallResult {
+        success,
+        message,
+        resolvable: None,
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -615,7 +619,11 @@ async fn run_harness_install_script(
 
     emit(message.clone(), "status".to_string(), true);
 
-    Ok(InstallResult { success, message })
+    Ok(InstallResult {
+        success,
+        message,
+        resolvable: None,
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -8,4 +8,5 @@ pub mod power;
 pub mod setup;
 pub mod terminal;
 pub mod threads;
+pub mod usage;
 pub mod workspace;
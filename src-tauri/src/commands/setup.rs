@@ -1,16 +1,21 @@
 use std::{ffi::OsString, path::Path};
 
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, State};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
-use crate::engines::codex::resolve_codex_executable;
-use crate::models::{DepStatus, DependencyReport, InstallProgressEvent, InstallResult};
+use crate::engines::{codex::resolve_codex_executable, EngineManager};
+use crate::models::{
+    DepStatus, DependencyReport, EnvironmentRefreshDto, InstallProgressEvent, InstallResult,
+    ResolvedExecutableDto,
+};
 use crate::process_utils;
 use crate::runtime_env;
+use crate::state::AppState;
 
 const LOGIN_SHELL_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const WELL_KNOWN_EXECUTABLES: &[&str] = &["node", "npm", "git", "codex", "claude", "opencode"];
 
 // ---------------------------------------------------------------------------
 // check_dependencies
@@ -38,6 +43,7 @@ pub async fn check_dependencies() -> Result<DependencyReport, String> {
 #[tauri::command]
 pub async fn install_dependency(
     app: AppHandle,
+    state: State<'_, AppState>,
     dependency: String,
     method: String,
 ) -> Result<InstallResult, String> {
@@ -65,7 +71,50 @@ pub async fn install_dependency(
         }
     };
 
-    run_install_process(&app, &dependency, &program, &args).await
+    let mut result = run_install_process(&app, &dependency, &program, &args).await?;
+    if result.success {
+        refresh_environment_caches(&state.engines).await;
+        result.resolvable = Some(dependency_resolvable(&dependency).await);
+    }
+    Ok(result)
+}
+
+// ---------------------------------------------------------------------------
+// refresh_environment
+// ---------------------------------------------------------------------------
+
+/// Re-reads PATH and re-resolves well-known tools after something was
+/// installed while the app was running.
+#[tauri::command]
+pub async fn refresh_environment(
+    state: State<'_, AppState>,
+) -> Result<EnvironmentRefreshDto, String> {
+    refresh_environment_caches(&state.engines).await;
+    Ok(EnvironmentRefreshDto {
+        path: runtime_env::augmented_path().map(|path| path.to_string_lossy().to_string()),
+        executables: WELL_KNOWN_EXECUTABLES
+            .iter()
+            .map(|name| ResolvedExecutableDto {
+                name: name.to_string(),
+                path: runtime_env::resolve_executable(name).map(|path| path.display().to_string()),
+            })
+            .collect(),
+    })
+}
+
+async fn refresh_environment_caches(engines: &EngineManager) {
+    runtime_env::invalidate_environment_caches().await;
+    engines.invalidate_executable_caches().await;
+}
+
+async fn dependency_resolvable(dependency: &str) -> bool {
+    if dependency == "codex" {
+        return resolve_codex_executable().await.executable.is_some();
+    }
+    runtime_env::resolve_executable(dependency).is_some()
+        || detect_via_login_shell(dependency, "--version")
+            .await
+            .is_some()
 }
 
 // ---------------------------------------------------------------------------
@@ -286,7 +335,11 @@ async fn run_install_process(
 
     emit_progress(dep, message.clone(), "status".to_string(), true);
 
-    Ok(InstallResult { success, message })
+    Ok(InstallResult {
+        success,
+        message,
+        resolvable: None,
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -1,14 +1,20 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 use tauri::State;
 
 use crate::{
-    db,
+    config::app_config::DEFAULT_TERMINAL_MAX_SESSIONS_PER_WORKSPACE,
+    db, metrics,
     models::{
-        TerminalNotificationDto, TerminalRendererDiagnosticsDto, TerminalResumeSessionDto,
-        TerminalSessionDto,
+        TerminalAttachDto, TerminalNotificationDto, TerminalOutputSearchDto,
+        TerminalPathInjectionDto, TerminalRendererDiagnosticsDto, TerminalResumeSessionDto,
+        TerminalScrollbackDto, TerminalSessionDto, TerminalSessionLimitsDto,
     },
     path_utils,
     state::AppState,
+    terminal::{TerminalEnvOverrides, TerminalSessionOptions, TerminalSignal},
 };
 
 async fn run_db<T, F>(db: crate::db::Database, operation: F) -> Result<T, String>
@@ -16,6 +22,7 @@ where
     T: Send + 'static,
     F: FnOnce(&crate::db::Database) -> anyhow::Result<T> + Send + 'static,
 {
+    let db = db.acquire_scope().await.map_err(err_to_string)?;
     tokio::task::spawn_blocking(move || operation(&db))
         .await
         .map_err(|error| error.to_string())?
@@ -23,6 +30,7 @@ where
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn terminal_create_session(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
@@ -30,7 +38,15 @@ pub async fn terminal_create_session(
     cols: u16,
     rows: u16,
     cwd: Option<String>,
+    env: Option<HashMap<String, String>>,
+    replace_path: Option<bool>,
+    record: Option<bool>,
 ) -> Result<TerminalSessionDto, String> {
+    let env_overrides = TerminalEnvOverrides {
+        vars: env.unwrap_or_default(),
+        replace_path: replace_path.unwrap_or(false),
+    };
+    env_overrides.validate().map_err(err_to_string)?;
     let workspace_root = workspace_root_path(state.inner(), &workspace_id).await?;
     let workspace_root_canonical =
         canonicalize_existing_dir(&workspace_root, "workspace root directory")?;
@@ -47,7 +63,13 @@ pub async fn terminal_create_session(
         }
         None => workspace_root_canonical.to_string_lossy().to_string(),
     };
-    state
+    let options = TerminalSessionOptions::load_for_workspace(&workspace_id).await;
+    let options = TerminalSessionOptions {
+        record_transcript: options.record_transcript || record.unwrap_or(false),
+        env_overrides,
+        ..options
+    };
+    let session = state
         .terminals
         .create_session(
             app,
@@ -56,9 +78,12 @@ pub async fn terminal_create_session(
             resolved_cwd,
             cols.max(1),
             rows.max(1),
+            options,
         )
         .await
-        .map_err(err_to_string)
+        .map_err(err_to_string)?;
+    metrics::spawn_incr(&state.db, vec![(metrics::TERMINALS_OPENED.to_string(), 1)]);
+    Ok(session)
 }
 
 fn canonicalize_existing_dir(path: &str, label: &str) -> Result<PathBuf, String> {
@@ -123,6 +148,36 @@ pub async fn terminal_resize(
         .map_err(err_to_string)
 }
 
+#[tauri::command]
+pub async fn terminal_signal(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+    signal: String,
+) -> Result<(), String> {
+    let signal = TerminalSignal::parse(&signal).map_err(err_to_string)?;
+    state
+        .terminals
+        .signal(&workspace_id, &session_id, signal)
+        .await
+        .map_err(err_to_string)
+}
+
+/// Only called when the user opts in from the UI; the line is typed into
+/// the shell like any other input.
+#[tauri::command]
+pub async fn terminal_inject_path(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+) -> Result<TerminalPathInjectionDto, String> {
+    state
+        .terminals
+        .inject_path(&workspace_id, &session_id)
+        .await
+        .map_err(err_to_string)
+}
+
 #[tauri::command]
 pub async fn terminal_close_session(
     app: tauri::AppHandle,
@@ -147,10 +202,11 @@ pub async fn terminal_close_workspace_sessions(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     workspace_id: String,
+    archiving: Option<bool>,
 ) -> Result<(), String> {
     state
         .terminals
-        .close_workspace(app.clone(), &workspace_id)
+        .close_workspace(app.clone(), &workspace_id, archiving.unwrap_or(false))
         .await
         .map_err(err_to_string)?;
     state
@@ -160,6 +216,20 @@ pub async fn terminal_close_workspace_sessions(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn terminal_restore_scrollback(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+    max_bytes: Option<usize>,
+) -> Result<String, String> {
+    state
+        .terminals
+        .restore_scrollback(&workspace_id, &session_id, max_bytes)
+        .await
+        .map_err(err_to_string)
+}
+
 #[tauri::command]
 pub async fn terminal_list_sessions(
     state: State<'_, AppState>,
@@ -168,6 +238,22 @@ pub async fn terminal_list_sessions(
     Ok(state.terminals.list_sessions(&workspace_id).await)
 }
 
+/// Lets the UI disable "new terminal" once the workspace is at its limit.
+#[tauri::command]
+pub async fn terminal_session_limits(
+    state: State<'_, AppState>,
+    workspace_id: String,
+) -> Result<TerminalSessionLimitsDto, String> {
+    let limit = tokio::task::spawn_blocking(|| {
+        crate::config::app_config::AppConfig::load_or_create()
+            .map(|config| config.terminal_max_sessions_per_workspace())
+            .unwrap_or(DEFAULT_TERMINAL_MAX_SESSIONS_PER_WORKSPACE)
+    })
+    .await
+    .unwrap_or(DEFAULT_TERMINAL_MAX_SESSIONS_PER_WORKSPACE);
+    Ok(state.terminals.session_limits(&workspace_id, limit).await)
+}
+
 #[tauri::command]
 pub async fn terminal_get_renderer_diagnostics(
     state: State<'_, AppState>,
@@ -181,6 +267,90 @@ pub async fn terminal_get_renderer_diagnostics(
         .map_err(err_to_string)
 }
 
+#[tauri::command]
+pub async fn terminal_list_renderer_diagnostics(
+    state: State<'_, AppState>,
+    workspace_id: String,
+) -> Result<Vec<TerminalRendererDiagnosticsDto>, String> {
+    Ok(state
+        .terminals
+        .workspace_renderer_diagnostics(&workspace_id)
+        .await)
+}
+
+#[tauri::command]
+pub async fn terminal_get_scrollback(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+) -> Result<TerminalScrollbackDto, String> {
+    state
+        .terminals
+        .scrollback(&workspace_id, &session_id)
+        .await
+        .map_err(err_to_string)
+}
+
+#[tauri::command]
+pub async fn terminal_search_output(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+    query: String,
+    case_sensitive: Option<bool>,
+) -> Result<TerminalOutputSearchDto, String> {
+    state
+        .terminals
+        .search_output(
+            &workspace_id,
+            &session_id,
+            query,
+            case_sensitive.unwrap_or(false),
+        )
+        .await
+        .map_err(err_to_string)
+}
+
+#[tauri::command]
+pub async fn terminal_get_transcript_path(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    state
+        .terminals
+        .transcript_path(&workspace_id, &session_id)
+        .await
+        .map_err(err_to_string)
+}
+
+#[tauri::command]
+pub async fn terminal_attach(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+    from_seq: Option<u64>,
+) -> Result<TerminalAttachDto, String> {
+    state
+        .terminals
+        .attach(&workspace_id, &session_id, from_seq)
+        .await
+        .map_err(err_to_string)
+}
+
+#[tauri::command]
+pub async fn terminal_detach(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    session_id: String,
+) -> Result<(), String> {
+    state
+        .terminals
+        .detach(&workspace_id, &session_id)
+        .await
+        .map_err(err_to_string)
+}
+
 #[tauri::command]
 pub async fn terminal_resume_session(
     state: State<'_, AppState>,
@@ -1,30 +1,48 @@
-use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset, Utc};
 use serde_json::{json, Value};
-use tauri::State;
+use tauri::{Emitter, State};
+use tauri_plugin_dialog::DialogExt;
 
+use super::chat::{thread_checkout_path, ThreadUpdatedEvent};
 use crate::{
     config::app_config::AppConfig,
-    db,
+    conversation_export, conversation_import, db,
+    engines::events::UsageLimitsSnapshot,
+    engines::executable_resolution,
     engines::validate_engine_sandbox_mode,
     engines::CodexRemoteThreadSummary,
     engines::ModelInfo,
     engines::OpenCodeRemoteSessionSummary,
     engines::SandboxPolicy,
     engines::ThreadSyncSnapshot,
+    git::worktree,
+    metrics,
     models::{
-        CodexRemoteThreadDto, CodexRemoteThreadPageDto, MessageStatusDto, OpenCodeRemoteSessionDto,
-        OpenCodeRemoteSessionPageDto, RepoDto, ThreadDto, ThreadStatusDto, TrustLevelDto,
+        CodexRemoteThreadDto, CodexRemoteThreadPageDto, ConversationImportDto,
+        ConversationImportFormatDto, MessageDto, MessageStatusDto, OpenCodeRemoteSessionDto,
+        OpenCodeRemoteSessionPageDto, RepoDto, TerminalSessionDto, ThreadDto,
+        ThreadExportFormatDto, ThreadStatusDto, ThreadTimelineCursorDto, ThreadTimelineDto,
+        TrustLevelDto,
     },
+    runtime_env,
     state::AppState,
+    terminal::TerminalSessionOptions,
 };
 
 const MAX_THREAD_TITLE_CHARS: usize = 120;
+const THREAD_TIMELINE_DEFAULT_LIMIT: usize = 100;
+const THREAD_TIMELINE_MAX_LIMIT: usize = 500;
+const MAX_THREAD_CUSTOM_INSTRUCTIONS_CHARS: usize = 8_000;
 
 async fn run_db<T, F>(db: crate::db::Database, operation: F) -> Result<T, String>
 where
     T: Send + 'static,
     F: FnOnce(&crate::db::Database) -> anyhow::Result<T> + Send + 'static,
 {
+    let db = db.acquire_scope().await.map_err(err_to_string)?;
     tokio::task::spawn_blocking(move || operation(&db))
         .await
         .map_err(|error| error.to_string())?
@@ -53,6 +71,240 @@ pub async fn list_archived_threads(
     .await
 }
 
+#[tauri::command]
+pub async fn get_thread_timeline(
+    state: State<'_, AppState>,
+    thread_id: String,
+    limit: Option<usize>,
+    before: Option<ThreadTimelineCursorDto>,
+) -> Result<ThreadTimelineDto, String> {
+    let limit = limit
+        .unwrap_or(THREAD_TIMELINE_DEFAULT_LIMIT)
+        .clamp(1, THREAD_TIMELINE_MAX_LIMIT);
+    run_db(state.db.clone(), move |db| {
+        db::views::get_thread_timeline(db, &thread_id, before.as_ref(), limit)
+    })
+    .await
+}
+
+/// Last usage snapshot an engine reported for the thread, so the header can
+/// show context usage as soon as the thread opens. `None` until a turn has
+/// reported one.
+#[tauri::command]
+pub async fn get_thread_usage(
+    state: State<'_, AppState>,
+    thread_id: String,
+) -> Result<Option<UsageLimitsSnapshot>, String> {
+    run_db(state.db.clone(), move |db| {
+        db::threads::get_usage_limits(db, &thread_id)
+    })
+    .await
+}
+
+/// Renders the thread as `format` and writes it to `output_path`, which the
+/// frontend picks with the dialog plugin. Action output is cut at
+/// `debug.max_action_output_chars`.
+#[tauri::command]
+pub async fn export_thread(
+    state: State<'_, AppState>,
+    thread_id: String,
+    format: ThreadExportFormatDto,
+    include_thinking: bool,
+    include_actions: bool,
+    output_path: String,
+    utc_offset_minutes: Option<i32>,
+) -> Result<(), String> {
+    if output_path.trim().is_empty() {
+        return Err("output path is required".to_string());
+    }
+    let options = thread_export_options(
+        &state,
+        include_thinking,
+        include_actions,
+        utc_offset_minutes,
+    )?;
+    let (thread, messages) = load_thread_for_export(&state, thread_id).await?;
+    let rendered = match format {
+        ThreadExportFormatDto::Markdown => {
+            conversation_export::render_thread_markdown(&thread, &messages, &options)
+        }
+        ThreadExportFormatDto::Json => {
+            conversation_export::render_thread_json(&thread, &messages, &options)
+                .map_err(err_to_string)?
+        }
+    };
+    write_thread_export(PathBuf::from(output_path), rendered).await
+}
+
+/// Renders the thread as Markdown and writes it wherever the user picks in a
+/// save dialog. Returns the written path, or `None` when the dialog was
+/// cancelled.
+#[tauri::command]
+pub async fn export_thread_markdown(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    utc_offset_minutes: Option<i32>,
+) -> Result<Option<String>, String> {
+    let options = thread_export_options(&state, true, true, utc_offset_minutes)?;
+    let (thread, messages) = load_thread_for_export(&state, thread_id).await?;
+    let markdown = conversation_export::render_thread_markdown(&thread, &messages, &options);
+    let file_name = format!("{}.md", export_file_stem(&thread.title));
+
+    let picked = tokio::task::spawn_blocking(move || {
+        app.dialog()
+            .file()
+            .set_title("Export thread")
+            .set_file_name(file_name)
+            .add_filter("Markdown", &["md"])
+            .blocking_save_file()
+    })
+    .await
+    .map_err(err_to_string)?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(err_to_string)?;
+    write_thread_export(path.clone(), markdown).await?;
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+fn thread_export_options(
+    state: &AppState,
+    include_thinking: bool,
+    include_actions: bool,
+    utc_offset_minutes: Option<i32>,
+) -> Result<conversation_export::ExportOptions, String> {
+    let offset_seconds = utc_offset_minutes.unwrap_or(0).clamp(-18 * 60, 18 * 60) * 60;
+    Ok(conversation_export::ExportOptions {
+        include_thinking,
+        include_actions,
+        max_action_output_chars: state.config.debug.max_action_output_chars,
+        offset: FixedOffset::east_opt(offset_seconds).ok_or("invalid utc offset")?,
+        exported_at: Utc::now(),
+    })
+}
+
+async fn load_thread_for_export(
+    state: &AppState,
+    thread_id: String,
+) -> Result<(ThreadDto, Vec<MessageDto>), String> {
+    run_db(state.db.clone(), move |db| {
+        let thread = db::threads::get_thread(db, &thread_id)?.context("thread not found")?;
+        let messages = db::messages::get_thread_messages(db, &thread_id)?;
+        Ok((thread, messages))
+    })
+    .await
+}
+
+async fn write_thread_export(path: PathBuf, contents: String) -> Result<(), String> {
+    tokio::fs::write(&path, contents)
+        .await
+        .with_context(|| format!("failed to write {}", path.display()))
+        .map_err(err_to_string)
+}
+
+/// File-name-safe version of a thread title for the save dialog default.
+fn export_file_stem(title: &str) -> String {
+    let mut stem = String::new();
+    for ch in title.trim().chars() {
+        if ch.is_alphanumeric() {
+            stem.extend(ch.to_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-');
+    if stem.is_empty() {
+        "thread".to_string()
+    } else {
+        stem.chars().take(80).collect()
+    }
+}
+
+/// Creates one local thread per conversation found in `source_path`. The
+/// threads use the configured default engine and model and have no engine
+/// session, so the first message sent starts a fresh one.
+#[tauri::command]
+pub async fn import_conversation(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    repo_id: Option<String>,
+    source_path: String,
+    format: ConversationImportFormatDto,
+) -> Result<ConversationImportDto, String> {
+    let (parsed, config) = tokio::task::spawn_blocking(move || {
+        let parsed = conversation_import::read_conversations(Path::new(&source_path), format)?;
+        let config = AppConfig::load_or_create()?;
+        Ok::<_, anyhow::Error>((parsed, config))
+    })
+    .await
+    .map_err(err_to_string)?
+    .map_err(err_to_string)?;
+    if parsed.conversations.is_empty() {
+        return Err("no importable messages were found".to_string());
+    }
+
+    let mut threads = Vec::with_capacity(parsed.conversations.len());
+    let mut imported_messages = 0;
+    for conversation in parsed.conversations {
+        let title = normalize_thread_title(&conversation.title)
+            .unwrap_or_else(|_| "Imported conversation".to_string());
+        let mut new_thread = validate_new_thread(
+            state.inner(),
+            workspace_id.clone(),
+            repo_id.clone(),
+            config.general.default_engine.clone(),
+            config.general.default_model.clone(),
+            title,
+            None,
+            None,
+            config.default_autonomy_preset().map(ToOwned::to_owned),
+        )
+        .await?;
+        let mut metadata = new_thread.metadata.take().unwrap_or_else(|| json!({}));
+        if let Some(object) = metadata.as_object_mut() {
+            object.insert("manualTitle".to_string(), json!(true));
+            object.insert("importedFrom".to_string(), json!(format.as_str()));
+        }
+        new_thread.metadata = Some(metadata);
+
+        imported_messages += conversation.messages.len();
+        // The thread and its messages commit together, so a failed import
+        // leaves no empty thread behind.
+        let thread = run_db(state.db.clone(), move |db| {
+            let mut conn = db.connect()?;
+            let tx = conn
+                .transaction()
+                .context("failed to start conversation import transaction")?;
+            let created = db::threads::create_thread_with_metadata_on(
+                &tx,
+                &new_thread.workspace_id,
+                new_thread.repo_id.as_deref(),
+                &new_thread.engine_id,
+                &new_thread.model_id,
+                &new_thread.title,
+                new_thread.metadata.as_ref(),
+            )?;
+            db::messages::replace_thread_messages_on(&tx, &created.id, &conversation.messages)?;
+            db::threads::refresh_thread_message_stats_on(&tx, &created.id)?;
+            let thread = db::threads::get_thread_on(&tx, &created.id)?
+                .ok_or_else(|| anyhow::anyhow!("thread not found after import: {}", created.id))?;
+            tx.commit()
+                .context("failed to commit conversation import transaction")?;
+            Ok(thread)
+        })
+        .await?;
+        threads.push(thread);
+    }
+
+    Ok(ConversationImportDto {
+        threads,
+        imported_messages,
+        skipped_items: parsed.skipped_items,
+    })
+}
+
 #[tauri::command]
 pub async fn list_codex_remote_threads(
     state: State<'_, AppState>,
@@ -417,6 +669,7 @@ async fn resolve_thread_cwd(state: &AppState, thread: &ThreadDto) -> Result<Stri
     let workspace_id = thread.workspace_id.clone();
     let repo_id = thread.repo_id.clone();
     let thread_id = thread.id.clone();
+    let metadata = thread.engine_metadata.clone();
 
     run_db(state.db.clone(), move |db| {
         let workspace = db::workspaces::find_workspace_by_id(db, &workspace_id)?
@@ -424,7 +677,7 @@ async fn resolve_thread_cwd(state: &AppState, thread: &ThreadDto) -> Result<Stri
         if let Some(repo_id) = repo_id.as_deref() {
             let repo = db::repos::find_repo_by_id(db, repo_id)?
                 .ok_or_else(|| anyhow::anyhow!("repo not found for thread {thread_id}"))?;
-            return Ok(repo.path);
+            return thread_checkout_path(&repo.path, metadata.as_ref()).map_err(anyhow::Error::msg);
         }
 
         Ok(workspace.root_path)
@@ -781,6 +1034,56 @@ async fn create_thread_inner(
     service_tier: Option<String>,
     initial_autonomy_preset: Option<String>,
 ) -> Result<ThreadDto, String> {
+    let new_thread = validate_new_thread(
+        state,
+        workspace_id,
+        repo_id,
+        engine_id,
+        model_id,
+        title,
+        reasoning_effort,
+        service_tier,
+        initial_autonomy_preset,
+    )
+    .await?;
+    run_db(state.db.clone(), move |db| {
+        db::threads::create_thread_with_metadata(
+            db,
+            &new_thread.workspace_id,
+            new_thread.repo_id.as_deref(),
+            &new_thread.engine_id,
+            &new_thread.model_id,
+            &new_thread.title,
+            new_thread.metadata.as_ref(),
+        )
+    })
+    .await
+    .map_err(|error| format!("thread was not created: {error}"))
+}
+
+/// A thread whose model, effort, service tier and autonomy preset passed
+/// validation, ready to insert.
+struct NewThread {
+    workspace_id: String,
+    repo_id: Option<String>,
+    engine_id: String,
+    model_id: String,
+    title: String,
+    metadata: Option<Value>,
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn validate_new_thread(
+    state: &AppState,
+    workspace_id: String,
+    repo_id: Option<String>,
+    engine_id: String,
+    model_id: String,
+    title: String,
+    reasoning_effort: Option<String>,
+    service_tier: Option<String>,
+    initial_autonomy_preset: Option<String>,
+) -> Result<NewThread, String> {
     let normalized_service_tier = if engine_id == "codex" {
         normalize_thread_service_tier(service_tier)?
     } else {
@@ -846,22 +1149,40 @@ async fn create_thread_inner(
         }
     }
 
-    let metadata = (!metadata.is_empty()).then_some(Value::Object(metadata));
+    Ok(NewThread {
+        workspace_id,
+        repo_id,
+        engine_id,
+        model_id: effective_model_id,
+        title,
+        metadata: (!metadata.is_empty()).then_some(Value::Object(metadata)),
+    })
+}
 
-    run_db(state.db.clone(), move |db| {
-        let created = db::threads::create_thread(
-            db,
-            &workspace_id,
-            repo_id.as_deref(),
-            &engine_id,
-            &effective_model_id,
-            &title,
-        )?;
-        if let Some(metadata) = metadata.as_ref() {
-            db::threads::update_engine_metadata(db, &created.id, metadata)?;
+/// Deletes threads in the workspace that never received a message or an
+/// engine thread and are at least `older_than_hours` old (default
+/// [`db::threads::STALE_EMPTY_THREAD_HOURS`]). Returns how many were removed.
+#[tauri::command]
+pub async fn cleanup_empty_threads(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    older_than_hours: Option<u32>,
+) -> Result<usize, String> {
+    let older_than_hours = older_than_hours.unwrap_or(db::threads::STALE_EMPTY_THREAD_HOURS);
+    let candidates = run_db(state.db.clone(), {
+        let workspace_id = workspace_id.clone();
+        move |db| db::threads::list_stale_empty_threads(db, Some(&workspace_id), older_than_hours)
+    })
+    .await?;
+    let mut busy = Vec::new();
+    for thread_id in candidates {
+        if state.turns.is_active(&thread_id) {
+            busy.push(thread_id);
         }
-        db::threads::get_thread(db, &created.id)?
-            .ok_or_else(|| anyhow::anyhow!("thread not found after insert: {}", created.id))
+    }
+
+    run_db(state.db.clone(), move |db| {
+        db::threads::delete_stale_empty_threads(db, Some(&workspace_id), older_than_hours, &busy)
     })
     .await
 }
@@ -988,6 +1309,148 @@ pub async fn set_thread_reasoning_effort(
     .await
 }
 
+/// Continues a Codex thread in the `codex` TUI, in a new terminal opened in
+/// the thread's directory. The thread stays flagged as attached until that
+/// terminal exits or `detach_thread_from_cli` clears it, and turns started
+/// from Panes meanwhile carry a warning.
+#[tauri::command]
+pub async fn open_thread_in_cli(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    cols: u16,
+    rows: u16,
+) -> Result<TerminalSessionDto, String> {
+    let db = state.db.clone();
+    let thread = run_db(db.clone(), {
+        let thread_id = thread_id.clone();
+        move |db| db::threads::get_thread(db, &thread_id)
+    })
+    .await?
+    .ok_or_else(|| format!("thread not found: {thread_id}"))?;
+    if thread.engine_id != "codex" {
+        return Err(format!(
+            "continuing in a terminal is not supported for engine `{}`",
+            thread.engine_id
+        ));
+    }
+    let engine_thread_id = thread
+        .engine_thread_id
+        .clone()
+        .ok_or_else(|| "thread has no Codex session yet; send a message first".to_string())?;
+    if state.turns.is_active(&thread_id) {
+        return Err("a turn is still running on this thread; wait for it or cancel it".to_string());
+    }
+
+    let (program, args) = state
+        .engines
+        .codex_cli_resume_command(&engine_thread_id)
+        .await
+        .map_err(err_to_string)?;
+    let cwd = resolve_thread_cwd(state.inner(), &thread).await?;
+    let workspace_id = thread.workspace_id.clone();
+    let options = TerminalSessionOptions::load_for_workspace(&workspace_id).await;
+    let session = state
+        .terminals
+        .create_session(
+            app.clone(),
+            state.notifications.clone(),
+            workspace_id.clone(),
+            cwd,
+            cols.max(1),
+            rows.max(1),
+            options,
+        )
+        .await
+        .map_err(err_to_string)?;
+    metrics::spawn_incr(&state.db, vec![(metrics::TERMINALS_OPENED.to_string(), 1)]);
+
+    let exited = state.terminals.watch_exit(&workspace_id, &session.id).await;
+    let command = runtime_env::shell_command_line(Path::new(&session.shell), &program, &args);
+    if let Err(error) = state
+        .terminals
+        .write(&workspace_id, &session.id, format!("{command}\r"))
+        .await
+    {
+        let _ = state
+            .terminals
+            .close_session(app.clone(), &workspace_id, &session.id)
+            .await;
+        return Err(err_to_string(error));
+    }
+
+    run_db(db.clone(), {
+        let thread_id = thread_id.clone();
+        let session_id = session.id.clone();
+        move |db| db::threads::set_external_cli_session(db, &thread_id, &session_id)
+    })
+    .await?;
+    emit_thread_updated(&app, &db, &thread_id).await;
+
+    // Registered before the flag was set, so an early exit still clears it.
+    tokio::spawn({
+        let session_id = session.id.clone();
+        async move {
+            let _ = exited.await;
+            let cleared = run_db(db.clone(), {
+                let thread_id = thread_id.clone();
+                move |db| db::threads::clear_external_cli_session(db, &thread_id, Some(&session_id))
+            })
+            .await;
+            match cleared {
+                Ok(true) => emit_thread_updated(&app, &db, &thread_id).await,
+                Ok(false) => {}
+                Err(error) => {
+                    log::warn!("failed to clear CLI attachment of thread {thread_id}: {error}")
+                }
+            }
+        }
+    });
+
+    Ok(session)
+}
+
+/// Clears the attachment recorded by `open_thread_in_cli` without waiting
+/// for its terminal to exit.
+#[tauri::command]
+pub async fn detach_thread_from_cli(
+    state: State<'_, AppState>,
+    thread_id: String,
+) -> Result<ThreadDto, String> {
+    run_db(state.db.clone(), move |db| {
+        db::threads::clear_external_cli_session(db, &thread_id, None)?;
+        db::threads::get_thread(db, &thread_id)?
+            .ok_or_else(|| anyhow::anyhow!("thread not found: {thread_id}"))
+    })
+    .await
+}
+
+pub(crate) async fn emit_thread_updated(
+    app: &tauri::AppHandle,
+    db: &db::Database,
+    thread_id: &str,
+) {
+    let thread = run_db(db.clone(), {
+        let thread_id = thread_id.to_string();
+        move |db| db::threads::get_thread(db, &thread_id)
+    })
+    .await;
+    match thread {
+        Ok(Some(thread)) => {
+            let _ = app.emit(
+                "thread-updated",
+                ThreadUpdatedEvent {
+                    thread_id: thread.id.clone(),
+                    workspace_id: thread.workspace_id.clone(),
+                    thread: Some(thread),
+                },
+            );
+        }
+        Ok(None) => {}
+        Err(error) => log::warn!("failed to load thread {thread_id} for thread-updated: {error}"),
+    }
+}
+
 #[tauri::command]
 pub async fn rename_thread(
     state: State<'_, AppState>,
@@ -1072,6 +1535,7 @@ pub async fn delete_thread(state: State<'_, AppState>, thread_id: String) -> Res
     })
     .await?;
     state.turns.finish(&thread_id).await;
+    state.turns.forget_context_usage(&thread_id).await;
     Ok(())
 }
 
@@ -1218,7 +1682,7 @@ pub async fn sync_thread_from_engine(
         return Ok(thread);
     };
 
-    let has_local_turn = state.turns.get(&thread_id).await.is_some();
+    let has_local_turn = state.turns.is_active(&thread_id);
     let has_active_remote_turn =
         !snapshot.active_flags.is_empty() || imported_messages_have_streaming_turn(&snapshot);
     let should_import_messages =
@@ -1307,7 +1771,7 @@ pub async fn fork_codex_thread(
     state: State<'_, AppState>,
     thread_id: String,
 ) -> Result<ThreadDto, String> {
-    if state.turns.get(&thread_id).await.is_some() {
+    if state.turns.is_active(&thread_id) {
         return Err("cannot fork a thread while a turn is still active".to_string());
     }
 
@@ -1348,6 +1812,86 @@ pub async fn fork_codex_thread(
     .await
 }
 
+/// Branches `thread_id` into a new local thread holding its history through
+/// `up_to_message_id`. The fork keeps the workspace, repo, engine, model and
+/// thread settings but has no engine thread, so its next turn opens a new
+/// engine session. The fork and its copied history commit together.
+#[tauri::command]
+pub async fn fork_thread(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    thread_id: String,
+    up_to_message_id: String,
+    new_title: Option<String>,
+) -> Result<ThreadDto, String> {
+    if state.turns.is_active(&thread_id) {
+        return Err("cannot fork a thread while a turn is still active".to_string());
+    }
+
+    let db = state.db.clone();
+    let forked = run_db(db.clone(), move |db| {
+        let mut conn = db.connect()?;
+        let tx = conn
+            .transaction()
+            .context("failed to start thread fork transaction")?;
+        let source = db::threads::get_thread_on(&tx, &thread_id)?
+            .ok_or_else(|| anyhow::anyhow!("thread not found: {thread_id}"))?;
+        let title = new_title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .unwrap_or(&source.title);
+        let metadata = fork_thread_metadata(source.engine_metadata.as_ref());
+        let created = db::threads::create_thread_with_metadata_on(
+            &tx,
+            &source.workspace_id,
+            source.repo_id.as_deref(),
+            &source.engine_id,
+            &source.model_id,
+            title,
+            metadata.as_ref(),
+        )?;
+        db::messages::clone_thread_messages_on(
+            &tx,
+            &source.id,
+            &created.id,
+            Some(&up_to_message_id),
+        )?;
+        db::threads::refresh_thread_message_stats_on(&tx, &created.id)?;
+        let forked = db::threads::get_thread_on(&tx, &created.id)?
+            .ok_or_else(|| anyhow::anyhow!("thread not found: {}", created.id))?;
+        tx.commit()
+            .context("failed to commit thread fork transaction")?;
+        Ok(forked)
+    })
+    .await?;
+
+    emit_thread_updated(&app, &db, &forked.id).await;
+    Ok(forked)
+}
+
+/// Thread settings carry over to a fork; the source's title lock, engine
+/// runtime state and attached sessions do not.
+fn fork_thread_metadata(existing: Option<&Value>) -> Option<Value> {
+    let mut metadata = existing?.clone();
+    let object = metadata.as_object_mut()?;
+    for key in [
+        "manualTitle",
+        "manualTitleUpdatedAt",
+        "codexThreadStatus",
+        "codexThreadActiveFlags",
+        "codexPreview",
+        "codexSyncRequired",
+        "codexSyncReason",
+        "codexTranscriptImported",
+        "opencodeRemoteSessionAttached",
+        db::threads::EXTERNAL_CLI_SESSION_KEY,
+    ] {
+        object.remove(key);
+    }
+    Some(metadata)
+}
+
 #[tauri::command]
 pub async fn rollback_codex_thread(
     state: State<'_, AppState>,
@@ -1357,7 +1901,7 @@ pub async fn rollback_codex_thread(
     if num_turns == 0 {
         return Err("rollback requires at least one turn".to_string());
     }
-    if state.turns.get(&thread_id).await.is_some() {
+    if state.turns.is_active(&thread_id) {
         return Err("cannot rollback a thread while a turn is still active".to_string());
     }
 
@@ -1432,7 +1976,7 @@ pub async fn compact_codex_thread(
     state: State<'_, AppState>,
     thread_id: String,
 ) -> Result<ThreadDto, String> {
-    if state.turns.get(&thread_id).await.is_some() {
+    if state.turns.is_active(&thread_id) {
         return Err("cannot compact a thread while a turn is still active".to_string());
     }
 
@@ -1643,6 +2187,7 @@ async fn set_thread_execution_policy_inner(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn set_thread_codex_config(
     state: State<'_, AppState>,
     thread_id: String,
@@ -1652,6 +2197,8 @@ pub async fn set_thread_codex_config(
     service_tier: Option<String>,
     update_output_schema: bool,
     output_schema: Option<Value>,
+    update_executable_override: bool,
+    executable_override: Option<String>,
 ) -> Result<ThreadDto, String> {
     set_thread_codex_config_inner(
         state.inner(),
@@ -1662,10 +2209,13 @@ pub async fn set_thread_codex_config(
         service_tier,
         update_output_schema,
         output_schema,
+        update_executable_override,
+        executable_override,
     )
     .await
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn set_thread_codex_config_inner(
     state: &AppState,
     thread_id: String,
@@ -1675,6 +2225,8 @@ async fn set_thread_codex_config_inner(
     service_tier: Option<String>,
     update_output_schema: bool,
     output_schema: Option<Value>,
+    update_executable_override: bool,
+    executable_override: Option<String>,
 ) -> Result<ThreadDto, String> {
     let db = state.db.clone();
     let thread = run_db(db.clone(), {
@@ -1703,6 +2255,11 @@ async fn set_thread_codex_config_inner(
     } else {
         None
     };
+    let normalized_executable_override = if update_executable_override {
+        normalize_thread_executable_override(executable_override)?
+    } else {
+        None
+    };
 
     let mut metadata = thread.engine_metadata.unwrap_or_else(|| json!({}));
     if !metadata.is_object() {
@@ -1742,6 +2299,17 @@ async fn set_thread_codex_config_inner(
                 }
             }
         }
+
+        if update_executable_override {
+            match normalized_executable_override {
+                Some(value) => {
+                    object.insert("engineExecutableOverride".to_string(), json!(value));
+                }
+                None => {
+                    object.remove("engineExecutableOverride");
+                }
+            }
+        }
     }
 
     run_db(db.clone(), {
@@ -1826,6 +2394,154 @@ async fn set_thread_opencode_config_inner(
     .ok_or_else(|| format!("thread not found after OpenCode config update: {thread_id}"))
 }
 
+/// Points a repo thread at one of the repo's linked worktrees, so its turns
+/// run there instead of in the main checkout. `None`, or the main checkout
+/// itself, clears it.
+#[tauri::command]
+pub async fn set_thread_worktree(
+    state: State<'_, AppState>,
+    thread_id: String,
+    worktree_path: Option<String>,
+) -> Result<ThreadDto, String> {
+    set_thread_worktree_inner(state.inner(), thread_id, worktree_path).await
+}
+
+async fn set_thread_worktree_inner(
+    state: &AppState,
+    thread_id: String,
+    worktree_path: Option<String>,
+) -> Result<ThreadDto, String> {
+    let db = state.db.clone();
+    let (thread, repo) = run_db(db.clone(), {
+        let thread_id = thread_id.clone();
+        move |db| {
+            let thread = db::threads::get_thread(db, &thread_id)?
+                .ok_or_else(|| anyhow::anyhow!("thread not found: {thread_id}"))?;
+            let repo = match thread.repo_id.as_deref() {
+                Some(repo_id) => db::repos::find_repo_by_id(db, repo_id)?,
+                None => None,
+            };
+            Ok((thread, repo))
+        }
+    })
+    .await?;
+    let Some(repo) = repo else {
+        return Err("only repo threads can target a worktree".to_string());
+    };
+
+    let worktree_path = worktree_path
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let worktree = match worktree_path {
+        Some(worktree_path) => {
+            let repo_path = repo.path.clone();
+            let found = tokio::task::spawn_blocking(move || {
+                worktree::find_worktree(&repo_path, &worktree_path)
+                    .map_err(err_to_string)?
+                    .ok_or_else(|| format!("`{worktree_path}` is not a worktree of {repo_path}"))
+            })
+            .await
+            .map_err(|error| error.to_string())??;
+            (!found.is_main).then_some(found.path)
+        }
+        None => None,
+    };
+
+    let mut metadata = thread.engine_metadata.unwrap_or_else(|| json!({}));
+    if !metadata.is_object() {
+        metadata = json!({});
+    }
+    if let Some(object) = metadata.as_object_mut() {
+        match worktree {
+            Some(path) => {
+                object.insert(db::threads::WORKTREE_PATH_KEY.to_string(), json!(path));
+            }
+            None => {
+                object.remove(db::threads::WORKTREE_PATH_KEY);
+            }
+        }
+    }
+
+    run_db(db.clone(), {
+        let thread_id = thread_id.clone();
+        let metadata = metadata.clone();
+        move |db| db::threads::update_engine_metadata(db, &thread_id, &metadata)
+    })
+    .await?;
+
+    run_db(db, {
+        let thread_id = thread_id.clone();
+        move |db| db::threads::get_thread(db, &thread_id)
+    })
+    .await?
+    .ok_or_else(|| format!("thread not found after worktree update: {thread_id}"))
+}
+
+/// Stores instructions Panes adds to every turn of a Codex or Claude thread,
+/// on top of the engine's own system prompt and any AGENTS.md/CLAUDE.md.
+/// `None` or blank clears them.
+#[tauri::command]
+pub async fn set_thread_custom_instructions(
+    state: State<'_, AppState>,
+    thread_id: String,
+    instructions: Option<String>,
+) -> Result<ThreadDto, String> {
+    set_thread_custom_instructions_inner(state.inner(), thread_id, instructions).await
+}
+
+async fn set_thread_custom_instructions_inner(
+    state: &AppState,
+    thread_id: String,
+    instructions: Option<String>,
+) -> Result<ThreadDto, String> {
+    let db = state.db.clone();
+    let thread = run_db(db.clone(), {
+        let thread_id = thread_id.clone();
+        move |db| db::threads::get_thread(db, &thread_id)
+    })
+    .await?
+    .ok_or_else(|| format!("thread not found: {thread_id}"))?;
+
+    if !matches!(thread.engine_id.as_str(), "codex" | "claude") {
+        return Err(format!(
+            "custom instructions are not supported for {} threads",
+            thread.engine_id
+        ));
+    }
+
+    let normalized_instructions = normalize_thread_custom_instructions(instructions)?;
+
+    let mut metadata = thread.engine_metadata.unwrap_or_else(|| json!({}));
+    if !metadata.is_object() {
+        metadata = json!({});
+    }
+
+    if let Some(object) = metadata.as_object_mut() {
+        match normalized_instructions {
+            Some(value) => {
+                object.insert("customInstructions".to_string(), json!(value));
+            }
+            None => {
+                object.remove("customInstructions");
+            }
+        }
+    }
+
+    run_db(db.clone(), {
+        let thread_id = thread_id.clone();
+        let metadata = metadata.clone();
+        move |db| db::threads::update_engine_metadata(db, &thread_id, &metadata)
+    })
+    .await?;
+
+    run_db(db, {
+        let thread_id = thread_id.clone();
+        move |db| db::threads::get_thread(db, &thread_id)
+    })
+    .await?
+    .ok_or_else(|| format!("thread not found after instructions update: {thread_id}"))
+}
+
 async fn validate_reasoning_effort(
     state: &AppState,
     engine_id: &str,
@@ -2021,8 +2737,12 @@ async fn build_codex_branch_context(
         }
     }
 
-    let writable_roots = match selected_repo.as_ref() {
-        Some(repo) => vec![repo.path.clone()],
+    let checkout_path = selected_repo
+        .as_ref()
+        .map(|repo| thread_checkout_path(&repo.path, thread.engine_metadata.as_ref()))
+        .transpose()?;
+    let writable_roots = match checkout_path.as_ref() {
+        Some(path) => vec![path.clone()],
         None => workspace_writable_roots
             .as_ref()
             .map(|resolution| resolution.roots.clone())
@@ -2040,10 +2760,7 @@ async fn build_codex_branch_context(
     )?;
 
     Ok((
-        selected_repo
-            .as_ref()
-            .map(|repo| repo.path.clone())
-            .unwrap_or(workspace_root),
+        checkout_path.unwrap_or(workspace_root),
         thread_last_model_id(thread.engine_metadata.as_ref())
             .unwrap_or_else(|| thread.model_id.clone()),
         SandboxPolicy {
@@ -2066,6 +2783,12 @@ async fn build_codex_branch_context(
             personality: thread_personality(thread.engine_metadata.as_ref()),
             output_schema: thread_output_schema(thread.engine_metadata.as_ref()),
             opencode_agent: thread_opencode_agent(thread.engine_metadata.as_ref()),
+            executable_override: if thread.engine_id == "codex" {
+                thread_executable_override(thread.engine_metadata.as_ref())
+            } else {
+                None
+            },
+            custom_instructions: thread_custom_instructions(thread.engine_metadata.as_ref()),
         },
     ))
 }
@@ -2109,7 +2832,7 @@ async fn create_codex_branch_thread(
             )?;
             db::threads::set_engine_thread_id(db, &created.id, &engine_thread_id)?;
             if clone_local_history {
-                db::messages::clone_thread_messages(db, &source_thread.id, &created.id)?;
+                db::messages::clone_thread_messages(db, &source_thread.id, &created.id, None)?;
                 if let Some(turns) = rollback_turns {
                     db::messages::drop_last_turns(db, &created.id, turns)?;
                 }
@@ -2478,6 +3201,24 @@ fn thread_opencode_agent(metadata: Option<&Value>) -> Option<String> {
         .map(ToOwned::to_owned)
 }
 
+fn thread_executable_override(metadata: Option<&Value>) -> Option<PathBuf> {
+    metadata
+        .and_then(|value| value.get("engineExecutableOverride"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+fn thread_custom_instructions(metadata: Option<&Value>) -> Option<String> {
+    metadata
+        .and_then(|value| value.get("customInstructions"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToOwned::to_owned)
+}
+
 fn map_codex_thread_status_to_local(
     raw_status: Option<&str>,
     active_flags: &[String],
@@ -2664,6 +3405,36 @@ fn normalize_thread_output_schema(value: Option<Value>) -> Result<Option<Value>,
     }
 }
 
+fn normalize_thread_custom_instructions(value: Option<String>) -> Result<Option<String>, String> {
+    let Some(value) = value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let length = value.chars().count();
+    if length > MAX_THREAD_CUSTOM_INSTRUCTIONS_CHARS {
+        return Err(format!(
+            "custom instructions are {length} characters; the limit is {MAX_THREAD_CUSTOM_INSTRUCTIONS_CHARS}"
+        ));
+    }
+    Ok(Some(value))
+}
+
+fn normalize_thread_executable_override(value: Option<String>) -> Result<Option<String>, String> {
+    let Some(value) = value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    executable_resolution::validate_executable_override(Path::new(&value))
+        .map_err(err_to_string)?;
+    Ok(Some(value))
+}
+
 fn normalize_thread_permission_profile(value: Option<Value>) -> Result<Option<Value>, String> {
     let Some(value) = value else {
         return Ok(None);
@@ -2906,6 +3677,7 @@ mod tests {
     use crate::{
         config::app_config::AppConfig,
         engines::EngineManager,
+        file_watcher::FileWatcherManager,
         git::{repo::FileTreeCache, watcher::GitWatcherManager},
         power::KeepAwakeManager,
         state::{AppState, TurnManager},
@@ -2917,14 +3689,16 @@ mod tests {
     fn test_app_state() -> AppState {
         let root = std::env::temp_dir().join(format!("panes-threads-cmd-{}", Uuid::new_v4()));
         fs::create_dir_all(&root).expect("failed to create temp root");
-        let db = crate::db::Database::open(root.join("workspaces.db"))
-            .expect("failed to create test database");
+        let db =
+            crate::db::Database::open(root.join("workspaces.db"), crate::db::PoolLimits::default())
+                .expect("failed to create test database");
         AppState {
             db,
             config: Arc::new(AppConfig::default()),
             config_write_lock: Arc::new(tokio::sync::Mutex::new(())),
-            engines: Arc::new(EngineManager::new()),
+            engines: Arc::new(EngineManager::default()),
             git_watchers: Arc::new(GitWatcherManager::default()),
+            file_watchers: Arc::new(FileWatcherManager::default()),
             terminals: Arc::new(TerminalManager::default()),
             notifications: Arc::new(TerminalNotificationManager::default()),
             keep_awake: Arc::new(KeepAwakeManager::new()),
@@ -2958,6 +3732,15 @@ mod tests {
         .expect("failed to create thread")
     }
 
+    #[test]
+    fn export_file_stem_slugs_titles_for_the_save_dialog() {
+        assert_eq!(
+            export_file_stem("Fix the build: CI/macOS!"),
+            "fix-the-build-ci-macos"
+        );
+        assert_eq!(export_file_stem("  ?? "), "thread");
+    }
+
     #[test]
     fn thread_allow_network_reads_explicit_override_in_full_access_mode() {
         let metadata = json!({
@@ -3174,6 +3957,7 @@ mod tests {
             default_branch: "main".to_string(),
             is_active: true,
             trust_level: TrustLevelDto::Standard,
+            detected_configs: Vec::new(),
         }];
 
         assert_eq!(
@@ -3302,6 +4086,25 @@ mod tests {
         assert_eq!(metadata.get("reasoningEffort"), Some(&json!("high")));
     }
 
+    #[test]
+    fn fork_thread_metadata_keeps_settings_and_drops_runtime_state() {
+        let metadata = fork_thread_metadata(Some(&json!({
+            "manualTitle": true,
+            "codexThreadStatus": "active",
+            "codexSyncRequired": true,
+            "externalCliSession": { "terminalSessionId": "term-1" },
+            "serviceTier": "fast",
+            "lastModelId": "gpt-5.4",
+        })))
+        .unwrap();
+
+        assert_eq!(
+            metadata,
+            json!({ "serviceTier": "fast", "lastModelId": "gpt-5.4" })
+        );
+        assert_eq!(fork_thread_metadata(None), None);
+    }
+
     #[test]
     fn clone_codex_branch_metadata_marks_local_transcript_as_imported() {
         let metadata = clone_codex_branch_metadata(
@@ -3334,12 +4137,14 @@ mod tests {
             engine_metadata: Some(json!({
                 "codexSyncRequired": false,
             })),
+            reasoning_effort: None,
             title: "Thread".to_string(),
             status: ThreadStatusDto::Idle,
             message_count: 2,
             total_tokens: 0,
             created_at: "2026-03-13T00:00:00Z".to_string(),
             last_activity_at: "2026-03-13T00:00:00Z".to_string(),
+            engine_capabilities: None,
         };
 
         assert!(should_clone_local_branch_history(&thread));
@@ -3753,6 +4558,8 @@ mod tests {
                     "summary": { "type": "string" }
                 }
             })),
+            false,
+            None,
         )
         .await
         .expect("expected codex config update to succeed");
@@ -3787,6 +4594,8 @@ mod tests {
             None,
             false,
             None,
+            false,
+            None,
         )
         .await
         .expect_err("expected non-codex thread to be rejected");
@@ -3794,6 +4603,209 @@ mod tests {
         assert!(error.contains("Codex thread config is only available for Codex threads"));
     }
 
+    #[tokio::test]
+    async fn set_thread_codex_config_validates_and_clears_executable_override() {
+        let state = test_app_state();
+        let thread = test_thread(&state, "codex", "gpt-5.4");
+        let missing = std::env::temp_dir().join("panes-missing-codex-build");
+
+        let error = set_thread_codex_config_inner(
+            &state,
+            thread.id.clone(),
+            false,
+            None,
+            false,
+            None,
+            false,
+            None,
+            true,
+            Some(missing.to_string_lossy().to_string()),
+        )
+        .await
+        .expect_err("expected a missing executable to be rejected");
+        assert!(error.contains("does not exist or is not a file"));
+
+        let executable = std::env::current_exe().expect("test binary path");
+        let updated = set_thread_codex_config_inner(
+            &state,
+            thread.id.clone(),
+            false,
+            None,
+            false,
+            None,
+            false,
+            None,
+            true,
+            Some(format!("  {}  ", executable.display())),
+        )
+        .await
+        .expect("expected the override to be stored");
+        assert_eq!(
+            thread_executable_override(updated.engine_metadata.as_ref()),
+            Some(executable)
+        );
+
+        let cleared = set_thread_codex_config_inner(
+            &state,
+            thread.id.clone(),
+            false,
+            None,
+            false,
+            None,
+            false,
+            None,
+            true,
+            None,
+        )
+        .await
+        .expect("expected the override to be cleared");
+        assert_eq!(
+            thread_executable_override(cleared.engine_metadata.as_ref()),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn set_thread_custom_instructions_caps_length_and_clears_blank_values() {
+        let state = test_app_state();
+        let thread = test_thread(&state, "claude", "sonnet");
+
+        let error = set_thread_custom_instructions_inner(
+            &state,
+            thread.id.clone(),
+            Some("x".repeat(MAX_THREAD_CUSTOM_INSTRUCTIONS_CHARS + 1)),
+        )
+        .await
+        .expect_err("expected oversized instructions to be rejected");
+        assert!(error.contains("the limit is 8000"));
+
+        let updated = set_thread_custom_instructions_inner(
+            &state,
+            thread.id.clone(),
+            Some("  Always use pnpm.\n".to_string()),
+        )
+        .await
+        .expect("expected instructions to be stored");
+        assert_eq!(
+            thread_custom_instructions(updated.engine_metadata.as_ref()).as_deref(),
+            Some("Always use pnpm.")
+        );
+
+        let cleared =
+            set_thread_custom_instructions_inner(&state, thread.id.clone(), Some("  ".to_string()))
+                .await
+                .expect("expected blank instructions to clear the setting");
+        assert_eq!(
+            thread_custom_instructions(cleared.engine_metadata.as_ref()),
+            None
+        );
+
+        let opencode = test_thread(&state, "opencode", "gpt-5.4");
+        let error = set_thread_custom_instructions_inner(
+            &state,
+            opencode.id,
+            Some("Always use pnpm.".to_string()),
+        )
+        .await
+        .expect_err("expected unsupported engines to be rejected");
+        assert!(error.contains("opencode"));
+    }
+
+    #[test]
+    fn set_thread_worktree_accepts_only_linked_worktrees_of_the_thread_repo() {
+        // Holds the env lock so git is not spawned while another test points
+        // PATH at an empty dir.
+        let _env_guard = crate::process_utils::test_env_lock()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let state = test_app_state();
+        let workspace = test_workspace(&state);
+        let root = std::path::PathBuf::from(&workspace.root_path);
+        let repo_dir = root.join("repo");
+        fs::create_dir_all(&repo_dir).expect("create repo dir");
+        let repo_path = repo_dir.to_string_lossy().to_string();
+        let feature_path = root.join("feature").to_string_lossy().to_string();
+        let git = |args: &[&str]| {
+            crate::git::cli_fallback::run_git(&repo_path, args).expect("git");
+        };
+        git(&["init", "--initial-branch=main"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        fs::write(repo_dir.join("README.md"), "hello\n").expect("write readme");
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "init"]);
+        git(&["worktree", "add", "-b", "feature", &feature_path]);
+        fs::create_dir_all(root.join("elsewhere")).expect("create plain dir");
+
+        let repo = crate::db::repos::upsert_repo(
+            &state.db,
+            &workspace.id,
+            "repo",
+            &repo_path,
+            "main",
+            true,
+        )
+        .expect("register repo");
+        let thread = crate::db::threads::create_thread(
+            &state.db,
+            &workspace.id,
+            Some(&repo.id),
+            "codex",
+            "gpt-5.3-codex",
+            "Worktree",
+        )
+        .expect("create thread");
+        let workspace_thread = test_thread(&state, "codex", "gpt-5.3-codex");
+
+        let runtime = tokio::runtime::Runtime::new().expect("runtime should build");
+        runtime.block_on(async {
+            let set =
+                |path: Option<String>| set_thread_worktree_inner(&state, thread.id.clone(), path);
+
+            let error = set(Some(root.join("elsewhere").to_string_lossy().to_string()))
+                .await
+                .expect_err("a plain directory is not a worktree");
+            assert!(error.contains("is not a worktree of"));
+
+            let updated = set(Some(feature_path.clone()))
+                .await
+                .expect("linked worktree should be accepted");
+            let checkout = thread_checkout_path(&repo.path, updated.engine_metadata.as_ref())
+                .expect("worktree exists");
+            assert_ne!(checkout, repo.path);
+            assert!(crate::git::worktree::find_worktree(&repo_path, &checkout)
+                .expect("list worktrees")
+                .is_some_and(|found| found.branch.as_deref() == Some("feature")));
+
+            let cleared = set(Some(repo_path.clone()))
+                .await
+                .expect("main checkout should clear the setting");
+            assert_eq!(
+                thread_checkout_path(&repo.path, cleared.engine_metadata.as_ref()),
+                Ok(repo.path.clone())
+            );
+
+            let updated = set(Some(feature_path.clone()))
+                .await
+                .expect("linked worktree should be accepted again");
+            fs::remove_dir_all(&feature_path).expect("remove worktree dir");
+            let error = thread_checkout_path(&repo.path, updated.engine_metadata.as_ref())
+                .expect_err("vanished worktree should be reported");
+            assert!(error.contains("no longer exists"));
+
+            let error = set_thread_worktree_inner(
+                &state,
+                workspace_thread.id.clone(),
+                Some(feature_path.clone()),
+            )
+            .await
+            .expect_err("workspace threads have no repo");
+            assert_eq!(error, "only repo threads can target a worktree");
+        });
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[tokio::test]
     async fn set_thread_opencode_config_persists_agent() {
         let state = test_app_state();
@@ -0,0 +1,97 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use tauri::State;
+
+use crate::{
+    db::{self, usage::UsageQuery},
+    models::{EngineUsageLimitsDto, UsageGroupByDto, UsageSummaryDto},
+    state::AppState,
+};
+
+async fn run_db<T, F>(db: crate::db::Database, operation: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&crate::db::Database) -> anyhow::Result<T> + Send + 'static,
+{
+    let db = db.acquire_scope().await.map_err(err_to_string)?;
+    tokio::task::spawn_blocking(move || operation(&db))
+        .await
+        .map_err(|error| error.to_string())?
+        .map_err(err_to_string)
+}
+
+/// Token usage for a workspace between `since` (inclusive) and `until`
+/// (exclusive). Bounds are RFC 3339 timestamps or `YYYY-MM-DD` dates, which
+/// mean the start of that UTC day. Archived threads are left out unless
+/// `include_archived` is set.
+#[tauri::command]
+pub async fn get_usage_summary(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    since: Option<String>,
+    until: Option<String>,
+    group_by: Option<Vec<UsageGroupByDto>>,
+    include_archived: Option<bool>,
+) -> Result<UsageSummaryDto, String> {
+    let query = UsageQuery {
+        since: since.as_deref().map(parse_usage_bound).transpose()?,
+        until: until.as_deref().map(parse_usage_bound).transpose()?,
+        group_by: group_by.unwrap_or_default(),
+        include_archived: include_archived.unwrap_or(false),
+    };
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since > until {
+            return Err("usage range starts after it ends".to_string());
+        }
+    }
+    run_db(state.db.clone(), move |db| {
+        db::usage::usage_summary(db, &workspace_id, &query)
+    })
+    .await
+}
+
+fn parse_usage_bound(raw: &str) -> Result<DateTime<Utc>, String> {
+    let raw = raw.trim();
+    if let Ok(day) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return Ok(day.and_time(Default::default()).and_utc());
+    }
+    DateTime::parse_from_rfc3339(raw)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|_| format!("invalid usage bound: {raw}"))
+}
+
+/// Last rate limits `engine_id` reported, kept across restarts so usage
+/// indicators are filled before the next turn. `None` until one arrives.
+#[tauri::command]
+pub async fn get_usage_limits(
+    state: State<'_, AppState>,
+    engine_id: String,
+) -> Result<Option<EngineUsageLimitsDto>, String> {
+    run_db(state.db.clone(), move |db| {
+        db::usage_limits::get_engine_usage_limits(db, &engine_id)
+    })
+    .await
+}
+
+fn err_to_string(error: impl std::fmt::Display) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_bounds_accept_dates_and_rfc3339_timestamps() {
+        assert_eq!(
+            parse_usage_bound("2026-03-02").unwrap().to_rfc3339(),
+            "2026-03-02T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_usage_bound("2026-03-02T01:30:00-03:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2026-03-02T04:30:00+00:00"
+        );
+        assert!(parse_usage_bound("last week").is_err());
+    }
+}
@@ -1,11 +1,13 @@
+use std::path::{Path, PathBuf};
+
 use tauri::State;
 
 use crate::{
-    db, fs_ops,
+    agent_configs, commands, db, fs_ops,
     git::{multi_repo, repo},
     models::{
-        FileTreeEntryDto, FileTreePageDto, RepoDto, TrustLevelDto, WorkspaceDto,
-        WorkspaceGitSelectionStatusDto,
+        DetectedAgentConfigDto, FileTreeEntryDto, FileTreePageDto, RepoDto, ThreadDto,
+        TrustLevelDto, WorkspaceArchiveReportDto, WorkspaceDto, WorkspaceGitSelectionStatusDto,
     },
     state::AppState,
     workspace_startup::{
@@ -24,6 +26,7 @@ where
     T: Send + 'static,
     F: FnOnce(&crate::db::Database) -> anyhow::Result<T> + Send + 'static,
 {
+    let db = db.acquire_scope().await.map_err(err_to_string)?;
     tokio::task::spawn_blocking(move || operation(&db))
         .await
         .map_err(|error| error.to_string())?
@@ -83,7 +86,50 @@ pub async fn get_repos(
     workspace_id: String,
 ) -> Result<Vec<RepoDto>, String> {
     run_db(state.db.clone(), move |db| {
-        db::repos::get_repos(db, &workspace_id)
+        let mut repos = db::repos::get_repos(db, &workspace_id)?;
+        for repo in &mut repos {
+            let adopted = db::repos::get_adopted_agent_configs(db, &repo.id)?;
+            repo.detected_configs =
+                agent_configs::detect_agent_configs(Path::new(&repo.path), &adopted);
+        }
+        Ok(repos)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn detect_agent_configs(
+    state: State<'_, AppState>,
+    repo_id: String,
+) -> Result<Vec<DetectedAgentConfigDto>, String> {
+    run_db(state.db.clone(), move |db| {
+        let repo = db::repos::find_repo_by_id(db, &repo_id)?
+            .ok_or_else(|| anyhow::anyhow!("repo not found: {repo_id}"))?;
+        let adopted = db::repos::get_adopted_agent_configs(db, &repo.id)?;
+        Ok(agent_configs::detect_agent_configs(
+            Path::new(&repo.path),
+            &adopted,
+        ))
+    })
+    .await
+}
+
+/// Marks a detected Codex/Claude project file as in effect for the repo and
+/// returns the refreshed detection.
+#[tauri::command]
+pub async fn adopt_agent_config(
+    state: State<'_, AppState>,
+    repo_id: String,
+    path: String,
+) -> Result<Vec<DetectedAgentConfigDto>, String> {
+    run_db(state.db.clone(), move |db| {
+        let repo = db::repos::find_repo_by_id(db, &repo_id)?
+            .ok_or_else(|| anyhow::anyhow!("repo not found: {repo_id}"))?;
+        let repo_path = Path::new(&repo.path);
+        let mut adopted = db::repos::get_adopted_agent_configs(db, &repo.id)?;
+        agent_configs::adopt_agent_config(repo_path, &path, &mut adopted)?;
+        db::repos::set_adopted_agent_configs(db, &repo.id, &adopted)?;
+        Ok(agent_configs::detect_agent_configs(repo_path, &adopted))
     })
     .await
 }
@@ -100,6 +146,29 @@ pub async fn set_repo_trust_level(
     .await
 }
 
+#[tauri::command]
+pub async fn get_repo_protected_branches(
+    state: State<'_, AppState>,
+    repo_path: String,
+) -> Result<Vec<String>, String> {
+    run_db(state.db.clone(), move |db| {
+        db::repos::get_protected_branches(db, &repo_path)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_repo_protected_branches(
+    state: State<'_, AppState>,
+    repo_id: String,
+    patterns: Option<Vec<String>>,
+) -> Result<(), String> {
+    run_db(state.db.clone(), move |db| {
+        db::repos::set_repo_protected_branches(db, &repo_id, patterns)
+    })
+    .await
+}
+
 #[tauri::command]
 pub async fn set_repo_git_active(
     state: State<'_, AppState>,
@@ -149,32 +218,126 @@ pub async fn delete_workspace(
     state: State<'_, AppState>,
     workspace_id: String,
 ) -> Result<(), String> {
-    run_db(state.db.clone(), move |db| {
-        db::workspaces::delete_workspace(db, &workspace_id)
+    let (workspace, repos) = run_db(state.db.clone(), move |db| {
+        let workspace = db::workspaces::find_workspace_by_id(db, &workspace_id)?;
+        let repos = db::repos::get_repos(db, &workspace_id)?;
+        db::workspaces::delete_workspace(db, &workspace_id)?;
+        Ok((workspace, repos))
     })
-    .await
+    .await?;
+    stop_repo_watchers(state.inner(), &repos).await;
+    stop_file_watchers(state.inner(), workspace.as_ref(), &repos).await;
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn archive_workspace(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     workspace_id: String,
-) -> Result<(), String> {
-    run_db(state.db.clone(), move |db| {
-        db::workspaces::archive_workspace(db, &workspace_id)
+) -> Result<WorkspaceArchiveReportDto, String> {
+    let (workspace, repos, threads) = run_db(state.db.clone(), {
+        let workspace_id = workspace_id.clone();
+        move |db| {
+            db::workspaces::archive_workspace(db, &workspace_id)?;
+            Ok((
+                db::workspaces::find_workspace_by_id(db, &workspace_id)?,
+                db::repos::get_repos(db, &workspace_id)?,
+                db::threads::list_threads_for_workspace(db, &workspace_id)?,
+            ))
+        }
+    })
+    .await?;
+    state
+        .notifications
+        .set_workspace_archived(&workspace_id, true)
+        .await;
+
+    let stopped_watchers = stop_repo_watchers(state.inner(), &repos).await;
+    stop_file_watchers(state.inner(), workspace.as_ref(), &repos).await;
+    let cancelled_threads = interrupt_running_turns(state.inner(), &threads).await;
+    let closed_terminal_sessions = state
+        .terminals
+        .close_workspace(app.clone(), &workspace_id, true)
+        .await
+        .map_err(err_to_string)?;
+    state
+        .notifications
+        .clear_for_workspace(&app, &workspace_id)
+        .await;
+
+    Ok(WorkspaceArchiveReportDto {
+        workspace_id,
+        stopped_watchers,
+        closed_terminal_sessions,
+        cancelled_threads,
     })
-    .await
 }
 
 #[tauri::command]
 pub async fn restore_workspace(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     workspace_id: String,
 ) -> Result<WorkspaceDto, String> {
-    run_db(state.db.clone(), move |db| {
-        db::workspaces::restore_workspace(db, &workspace_id)
+    let (workspace, repos) = run_db(state.db.clone(), {
+        let workspace_id = workspace_id.clone();
+        move |db| {
+            let workspace = db::workspaces::restore_workspace(db, &workspace_id)?;
+            Ok((workspace, db::repos::get_repos(db, &workspace_id)?))
+        }
     })
-    .await
+    .await?;
+    state
+        .notifications
+        .set_workspace_archived(&workspace_id, false)
+        .await;
+
+    for repo in repos.into_iter().filter(|repo| repo.is_active) {
+        if let Err(error) =
+            commands::git::register_repo_watchers(&app, state.inner(), repo.path.clone()).await
+        {
+            log::warn!("failed to watch {} after restore: {error}", repo.path);
+            continue;
+        }
+        commands::git::spawn_repo_ready(&app, workspace_id.clone(), repo.path);
+    }
+
+    Ok(workspace)
+}
+
+/// Stops the watchers of each repo, returning the paths that had any.
+async fn stop_repo_watchers(state: &AppState, repos: &[RepoDto]) -> Vec<String> {
+    let mut stopped = Vec::new();
+    for repo in repos {
+        if state.git_watchers.unwatch_repo(&repo.path).await {
+            stopped.push(repo.path.clone());
+        }
+    }
+    stopped
+}
+
+/// Stops the editor's file watches under the workspace root or any of its
+/// repos.
+async fn stop_file_watchers(state: &AppState, workspace: Option<&WorkspaceDto>, repos: &[RepoDto]) {
+    let roots = workspace
+        .map(|workspace| PathBuf::from(&workspace.root_path))
+        .into_iter()
+        .chain(repos.iter().map(|repo| PathBuf::from(&repo.path)))
+        .collect::<Vec<_>>();
+    state.file_watchers.unwatch_within(&roots).await;
+}
+
+/// Interrupts the running turn of each thread, returning the ids of the
+/// threads that had one.
+async fn interrupt_running_turns(state: &AppState, threads: &[ThreadDto]) -> Vec<String> {
+    let mut cancelled = Vec::new();
+    for thread in threads {
+        if commands::chat::interrupt_running_turn(state, thread).await {
+            cancelled.push(thread.id.clone());
+        }
+    }
+    cancelled
 }
 
 #[tauri::command]
@@ -311,8 +474,11 @@ pub async fn list_workspace_dirs(
 ) -> Result<Vec<FileTreeEntryDto>, String> {
     run_db(state.db.clone(), move |db| {
         let workspace = load_workspace(db, &workspace_id)?;
-        let mut entries =
-            fs_ops::list_dir(&workspace.root_path, dir_path.as_deref().unwrap_or(""))?;
+        let mut entries = fs_ops::list_dir(
+            &workspace.root_path,
+            dir_path.as_deref().unwrap_or(""),
+            true,
+        )?;
         entries.retain(|entry| entry.is_dir);
         Ok(entries)
     })
@@ -8,12 +8,63 @@ use std::{
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
-use crate::runtime_env;
+use crate::{db, runtime_env};
 
 pub const DEFAULT_TERMINAL_FONT_SIZE: u32 = 12;
 pub const MIN_TERMINAL_FONT_SIZE: u32 = 8;
 pub const MAX_TERMINAL_FONT_SIZE: u32 = 32;
 pub const VALID_AUTONOMY_PRESETS: [&str; 4] = ["read-only", "ask", "auto", "full"];
+pub const DEFAULT_TERMINAL_MIN_EMIT_INTERVAL_MS: u64 = 16;
+pub const DEFAULT_TERMINAL_MAX_EMIT_BYTES: usize = 256 * 1024;
+pub const DEFAULT_TERMINAL_BUFFER_MAX_BYTES: usize = 2 * 1024 * 1024;
+pub const DEFAULT_TERMINAL_SCROLLBACK_MAX_BYTES: usize = 512 * 1024;
+pub const DEFAULT_TERMINAL_CLOSE_GRACE_PERIOD_MS: u64 = 2_000;
+pub const DEFAULT_TERMINAL_MAX_SESSIONS_PER_WORKSPACE: usize = 20;
+pub const DEFAULT_CONTEXT_BUDGET_THRESHOLD_PERCENT: u8 = 90;
+pub const DEFAULT_CONTEXT_ROLLOVER_THRESHOLD_PERCENT: u8 = 85;
+pub const DEFAULT_CONTEXT_ROLLOVER_SEED_MESSAGES: usize = 12;
+pub const DEFAULT_API_DIRECT_BASE_URL: &str = "http://localhost:11434/v1";
+pub const DEFAULT_CODEX_REGENERATE_PROMPT_TEMPLATE: &str = "Your previous answer to the \
+message below was discarded. Disregard it and answer the message again.\n\n{message}";
+const TERMINAL_MIN_EMIT_INTERVAL_MS: u64 = 4;
+const TERMINAL_MAX_EMIT_INTERVAL_MS: u64 = 200;
+const TERMINAL_MIN_EMIT_BYTES: usize = 4 * 1024;
+const TERMINAL_MIN_BUFFER_BYTES: usize = 1024 * 1024;
+const TERMINAL_MAX_BUFFER_BYTES: usize = 64 * 1024 * 1024;
+const TERMINAL_MIN_SCROLLBACK_BYTES: usize = 16 * 1024;
+const TERMINAL_MAX_SCROLLBACK_BYTES: usize = 16 * 1024 * 1024;
+const TERMINAL_MAX_CLOSE_GRACE_PERIOD_MS: u64 = 30_000;
+const TERMINAL_SESSIONS_PER_WORKSPACE_CEILING: usize = 200;
+// Leaves room for the one-minute warning before an idle session is closed.
+const TERMINAL_MIN_IDLE_TIMEOUT_SECS: u64 = 120;
+pub const DEFAULT_ENGINE_REQUEST_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_ENGINE_TURN_REQUEST_TIMEOUT_SECS: u64 = 600;
+// Floors keep a typo (or 0) from failing every request before the engine can
+// answer; the app-server handshake alone can take several seconds.
+const ENGINE_MIN_REQUEST_TIMEOUT_SECS: u64 = 5;
+const ENGINE_MIN_TURN_REQUEST_TIMEOUT_SECS: u64 = 30;
+const ENGINE_MIN_TURN_COMPLETION_INACTIVITY_TIMEOUT_SECS: u64 = 60;
+pub const DEFAULT_STREAM_COALESCE_MAX_CHARS: usize = 8_192;
+pub const DEFAULT_STREAM_DB_FLUSH_INTERVAL_MS: u64 = 250;
+pub const DEFAULT_TURN_EVENT_LOG_RETENTION_DAYS: u32 = 14;
+const STREAM_MIN_COALESCE_MAX_CHARS: usize = 64;
+const STREAM_MAX_COALESCE_MAX_CHARS: usize = 256 * 1024;
+const STREAM_MIN_DB_FLUSH_INTERVAL_MS: u64 = 50;
+const STREAM_MAX_DB_FLUSH_INTERVAL_MS: u64 = 10_000;
+pub const DEFAULT_GIT_WATCHER_DEBOUNCE_MS: u64 = 300;
+const GIT_WATCHER_MIN_DEBOUNCE_MS: u64 = 50;
+const GIT_WATCHER_MAX_DEBOUNCE_MS: u64 = 5_000;
+pub const DEFAULT_COMMIT_MESSAGE_DIFF_BUDGET_BYTES: usize = 32 * 1024;
+const COMMIT_MESSAGE_MIN_DIFF_BUDGET_BYTES: usize = 1024;
+const COMMIT_MESSAGE_MAX_DIFF_BUDGET_BYTES: usize = 512 * 1024;
+pub const DEFAULT_COMMIT_MESSAGE_TIMEOUT_SECS: u64 = 120;
+const COMMIT_MESSAGE_MIN_TIMEOUT_SECS: u64 = 10;
+const COMMIT_MESSAGE_MAX_TIMEOUT_SECS: u64 = 900;
+pub const DEFAULT_ATTACHMENT_MAX_FILE_SIZE_MB: u64 = 10;
+pub const DEFAULT_ATTACHMENT_MAX_TURN_SIZE_MB: u64 = 25;
+const ATTACHMENT_MAX_SIZE_MB_CEILING: u64 = 1024;
+pub const DEFAULT_EDITOR_PREVIEW_THRESHOLD_MB: u64 = 5;
+const EDITOR_PREVIEW_THRESHOLD_MB_CEILING: u64 = 1024;
 
 /// Clamp a requested terminal font size into the supported range.
 pub fn clamp_terminal_font_size(font_size: u32) -> u32 {
@@ -27,6 +78,15 @@ pub struct AppConfig {
     pub ui: UiConfig,
     pub debug: DebugConfig,
     pub power: PowerConfig,
+    pub database: DatabaseConfig,
+    pub codex: CodexConfig,
+    pub engine_timeouts: EngineTimeoutsConfig,
+    pub api_direct: ApiDirectConfig,
+    pub terminal: TerminalConfig,
+    pub context_budget: ContextBudgetConfig,
+    pub attachments: AttachmentsConfig,
+    pub git: GitConfig,
+    pub editor: EditorConfig,
     #[serde(skip_serializing_if = "HarnessesConfig::is_empty")]
     pub harnesses: HarnessesConfig,
 }
@@ -47,6 +107,12 @@ pub struct GeneralConfig {
     pub chat_notifications: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub terminal_notifications: Option<bool>,
+    /// Weekly desktop notification summarizing local usage metrics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_usage_summary: Option<bool>,
+    /// Mirror terminal output to disk so panes can repaint after a restart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal_persist_scrollback: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notification_sound: Option<String>,
     /// Autonomy preset applied to newly created chat threads
@@ -67,7 +133,21 @@ pub struct UiConfig {
 #[serde(default)]
 pub struct DebugConfig {
     pub persist_engine_event_logs: bool,
+    /// Write each turn's engine events to
+    /// `logs/turns/<thread_id>/<message_id>.jsonl` in the app data directory.
+    pub record_turn_event_logs: bool,
+    /// Turn event logs older than this are deleted on startup; 0 keeps them.
+    pub turn_event_log_retention_days: u32,
     pub max_action_output_chars: usize,
+    /// Record every terminal session's I/O under `logs/terminal/` in the app
+    /// data directory.
+    pub record_terminal_transcripts: bool,
+    /// Streamed text merged into one event before it is sent to the UI;
+    /// lower values make fast models render more smoothly.
+    pub stream_coalesce_max_chars: usize,
+    /// How often a streaming turn's message state is written to the
+    /// database; raise it on slow disks.
+    pub stream_db_flush_interval_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +164,226 @@ pub struct PowerConfig {
     pub prevent_closed_display_sleep: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Upper bound on concurrently checked-out sqlite connections.
+    pub max_connections: usize,
+    /// How long a caller waits for a free connection before failing with a
+    /// "database saturated" error.
+    pub acquire_timeout_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodexConfig {
+    /// Run one `codex app-server` per workspace root instead of sharing a
+    /// single process across all threads.
+    pub transport_per_workspace: bool,
+    /// Cap on per-workspace app-servers kept alive; idle ones are evicted
+    /// least-recently-used first.
+    pub max_workspace_transports: usize,
+    /// Prompt sent when an answer is regenerated. Codex keeps the discarded
+    /// answer in its server-side history, so this tells it to ignore that
+    /// attempt; `{message}` is replaced with the original message.
+    pub regenerate_prompt_template: String,
+    /// How long the `model/list` catalog is served from cache before it is
+    /// refreshed in the background.
+    pub model_cache_ttl_secs: u64,
+}
+
+/// Request deadlines for engine runtimes. Values below the documented
+/// minimums are raised to them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineTimeoutsConfig {
+    /// Ordinary requests such as thread, model and config reads. Minimum 5.
+    pub request_timeout_secs: u64,
+    /// Starting or steering a turn, which waits for the engine to accept it;
+    /// 0 waits indefinitely. Minimum 30 when set.
+    pub turn_request_timeout_secs: u64,
+    /// Fail a running turn after this long without any engine event; 0 (the
+    /// default) waits indefinitely. Minimum 60 when set.
+    pub turn_completion_inactivity_timeout_secs: u64,
+    /// Values that replace the ones above for one engine, keyed by engine id,
+    /// as in `[engine_timeouts.per_engine.codex]`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub per_engine: BTreeMap<String, EngineTimeoutOverrides>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct EngineTimeoutOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_request_timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_completion_inactivity_timeout_secs: Option<u64>,
+}
+
+/// OpenAI-compatible chat-completions server behind the `api` engine, such
+/// as a local Ollama instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiDirectConfig {
+    /// Base URL including the version segment; `/chat/completions` and
+    /// `/models` are appended to it.
+    pub base_url: String,
+    /// Environment variable holding a bearer token, for servers that need one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// Models offered when the server's `/models` list cannot be read.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+}
+
+/// Guardrail applied before a turn is sent to an engine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextBudgetConfig {
+    /// Share of the engine's input budget, in percent, a turn may fill before
+    /// it is refused.
+    pub threshold_percent: u8,
+    /// Approximate max input tokens keyed by engine id; replaces both the
+    /// built-in estimate and the engine-reported window.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub engine_max_input_tokens: BTreeMap<String, u64>,
+    /// Once the engine reports the thread past `rollover_threshold_percent`
+    /// of its context window, the next message starts a fresh engine session
+    /// seeded with recent history instead of growing the old one.
+    pub rollover_enabled: bool,
+    pub rollover_threshold_percent: u8,
+    /// Most recent messages whose text seeds the fresh session.
+    pub rollover_seed_messages: usize,
+}
+
+/// Size caps checked against the files on disk before a turn is sent.
+/// Engines may still refuse files below these with limits of their own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentsConfig {
+    pub max_file_size_mb: u64,
+    pub max_turn_size_mb: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// Files larger than this open as a read-only preview of their start
+    /// unless the read is forced.
+    pub preview_threshold_mb: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Repo metadata changes seen within this window are reported to the
+    /// UI as one `git-repo-changed` event.
+    pub watcher_debounce_ms: u64,
+    /// Staged diff bytes sent to the engine when generating a commit
+    /// message; the rest is summarized by file name.
+    pub commit_message_diff_budget_bytes: usize,
+    /// How long commit message generation waits for the engine.
+    pub commit_message_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Decoded output each live session keeps for repainting a remounted
+    /// terminal view.
+    pub scrollback_max_bytes: usize,
+    /// How long closing a terminal waits for the shell and its foreground
+    /// job to exit after SIGTERM/SIGHUP before killing it; 0 kills at once.
+    /// Unix only.
+    pub close_grace_period_ms: u64,
+    /// Open terminal sessions allowed per workspace; each one costs a PTY
+    /// plus a reader and an emitter thread.
+    pub max_sessions_per_workspace: usize,
+    /// Close sessions with no input or output for this long; 0 (the
+    /// default) keeps them until the workspace closes. Sessions running a
+    /// foreground job are never closed.
+    pub idle_timeout_secs: u64,
+    pub throttle: TerminalThrottleConfig,
+    /// Throttle overrides keyed by workspace id; fields left unset fall back
+    /// to `throttle`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub workspace_throttle: BTreeMap<String, TerminalThrottleOverride>,
+}
+
+/// How PTY output is coalesced before it is emitted to the webview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalThrottleConfig {
+    /// Minimum gap between two output emits for one session.
+    pub min_emit_interval_ms: u64,
+    /// Largest payload a single emit carries.
+    pub max_emit_bytes: usize,
+    /// Pending output kept between emits; the oldest output is dropped past
+    /// this.
+    pub buffer_max_bytes: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalThrottleOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_emit_interval_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_emit_bytes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffer_max_bytes: Option<usize>,
+}
+
+impl TerminalThrottleConfig {
+    /// Clamps every limit into a range the emitter can work with; the emit
+    /// size never exceeds the buffer. Zero falls back to the default, and
+    /// either correction is logged.
+    pub fn normalized(self) -> Self {
+        let defaults = Self::default();
+        let buffer_max_bytes = normalize_throttle_limit(
+            "buffer_max_bytes",
+            self.buffer_max_bytes,
+            defaults.buffer_max_bytes,
+            TERMINAL_MIN_BUFFER_BYTES,
+            TERMINAL_MAX_BUFFER_BYTES,
+        );
+        Self {
+            min_emit_interval_ms: normalize_throttle_limit(
+                "min_emit_interval_ms",
+                self.min_emit_interval_ms,
+                defaults.min_emit_interval_ms,
+                TERMINAL_MIN_EMIT_INTERVAL_MS,
+                TERMINAL_MAX_EMIT_INTERVAL_MS,
+            ),
+            max_emit_bytes: normalize_throttle_limit(
+                "max_emit_bytes",
+                self.max_emit_bytes,
+                defaults.max_emit_bytes.min(buffer_max_bytes),
+                TERMINAL_MIN_EMIT_BYTES,
+                buffer_max_bytes,
+            ),
+            buffer_max_bytes,
+        }
+    }
+}
+
+fn normalize_throttle_limit<T>(name: &str, value: T, default: T, min: T, max: T) -> T
+where
+    T: Copy + Default + Ord + std::fmt::Display,
+{
+    if value == T::default() {
+        log::warn!("terminal.throttle.{name} must be positive; using the default {default}");
+        return default;
+    }
+    let clamped = value.clamp(min, max);
+    if clamped != value {
+        log::warn!("terminal.throttle.{name} = {value} is outside {min}..={max}; using {clamped}");
+    }
+    clamped
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct HarnessesConfig {
@@ -110,6 +410,8 @@ impl Default for GeneralConfig {
             terminal_font_size: None,
             chat_notifications: None,
             terminal_notifications: None,
+            weekly_usage_summary: None,
+            terminal_persist_scrollback: None,
             notification_sound: None,
             default_autonomy_preset: None,
         }
@@ -155,7 +457,12 @@ impl Default for DebugConfig {
     fn default() -> Self {
         Self {
             persist_engine_event_logs: false,
+            record_turn_event_logs: false,
+            turn_event_log_retention_days: DEFAULT_TURN_EVENT_LOG_RETENTION_DAYS,
             max_action_output_chars: 20_000,
+            record_terminal_transcripts: false,
+            stream_coalesce_max_chars: DEFAULT_STREAM_COALESCE_MAX_CHARS,
+            stream_db_flush_interval_ms: DEFAULT_STREAM_DB_FLUSH_INTERVAL_MS,
         }
     }
 }
@@ -174,6 +481,155 @@ impl Default for PowerConfig {
     }
 }
 
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: db::DEFAULT_SQLITE_POOL_MAX_CONNECTIONS,
+            acquire_timeout_ms: db::DEFAULT_SQLITE_POOL_ACQUIRE_TIMEOUT.as_millis() as u64,
+        }
+    }
+}
+
+impl Default for CodexConfig {
+    fn default() -> Self {
+        Self {
+            transport_per_workspace: false,
+            max_workspace_transports: 4,
+            regenerate_prompt_template: DEFAULT_CODEX_REGENERATE_PROMPT_TEMPLATE.to_string(),
+            model_cache_ttl_secs: 300,
+        }
+    }
+}
+
+impl Default for EngineTimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_secs: DEFAULT_ENGINE_REQUEST_TIMEOUT_SECS,
+            turn_request_timeout_secs: DEFAULT_ENGINE_TURN_REQUEST_TIMEOUT_SECS,
+            turn_completion_inactivity_timeout_secs: 0,
+            per_engine: BTreeMap::new(),
+        }
+    }
+}
+
+impl EngineTimeoutsConfig {
+    /// These timeouts with `engine_id`'s overrides applied.
+    pub fn for_engine(&self, engine_id: &str) -> Self {
+        let overrides = self.per_engine.get(engine_id).cloned().unwrap_or_default();
+        Self {
+            request_timeout_secs: overrides
+                .request_timeout_secs
+                .unwrap_or(self.request_timeout_secs),
+            turn_request_timeout_secs: overrides
+                .turn_request_timeout_secs
+                .unwrap_or(self.turn_request_timeout_secs),
+            turn_completion_inactivity_timeout_secs: overrides
+                .turn_completion_inactivity_timeout_secs
+                .unwrap_or(self.turn_completion_inactivity_timeout_secs),
+            per_engine: BTreeMap::new(),
+        }
+    }
+
+    pub fn request_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(
+            self.request_timeout_secs
+                .max(ENGINE_MIN_REQUEST_TIMEOUT_SECS),
+        )
+    }
+
+    /// `None` when turn requests wait indefinitely.
+    pub fn turn_request_timeout(&self) -> Option<std::time::Duration> {
+        match self.turn_request_timeout_secs {
+            0 => None,
+            secs => Some(std::time::Duration::from_secs(
+                secs.max(ENGINE_MIN_TURN_REQUEST_TIMEOUT_SECS),
+            )),
+        }
+    }
+
+    /// `None` when running turns are never failed for inactivity.
+    pub fn turn_completion_inactivity_timeout(&self) -> Option<std::time::Duration> {
+        match self.turn_completion_inactivity_timeout_secs {
+            0 => None,
+            secs => Some(std::time::Duration::from_secs(
+                secs.max(ENGINE_MIN_TURN_COMPLETION_INACTIVITY_TIMEOUT_SECS),
+            )),
+        }
+    }
+}
+
+impl Default for ApiDirectConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_API_DIRECT_BASE_URL.to_string(),
+            api_key_env: None,
+            models: Vec::new(),
+        }
+    }
+}
+
+impl Default for ContextBudgetConfig {
+    fn default() -> Self {
+        Self {
+            threshold_percent: DEFAULT_CONTEXT_BUDGET_THRESHOLD_PERCENT,
+            engine_max_input_tokens: BTreeMap::new(),
+            rollover_enabled: false,
+            rollover_threshold_percent: DEFAULT_CONTEXT_ROLLOVER_THRESHOLD_PERCENT,
+            rollover_seed_messages: DEFAULT_CONTEXT_ROLLOVER_SEED_MESSAGES,
+        }
+    }
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        Self {
+            preview_threshold_mb: DEFAULT_EDITOR_PREVIEW_THRESHOLD_MB,
+        }
+    }
+}
+
+impl Default for AttachmentsConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size_mb: DEFAULT_ATTACHMENT_MAX_FILE_SIZE_MB,
+            max_turn_size_mb: DEFAULT_ATTACHMENT_MAX_TURN_SIZE_MB,
+        }
+    }
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            watcher_debounce_ms: DEFAULT_GIT_WATCHER_DEBOUNCE_MS,
+            commit_message_diff_budget_bytes: DEFAULT_COMMIT_MESSAGE_DIFF_BUDGET_BYTES,
+            commit_message_timeout_secs: DEFAULT_COMMIT_MESSAGE_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            scrollback_max_bytes: DEFAULT_TERMINAL_SCROLLBACK_MAX_BYTES,
+            close_grace_period_ms: DEFAULT_TERMINAL_CLOSE_GRACE_PERIOD_MS,
+            max_sessions_per_workspace: DEFAULT_TERMINAL_MAX_SESSIONS_PER_WORKSPACE,
+            idle_timeout_secs: 0,
+            throttle: TerminalThrottleConfig::default(),
+            workspace_throttle: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for TerminalThrottleConfig {
+    fn default() -> Self {
+        Self {
+            min_emit_interval_ms: DEFAULT_TERMINAL_MIN_EMIT_INTERVAL_MS,
+            max_emit_bytes: DEFAULT_TERMINAL_MAX_EMIT_BYTES,
+            buffer_max_bytes: DEFAULT_TERMINAL_BUFFER_MAX_BYTES,
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -181,6 +637,15 @@ impl Default for AppConfig {
             ui: UiConfig::default(),
             debug: DebugConfig::default(),
             power: PowerConfig::default(),
+            database: DatabaseConfig::default(),
+            codex: CodexConfig::default(),
+            engine_timeouts: EngineTimeoutsConfig::default(),
+            api_direct: ApiDirectConfig::default(),
+            terminal: TerminalConfig::default(),
+            context_budget: ContextBudgetConfig::default(),
+            attachments: AttachmentsConfig::default(),
+            git: GitConfig::default(),
+            editor: EditorConfig::default(),
             harnesses: HarnessesConfig::default(),
         }
     }
@@ -206,6 +671,139 @@ impl AppConfig {
         self.general.terminal_notifications.unwrap_or(false)
     }
 
+    pub fn weekly_usage_summary_enabled(&self) -> bool {
+        self.general.weekly_usage_summary.unwrap_or(false)
+    }
+
+    pub fn terminal_scrollback_persistence_enabled(&self) -> bool {
+        self.general.terminal_persist_scrollback.unwrap_or(false)
+    }
+
+    pub fn terminal_scrollback_max_bytes(&self) -> usize {
+        self.terminal
+            .scrollback_max_bytes
+            .clamp(TERMINAL_MIN_SCROLLBACK_BYTES, TERMINAL_MAX_SCROLLBACK_BYTES)
+    }
+
+    pub fn terminal_close_grace_period(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(
+            self.terminal
+                .close_grace_period_ms
+                .min(TERMINAL_MAX_CLOSE_GRACE_PERIOD_MS),
+        )
+    }
+
+    pub fn terminal_max_sessions_per_workspace(&self) -> usize {
+        self.terminal
+            .max_sessions_per_workspace
+            .clamp(1, TERMINAL_SESSIONS_PER_WORKSPACE_CEILING)
+    }
+
+    /// `None` when idle terminal sessions are kept open.
+    pub fn terminal_idle_timeout(&self) -> Option<std::time::Duration> {
+        match self.terminal.idle_timeout_secs {
+            0 => None,
+            secs => Some(std::time::Duration::from_secs(
+                secs.max(TERMINAL_MIN_IDLE_TIMEOUT_SECS),
+            )),
+        }
+    }
+
+    pub fn stream_coalesce_max_chars(&self) -> usize {
+        self.debug
+            .stream_coalesce_max_chars
+            .clamp(STREAM_MIN_COALESCE_MAX_CHARS, STREAM_MAX_COALESCE_MAX_CHARS)
+    }
+
+    pub fn stream_db_flush_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.debug.stream_db_flush_interval_ms.clamp(
+            STREAM_MIN_DB_FLUSH_INTERVAL_MS,
+            STREAM_MAX_DB_FLUSH_INTERVAL_MS,
+        ))
+    }
+
+    pub fn git_watcher_debounce(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(
+            self.git
+                .watcher_debounce_ms
+                .clamp(GIT_WATCHER_MIN_DEBOUNCE_MS, GIT_WATCHER_MAX_DEBOUNCE_MS),
+        )
+    }
+
+    pub fn commit_message_diff_budget_bytes(&self) -> usize {
+        self.git.commit_message_diff_budget_bytes.clamp(
+            COMMIT_MESSAGE_MIN_DIFF_BUDGET_BYTES,
+            COMMIT_MESSAGE_MAX_DIFF_BUDGET_BYTES,
+        )
+    }
+
+    pub fn commit_message_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.git.commit_message_timeout_secs.clamp(
+            COMMIT_MESSAGE_MIN_TIMEOUT_SECS,
+            COMMIT_MESSAGE_MAX_TIMEOUT_SECS,
+        ))
+    }
+
+    /// Per-file attachment cap in bytes; at least 1 MB.
+    pub fn attachment_max_file_bytes(&self) -> u64 {
+        self.attachments
+            .max_file_size_mb
+            .clamp(1, ATTACHMENT_MAX_SIZE_MB_CEILING)
+            * 1024
+            * 1024
+    }
+
+    /// Size in bytes above which the editor gets a preview instead of the
+    /// whole file; at least 1 MB.
+    pub fn editor_preview_threshold_bytes(&self) -> u64 {
+        self.editor
+            .preview_threshold_mb
+            .clamp(1, EDITOR_PREVIEW_THRESHOLD_MB_CEILING)
+            * 1024
+            * 1024
+    }
+
+    /// Per-turn attachment cap in bytes; never below the per-file cap.
+    pub fn attachment_max_turn_bytes(&self) -> u64 {
+        (self
+            .attachments
+            .max_turn_size_mb
+            .clamp(1, ATTACHMENT_MAX_SIZE_MB_CEILING)
+            * 1024
+            * 1024)
+            .max(self.attachment_max_file_bytes())
+    }
+
+    /// Effective output throttle for terminals in `workspace_id`.
+    pub fn terminal_throttle(&self, workspace_id: &str) -> TerminalThrottleConfig {
+        let base = self.terminal.throttle;
+        let Some(overrides) = self.terminal.workspace_throttle.get(workspace_id) else {
+            return base.normalized();
+        };
+        TerminalThrottleConfig {
+            min_emit_interval_ms: overrides
+                .min_emit_interval_ms
+                .unwrap_or(base.min_emit_interval_ms),
+            max_emit_bytes: overrides.max_emit_bytes.unwrap_or(base.max_emit_bytes),
+            buffer_max_bytes: overrides.buffer_max_bytes.unwrap_or(base.buffer_max_bytes),
+        }
+        .normalized()
+    }
+
+    /// Codex prompt for regenerating the answer to `message`. A template
+    /// without `{message}` is used as a preamble; a blank one sends the
+    /// message unchanged.
+    pub fn codex_regenerate_prompt(&self, message: &str) -> String {
+        let template = self.codex.regenerate_prompt_template.trim();
+        if template.is_empty() {
+            message.to_string()
+        } else if template.contains("{message}") {
+            template.replace("{message}", message)
+        } else {
+            format!("{template}\n\n{message}")
+        }
+    }
+
     /// Extra launch flags configured for a harness, or `None` when unset or
     /// blank.
     pub fn harness_launch_args(&self, harness_id: &str) -> Option<&str> {
@@ -254,7 +852,13 @@ impl AppConfig {
         }
 
         let raw = fs::read_to_string(&path)?;
-        let config = toml::from_str::<Self>(&raw).unwrap_or_default();
+        let config = toml::from_str::<Self>(&raw).unwrap_or_else(|error| {
+            log::warn!(
+                "invalid config at {}, using defaults: {error}",
+                path.display()
+            );
+            Self::default()
+        });
         Ok(config)
     }
 
@@ -339,9 +943,12 @@ fn replace_file(temp_path: &std::path::Path, path: &std::path::Path) -> std::io:
 
 #[cfg(test)]
 mod tests {
-    use std::fs;
+    use std::{fs, time::Duration};
 
-    use super::AppConfig;
+    use super::{
+        AppConfig, EngineTimeoutsConfig, TerminalThrottleConfig,
+        DEFAULT_ATTACHMENT_MAX_TURN_SIZE_MB, DEFAULT_TERMINAL_MAX_EMIT_BYTES,
+    };
     use uuid::Uuid;
 
     const APP_DATA_ENV_VARS: [&str; 4] = ["HOME", "USERPROFILE", "LOCALAPPDATA", "APPDATA"];
@@ -419,6 +1026,25 @@ max_action_output_chars = 20000
         assert!(!raw.contains("harnesses"));
     }
 
+    #[test]
+    fn attachment_caps_are_clamped_and_the_turn_cap_covers_one_file() {
+        let config = toml::from_str::<AppConfig>(
+            r#"
+[attachments]
+max_file_size_mb = 40
+max_turn_size_mb = 0
+"#,
+        )
+        .expect("config should deserialize");
+
+        assert_eq!(config.attachment_max_file_bytes(), 40 * 1024 * 1024);
+        assert_eq!(config.attachment_max_turn_bytes(), 40 * 1024 * 1024);
+        assert_eq!(
+            AppConfig::default().attachment_max_turn_bytes(),
+            DEFAULT_ATTACHMENT_MAX_TURN_SIZE_MB * 1024 * 1024
+        );
+    }
+
     #[test]
     fn harness_launch_args_roundtrip_and_lookup() {
         let mut config = AppConfig::default();
@@ -615,4 +1241,130 @@ keep_awake_enabled = true
         assert_eq!(config.power.session_duration_secs, None);
         assert!(!config.power.prevent_closed_display_sleep);
     }
+
+    #[test]
+    fn terminal_throttle_layers_workspace_overrides_over_defaults() {
+        let raw = r#"
+[terminal.throttle]
+min_emit_interval_ms = 33
+
+[terminal.workspace_throttle.ws-remote]
+max_emit_bytes = 64
+buffer_max_bytes = 8388608
+"#;
+
+        let config = toml::from_str::<AppConfig>(raw).expect("terminal config should deserialize");
+
+        assert_eq!(
+            AppConfig::default().terminal_throttle("any"),
+            TerminalThrottleConfig::default()
+        );
+        let global = config.terminal_throttle("ws-local");
+        assert_eq!(global.min_emit_interval_ms, 33);
+        assert_eq!(global.max_emit_bytes, DEFAULT_TERMINAL_MAX_EMIT_BYTES);
+
+        let remote = config.terminal_throttle("ws-remote");
+        assert_eq!(remote.min_emit_interval_ms, 33);
+        assert_eq!(remote.max_emit_bytes, 4 * 1024, "clamped to the minimum");
+        assert_eq!(remote.buffer_max_bytes, 8 * 1024 * 1024);
+    }
+
+    #[test]
+    fn terminal_throttle_clamps_out_of_range_values_and_defaults_zeroes() {
+        let clamped = TerminalThrottleConfig {
+            min_emit_interval_ms: 1,
+            max_emit_bytes: 256 * 1024 * 1024,
+            buffer_max_bytes: 256 * 1024 * 1024,
+        }
+        .normalized();
+        assert_eq!(clamped.min_emit_interval_ms, 4);
+        assert_eq!(clamped.buffer_max_bytes, 64 * 1024 * 1024);
+        assert_eq!(clamped.max_emit_bytes, 64 * 1024 * 1024);
+
+        let slow = TerminalThrottleConfig {
+            min_emit_interval_ms: 5_000,
+            max_emit_bytes: 1024,
+            buffer_max_bytes: 1024,
+        }
+        .normalized();
+        assert_eq!(slow.min_emit_interval_ms, 200);
+        assert_eq!(slow.buffer_max_bytes, 1024 * 1024);
+        assert_eq!(slow.max_emit_bytes, 4 * 1024);
+
+        let zeroes = TerminalThrottleConfig {
+            min_emit_interval_ms: 0,
+            max_emit_bytes: 0,
+            buffer_max_bytes: 0,
+        }
+        .normalized();
+        assert_eq!(zeroes, TerminalThrottleConfig::default());
+    }
+
+    #[test]
+    fn codex_regenerate_prompt_fills_or_prefixes_the_template() {
+        let mut config = AppConfig::default();
+        let prompt = config.codex_regenerate_prompt("fix the test");
+        assert!(prompt.starts_with("Your previous answer"));
+        assert!(prompt.ends_with("\n\nfix the test"));
+
+        config.codex.regenerate_prompt_template = "Try again.".to_string();
+        assert_eq!(
+            config.codex_regenerate_prompt("fix the test"),
+            "Try again.\n\nfix the test"
+        );
+        config.codex.regenerate_prompt_template = "  ".to_string();
+        assert_eq!(
+            config.codex_regenerate_prompt("fix the test"),
+            "fix the test"
+        );
+    }
+
+    #[test]
+    fn engine_timeouts_raise_values_below_the_minimums() {
+        let config = toml::from_str::<AppConfig>(
+            "[engine_timeouts]\nrequest_timeout_secs = 0\nturn_request_timeout_secs = 1\nturn_completion_inactivity_timeout_secs = 2\n",
+        )
+        .expect("config should parse");
+        let timeouts = &config.engine_timeouts;
+        assert_eq!(timeouts.request_timeout(), Duration::from_secs(5));
+        assert_eq!(
+            timeouts.turn_request_timeout(),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            timeouts.turn_completion_inactivity_timeout(),
+            Some(Duration::from_secs(60))
+        );
+
+        let defaults = EngineTimeoutsConfig::default();
+        assert_eq!(defaults.request_timeout(), Duration::from_secs(30));
+        assert_eq!(
+            defaults.turn_request_timeout(),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(defaults.turn_completion_inactivity_timeout(), None);
+    }
+
+    #[test]
+    fn engine_timeouts_apply_per_engine_overrides_and_zero_disables() {
+        let config = toml::from_str::<AppConfig>(
+            "[engine_timeouts]\nturn_request_timeout_secs = 900\n\n[engine_timeouts.per_engine.codex]\nturn_request_timeout_secs = 0\nturn_completion_inactivity_timeout_secs = 300\n",
+        )
+        .expect("config should parse");
+
+        let codex = config.engine_timeouts.for_engine("codex");
+        assert_eq!(codex.turn_request_timeout(), None);
+        assert_eq!(
+            codex.turn_completion_inactivity_timeout(),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(codex.request_timeout(), Duration::from_secs(30));
+
+        let claude = config.engine_timeouts.for_engine("claude");
+        assert_eq!(
+            claude.turn_request_timeout(),
+            Some(Duration::from_secs(900))
+        );
+        assert_eq!(claude.turn_completion_inactivity_timeout(), None);
+    }
 }
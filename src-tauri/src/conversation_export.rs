@@ -0,0 +1,749 @@
+//! Renders a thread as Markdown or JSON for sharing outside the app.
+//! Markdown messages are written under `## User` / `## Assistant` headings
+//! so the file can be brought back in through `conversation_import`.
+
+use std::fmt::Write as _;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::Serialize;
+
+use crate::{
+    blocks::{ActionBlockResult, ActionOutputChunk, ContentBlock},
+    db::messages::parse_message_timestamp,
+    models::{MessageDto, MessageStatusDto, ThreadDto, TokenUsageDto},
+};
+
+/// Bumped whenever a field of the JSON export changes meaning or goes away.
+pub const THREAD_EXPORT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExportOptions {
+    pub include_thinking: bool,
+    pub include_actions: bool,
+    /// Action output kept per action; the rest is replaced by a marker
+    /// saying how much was cut.
+    pub max_action_output_chars: usize,
+    pub offset: FixedOffset,
+    pub exported_at: DateTime<Utc>,
+}
+
+/// Renders `messages` (in thread order) with a header naming the thread's
+/// engine, model and timestamps. Superseded regenerations are left out so
+/// the export reads as the conversation the user ended up with.
+pub fn render_thread_markdown(
+    thread: &ThreadDto,
+    messages: &[MessageDto],
+    options: &ExportOptions,
+) -> String {
+    let offset = options.offset;
+    let mut out = String::new();
+    let title = thread.title.trim();
+    let _ = writeln!(
+        out,
+        "# {}\n",
+        if title.is_empty() {
+            "Untitled thread"
+        } else {
+            title
+        }
+    );
+    let _ = writeln!(out, "- Engine: {}", thread.engine_id);
+    let _ = writeln!(out, "- Model: {}", thread.model_id);
+    let _ = writeln!(
+        out,
+        "- Created: {}",
+        local_timestamp(&thread.created_at, offset)
+    );
+    let _ = writeln!(
+        out,
+        "- Last activity: {}",
+        local_timestamp(&thread.last_activity_at, offset)
+    );
+    let _ = writeln!(
+        out,
+        "- Exported: {}\n",
+        format_local(options.exported_at, offset)
+    );
+
+    for message in exported_messages(messages) {
+        let heading = match message.role.as_str() {
+            "user" => "User",
+            "assistant" => "Assistant",
+            _ => continue,
+        };
+        let _ = writeln!(out, "## {heading}\n");
+        let mut meta = local_timestamp(&message.created_at, offset);
+        if message.role == "assistant" {
+            if let Some(model) = message.turn_model_id.as_deref() {
+                let _ = write!(meta, " · {model}");
+            }
+        }
+        let _ = writeln!(out, "_{meta}_\n");
+
+        for block in exported_blocks(message, options) {
+            render_block(&mut out, &block);
+        }
+    }
+    out
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadExportJson<'a> {
+    schema_version: u32,
+    exported_at: String,
+    thread: ThreadExportHeaderJson<'a>,
+    messages: Vec<MessageExportJson<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadExportHeaderJson<'a> {
+    id: &'a str,
+    title: &'a str,
+    engine_id: &'a str,
+    model_id: &'a str,
+    created_at: String,
+    last_activity_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageExportJson<'a> {
+    id: &'a str,
+    /// Position in the thread; see `messages.seq`.
+    seq: i64,
+    role: &'a str,
+    status: &'a MessageStatusDto,
+    created_at: String,
+    engine_id: Option<&'a str>,
+    model_id: Option<&'a str>,
+    token_usage: Option<&'a TokenUsageDto>,
+    /// Same shape as the blocks the app stores, after the export's filters
+    /// and truncation.
+    blocks: Vec<ContentBlock>,
+}
+
+/// Renders the same selection as [`render_thread_markdown`] as pretty JSON.
+/// Timestamps are RFC 3339 in the export's offset.
+pub fn render_thread_json(
+    thread: &ThreadDto,
+    messages: &[MessageDto],
+    options: &ExportOptions,
+) -> anyhow::Result<String> {
+    let offset = options.offset;
+    let export = ThreadExportJson {
+        schema_version: THREAD_EXPORT_SCHEMA_VERSION,
+        exported_at: rfc3339_local(options.exported_at, offset),
+        thread: ThreadExportHeaderJson {
+            id: &thread.id,
+            title: &thread.title,
+            engine_id: &thread.engine_id,
+            model_id: &thread.model_id,
+            created_at: rfc3339_timestamp(&thread.created_at, offset),
+            last_activity_at: rfc3339_timestamp(&thread.last_activity_at, offset),
+        },
+        messages: exported_messages(messages)
+            .map(|message| MessageExportJson {
+                id: &message.id,
+                seq: message.seq,
+                role: &message.role,
+                status: &message.status,
+                created_at: rfc3339_timestamp(&message.created_at, offset),
+                engine_id: message.turn_engine_id.as_deref(),
+                model_id: message.turn_model_id.as_deref(),
+                token_usage: message.token_usage.as_ref(),
+                blocks: exported_blocks(message, options),
+            })
+            .collect(),
+    };
+    let mut json = serde_json::to_string_pretty(&export)?;
+    json.push('\n');
+    Ok(json)
+}
+
+fn exported_messages(messages: &[MessageDto]) -> impl Iterator<Item = &MessageDto> {
+    messages.iter().filter(|message| !message.superseded)
+}
+
+/// The message's blocks with thinking and actions dropped as requested and
+/// action output cut to the configured size. Messages stored before blocks
+/// existed come back as one text block.
+fn exported_blocks(message: &MessageDto, options: &ExportOptions) -> Vec<ContentBlock> {
+    let blocks = message
+        .blocks
+        .as_ref()
+        .map(ContentBlock::parse_list)
+        .unwrap_or_default();
+    if blocks.is_empty() {
+        return message
+            .content
+            .iter()
+            .filter(|content| !content.trim().is_empty())
+            .map(|content| ContentBlock::Text {
+                content: content.clone(),
+                plan_mode: None,
+                is_steer: None,
+            })
+            .collect();
+    }
+    blocks
+        .into_iter()
+        .filter_map(|block| match block {
+            ContentBlock::Thinking { .. } if !options.include_thinking => None,
+            ContentBlock::Action { .. } if !options.include_actions => None,
+            ContentBlock::Action {
+                action_id,
+                engine_action_id,
+                action_type,
+                summary,
+                details,
+                output_chunks,
+                status,
+                mut result,
+            } => {
+                let max_chars = options.max_action_output_chars;
+                let output_chunks = truncate_output_chunks(output_chunks, max_chars);
+                if let Some(result) = result.as_mut() {
+                    result.output = result
+                        .output
+                        .take()
+                        .map(|output| truncate_with_marker(&output, max_chars));
+                }
+                Some(ContentBlock::Action {
+                    action_id,
+                    engine_action_id,
+                    action_type,
+                    summary,
+                    details,
+                    output_chunks,
+                    status,
+                    result,
+                })
+            }
+            block => Some(block),
+        })
+        .collect()
+}
+
+/// Keeps whole chunks while they fit and cuts the first one that doesn't,
+/// so stream boundaries survive in the JSON export.
+fn truncate_output_chunks(
+    chunks: Vec<ActionOutputChunk>,
+    max_chars: usize,
+) -> Vec<ActionOutputChunk> {
+    let total: usize = chunks
+        .iter()
+        .map(|chunk| chunk.content.chars().count())
+        .sum();
+    if total <= max_chars {
+        return chunks;
+    }
+    let mut kept = Vec::new();
+    let mut remaining = max_chars;
+    for chunk in chunks {
+        let len = chunk.content.chars().count();
+        if len <= remaining {
+            remaining -= len;
+            kept.push(chunk);
+            continue;
+        }
+        let mut content: String = chunk.content.chars().take(remaining).collect();
+        content.push_str(&truncation_marker(total - max_chars));
+        kept.push(ActionOutputChunk {
+            stream: chunk.stream,
+            content,
+        });
+        break;
+    }
+    kept
+}
+
+fn truncate_with_marker(value: &str, max_chars: usize) -> String {
+    let total = value.chars().count();
+    if total <= max_chars {
+        return value.to_string();
+    }
+    let mut output: String = value.chars().take(max_chars).collect();
+    output.push_str(&truncation_marker(total - max_chars));
+    output
+}
+
+fn truncation_marker(cut_chars: usize) -> String {
+    format!("\n... [truncated {cut_chars} characters]")
+}
+
+fn render_block(out: &mut String, block: &ContentBlock) {
+    match block {
+        ContentBlock::Text { content, .. } => push_paragraph(out, content),
+        ContentBlock::Thinking { content, .. } => {
+            if content.trim().is_empty() {
+                return;
+            }
+            out.push_str("<details>\n<summary>Thinking</summary>\n\n");
+            push_paragraph(out, content);
+            out.push_str("</details>\n\n");
+        }
+        ContentBlock::Diff { diff, scope } => {
+            let _ = writeln!(out, "**Diff** ({scope})\n");
+            push_fenced(out, "diff", diff);
+        }
+        ContentBlock::Action {
+            action_type,
+            summary,
+            output_chunks,
+            status,
+            result,
+            ..
+        } => render_action(out, action_type, summary, output_chunks, status, result),
+        ContentBlock::Approval {
+            action_type,
+            summary,
+            status,
+            decision,
+            ..
+        } => {
+            let outcome = decision.as_deref().unwrap_or(status);
+            let _ = writeln!(
+                out,
+                "> **Approval** ({action_type}): {} — {outcome}\n",
+                single_line(summary)
+            );
+        }
+        ContentBlock::Notice { title, message, .. } => {
+            let _ = writeln!(
+                out,
+                "> **{}** {}\n",
+                single_line(title),
+                single_line(message)
+            );
+        }
+        ContentBlock::Error { message } => {
+            let _ = writeln!(out, "> **Error:** {}\n", single_line(message));
+        }
+        ContentBlock::Attachment {
+            file_name,
+            file_path,
+            size_bytes,
+            ..
+        } => {
+            let _ = writeln!(
+                out,
+                "📎 {} (`{file_path}`, {size_bytes} bytes)\n",
+                single_line(file_name)
+            );
+        }
+        ContentBlock::Skill { name, .. } => {
+            let _ = writeln!(out, "_Skill: {}_\n", single_line(name));
+        }
+        ContentBlock::Mention { name, path } => {
+            let _ = writeln!(out, "_Mentioned {} (`{path}`)_\n", single_line(name));
+        }
+        ContentBlock::Unknown { .. } => {}
+    }
+}
+
+/// One summary line, then the output and any error collapsed under a
+/// `<details>` so long logs don't bury the conversation. Diffs stay open.
+fn render_action(
+    out: &mut String,
+    action_type: &str,
+    summary: &str,
+    output_chunks: &[ActionOutputChunk],
+    status: &str,
+    result: &Option<ActionBlockResult>,
+) {
+    let _ = write!(
+        out,
+        "**{action_type}**: {} — {status}",
+        single_line(summary)
+    );
+    if let Some(result) = result {
+        let _ = write!(out, " ({} ms)", result.duration_ms);
+    }
+    out.push_str("\n\n");
+
+    let streamed: String = output_chunks
+        .iter()
+        .map(|chunk| chunk.content.as_str())
+        .collect();
+    let output = if streamed.trim().is_empty() {
+        result
+            .as_ref()
+            .and_then(|result| result.output.clone())
+            .unwrap_or_default()
+    } else {
+        streamed
+    };
+    let error = result
+        .as_ref()
+        .and_then(|result| result.error.as_deref())
+        .filter(|error| !error.trim().is_empty());
+    if !output.trim().is_empty() || error.is_some() {
+        out.push_str("<details>\n<summary>Output</summary>\n\n");
+        if !output.trim().is_empty() {
+            push_fenced(out, "text", &output);
+        }
+        if let Some(error) = error {
+            push_fenced(out, "text", error);
+        }
+        out.push_str("</details>\n\n");
+    }
+    if let Some(diff) = result
+        .as_ref()
+        .and_then(|result| result.diff.as_deref())
+        .filter(|diff| !diff.trim().is_empty())
+    {
+        push_fenced(out, "diff", diff);
+    }
+}
+
+fn push_paragraph(out: &mut String, content: &str) {
+    let content = content.trim();
+    if content.is_empty() {
+        return;
+    }
+    out.push_str(content);
+    out.push_str("\n\n");
+}
+
+/// Wraps `content` in a backtick fence longer than any run of backticks
+/// inside it, so embedded fences can't close the block early.
+fn push_fenced(out: &mut String, lang: &str, content: &str) {
+    let longest_run = content
+        .split(|ch| ch != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let _ = writeln!(
+        out,
+        "{fence}{lang}\n{}\n{fence}\n",
+        content.trim_end_matches('\n')
+    );
+}
+
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn local_timestamp(raw: &str, offset: FixedOffset) -> String {
+    parse_message_timestamp(raw)
+        .map(|at| format_local(at, offset))
+        .unwrap_or_else(|| raw.to_string())
+}
+
+fn format_local(at: DateTime<Utc>, offset: FixedOffset) -> String {
+    at.with_timezone(&offset)
+        .format("%Y-%m-%d %H:%M:%S %:z")
+        .to_string()
+}
+
+fn rfc3339_timestamp(raw: &str, offset: FixedOffset) -> String {
+    parse_message_timestamp(raw)
+        .map(|at| rfc3339_local(at, offset))
+        .unwrap_or_else(|| raw.to_string())
+}
+
+fn rfc3339_local(at: DateTime<Utc>, offset: FixedOffset) -> String {
+    at.with_timezone(&offset)
+        .format("%Y-%m-%dT%H:%M:%S%.3f%:z")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::{MessageStatusDto, ThreadStatusDto};
+
+    fn thread() -> ThreadDto {
+        ThreadDto {
+            id: "t1".to_string(),
+            workspace_id: "w1".to_string(),
+            repo_id: None,
+            engine_id: "codex".to_string(),
+            model_id: "gpt-5.4".to_string(),
+            engine_thread_id: None,
+            engine_metadata: None,
+            reasoning_effort: None,
+            title: "Fix the build".to_string(),
+            status: ThreadStatusDto::Idle,
+            message_count: 2,
+            total_tokens: 0,
+            created_at: "2026-01-01 10:00:00".to_string(),
+            last_activity_at: "2026-01-01 10:05:00".to_string(),
+            engine_capabilities: None,
+        }
+    }
+
+    fn message(id: &str, role: &str, blocks: serde_json::Value) -> MessageDto {
+        MessageDto {
+            id: id.to_string(),
+            thread_id: "t1".to_string(),
+            role: role.to_string(),
+            content: None,
+            blocks: Some(blocks),
+            turn_engine_id: Some("codex".to_string()),
+            turn_model_id: Some("gpt-5.4".to_string()),
+            turn_reasoning_effort: None,
+            schema_version: 1,
+            status: MessageStatusDto::Completed,
+            token_usage: None,
+            created_at: "2026-01-01 10:00:00".to_string(),
+            seq: 0,
+            regenerated_from: None,
+            superseded: false,
+        }
+    }
+
+    fn options(offset_hours: i32) -> ExportOptions {
+        ExportOptions {
+            include_thinking: true,
+            include_actions: true,
+            max_action_output_chars: 20_000,
+            offset: FixedOffset::east_opt(offset_hours * 3600).unwrap(),
+            exported_at: parse_message_timestamp("2026-01-02 00:00:00").unwrap(),
+        }
+    }
+
+    #[test]
+    fn renders_header_blocks_and_fences_that_outlast_embedded_backticks() {
+        let user = message(
+            "m1",
+            "user",
+            json!([
+                { "type": "text", "content": "Why does it fail?" },
+                { "type": "attachment", "fileName": "log.txt", "filePath": "/tmp/log.txt", "sizeBytes": 12 }
+            ]),
+        );
+        let mut stale = message(
+            "m2",
+            "assistant",
+            json!([{ "type": "text", "content": "old" }]),
+        );
+        stale.superseded = true;
+        let assistant = message(
+            "m3",
+            "assistant",
+            json!([
+                { "type": "thinking", "content": "Check cargo output." },
+                {
+                    "type": "action", "actionId": "a1", "actionType": "command",
+                    "summary": "cargo build", "details": {}, "status": "done",
+                    "outputChunks": [{ "stream": "stdout", "content": "see ```rust``` docs" }],
+                    "result": { "success": true, "durationMs": 40, "diff": "+fixed" }
+                },
+                {
+                    "type": "approval", "approvalId": "p1", "actionType": "command",
+                    "summary": "rm -rf target", "details": {}, "status": "answered",
+                    "decision": "accept"
+                },
+                { "type": "text", "content": "Fixed." }
+            ]),
+        );
+        let markdown = render_thread_markdown(&thread(), &[user, stale, assistant], &options(2));
+
+        assert!(markdown.starts_with("# Fix the build\n"));
+        assert!(markdown.contains("- Model: gpt-5.4\n"));
+        assert!(markdown.contains("- Created: 2026-01-01 12:00:00 +02:00\n"));
+        assert!(markdown.contains("- Exported: 2026-01-02 02:00:00 +02:00\n"));
+        assert!(markdown.contains("📎 log.txt (`/tmp/log.txt`, 12 bytes)"));
+        assert!(!markdown.contains("old"));
+        assert!(markdown.contains("<details>\n<summary>Thinking</summary>\n\nCheck cargo output."));
+        assert!(markdown.contains("**command**: cargo build — done (40 ms)"));
+        assert!(markdown.contains("````text\nsee ```rust``` docs\n````"));
+        assert!(markdown.contains("```diff\n+fixed\n```"));
+        assert!(markdown.contains("> **Approval** (command): rm -rf target — accept"));
+        assert_eq!(markdown.matches("\n## ").count(), 2);
+    }
+
+    /// One message pair holding every block type the app stores.
+    fn every_block_type() -> Vec<MessageDto> {
+        let user = message(
+            "m1",
+            "user",
+            json!([
+                { "type": "text", "content": "Tidy up the parser" },
+                { "type": "attachment", "fileName": "spec.md", "filePath": "/work/spec.md", "sizeBytes": 2048, "mimeType": "text/markdown" },
+                { "type": "skill", "name": "rust-style", "path": "/skills/rust-style" },
+                { "type": "mention", "name": "parser.rs", "path": "src/parser.rs" }
+            ]),
+        );
+        let assistant = message(
+            "m2",
+            "assistant",
+            json!([
+                { "type": "thinking", "content": "Start with the tests.", "durationMs": 800.0 },
+                {
+                    "type": "action", "actionId": "a1", "actionType": "command",
+                    "summary": "cargo test", "details": { "command": "cargo test" }, "status": "done",
+                    "outputChunks": [
+                        { "stream": "stdout", "content": "running 3 tests\n" },
+                        { "stream": "stderr", "content": "warning: unused import\n" }
+                    ],
+                    "result": { "success": false, "error": "1 test failed", "durationMs": 1200 }
+                },
+                {
+                    "type": "action", "actionId": "a2", "actionType": "file_edit",
+                    "summary": "Edit src/parser.rs", "details": {}, "status": "done",
+                    "outputChunks": [],
+                    "result": { "success": true, "diff": "-old\n+new", "durationMs": 3 }
+                },
+                {
+                    "type": "approval", "approvalId": "p1", "actionType": "command",
+                    "summary": "git push", "details": {}, "status": "pending"
+                },
+                { "type": "diff", "diff": "--- a/x\n+++ b/x\n-1\n+2", "scope": "turn" },
+                { "type": "notice", "kind": "model_rerouted", "level": "info", "title": "Rerouted", "message": "Using a\nfallback model" },
+                { "type": "error", "message": "Rate limited" },
+                { "type": "future_block", "payload": 1 },
+                { "type": "text", "content": "Done." }
+            ]),
+        );
+        vec![user, assistant]
+    }
+
+    const EVERY_BLOCK_TYPE_MARKDOWN: &str = r#"# Fix the build
+
+- Engine: codex
+- Model: gpt-5.4
+- Created: 2026-01-01 10:00:00 +00:00
+- Last activity: 2026-01-01 10:05:00 +00:00
+- Exported: 2026-01-02 00:00:00 +00:00
+
+## User
+
+_2026-01-01 10:00:00 +00:00_
+
+Tidy up the parser
+
+📎 spec.md (`/work/spec.md`, 2048 bytes)
+
+_Skill: rust-style_
+
+_Mentioned parser.rs (`src/parser.rs`)_
+
+## Assistant
+
+_2026-01-01 10:00:00 +00:00 · gpt-5.4_
+
+<details>
+<summary>Thinking</summary>
+
+Start with the tests.
+
+</details>
+
+**command**: cargo test — done (1200 ms)
+
+<details>
+<summary>Output</summary>
+
+```text
+running 3 tests
+warning: unused import
+```
+
+```text
+1 test failed
+```
+
+</details>
+
+**file_edit**: Edit src/parser.rs — done (3 ms)
+
+```diff
+-old
++new
+```
+
+> **Approval** (command): git push — pending
+
+**Diff** (turn)
+
+```diff
+--- a/x
++++ b/x
+-1
++2
+```
+
+> **Rerouted** Using a fallback model
+
+> **Error:** Rate limited
+
+Done.
+
+"#;
+
+    #[test]
+    fn markdown_snapshot_covers_every_block_type() {
+        let markdown = render_thread_markdown(&thread(), &every_block_type(), &options(0));
+        assert_eq!(markdown, EVERY_BLOCK_TYPE_MARKDOWN);
+    }
+
+    #[test]
+    fn exports_drop_thinking_and_actions_on_request_and_mark_truncated_output() {
+        let messages = every_block_type();
+        let trimmed = ExportOptions {
+            include_thinking: false,
+            max_action_output_chars: 20,
+            ..options(0)
+        };
+        let markdown = render_thread_markdown(&thread(), &messages, &trimmed);
+        assert!(!markdown.contains("Thinking"));
+        assert!(markdown.contains("running 3 tests\nwarn\n... [truncated 19 characters]\n"));
+
+        let without_actions = ExportOptions {
+            include_actions: false,
+            ..trimmed
+        };
+        let markdown = render_thread_markdown(&thread(), &messages, &without_actions);
+        assert!(!markdown.contains("cargo test"));
+        assert!(markdown.contains("> **Approval** (command): git push — pending"));
+    }
+
+    #[test]
+    fn json_export_keeps_the_stored_block_shape_under_a_versioned_envelope() {
+        let mut messages = every_block_type();
+        let mut legacy = message("m0", "user", json!(null));
+        legacy.blocks = None;
+        legacy.content = Some("From before blocks".to_string());
+        legacy.seq = 1;
+        messages.insert(0, legacy);
+        let trimmed = ExportOptions {
+            max_action_output_chars: 20,
+            ..options(2)
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_thread_json(&thread(), &messages, &trimmed).unwrap())
+                .unwrap();
+
+        assert_eq!(json["schemaVersion"], THREAD_EXPORT_SCHEMA_VERSION);
+        assert_eq!(json["exportedAt"], "2026-01-02T02:00:00.000+02:00");
+        assert_eq!(json["thread"]["title"], "Fix the build");
+        assert_eq!(json["thread"]["createdAt"], "2026-01-01T12:00:00.000+02:00");
+        let exported = json["messages"].as_array().unwrap();
+        assert_eq!(exported.len(), 3);
+        assert_eq!(exported[0]["seq"], 1);
+        assert_eq!(exported[0]["createdAt"], "2026-01-01T12:00:00.000+02:00");
+        assert_eq!(
+            exported[0]["blocks"],
+            json!([{ "type": "text", "content": "From before blocks" }])
+        );
+        let blocks = exported[2]["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 9);
+        assert_eq!(blocks[7], json!({ "type": "future_block", "payload": 1 }));
+        assert_eq!(
+            blocks[1]["outputChunks"],
+            json!([
+                { "stream": "stdout", "content": "running 3 tests\n" },
+                { "stream": "stderr", "content": "warn\n... [truncated 19 characters]" }
+            ])
+        );
+        assert_eq!(blocks[1]["details"], json!({ "command": "cargo test" }));
+    }
+}
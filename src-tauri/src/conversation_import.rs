@@ -0,0 +1,552 @@
+//! Parses conversations exported from other tools (ChatGPT's
+//! `conversations.json`, or markdown with `## User` / `## Assistant`
+//! sections) into message records for a local thread.
+
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::Context;
+use chrono::DateTime;
+use serde_json::{json, Value};
+
+use crate::{
+    blocks::ContentBlock,
+    db::messages::{format_message_timestamp, ImportedMessageRecord},
+    models::{ConversationImportFormatDto, MessageStatusDto},
+};
+
+/// Largest source file accepted; a full ChatGPT export can run to tens of
+/// megabytes.
+const MAX_IMPORT_SOURCE_BYTES: u64 = 64 * 1024 * 1024;
+/// Total message text kept across one import. Messages past the budget are
+/// skipped and counted like unsupported entries.
+const MAX_IMPORTED_TEXT_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub struct ParsedConversation {
+    pub title: String,
+    pub messages: Vec<ImportedMessageRecord>,
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub conversations: Vec<ParsedConversation>,
+    /// Entries that were dropped: unsupported content types, malformed
+    /// nodes, empty sections, or text past the size budget.
+    pub skipped_items: usize,
+}
+
+pub fn read_conversations(
+    source_path: &Path,
+    format: ConversationImportFormatDto,
+) -> anyhow::Result<ParsedImport> {
+    let size = fs::metadata(source_path)
+        .with_context(|| format!("failed to read {}", source_path.display()))?
+        .len();
+    if size > MAX_IMPORT_SOURCE_BYTES {
+        anyhow::bail!(
+            "{} is {size} bytes; imports are limited to {MAX_IMPORT_SOURCE_BYTES} bytes",
+            source_path.display()
+        );
+    }
+    let raw = fs::read_to_string(source_path)
+        .with_context(|| format!("failed to read {}", source_path.display()))?;
+    let fallback_title = source_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Imported conversation".to_string());
+
+    match format {
+        ConversationImportFormatDto::ChatgptJson => parse_chatgpt_export(&raw, &fallback_title),
+        ConversationImportFormatDto::Markdown => Ok(parse_markdown(&raw, &fallback_title)),
+    }
+}
+
+struct ImportBuilder {
+    import: ParsedImport,
+    remaining_text_bytes: usize,
+}
+
+impl ImportBuilder {
+    fn new() -> Self {
+        Self {
+            import: ParsedImport::default(),
+            remaining_text_bytes: MAX_IMPORTED_TEXT_BYTES,
+        }
+    }
+
+    fn skip(&mut self) {
+        self.import.skipped_items += 1;
+    }
+
+    fn push_message(
+        &mut self,
+        messages: &mut Vec<ImportedMessageRecord>,
+        role: &str,
+        text: &str,
+        created_at: Option<String>,
+    ) {
+        let text = text.trim();
+        if text.len() > self.remaining_text_bytes {
+            self.skip();
+            return;
+        }
+        self.remaining_text_bytes -= text.len();
+        messages.push(ImportedMessageRecord {
+            role: role.to_string(),
+            content: Some(text.to_string()),
+            blocks: json!([ContentBlock::Text {
+                content: text.to_string(),
+                plan_mode: None,
+                is_steer: None,
+            }]),
+            status: MessageStatusDto::Completed,
+            turn_engine_id: None,
+            turn_model_id: None,
+            turn_reasoning_effort: None,
+            token_input: 0,
+            token_output: 0,
+            created_at,
+        });
+    }
+
+    fn push_conversation(&mut self, title: String, messages: Vec<ImportedMessageRecord>) {
+        if messages.is_empty() {
+            return;
+        }
+        self.import
+            .conversations
+            .push(ParsedConversation { title, messages });
+    }
+}
+
+// ── ChatGPT ───────────────────────────────────────────────────────────
+
+/// Accepts a full export (an array of conversations) or a single
+/// conversation, whose messages are either a `mapping` tree of nodes or a
+/// linear `messages` list.
+fn parse_chatgpt_export(raw: &str, fallback_title: &str) -> anyhow::Result<ParsedImport> {
+    let value = serde_json::from_str::<Value>(raw).context("invalid ChatGPT export JSON")?;
+    let conversations = match &value {
+        Value::Array(items) => items.iter().collect::<Vec<_>>(),
+        Value::Object(_) => vec![&value],
+        _ => anyhow::bail!("ChatGPT export must be a conversation or a list of conversations"),
+    };
+    let numbered = conversations.len() > 1;
+
+    let mut builder = ImportBuilder::new();
+    for (index, conversation) in conversations.into_iter().enumerate() {
+        if !conversation.is_object() {
+            builder.skip();
+            continue;
+        }
+        let title = conversation
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                if numbered {
+                    format!("{fallback_title} {}", index + 1)
+                } else {
+                    fallback_title.to_string()
+                }
+            });
+
+        let mut messages = Vec::new();
+        for message in chatgpt_message_list(conversation) {
+            match chatgpt_message_text(message) {
+                Ok(Some((role, text))) => {
+                    builder.push_message(&mut messages, role, &text, chatgpt_created_at(message))
+                }
+                Ok(None) => {}
+                Err(()) => builder.skip(),
+            }
+        }
+        builder.push_conversation(title, messages);
+    }
+    Ok(builder.import)
+}
+
+fn chatgpt_message_list(conversation: &Value) -> Vec<&Value> {
+    if let Some(mapping) = conversation.get("mapping").and_then(Value::as_object) {
+        // Follow the branch that was on screen: from `current_node` (or the
+        // newest leaf) back to the root.
+        let start = conversation
+            .get("current_node")
+            .and_then(Value::as_str)
+            .filter(|id| mapping.contains_key(*id))
+            .or_else(|| {
+                mapping
+                    .iter()
+                    .filter(|(_, node)| {
+                        node.get("children")
+                            .and_then(Value::as_array)
+                            .is_none_or(Vec::is_empty)
+                    })
+                    .max_by(|(_, left), (_, right)| {
+                        let created = |node: &Value| {
+                            node.pointer("/message/create_time")
+                                .and_then(Value::as_f64)
+                                .unwrap_or(0.0)
+                        };
+                        created(left).total_cmp(&created(right))
+                    })
+                    .map(|(id, _)| id.as_str())
+            });
+
+        let mut branch = Vec::new();
+        let mut visited = HashSet::new();
+        let mut cursor = start;
+        while let Some(id) = cursor {
+            if !visited.insert(id) {
+                break;
+            }
+            let Some(node) = mapping.get(id) else {
+                break;
+            };
+            if let Some(message) = node.get("message").filter(|message| !message.is_null()) {
+                branch.push(message);
+            }
+            cursor = node.get("parent").and_then(Value::as_str);
+        }
+        branch.reverse();
+        return branch;
+    }
+
+    conversation
+        .get("messages")
+        .and_then(Value::as_array)
+        .map(|messages| messages.iter().collect())
+        .unwrap_or_default()
+}
+
+/// `Ok(None)` for entries that are not part of the visible conversation
+/// (hidden or empty system nodes, blank placeholders); `Err` for entries
+/// that cannot be imported.
+fn chatgpt_message_text(message: &Value) -> Result<Option<(&'static str, String)>, ()> {
+    let role = message
+        .pointer("/author/role")
+        .or_else(|| message.get("role"))
+        .and_then(Value::as_str)
+        .ok_or(())?;
+    if message
+        .pointer("/metadata/is_visually_hidden_from_conversation")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        return Ok(None);
+    }
+
+    let text = chatgpt_content_text(message.get("content").ok_or(())?)?;
+    let role = match role {
+        "user" => "user",
+        "assistant" => "assistant",
+        "system" if text.trim().is_empty() => return Ok(None),
+        _ => return Err(()),
+    };
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((role, text)))
+}
+
+fn chatgpt_content_text(content: &Value) -> Result<String, ()> {
+    if let Some(text) = content.as_str() {
+        return Ok(text.to_string());
+    }
+    let content_type = content.get("content_type").and_then(Value::as_str);
+    if !matches!(content_type, None | Some("text") | Some("multimodal_text")) {
+        return Err(());
+    }
+    if let Some(parts) = content.get("parts").and_then(Value::as_array) {
+        // Multimodal parts mix strings with asset pointers; only the text
+        // survives the import.
+        let text = parts
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if text.trim().is_empty() && !parts.is_empty() && parts.iter().all(|part| !part.is_string())
+        {
+            return Err(());
+        }
+        return Ok(text);
+    }
+    content
+        .get("text")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or(())
+}
+
+fn chatgpt_created_at(message: &Value) -> Option<String> {
+    let seconds = message.get("create_time").and_then(Value::as_f64)?;
+    DateTime::from_timestamp_millis((seconds * 1000.0) as i64).map(format_message_timestamp)
+}
+
+// ── Markdown ──────────────────────────────────────────────────────────
+
+/// Splits on `## User` / `## Assistant` headings outside code fences. A
+/// leading `# Title` names the thread; anything else before the first
+/// section is ignored.
+fn parse_markdown(raw: &str, fallback_title: &str) -> ParsedImport {
+    let mut builder = ImportBuilder::new();
+    let mut title = None;
+    let mut messages = Vec::new();
+    let mut section: Option<(&'static str, Vec<&str>)> = None;
+    let mut in_fence = false;
+
+    for line in raw.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            if let Some(role) = markdown_section_role(trimmed) {
+                if let Some((role, lines)) = section.take() {
+                    push_markdown_section(&mut builder, &mut messages, role, &lines);
+                }
+                section = Some((role, Vec::new()));
+                continue;
+            }
+            if section.is_none() && title.is_none() {
+                if let Some(heading) = trimmed.strip_prefix("# ") {
+                    title = Some(heading.trim().to_string());
+                    continue;
+                }
+            }
+        }
+        if let Some((_, lines)) = section.as_mut() {
+            lines.push(line);
+        }
+    }
+    if let Some((role, lines)) = section.take() {
+        push_markdown_section(&mut builder, &mut messages, role, &lines);
+    }
+
+    let title = title
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| fallback_title.to_string());
+    builder.push_conversation(title, messages);
+    builder.import
+}
+
+fn markdown_section_role(line: &str) -> Option<&'static str> {
+    let heading = line.strip_prefix("## ")?.trim().trim_end_matches(':');
+    if heading.eq_ignore_ascii_case("user") {
+        Some("user")
+    } else if heading.eq_ignore_ascii_case("assistant") {
+        Some("assistant")
+    } else {
+        None
+    }
+}
+
+fn push_markdown_section(
+    builder: &mut ImportBuilder,
+    messages: &mut Vec<ImportedMessageRecord>,
+    role: &str,
+    lines: &[&str],
+) {
+    let text = lines.join("\n");
+    if text.trim().is_empty() {
+        builder.skip();
+    } else {
+        builder.push_message(messages, role, &text, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHATGPT_EXPORT: &str = r#"[
+      {
+        "title": "Rust lifetimes",
+        "create_time": 1700000000.0,
+        "current_node": "a2",
+        "mapping": {
+          "root": { "id": "root", "message": null, "parent": null, "children": ["sys"] },
+          "sys": {
+            "id": "sys",
+            "message": {
+              "author": { "role": "system" },
+              "content": { "content_type": "text", "parts": [""] },
+              "metadata": { "is_visually_hidden_from_conversation": true }
+            },
+            "parent": "root",
+            "children": ["u1"]
+          },
+          "u1": {
+            "id": "u1",
+            "message": {
+              "author": { "role": "user" },
+              "create_time": 1700000001.5,
+              "content": { "content_type": "text", "parts": ["What is 'a?"] }
+            },
+            "parent": "sys",
+            "children": ["a1", "a2"]
+          },
+          "a1": {
+            "id": "a1",
+            "message": {
+              "author": { "role": "assistant" },
+              "content": { "content_type": "text", "parts": ["Abandoned draft"] }
+            },
+            "parent": "u1",
+            "children": []
+          },
+          "tool": {
+            "id": "tool",
+            "message": {
+              "author": { "role": "tool" },
+              "content": { "content_type": "execution_output", "text": "42" }
+            },
+            "parent": "u1",
+            "children": ["a2"]
+          },
+          "a2": {
+            "id": "a2",
+            "message": {
+              "author": { "role": "assistant" },
+              "content": { "content_type": "text", "parts": ["A lifetime ", "parameter."] }
+            },
+            "parent": "tool",
+            "children": []
+          }
+        }
+      },
+      {
+        "messages": [
+          { "role": "user", "content": "Linear variant" },
+          { "role": "assistant" },
+          { "role": "assistant", "content": { "content_type": "multimodal_text", "parts": [{ "asset_pointer": "file-1" }, "Here is the chart"] } }
+        ]
+      },
+      "not a conversation"
+    ]"#;
+
+    const MARKDOWN_EXPORT: &str = "# Deploy notes
+
+Preamble that is not part of any turn.
+
+## User
+How do I deploy?
+
+## Assistant
+Run:
+
+```sh
+## User
+make deploy
+```
+
+## Assistant
+
+## user:
+Thanks!
+";
+
+    fn texts(conversation: &ParsedConversation) -> Vec<(&str, &str)> {
+        conversation
+            .messages
+            .iter()
+            .map(|message| {
+                (
+                    message.role.as_str(),
+                    message.content.as_deref().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chatgpt_exports_follow_the_visible_branch_and_count_skipped_entries() {
+        let import = parse_chatgpt_export(CHATGPT_EXPORT, "conversations").expect("parse");
+
+        assert_eq!(import.conversations.len(), 2);
+        let first = &import.conversations[0];
+        assert_eq!(first.title, "Rust lifetimes");
+        assert_eq!(
+            texts(first),
+            [
+                ("user", "What is 'a?"),
+                ("assistant", "A lifetime \nparameter.")
+            ]
+        );
+        assert_eq!(
+            first.messages[0].created_at.as_deref(),
+            Some("2023-11-14 22:13:21.500Z")
+        );
+        assert_eq!(
+            first.messages[1].blocks,
+            json!([{ "type": "text", "content": "A lifetime \nparameter." }])
+        );
+
+        let second = &import.conversations[1];
+        assert_eq!(second.title, "conversations 2");
+        assert_eq!(
+            texts(second),
+            [
+                ("user", "Linear variant"),
+                ("assistant", "Here is the chart")
+            ]
+        );
+        // The tool output node, the content-less message, and the string
+        // entry in the top-level list.
+        assert_eq!(import.skipped_items, 3);
+    }
+
+    #[test]
+    fn chatgpt_exports_without_a_current_node_use_the_newest_leaf() {
+        let export = json!({
+            "mapping": {
+                "u": { "message": { "author": { "role": "user" }, "create_time": 1.0, "content": { "parts": ["q"] } }, "parent": null, "children": ["old", "new"] },
+                "old": { "message": { "author": { "role": "assistant" }, "create_time": 2.0, "content": { "parts": ["old"] } }, "parent": "u", "children": [] },
+                "new": { "message": { "author": { "role": "assistant" }, "create_time": 3.0, "content": { "parts": ["new"] } }, "parent": "u", "children": [] }
+            }
+        });
+        let import = parse_chatgpt_export(&export.to_string(), "chat").expect("parse");
+
+        assert_eq!(
+            texts(&import.conversations[0]),
+            [("user", "q"), ("assistant", "new")]
+        );
+        assert!(parse_chatgpt_export("{", "chat").is_err());
+    }
+
+    #[test]
+    fn markdown_sections_split_on_role_headings_outside_code_fences() {
+        let import = parse_markdown(MARKDOWN_EXPORT, "notes");
+
+        assert_eq!(import.conversations.len(), 1);
+        let conversation = &import.conversations[0];
+        assert_eq!(conversation.title, "Deploy notes");
+        assert_eq!(
+            texts(conversation),
+            [
+                ("user", "How do I deploy?"),
+                ("assistant", "Run:\n\n```sh\n## User\nmake deploy\n```"),
+                ("user", "Thanks!"),
+            ]
+        );
+        // The empty `## Assistant` section.
+        assert_eq!(import.skipped_items, 1);
+        assert_eq!(
+            parse_markdown("no sections", "notes").conversations.len(),
+            0
+        );
+    }
+
+    #[test]
+    fn text_past_the_size_budget_is_skipped() {
+        let mut builder = ImportBuilder::new();
+        builder.remaining_text_bytes = 5;
+        let mut messages = Vec::new();
+        builder.push_message(&mut messages, "user", "hello", None);
+        builder.push_message(&mut messages, "assistant", "!", None);
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(builder.import.skipped_items, 1);
+    }
+}
@@ -1,14 +1,17 @@
 use anyhow::Context;
-use rusqlite::{params, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde_json::Value;
 
-use crate::engines::events::{ActionResult, ActionType};
+use crate::{
+    engines::events::{ActionResult, ActionType},
+    models::TurnDiagnosticDto,
+};
 
 use super::Database;
 
 #[allow(clippy::too_many_arguments)]
-pub fn insert_action_started(
-    db: &Database,
+pub(crate) fn insert_action_started_on(
+    conn: &Connection,
     action_id: &str,
     thread_id: &str,
     message_id: &str,
@@ -17,7 +20,6 @@ pub fn insert_action_started(
     summary: &str,
     details: &Value,
 ) -> anyhow::Result<()> {
-    let conn = db.connect()?;
     conn.execute(
         "INSERT OR REPLACE INTO actions (
       id, thread_id, message_id, engine_action_id, action_type, summary, details_json, status
@@ -36,13 +38,12 @@ pub fn insert_action_started(
     Ok(())
 }
 
-pub fn update_action_completed(
-    db: &Database,
+pub(crate) fn update_action_completed_on(
+    conn: &Connection,
     action_id: &str,
     result: &ActionResult,
 ) -> anyhow::Result<()> {
     let status = if result.success { "done" } else { "error" };
-    let conn = db.connect()?;
     conn.execute(
         "UPDATE actions
      SET status = ?1, result_json = ?2, duration_ms = ?3
@@ -58,8 +59,8 @@ pub fn update_action_completed(
     Ok(())
 }
 
-pub fn insert_approval(
-    db: &Database,
+pub(crate) fn insert_approval_on(
+    conn: &Connection,
     approval_id: &str,
     thread_id: &str,
     message_id: &str,
@@ -67,7 +68,6 @@ pub fn insert_approval(
     summary: &str,
     details: &Value,
 ) -> anyhow::Result<()> {
-    let conn = db.connect()?;
     conn.execute(
         "INSERT OR REPLACE INTO approvals (
       id, thread_id, message_id, action_type, summary, details_json, status
@@ -97,9 +97,29 @@ pub fn answer_approval(db: &Database, approval_id: &str, decision: &str) -> anyh
     Ok(())
 }
 
-#[cfg(test)]
-pub fn resolve_approval(db: &Database, approval_id: &str) -> anyhow::Result<()> {
+/// Resolves the message's approvals that were never answered and returns
+/// their ids, for engines that deny unanswered approvals when the turn that
+/// raised them ends.
+pub fn resolve_pending_approvals_for_message(
+    db: &Database,
+    message_id: &str,
+) -> anyhow::Result<Vec<String>> {
     let conn = db.connect()?;
+    let mut stmt = conn
+        .prepare(
+            "UPDATE approvals
+     SET status = 'answered', answered_at = datetime('now')
+     WHERE message_id = ?1 AND status = 'pending'
+     RETURNING id",
+        )
+        .context("failed to prepare pending approval resolution")?;
+    let rows = stmt.query_map(params![message_id], |row| row.get(0))?;
+    rows.collect::<Result<Vec<String>, _>>()
+        .context("failed to resolve pending approvals")
+}
+
+/// Marks the approval answered, keeping the time of an earlier answer.
+pub(crate) fn resolve_approval_on(conn: &Connection, approval_id: &str) -> anyhow::Result<()> {
     conn.execute(
         "UPDATE approvals
      SET status = 'answered', answered_at = COALESCE(answered_at, datetime('now'))
@@ -170,13 +190,12 @@ pub fn find_approval_context(
     Ok(context)
 }
 
-pub fn append_event_log(
-    db: &Database,
+pub(crate) fn append_event_log_on(
+    conn: &Connection,
     thread_id: &str,
     message_id: &str,
     event: &Value,
 ) -> anyhow::Result<()> {
-    let conn = db.connect()?;
     conn.execute(
         "INSERT INTO engine_event_logs (thread_id, message_id, event_json) VALUES (?1, ?2, ?3)",
         params![thread_id, message_id, event.to_string()],
@@ -184,3 +203,61 @@ pub fn append_event_log(
     .context("failed to append engine event log")?;
     Ok(())
 }
+
+const TURN_DIAGNOSTIC_EVENT_TYPE: &str = "turn_diagnostic";
+
+/// Stores a failed turn's diagnostics as `turn_diagnostic` event log rows.
+pub fn append_turn_diagnostics(
+    db: &Database,
+    thread_id: &str,
+    message_id: &str,
+    entries: &[TurnDiagnosticDto],
+) -> anyhow::Result<()> {
+    let mut conn = db.connect()?;
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO engine_event_logs (thread_id, message_id, event_json) VALUES (?1, ?2, ?3)",
+        )?;
+        for entry in entries {
+            let mut event = serde_json::to_value(entry)?;
+            if let Value::Object(object) = &mut event {
+                object.insert(
+                    "type".to_string(),
+                    Value::String(TURN_DIAGNOSTIC_EVENT_TYPE.to_string()),
+                );
+            }
+            stmt.execute(params![thread_id, message_id, event.to_string()])
+                .context("failed to append turn diagnostic")?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn get_turn_diagnostics(
+    db: &Database,
+    message_id: &str,
+) -> anyhow::Result<Vec<TurnDiagnosticDto>> {
+    let conn = db.connect()?;
+    let mut stmt = conn.prepare(
+        "SELECT event_json
+     FROM engine_event_logs
+     WHERE message_id = ?1
+       AND json_extract(event_json, '$.type') = ?2
+     ORDER BY id ASC",
+    )?;
+    let rows = stmt.query_map(params![message_id, TURN_DIAGNOSTIC_EVENT_TYPE], |row| {
+        row.get::<_, String>(0)
+    })?;
+
+    let mut diagnostics = Vec::new();
+    for row in rows {
+        let raw = row?;
+        match serde_json::from_str::<TurnDiagnosticDto>(&raw) {
+            Ok(diagnostic) => diagnostics.push(diagnostic),
+            Err(error) => log::warn!("skipping malformed turn diagnostic row: {error}"),
+        }
+    }
+    Ok(diagnostics)
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{Read, Write},
+    time::Duration,
+};
+
+use anyhow::Context;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use rusqlite::{params, params_from_iter, Connection};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::models::BlobStorageReportDto;
+
+use super::Database;
+
+/// Diff payloads at or above this size are stored once in `content_blobs` and
+/// referenced from the block JSON instead of being inlined.
+pub const BLOB_THRESHOLD_BYTES: usize = 8 * 1024;
+/// Zero-ref blobs younger than this survive a sweep, so a message write that
+/// stored its blob just before the sweep still finds it.
+pub const BLOB_SWEEP_GRACE: Duration = Duration::from_secs(60 * 60);
+const BLOB_REF_PREFIX: &str = "sha256:";
+const BLOB_LOOKUP_CHUNK: usize = 500;
+
+/// Field holding the externalizable payload for a block type.
+fn blob_field(block_type: &str) -> Option<&'static str> {
+    match block_type {
+        "diff" => Some("diff"),
+        _ => None,
+    }
+}
+
+/// Moves large payloads out of `blocks` into the blob store, leaving
+/// `{"ref": "sha256:..."}` in their place. Refcounts are maintained by the
+/// messages triggers once the rewritten JSON is written.
+pub fn externalize_large_blocks(conn: &Connection, blocks: &mut Value) -> anyhow::Result<()> {
+    let Some(items) = blocks.as_array_mut() else {
+        return Ok(());
+    };
+
+    for block in items {
+        let Some(object) = block.as_object_mut() else {
+            continue;
+        };
+        let Some(field) = object
+            .get("type")
+            .and_then(Value::as_str)
+            .and_then(blob_field)
+        else {
+            continue;
+        };
+        let Some(content) = object.get(field).and_then(Value::as_str) else {
+            continue;
+        };
+        if content.len() < BLOB_THRESHOLD_BYTES {
+            continue;
+        }
+
+        let hash = put_blob(conn, content)?;
+        object.remove(field);
+        object.insert("ref".to_string(), Value::String(hash));
+    }
+
+    Ok(())
+}
+
+/// Same as [`externalize_large_blocks`] for a serialized block array; input
+/// that isn't JSON is returned unchanged.
+pub fn externalize_large_blocks_json(
+    conn: &Connection,
+    blocks_json: &str,
+) -> anyhow::Result<String> {
+    if blocks_json.len() < BLOB_THRESHOLD_BYTES {
+        return Ok(blocks_json.to_string());
+    }
+    let Ok(mut blocks) = serde_json::from_str::<Value>(blocks_json) else {
+        return Ok(blocks_json.to_string());
+    };
+    externalize_large_blocks(conn, &mut blocks)?;
+    Ok(blocks.to_string())
+}
+
+/// Inserts `content` if it isn't stored yet, otherwise just marks it as
+/// recently written. Returns the `sha256:` reference.
+pub fn put_blob(conn: &Connection, content: &str) -> anyhow::Result<String> {
+    let hash = blob_hash(content.as_bytes());
+    let touched = conn
+        .execute(
+            "UPDATE content_blobs SET last_written_at = datetime('now') WHERE hash = ?1",
+            params![hash],
+        )
+        .context("failed to touch content blob")?;
+    if touched > 0 {
+        return Ok(hash);
+    }
+
+    let compressed = compress(content.as_bytes())?;
+    conn.execute(
+        "INSERT OR IGNORE INTO content_blobs (hash, content, size_bytes, stored_bytes)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            hash,
+            compressed,
+            content.len() as i64,
+            compressed.len() as i64
+        ],
+    )
+    .context("failed to insert content blob")?;
+    Ok(hash)
+}
+
+/// Replaces blob references across all `blocks` with their content. Distinct
+/// hashes are fetched in chunked `IN (...)` lookups rather than one query per
+/// block; returns the number of lookups issued.
+pub fn resolve_blob_refs<'a>(
+    conn: &Connection,
+    blocks: impl IntoIterator<Item = &'a mut Value>,
+) -> anyhow::Result<usize> {
+    let mut pending: Vec<&'a mut Map<String, Value>> = Vec::new();
+    for blocks in blocks {
+        let Some(items) = blocks.as_array_mut() else {
+            continue;
+        };
+        pending.extend(
+            items
+                .iter_mut()
+                .filter_map(Value::as_object_mut)
+                .filter(|object| block_ref(object).is_some()),
+        );
+    }
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let hashes = pending
+        .iter()
+        .filter_map(|object| block_ref(object))
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let mut contents = HashMap::with_capacity(hashes.len());
+    let mut lookups = 0;
+    for chunk in hashes.chunks(BLOB_LOOKUP_CHUNK) {
+        let placeholders = std::iter::repeat_n("?", chunk.len())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT hash, content FROM content_blobs WHERE hash IN ({placeholders})"
+        ))?;
+        let rows = stmt.query_map(params_from_iter(chunk.iter()), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })?;
+        for row in rows {
+            let (hash, compressed) = row?;
+            contents.insert(hash, decompress(&compressed)?);
+        }
+        lookups += 1;
+    }
+
+    for object in pending {
+        let Some(hash) = block_ref(object).map(str::to_string) else {
+            continue;
+        };
+        let field = object
+            .get("type")
+            .and_then(Value::as_str)
+            .and_then(blob_field)
+            .unwrap_or("content");
+        match contents.get(&hash) {
+            Some(content) => {
+                object.remove("ref");
+                object.insert(field.to_string(), Value::String(content.clone()));
+            }
+            None => {
+                // Keep the ref so the block can be identified, but give
+                // readers the inline shape they expect.
+                log::warn!("message block references missing content blob {hash}");
+                object.insert(field.to_string(), Value::String(String::new()));
+            }
+        }
+    }
+
+    Ok(lookups)
+}
+
+/// Deletes blobs no message references anymore, keeping ones written within
+/// `grace`. Returns the number of blobs removed.
+pub fn sweep_unreferenced_blobs(db: &Database, grace: Duration) -> anyhow::Result<usize> {
+    let conn = db.connect()?;
+    conn.execute(
+        "DELETE FROM content_blobs
+         WHERE refcount <= 0
+           AND last_written_at <= datetime('now', ?1)",
+        params![format!("-{} seconds", grace.as_secs())],
+    )
+    .context("failed to sweep unreferenced content blobs")
+}
+
+pub fn storage_report(db: &Database) -> anyhow::Result<BlobStorageReportDto> {
+    let conn = db.connect()?;
+    conn.query_row(
+        "SELECT COUNT(*),
+            COALESCE(SUM(CASE WHEN refcount <= 0 THEN 1 ELSE 0 END), 0),
+            COALESCE(SUM(MAX(refcount, 0)), 0),
+            COALESCE(SUM(stored_bytes), 0),
+            COALESCE(SUM(size_bytes * MAX(refcount, 0)), 0)
+         FROM content_blobs",
+        [],
+        |row| {
+            let stored_bytes = row.get::<_, i64>(3)?.max(0) as u64;
+            let inline_bytes = row.get::<_, i64>(4)?.max(0) as u64;
+            Ok(BlobStorageReportDto {
+                category: "diff_blobs".to_string(),
+                blob_count: row.get::<_, i64>(0)?.max(0) as u64,
+                unreferenced_blob_count: row.get::<_, i64>(1)?.max(0) as u64,
+                reference_count: row.get::<_, i64>(2)?.max(0) as u64,
+                stored_bytes,
+                inline_bytes,
+                saved_bytes: inline_bytes.saturating_sub(stored_bytes),
+            })
+        },
+    )
+    .context("failed to build blob storage report")
+}
+
+fn block_ref(object: &Map<String, Value>) -> Option<&str> {
+    object
+        .get("ref")
+        .and_then(Value::as_str)
+        .filter(|value| value.starts_with(BLOB_REF_PREFIX))
+}
+
+fn blob_hash(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    let mut hash = String::with_capacity(BLOB_REF_PREFIX.len() + digest.len() * 2);
+    hash.push_str(BLOB_REF_PREFIX);
+    for byte in digest {
+        hash.push_str(&format!("{byte:02x}"));
+    }
+    hash
+}
+
+fn compress(content: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(content)
+        .context("failed to compress content blob")?;
+    encoder.finish().context("failed to compress content blob")
+}
+
+fn decompress(compressed: &[u8]) -> anyhow::Result<String> {
+    let mut content = String::new();
+    ZlibDecoder::new(compressed)
+        .read_to_string(&mut content)
+        .context("failed to decompress content blob")?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        db::{messages, threads, workspaces, PoolLimits},
+        models::MessageStatusDto,
+    };
+
+    use super::*;
+
+    fn test_db() -> Database {
+        let path = std::env::temp_dir().join(format!("panes-blobs-{}.db", Uuid::new_v4()));
+        let db = Database::unmigrated(path, PoolLimits::default());
+        db.run_migrations().expect("failed to run test migrations");
+        db
+    }
+
+    fn test_thread(db: &Database) -> String {
+        let root = std::env::temp_dir().join(format!("panes-workspace-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("failed to create temp workspace root");
+        let workspace =
+            workspaces::upsert_workspace(db, root.to_string_lossy().as_ref(), Some(1)).unwrap();
+        threads::create_thread(db, &workspace.id, None, "codex", "gpt-5.3-codex", "test")
+            .unwrap()
+            .id
+    }
+
+    fn large_diff(seed: &str) -> String {
+        format!("--- a/{seed}\n+++ b/{seed}\n")
+            + &format!("+{seed} changed line\n").repeat(BLOB_THRESHOLD_BYTES / 8)
+    }
+
+    fn diff_blocks(diff: &str) -> Value {
+        json!([
+            { "type": "text", "content": "patched" },
+            { "type": "diff", "diff": diff, "scope": "turn" }
+        ])
+    }
+
+    fn insert_diff_message(db: &Database, thread_id: &str, diff: &str) -> String {
+        let message =
+            messages::insert_assistant_placeholder(db, thread_id, Some("codex"), None, None)
+                .unwrap();
+        messages::update_assistant_blocks_json_on(
+            &db.connect().unwrap(),
+            &message.id,
+            &diff_blocks(diff).to_string(),
+            MessageStatusDto::Completed,
+            None,
+        )
+        .unwrap();
+        message.id
+    }
+
+    fn refcount(db: &Database, diff: &str) -> Option<i64> {
+        let conn = db.connect().unwrap();
+        conn.query_row(
+            "SELECT refcount FROM content_blobs WHERE hash = ?1",
+            params![blob_hash(diff.as_bytes())],
+            |row| row.get(0),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn refcounts_track_message_and_thread_deletes() {
+        let db = test_db();
+        let thread_a = test_thread(&db);
+        let thread_b = test_thread(&db);
+        let diff = large_diff("shared.rs");
+
+        let first = insert_diff_message(&db, &thread_a, &diff);
+        insert_diff_message(&db, &thread_a, &diff);
+        insert_diff_message(&db, &thread_b, &diff);
+        assert_eq!(refcount(&db, &diff), Some(3));
+
+        let stored: String = db
+            .connect()
+            .unwrap()
+            .query_row(
+                "SELECT blocks_json FROM messages WHERE id = ?1",
+                params![first],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(stored.contains(BLOB_REF_PREFIX));
+        assert!(!stored.contains("shared.rs changed line"));
+
+        messages::delete_message(&db, &thread_a, &first, false).unwrap();
+        assert_eq!(refcount(&db, &diff), Some(2));
+
+        threads::delete_thread(&db, &thread_a).unwrap();
+        assert_eq!(refcount(&db, &diff), Some(1));
+        assert_eq!(
+            sweep_unreferenced_blobs(&db, Duration::ZERO).unwrap(),
+            0,
+            "referenced blobs must survive a sweep"
+        );
+
+        threads::delete_thread(&db, &thread_b).unwrap();
+        assert_eq!(refcount(&db, &diff), Some(0));
+        assert_eq!(sweep_unreferenced_blobs(&db, BLOB_SWEEP_GRACE).unwrap(), 0);
+        assert_eq!(sweep_unreferenced_blobs(&db, Duration::ZERO).unwrap(), 1);
+        assert_eq!(refcount(&db, &diff), None);
+    }
+
+    #[test]
+    fn resolve_blob_refs_batches_lookups() {
+        let db = test_db();
+        let conn = db.connect().unwrap();
+        let diffs = (0..20)
+            .map(|index| large_diff(&format!("file-{index}.rs")))
+            .collect::<Vec<_>>();
+        let mut messages = diffs
+            .iter()
+            .map(|diff| {
+                let mut blocks = diff_blocks(diff);
+                externalize_large_blocks(&conn, &mut blocks).unwrap();
+                blocks
+            })
+            .collect::<Vec<_>>();
+
+        let lookups = resolve_blob_refs(&conn, messages.iter_mut()).unwrap();
+        assert_eq!(lookups, 1);
+        for (blocks, diff) in messages.iter().zip(&diffs) {
+            assert_eq!(blocks, &diff_blocks(diff));
+        }
+
+        assert_eq!(resolve_blob_refs(&conn, messages.iter_mut()).unwrap(), 0);
+    }
+
+    #[test]
+    fn thread_messages_resolve_mixed_inline_and_ref_blocks() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let small = "--- a/x\n+++ b/x\n+tiny\n".to_string();
+        let large = large_diff("big.rs");
+        insert_diff_message(&db, &thread_id, &small);
+        insert_diff_message(&db, &thread_id, &large);
+
+        let report = storage_report(&db).unwrap();
+        assert_eq!(report.blob_count, 1);
+        assert_eq!(report.reference_count, 1);
+        assert_eq!(report.inline_bytes, large.len() as u64);
+        assert!(report.saved_bytes > 0);
+
+        let loaded = messages::get_thread_messages(&db, &thread_id).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].blocks, Some(diff_blocks(&small)));
+        assert_eq!(loaded[1].blocks, Some(diff_blocks(&large)));
+    }
+}
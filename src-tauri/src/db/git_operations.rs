@@ -0,0 +1,83 @@
+use anyhow::Context;
+use rusqlite::params;
+
+use crate::models::GitOperationLogDto;
+
+use super::Database;
+
+const GIT_OPERATION_LOG_MAX_LIMIT: usize = 500;
+
+/// One destructive git operation attempt, including refusals, so the log
+/// shows what was acknowledged as well as what actually ran.
+#[derive(Debug, Clone)]
+pub struct GitOperationRecord {
+    pub repo_path: String,
+    /// Thread the operation was run from, when it was run from one.
+    pub thread_id: Option<String>,
+    pub operation: &'static str,
+    pub target: String,
+    pub force: bool,
+    pub force_with_lease: bool,
+    pub outcome: &'static str,
+    pub detail: Option<String>,
+}
+
+pub fn record_git_operation(db: &Database, record: &GitOperationRecord) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    conn.execute(
+        "INSERT INTO git_operation_logs (
+            repo_path, thread_id, operation, target, force, force_with_lease, outcome, detail
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            record.repo_path,
+            record.thread_id,
+            record.operation,
+            record.target,
+            record.force,
+            record.force_with_lease,
+            record.outcome,
+            record.detail,
+        ],
+    )
+    .context("failed to record git operation")?;
+    Ok(())
+}
+
+pub fn list_git_operations(
+    db: &Database,
+    repo_path: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<GitOperationLogDto>> {
+    let conn = db.connect()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, repo_path, thread_id, operation, target, force, force_with_lease, outcome,
+                detail, created_at
+         FROM git_operation_logs
+         WHERE repo_path = ?1
+         ORDER BY id DESC
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            repo_path,
+            limit.clamp(1, GIT_OPERATION_LOG_MAX_LIMIT) as i64
+        ],
+        |row| {
+            Ok(GitOperationLogDto {
+                id: row.get(0)?,
+                repo_path: row.get(1)?,
+                thread_id: row.get(2)?,
+                operation: row.get(3)?,
+                target: row.get(4)?,
+                force: row.get(5)?,
+                force_with_lease: row.get(6)?,
+                outcome: row.get(7)?,
+                detail: row.get(8)?,
+                created_at: row.get(9)?,
+            })
+        },
+    )?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load git operation log")
+}
@@ -0,0 +1,69 @@
+use anyhow::Context;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::Database;
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+pub fn incr_on(conn: &Connection, day: NaiveDate, key: &str, by: i64) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT INTO local_metrics (day, key, value) VALUES (?1, ?2, ?3)
+         ON CONFLICT(day, key) DO UPDATE SET value = value + excluded.value",
+        params![day.format(DAY_FORMAT).to_string(), key, by],
+    )
+    .context("failed to increment local metric")?;
+    Ok(())
+}
+
+pub fn incr(db: &Database, day: NaiveDate, key: &str, by: i64) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    incr_on(&conn, day, key, by)
+}
+
+/// Counter totals per key for the days in `start..=end`.
+pub fn sum_between(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> anyhow::Result<Vec<(String, i64)>> {
+    let conn = db.connect()?;
+    let mut stmt = conn.prepare(
+        "SELECT key, SUM(value)
+         FROM local_metrics
+         WHERE day >= ?1 AND day <= ?2
+         GROUP BY key
+         ORDER BY key",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            start.format(DAY_FORMAT).to_string(),
+            end.format(DAY_FORMAT).to_string()
+        ],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load local metrics")
+}
+
+pub fn get_state(db: &Database, key: &str) -> anyhow::Result<Option<String>> {
+    let conn = db.connect()?;
+    conn.query_row(
+        "SELECT value FROM local_metrics_state WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("failed to load local metrics state")
+}
+
+pub fn set_state(db: &Database, key: &str, value: &str) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    conn.execute(
+        "INSERT INTO local_metrics_state (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .context("failed to save local metrics state")?;
+    Ok(())
+}
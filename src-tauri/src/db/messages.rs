@@ -1,17 +1,56 @@
 use anyhow::Context;
-use chrono::{Duration as ChronoDuration, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, Utc};
 use std::collections::HashMap;
 
-use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use rusqlite::{
+    params, params_from_iter, types::Value as SqlValue, Connection, OptionalExtension, Row,
+};
 use serde_json::Value;
 use uuid::Uuid;
 
+use crate::blocks::{ContentBlock, BLOCK_SCHEMA_VERSION};
 use crate::models::{
     ActionOutputChunkDto, ActionOutputDto, MessageDto, MessageStatusDto, MessageWindowCursorDto,
-    MessageWindowDto, SearchResultDto, TokenUsageDto,
+    MessageWindowDto, SearchMatchRangeDto, SearchResultDto, SearchSnippetDto,
+    ThreadModelHistoryDto, ThreadModelHistoryEntryDto, ThreadModelRangeDto, TokenUsageDto,
 };
 
-use super::Database;
+use super::{blobs, Database};
+
+/// Canonical `messages.created_at` format: RFC3339 with millisecond precision.
+/// Uses the space separator RFC3339 allows so values keep sorting lexically
+/// against the `datetime('now')` timestamps other tables store.
+const MESSAGE_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3fZ";
+
+/// Assigns the next per-thread `seq` inside the INSERT itself; sqlite
+/// serializes writers, so two inserts can't observe the same maximum.
+const NEXT_MESSAGE_SEQ_SQL: &str =
+    "(SELECT COALESCE(MAX(seq), 0) + 1 FROM messages WHERE thread_id = ?2)";
+
+pub fn format_message_timestamp(at: DateTime<Utc>) -> String {
+    at.format(MESSAGE_TIMESTAMP_FORMAT).to_string()
+}
+
+pub fn message_timestamp_now() -> String {
+    format_message_timestamp(Utc::now())
+}
+
+/// Parses the timestamp shapes found in `messages.created_at`: the canonical
+/// format, full RFC3339 with either separator, and sqlite's offset-less
+/// `datetime('now')` output (UTC, any fractional precision).
+pub fn parse_message_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(raw) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(&raw.replacen(' ', "T", 1)) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(raw, format).ok())
+        .map(|naive| naive.and_utc())
+}
 
 #[derive(Debug, Clone)]
 pub struct ImportedMessageRecord {
@@ -56,8 +95,25 @@ pub fn insert_assistant_placeholder(
     turn_model_id: Option<&str>,
     turn_reasoning_effort: Option<&str>,
 ) -> anyhow::Result<MessageDto> {
-    insert_message(
-        db,
+    let conn = db.connect()?;
+    insert_assistant_placeholder_on(
+        &conn,
+        thread_id,
+        turn_engine_id,
+        turn_model_id,
+        turn_reasoning_effort,
+    )
+}
+
+fn insert_assistant_placeholder_on(
+    conn: &Connection,
+    thread_id: &str,
+    turn_engine_id: Option<&str>,
+    turn_model_id: Option<&str>,
+    turn_reasoning_effort: Option<&str>,
+) -> anyhow::Result<MessageDto> {
+    insert_message_on(
+        conn,
         thread_id,
         "assistant",
         None,
@@ -69,25 +125,302 @@ pub fn insert_assistant_placeholder(
     )
 }
 
-pub fn delete_message(db: &Database, message_id: &str) -> anyhow::Result<()> {
+/// An assistant message picked for regeneration and the user message it
+/// answered.
+#[derive(Debug, Clone)]
+pub struct RegenerationPair {
+    pub assistant: MessageDto,
+    pub user: MessageDto,
+}
+
+/// Validates that `message_id` is a finished, current assistant message and
+/// finds the user message it answered: the closest earlier user row that is
+/// not a mid-turn steer. A regeneration is appended after whatever followed
+/// its original, so the lookup starts from the first answer in the chain.
+pub fn find_regeneration_pair(db: &Database, message_id: &str) -> anyhow::Result<RegenerationPair> {
+    let conn = db.connect()?;
+    find_regeneration_pair_on(&conn, message_id)
+}
+
+pub(crate) fn find_regeneration_pair_on(
+    conn: &Connection,
+    message_id: &str,
+) -> anyhow::Result<RegenerationPair> {
+    let assistant = conn
+        .query_row(
+            "SELECT id, thread_id, role, content, blocks_json, schema_version, status,
+            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at, seq,
+            regenerated_from, superseded_at IS NOT NULL
+     FROM messages
+     WHERE id = ?1",
+            params![message_id],
+            map_message_row,
+        )
+        .optional()
+        .context("failed to load message")?
+        .with_context(|| format!("message not found: {message_id}"))?;
+    if assistant.role != "assistant" {
+        anyhow::bail!("only assistant messages can be regenerated");
+    }
+    if !matches!(
+        assistant.status,
+        MessageStatusDto::Completed | MessageStatusDto::Error
+    ) {
+        anyhow::bail!("only completed or failed assistant messages can be regenerated");
+    }
+    if assistant.superseded {
+        anyhow::bail!("message {message_id} has already been regenerated");
+    }
+
+    let user = find_prompt_for_reply(conn, &assistant)?;
+    Ok(RegenerationPair { assistant, user })
+}
+
+/// The user message `assistant` answered: the closest earlier user row that
+/// is not a mid-turn steer, measured from the first answer in its
+/// regeneration chain.
+fn find_prompt_for_reply(conn: &Connection, assistant: &MessageDto) -> anyhow::Result<MessageDto> {
+    let mut anchor_seq = assistant.seq;
+    let mut origin = assistant.regenerated_from.clone();
+    let mut visited = std::collections::HashSet::new();
+    while let Some(origin_id) = origin.take() {
+        if !visited.insert(origin_id.clone()) {
+            break;
+        }
+        let row: Option<(Option<i64>, Option<String>)> = conn
+            .query_row(
+                "SELECT seq, regenerated_from FROM messages WHERE id = ?1 AND thread_id = ?2",
+                params![origin_id, assistant.thread_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if let Some((seq, next)) = row {
+            anchor_seq = seq.unwrap_or(anchor_seq);
+            origin = next;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, thread_id, role, content, blocks_json, schema_version, status,
+            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at, seq,
+            regenerated_from, superseded_at IS NOT NULL
+     FROM messages
+     WHERE thread_id = ?1 AND role = 'user' AND seq < ?2
+     ORDER BY seq DESC",
+    )?;
+    let mut rows = stmt.query_map(params![assistant.thread_id, anchor_seq], map_message_row)?;
+    loop {
+        let Some(row) = rows.next() else {
+            anyhow::bail!(
+                "no user message precedes assistant message {}",
+                assistant.id
+            );
+        };
+        let mut candidate = row?;
+        blobs::resolve_blob_refs(conn, candidate.blocks.as_mut())?;
+        if !message_has_steer_marker(&candidate) {
+            return Ok(candidate);
+        }
+    }
+}
+
+/// The prompt to re-run for a thread whose last turn failed, and what to
+/// clear out first.
+#[derive(Debug, Clone)]
+pub struct RetryTarget {
+    pub user: MessageDto,
+    /// The failed reply and anything sent to steer it.
+    pub discarded_message_ids: Vec<String>,
+    /// Set when the failed turn was a regeneration; the retry supersedes the
+    /// same original answer.
+    pub superseded_message_id: Option<String>,
+}
+
+/// Finds the latest turn of `thread_id` for a retry. Its reply must have
+/// failed, been interrupted, or never been written; a completed reply is
+/// refused since regenerating it is the way to ask again.
+pub fn find_retry_target(db: &Database, thread_id: &str) -> anyhow::Result<RetryTarget> {
     let conn = db.connect()?;
-    conn.execute("DELETE FROM messages WHERE id = ?1", params![message_id])
-        .context("failed to delete message")?;
+    find_retry_target_on(&conn, thread_id)
+}
+
+pub(crate) fn find_retry_target_on(
+    conn: &Connection,
+    thread_id: &str,
+) -> anyhow::Result<RetryTarget> {
+    let messages = get_thread_messages_on(conn, thread_id)?;
+    let latest_prompt = messages
+        .iter()
+        .rposition(|message| message.role == "user" && !message_has_steer_marker(message))
+        .context("this thread has no message to retry")?;
+    let latest_reply = messages
+        .iter()
+        .rposition(|message| message.role == "assistant" && !message.superseded)
+        .filter(|index| *index > latest_prompt)
+        .map(|index| &messages[index]);
+
+    if let Some(reply) = latest_reply {
+        if reply.status == MessageStatusDto::Completed {
+            anyhow::bail!("the last turn completed; regenerate its response instead");
+        }
+        if let Some(original) = reply.regenerated_from.clone() {
+            return Ok(RetryTarget {
+                user: find_prompt_for_reply(conn, reply)?,
+                discarded_message_ids: vec![reply.id.clone()],
+                superseded_message_id: Some(original),
+            });
+        }
+    }
+
+    Ok(RetryTarget {
+        user: messages[latest_prompt].clone(),
+        discarded_message_ids: messages[latest_prompt + 1..]
+            .iter()
+            .filter(|message| !message.superseded)
+            .map(|message| message.id.clone())
+            .collect(),
+        superseded_message_id: None,
+    })
+}
+
+/// Marks `superseded_message_id` as replaced and inserts the assistant
+/// placeholder that replaces it.
+pub fn insert_regenerated_placeholder(
+    db: &Database,
+    thread_id: &str,
+    superseded_message_id: &str,
+    turn_engine_id: Option<&str>,
+    turn_model_id: Option<&str>,
+    turn_reasoning_effort: Option<&str>,
+) -> anyhow::Result<MessageDto> {
+    let mut conn = db.connect()?;
+    let tx = conn
+        .transaction()
+        .context("failed to start message regeneration transaction")?;
+    let mut message = insert_assistant_placeholder_on(
+        &tx,
+        thread_id,
+        turn_engine_id,
+        turn_model_id,
+        turn_reasoning_effort,
+    )?;
+    tx.execute(
+        "UPDATE messages SET regenerated_from = ?1 WHERE id = ?2",
+        params![superseded_message_id, message.id],
+    )
+    .context("failed to link regenerated message")?;
+    tx.execute(
+        "UPDATE messages SET superseded_at = ?1 WHERE id = ?2 AND thread_id = ?3",
+        params![message_timestamp_now(), superseded_message_id, thread_id],
+    )
+    .context("failed to mark message superseded")?;
+    tx.commit()
+        .context("failed to commit message regeneration transaction")?;
+    message.regenerated_from = Some(superseded_message_id.to_string());
+    Ok(message)
+}
+
+/// Deletes one message from `thread_id` with its actions and approvals. While
+/// `turn_active`, the streaming assistant reply of that turn is refused.
+pub fn delete_message(
+    db: &Database,
+    thread_id: &str,
+    message_id: &str,
+    turn_active: bool,
+) -> anyhow::Result<()> {
+    let mut conn = db.connect()?;
+    let tx = conn
+        .transaction()
+        .context("failed to start message delete transaction")?;
+    delete_message_on(&tx, thread_id, message_id, turn_active)?;
+    tx.commit()
+        .context("failed to commit message delete transaction")?;
     Ok(())
 }
 
+/// [`delete_message`] on a connection the caller has already opened a
+/// transaction on.
+pub(crate) fn delete_message_on(
+    conn: &Connection,
+    thread_id: &str,
+    message_id: &str,
+    turn_active: bool,
+) -> anyhow::Result<()> {
+    let (role, status): (String, String) = conn
+        .query_row(
+            "SELECT role, status FROM messages WHERE id = ?1 AND thread_id = ?2",
+            params![message_id, thread_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .context("failed to load message")?
+        .ok_or_else(|| anyhow::anyhow!("message {message_id} not found in thread {thread_id}"))?;
+    if turn_active && role == "assistant" && status == MessageStatusDto::Streaming.as_str() {
+        anyhow::bail!("the reply of a running turn cannot be deleted; cancel the turn first");
+    }
+    delete_thread_messages(conn, thread_id, &[message_id.to_string()])
+}
+
+/// Copies the messages of `source_thread_id`, through `up_to_message_id` when
+/// given, into `target_thread_id` under new ids, along with their action rows.
+/// Action ids are reissued and the copied blocks point at the new ones.
 pub fn clone_thread_messages(
     db: &Database,
     source_thread_id: &str,
     target_thread_id: &str,
+    up_to_message_id: Option<&str>,
 ) -> anyhow::Result<usize> {
-    let messages = get_thread_messages(db, source_thread_id)?;
     let mut conn = db.connect()?;
     let tx = conn
         .transaction()
         .context("failed to start thread message clone transaction")?;
+    let cloned =
+        clone_thread_messages_on(&tx, source_thread_id, target_thread_id, up_to_message_id)?;
+    tx.commit()
+        .context("failed to commit thread message clone transaction")?;
+    Ok(cloned)
+}
+
+/// [`clone_thread_messages`] on a connection the caller has already opened a
+/// transaction on.
+pub(crate) fn clone_thread_messages_on(
+    tx: &Connection,
+    source_thread_id: &str,
+    target_thread_id: &str,
+    up_to_message_id: Option<&str>,
+) -> anyhow::Result<usize> {
+    let mut messages = get_thread_messages_on(tx, source_thread_id)?;
+    if let Some(up_to_message_id) = up_to_message_id {
+        let index = messages
+            .iter()
+            .position(|message| message.id == up_to_message_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!("message {up_to_message_id} not found in thread {source_thread_id}")
+            })?;
+        messages.truncate(index + 1);
+    }
 
+    let mut cloned_ids = HashMap::with_capacity(messages.len());
     for (index, message) in messages.iter().enumerate() {
+        let cloned_id = Uuid::new_v4().to_string();
+        cloned_ids.insert(message.id.as_str(), cloned_id.clone());
+        let regenerated_from = message
+            .regenerated_from
+            .as_deref()
+            .and_then(|id| cloned_ids.get(id).cloned());
+        let actions = load_message_actions(tx, &message.id)?
+            .into_iter()
+            .map(|action| (Uuid::new_v4().to_string(), action))
+            .collect::<Vec<_>>();
+        let blocks = message
+            .blocks
+            .clone()
+            .map(|mut blocks| {
+                remap_block_action_ids(&mut blocks, &actions);
+                blobs::externalize_large_blocks(tx, &mut blocks)?;
+                anyhow::Ok(blocks)
+            })
+            .transpose()?;
         let token_usage = message
             .token_usage
             .as_ref()
@@ -96,22 +429,26 @@ pub fn clone_thread_messages(
                 input: 0,
                 output: 0,
             });
-        let created_at = (Utc::now() + ChronoDuration::milliseconds(index as i64))
-            .format("%Y-%m-%d %H:%M:%S%.3f")
-            .to_string();
+        let created_at =
+            format_message_timestamp(Utc::now() + ChronoDuration::milliseconds(index as i64));
 
         tx.execute(
-            "INSERT INTO messages (
+            &format!(
+                "INSERT INTO messages (
                 id, thread_id, role, content, blocks_json, turn_engine_id, turn_model_id,
                 turn_reasoning_effort, schema_version, stream_seq, status, token_input,
-                token_output, created_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11, ?12, ?13)",
+                token_output, created_at, regenerated_from, superseded_at, seq
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11, ?12, ?13, ?14,
+                CASE WHEN ?15 THEN ?13 END, {NEXT_MESSAGE_SEQ_SQL}
+            )"
+            ),
             params![
-                Uuid::new_v4().to_string(),
+                cloned_id,
                 target_thread_id,
                 message.role,
                 message.content,
-                message.blocks.as_ref().map(Value::to_string),
+                blocks.as_ref().map(Value::to_string),
                 message.turn_engine_id,
                 message.turn_model_id,
                 message.turn_reasoning_effort,
@@ -120,16 +457,94 @@ pub fn clone_thread_messages(
                 token_usage.input as i64,
                 token_usage.output as i64,
                 created_at,
+                regenerated_from,
+                message.superseded,
             ],
         )
         .context("failed to clone thread message")?;
+
+        for (action_id, action) in &actions {
+            tx.execute(
+                "INSERT INTO actions (
+                    id, thread_id, message_id, engine_action_id, action_type, summary,
+                    details_json, status, truncated, result_json, duration_ms, created_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    action_id,
+                    target_thread_id,
+                    cloned_id,
+                    action.engine_action_id,
+                    action.action_type,
+                    action.summary,
+                    action.details_json,
+                    action.status,
+                    action.truncated,
+                    action.result_json,
+                    action.duration_ms,
+                    action.created_at,
+                ],
+            )
+            .context("failed to clone thread action")?;
+        }
     }
 
-    tx.commit()
-        .context("failed to commit thread message clone transaction")?;
     Ok(messages.len())
 }
 
+struct ActionRow {
+    id: String,
+    engine_action_id: Option<String>,
+    action_type: String,
+    summary: String,
+    details_json: String,
+    status: String,
+    truncated: i64,
+    result_json: Option<String>,
+    duration_ms: Option<i64>,
+    created_at: String,
+}
+
+fn load_message_actions(conn: &Connection, message_id: &str) -> anyhow::Result<Vec<ActionRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, engine_action_id, action_type, summary, details_json, status, truncated,
+                result_json, duration_ms, created_at
+         FROM actions
+         WHERE message_id = ?1
+         ORDER BY created_at ASC, id ASC",
+    )?;
+    let rows = stmt.query_map(params![message_id], |row| {
+        Ok(ActionRow {
+            id: row.get(0)?,
+            engine_action_id: row.get(1)?,
+            action_type: row.get(2)?,
+            summary: row.get(3)?,
+            details_json: row.get(4)?,
+            status: row.get(5)?,
+            truncated: row.get(6)?,
+            result_json: row.get(7)?,
+            duration_ms: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load message actions")
+}
+
+/// Points `actionId` on each block at the action's reissued id.
+fn remap_block_action_ids(blocks: &mut Value, actions: &[(String, ActionRow)]) {
+    let Some(blocks) = blocks.as_array_mut() else {
+        return;
+    };
+    for block in blocks {
+        let Some(action_id) = block.get("actionId").and_then(Value::as_str) else {
+            continue;
+        };
+        if let Some((cloned_id, _)) = actions.iter().find(|(_, action)| action.id == action_id) {
+            block["actionId"] = Value::String(cloned_id.clone());
+        }
+    }
+}
+
 pub fn replace_thread_messages(
     db: &Database,
     thread_id: &str,
@@ -139,7 +554,19 @@ pub fn replace_thread_messages(
     let tx = conn
         .transaction()
         .context("failed to start thread message import transaction")?;
+    let imported = replace_thread_messages_on(&tx, thread_id, messages)?;
+    tx.commit()
+        .context("failed to commit thread message import transaction")?;
+    Ok(imported)
+}
 
+/// [`replace_thread_messages`] on a connection the caller has already opened
+/// a transaction on.
+pub(crate) fn replace_thread_messages_on(
+    tx: &Connection,
+    thread_id: &str,
+    messages: &[ImportedMessageRecord],
+) -> anyhow::Result<usize> {
     tx.execute(
         "DELETE FROM actions WHERE thread_id = ?1",
         params![thread_id],
@@ -158,38 +585,39 @@ pub fn replace_thread_messages(
 
     let fallback_created_at_base = Utc::now();
     for (index, message) in messages.iter().enumerate() {
-        let created_at = message.created_at.clone().unwrap_or_else(|| {
-            (fallback_created_at_base + ChronoDuration::milliseconds(index as i64))
-                .format("%Y-%m-%d %H:%M:%S%.3f")
-                .to_string()
-        });
+        let created_at = message
+            .created_at
+            .as_deref()
+            .and_then(parse_message_timestamp)
+            .unwrap_or(fallback_created_at_base + ChronoDuration::milliseconds(index as i64));
 
         tx.execute(
-            "INSERT INTO messages (
+            &format!(
+                "INSERT INTO messages (
                 id, thread_id, role, content, blocks_json, turn_engine_id, turn_model_id,
                 turn_reasoning_effort, schema_version, stream_seq, status, token_input,
-                token_output, created_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1, 0, ?9, ?10, ?11, ?12)",
+                token_output, created_at, seq
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?13, 0, ?9, ?10, ?11, ?12, {NEXT_MESSAGE_SEQ_SQL})"
+            ),
             params![
                 Uuid::new_v4().to_string(),
                 thread_id,
                 message.role,
                 message.content,
-                message.blocks.to_string(),
+                blobs::externalize_large_blocks_json(tx, &message.blocks.to_string())?,
                 message.turn_engine_id,
                 message.turn_model_id,
                 message.turn_reasoning_effort,
                 message.status.as_str(),
                 message.token_input as i64,
                 message.token_output as i64,
-                created_at,
+                format_message_timestamp(created_at),
+                BLOCK_SCHEMA_VERSION,
             ],
         )
         .context("failed to insert imported thread message")?;
     }
 
-    tx.commit()
-        .context("failed to commit thread message import transaction")?;
     Ok(messages.len())
 }
 
@@ -228,7 +656,86 @@ pub fn drop_last_turns(db: &Database, thread_id: &str, num_turns: u32) -> anyhow
     let tx = conn
         .transaction()
         .context("failed to start thread rollback transaction")?;
+    delete_thread_messages(&tx, thread_id, &message_ids)?;
+
+    // Note: all approvals (including pending ones) for the deleted messages are already
+    // removed by delete_thread_messages, which scopes deletions to
+    // message_id IN (...). The previous thread-wide pending approval sweep is intentionally
+    // removed here to avoid deleting pending approvals that belong to retained messages.
+
+    tx.commit()
+        .context("failed to commit thread rollback transaction")?;
+    Ok(message_ids.len())
+}
+
+/// Replaces the text of user message `message_id` and deletes every message
+/// after it in the thread, with their actions and approvals. Attachments,
+/// skills and mentions on the edited message are kept. Runs on `conn` so the
+/// caller can commit it with the rest of the edit. Returns how many later
+/// messages were removed.
+pub(crate) fn rewrite_user_message_on(
+    conn: &Connection,
+    thread_id: &str,
+    message_id: &str,
+    content: &str,
+) -> anyhow::Result<usize> {
+    let messages = get_thread_messages_on(conn, thread_id)?;
+    let index = messages
+        .iter()
+        .position(|message| message.id == message_id)
+        .ok_or_else(|| anyhow::anyhow!("message {message_id} not found in thread {thread_id}"))?;
+    let message = &messages[index];
+    if message.role != "user" {
+        anyhow::bail!("only user messages can be edited");
+    }
+    if message_has_steer_marker(message) {
+        anyhow::bail!("messages sent to steer a running turn cannot be edited");
+    }
+    // The blocks came back with blob refs resolved; store large payloads
+    // out of line again like an insert does.
+    let blocks = message
+        .blocks
+        .clone()
+        .map(|mut blocks| {
+            replace_user_text_block(&mut blocks, content);
+            blobs::externalize_large_blocks(conn, &mut blocks)?;
+            anyhow::Ok(blocks)
+        })
+        .transpose()?;
+    let later_message_ids = messages[index + 1..]
+        .iter()
+        .map(|message| message.id.clone())
+        .collect::<Vec<_>>();
+
+    conn.execute(
+        "UPDATE messages SET content = ?1, blocks_json = ?2 WHERE id = ?3",
+        params![content, blocks.as_ref().map(Value::to_string), message_id],
+    )
+    .context("failed to update edited message")?;
+    delete_thread_messages(conn, thread_id, &later_message_ids)?;
+    Ok(later_message_ids.len())
+}
+
+fn replace_user_text_block(blocks: &mut Value, content: &str) {
+    let Some(blocks) = blocks.as_array_mut() else {
+        return;
+    };
+    match blocks
+        .iter_mut()
+        .find(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+    {
+        Some(block) => block["content"] = Value::String(content.to_string()),
+        None => blocks.push(serde_json::json!({ "type": "text", "content": content })),
+    }
+}
 
+/// Deletes `message_ids` from `thread_id` along with their actions and
+/// approvals.
+fn delete_thread_messages(
+    conn: &Connection,
+    thread_id: &str,
+    message_ids: &[String],
+) -> anyhow::Result<()> {
     for chunk in message_ids.chunks(500) {
         let placeholders = std::iter::repeat_n("?", chunk.len())
             .collect::<Vec<_>>()
@@ -243,30 +750,22 @@ pub fn drop_last_turns(db: &Database, thread_id: &str, num_turns: u32) -> anyhow
         let mut actions_params = Vec::with_capacity(chunk.len() + 1);
         actions_params.push(rusqlite::types::Value::from(thread_id.to_string()));
         actions_params.extend(chunk.iter().cloned().map(rusqlite::types::Value::from));
-        tx.execute(&delete_actions_sql, params_from_iter(actions_params))
-            .context("failed to delete rolled-back thread actions")?;
+        conn.execute(&delete_actions_sql, params_from_iter(actions_params))
+            .context("failed to delete thread actions")?;
 
         let mut approvals_params = Vec::with_capacity(chunk.len() + 1);
         approvals_params.push(rusqlite::types::Value::from(thread_id.to_string()));
         approvals_params.extend(chunk.iter().cloned().map(rusqlite::types::Value::from));
-        tx.execute(&delete_approvals_sql, params_from_iter(approvals_params))
-            .context("failed to delete rolled-back thread approvals")?;
+        conn.execute(&delete_approvals_sql, params_from_iter(approvals_params))
+            .context("failed to delete thread approvals")?;
 
         let mut message_params = Vec::with_capacity(chunk.len() + 1);
         message_params.push(rusqlite::types::Value::from(thread_id.to_string()));
         message_params.extend(chunk.iter().cloned().map(rusqlite::types::Value::from));
-        tx.execute(&delete_messages_sql, params_from_iter(message_params))
-            .context("failed to delete rolled-back thread messages")?;
+        conn.execute(&delete_messages_sql, params_from_iter(message_params))
+            .context("failed to delete thread messages")?;
     }
-
-    // Note: all approvals (including pending ones) for the deleted messages are already
-    // removed by the chunked delete_approvals_sql above, which scopes deletions to
-    // message_id IN (...). The previous thread-wide pending approval sweep is intentionally
-    // removed here to avoid deleting pending approvals that belong to retained messages.
-
-    tx.commit()
-        .context("failed to commit thread rollback transaction")?;
-    Ok(message_ids.len())
+    Ok(())
 }
 
 fn message_has_steer_marker(message: &MessageDto) -> bool {
@@ -289,29 +788,31 @@ fn message_has_steer_marker(message: &MessageDto) -> bool {
     })
 }
 
-pub fn update_assistant_blocks_json(
-    db: &Database,
+pub(crate) fn update_assistant_blocks_json_on(
+    conn: &Connection,
     message_id: &str,
     blocks_json: &str,
     status: MessageStatusDto,
     turn_model_id: Option<&str>,
 ) -> anyhow::Result<()> {
-    let conn = db.connect()?;
-    let normalized_blocks_json = normalize_blocks_json_for_message(&conn, message_id, blocks_json)?;
+    let normalized_blocks_json = normalize_blocks_json_for_message(conn, message_id, blocks_json)?;
+    let stored_blocks_json = blobs::externalize_large_blocks_json(conn, &normalized_blocks_json)?;
     // Mirror the text blocks into the content column: the messages_fts
     // triggers index only content, so leaving it NULL keeps assistant replies
     // out of global search.
     let searchable_text = extract_searchable_text_from_blocks(&normalized_blocks_json);
     conn.execute(
         "UPDATE messages
-     SET blocks_json = ?1, content = ?2, status = ?3, turn_model_id = COALESCE(?4, turn_model_id)
+     SET blocks_json = ?1, content = ?2, status = ?3, turn_model_id = COALESCE(?4, turn_model_id),
+         schema_version = ?6
      WHERE id = ?5",
         params![
-            normalized_blocks_json,
+            stored_blocks_json,
             searchable_text,
             status.as_str(),
             turn_model_id,
-            message_id
+            message_id,
+            BLOCK_SCHEMA_VERSION
         ],
     )
     .context("failed to update assistant blocks")?;
@@ -320,12 +821,12 @@ pub fn update_assistant_blocks_json(
 
 fn extract_searchable_text_from_blocks(blocks_json: &str) -> Option<String> {
     let blocks: Value = serde_json::from_str(blocks_json).ok()?;
-    let parts: Vec<&str> = blocks
-        .as_array()?
-        .iter()
-        .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
-        .filter_map(|block| block.get("content").and_then(Value::as_str))
-        .map(str::trim)
+    let parts: Vec<String> = ContentBlock::parse_list(&blocks)
+        .into_iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { content, .. } => Some(content.trim().to_string()),
+            _ => None,
+        })
         .filter(|content| !content.is_empty())
         .collect();
 
@@ -336,12 +837,11 @@ fn extract_searchable_text_from_blocks(blocks_json: &str) -> Option<String> {
     }
 }
 
-pub fn update_assistant_status(
-    db: &Database,
+pub(crate) fn update_assistant_status_on(
+    conn: &Connection,
     message_id: &str,
     status: MessageStatusDto,
 ) -> anyhow::Result<()> {
-    let conn = db.connect()?;
     conn.execute(
         "UPDATE messages
      SET status = ?1
@@ -380,12 +880,11 @@ pub fn complete_assistant_message(
     Ok(())
 }
 
-pub fn update_assistant_turn_model_id(
-    db: &Database,
+pub(crate) fn update_assistant_turn_model_id_on(
+    conn: &Connection,
     message_id: &str,
     turn_model_id: &str,
 ) -> anyhow::Result<()> {
-    let conn = db.connect()?;
     conn.execute(
         "UPDATE messages
      SET turn_model_id = ?1
@@ -398,12 +897,20 @@ pub fn update_assistant_turn_model_id(
 
 pub fn get_thread_messages(db: &Database, thread_id: &str) -> anyhow::Result<Vec<MessageDto>> {
     let conn = db.connect()?;
+    get_thread_messages_on(&conn, thread_id)
+}
+
+pub(crate) fn get_thread_messages_on(
+    conn: &Connection,
+    thread_id: &str,
+) -> anyhow::Result<Vec<MessageDto>> {
     let mut stmt = conn.prepare(
         "SELECT id, thread_id, role, content, blocks_json, schema_version, status,
-            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at
+            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at, seq,
+            regenerated_from, superseded_at IS NOT NULL
      FROM messages
      WHERE thread_id = ?1
-     ORDER BY created_at ASC, rowid ASC",
+     ORDER BY seq ASC, rowid ASC",
     )?;
 
     let rows = stmt.query_map(params![thread_id], map_message_row)?;
@@ -412,58 +919,185 @@ pub fn get_thread_messages(db: &Database, thread_id: &str) -> anyhow::Result<Vec
     for row in rows {
         out.push(row?);
     }
-    reconcile_answered_approvals_for_messages(&conn, &mut out)?;
+    blobs::resolve_blob_refs(conn, out.iter_mut().filter_map(|m| m.blocks.as_mut()))?;
+    reconcile_answered_approvals_for_messages(conn, &mut out)?;
 
     Ok(out)
 }
 
-pub fn get_thread_messages_window(
+const MODEL_HISTORY_SELECT_SQL: &str =
+    "SELECT id, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at
+     FROM messages
+     WHERE thread_id = ?1
+       AND role = 'assistant'
+       AND turn_engine_id IS NOT NULL
+       AND turn_model_id IS NOT NULL";
+
+/// Engine/model/effort each assistant turn ran with, oldest first. Rows from
+/// before turn metadata was recorded carry no engine or model and are left out.
+pub fn get_thread_model_history(
+    db: &Database,
+    thread_id: &str,
+) -> anyhow::Result<ThreadModelHistoryDto> {
+    let conn = db.connect()?;
+    let mut stmt = conn.prepare(&format!(
+        "{MODEL_HISTORY_SELECT_SQL}
+     ORDER BY seq ASC, rowid ASC"
+    ))?;
+    let entries = stmt
+        .query_map(params![thread_id], map_model_history_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    let ranges = collapse_model_history(&entries);
+    Ok(ThreadModelHistoryDto {
+        thread_id: thread_id.to_string(),
+        entries,
+        ranges,
+    })
+}
+
+pub fn latest_assistant_model_entry(
+    db: &Database,
+    thread_id: &str,
+) -> anyhow::Result<Option<ThreadModelHistoryEntryDto>> {
+    let conn = db.connect()?;
+    conn.query_row(
+        &format!(
+            "{MODEL_HISTORY_SELECT_SQL}
+     ORDER BY seq DESC, rowid DESC
+     LIMIT 1"
+        ),
+        params![thread_id],
+        map_model_history_row,
+    )
+    .optional()
+    .context("failed to load latest assistant model")
+}
+
+/// `(role, text)` of the thread's last `limit` user and assistant messages
+/// that have text, oldest first. Superseded replies are skipped.
+pub fn recent_message_texts(
     db: &Database,
     thread_id: &str,
-    cursor: Option<&MessageWindowCursorDto>,
     limit: usize,
-) -> anyhow::Result<MessageWindowDto> {
+) -> anyhow::Result<Vec<(String, String)>> {
     let conn = db.connect()?;
     let mut stmt = conn.prepare(
-        "SELECT id, thread_id, role, content, blocks_json, schema_version, status,
-            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at, rowid
-     FROM messages
-     WHERE thread_id = ?1
-       AND (
-         ?2 IS NULL
-         OR created_at < ?2
-         OR (
-           created_at = ?2
-           AND (
-             (?3 IS NOT NULL AND rowid < ?3)
-             OR (?3 IS NULL AND ?4 IS NOT NULL AND id < ?4)
-           )
-         )
-       )
-     ORDER BY created_at DESC, rowid DESC
-     LIMIT ?5",
+        "SELECT role, content
+         FROM messages
+         WHERE thread_id = ?1
+           AND role IN ('user', 'assistant')
+           AND superseded_at IS NULL
+           AND TRIM(COALESCE(content, '')) != ''
+         ORDER BY seq DESC, rowid DESC
+         LIMIT ?2",
     )?;
+    let mut texts = stmt
+        .query_map(params![thread_id, limit as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to load recent message text")?;
+    texts.reverse();
+    Ok(texts)
+}
 
-    let cursor_created_at = cursor.map(|value| value.created_at.as_str());
-    let cursor_row_id = cursor.and_then(|value| value.row_id);
-    let cursor_id = cursor.map(|value| value.id.as_str());
-    let query_limit = limit.max(1).saturating_add(1) as i64;
-    let rows = stmt.query_map(
-        params![
-            thread_id,
-            cursor_created_at,
-            cursor_row_id,
-            cursor_id,
-            query_limit
-        ],
-        |row| {
-            let message = map_message_row(row)?;
-            let row_id: i64 = row.get(13)?;
-            Ok((message, row_id))
-        },
-    )?;
+fn map_model_history_row(row: &Row<'_>) -> rusqlite::Result<ThreadModelHistoryEntryDto> {
+    Ok(ThreadModelHistoryEntryDto {
+        message_id: row.get(0)?,
+        engine_id: row.get(1)?,
+        model_id: row.get(2)?,
+        reasoning_effort: row.get(3)?,
+        started_at: row.get(4)?,
+    })
+}
 
-    let mut messages_desc: Vec<(MessageDto, i64)> = Vec::new();
+fn collapse_model_history(entries: &[ThreadModelHistoryEntryDto]) -> Vec<ThreadModelRangeDto> {
+    let mut ranges: Vec<ThreadModelRangeDto> = Vec::new();
+    for entry in entries {
+        if let Some(range) = ranges.last_mut() {
+            if range.engine_id == entry.engine_id
+                && range.model_id == entry.model_id
+                && range.reasoning_effort == entry.reasoning_effort
+            {
+                range.last_message_id = entry.message_id.clone();
+                range.turn_count += 1;
+                continue;
+            }
+        }
+        ranges.push(ThreadModelRangeDto {
+            engine_id: entry.engine_id.clone(),
+            model_id: entry.model_id.clone(),
+            reasoning_effort: entry.reasoning_effort.clone(),
+            first_message_id: entry.message_id.clone(),
+            last_message_id: entry.message_id.clone(),
+            started_at: entry.started_at.clone(),
+            turn_count: 1,
+        });
+    }
+    ranges
+}
+
+pub fn get_thread_messages_window(
+    db: &Database,
+    thread_id: &str,
+    cursor: Option<&MessageWindowCursorDto>,
+    limit: usize,
+) -> anyhow::Result<MessageWindowDto> {
+    let conn = db.connect()?;
+    let cursor_seq = cursor
+        .map(|cursor| resolve_window_cursor_seq(&conn, thread_id, cursor))
+        .transpose()?;
+    load_message_window(&conn, thread_id, cursor_seq, limit)
+}
+
+/// Up to `limit` messages older than `before_message_id` (or the newest ones
+/// when `None`), in chronological order.
+pub fn get_thread_messages_page(
+    db: &Database,
+    thread_id: &str,
+    before_message_id: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<MessageWindowDto> {
+    let conn = db.connect()?;
+    let before_seq = before_message_id
+        .map(|message_id| {
+            conn.query_row(
+                "SELECT seq FROM messages WHERE id = ?1 AND thread_id = ?2",
+                params![message_id, thread_id],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .with_context(|| format!("message {message_id} not found in thread {thread_id}"))
+        })
+        .transpose()?;
+    load_message_window(&conn, thread_id, before_seq, limit)
+}
+
+fn load_message_window(
+    conn: &Connection,
+    thread_id: &str,
+    before_seq: Option<i64>,
+    limit: usize,
+) -> anyhow::Result<MessageWindowDto> {
+    let mut stmt = conn.prepare(
+        "SELECT id, thread_id, role, content, blocks_json, schema_version, status,
+            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at, seq,
+            regenerated_from, superseded_at IS NOT NULL, rowid
+     FROM messages
+     WHERE thread_id = ?1
+       AND (?2 IS NULL OR seq < ?2)
+     ORDER BY seq DESC
+     LIMIT ?3",
+    )?;
+
+    let query_limit = limit.max(1).saturating_add(1) as i64;
+    let rows = stmt.query_map(params![thread_id, before_seq, query_limit], |row| {
+        let message = map_message_row(row)?;
+        let row_id: i64 = row.get(16)?;
+        Ok((message, row_id))
+    })?;
+
+    let mut messages_desc: Vec<(MessageDto, i64)> = Vec::new();
     for row in rows {
         messages_desc.push(row?);
     }
@@ -481,6 +1115,7 @@ pub fn get_thread_messages_window(
                 created_at: message.created_at.clone(),
                 id: message.id.clone(),
                 row_id: Some(*row_id),
+                seq: Some(message.seq),
             })
     } else {
         None
@@ -491,13 +1126,63 @@ pub fn get_thread_messages_window(
         .into_iter()
         .map(|(message, _)| message)
         .collect();
-    reconcile_answered_approvals_for_messages(&conn, &mut messages)?;
+    blobs::resolve_blob_refs(conn, messages.iter_mut().filter_map(|m| m.blocks.as_mut()))?;
+    reconcile_answered_approvals_for_messages(conn, &mut messages)?;
+    let total_count = conn
+        .query_row(
+            "SELECT COUNT(*) FROM messages WHERE thread_id = ?1",
+            params![thread_id],
+            |row| row.get(0),
+        )
+        .context("failed to count thread messages")?;
     Ok(MessageWindowDto {
         messages,
         next_cursor,
+        has_more,
+        total_count,
     })
 }
 
+/// Cursors issued before `seq` existed only carry the boundary message id and
+/// timestamp; map them onto the seq of that message, or of the first message
+/// at or after the timestamp when it has since been deleted.
+fn resolve_window_cursor_seq(
+    conn: &Connection,
+    thread_id: &str,
+    cursor: &MessageWindowCursorDto,
+) -> anyhow::Result<i64> {
+    if let Some(seq) = cursor.seq {
+        return Ok(seq);
+    }
+    let by_id: Option<Option<i64>> = conn
+        .query_row(
+            "SELECT seq FROM messages WHERE id = ?1 AND thread_id = ?2",
+            params![cursor.id, thread_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(Some(seq)) = by_id {
+        return Ok(seq);
+    }
+    let by_time: Option<i64> = conn.query_row(
+        "SELECT MIN(seq) FROM messages WHERE thread_id = ?1 AND created_at >= ?2",
+        params![thread_id, cursor.created_at],
+        |row| row.get(0),
+    )?;
+    Ok(by_time.unwrap_or(i64::MAX))
+}
+
+pub fn message_thread_id(db: &Database, message_id: &str) -> anyhow::Result<Option<String>> {
+    let conn = db.connect()?;
+    conn.query_row(
+        "SELECT thread_id FROM messages WHERE id = ?1",
+        params![message_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("failed to load message thread")
+}
+
 pub fn get_message_blocks(db: &Database, message_id: &str) -> anyhow::Result<Option<Value>> {
     let conn = db.connect()?;
     let raw_blocks: Option<Option<String>> = conn
@@ -516,6 +1201,7 @@ pub fn get_message_blocks(db: &Database, message_id: &str) -> anyhow::Result<Opt
     let mut blocks = raw_blocks
         .and_then(|raw| serde_json::from_str(&raw).ok())
         .unwrap_or_else(|| serde_json::json!([]));
+    blobs::resolve_blob_refs(&conn, std::iter::once(&mut blocks))?;
     reconcile_answered_approvals_for_message(&conn, message_id, &mut blocks)?;
     Ok(Some(blocks))
 }
@@ -657,54 +1343,422 @@ pub fn mark_approval_block_resolved(
     Ok(true)
 }
 
+/// Characters of context kept around each match in search snippets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnippetContext {
+    pub before: usize,
+    pub after: usize,
+}
+
+const SEARCH_SNIPPET_MAX_CONTEXT_CHARS: usize = 1_000;
+const SEARCH_RESULT_MAX_SNIPPETS: usize = 3;
+const SEARCH_RESULT_DEFAULT_LIMIT: usize = 50;
+const SEARCH_RESULT_MAX_LIMIT: usize = 200;
+const SEARCH_RESULT_COLUMNS: &str = "SELECT m.thread_id,
+            t.title,
+            w.name,
+            t.repo_id,
+            m.id,
+            m.role,
+            COALESCE(m.content, '')";
+const SEARCH_RESULT_SCOPE: &str = "JOIN threads t ON t.id = m.thread_id
+     JOIN workspaces w ON w.id = t.workspace_id
+     WHERE t.workspace_id = ?
+       AND t.archived_at IS NULL";
+
+/// Window into the ranked results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchPage {
+    pub limit: usize,
+    pub offset: usize,
+}
+
+impl Default for SearchPage {
+    fn default() -> Self {
+        Self {
+            limit: SEARCH_RESULT_DEFAULT_LIMIT,
+            offset: 0,
+        }
+    }
+}
+
+impl SearchPage {
+    pub fn new(limit: Option<usize>, offset: Option<usize>) -> Self {
+        Self {
+            limit: limit
+                .unwrap_or(SEARCH_RESULT_DEFAULT_LIMIT)
+                .clamp(1, SEARCH_RESULT_MAX_LIMIT),
+            offset: offset.unwrap_or(0),
+        }
+    }
+}
+
+impl Default for SnippetContext {
+    fn default() -> Self {
+        Self {
+            before: 48,
+            after: 120,
+        }
+    }
+}
+
+impl SnippetContext {
+    pub fn new(before: Option<usize>, after: Option<usize>) -> Self {
+        let defaults = Self::default();
+        Self {
+            before: before
+                .unwrap_or(defaults.before)
+                .min(SEARCH_SNIPPET_MAX_CONTEXT_CHARS),
+            after: after
+                .unwrap_or(defaults.after)
+                .min(SEARCH_SNIPPET_MAX_CONTEXT_CHARS),
+        }
+    }
+}
+
+/// Messages in `workspace_id` matching `query`, best first by bm25 over
+/// the stemmed FTS index. Without FTS5 every term must appear as a
+/// substring and results come newest first.
 pub fn search_messages(
     db: &Database,
     workspace_id: &str,
     query: &str,
+    context: SnippetContext,
+    page: SearchPage,
 ) -> anyhow::Result<Vec<SearchResultDto>> {
-    let Some(search_query) = build_search_messages_query(query) else {
-        return Ok(Vec::new());
-    };
-
+    let terms = search_snippet_terms(query);
     let conn = db.connect()?;
-    let mut stmt = conn.prepare(
-        "SELECT m.thread_id,
-            t.title,
-            w.name,
-            t.repo_id,
-            m.id,
-            COALESCE(m.content, '')
+    let fts_available = super::messages_fts_available(&conn)?;
+    let mut args = vec![SqlValue::Text(workspace_id.to_string())];
+    let sql = if fts_available {
+        let Some(search_query) = build_search_messages_query(query) else {
+            return Ok(Vec::new());
+        };
+        args.push(SqlValue::Text(search_query));
+        format!(
+            "{SEARCH_RESULT_COLUMNS}
      FROM messages_fts
      JOIN messages m ON m.rowid = messages_fts.rowid
-     JOIN threads t ON t.id = m.thread_id
-     JOIN workspaces w ON w.id = t.workspace_id
-     WHERE t.workspace_id = ?1
-       AND t.archived_at IS NULL
-       AND messages_fts MATCH ?2
-     ORDER BY rank
-     LIMIT 50",
+     {SEARCH_RESULT_SCOPE}
+       AND messages_fts MATCH ?
+     ORDER BY bm25(messages_fts), m.rowid DESC
+     LIMIT ? OFFSET ?"
+        )
+    } else {
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut clauses = String::new();
+        for term in &terms {
+            clauses.push_str("\n       AND m.content LIKE ? ESCAPE '\\'");
+            args.push(SqlValue::Text(format!("%{}%", escape_like_pattern(term))));
+        }
+        format!(
+            "{SEARCH_RESULT_COLUMNS}
+     FROM messages m
+     {SEARCH_RESULT_SCOPE}{clauses}
+     ORDER BY m.created_at DESC, m.rowid DESC
+     LIMIT ? OFFSET ?"
+        )
+    };
+    args.push(SqlValue::Integer(page.limit as i64));
+    args.push(SqlValue::Integer(page.offset as i64));
+    let mut stmt = conn.prepare(&sql)?;
+    // Pull only the text blocks of a matched message so large tool payloads
+    // never leave SQLite.
+    let mut blocks_stmt = conn.prepare(
+        "SELECT CAST(b.key AS INTEGER), json_extract(b.value, '$.content')
+     FROM messages m,
+          json_each(CASE WHEN json_valid(m.blocks_json) THEN m.blocks_json ELSE '[]' END) b
+     WHERE m.id = ?1
+       AND json_extract(b.value, '$.type') = 'text'
+       AND json_type(b.value, '$.content') = 'text'
+     ORDER BY b.key",
     )?;
 
-    let rows = stmt.query_map(params![workspace_id, search_query], |row| {
-        Ok(SearchResultDto {
-            thread_id: row.get(0)?,
-            thread_title: row.get(1)?,
-            workspace_name: row.get(2)?,
-            repo_id: row.get(3)?,
-            message_id: row.get(4)?,
-            snippet: build_search_result_snippet(&row.get::<_, String>(5)?, query),
-        })
+    let rows = stmt.query_map(params_from_iter(args), |row| {
+        Ok((
+            SearchResultDto {
+                thread_id: row.get(0)?,
+                thread_title: row.get(1)?,
+                workspace_name: row.get(2)?,
+                repo_id: row.get(3)?,
+                message_id: row.get(4)?,
+                role: row.get(5)?,
+                snippet: String::new(),
+                block_type: None,
+                block_index: None,
+                match_count: 0,
+                snippets: Vec::new(),
+            },
+            row.get::<_, String>(6)?,
+        ))
     })?;
+    // Finish the page before highlighting writes to the scratch table.
+    let rows = rows.collect::<Result<Vec<_>, _>>()?;
 
     let mut out = Vec::new();
-    for row in rows {
-        out.push(row?);
+    for (mut result, content) in rows {
+        let mut segments = blocks_stmt
+            .query_map(params![result.message_id], |row| {
+                Ok(SearchSegment {
+                    block_type: "text",
+                    block_index: row.get::<_, i64>(0)?.try_into().ok(),
+                    text: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        segments.retain(|segment| !segment.text.trim().is_empty());
+        if segments.is_empty() {
+            segments.push(SearchSegment {
+                block_type: "text",
+                block_index: None,
+                text: content,
+            });
+        }
+
+        let mut texts = segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<_>>();
+        texts.push(&content);
+        let mut ranges = if fts_available {
+            fts_match_ranges(&conn, &texts, query)?
+        } else {
+            texts
+                .iter()
+                .map(|text| find_search_match_ranges(text, &terms))
+                .collect()
+        };
+        let content_ranges = ranges.pop().unwrap_or_default();
+        result.snippet = build_search_result_snippet(&content, &content_ranges);
+
+        let (snippets, match_count) = build_search_snippets(&segments, &ranges, context);
+        if let Some(first) = snippets.first() {
+            result.block_type = Some(first.block_type.clone());
+            result.block_index = first.block_index;
+        }
+        result.match_count = match_count;
+        result.snippets = snippets;
+        out.push(result);
     }
 
     Ok(out)
 }
 
+fn escape_like_pattern(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+struct SearchSegment {
+    block_type: &'static str,
+    block_index: Option<usize>,
+    text: String,
+}
+
+fn search_snippet_terms(query: &str) -> Vec<String> {
+    tokenize_search_query(query)
+        .into_iter()
+        .map(|term| term.trim_end_matches('*').to_string())
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+/// Byte ranges of the tokens `query` matches in each of `texts`, as the
+/// stemmed index sees them. The texts go through a scratch FTS5 table with
+/// the `messages_fts` tokenizer so `highlight()` can mark stemmed and prefix
+/// hits ("running" for "run") that a plain substring scan would miss.
+fn fts_match_ranges(
+    conn: &Connection,
+    texts: &[&str],
+    query: &str,
+) -> anyhow::Result<Vec<Vec<SearchMatchRangeDto>>> {
+    let mut out = vec![Vec::new(); texts.len()];
+    let Some(highlight_query) = build_search_highlight_query(query) else {
+        return Ok(out);
+    };
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS temp.message_search_highlight
+       USING fts5(text, tokenize='porter unicode61');
+     DELETE FROM temp.message_search_highlight;",
+    )
+    .context("failed to prepare message search highlighting")?;
+    {
+        let mut insert =
+            conn.prepare("INSERT INTO temp.message_search_highlight(rowid, text) VALUES (?1, ?2)")?;
+        for (idx, text) in texts.iter().enumerate() {
+            insert.execute(params![idx as i64, text])?;
+        }
+    }
+    // Each segment only needs one of the terms to show a hit, so highlight
+    // with the terms OR'd rather than the AND the search itself used.
+    let mut stmt = conn.prepare(
+        "SELECT rowid, highlight(message_search_highlight, 0, char(1), char(2))
+     FROM temp.message_search_highlight
+     WHERE message_search_highlight MATCH ?1",
+    )?;
+    let rows = stmt.query_map(params![highlight_query], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (idx, marked) = row?;
+        let Ok(idx) = usize::try_from(idx) else {
+            continue;
+        };
+        let Some(text) = texts.get(idx) else {
+            continue;
+        };
+        out[idx] = if text.contains(['\u{1}', '\u{2}']) {
+            // The markers would be ambiguous; fall back to the raw terms.
+            find_search_match_ranges(text, &search_snippet_terms(query))
+        } else {
+            parse_highlight_ranges(&marked)
+        };
+    }
+    conn.execute("DELETE FROM temp.message_search_highlight", [])
+        .context("failed to clear message search highlighting")?;
+    Ok(out)
+}
+
+/// Byte ranges, in the unmarked text, between the `\u{1}`/`\u{2}` markers
+/// `highlight()` inserted.
+fn parse_highlight_ranges(marked: &str) -> Vec<SearchMatchRangeDto> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    let mut open = None;
+    for ch in marked.chars() {
+        match ch {
+            '\u{1}' => open = Some(offset),
+            '\u{2}' => {
+                if let Some(start) = open.take() {
+                    ranges.push(SearchMatchRangeDto { start, end: offset });
+                }
+            }
+            _ => offset += ch.len_utf8(),
+        }
+    }
+    ranges
+}
+
+/// Cuts up to `SEARCH_RESULT_MAX_SNIPPETS` snippets from the matched segments,
+/// merging matches that fall within one context window. `ranges` holds each
+/// segment's sorted match ranges. Returns the snippets and the total number
+/// of matches across all segments.
+fn build_search_snippets(
+    segments: &[SearchSegment],
+    ranges: &[Vec<SearchMatchRangeDto>],
+    context: SnippetContext,
+) -> (Vec<SearchSnippetDto>, usize) {
+    let mut snippets = Vec::new();
+    let mut match_count = 0;
+
+    for (segment, ranges) in segments.iter().zip(ranges) {
+        match_count += ranges.len();
+
+        let mut pending = ranges.as_slice();
+        while let Some(first) = pending.first() {
+            if snippets.len() >= SEARCH_RESULT_MAX_SNIPPETS {
+                break;
+            }
+
+            let start = step_back_chars(&segment.text, first.start, context.before);
+            let mut end = step_forward_chars(&segment.text, first.end, context.after);
+            let mut taken = 0;
+            for range in pending {
+                if range.start >= end {
+                    break;
+                }
+                end = end.max(range.end);
+                taken += 1;
+            }
+
+            snippets.push(SearchSnippetDto {
+                text: segment.text[start..end].to_string(),
+                block_type: segment.block_type.to_string(),
+                block_index: segment.block_index,
+                match_ranges: pending[..taken]
+                    .iter()
+                    .map(|range| SearchMatchRangeDto {
+                        start: range.start - start,
+                        end: range.end - start,
+                    })
+                    .collect(),
+                truncated_start: start > 0,
+                truncated_end: end < segment.text.len(),
+            });
+            pending = &pending[taken..];
+        }
+    }
+
+    // FTS tokenization can match text the plain substring scan misses; keep a
+    // leading excerpt so the result still has something to show.
+    if snippets.is_empty() {
+        if let Some(segment) = segments.first() {
+            let end = step_forward_chars(&segment.text, 0, context.before + context.after);
+            snippets.push(SearchSnippetDto {
+                text: segment.text[..end].to_string(),
+                block_type: segment.block_type.to_string(),
+                block_index: segment.block_index,
+                match_ranges: Vec::new(),
+                truncated_start: false,
+                truncated_end: end < segment.text.len(),
+            });
+        }
+    }
+
+    (snippets, match_count)
+}
+
+/// Sorted, non-overlapping byte ranges in `text` matching any of `terms`.
+fn find_search_match_ranges(text: &str, terms: &[String]) -> Vec<SearchMatchRangeDto> {
+    let mut ranges = terms
+        .iter()
+        .flat_map(|term| find_term_ranges_case_insensitive(text, term))
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|range| (range.start, range.end));
+
+    let mut merged: Vec<SearchMatchRangeDto> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn step_back_chars(text: &str, from: usize, chars: usize) -> usize {
+    if chars == 0 {
+        return from;
+    }
+    text[..from]
+        .char_indices()
+        .rev()
+        .take(chars)
+        .last()
+        .map(|(idx, _)| idx)
+        .unwrap_or(from)
+}
+
+fn step_forward_chars(text: &str, from: usize, chars: usize) -> usize {
+    text[from..]
+        .char_indices()
+        .nth(chars)
+        .map(|(idx, _)| from + idx)
+        .unwrap_or(text.len())
+}
+
 fn build_search_messages_query(query: &str) -> Option<String> {
+    join_search_messages_tokens(query, " AND ")
+}
+
+fn build_search_highlight_query(query: &str) -> Option<String> {
+    join_search_messages_tokens(query, " OR ")
+}
+
+fn join_search_messages_tokens(query: &str, separator: &str) -> Option<String> {
     let tokens = tokenize_search_query(query)
         .into_iter()
         .filter_map(|token| format_search_messages_token(&token))
@@ -713,7 +1767,7 @@ fn build_search_messages_query(query: &str) -> Option<String> {
     if tokens.is_empty() {
         None
     } else {
-        Some(tokens.join(" AND "))
+        Some(tokens.join(separator))
     }
 }
 
@@ -733,24 +1787,18 @@ fn format_search_messages_token(token: &str) -> Option<String> {
     Some(format!("\"{}\"{}", escaped, suffix))
 }
 
-fn build_search_result_snippet(content: &str, query: &str) -> String {
+/// Single excerpt around the first of `ranges`, which are byte ranges into
+/// the untrimmed `content`.
+fn build_search_result_snippet(content: &str, ranges: &[SearchMatchRangeDto]) -> String {
     let trimmed = content.trim();
     if trimmed.is_empty() {
         return String::new();
     }
 
-    let terms = tokenize_search_query(query);
-    let match_index = terms
-        .iter()
-        .filter_map(|term| {
-            let snippet_term = term.trim_end_matches('*');
-            if snippet_term.is_empty() {
-                None
-            } else {
-                find_term_case_insensitive(trimmed, snippet_term)
-            }
-        })
-        .min()
+    let leading = content.len() - content.trim_start().len();
+    let match_index = ranges
+        .first()
+        .map(|range| range.start.saturating_sub(leading).min(trimmed.len()))
         .unwrap_or(0);
 
     let context_before = 48usize;
@@ -786,34 +1834,50 @@ fn build_search_result_snippet(content: &str, query: &str) -> String {
     snippet
 }
 
-fn find_term_case_insensitive(haystack: &str, needle: &str) -> Option<usize> {
-    if needle.is_empty() {
-        return Some(0);
+/// Every case-insensitive occurrence of `needle`, as byte ranges into the
+/// original (unfolded) `haystack`.
+fn find_term_ranges_case_insensitive(haystack: &str, needle: &str) -> Vec<SearchMatchRangeDto> {
+    let folded_needle = fold_case(needle);
+    if folded_needle.is_empty() {
+        return Vec::new();
     }
 
-    let folded_needle = needle
-        .chars()
-        .flat_map(char::to_lowercase)
-        .collect::<String>();
-    if folded_needle.is_empty() {
-        return Some(0);
+    let (folded_haystack, byte_to_original) = fold_case_with_offsets(haystack);
+    let mut ranges = Vec::new();
+    let mut from = 0;
+    while let Some(found) = folded_haystack[from..].find(&folded_needle) {
+        let folded_start = from + found;
+        let folded_end = folded_start + folded_needle.len();
+        ranges.push(SearchMatchRangeDto {
+            start: byte_to_original[folded_start].0,
+            end: byte_to_original[folded_end - 1].1,
+        });
+        from = folded_end;
     }
+    ranges
+}
+
+fn fold_case(value: &str) -> String {
+    value.chars().flat_map(char::to_lowercase).collect()
+}
 
+/// Lowercases `haystack`, mapping each folded byte back to the byte range of
+/// the original character it came from.
+fn fold_case_with_offsets(haystack: &str) -> (String, Vec<(usize, usize)>) {
     let mut folded_haystack = String::with_capacity(haystack.len());
     let mut byte_to_original = Vec::new();
 
     for (idx, ch) in haystack.char_indices() {
+        let original = (idx, idx + ch.len_utf8());
         for lower in ch.to_lowercase() {
             let mut buffer = [0; 4];
             let encoded = lower.encode_utf8(&mut buffer);
             folded_haystack.push_str(encoded);
-            byte_to_original.extend(std::iter::repeat_n(idx, encoded.len()));
+            byte_to_original.extend(std::iter::repeat_n(original, encoded.len()));
         }
     }
 
-    folded_haystack
-        .find(&folded_needle)
-        .and_then(|folded_idx| byte_to_original.get(folded_idx).copied())
+    (folded_haystack, byte_to_original)
 }
 
 fn tokenize_search_query(query: &str) -> Vec<String> {
@@ -871,13 +1935,47 @@ fn insert_message(
     turn_model_id: Option<&str>,
     turn_reasoning_effort: Option<&str>,
 ) -> anyhow::Result<MessageDto> {
-    let id = Uuid::new_v4().to_string();
     let conn = db.connect()?;
+    insert_message_on(
+        &conn,
+        thread_id,
+        role,
+        content,
+        blocks,
+        status,
+        turn_engine_id,
+        turn_model_id,
+        turn_reasoning_effort,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_message_on(
+    conn: &Connection,
+    thread_id: &str,
+    role: &str,
+    content: Option<String>,
+    blocks: Option<Value>,
+    status: MessageStatusDto,
+    turn_engine_id: Option<&str>,
+    turn_model_id: Option<&str>,
+    turn_reasoning_effort: Option<&str>,
+) -> anyhow::Result<MessageDto> {
+    let id = Uuid::new_v4().to_string();
+    let blocks = blocks
+        .map(|mut blocks| {
+            blobs::externalize_large_blocks(conn, &mut blocks)?;
+            anyhow::Ok(blocks)
+        })
+        .transpose()?;
     conn.execute(
-        "INSERT INTO messages (
-            id, thread_id, role, content, blocks_json, schema_version, status, turn_engine_id, turn_model_id, turn_reasoning_effort
+        &format!(
+            "INSERT INTO messages (
+            id, thread_id, role, content, blocks_json, schema_version, status, turn_engine_id, turn_model_id, turn_reasoning_effort,
+            created_at, seq
         )
-     VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7, ?8, ?9)",
+     VALUES (?1, ?2, ?3, ?4, ?5, ?11, ?6, ?7, ?8, ?9, ?10, {NEXT_MESSAGE_SEQ_SQL})"
+        ),
         params![
             id,
             thread_id,
@@ -887,21 +1985,26 @@ fn insert_message(
             status.as_str(),
             turn_engine_id,
             turn_model_id,
-            turn_reasoning_effort
+            turn_reasoning_effort,
+            message_timestamp_now(),
+            BLOCK_SCHEMA_VERSION,
         ],
     )
     .context("failed to insert message")?;
 
-    let conn = db.connect()?;
-    conn.query_row(
-        "SELECT id, thread_id, role, content, blocks_json, schema_version, status,
-            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at
+    let mut message = conn
+        .query_row(
+            "SELECT id, thread_id, role, content, blocks_json, schema_version, status,
+            token_input, token_output, turn_engine_id, turn_model_id, turn_reasoning_effort, created_at, seq,
+            regenerated_from, superseded_at IS NOT NULL
      FROM messages
      WHERE id = ?1",
-        params![id],
-        map_message_row,
-    )
-    .context("failed to load inserted message")
+            params![id],
+            map_message_row,
+        )
+        .context("failed to load inserted message")?;
+    blobs::resolve_blob_refs(conn, message.blocks.as_mut())?;
+    Ok(message)
 }
 
 fn map_message_row(row: &Row<'_>) -> rusqlite::Result<MessageDto> {
@@ -928,6 +2031,9 @@ fn map_message_row(row: &Row<'_>) -> rusqlite::Result<MessageDto> {
             None
         },
         created_at: row.get(12)?,
+        seq: row.get::<_, Option<i64>>(13)?.unwrap_or_default(),
+        regenerated_from: row.get(14)?,
+        superseded: row.get(15)?,
     })
 }
 
@@ -1117,15 +2223,12 @@ fn load_resolved_approvals_for_message_ids(
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        fs,
-        sync::{Arc, Mutex},
-    };
+    use std::fs;
 
     use serde_json::json;
 
     use crate::{
-        db::{actions, threads, workspaces, ConnectionPool, SQLITE_POOL_MAX_IDLE},
+        db::{actions, threads, workspaces, PoolLimits},
         engines::events::ActionType,
     };
 
@@ -1133,13 +2236,7 @@ mod tests {
 
     fn test_db() -> Database {
         let path = std::env::temp_dir().join(format!("panes-messages-{}.db", Uuid::new_v4()));
-        let db = Database {
-            path,
-            pool: Arc::new(ConnectionPool {
-                idle: Mutex::new(Vec::new()),
-                max_idle: SQLITE_POOL_MAX_IDLE,
-            }),
-        };
+        let db = Database::unmigrated(path, PoolLimits::default());
         db.run_migrations().expect("failed to run test migrations");
         db
     }
@@ -1208,6 +2305,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn inserted_messages_use_millisecond_rfc3339_timestamps() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let message = insert_user_message(&db, &thread_id, "hi", None, None, None, None).unwrap();
+
+        let created_at = &message.created_at;
+        assert_eq!(created_at.len(), "2026-01-01 10:00:00.000Z".len());
+        assert_eq!(&created_at[10..11], " ");
+        assert_eq!(&created_at[19..20], ".");
+        assert!(created_at.ends_with('Z'));
+        assert!(DateTime::parse_from_rfc3339(&created_at.replacen(' ', "T", 1)).is_ok());
+        assert_eq!(
+            parse_message_timestamp(created_at).map(format_message_timestamp),
+            Some(created_at.clone())
+        );
+    }
+
+    #[test]
+    fn parse_message_timestamp_accepts_legacy_formats() {
+        let expected = "2026-01-01 10:00:00.000Z";
+        for raw in [
+            "2026-01-01 10:00:00",
+            "2026-01-01T10:00:00Z",
+            "2026-01-01T12:00:00+02:00",
+            "2026-01-01 10:00:00.000",
+        ] {
+            assert_eq!(
+                parse_message_timestamp(raw).map(format_message_timestamp),
+                Some(expected.to_string()),
+                "{raw}"
+            );
+        }
+        assert_eq!(parse_message_timestamp("yesterday"), None);
+    }
+
     #[test]
     fn build_search_messages_query_tolerates_unterminated_quotes() {
         assert_eq!(
@@ -1242,39 +2375,229 @@ mod tests {
         )
         .unwrap();
 
-        let results = search_messages(&db, &workspace_id, "foo:bar baz(");
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "foo:bar baz(",
+            SnippetContext::default(),
+            SearchPage::default(),
+        );
         assert!(results.is_ok());
     }
 
+    fn user_message_with_text(db: &Database, thread_id: &str, text: &str) -> MessageDto {
+        insert_user_message(db, thread_id, text, None, None, None, None).unwrap()
+    }
+
     #[test]
-    fn search_messages_finds_assistant_text_after_blocks_update() {
+    fn search_messages_matches_stemmed_words_and_pages_by_rank() {
         let db = test_db();
         let workspace_id = test_workspace(&db);
         let thread =
             threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
                 .unwrap();
-        let message = insert_assistant_placeholder(&db, &thread.id, None, None, None).unwrap();
+        let strong =
+            user_message_with_text(&db, &thread.id, "migrate the migrations after migrating");
+        let weak = user_message_with_text(
+            &db,
+            &thread.id,
+            "a long note about the release that mentions one migration in passing",
+        );
+        user_message_with_text(&db, &thread.id, "unrelated");
 
-        let blocks = json!([
-            { "type": "thinking", "content": "planning the reply" },
-            { "type": "text", "content": "the flux capacitor needs recalibration" }
-        ]);
-        update_assistant_blocks_json(
+        let all = search_messages(
             &db,
-            &message.id,
-            &blocks.to_string(),
-            MessageStatusDto::Completed,
-            None,
+            &workspace_id,
+            "migrate",
+            SnippetContext::default(),
+            SearchPage::default(),
         )
         .unwrap();
+        assert_eq!(
+            all.iter()
+                .map(|result| result.message_id.as_str())
+                .collect::<Vec<_>>(),
+            [strong.id.as_str(), weak.id.as_str()]
+        );
 
-        let results = search_messages(&db, &workspace_id, "capacitor").unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].message_id, message.id);
-        assert!(results[0].snippet.contains("flux capacitor"));
-    }
-
-    #[test]
+        let second = search_messages(
+            &db,
+            &workspace_id,
+            "migrate",
+            SnippetContext::default(),
+            SearchPage::new(Some(1), Some(1)),
+        )
+        .unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].message_id, weak.id);
+        assert_eq!(SearchPage::new(Some(0), None).limit, 1);
+    }
+
+    #[test]
+    fn search_messages_falls_back_to_substring_matching_without_fts() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let thread =
+            threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
+                .unwrap();
+        db.connect()
+            .unwrap()
+            .execute_batch(
+                "DROP TRIGGER messages_fts_insert;
+                 DROP TRIGGER messages_fts_delete;
+                 DROP TRIGGER messages_fts_update;
+                 DROP TABLE messages_fts;",
+            )
+            .unwrap();
+        let hit = user_message_with_text(&db, &thread.id, "Coverage went to 50% on the parser");
+        user_message_with_text(&db, &thread.id, "Coverage went to 50 on the lexer");
+
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "coverage 50%",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].message_id, hit.id);
+        assert_eq!(results[0].snippets[0].match_ranges.len(), 2);
+    }
+
+    #[test]
+    fn search_index_follows_message_edits_and_deletes_but_not_status_updates() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let thread =
+            threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
+                .unwrap();
+        let prompt = user_message_with_text(&db, &thread.id, "explain the borrow checker");
+        let search = |query: &str| {
+            search_messages(
+                &db,
+                &workspace_id,
+                query,
+                SnippetContext::default(),
+                SearchPage::default(),
+            )
+            .unwrap()
+            .len()
+        };
+
+        rewrite_user_message_on(
+            &db.connect().unwrap(),
+            &thread.id,
+            &prompt.id,
+            "explain lifetimes",
+        )
+        .unwrap();
+        assert_eq!(search("borrow"), 0);
+        assert_eq!(search("lifetimes"), 1);
+
+        // total_changes() counts rows written by triggers, so a status update
+        // that re-indexed the text would report more than the one row.
+        let conn = db.connect().unwrap();
+        let total_changes = || -> i64 {
+            conn.query_row("SELECT total_changes()", [], |row| row.get(0))
+                .unwrap()
+        };
+        let before = total_changes();
+        conn.execute(
+            "UPDATE messages SET status = 'interrupted' WHERE id = ?1",
+            params![prompt.id],
+        )
+        .unwrap();
+        assert_eq!(total_changes() - before, 1);
+
+        delete_message(&db, &thread.id, &prompt.id, false).unwrap();
+        assert_eq!(search("lifetimes"), 0);
+    }
+
+    #[test]
+    fn migrations_rebuild_an_unstemmed_fts_index_from_existing_rows() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let thread =
+            threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
+                .unwrap();
+        // The index as the previous schema created it: no stemming.
+        db.connect()
+            .unwrap()
+            .execute_batch(
+                "DROP TABLE messages_fts;
+                 CREATE VIRTUAL TABLE messages_fts USING fts5(
+                   thread_id UNINDEXED,
+                   role UNINDEXED,
+                   searchable_text,
+                   content=messages,
+                   content_rowid=rowid
+                 );",
+            )
+            .unwrap();
+        let old = user_message_with_text(&db, &thread.id, "the migrations are flaky");
+        let search = |query: &str| {
+            search_messages(
+                &db,
+                &workspace_id,
+                query,
+                SnippetContext::default(),
+                SearchPage::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(search("migrations").len(), 1);
+        assert!(search("migrate").is_empty());
+
+        db.run_migrations().expect("failed to rerun migrations");
+
+        let results = search("migrate");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].message_id, old.id);
+        // The triggers keep indexing into the rebuilt table.
+        let new = user_message_with_text(&db, &thread.id, "migrating again tomorrow");
+        assert_eq!(search("migrate").len(), 2);
+        delete_thread_messages(&db.connect().unwrap(), &thread.id, &[new.id]).unwrap();
+        assert_eq!(search("migrate").len(), 1);
+    }
+
+    #[test]
+    fn search_messages_finds_assistant_text_after_blocks_update() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let thread =
+            threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
+                .unwrap();
+        let message = insert_assistant_placeholder(&db, &thread.id, None, None, None).unwrap();
+
+        let blocks = json!([
+            { "type": "thinking", "content": "planning the reply" },
+            { "type": "text", "content": "the flux capacitor needs recalibration" }
+        ]);
+        update_assistant_blocks_json_on(
+            &db.connect().unwrap(),
+            &message.id,
+            &blocks.to_string(),
+            MessageStatusDto::Completed,
+            None,
+        )
+        .unwrap();
+
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "capacitor",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].message_id, message.id);
+        assert!(results[0].snippet.contains("flux capacitor"));
+    }
+
+    #[test]
     fn migrations_backfill_assistant_content_for_legacy_rows() {
         let db = test_db();
         let workspace_id = test_workspace(&db);
@@ -1296,13 +2619,26 @@ mod tests {
         .unwrap();
         drop(conn);
 
-        assert!(search_messages(&db, &workspace_id, "quasars")
-            .unwrap()
-            .is_empty());
+        assert!(search_messages(
+            &db,
+            &workspace_id,
+            "quasars",
+            SnippetContext::default(),
+            SearchPage::default()
+        )
+        .unwrap()
+        .is_empty());
 
         db.run_migrations().expect("failed to rerun migrations");
 
-        let results = search_messages(&db, &workspace_id, "quasars").unwrap();
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "quasars",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].message_id, message.id);
     }
@@ -1321,12 +2657,51 @@ mod tests {
         );
         insert_user_message(&db, &thread.id, &content, None, None, None, None).unwrap();
 
-        let results = search_messages(&db, &workspace_id, "workspace").unwrap();
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "workspace",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
         assert_eq!(results.len(), 1);
         assert!(results[0].snippet.contains("Workspace marker"));
         assert!(!results[0].snippet.starts_with("opening prelude"));
     }
 
+    #[test]
+    fn search_messages_snippet_tracks_stemmed_fts_match() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let thread =
+            threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
+                .unwrap();
+        let content = format!(
+            "{} Running marker {}",
+            "opening prelude ".repeat(20),
+            "closing context ".repeat(10)
+        );
+        insert_user_message(&db, &thread.id, &content, None, None, None, None).unwrap();
+
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "run",
+            SnippetContext::new(Some(10), Some(10)),
+            SearchPage::default(),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert!(result.snippet.contains("Running marker"));
+        assert!(!result.snippet.starts_with("opening prelude"));
+        assert_eq!(result.match_count, 1);
+        let snippet = &result.snippets[0];
+        let range = snippet.match_ranges[0];
+        assert_eq!(&snippet.text[range.start..range.end], "Running");
+    }
+
     #[test]
     fn search_messages_preserves_phrase_queries() {
         let db = test_db();
@@ -1355,7 +2730,14 @@ mod tests {
         )
         .unwrap();
 
-        let results = search_messages(&db, &workspace_id, "\"foo bar\"").unwrap();
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "\"foo bar\"",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
 
         assert_eq!(results.len(), 1);
         assert!(results[0].snippet.contains("foo bar"));
@@ -1380,7 +2762,14 @@ mod tests {
         .unwrap();
         insert_user_message(&db, &thread.id, "prefix bar suffix", None, None, None, None).unwrap();
 
-        let results = search_messages(&db, &workspace_id, "foo*").unwrap();
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "foo*",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
 
         assert_eq!(results.len(), 1);
         assert!(results[0].snippet.contains("foobar"));
@@ -1400,13 +2789,102 @@ mod tests {
         );
         insert_user_message(&db, &thread.id, &content, None, None, None, None).unwrap();
 
-        let results = search_messages(&db, &workspace_id, "café").unwrap();
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "café",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
 
         assert_eq!(results.len(), 1);
         assert!(results[0].snippet.contains("CAFÉ marker"));
         assert!(!results[0].snippet.starts_with("opening prelude"));
     }
 
+    #[test]
+    fn search_messages_reports_byte_offsets_in_multibyte_text() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let thread =
+            threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
+                .unwrap();
+        let message = insert_assistant_placeholder(&db, &thread.id, None, None, None).unwrap();
+        let blocks = json!([
+            { "type": "thinking", "content": "naïve planning notes" },
+            { "type": "text", "content": "résumé: 日本語のテキスト — Überprüfung fertig" }
+        ]);
+        update_assistant_blocks_json_on(
+            &db.connect().unwrap(),
+            &message.id,
+            &blocks.to_string(),
+            MessageStatusDto::Completed,
+            None,
+        )
+        .unwrap();
+
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "überprüfung",
+            SnippetContext::new(Some(4), Some(3)),
+            SearchPage::default(),
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.role, "assistant");
+        assert_eq!(result.block_type.as_deref(), Some("text"));
+        assert_eq!(result.block_index, Some(1));
+        assert_eq!(result.match_count, 1);
+
+        let snippet = &result.snippets[0];
+        assert_eq!(snippet.text, "ト — Überprüfung fe");
+        assert!(snippet.truncated_start);
+        assert!(snippet.truncated_end);
+        let range = snippet.match_ranges[0];
+        assert_eq!(&snippet.text[range.start..range.end], "Überprüfung");
+    }
+
+    #[test]
+    fn search_messages_collapses_multiple_matches_into_one_result() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let thread =
+            threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "test")
+                .unwrap();
+        let filler = "unrelated filler text ".repeat(10);
+        let content = format!(
+            "Marker one {filler} marker two, MARKER three {filler} marker four {filler} marker five"
+        );
+        insert_user_message(&db, &thread.id, &content, None, None, None, None).unwrap();
+
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "marker",
+            SnippetContext::new(Some(10), Some(20)),
+            SearchPage::default(),
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.match_count, 5);
+        assert_eq!(result.block_index, None);
+        assert_eq!(result.snippets.len(), 3);
+        // "marker two" and "MARKER three" share one context window.
+        assert_eq!(result.snippets[1].match_ranges.len(), 2);
+        for snippet in &result.snippets {
+            for range in &snippet.match_ranges {
+                assert!(snippet.text[range.start..range.end].eq_ignore_ascii_case("marker"));
+            }
+        }
+        assert!(!result.snippets[0].truncated_start);
+    }
+
     #[test]
     fn search_messages_excludes_archived_threads() {
         let db = test_db();
@@ -1446,12 +2924,59 @@ mod tests {
         .unwrap();
         threads::archive_thread(&db, &archived_thread.id).unwrap();
 
-        let results = search_messages(&db, &workspace_id, "shared").unwrap();
+        let results = search_messages(
+            &db,
+            &workspace_id,
+            "shared",
+            SnippetContext::default(),
+            SearchPage::default(),
+        )
+        .unwrap();
 
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].thread_id, active_thread.id);
     }
 
+    #[test]
+    fn messages_with_future_block_types_still_load_and_index_their_text() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let message = insert_message(
+            &db,
+            &thread_id,
+            "assistant",
+            None,
+            None,
+            MessageStatusDto::Streaming,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let blocks = json!([
+            {"type": "text", "content": "First part"},
+            {"type": "hologram", "frames": 3},
+            {"type": "text", "content": "Second part"}
+        ]);
+
+        update_assistant_blocks_json_on(
+            &db.connect().unwrap(),
+            &message.id,
+            &blocks.to_string(),
+            MessageStatusDto::Completed,
+            None,
+        )
+        .unwrap();
+
+        let loaded = get_thread_messages(&db, &thread_id).unwrap();
+        assert_eq!(loaded[0].blocks.as_ref(), Some(&blocks));
+        assert_eq!(
+            loaded[0].content.as_deref(),
+            Some("First part\n\nSecond part")
+        );
+        assert_eq!(loaded[0].schema_version, BLOCK_SCHEMA_VERSION);
+    }
+
     #[test]
     fn update_assistant_blocks_json_preserves_answered_approval_status() {
         let db = test_db();
@@ -1471,8 +2996,8 @@ mod tests {
         )
         .unwrap();
 
-        actions::insert_approval(
-            &db,
+        actions::insert_approval_on(
+            &db.connect().unwrap(),
             approval_id,
             &thread_id,
             &message.id,
@@ -1483,8 +3008,8 @@ mod tests {
         .unwrap();
         actions::answer_approval(&db, approval_id, "accept").unwrap();
 
-        update_assistant_blocks_json(
-            &db,
+        update_assistant_blocks_json_on(
+            &db.connect().unwrap(),
             &message.id,
             &pending_blocks.to_string(),
             MessageStatusDto::Completed,
@@ -1517,8 +3042,8 @@ mod tests {
         )
         .unwrap();
 
-        actions::insert_approval(
-            &db,
+        actions::insert_approval_on(
+            &db.connect().unwrap(),
             approval_id,
             &thread_id,
             &message.id,
@@ -1527,10 +3052,10 @@ mod tests {
             &json!({}),
         )
         .unwrap();
-        actions::resolve_approval(&db, approval_id).unwrap();
+        actions::resolve_approval_on(&db.connect().unwrap(), approval_id).unwrap();
 
-        update_assistant_blocks_json(
-            &db,
+        update_assistant_blocks_json_on(
+            &db.connect().unwrap(),
             &message.id,
             &pending_blocks.to_string(),
             MessageStatusDto::Completed,
@@ -1561,13 +3086,136 @@ mod tests {
         )
         .unwrap();
 
-        update_assistant_turn_model_id(&db, &message.id, "gpt-5.3-codex").unwrap();
+        update_assistant_turn_model_id_on(&db.connect().unwrap(), &message.id, "gpt-5.3-codex")
+            .unwrap();
 
         let reloaded = get_thread_messages(&db, &thread_id).unwrap();
         assert_eq!(reloaded.len(), 1);
         assert_eq!(reloaded[0].turn_model_id.as_deref(), Some("gpt-5.3-codex"));
     }
 
+    #[test]
+    fn model_history_collapses_consecutive_turns_into_ranges() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let turns = [
+            ("gpt-5.3-codex", Some("high")),
+            ("gpt-5.3-codex", Some("high")),
+            ("gpt-5.3-codex", Some("low")),
+            ("gpt-5.1-codex-mini", None),
+            ("gpt-5.3-codex", Some("high")),
+        ];
+        let mut ids = Vec::new();
+        for (model_id, effort) in turns {
+            insert_user_message(&db, &thread_id, "hi", None, None, None, None).unwrap();
+            let message = insert_assistant_placeholder(
+                &db,
+                &thread_id,
+                Some("codex"),
+                Some(model_id),
+                effort,
+            )
+            .unwrap();
+            ids.push(message.id);
+        }
+        insert_assistant_placeholder(&db, &thread_id, None, None, None).unwrap();
+
+        let history = get_thread_model_history(&db, &thread_id).unwrap();
+
+        assert_eq!(history.entries.len(), 5);
+        let ranges = history
+            .ranges
+            .iter()
+            .map(|range| {
+                (
+                    range.model_id.as_str(),
+                    range.reasoning_effort.as_deref(),
+                    range.first_message_id.as_str(),
+                    range.last_message_id.as_str(),
+                    range.turn_count,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranges,
+            vec![
+                (
+                    "gpt-5.3-codex",
+                    Some("high"),
+                    ids[0].as_str(),
+                    ids[1].as_str(),
+                    2
+                ),
+                (
+                    "gpt-5.3-codex",
+                    Some("low"),
+                    ids[2].as_str(),
+                    ids[2].as_str(),
+                    1
+                ),
+                (
+                    "gpt-5.1-codex-mini",
+                    None,
+                    ids[3].as_str(),
+                    ids[3].as_str(),
+                    1
+                ),
+                (
+                    "gpt-5.3-codex",
+                    Some("high"),
+                    ids[4].as_str(),
+                    ids[4].as_str(),
+                    1
+                ),
+            ]
+        );
+        assert_eq!(
+            latest_assistant_model_entry(&db, &thread_id)
+                .unwrap()
+                .map(|entry| entry.message_id),
+            Some(ids[4].clone())
+        );
+    }
+
+    #[test]
+    fn thread_message_pages_walk_back_from_a_message_id_in_seq_order() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let ids: Vec<String> = (0..5)
+            .map(|turn| user_message_with_text(&db, &thread_id, &format!("turn {turn}")).id)
+            .collect();
+        // Rows written in the same millisecond still page in insertion order.
+        db.connect()
+            .unwrap()
+            .execute(
+                "UPDATE messages SET created_at = '2026-03-13T00:00:00.000Z' WHERE thread_id = ?1",
+                params![thread_id],
+            )
+            .unwrap();
+        let page_ids = |page: &MessageWindowDto| {
+            page.messages
+                .iter()
+                .map(|message| message.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let newest = get_thread_messages_page(&db, &thread_id, None, 2).unwrap();
+        assert_eq!(page_ids(&newest), ids[3..5]);
+        assert!(newest.has_more);
+        assert_eq!(newest.total_count, 5);
+
+        let middle = get_thread_messages_page(&db, &thread_id, Some(&ids[3]), 2).unwrap();
+        assert_eq!(page_ids(&middle), ids[1..3]);
+        assert!(middle.has_more);
+
+        let oldest = get_thread_messages_page(&db, &thread_id, Some(&ids[1]), 2).unwrap();
+        assert_eq!(page_ids(&oldest), ids[..1]);
+        assert!(!oldest.has_more);
+        assert!(oldest.next_cursor.is_none());
+
+        assert!(get_thread_messages_page(&db, &thread_id, Some("missing"), 2).is_err());
+    }
+
     #[test]
     fn get_thread_messages_window_reconciles_stale_approval_blocks() {
         let db = test_db();
@@ -1587,8 +3235,8 @@ mod tests {
         )
         .unwrap();
 
-        actions::insert_approval(
-            &db,
+        actions::insert_approval_on(
+            &db.connect().unwrap(),
             approval_id,
             &thread_id,
             &message.id,
@@ -1614,6 +3262,49 @@ mod tests {
         assert_eq!(decision, Some("accept"));
     }
 
+    #[test]
+    fn resolving_pending_approvals_leaves_answered_ones_alone() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let message = insert_message(
+            &db,
+            &thread_id,
+            "assistant",
+            None,
+            Some(approval_blocks_json("approval-open")),
+            MessageStatusDto::Interrupted,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        for approval_id in ["approval-open", "approval-answered"] {
+            actions::insert_approval_on(
+                &db.connect().unwrap(),
+                approval_id,
+                &thread_id,
+                &message.id,
+                &ActionType::Command,
+                "Run tests",
+                &json!({}),
+            )
+            .unwrap();
+        }
+        actions::answer_approval(&db, "approval-answered", "decline").unwrap();
+
+        let resolved = actions::resolve_pending_approvals_for_message(&db, &message.id).unwrap();
+        assert_eq!(resolved, ["approval-open"]);
+        assert!(
+            actions::resolve_pending_approvals_for_message(&db, &message.id)
+                .unwrap()
+                .is_empty()
+        );
+
+        let window = get_thread_messages_window(&db, &thread_id, None, 20).unwrap();
+        let blocks = window.messages[0].blocks.as_ref().unwrap();
+        assert_eq!(approval_block_status(blocks), Some(("answered", None)));
+    }
+
     #[test]
     fn get_thread_messages_window_reconciles_resolved_approval_without_decision() {
         let db = test_db();
@@ -1633,8 +3324,8 @@ mod tests {
         )
         .unwrap();
 
-        actions::insert_approval(
-            &db,
+        actions::insert_approval_on(
+            &db.connect().unwrap(),
             approval_id,
             &thread_id,
             &message.id,
@@ -1643,7 +3334,7 @@ mod tests {
             &json!({}),
         )
         .unwrap();
-        actions::resolve_approval(&db, approval_id).unwrap();
+        actions::resolve_approval_on(&db.connect().unwrap(), approval_id).unwrap();
 
         let conn = db.connect().unwrap();
         conn.execute(
@@ -1660,6 +3351,64 @@ mod tests {
         assert_eq!(decision, None);
     }
 
+    #[test]
+    fn clone_thread_messages_stops_at_a_message_and_reissues_its_actions() {
+        let db = test_db();
+        let source_thread_id = test_thread(&db);
+        let target_thread_id = test_thread(&db);
+        insert_user_message(&db, &source_thread_id, "turn 1", None, None, None, None).unwrap();
+        let answer = insert_message(
+            &db,
+            &source_thread_id,
+            "assistant",
+            Some("ran it".to_string()),
+            Some(json!([{ "type": "action", "actionId": "action-1", "status": "done" }])),
+            MessageStatusDto::Completed,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        actions::insert_action_started_on(
+            &db.connect().unwrap(),
+            "action-1",
+            &source_thread_id,
+            &answer.id,
+            None,
+            &ActionType::Command,
+            "Run tests",
+            &json!({ "command": "cargo test" }),
+        )
+        .unwrap();
+        insert_user_message(&db, &source_thread_id, "turn 2", None, None, None, None).unwrap();
+
+        let cloned =
+            clone_thread_messages(&db, &source_thread_id, &target_thread_id, Some(&answer.id))
+                .unwrap();
+        assert_eq!(cloned, 2);
+        assert!(
+            clone_thread_messages(&db, &source_thread_id, &target_thread_id, Some("missing"))
+                .is_err()
+        );
+
+        let target_messages = get_thread_messages(&db, &target_thread_id).unwrap();
+        assert_eq!(target_messages.len(), 2);
+        let conn = db.connect().unwrap();
+        let (action_id, message_id): (String, String) = conn
+            .query_row(
+                "SELECT id, message_id FROM actions WHERE thread_id = ?1",
+                params![target_thread_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_ne!(action_id, "action-1");
+        assert_eq!(message_id, target_messages[1].id);
+        assert_eq!(
+            target_messages[1].blocks.as_ref().unwrap()[0]["actionId"],
+            json!(action_id)
+        );
+    }
+
     #[test]
     fn clone_thread_messages_copies_message_history_and_metadata() {
         let db = test_db();
@@ -1696,7 +3445,8 @@ mod tests {
         .unwrap();
         drop(conn);
 
-        let cloned = clone_thread_messages(&db, &source_thread_id, &target_thread_id).unwrap();
+        let cloned =
+            clone_thread_messages(&db, &source_thread_id, &target_thread_id, None).unwrap();
         assert_eq!(cloned, 2);
 
         let source_messages = get_thread_messages(&db, &source_thread_id).unwrap();
@@ -1738,6 +3488,59 @@ mod tests {
         let _ = user_message;
     }
 
+    #[test]
+    fn delete_message_removes_its_approvals_and_guards_a_live_reply() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        insert_user_message(&db, &thread_id, "turn 1", None, None, None, None).unwrap();
+        let answer = insert_answer(&db, &thread_id, "answer 1");
+        let live = insert_message(
+            &db,
+            &thread_id,
+            "assistant",
+            None,
+            Some(approval_blocks_json("approval-live")),
+            MessageStatusDto::Streaming,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        actions::insert_approval_on(
+            &db.connect().unwrap(),
+            "approval-live",
+            &thread_id,
+            &live.id,
+            &ActionType::Command,
+            "Run cleanup",
+            &json!({}),
+        )
+        .unwrap();
+
+        assert!(delete_message(&db, &thread_id, &live.id, true).is_err());
+        assert!(delete_message(&db, "other-thread", &answer.id, false).is_err());
+
+        delete_message(&db, &thread_id, &answer.id, true).unwrap();
+        delete_message(&db, &thread_id, &live.id, false).unwrap();
+        threads::refresh_thread_message_stats(&db, &thread_id).unwrap();
+
+        let remaining = get_thread_messages(&db, &thread_id).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].content.as_deref(), Some("turn 1"));
+        let thread = threads::get_thread(&db, &thread_id).unwrap().unwrap();
+        assert_eq!(thread.message_count, 1);
+        let approval_count: i64 = db
+            .connect()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM approvals WHERE thread_id = ?1",
+                params![thread_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(approval_count, 0);
+    }
+
     #[test]
     fn drop_last_turns_removes_latest_turn_and_pending_approvals() {
         let db = test_db();
@@ -1768,8 +3571,8 @@ mod tests {
             None,
         )
         .unwrap();
-        actions::insert_approval(
-            &db,
+        actions::insert_approval_on(
+            &db.connect().unwrap(),
             "approval-rollback",
             &thread_id,
             &pending_assistant.id,
@@ -1847,4 +3650,270 @@ mod tests {
         assert_eq!(remaining_messages[0].content.as_deref(), Some("turn 1"));
         assert_eq!(remaining_messages[1].content.as_deref(), Some("answer 1"));
     }
+
+    #[test]
+    fn rewrite_user_message_keeps_attachments_and_drops_later_messages() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let first = insert_user_message(&db, &thread_id, "turn 1", None, None, None, None).unwrap();
+        insert_answer(&db, &thread_id, "answer 1");
+        let edited = insert_user_message(
+            &db,
+            &thread_id,
+            "turn 2",
+            Some(json!([
+                { "type": "text", "content": "turn 2" },
+                {
+                    "type": "attachment",
+                    "fileName": "notes.md",
+                    "filePath": "/tmp/notes.md",
+                    "sizeBytes": 12
+                }
+            ])),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        insert_answer(&db, &thread_id, "answer 2");
+        insert_user_message(&db, &thread_id, "turn 3", None, None, None, None).unwrap();
+
+        let conn = db.connect().unwrap();
+        let removed =
+            rewrite_user_message_on(&conn, &thread_id, &edited.id, "turn 2, edited").unwrap();
+        assert_eq!(removed, 2);
+
+        let messages = get_thread_messages(&db, &thread_id).unwrap();
+        assert_eq!(messages.len(), 3);
+        let rewritten = &messages[2];
+        assert_eq!(rewritten.id, edited.id);
+        assert_eq!(rewritten.content.as_deref(), Some("turn 2, edited"));
+        let blocks = rewritten.blocks.as_ref().and_then(Value::as_array).unwrap();
+        assert_eq!(blocks[0]["content"], "turn 2, edited");
+        assert_eq!(blocks[1]["fileName"], "notes.md");
+
+        let assistant_id = messages[1].id.clone();
+        assert!(rewrite_user_message_on(&conn, &thread_id, &assistant_id, "nope").is_err());
+        assert!(rewrite_user_message_on(&conn, &thread_id, &first.id, "turn 1").is_ok());
+        assert_eq!(get_thread_messages(&db, &thread_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rewrite_user_message_keeps_large_blocks_in_blob_storage() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let diff = "+line\n".repeat(blobs::BLOB_THRESHOLD_BYTES);
+        let edited = insert_user_message(
+            &db,
+            &thread_id,
+            "review this",
+            Some(json!([
+                { "type": "text", "content": "review this" },
+                { "type": "diff", "diff": diff, "scope": "turn" }
+            ])),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let conn = db.connect().unwrap();
+        rewrite_user_message_on(&conn, &thread_id, &edited.id, "review this, edited").unwrap();
+
+        let stored: String = conn
+            .query_row(
+                "SELECT blocks_json FROM messages WHERE id = ?1",
+                params![edited.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let stored: Value = serde_json::from_str(&stored).unwrap();
+        assert!(stored[1].get("diff").is_none());
+        assert!(stored[1]["ref"].as_str().unwrap().starts_with("sha256:"));
+
+        let messages = get_thread_messages(&db, &thread_id).unwrap();
+        let blocks = messages[0].blocks.as_ref().unwrap();
+        assert_eq!(blocks[0]["content"], "review this, edited");
+        assert_eq!(blocks[1]["diff"], diff);
+    }
+
+    fn insert_answer(db: &Database, thread_id: &str, content: &str) -> MessageDto {
+        insert_message(
+            db,
+            thread_id,
+            "assistant",
+            Some(content.to_string()),
+            Some(json!([{ "type": "text", "content": content }])),
+            MessageStatusDto::Completed,
+            Some("codex"),
+            Some("gpt-5.4"),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn retry_target_replaces_failed_replies_and_refuses_completed_turns() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        assert!(find_retry_target(&db, &thread_id).is_err());
+
+        let first = insert_user_message(&db, &thread_id, "turn 1", None, None, None, None).unwrap();
+        let target = find_retry_target(&db, &thread_id).unwrap();
+        assert_eq!(target.user.id, first.id);
+        assert!(target.discarded_message_ids.is_empty());
+
+        let answer = insert_answer(&db, &thread_id, "answer 1");
+        assert!(find_retry_target(&db, &thread_id).is_err());
+
+        let second =
+            insert_user_message(&db, &thread_id, "turn 2", None, None, None, None).unwrap();
+        let failed = insert_message(
+            &db,
+            &thread_id,
+            "assistant",
+            None,
+            Some(json!([])),
+            MessageStatusDto::Error,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let steer = insert_user_message(
+            &db,
+            &thread_id,
+            "also check the docs",
+            Some(steer_blocks_json("also check the docs")),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let target = find_retry_target(&db, &thread_id).unwrap();
+        assert_eq!(target.user.id, second.id);
+        assert_eq!(target.discarded_message_ids, [failed.id.clone(), steer.id]);
+        assert_eq!(target.superseded_message_id, None);
+
+        delete_message(&db, &thread_id, &failed.id, false).unwrap();
+        let regenerated =
+            insert_regenerated_placeholder(&db, &thread_id, &answer.id, None, None, None).unwrap();
+        db.connect()
+            .unwrap()
+            .execute(
+                "UPDATE messages SET status = 'interrupted' WHERE id = ?1",
+                params![regenerated.id],
+            )
+            .unwrap();
+        let target = find_retry_target(&db, &thread_id).unwrap();
+        assert_eq!(target.user.id, first.id);
+        assert_eq!(target.discarded_message_ids, [regenerated.id]);
+        assert_eq!(target.superseded_message_id, Some(answer.id));
+    }
+
+    #[test]
+    fn regeneration_pairs_with_the_prompt_across_interleaved_rows() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        insert_user_message(&db, &thread_id, "turn 1", None, None, None, None).unwrap();
+        insert_answer(&db, &thread_id, "answer 1");
+        let prompt =
+            insert_user_message(&db, &thread_id, "turn 2", None, None, None, None).unwrap();
+        insert_message(
+            &db,
+            &thread_id,
+            "system",
+            Some("context compacted".to_string()),
+            Some(json!([{ "type": "notice", "kind": "info" }])),
+            MessageStatusDto::Completed,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        insert_user_message(
+            &db,
+            &thread_id,
+            "focus on tests",
+            Some(steer_blocks_json("focus on tests")),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let answer = insert_answer(&db, &thread_id, "answer 2");
+        insert_user_message(&db, &thread_id, "turn 3", None, None, None, None).unwrap();
+        insert_answer(&db, &thread_id, "answer 3");
+
+        let pair = find_regeneration_pair(&db, &answer.id).unwrap();
+        assert_eq!(pair.user.id, prompt.id);
+        assert_eq!(pair.assistant.id, answer.id);
+
+        // The regeneration lands after turn 3 but still answers turn 2.
+        let regenerated = insert_regenerated_placeholder(
+            &db,
+            &thread_id,
+            &answer.id,
+            Some("codex"),
+            Some("gpt-5.4"),
+            None,
+        )
+        .unwrap();
+        complete_assistant_message(
+            &db,
+            &regenerated.id,
+            MessageStatusDto::Completed,
+            None,
+            None,
+        )
+        .unwrap();
+        let pair = find_regeneration_pair(&db, &regenerated.id).unwrap();
+        assert_eq!(pair.user.id, prompt.id);
+
+        let error = find_regeneration_pair(&db, &prompt.id).unwrap_err();
+        assert!(error.to_string().contains("only assistant messages"));
+    }
+
+    #[test]
+    fn superseded_messages_are_flagged_in_listings_and_cannot_be_regenerated_again() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        insert_user_message(&db, &thread_id, "prompt", None, None, None, None).unwrap();
+        let answer = insert_answer(&db, &thread_id, "bad answer");
+
+        let regenerated = insert_regenerated_placeholder(
+            &db,
+            &thread_id,
+            &answer.id,
+            Some("codex"),
+            Some("gpt-5.4"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            regenerated.regenerated_from.as_deref(),
+            Some(answer.id.as_str())
+        );
+
+        let messages = get_thread_messages(&db, &thread_id).unwrap();
+        let flags = messages
+            .iter()
+            .map(|message| (message.id.as_str(), message.superseded))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flags[1..],
+            [(answer.id.as_str(), true), (regenerated.id.as_str(), false)]
+        );
+        let window = get_thread_messages_window(&db, &thread_id, None, 10).unwrap();
+        assert!(window.messages[1].superseded);
+        assert_eq!(
+            window.messages[2].regenerated_from.as_deref(),
+            Some(answer.id.as_str())
+        );
+
+        let error = find_regeneration_pair(&db, &answer.id).unwrap_err();
+        assert!(error.to_string().contains("already been regenerated"));
+        let error = find_regeneration_pair(&db, &regenerated.id).unwrap_err();
+        assert!(error.to_string().contains("completed or failed"));
+    }
 }
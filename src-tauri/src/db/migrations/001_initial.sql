@@ -19,6 +19,8 @@ CREATE TABLE IF NOT EXISTS repos (
   is_active INTEGER NOT NULL DEFAULT 1,
   is_discovered INTEGER NOT NULL DEFAULT 1,
   trust_level TEXT NOT NULL DEFAULT 'standard',
+  protected_branches_json TEXT,
+  adopted_agent_configs_json TEXT,
   UNIQUE(workspace_id, path)
 );
 
@@ -31,6 +33,7 @@ CREATE TABLE IF NOT EXISTS threads (
   engine_thread_id TEXT,
   engine_metadata_json TEXT,
   engine_capabilities_json TEXT,
+  usage_limits_json TEXT,
   title TEXT,
   status TEXT NOT NULL DEFAULT 'idle',
   archived_at TEXT,
@@ -51,9 +54,12 @@ CREATE TABLE IF NOT EXISTS messages (
   turn_reasoning_effort TEXT,
   schema_version INTEGER NOT NULL DEFAULT 1,
   stream_seq INTEGER NOT NULL DEFAULT 0,
+  seq INTEGER,
   status TEXT NOT NULL DEFAULT 'completed',
   token_input INTEGER DEFAULT 0,
   token_output INTEGER DEFAULT 0,
+  regenerated_from TEXT,
+  superseded_at TEXT,
   created_at TEXT NOT NULL DEFAULT (datetime('now'))
 );
 
@@ -100,32 +106,120 @@ CREATE INDEX IF NOT EXISTS idx_threads_activity ON threads(workspace_id, last_ac
 CREATE INDEX IF NOT EXISTS idx_threads_workspace_status_activity ON threads(workspace_id, status, last_activity_at DESC);
 CREATE INDEX IF NOT EXISTS idx_messages_thread ON messages(thread_id, created_at ASC);
 CREATE INDEX IF NOT EXISTS idx_messages_thread_status_created ON messages(thread_id, status, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_messages_created ON messages(created_at);
 CREATE INDEX IF NOT EXISTS idx_actions_thread ON actions(thread_id, created_at ASC);
 CREATE INDEX IF NOT EXISTS idx_actions_thread_status_created ON actions(thread_id, status, created_at DESC);
 CREATE INDEX IF NOT EXISTS idx_approvals_thread ON approvals(thread_id, created_at ASC);
 CREATE INDEX IF NOT EXISTS idx_approvals_message_status ON approvals(message_id, status, created_at ASC);
 
-CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
-  thread_id UNINDEXED,
-  role UNINDEXED,
-  searchable_text,
-  content=messages,
-  content_rowid=rowid
+-- Large block payloads (diffs) stored once and referenced from blocks_json as
+-- {"ref": "sha256:..."}. Refcounts follow the messages that reference them.
+CREATE TABLE IF NOT EXISTS content_blobs (
+  hash TEXT PRIMARY KEY,
+  content BLOB NOT NULL,
+  size_bytes INTEGER NOT NULL,
+  stored_bytes INTEGER NOT NULL,
+  refcount INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  last_written_at TEXT NOT NULL DEFAULT (datetime('now'))
 );
 
-CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
-  INSERT INTO messages_fts(rowid, thread_id, role, searchable_text)
-  VALUES (new.rowid, new.thread_id, new.role, COALESCE(new.content, ''));
+CREATE INDEX IF NOT EXISTS idx_content_blobs_refcount ON content_blobs(refcount, last_written_at);
+
+CREATE TRIGGER IF NOT EXISTS messages_blob_refs_insert AFTER INSERT ON messages
+WHEN new.blocks_json LIKE '%"ref":"sha256:%' AND json_valid(new.blocks_json) BEGIN
+  UPDATE content_blobs
+  SET refcount = refcount + (
+    SELECT COUNT(*) FROM json_each(new.blocks_json) AS block
+    WHERE json_extract(block.value, '$.ref') = content_blobs.hash
+  )
+  WHERE hash IN (
+    SELECT json_extract(block.value, '$.ref') FROM json_each(new.blocks_json) AS block
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS messages_blob_refs_delete AFTER DELETE ON messages
+WHEN old.blocks_json LIKE '%"ref":"sha256:%' AND json_valid(old.blocks_json) BEGIN
+  UPDATE content_blobs
+  SET refcount = refcount - (
+    SELECT COUNT(*) FROM json_each(old.blocks_json) AS block
+    WHERE json_extract(block.value, '$.ref') = content_blobs.hash
+  )
+  WHERE hash IN (
+    SELECT json_extract(block.value, '$.ref') FROM json_each(old.blocks_json) AS block
+  );
 END;
 
-CREATE TRIGGER IF NOT EXISTS messages_fts_delete BEFORE DELETE ON messages BEGIN
-  INSERT INTO messages_fts(messages_fts, rowid, thread_id, role, searchable_text)
-  VALUES ('delete', old.rowid, old.thread_id, old.role, COALESCE(old.content, ''));
+CREATE TRIGGER IF NOT EXISTS messages_blob_refs_update_old AFTER UPDATE OF blocks_json ON messages
+WHEN old.blocks_json LIKE '%"ref":"sha256:%' AND json_valid(old.blocks_json) BEGIN
+  UPDATE content_blobs
+  SET refcount = refcount - (
+    SELECT COUNT(*) FROM json_each(old.blocks_json) AS block
+    WHERE json_extract(block.value, '$.ref') = content_blobs.hash
+  )
+  WHERE hash IN (
+    SELECT json_extract(block.value, '$.ref') FROM json_each(old.blocks_json) AS block
+  );
 END;
 
-CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE ON messages BEGIN
-  INSERT INTO messages_fts(messages_fts, rowid, thread_id, role, searchable_text)
-  VALUES ('delete', old.rowid, old.thread_id, old.role, COALESCE(old.content, ''));
-  INSERT INTO messages_fts(rowid, thread_id, role, searchable_text)
-  VALUES (new.rowid, new.thread_id, new.role, COALESCE(new.content, ''));
+CREATE TRIGGER IF NOT EXISTS messages_blob_refs_update_new AFTER UPDATE OF blocks_json ON messages
+WHEN new.blocks_json LIKE '%"ref":"sha256:%' AND json_valid(new.blocks_json) BEGIN
+  UPDATE content_blobs
+  SET refcount = refcount + (
+    SELECT COUNT(*) FROM json_each(new.blocks_json) AS block
+    WHERE json_extract(block.value, '$.ref') = content_blobs.hash
+  )
+  WHERE hash IN (
+    SELECT json_extract(block.value, '$.ref') FROM json_each(new.blocks_json) AS block
+  );
 END;
+
+CREATE TABLE IF NOT EXISTS pending_messages (
+  id TEXT PRIMARY KEY,
+  thread_id TEXT NOT NULL REFERENCES threads(id) ON DELETE CASCADE,
+  message TEXT NOT NULL,
+  model_id TEXT,
+  reasoning_effort TEXT,
+  attachments_json TEXT,
+  input_items_json TEXT,
+  plan_mode INTEGER NOT NULL DEFAULT 0,
+  run_after_error INTEGER NOT NULL DEFAULT 0,
+  held INTEGER NOT NULL DEFAULT 0,
+  position INTEGER NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_messages_thread ON pending_messages(thread_id, position);
+
+CREATE TABLE IF NOT EXISTS engine_usage_limits (
+  engine_id TEXT PRIMARY KEY,
+  usage_limits_json TEXT NOT NULL,
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS git_operation_logs (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  repo_path TEXT NOT NULL,
+  thread_id TEXT,
+  operation TEXT NOT NULL,
+  target TEXT NOT NULL,
+  force INTEGER NOT NULL DEFAULT 0,
+  force_with_lease INTEGER NOT NULL DEFAULT 0,
+  outcome TEXT NOT NULL,
+  detail TEXT,
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_git_operation_logs_repo ON git_operation_logs(repo_path, id DESC);
+
+CREATE TABLE IF NOT EXISTS local_metrics (
+  day TEXT NOT NULL,
+  key TEXT NOT NULL,
+  value INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (day, key)
+);
+
+CREATE TABLE IF NOT EXISTS local_metrics_state (
+  key TEXT PRIMARY KEY,
+  value TEXT NOT NULL
+);
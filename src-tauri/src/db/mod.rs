@@ -1,39 +1,238 @@
 use std::{
     collections::HashMap,
     fs,
+    future::Future,
     ops::{Deref, DerefMut},
     path::PathBuf,
-    sync::{Arc, Mutex},
-    time::Duration,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context as TaskContext, Poll, Wake, Waker},
+    thread::Thread,
+    time::{Duration, Instant},
 };
 
 use anyhow::Context;
-use rusqlite::{params, Connection, Transaction};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
-use crate::{path_utils, runtime_env};
+use crate::{models::DatabasePoolMetricsDto, path_utils, runtime_env};
 
 pub mod actions;
+pub mod blobs;
+pub mod git_operations;
+pub mod local_metrics;
 pub mod messages;
+pub mod pending_messages;
 pub mod repos;
 pub mod threads;
+pub mod turn_writes;
+pub mod usage;
+pub mod usage_limits;
+pub mod views;
 pub mod workspaces;
 
 const SQLITE_POOL_MAX_IDLE: usize = 8;
+pub const DEFAULT_SQLITE_POOL_MAX_CONNECTIONS: usize = 16;
+pub const DEFAULT_SQLITE_POOL_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolLimits {
+    pub max_connections: usize,
+    pub acquire_timeout: Duration,
+}
+
+impl Default for PoolLimits {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_SQLITE_POOL_MAX_CONNECTIONS,
+            acquire_timeout: DEFAULT_SQLITE_POOL_ACQUIRE_TIMEOUT,
+        }
+    }
+}
+
+/// Returned when no connection permit frees up within the pool's acquire
+/// timeout.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "database saturated: all {max_connections} sqlite connections stayed busy for {waited_ms}ms"
+)]
+pub struct DbSaturatedError {
+    pub max_connections: usize,
+    pub waited_ms: u64,
+}
 
 #[derive(Clone)]
 pub struct Database {
     path: PathBuf,
     pool: Arc<ConnectionPool>,
+    /// Permit taken by `acquire_scope` before a blocking task starts. It is
+    /// lent to one connection at a time, so back-to-back `connect()` calls
+    /// inside one operation never wait; a connection opened while another is
+    /// still checked out takes a permit of its own.
+    scope: Option<Arc<ScopePermit>>,
 }
 
 struct ConnectionPool {
     idle: Mutex<Vec<Connection>>,
     max_idle: usize,
+    limits: PoolLimits,
+    permits: Arc<Semaphore>,
+    open: AtomicUsize,
+    waiters: AtomicUsize,
+    peak_in_use: AtomicUsize,
+}
+
+impl ConnectionPool {
+    fn new(limits: PoolLimits) -> Self {
+        let max_connections = limits.max_connections.max(1);
+        Self {
+            idle: Mutex::new(Vec::new()),
+            max_idle: SQLITE_POOL_MAX_IDLE.min(max_connections),
+            limits: PoolLimits {
+                max_connections,
+                ..limits
+            },
+            permits: Arc::new(Semaphore::new(max_connections)),
+            open: AtomicUsize::new(0),
+            waiters: AtomicUsize::new(0),
+            peak_in_use: AtomicUsize::new(0),
+        }
+    }
+
+    fn in_use(&self) -> usize {
+        self.limits
+            .max_connections
+            .saturating_sub(self.permits.available_permits())
+    }
+
+    fn record_acquired(&self) {
+        self.peak_in_use.fetch_max(self.in_use(), Ordering::Relaxed);
+    }
+
+    fn saturated_error(&self, waited: Duration) -> anyhow::Error {
+        DbSaturatedError {
+            max_connections: self.limits.max_connections,
+            waited_ms: waited.as_millis() as u64,
+        }
+        .into()
+    }
+
+    /// Blocking acquisition for callers already off the async runtime
+    /// (spawn_blocking bodies, startup, tests). Waits in the semaphore's own
+    /// queue, so blocking and async callers are served in arrival order.
+    fn acquire_blocking(&self) -> anyhow::Result<OwnedSemaphorePermit> {
+        if let Ok(permit) = self.permits.clone().try_acquire_owned() {
+            self.record_acquired();
+            return Ok(permit);
+        }
+
+        let started = Instant::now();
+        self.waiters.fetch_add(1, Ordering::Relaxed);
+        let result = park_until(
+            self.permits.clone().acquire_owned(),
+            self.limits.acquire_timeout,
+        );
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+        match result {
+            Some(Ok(permit)) => {
+                self.record_acquired();
+                Ok(permit)
+            }
+            Some(Err(_)) => anyhow::bail!("sqlite connection pool closed"),
+            None => Err(self.saturated_error(started.elapsed())),
+        }
+    }
+
+    async fn acquire_async(&self) -> anyhow::Result<OwnedSemaphorePermit> {
+        if let Ok(permit) = self.permits.clone().try_acquire_owned() {
+            self.record_acquired();
+            return Ok(permit);
+        }
+
+        let started = Instant::now();
+        self.waiters.fetch_add(1, Ordering::Relaxed);
+        let result = tokio::time::timeout(
+            self.limits.acquire_timeout,
+            self.permits.clone().acquire_owned(),
+        )
+        .await;
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+        match result {
+            Ok(Ok(permit)) => {
+                self.record_acquired();
+                Ok(permit)
+            }
+            Ok(Err(_)) => anyhow::bail!("sqlite connection pool closed"),
+            Err(_) => Err(self.saturated_error(started.elapsed())),
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Polls `future` on the current thread, parking between wakeups. `None`
+/// once `timeout` passes without it completing.
+fn park_until<F: Future>(future: F, timeout: Duration) -> Option<F::Output> {
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = TaskContext::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Some(output);
+        }
+        match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return None;
+                }
+                std::thread::park_timeout(remaining);
+            }
+            None => std::thread::park(),
+        }
+    }
+}
+
+struct ScopePermit {
+    /// `None` while lent to a connection.
+    permit: Mutex<Option<OwnedSemaphorePermit>>,
+}
+
+impl ScopePermit {
+    fn lend(&self) -> Option<OwnedSemaphorePermit> {
+        match self.permit.lock() {
+            Ok(mut guard) => guard.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        }
+    }
+
+    fn give_back(&self, permit: OwnedSemaphorePermit) {
+        match self.permit.lock() {
+            Ok(mut guard) => *guard = Some(permit),
+            Err(poisoned) => *poisoned.into_inner() = Some(permit),
+        }
+    }
 }
 
 pub struct PooledConnection {
     conn: Option<Connection>,
     pool: Arc<ConnectionPool>,
+    permit: Option<OwnedSemaphorePermit>,
+    /// Where the permit goes back to when it was lent by a scope.
+    scope: Option<Arc<ScopePermit>>,
 }
 
 impl Deref for PooledConnection {
@@ -56,61 +255,118 @@ impl DerefMut for PooledConnection {
 
 impl Drop for PooledConnection {
     fn drop(&mut self) {
-        let Some(conn) = self.conn.take() else {
-            return;
-        };
-
-        let mut idle = match self.pool.idle.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
+        if let Some(conn) = self.conn.take() {
+            let mut idle = match self.pool.idle.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+
+            if idle.len() < self.pool.max_idle {
+                idle.push(conn);
+            } else {
+                drop(idle);
+                drop(conn);
+                self.pool.open.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
 
-        if idle.len() < self.pool.max_idle {
-            idle.push(conn);
+        if let (Some(permit), Some(scope)) = (self.permit.take(), self.scope.as_ref()) {
+            scope.give_back(permit);
         }
     }
 }
 
 impl Database {
-    pub fn init() -> anyhow::Result<Self> {
+    pub fn init(limits: PoolLimits) -> anyhow::Result<Self> {
         runtime_env::migrate_legacy_app_data_dir()
             .context("failed to migrate legacy app data dir")?;
         let base_dir = runtime_env::app_data_dir();
         fs::create_dir_all(base_dir.join("logs")).context("failed to create app data dir")?;
 
         let path = base_dir.join("workspaces.db");
-        Self::open(path)
+        Self::open(path, limits)
     }
 
-    pub fn open(path: PathBuf) -> anyhow::Result<Self> {
-        let db = Self {
-            path,
-            pool: Arc::new(ConnectionPool {
-                idle: Mutex::new(Vec::new()),
-                max_idle: SQLITE_POOL_MAX_IDLE,
-            }),
-        };
+    pub fn open(path: PathBuf, limits: PoolLimits) -> anyhow::Result<Self> {
+        let db = Self::unmigrated(path, limits);
         db.run_migrations()?;
 
         Ok(db)
     }
 
+    fn unmigrated(path: PathBuf, limits: PoolLimits) -> Self {
+        Self {
+            path,
+            pool: Arc::new(ConnectionPool::new(limits)),
+            scope: None,
+        }
+    }
+
+    /// Waits (asynchronously) for a connection permit and returns a handle
+    /// whose connections all draw on it. Call before `spawn_blocking` so a
+    /// saturated pool parks a future instead of a blocking-pool thread.
+    pub async fn acquire_scope(&self) -> anyhow::Result<Self> {
+        if self.scope.is_some() {
+            return Ok(self.clone());
+        }
+
+        let permit = self.pool.acquire_async().await?;
+        Ok(Self {
+            path: self.path.clone(),
+            pool: self.pool.clone(),
+            scope: Some(Arc::new(ScopePermit {
+                permit: Mutex::new(Some(permit)),
+            })),
+        })
+    }
+
     pub fn connect(&self) -> anyhow::Result<PooledConnection> {
+        let lent = self
+            .scope
+            .as_ref()
+            .and_then(|scope| Some((scope.lend()?, scope.clone())));
+        let (permit, scope) = match lent {
+            Some((permit, scope)) => (permit, Some(scope)),
+            None => (self.pool.acquire_blocking()?, None),
+        };
+        let permit = Some(permit);
+
         if let Some(conn) = self.take_idle_connection() {
             return Ok(PooledConnection {
                 conn: Some(conn),
                 pool: self.pool.clone(),
+                permit,
+                scope,
             });
         }
 
         let conn = Connection::open(&self.path).context("failed to open sqlite database")?;
         configure_connection(&conn)?;
+        self.pool.open.fetch_add(1, Ordering::Relaxed);
         Ok(PooledConnection {
             conn: Some(conn),
             pool: self.pool.clone(),
+            permit,
+            scope,
         })
     }
 
+    pub fn pool_metrics(&self) -> DatabasePoolMetricsDto {
+        let idle = match self.pool.idle.lock() {
+            Ok(guard) => guard.len(),
+            Err(poisoned) => poisoned.into_inner().len(),
+        };
+        DatabasePoolMetricsDto {
+            max_connections: self.pool.limits.max_connections,
+            acquire_timeout_ms: self.pool.limits.acquire_timeout.as_millis() as u64,
+            open: self.pool.open.load(Ordering::Relaxed),
+            idle,
+            in_use: self.pool.in_use(),
+            waiters: self.pool.waiters.load(Ordering::Relaxed),
+            peak_in_use: self.pool.peak_in_use.load(Ordering::Relaxed),
+        }
+    }
+
     fn take_idle_connection(&self) -> Option<Connection> {
         let mut idle = match self.pool.idle.lock() {
             Ok(guard) => guard,
@@ -128,13 +384,175 @@ impl Database {
         ensure_repo_columns(&conn)?;
         ensure_workspace_startup_columns(&conn)?;
         ensure_runtime_columns(&conn)?;
+        ensure_git_operation_log_columns(&conn)?;
         ensure_messages_audit_columns(&conn)?;
+        ensure_message_seq(&mut conn)?;
+        ensure_messages_fts(&mut conn)?;
         backfill_assistant_message_content(&conn)?;
         repair_normalized_workspace_and_repo_paths(&mut conn)?;
         Ok(())
     }
 }
 
+const MESSAGE_SEQ_BACKFILL_BATCH: usize = 1_000;
+
+/// `messages.seq` is the per-thread insertion order and the authoritative sort
+/// key; `created_at` alone is ambiguous for rows written within the same
+/// second. Rows from before the column existed are numbered by
+/// `created_at, rowid`, continuing after any seq already assigned in the
+/// thread. Runs in bounded batches and only touches rows still missing a seq,
+/// so an interrupted backfill resumes where it stopped.
+fn ensure_message_seq(conn: &mut Connection) -> anyhow::Result<()> {
+    ensure_column(conn, "messages", "seq", "INTEGER")?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_messages_thread_seq ON messages(thread_id, seq)",
+        [],
+    )
+    .context("failed to create messages seq index")?;
+
+    let mut next_seq_by_thread: HashMap<String, i64> = HashMap::new();
+    loop {
+        let tx = conn
+            .transaction()
+            .context("failed to start message seq backfill transaction")?;
+        let batch = {
+            let mut stmt = tx.prepare(
+                "SELECT rowid, thread_id
+                 FROM messages
+                 WHERE seq IS NULL
+                 ORDER BY thread_id ASC, created_at ASC, rowid ASC
+                 LIMIT ?1",
+            )?;
+            let rows = stmt.query_map(params![MESSAGE_SEQ_BACKFILL_BATCH as i64], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .context("failed to load messages missing seq")?
+        };
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        for (row_id, thread_id) in &batch {
+            let next_seq = match next_seq_by_thread.get_mut(thread_id) {
+                Some(next_seq) => next_seq,
+                None => {
+                    let max_seq: i64 = tx.query_row(
+                        "SELECT COALESCE(MAX(seq), 0) FROM messages WHERE thread_id = ?1",
+                        params![thread_id],
+                        |row| row.get(0),
+                    )?;
+                    next_seq_by_thread
+                        .entry(thread_id.clone())
+                        .or_insert(max_seq + 1)
+                }
+            };
+            tx.execute(
+                "UPDATE messages SET seq = ?1 WHERE rowid = ?2",
+                params![*next_seq, row_id],
+            )
+            .context("failed to backfill message seq")?;
+            *next_seq += 1;
+        }
+        tx.commit()
+            .context("failed to commit message seq backfill batch")?;
+    }
+}
+
+// Search highlighting tokenizes snippets the same way; keep
+// `messages::fts_match_ranges` on the same tokenizer.
+const MESSAGES_FTS_TABLE_SQL: &str = "CREATE VIRTUAL TABLE messages_fts USING fts5(
+  thread_id UNINDEXED,
+  role UNINDEXED,
+  searchable_text,
+  content=messages,
+  content_rowid=rowid,
+  tokenize='porter unicode61'
+)";
+
+const MESSAGES_FTS_TRIGGERS_SQL: &str = "
+CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+  INSERT INTO messages_fts(rowid, thread_id, role, searchable_text)
+  VALUES (new.rowid, new.thread_id, new.role, COALESCE(new.content, ''));
+END;
+
+CREATE TRIGGER IF NOT EXISTS messages_fts_delete BEFORE DELETE ON messages BEGIN
+  INSERT INTO messages_fts(messages_fts, rowid, thread_id, role, searchable_text)
+  VALUES ('delete', old.rowid, old.thread_id, old.role, COALESCE(old.content, ''));
+END;
+
+-- Status, seq, token and approval updates leave the indexed text alone, so
+-- only re-index when it changes. Recreated so older databases pick up the
+-- narrower trigger.
+DROP TRIGGER IF EXISTS messages_fts_update;
+CREATE TRIGGER messages_fts_update AFTER UPDATE OF content, role, thread_id ON messages
+WHEN old.content IS NOT new.content
+  OR old.role IS NOT new.role
+  OR old.thread_id IS NOT new.thread_id
+BEGIN
+  INSERT INTO messages_fts(messages_fts, rowid, thread_id, role, searchable_text)
+  VALUES ('delete', old.rowid, old.thread_id, old.role, COALESCE(old.content, ''));
+  INSERT INTO messages_fts(rowid, thread_id, role, searchable_text)
+  VALUES (new.rowid, new.thread_id, new.role, COALESCE(new.content, ''));
+END;
+";
+
+/// Creates the `messages_fts` index and the triggers that keep it in sync
+/// with `messages.content`. An index from before stemming (or none at all)
+/// is rebuilt with the porter tokenizer and re-filled from existing rows.
+/// SQLite builds without FTS5 get no index; search then falls back to
+/// substring matching.
+fn ensure_messages_fts(conn: &mut Connection) -> anyhow::Result<()> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'messages_fts'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("failed to inspect messages_fts")?;
+    if !existing
+        .as_deref()
+        .is_some_and(|sql| sql.contains("porter"))
+    {
+        let tx = conn
+            .transaction()
+            .context("failed to start messages_fts rebuild transaction")?;
+        tx.execute_batch("DROP TABLE IF EXISTS messages_fts")
+            .context("failed to drop outdated messages_fts")?;
+        if let Err(error) = tx.execute_batch(MESSAGES_FTS_TABLE_SQL) {
+            if error.to_string().contains("no such module") {
+                log::warn!("SQLite was built without FTS5; message search uses substring matching");
+                return Ok(());
+            }
+            return Err(error).context("failed to create messages_fts");
+        }
+        // `content=messages` has no `searchable_text` column, so FTS5's own
+        // 'rebuild' can't read it; fill the index the way the triggers do.
+        tx.execute(
+            "INSERT INTO messages_fts(rowid, thread_id, role, searchable_text)
+             SELECT rowid, thread_id, role, COALESCE(content, '') FROM messages",
+            [],
+        )
+        .context("failed to backfill messages_fts")?;
+        tx.commit()
+            .context("failed to commit messages_fts rebuild")?;
+    }
+    conn.execute_batch(MESSAGES_FTS_TRIGGERS_SQL)
+        .context("failed to create messages_fts triggers")?;
+    Ok(())
+}
+
+/// Whether `messages_fts` exists; see [`ensure_messages_fts`].
+pub(crate) fn messages_fts_available(conn: &Connection) -> anyhow::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'messages_fts')",
+        [],
+        |row| row.get(0),
+    )
+    .context("failed to check for messages_fts")
+}
+
 /// Assistant messages used to keep content NULL forever (text only ever landed
 /// in blocks_json), so the FTS triggers never indexed them and global search
 /// could not find assistant replies. Populate content from the text blocks for
@@ -197,6 +615,8 @@ fn ensure_workspace_git_columns(conn: &Connection) -> anyhow::Result<()> {
 
 fn ensure_repo_columns(conn: &Connection) -> anyhow::Result<()> {
     ensure_column(conn, "repos", "is_discovered", "INTEGER NOT NULL DEFAULT 1")?;
+    ensure_column(conn, "repos", "protected_branches_json", "TEXT")?;
+    ensure_column(conn, "repos", "adopted_agent_configs_json", "TEXT")?;
     Ok(())
 }
 
@@ -208,8 +628,24 @@ fn ensure_workspace_startup_columns(conn: &Connection) -> anyhow::Result<()> {
 
 fn ensure_runtime_columns(conn: &Connection) -> anyhow::Result<()> {
     ensure_column(conn, "threads", "engine_capabilities_json", "TEXT")?;
+    ensure_column(conn, "threads", "usage_limits_json", "TEXT")?;
     ensure_column(conn, "messages", "stream_seq", "INTEGER NOT NULL DEFAULT 0")?;
     ensure_column(conn, "actions", "truncated", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "messages", "regenerated_from", "TEXT")?;
+    ensure_column(conn, "messages", "superseded_at", "TEXT")?;
+    Ok(())
+}
+
+/// `thread_id` attributes an operation to the thread it was run from so the
+/// thread timeline can show it.
+fn ensure_git_operation_log_columns(conn: &Connection) -> anyhow::Result<()> {
+    ensure_column(conn, "git_operation_logs", "thread_id", "TEXT")?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_git_operation_logs_thread
+         ON git_operation_logs(thread_id, created_at)",
+        [],
+    )
+    .context("failed to create git operation log thread index")?;
     Ok(())
 }
 
@@ -731,22 +1167,149 @@ fn invert_timestamp(value: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::{Arc, Mutex};
     use uuid::Uuid;
 
     fn test_db() -> Database {
         let path = std::env::temp_dir().join(format!("panes-db-mod-{}.db", Uuid::new_v4()));
-        let db = Database {
-            path,
-            pool: Arc::new(ConnectionPool {
-                idle: Mutex::new(Vec::new()),
-                max_idle: SQLITE_POOL_MAX_IDLE,
-            }),
-        };
+        let db = Database::unmigrated(path, PoolLimits::default());
         db.run_migrations().expect("failed to initialize test db");
         db
     }
 
+    fn capped_test_db(max_connections: usize, acquire_timeout: Duration) -> Database {
+        let path = std::env::temp_dir().join(format!("panes-db-pool-{}.db", Uuid::new_v4()));
+        Database::open(
+            path,
+            PoolLimits {
+                max_connections,
+                acquire_timeout,
+            },
+        )
+        .expect("failed to initialize capped test db")
+    }
+
+    // Mirrors the command-layer `run_db` helpers.
+    async fn run_db<T, F>(db: Database, operation: F) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> anyhow::Result<T> + Send + 'static,
+    {
+        let db = db.acquire_scope().await?;
+        tokio::task::spawn_blocking(move || operation(&db)).await?
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_run_db_calls_respect_connection_cap_without_deadlock() {
+        let db = capped_test_db(4, Duration::from_secs(5));
+        let mut tasks = Vec::new();
+        for index in 0..48 {
+            tasks.push(tokio::spawn(run_db(db.clone(), move |db| {
+                drop(db.connect()?);
+                // A later connect inside the same scope reuses the permit its
+                // caller already holds instead of waiting for another.
+                let conn = db.connect()?;
+                let count: i64 =
+                    conn.query_row("SELECT COUNT(*) FROM workspaces", [], |row| row.get(0))?;
+                std::thread::sleep(Duration::from_millis(5));
+                Ok((index, count))
+            })));
+        }
+        // Unscoped blocking callers queue alongside the async ones rather
+        // than losing every release to them.
+        for index in 0..16 {
+            let db = db.clone();
+            tasks.push(tokio::task::spawn_blocking(move || {
+                let conn = db.connect()?;
+                let count: i64 =
+                    conn.query_row("SELECT COUNT(*) FROM workspaces", [], |row| row.get(0))?;
+                std::thread::sleep(Duration::from_millis(5));
+                Ok((index, count))
+            }));
+        }
+
+        let joined = tokio::time::timeout(Duration::from_secs(20), async {
+            let mut completed = 0;
+            for task in tasks {
+                task.await.expect("task panicked").expect("db call failed");
+                completed += 1;
+            }
+            completed
+        })
+        .await
+        .expect("db calls deadlocked");
+
+        assert_eq!(joined, 64);
+        let metrics = db.pool_metrics();
+        assert!(metrics.peak_in_use <= 4, "peak {}", metrics.peak_in_use);
+        assert_eq!(metrics.in_use, 0);
+        assert_eq!(metrics.waiters, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn scoped_handle_connections_stay_within_the_cap() {
+        let db = capped_test_db(2, Duration::from_millis(50));
+        let scoped = db.acquire_scope().await.expect("scope should succeed");
+        tokio::task::spawn_blocking(move || {
+            let first = scoped
+                .connect()
+                .expect("first connect uses the scope permit");
+            let second = scoped
+                .connect()
+                .expect("second connect takes the free permit");
+            let metrics = scoped.pool_metrics();
+            assert_eq!(metrics.in_use, 2);
+            assert!(metrics.open <= metrics.max_connections);
+
+            let error = scoped
+                .connect()
+                .err()
+                .expect("third connect should find the pool saturated");
+            assert!(error.downcast_ref::<DbSaturatedError>().is_some());
+            assert!(scoped.pool_metrics().in_use <= 2);
+
+            drop(second);
+            drop(first);
+            assert_eq!(scoped.pool_metrics().in_use, 1);
+            drop(scoped.connect().expect("scope permit is lent again"));
+            assert_eq!(scoped.pool_metrics().in_use, 1);
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(db.pool_metrics().in_use, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn saturated_pool_returns_typed_error_after_timeout() {
+        let db = capped_test_db(1, Duration::from_millis(50));
+        let held = db
+            .acquire_scope()
+            .await
+            .expect("first scope should succeed");
+
+        let error = db
+            .acquire_scope()
+            .await
+            .err()
+            .expect("second scope should time out");
+        let saturated = error
+            .downcast_ref::<DbSaturatedError>()
+            .expect("error should be DbSaturatedError");
+        assert_eq!(saturated.max_connections, 1);
+
+        let blocking_error = tokio::task::spawn_blocking({
+            let db = db.clone();
+            move || db.connect().err()
+        })
+        .await
+        .unwrap()
+        .expect("blocking connect should time out");
+        assert!(blocking_error.downcast_ref::<DbSaturatedError>().is_some());
+
+        drop(held);
+        assert!(db.connect().is_ok());
+    }
+
     #[test]
     fn path_repair_merges_duplicate_workspaces_and_repos() {
         let db = test_db();
@@ -1037,6 +1600,90 @@ mod tests {
         assert_eq!(repos[0].trust_level.as_str(), "restricted");
         assert!(repos[0].is_active);
     }
+
+    #[test]
+    fn message_seq_backfill_orders_same_timestamp_rows_stably() {
+        let db = test_db();
+        let root = std::env::temp_dir().join(format!("panes-seq-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("failed to create temp workspace root");
+        let workspace = workspaces::upsert_workspace(&db, root.to_string_lossy().as_ref(), None)
+            .expect("failed to create workspace");
+        let thread_id = threads::create_thread(&db, &workspace.id, None, "codex", "gpt", "t")
+            .expect("failed to create thread")
+            .id;
+        let other_thread_id = threads::create_thread(&db, &workspace.id, None, "codex", "gpt", "o")
+            .expect("failed to create thread")
+            .id;
+
+        // Legacy rows: no seq and second-level timestamps that collide.
+        let conn = Connection::open(&db.path).expect("failed to open raw sqlite db");
+        for (id, thread, created_at) in [
+            ("m-b", &thread_id, "2026-01-01 10:00:00"),
+            ("m-a", &thread_id, "2026-01-01 10:00:00"),
+            ("other", &other_thread_id, "2026-01-01 10:00:00"),
+            ("m-c", &thread_id, "2026-01-01 09:59:59"),
+            ("m-d", &thread_id, "2026-01-01 10:00:00.500Z"),
+        ] {
+            conn.execute(
+                "INSERT INTO messages (id, thread_id, role, content, status, created_at)
+                 VALUES (?1, ?2, 'user', ?1, 'completed', ?3)",
+                params![id, thread, created_at],
+            )
+            .expect("failed to insert legacy message");
+        }
+        let legacy_ids = |db: &Database| {
+            messages::get_thread_messages(db, &thread_id)
+                .expect("failed to load messages")
+                .into_iter()
+                .map(|message| (message.id, message.seq))
+                .collect::<Vec<_>>()
+        };
+
+        db.run_migrations().expect("failed to backfill message seq");
+        let expected = vec![
+            ("m-c".to_string(), 1),
+            ("m-b".to_string(), 2),
+            ("m-a".to_string(), 3),
+            ("m-d".to_string(), 4),
+        ];
+        assert_eq!(legacy_ids(&db), expected);
+        assert_eq!(
+            legacy_ids(&db),
+            expected,
+            "order must not flip between fetches"
+        );
+
+        db.run_migrations().expect("failed to rerun migrations");
+        assert_eq!(legacy_ids(&db), expected, "backfill must be idempotent");
+
+        let next = messages::insert_user_message(&db, &thread_id, "new", None, None, None, None)
+            .expect("failed to insert message");
+        assert_eq!(next.seq, 5);
+        let other = messages::get_thread_messages(&db, &other_thread_id).unwrap();
+        assert_eq!(other[0].seq, 1);
+
+        let window = messages::get_thread_messages_window(&db, &thread_id, None, 2).unwrap();
+        assert_eq!(
+            window
+                .messages
+                .iter()
+                .map(|message| message.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["m-d", next.id.as_str()]
+        );
+        let mut legacy_cursor = window.next_cursor.expect("older page");
+        legacy_cursor.seq = None;
+        let older = messages::get_thread_messages_window(&db, &thread_id, Some(&legacy_cursor), 10)
+            .unwrap();
+        assert_eq!(
+            older
+                .messages
+                .iter()
+                .map(|message| message.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["m-c", "m-b", "m-a"]
+        );
+    }
 }
 
 fn ensure_column(
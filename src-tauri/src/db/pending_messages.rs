@@ -0,0 +1,324 @@
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension, Row};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::QueuedMessageDto;
+
+use super::Database;
+
+/// A message to send once the thread's running turn ends, with the same
+/// options `send_message` takes.
+#[derive(Debug, Clone, Default)]
+pub struct NewQueuedMessage {
+    pub message: String,
+    pub model_id: Option<String>,
+    pub reasoning_effort: Option<String>,
+    pub attachments: Option<Value>,
+    pub input_items: Option<Value>,
+    pub plan_mode: bool,
+    pub run_after_error: bool,
+}
+
+/// A queued message removed by [`take_next_queued_message`]. Handing it back
+/// to [`restore_queued_message`] puts it back in its old place.
+#[derive(Debug, Clone)]
+pub struct TakenQueuedMessage {
+    pub message: QueuedMessageDto,
+    position: i64,
+}
+
+const QUEUED_MESSAGE_COLUMNS: &str = "id, thread_id, message, model_id, reasoning_effort,
+    attachments_json, input_items_json, plan_mode, run_after_error, held, created_at, position";
+
+pub fn queue_message(
+    db: &Database,
+    thread_id: &str,
+    message: &NewQueuedMessage,
+) -> anyhow::Result<QueuedMessageDto> {
+    let conn = db.connect()?;
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO pending_messages (
+            id, thread_id, message, model_id, reasoning_effort, attachments_json,
+            input_items_json, plan_mode, run_after_error, position
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9,
+            (SELECT COALESCE(MAX(position), 0) + 1 FROM pending_messages WHERE thread_id = ?2))",
+        params![
+            id,
+            thread_id,
+            message.message,
+            message.model_id,
+            message.reasoning_effort,
+            message.attachments.as_ref().map(Value::to_string),
+            message.input_items.as_ref().map(Value::to_string),
+            message.plan_mode,
+            message.run_after_error,
+        ],
+    )
+    .context("failed to queue message")?;
+
+    conn.query_row(
+        &format!("SELECT {QUEUED_MESSAGE_COLUMNS} FROM pending_messages WHERE id = ?1"),
+        params![id],
+        map_queued_message,
+    )
+    .map(|(message, _)| message)
+    .context("failed to load queued message")
+}
+
+/// Queued messages of the thread, next to be sent first.
+pub fn list_queued_messages(
+    db: &Database,
+    thread_id: &str,
+) -> anyhow::Result<Vec<QueuedMessageDto>> {
+    let conn = db.connect()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {QUEUED_MESSAGE_COLUMNS}
+         FROM pending_messages
+         WHERE thread_id = ?1
+         ORDER BY position ASC"
+    ))?;
+    let rows = stmt.query_map(params![thread_id], map_queued_message)?;
+    rows.map(|row| row.map(|(message, _)| message))
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to load queued messages")
+}
+
+pub fn cancel_queued_message(
+    db: &Database,
+    thread_id: &str,
+    queued_message_id: &str,
+) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    let removed = conn
+        .execute(
+            "DELETE FROM pending_messages WHERE id = ?1 AND thread_id = ?2",
+            params![queued_message_id, thread_id],
+        )
+        .context("failed to cancel queued message")?;
+    if removed == 0 {
+        anyhow::bail!("queued message not found: {queued_message_id}");
+    }
+    Ok(())
+}
+
+/// Drops every queued message of the thread and returns how many there were.
+pub fn clear_queued_messages(db: &Database, thread_id: &str) -> anyhow::Result<usize> {
+    let conn = db.connect()?;
+    conn.execute(
+        "DELETE FROM pending_messages WHERE thread_id = ?1",
+        params![thread_id],
+    )
+    .context("failed to clear queued messages")
+}
+
+/// Removes and returns the thread's next queued message, skipping held
+/// ones. After a failed turn only a message queued with `run_after_error`
+/// is taken; otherwise the queue waits.
+pub fn take_next_queued_message(
+    db: &Database,
+    thread_id: &str,
+    after_error: bool,
+) -> anyhow::Result<Option<TakenQueuedMessage>> {
+    let mut conn = db.connect()?;
+    let tx = conn
+        .transaction()
+        .context("failed to start queued message transaction")?;
+    let next = tx
+        .query_row(
+            &format!(
+                "SELECT {QUEUED_MESSAGE_COLUMNS}
+                 FROM pending_messages
+                 WHERE thread_id = ?1 AND held = 0
+                 ORDER BY position ASC
+                 LIMIT 1"
+            ),
+            params![thread_id],
+            map_queued_message,
+        )
+        .optional()
+        .context("failed to load next queued message")?;
+    let Some((message, position)) = next else {
+        return Ok(None);
+    };
+    if after_error && !message.run_after_error {
+        return Ok(None);
+    }
+    tx.execute(
+        "DELETE FROM pending_messages WHERE id = ?1",
+        params![message.id],
+    )
+    .context("failed to take queued message")?;
+    tx.commit()
+        .context("failed to commit queued message transaction")?;
+    Ok(Some(TakenQueuedMessage { message, position }))
+}
+
+/// Puts a message taken by [`take_next_queued_message`] back in the queue.
+/// A held message stays visible but is not sent automatically.
+pub fn restore_queued_message(
+    db: &Database,
+    taken: &TakenQueuedMessage,
+    held: bool,
+) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    let message = &taken.message;
+    conn.execute(
+        "INSERT INTO pending_messages (
+            id, thread_id, message, model_id, reasoning_effort, attachments_json,
+            input_items_json, plan_mode, run_after_error, held, created_at, position
+        )
+        SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12
+        WHERE EXISTS (SELECT 1 FROM threads WHERE id = ?2)",
+        params![
+            message.id,
+            message.thread_id,
+            message.message,
+            message.model_id,
+            message.reasoning_effort,
+            message.attachments.as_ref().map(Value::to_string),
+            message.input_items.as_ref().map(Value::to_string),
+            message.plan_mode,
+            message.run_after_error,
+            held,
+            message.created_at,
+            taken.position,
+        ],
+    )
+    .context("failed to restore queued message")?;
+    Ok(())
+}
+
+fn map_queued_message(row: &Row<'_>) -> rusqlite::Result<(QueuedMessageDto, i64)> {
+    let attachments_json: Option<String> = row.get(5)?;
+    let input_items_json: Option<String> = row.get(6)?;
+    Ok((
+        QueuedMessageDto {
+            id: row.get(0)?,
+            thread_id: row.get(1)?,
+            message: row.get(2)?,
+            model_id: row.get(3)?,
+            reasoning_effort: row.get(4)?,
+            attachments: attachments_json.and_then(|raw| serde_json::from_str(&raw).ok()),
+            input_items: input_items_json.and_then(|raw| serde_json::from_str(&raw).ok()),
+            plan_mode: row.get(7)?,
+            run_after_error: row.get(8)?,
+            held: row.get(9)?,
+            created_at: row.get(10)?,
+        },
+        row.get(11)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::db::{threads, workspaces, PoolLimits};
+
+    fn test_db() -> Database {
+        let path = std::env::temp_dir().join(format!("panes-pending-{}.db", Uuid::new_v4()));
+        let db = Database::unmigrated(path, PoolLimits::default());
+        db.run_migrations().expect("failed to run test migrations");
+        db
+    }
+
+    fn test_thread(db: &Database) -> String {
+        let root = std::env::temp_dir().join(format!("panes-workspace-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("failed to create temp workspace root");
+        let workspace =
+            workspaces::upsert_workspace(db, root.to_string_lossy().as_ref(), Some(1)).unwrap();
+        threads::create_thread(db, &workspace.id, None, "codex", "gpt-5.3-codex", "test")
+            .unwrap()
+            .id
+    }
+
+    fn queued(text: &str, run_after_error: bool) -> NewQueuedMessage {
+        NewQueuedMessage {
+            message: text.to_string(),
+            run_after_error,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn queued_messages_are_taken_in_order_and_restored_in_place() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let first = queue_message(
+            &db,
+            &thread_id,
+            &NewQueuedMessage {
+                attachments: Some(json!([{ "fileName": "a.png", "filePath": "/tmp/a.png" }])),
+                plan_mode: true,
+                ..queued("first", false)
+            },
+        )
+        .unwrap();
+        queue_message(&db, &thread_id, &queued("second", false)).unwrap();
+        assert!(first.plan_mode);
+        assert_eq!(first.attachments.as_ref().unwrap()[0]["fileName"], "a.png");
+
+        let taken = take_next_queued_message(&db, &thread_id, false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(taken.message.message, "first");
+        assert_eq!(list_queued_messages(&db, &thread_id).unwrap().len(), 1);
+
+        restore_queued_message(&db, &taken, false).unwrap();
+        let texts = list_queued_messages(&db, &thread_id)
+            .unwrap()
+            .into_iter()
+            .map(|message| message.message)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn failed_turns_and_held_messages_do_not_release_the_queue() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        queue_message(&db, &thread_id, &queued("careful", false)).unwrap();
+
+        assert!(take_next_queued_message(&db, &thread_id, true)
+            .unwrap()
+            .is_none());
+
+        let taken = take_next_queued_message(&db, &thread_id, false)
+            .unwrap()
+            .unwrap();
+        restore_queued_message(&db, &taken, true).unwrap();
+        queue_message(&db, &thread_id, &queued("anyway", true)).unwrap();
+
+        let next = take_next_queued_message(&db, &thread_id, true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.message.message, "anyway");
+        let remaining = list_queued_messages(&db, &thread_id).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].held);
+    }
+
+    #[test]
+    fn runtime_recovery_holds_messages_queued_before_restart() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let message = queue_message(&db, &thread_id, &queued("later", false)).unwrap();
+
+        let report = threads::reconcile_runtime_state(&db).unwrap();
+
+        assert_eq!(report.queued_messages_held, 1);
+        assert!(take_next_queued_message(&db, &thread_id, false)
+            .unwrap()
+            .is_none());
+        cancel_queued_message(&db, &thread_id, &message.id).unwrap();
+        assert!(list_queued_messages(&db, &thread_id).unwrap().is_empty());
+        assert!(cancel_queued_message(&db, &thread_id, &message.id).is_err());
+    }
+}
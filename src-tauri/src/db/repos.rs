@@ -4,6 +4,8 @@ use anyhow::Context;
 use rusqlite::{params, OptionalExtension};
 use uuid::Uuid;
 
+use crate::agent_configs::AdoptedAgentConfig;
+use crate::git::safety;
 use crate::models::{RepoDto, TrustLevelDto};
 use crate::path_utils;
 
@@ -167,6 +169,102 @@ pub fn set_repo_trust_level(
     Ok(())
 }
 
+/// Branch patterns that refuse force operations in the repo at `repo_path`;
+/// repos without their own list use the defaults.
+pub fn get_protected_branches(db: &Database, repo_path: &str) -> anyhow::Result<Vec<String>> {
+    let conn = db.connect()?;
+    let configured: Option<String> = conn
+        .query_row(
+            "SELECT protected_branches_json
+         FROM repos
+         WHERE path = ?1 AND protected_branches_json IS NOT NULL
+         LIMIT 1",
+            params![repo_path],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("failed to load repo protected branches")?;
+
+    Ok(configured
+        .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+        .unwrap_or_else(safety::default_protected_branches))
+}
+
+/// Replaces the repo's protected branch patterns; `None` restores the
+/// defaults.
+pub fn set_repo_protected_branches(
+    db: &Database,
+    repo_id: &str,
+    patterns: Option<Vec<String>>,
+) -> anyhow::Result<()> {
+    let patterns = patterns.map(|patterns| {
+        patterns
+            .into_iter()
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect::<Vec<_>>()
+    });
+    let conn = db.connect()?;
+    let affected = conn
+        .execute(
+            "UPDATE repos SET protected_branches_json = ?1 WHERE id = ?2",
+            params![
+                patterns
+                    .map(|patterns| serde_json::to_string(&patterns))
+                    .transpose()?,
+                repo_id
+            ],
+        )
+        .context("failed to update repo protected branches")?;
+
+    if affected == 0 {
+        anyhow::bail!("repo not found: {repo_id}");
+    }
+
+    Ok(())
+}
+
+/// Agent configs adopted for the repo; see [`AdoptedAgentConfig`].
+pub fn get_adopted_agent_configs(
+    db: &Database,
+    repo_id: &str,
+) -> anyhow::Result<Vec<AdoptedAgentConfig>> {
+    let conn = db.connect()?;
+    let raw: Option<String> = conn
+        .query_row(
+            "SELECT adopted_agent_configs_json FROM repos WHERE id = ?1",
+            params![repo_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("failed to load repo agent configs")?
+        .flatten();
+
+    Ok(raw
+        .and_then(|raw| serde_json::from_str::<Vec<AdoptedAgentConfig>>(&raw).ok())
+        .unwrap_or_default())
+}
+
+pub fn set_adopted_agent_configs(
+    db: &Database,
+    repo_id: &str,
+    configs: &[AdoptedAgentConfig],
+) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    let affected = conn
+        .execute(
+            "UPDATE repos SET adopted_agent_configs_json = ?1 WHERE id = ?2",
+            params![serde_json::to_string(configs)?, repo_id],
+        )
+        .context("failed to update repo agent configs")?;
+
+    if affected == 0 {
+        anyhow::bail!("repo not found: {repo_id}");
+    }
+
+    Ok(())
+}
+
 pub fn set_repo_active(db: &Database, repo_id: &str, is_active: bool) -> anyhow::Result<()> {
     let conn = db.connect()?;
     let affected = conn
@@ -295,6 +393,7 @@ fn map_repo_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RepoDto> {
         default_branch: row.get(4)?,
         is_active: row.get::<_, i64>(5)? > 0,
         trust_level: TrustLevelDto::from_str(&row.get::<_, String>(6)?),
+        detected_configs: Vec::new(),
     })
 }
 
@@ -314,26 +413,17 @@ fn find_repo_id_by_workspace_and_path(
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        fs,
-        sync::{Arc, Mutex},
-    };
+    use std::fs;
 
     use uuid::Uuid;
 
-    use crate::db::{threads, workspaces, ConnectionPool, SQLITE_POOL_MAX_IDLE};
+    use crate::db::{threads, workspaces, PoolLimits};
 
     use super::*;
 
     fn test_db() -> Database {
         let path = std::env::temp_dir().join(format!("panes-repos-{}.db", Uuid::new_v4()));
-        let db = Database {
-            path,
-            pool: Arc::new(ConnectionPool {
-                idle: Mutex::new(Vec::new()),
-                max_idle: SQLITE_POOL_MAX_IDLE,
-            }),
-        };
+        let db = Database::unmigrated(path, PoolLimits::default());
         db.run_migrations().expect("failed to run test migrations");
         db
     }
@@ -1,15 +1,27 @@
 use anyhow::Context;
-use rusqlite::{params, OptionalExtension};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
 use uuid::Uuid;
 
-use crate::models::{ThreadDto, ThreadStatusDto};
+use crate::{
+    engines::{engine_capabilities_dto, events::UsageLimitsSnapshot},
+    models::{ThreadDto, ThreadStatusDto},
+};
 
-use super::Database;
+use super::{usage_limits, Database};
 
-#[derive(Debug, Default, Clone, Copy)]
+/// Threads with no messages and no engine thread that are older than this
+/// are reported by startup recovery as likely leftovers of failed creation.
+pub const STALE_EMPTY_THREAD_HOURS: u32 = 24;
+
+#[derive(Debug, Default, Clone)]
 pub struct RuntimeRecoveryReport {
     pub messages_marked_interrupted: usize,
     pub thread_status_updates: usize,
+    /// Messages queued before the restart; kept but no longer sent on their own.
+    pub queued_messages_held: usize,
+    /// Flagged only; `delete_stale_empty_threads` removes them on request.
+    pub stale_empty_thread_ids: Vec<String>,
 }
 
 pub fn create_thread(
@@ -19,24 +31,91 @@ pub fn create_thread(
     engine_id: &str,
     model_id: &str,
     title: &str,
+) -> anyhow::Result<ThreadDto> {
+    create_thread_with_metadata(db, workspace_id, repo_id, engine_id, model_id, title, None)
+}
+
+/// Inserts the thread and its initial engine metadata in one transaction,
+/// so a failure leaves no row behind.
+pub fn create_thread_with_metadata(
+    db: &Database,
+    workspace_id: &str,
+    repo_id: Option<&str>,
+    engine_id: &str,
+    model_id: &str,
+    title: &str,
+    metadata: Option<&serde_json::Value>,
+) -> anyhow::Result<ThreadDto> {
+    let mut conn = db.connect()?;
+    let tx = conn
+        .transaction()
+        .context("failed to start thread creation transaction")?;
+    let thread = create_thread_with_metadata_on(
+        &tx,
+        workspace_id,
+        repo_id,
+        engine_id,
+        model_id,
+        title,
+        metadata,
+    )?;
+    tx.commit()
+        .context("failed to commit thread creation transaction")?;
+    Ok(thread)
+}
+
+/// [`create_thread_with_metadata`] on a connection the caller has already
+/// opened a transaction on, e.g. to insert the thread's messages with it.
+pub(crate) fn create_thread_with_metadata_on(
+    tx: &Connection,
+    workspace_id: &str,
+    repo_id: Option<&str>,
+    engine_id: &str,
+    model_id: &str,
+    title: &str,
+    metadata: Option<&serde_json::Value>,
 ) -> anyhow::Result<ThreadDto> {
     let id = Uuid::new_v4().to_string();
-    let conn = db.connect()?;
-    conn.execute(
-        "INSERT INTO threads (id, workspace_id, repo_id, engine_id, model_id, title, status)
-     VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'idle')",
-        params![id, workspace_id, repo_id, engine_id, model_id, title],
+    let capabilities = serde_json::to_string(&engine_capabilities_dto(engine_id))
+        .context("failed to serialize engine capabilities")?;
+    tx.execute(
+        "INSERT INTO threads
+       (id, workspace_id, repo_id, engine_id, model_id, title, status, engine_capabilities_json)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'idle', ?7)",
+        params![
+            id,
+            workspace_id,
+            repo_id,
+            engine_id,
+            model_id,
+            title,
+            capabilities
+        ],
     )
     .context("failed to create thread")?;
-
-    get_thread(db, &id)?.context("thread not found after insert")
+    if let Some(metadata) = metadata {
+        tx.execute(
+            "UPDATE threads SET engine_metadata_json = ?1 WHERE id = ?2",
+            params![metadata.to_string(), id],
+        )
+        .context("failed to store initial engine metadata")?;
+    }
+    get_thread_on(tx, &id)?.context("thread not found after insert")
 }
 
 pub fn get_thread(db: &Database, thread_id: &str) -> anyhow::Result<Option<ThreadDto>> {
     let conn = db.connect()?;
+    get_thread_on(&conn, thread_id)
+}
+
+pub(crate) fn get_thread_on(
+    conn: &Connection,
+    thread_id: &str,
+) -> anyhow::Result<Option<ThreadDto>> {
     conn.query_row(
     "SELECT id, workspace_id, repo_id, engine_id, model_id, engine_thread_id, engine_metadata_json,
-            COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at
+            COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at,
+            engine_capabilities_json
      FROM threads WHERE id = ?1",
     params![thread_id],
     map_thread_row,
@@ -53,7 +132,8 @@ pub fn find_thread_by_engine_thread_id(
     let conn = db.connect()?;
     conn.query_row(
         "SELECT id, workspace_id, repo_id, engine_id, model_id, engine_thread_id, engine_metadata_json,
-                COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at
+                COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at,
+                engine_capabilities_json
          FROM threads
          WHERE engine_id = ?1
            AND engine_thread_id = ?2
@@ -72,7 +152,8 @@ pub fn list_threads_for_workspace(
     let conn = db.connect()?;
     let mut stmt = conn.prepare(
     "SELECT id, workspace_id, repo_id, engine_id, model_id, engine_thread_id, engine_metadata_json,
-            COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at
+            COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at,
+            engine_capabilities_json
      FROM threads
      WHERE workspace_id = ?1
        AND archived_at IS NULL
@@ -101,7 +182,8 @@ pub fn list_archived_threads_for_workspace(
     let conn = db.connect()?;
     let mut stmt = conn.prepare(
     "SELECT id, workspace_id, repo_id, engine_id, model_id, engine_thread_id, engine_metadata_json,
-            COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at
+            COALESCE(title, ''), status, message_count, total_tokens, created_at, last_activity_at,
+            engine_capabilities_json
      FROM threads
      WHERE workspace_id = ?1
        AND archived_at IS NOT NULL
@@ -129,6 +211,14 @@ pub fn update_thread_status(
     status: ThreadStatusDto,
 ) -> anyhow::Result<()> {
     let conn = db.connect()?;
+    update_thread_status_on(&conn, thread_id, status)
+}
+
+pub(crate) fn update_thread_status_on(
+    conn: &Connection,
+    thread_id: &str,
+    status: ThreadStatusDto,
+) -> anyhow::Result<()> {
     conn.execute(
         "UPDATE threads
      SET status = ?1, last_activity_at = datetime('now')
@@ -154,6 +244,22 @@ pub fn set_engine_thread_id(
     Ok(())
 }
 
+/// Detaches the thread from its engine-side conversation; the next turn
+/// starts a new one.
+pub fn clear_engine_thread_id(db: &Database, thread_id: &str) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    clear_engine_thread_id_on(&conn, thread_id)
+}
+
+pub(crate) fn clear_engine_thread_id_on(conn: &Connection, thread_id: &str) -> anyhow::Result<()> {
+    conn.execute(
+        "UPDATE threads SET engine_thread_id = NULL WHERE id = ?1",
+        params![thread_id],
+    )
+    .context("failed to clear engine thread id")?;
+    Ok(())
+}
+
 pub fn delete_thread(db: &Database, thread_id: &str) -> anyhow::Result<()> {
     let conn = db.connect()?;
     let affected = conn
@@ -220,6 +326,184 @@ pub fn update_engine_metadata(
     Ok(())
 }
 
+/// Last usage snapshot an engine reported for the thread, so a reopened
+/// thread can show its context usage before the next turn. Rate limits come
+/// from the engine's account-wide snapshot.
+pub fn get_usage_limits(
+    db: &Database,
+    thread_id: &str,
+) -> anyhow::Result<Option<UsageLimitsSnapshot>> {
+    let conn = db.connect()?;
+    let Some(engine_id) = thread_engine_id_on(&conn, thread_id)? else {
+        return Ok(None);
+    };
+    let thread_usage = get_usage_limits_on(&conn, thread_id)?;
+    let engine_usage =
+        usage_limits::get_engine_usage_limits_on(&conn, &engine_id)?.map(|limits| limits.usage);
+    Ok(match (thread_usage, engine_usage) {
+        (Some(thread_usage), Some(engine_usage)) => Some(usage_limits::merge_usage_limits(
+            thread_usage,
+            &engine_usage,
+        )),
+        (thread_usage, engine_usage) => thread_usage.or(engine_usage),
+    })
+}
+
+fn thread_engine_id_on(conn: &Connection, thread_id: &str) -> anyhow::Result<Option<String>> {
+    conn.query_row(
+        "SELECT engine_id FROM threads WHERE id = ?1",
+        params![thread_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("failed to read thread engine")
+}
+
+fn get_usage_limits_on(
+    conn: &Connection,
+    thread_id: &str,
+) -> anyhow::Result<Option<UsageLimitsSnapshot>> {
+    let raw = conn
+        .query_row(
+            "SELECT usage_limits_json FROM threads WHERE id = ?1",
+            params![thread_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .context("failed to read thread usage limits")?
+        .flatten();
+    Ok(raw.and_then(|raw| serde_json::from_str(&raw).ok()))
+}
+
+/// Forgets the context fill of the stored snapshot, keeping the window size
+/// and rate limits, for a thread that moved to a fresh engine session.
+pub fn reset_context_usage(db: &Database, thread_id: &str) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    reset_context_usage_on(&conn, thread_id)
+}
+
+pub(crate) fn reset_context_usage_on(conn: &Connection, thread_id: &str) -> anyhow::Result<()> {
+    let Some(mut snapshot) = get_usage_limits_on(conn, thread_id)? else {
+        return Ok(());
+    };
+    snapshot.current_tokens = None;
+    snapshot.context_window_percent = None;
+    conn.execute(
+        "UPDATE threads SET usage_limits_json = ?1 WHERE id = ?2",
+        params![serde_json::to_string(&snapshot)?, thread_id],
+    )
+    .context("failed to reset thread context usage")?;
+    Ok(())
+}
+
+/// Folds `usage` into the stored snapshots: context usage into the
+/// thread's, rate limits into its engine's. Fields the update leaves unset
+/// keep their last known value.
+pub(crate) fn update_usage_limits_on(
+    conn: &Connection,
+    thread_id: &str,
+    usage: &UsageLimitsSnapshot,
+) -> anyhow::Result<()> {
+    let Some(engine_id) = thread_engine_id_on(conn, thread_id)? else {
+        return Ok(());
+    };
+    let merged = match get_usage_limits_on(conn, thread_id)? {
+        Some(previous) => usage_limits::merge_usage_limits(previous, usage),
+        None => usage.clone(),
+    };
+    conn.execute(
+        "UPDATE threads SET usage_limits_json = ?1 WHERE id = ?2",
+        params![
+            serde_json::to_string(&usage_limits::context_fields(&merged))?,
+            thread_id
+        ],
+    )
+    .context("failed to update thread usage limits")?;
+    usage_limits::update_engine_usage_limits_on(conn, &engine_id, usage)
+}
+
+/// Metadata key recording a terminal that runs the engine's own CLI on this
+/// thread. While it is set, turns started from Panes may race that session.
+pub const EXTERNAL_CLI_SESSION_KEY: &str = "externalCliSession";
+
+/// Records `terminal_session_id` as the CLI session attached to the thread.
+pub fn set_external_cli_session(
+    db: &Database,
+    thread_id: &str,
+    terminal_session_id: &str,
+) -> anyhow::Result<()> {
+    let conn = db.connect()?;
+    let session = serde_json::json!({
+        "terminalSessionId": terminal_session_id,
+        "attachedAt": Utc::now().to_rfc3339(),
+    });
+    conn.execute(
+        "UPDATE threads
+       SET engine_metadata_json = json_set(
+             CASE WHEN json_valid(engine_metadata_json) THEN engine_metadata_json ELSE '{}' END,
+             '$.' || ?1,
+             json(?2)
+           )
+       WHERE id = ?3",
+        params![EXTERNAL_CLI_SESSION_KEY, session.to_string(), thread_id],
+    )
+    .context("failed to mark thread as attached to an external CLI")?;
+    Ok(())
+}
+
+/// Clears the CLI attachment. With `terminal_session_id` only that session's
+/// attachment is cleared, so a stale exit cannot drop a newer one. Returns
+/// whether anything changed.
+pub fn clear_external_cli_session(
+    db: &Database,
+    thread_id: &str,
+    terminal_session_id: Option<&str>,
+) -> anyhow::Result<bool> {
+    let conn = db.connect()?;
+    let changed = conn
+        .execute(
+            "UPDATE threads
+       SET engine_metadata_json = json_remove(engine_metadata_json, '$.' || ?1)
+       WHERE id = ?2
+         AND json_valid(engine_metadata_json)
+         AND json_type(engine_metadata_json, '$.' || ?1) IS NOT NULL
+         AND (?3 IS NULL
+              OR json_extract(engine_metadata_json, '$.' || ?1 || '.terminalSessionId') = ?3)",
+            params![EXTERNAL_CLI_SESSION_KEY, thread_id, terminal_session_id],
+        )
+        .context("failed to clear external CLI attachment")?;
+    Ok(changed > 0)
+}
+
+/// Metadata key holding the linked worktree a repo thread runs in instead of
+/// the repo's main checkout.
+pub const WORKTREE_PATH_KEY: &str = "worktreePath";
+
+/// Clears the worktree target of every thread pointed at `worktree_path`,
+/// returning their ids.
+pub fn clear_worktree_path(db: &Database, worktree_path: &str) -> anyhow::Result<Vec<String>> {
+    let conn = db.connect()?;
+    let mut stmt = conn
+        .prepare(
+            "UPDATE threads
+       SET engine_metadata_json = json_remove(engine_metadata_json, '$.' || ?1)
+       WHERE json_valid(engine_metadata_json)
+         AND json_extract(engine_metadata_json, '$.' || ?1) = ?2
+       RETURNING id",
+        )
+        .context("failed to prepare worktree target cleanup")?;
+    let rows = stmt.query_map(params![WORKTREE_PATH_KEY, worktree_path], |row| row.get(0))?;
+    rows.collect::<Result<Vec<String>, _>>()
+        .context("failed to clear thread worktree targets")
+}
+
+pub fn external_cli_session_id(metadata: Option<&serde_json::Value>) -> Option<&str> {
+    metadata?
+        .get(EXTERNAL_CLI_SESSION_KEY)?
+        .get("terminalSessionId")?
+        .as_str()
+}
+
 pub fn bump_message_counters(
     db: &Database,
     thread_id: &str,
@@ -251,6 +535,13 @@ pub fn update_thread_title(db: &Database, thread_id: &str, title: &str) -> anyho
 
 pub fn refresh_thread_message_stats(db: &Database, thread_id: &str) -> anyhow::Result<()> {
     let conn = db.connect()?;
+    refresh_thread_message_stats_on(&conn, thread_id)
+}
+
+pub(crate) fn refresh_thread_message_stats_on(
+    conn: &Connection,
+    thread_id: &str,
+) -> anyhow::Result<()> {
     let (message_count, total_tokens, latest_message_at): (i64, i64, Option<String>) = conn
         .query_row(
             "SELECT
@@ -357,15 +648,84 @@ pub fn reconcile_runtime_state(db: &Database) -> anyhow::Result<RuntimeRecoveryR
         thread_status_updates += changed;
     }
 
+    let queued_messages_held = tx
+        .execute("UPDATE pending_messages SET held = 1 WHERE held = 0", [])
+        .context("failed to hold queued messages from the previous session")?;
+
+    let stale_empty_thread_ids = stale_empty_thread_ids(&tx, None, STALE_EMPTY_THREAD_HOURS)?;
+
     tx.commit()
         .context("failed to commit runtime recovery transaction")?;
 
     Ok(RuntimeRecoveryReport {
         messages_marked_interrupted,
         thread_status_updates,
+        queued_messages_held,
+        stale_empty_thread_ids,
     })
 }
 
+const STALE_EMPTY_THREAD_FILTER: &str = "engine_thread_id IS NULL
+         AND NOT EXISTS (SELECT 1 FROM messages WHERE messages.thread_id = threads.id)
+         AND created_at <= datetime('now', '-' || ?1 || ' hours')
+         AND (?2 IS NULL OR workspace_id = ?2)";
+
+/// Threads that never got a message or an engine thread, created at least
+/// `older_than_hours` ago.
+fn stale_empty_thread_ids(
+    conn: &rusqlite::Connection,
+    workspace_id: Option<&str>,
+    older_than_hours: u32,
+) -> anyhow::Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id FROM threads WHERE {STALE_EMPTY_THREAD_FILTER} ORDER BY created_at ASC"
+        ))
+        .context("failed to prepare stale empty thread query")?;
+    let rows = stmt
+        .query_map(params![older_than_hours, workspace_id], |row| {
+            row.get::<_, String>(0)
+        })
+        .context("failed to query stale empty threads")?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to decode stale empty thread id")
+}
+
+pub fn list_stale_empty_threads(
+    db: &Database,
+    workspace_id: Option<&str>,
+    older_than_hours: u32,
+) -> anyhow::Result<Vec<String>> {
+    let conn = db.connect()?;
+    stale_empty_thread_ids(&conn, workspace_id, older_than_hours)
+}
+
+/// Deletes the threads [`list_stale_empty_threads`] would return, except
+/// `keep`, and returns how many were removed.
+pub fn delete_stale_empty_threads(
+    db: &Database,
+    workspace_id: Option<&str>,
+    older_than_hours: u32,
+    keep: &[String],
+) -> anyhow::Result<usize> {
+    let mut conn = db.connect()?;
+    let tx = conn
+        .transaction()
+        .context("failed to start empty thread cleanup transaction")?;
+    let mut deleted = 0;
+    for thread_id in stale_empty_thread_ids(&tx, workspace_id, older_than_hours)? {
+        if keep.contains(&thread_id) {
+            continue;
+        }
+        deleted += tx
+            .execute("DELETE FROM threads WHERE id = ?1", params![thread_id])
+            .context("failed to delete empty thread")?;
+    }
+    tx.commit()
+        .context("failed to commit empty thread cleanup transaction")?;
+    Ok(deleted)
+}
+
 fn derive_thread_status_for_recovery(
     conn: &rusqlite::Connection,
     thread_id: &str,
@@ -394,7 +754,7 @@ fn derive_thread_status_for_recovery(
        FROM messages
        WHERE thread_id = ?1
          AND role = 'assistant'
-       ORDER BY created_at DESC, rowid DESC
+       ORDER BY seq DESC, rowid DESC
        LIMIT 1",
             params![thread_id],
             |row| row.get::<_, String>(0),
@@ -414,7 +774,13 @@ fn derive_thread_status_for_recovery(
 
 fn map_thread_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ThreadDto> {
     let metadata_raw: Option<String> = row.get(6)?;
-    let metadata = metadata_raw.and_then(|raw| serde_json::from_str(&raw).ok());
+    let metadata: Option<serde_json::Value> =
+        metadata_raw.and_then(|raw| serde_json::from_str(&raw).ok());
+    let reasoning_effort = metadata
+        .as_ref()
+        .and_then(|value| value.get("reasoningEffort"))
+        .and_then(serde_json::Value::as_str)
+        .map(ToOwned::to_owned);
 
     Ok(ThreadDto {
         id: row.get(0)?,
@@ -424,38 +790,33 @@ fn map_thread_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ThreadDto> {
         model_id: row.get(4)?,
         engine_thread_id: row.get(5)?,
         engine_metadata: metadata,
+        reasoning_effort,
         title: row.get(7)?,
         status: ThreadStatusDto::from_str(&row.get::<_, String>(8)?),
         message_count: row.get(9)?,
         total_tokens: row.get(10)?,
         created_at: row.get(11)?,
         last_activity_at: row.get(12)?,
+        engine_capabilities: row
+            .get::<_, Option<String>>(13)?
+            .and_then(|raw| serde_json::from_str(&raw).ok()),
     })
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        fs,
-        sync::{Arc, Mutex},
-    };
+    use std::fs;
 
     use serde_json::json;
     use uuid::Uuid;
 
-    use crate::db::{messages, workspaces, ConnectionPool, SQLITE_POOL_MAX_IDLE};
+    use crate::db::{messages, workspaces, PoolLimits};
 
     use super::*;
 
     fn test_db() -> Database {
         let path = std::env::temp_dir().join(format!("panes-threads-{}.db", Uuid::new_v4()));
-        let db = Database {
-            path,
-            pool: Arc::new(ConnectionPool {
-                idle: Mutex::new(Vec::new()),
-                max_idle: SQLITE_POOL_MAX_IDLE,
-            }),
-        };
+        let db = Database::unmigrated(path, PoolLimits::default());
         db.run_migrations().expect("failed to run test migrations");
         db
     }
@@ -468,6 +829,185 @@ mod tests {
         create_thread(db, &workspace.id, None, "codex", "gpt-5.3-codex", title).unwrap()
     }
 
+    #[test]
+    fn created_threads_store_an_engine_capabilities_snapshot() {
+        let db = test_db();
+        let thread = test_thread(&db, "Snapshot");
+
+        let capabilities = thread
+            .engine_capabilities
+            .expect("expected a capabilities snapshot");
+        assert!(capabilities.plan_mode);
+        assert!(capabilities.thread_rename);
+
+        let conn = db.connect().unwrap();
+        conn.execute(
+            "UPDATE threads SET engine_capabilities_json = NULL WHERE id = ?1",
+            params![thread.id],
+        )
+        .unwrap();
+        let legacy = get_thread(&db, &thread.id).unwrap().unwrap();
+        assert!(legacy.engine_capabilities.is_none());
+    }
+
+    #[test]
+    fn removed_worktrees_are_cleared_only_from_threads_that_used_them() {
+        let db = test_db();
+        let on_worktree = test_thread(&db, "On worktree");
+        let elsewhere = test_thread(&db, "Elsewhere");
+        update_engine_metadata(
+            &db,
+            &on_worktree.id,
+            &json!({ "worktreePath": "/repo/.panes/feature", "reasoningEffort": "high" }),
+        )
+        .unwrap();
+        update_engine_metadata(
+            &db,
+            &elsewhere.id,
+            &json!({ "worktreePath": "/repo/.panes/other" }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            clear_worktree_path(&db, "/repo/.panes/feature").unwrap(),
+            vec![on_worktree.id.clone()]
+        );
+        assert_eq!(
+            get_thread(&db, &on_worktree.id)
+                .unwrap()
+                .unwrap()
+                .engine_metadata,
+            Some(json!({ "reasoningEffort": "high" }))
+        );
+        assert_eq!(
+            get_thread(&db, &elsewhere.id)
+                .unwrap()
+                .unwrap()
+                .engine_metadata,
+            Some(json!({ "worktreePath": "/repo/.panes/other" }))
+        );
+        assert!(clear_worktree_path(&db, "/repo/.panes/feature")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn usage_limit_updates_keep_fields_the_update_leaves_unset() {
+        let db = test_db();
+        let thread = test_thread(&db, "Usage");
+        assert!(get_usage_limits(&db, &thread.id).unwrap().is_none());
+
+        let conn = db.connect().unwrap();
+        update_usage_limits_on(
+            &conn,
+            &thread.id,
+            &UsageLimitsSnapshot {
+                current_tokens: Some(12_000),
+                max_context_tokens: Some(200_000),
+                five_hour_percent: Some(40),
+                ..UsageLimitsSnapshot::default()
+            },
+        )
+        .unwrap();
+        update_usage_limits_on(
+            &conn,
+            &thread.id,
+            &UsageLimitsSnapshot {
+                five_hour_percent: Some(55),
+                weekly_percent: Some(10),
+                ..UsageLimitsSnapshot::default()
+            },
+        )
+        .unwrap();
+
+        let stored = get_usage_limits(&db, &thread.id)
+            .unwrap()
+            .expect("usage snapshot should be stored");
+        assert_eq!(stored.current_tokens, Some(12_000));
+        assert_eq!(stored.max_context_tokens, Some(200_000));
+        assert_eq!(stored.five_hour_percent, Some(55));
+        assert_eq!(stored.weekly_percent, Some(10));
+    }
+
+    #[test]
+    fn rate_limits_are_shared_by_threads_of_the_same_engine() {
+        let db = test_db();
+        let first = test_thread(&db, "First");
+        let second = test_thread(&db, "Second");
+
+        let conn = db.connect().unwrap();
+        update_usage_limits_on(
+            &conn,
+            &first.id,
+            &UsageLimitsSnapshot {
+                current_tokens: Some(9_000),
+                weekly_percent: Some(30),
+                weekly_resets_at: Some(1_800_000_000),
+                ..UsageLimitsSnapshot::default()
+            },
+        )
+        .unwrap();
+
+        let engine = usage_limits::get_engine_usage_limits(&db, "codex")
+            .unwrap()
+            .expect("engine snapshot should be stored");
+        assert_eq!(engine.usage.weekly_percent, Some(30));
+        assert_eq!(engine.usage.current_tokens, None);
+        assert!(!engine.updated_at.is_empty());
+
+        let other = get_usage_limits(&db, &second.id)
+            .unwrap()
+            .expect("other thread sees the engine limits");
+        assert_eq!(other.weekly_percent, Some(30));
+        assert_eq!(other.weekly_resets_at, Some(1_800_000_000));
+        assert_eq!(other.current_tokens, None);
+
+        let raw: String = conn
+            .query_row(
+                "SELECT usage_limits_json FROM threads WHERE id = ?1",
+                params![first.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!raw.contains("\"weekly_percent\":30"));
+        assert!(usage_limits::get_engine_usage_limits(&db, "claude")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn external_cli_attachment_is_cleared_only_by_its_own_session() {
+        let db = test_db();
+        let thread = test_thread(&db, "CLI");
+        update_engine_metadata(&db, &thread.id, &json!({ "manualTitle": true })).unwrap();
+        let attached_to = |db: &Database| {
+            let thread = get_thread(db, &thread.id).unwrap().unwrap();
+            assert_eq!(
+                thread
+                    .engine_metadata
+                    .as_ref()
+                    .and_then(|value| value.get("manualTitle")),
+                Some(&json!(true))
+            );
+            external_cli_session_id(thread.engine_metadata.as_ref()).map(ToOwned::to_owned)
+        };
+
+        set_external_cli_session(&db, &thread.id, "term-1").unwrap();
+        assert_eq!(attached_to(&db).as_deref(), Some("term-1"));
+
+        set_external_cli_session(&db, &thread.id, "term-2").unwrap();
+        assert!(!clear_external_cli_session(&db, &thread.id, Some("term-1")).unwrap());
+        assert_eq!(attached_to(&db).as_deref(), Some("term-2"));
+
+        assert!(clear_external_cli_session(&db, &thread.id, Some("term-2")).unwrap());
+        assert_eq!(attached_to(&db), None);
+        assert!(!clear_external_cli_session(&db, &thread.id, Some("term-2")).unwrap());
+
+        set_external_cli_session(&db, &thread.id, "term-3").unwrap();
+        assert!(clear_external_cli_session(&db, &thread.id, None).unwrap());
+        assert_eq!(attached_to(&db), None);
+    }
+
     #[test]
     fn update_thread_runtime_snapshot_preserves_manual_title() {
         let db = test_db();
@@ -585,4 +1125,138 @@ mod tests {
         assert!(listed_ids.contains(&visible.id));
         assert!(!listed_ids.contains(&hidden.id));
     }
+
+    fn thread_count(db: &Database) -> i64 {
+        db.connect()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM threads", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn failed_thread_creation_leaves_no_row_behind() {
+        let db = test_db();
+        let workspace_id = test_thread(&db, "Existing").workspace_id;
+        let conn = db.connect().unwrap();
+        let before = thread_count(&db);
+
+        conn.execute_batch(
+            "CREATE TRIGGER fail_metadata BEFORE UPDATE OF engine_metadata_json ON threads
+             BEGIN SELECT RAISE(ABORT, 'injected metadata failure'); END;",
+        )
+        .unwrap();
+        let error = create_thread_with_metadata(
+            &db,
+            &workspace_id,
+            None,
+            "codex",
+            "gpt-5.3-codex",
+            "Ghost",
+            Some(&json!({ "reasoningEffort": "high" })),
+        )
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("injected metadata failure"));
+        assert_eq!(thread_count(&db), before);
+
+        conn.execute_batch(
+            "DROP TRIGGER fail_metadata;
+             CREATE TRIGGER fail_insert BEFORE INSERT ON threads
+             BEGIN SELECT RAISE(ABORT, 'injected insert failure'); END;",
+        )
+        .unwrap();
+        assert!(
+            create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "Ghost").is_err()
+        );
+        assert_eq!(thread_count(&db), before);
+
+        conn.execute_batch("DROP TRIGGER fail_insert;").unwrap();
+        let created = create_thread_with_metadata(
+            &db,
+            &workspace_id,
+            None,
+            "codex",
+            "gpt-5.3-codex",
+            "Real",
+            Some(&json!({ "reasoningEffort": "high" })),
+        )
+        .unwrap();
+        assert_eq!(
+            created
+                .engine_metadata
+                .as_ref()
+                .and_then(|value| value.get("reasoningEffort")),
+            Some(&json!("high"))
+        );
+        assert_eq!(thread_count(&db), before + 1);
+    }
+
+    #[test]
+    fn stale_empty_threads_are_flagged_by_recovery_and_removed_on_request() {
+        let db = test_db();
+        let backdate = |thread_id: &str| {
+            db.connect()
+                .unwrap()
+                .execute(
+                    "UPDATE threads SET created_at = datetime('now', '-3 days') WHERE id = ?1",
+                    params![thread_id],
+                )
+                .unwrap();
+        };
+
+        let orphan = test_thread(&db, "Orphan");
+        let workspace_id = orphan.workspace_id.clone();
+        backdate(&orphan.id);
+        let busy_orphan =
+            create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "Busy").unwrap();
+        backdate(&busy_orphan.id);
+        let fresh =
+            create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "Fresh").unwrap();
+        let with_engine =
+            create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "Engine").unwrap();
+        set_engine_thread_id(&db, &with_engine.id, "thr_1").unwrap();
+        backdate(&with_engine.id);
+        let with_message =
+            create_thread(&db, &workspace_id, None, "codex", "gpt-5.3-codex", "Chat").unwrap();
+        messages::insert_user_message(&db, &with_message.id, "hi", None, None, None, None).unwrap();
+        backdate(&with_message.id);
+        let elsewhere = test_thread(&db, "Other workspace");
+        backdate(&elsewhere.id);
+
+        let report = reconcile_runtime_state(&db).unwrap();
+        let mut flagged = report.stale_empty_thread_ids.clone();
+        flagged.sort();
+        let mut expected = vec![
+            orphan.id.clone(),
+            busy_orphan.id.clone(),
+            elsewhere.id.clone(),
+        ];
+        expected.sort();
+        assert_eq!(flagged, expected);
+        assert!(!flagged.contains(&fresh.id));
+
+        assert_eq!(
+            list_stale_empty_threads(&db, Some(&workspace_id), 100)
+                .unwrap()
+                .len(),
+            0
+        );
+        let deleted = delete_stale_empty_threads(
+            &db,
+            Some(&workspace_id),
+            STALE_EMPTY_THREAD_HOURS,
+            std::slice::from_ref(&busy_orphan.id),
+        )
+        .unwrap();
+        assert_eq!(deleted, 1);
+        assert!(get_thread(&db, &orphan.id).unwrap().is_none());
+        for kept in [
+            &busy_orphan,
+            &fresh,
+            &with_engine,
+            &with_message,
+            &elsewhere,
+        ] {
+            assert!(get_thread(&db, &kept.id).unwrap().is_some());
+        }
+    }
 }
@@ -0,0 +1,564 @@
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use rusqlite::Connection;
+use serde_json::Value;
+use tokio::{sync::oneshot, task::JoinHandle};
+
+use crate::{
+    engines::events::{ActionResult, ActionType, UsageLimitsSnapshot},
+    models::{MessageStatusDto, ThreadStatusDto},
+};
+
+use super::{actions, messages, threads, Database};
+
+/// Pending writes that trigger a commit without waiting for the delay.
+pub const TURN_WRITE_BATCH_MAX_WRITES: usize = 64;
+/// Longest a queued write waits before its batch is committed.
+pub const TURN_WRITE_BATCH_MAX_DELAY: Duration = Duration::from_millis(250);
+
+/// One row-level write issued while a turn streams.
+#[derive(Debug, Clone)]
+pub enum TurnWrite {
+    EventLog {
+        thread_id: String,
+        message_id: String,
+        event: Value,
+    },
+    ActionStarted {
+        action_id: String,
+        thread_id: String,
+        message_id: String,
+        engine_action_id: Option<String>,
+        action_type: ActionType,
+        summary: String,
+        details: Value,
+    },
+    ActionCompleted {
+        action_id: String,
+        result: ActionResult,
+    },
+    Approval {
+        approval_id: String,
+        thread_id: String,
+        message_id: String,
+        action_type: ActionType,
+        summary: String,
+        details: Value,
+    },
+    Blocks {
+        message_id: String,
+        blocks_json: String,
+        status: MessageStatusDto,
+        turn_model_id: Option<String>,
+    },
+    MessageStatus {
+        message_id: String,
+        status: MessageStatusDto,
+    },
+    TurnModel {
+        message_id: String,
+        turn_model_id: String,
+    },
+    ThreadStatus {
+        thread_id: String,
+        status: ThreadStatusDto,
+    },
+    UsageLimits {
+        thread_id: String,
+        usage: UsageLimitsSnapshot,
+    },
+}
+
+impl TurnWrite {
+    pub fn apply(&self, conn: &Connection) -> anyhow::Result<()> {
+        match self {
+            Self::EventLog {
+                thread_id,
+                message_id,
+                event,
+            } => actions::append_event_log_on(conn, thread_id, message_id, event),
+            Self::ActionStarted {
+                action_id,
+                thread_id,
+                message_id,
+                engine_action_id,
+                action_type,
+                summary,
+                details,
+            } => actions::insert_action_started_on(
+                conn,
+                action_id,
+                thread_id,
+                message_id,
+                engine_action_id.as_deref(),
+                action_type,
+                summary,
+                details,
+            ),
+            Self::ActionCompleted { action_id, result } => {
+                actions::update_action_completed_on(conn, action_id, result)
+            }
+            Self::Approval {
+                approval_id,
+                thread_id,
+                message_id,
+                action_type,
+                summary,
+                details,
+            } => actions::insert_approval_on(
+                conn,
+                approval_id,
+                thread_id,
+                message_id,
+                action_type,
+                summary,
+                details,
+            ),
+            Self::Blocks {
+                message_id,
+                blocks_json,
+                status,
+                turn_model_id,
+            } => messages::update_assistant_blocks_json_on(
+                conn,
+                message_id,
+                blocks_json,
+                status.clone(),
+                turn_model_id.as_deref(),
+            ),
+            Self::MessageStatus { message_id, status } => {
+                messages::update_assistant_status_on(conn, message_id, status.clone())
+            }
+            Self::TurnModel {
+                message_id,
+                turn_model_id,
+            } => messages::update_assistant_turn_model_id_on(conn, message_id, turn_model_id),
+            Self::ThreadStatus { thread_id, status } => {
+                threads::update_thread_status_on(conn, thread_id, status.clone())
+            }
+            Self::UsageLimits { thread_id, usage } => {
+                threads::update_usage_limits_on(conn, thread_id, usage)
+            }
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::EventLog { .. } => "engine event log",
+            Self::ActionStarted { .. } => "action start",
+            Self::ActionCompleted { .. } => "action completion",
+            Self::Approval { .. } => "approval",
+            Self::Blocks { .. } => "assistant stream blocks",
+            Self::MessageStatus { .. } => "assistant stream status",
+            Self::TurnModel { .. } => "assistant turn model id",
+            Self::ThreadStatus { .. } => "thread status",
+            Self::UsageLimits { .. } => "thread usage limits",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TurnWriteStats {
+    pub writes: usize,
+    pub transactions: usize,
+    /// Batches that failed and were replayed one write at a time.
+    pub retried_batches: usize,
+    pub failed_writes: usize,
+}
+
+enum TurnWriteCommand {
+    Write(Box<TurnWrite>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Serializes a turn's writes through one blocking worker.
+///
+/// Writes are queued without waiting and committed in transactions of up to
+/// [`TURN_WRITE_BATCH_MAX_WRITES`], or after [`TURN_WRITE_BATCH_MAX_DELAY`],
+/// in the order they were queued. A connection and its pool permit are
+/// checked out per batch and returned between flushes, so a turn costs one
+/// checkout per batch rather than per write, and an idle turn holds none.
+pub struct TurnWriteBatcher {
+    sender: mpsc::Sender<TurnWriteCommand>,
+    worker: JoinHandle<TurnWriteStats>,
+}
+
+impl TurnWriteBatcher {
+    pub fn start(db: &Database) -> Self {
+        let db = db.clone();
+        let (sender, receiver) = mpsc::channel();
+        let worker = tokio::task::spawn_blocking(move || {
+            run_worker(
+                db,
+                receiver,
+                TURN_WRITE_BATCH_MAX_WRITES,
+                TURN_WRITE_BATCH_MAX_DELAY,
+            )
+        });
+        Self { sender, worker }
+    }
+
+    pub fn enqueue(&self, write: TurnWrite) {
+        if let Err(mpsc::SendError(TurnWriteCommand::Write(write))) =
+            self.sender.send(TurnWriteCommand::Write(Box::new(write)))
+        {
+            log::warn!("turn write batcher stopped; dropping {}", write.label());
+        }
+    }
+
+    /// Commits everything queued so far and waits for it.
+    pub async fn flush(&self) {
+        let (ack, done) = oneshot::channel();
+        if self.sender.send(TurnWriteCommand::Flush(ack)).is_ok() {
+            let _ = done.await;
+        }
+    }
+
+    /// Drains the queue and reports what was done.
+    pub async fn finish(self) -> TurnWriteStats {
+        drop(self.sender);
+        match self.worker.await {
+            Ok(stats) => stats,
+            Err(error) => {
+                log::warn!("turn write batcher task failed: {error}");
+                TurnWriteStats::default()
+            }
+        }
+    }
+}
+
+fn run_worker(
+    db: Database,
+    receiver: mpsc::Receiver<TurnWriteCommand>,
+    max_writes: usize,
+    max_delay: Duration,
+) -> TurnWriteStats {
+    let mut worker = TurnWriteWorker {
+        db,
+        pending: Vec::new(),
+        stats: TurnWriteStats::default(),
+    };
+    let mut oldest_pending_at: Option<Instant> = None;
+    loop {
+        let received = match oldest_pending_at {
+            Some(queued_at) => receiver.recv_timeout(max_delay.saturating_sub(queued_at.elapsed())),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(TurnWriteCommand::Write(write)) => {
+                worker.pending.push(*write);
+                let queued_at = *oldest_pending_at.get_or_insert_with(Instant::now);
+                if worker.pending.len() >= max_writes || queued_at.elapsed() >= max_delay {
+                    worker.commit();
+                    oldest_pending_at = None;
+                }
+            }
+            Ok(TurnWriteCommand::Flush(ack)) => {
+                worker.commit();
+                oldest_pending_at = None;
+                let _ = ack.send(());
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                worker.commit();
+                oldest_pending_at = None;
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                worker.commit();
+                break;
+            }
+        }
+    }
+    worker.stats
+}
+
+struct TurnWriteWorker {
+    db: Database,
+    pending: Vec<TurnWrite>,
+    stats: TurnWriteStats,
+}
+
+impl TurnWriteWorker {
+    fn commit(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let batch = std::mem::take(&mut self.pending);
+        self.stats.writes += batch.len();
+
+        // Dropped at the end of the batch, handing the permit back to the
+        // pool until the next flush.
+        let mut conn = match self.db.connect() {
+            Ok(conn) => conn,
+            Err(error) => {
+                log::warn!("failed to persist {} turn write(s): {error}", batch.len());
+                self.stats.failed_writes += batch.len();
+                return;
+            }
+        };
+
+        self.stats.transactions += 1;
+        let committed = conn
+            .transaction()
+            .map_err(anyhow::Error::from)
+            .and_then(|tx| {
+                for write in &batch {
+                    write.apply(&tx)?;
+                }
+                tx.commit().map_err(anyhow::Error::from)
+            });
+        let Err(error) = committed else {
+            return;
+        };
+
+        // The rollback undid the whole batch; replay it in autocommit mode so
+        // one bad row only loses itself.
+        log::warn!(
+            "batched turn writes failed, retrying {} individually: {error}",
+            batch.len()
+        );
+        self.stats.retried_batches += 1;
+        for write in &batch {
+            self.stats.transactions += 1;
+            if let Err(error) = write.apply(&conn) {
+                log::warn!("failed to persist {}: {error}", write.label());
+                self.stats.failed_writes += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::params;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::db::{threads, workspaces, PoolLimits};
+
+    struct Fixture {
+        db: Database,
+        thread_id: String,
+        message_id: String,
+    }
+
+    fn fixture() -> Fixture {
+        fixture_with_limits(PoolLimits::default())
+    }
+
+    fn fixture_with_limits(limits: PoolLimits) -> Fixture {
+        let path = std::env::temp_dir().join(format!("panes-turn-writes-{}.db", Uuid::new_v4()));
+        let db = Database::unmigrated(path, limits);
+        db.run_migrations().expect("failed to run test migrations");
+        let root = std::env::temp_dir().join(format!("panes-turn-writes-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create workspace root");
+        let workspace = workspaces::upsert_workspace(&db, root.to_string_lossy().as_ref(), Some(0))
+            .expect("create workspace");
+        let thread =
+            threads::create_thread(&db, &workspace.id, None, "codex", "gpt-5", "Batch test")
+                .expect("create thread");
+        let message = messages::insert_assistant_placeholder(
+            &db,
+            &thread.id,
+            Some("codex"),
+            Some("gpt-5"),
+            None,
+        )
+        .expect("insert assistant placeholder");
+        Fixture {
+            db,
+            thread_id: thread.id,
+            message_id: message.id,
+        }
+    }
+
+    /// 100 actions started and completed, the block flushes in between, and
+    /// the closing status writes.
+    fn synthetic_turn(thread_id: &str, message_id: &str) -> Vec<TurnWrite> {
+        let mut writes = Vec::new();
+        for index in 0..100 {
+            let action_id = format!("action-{index}");
+            writes.push(TurnWrite::ActionStarted {
+                action_id: action_id.clone(),
+                thread_id: thread_id.to_string(),
+                message_id: message_id.to_string(),
+                engine_action_id: None,
+                action_type: ActionType::Command,
+                summary: format!("step {index}"),
+                details: serde_json::json!({ "index": index }),
+            });
+            writes.push(TurnWrite::ActionCompleted {
+                action_id,
+                result: ActionResult {
+                    success: index % 7 != 0,
+                    output: Some(format!("output {index}")),
+                    error: None,
+                    diff: None,
+                    duration_ms: index,
+                },
+            });
+            if index % 10 == 9 {
+                writes.push(TurnWrite::Blocks {
+                    message_id: message_id.to_string(),
+                    blocks_json: serde_json::json!([{ "type": "text", "content": format!("progress {index}") }])
+                        .to_string(),
+                    status: MessageStatusDto::Streaming,
+                    turn_model_id: Some("gpt-5".to_string()),
+                });
+            }
+        }
+        writes.push(TurnWrite::MessageStatus {
+            message_id: message_id.to_string(),
+            status: MessageStatusDto::Completed,
+        });
+        writes.push(TurnWrite::ThreadStatus {
+            thread_id: thread_id.to_string(),
+            status: ThreadStatusDto::Idle,
+        });
+        writes
+    }
+
+    fn persisted_state(fixture: &Fixture) -> (Vec<(String, String, String)>, String, String) {
+        let conn = fixture.db.connect().expect("connect");
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, status, COALESCE(result_json, '') FROM actions
+                 WHERE message_id = ?1 ORDER BY id",
+            )
+            .expect("prepare actions query");
+        let actions = stmt
+            .query_map(params![fixture.message_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .expect("query actions")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("decode actions");
+        let (blocks, status) = conn
+            .query_row(
+                "SELECT COALESCE(blocks_json, ''), status FROM messages WHERE id = ?1",
+                params![fixture.message_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("load message");
+        (actions, blocks, status)
+    }
+
+    #[tokio::test]
+    async fn batching_cuts_transactions_and_matches_unbatched_writes() {
+        let baseline = fixture();
+        let writes = synthetic_turn(&baseline.thread_id, &baseline.message_id);
+        assert!(writes.len() >= 200);
+        for write in &writes {
+            let conn = baseline.db.connect().expect("connect");
+            write.apply(&conn).expect("unbatched write");
+        }
+
+        let batched = fixture();
+        let batcher = TurnWriteBatcher::start(&batched.db);
+        for write in synthetic_turn(&batched.thread_id, &batched.message_id) {
+            batcher.enqueue(write);
+        }
+        let stats = batcher.finish().await;
+
+        assert_eq!(stats.writes, writes.len());
+        assert_eq!(stats.failed_writes, 0);
+        assert!(
+            stats.transactions * 10 <= writes.len(),
+            "expected an order of magnitude fewer transactions, got {}",
+            stats.transactions
+        );
+
+        let (baseline_actions, baseline_blocks, baseline_status) = persisted_state(&baseline);
+        let (batched_actions, batched_blocks, batched_status) = persisted_state(&batched);
+        assert_eq!(batched_actions.len(), 100);
+        assert_eq!(batched_actions, baseline_actions);
+        assert_eq!(batched_blocks, baseline_blocks);
+        assert_eq!(batched_status, baseline_status);
+        assert_eq!(batched_status, "completed");
+    }
+
+    #[tokio::test]
+    async fn failed_batch_is_retried_one_write_at_a_time() {
+        let fixture = fixture();
+        let batcher = TurnWriteBatcher::start(&fixture.db);
+        for (index, thread_id) in [
+            &fixture.thread_id,
+            &"missing-thread".to_string(),
+            &fixture.thread_id,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            batcher.enqueue(TurnWrite::ActionStarted {
+                action_id: format!("action-{index}"),
+                thread_id: thread_id.clone(),
+                message_id: fixture.message_id.clone(),
+                engine_action_id: None,
+                action_type: ActionType::Command,
+                summary: "step".to_string(),
+                details: Value::Null,
+            });
+        }
+        batcher.flush().await;
+        let (actions, _, _) = persisted_state(&fixture);
+        let stats = batcher.finish().await;
+
+        assert_eq!(
+            actions.iter().map(|row| row.0.as_str()).collect::<Vec<_>>(),
+            vec!["action-0", "action-2"]
+        );
+        assert_eq!(stats.retried_batches, 1);
+        assert_eq!(stats.failed_writes, 1);
+        assert_eq!(stats.transactions, 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn running_turns_leave_connections_for_other_callers() {
+        let max_connections = 2;
+        let fixture = fixture_with_limits(PoolLimits {
+            max_connections,
+            acquire_timeout: Duration::from_millis(200),
+        });
+        let batchers = (0..max_connections)
+            .map(|_| TurnWriteBatcher::start(&fixture.db))
+            .collect::<Vec<_>>();
+        for (index, batcher) in batchers.iter().enumerate() {
+            batcher.enqueue(TurnWrite::ActionStarted {
+                action_id: format!("action-{index}"),
+                thread_id: fixture.thread_id.clone(),
+                message_id: fixture.message_id.clone(),
+                engine_action_id: None,
+                action_type: ActionType::Command,
+                summary: "step".to_string(),
+                details: Value::Null,
+            });
+            batcher.flush().await;
+        }
+
+        // Every turn is still running, but none holds a permit between
+        // flushes.
+        assert_eq!(fixture.db.pool_metrics().in_use, 0);
+        let scoped = fixture
+            .db
+            .acquire_scope()
+            .await
+            .expect("other callers should still get a permit");
+        let count = tokio::task::spawn_blocking(move || {
+            let conn = scoped.connect()?;
+            conn.query_row("SELECT COUNT(*) FROM actions", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map_err(anyhow::Error::from)
+        })
+        .await
+        .unwrap()
+        .expect("other callers should still get a connection");
+        assert_eq!(count, max_connections as i64);
+
+        for batcher in batchers {
+            assert_eq!(batcher.finish().await.failed_writes, 0);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rusqlite::{params_from_iter, types::Value as SqlValue};
+
+use crate::models::{UsageGroupByDto, UsageSummaryDto, UsageSummaryRowDto};
+
+use super::Database;
+
+const BOUND_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Filters for [`usage_summary`]. `since` is inclusive and `until`
+/// exclusive; either may be left open.
+#[derive(Debug, Clone, Default)]
+pub struct UsageQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub group_by: Vec<UsageGroupByDto>,
+    pub include_archived: bool,
+}
+
+/// Token and message totals for a workspace's messages, grouped by any mix
+/// of UTC day, engine and model. Messages from before per-turn engine and
+/// model were recorded fall back to their thread's. Superseded
+/// regenerations are counted: their tokens were spent all the same.
+pub fn usage_summary(
+    db: &Database,
+    workspace_id: &str,
+    query: &UsageQuery,
+) -> anyhow::Result<UsageSummaryDto> {
+    let by_day = query.group_by.contains(&UsageGroupByDto::Day);
+    let by_engine = query.group_by.contains(&UsageGroupByDto::Engine);
+    let by_model = query.group_by.contains(&UsageGroupByDto::Model);
+    let mut keys = Vec::new();
+    let mut select = Vec::new();
+    for (grouped, expr) in [
+        (by_day, "substr(m.created_at, 1, 10)"),
+        (by_engine, "COALESCE(m.turn_engine_id, t.engine_id)"),
+        (by_model, "COALESCE(m.turn_model_id, t.model_id)"),
+    ] {
+        if grouped {
+            keys.push(expr);
+            select.push(expr);
+        } else {
+            select.push("NULL");
+        }
+    }
+
+    let mut sql = format!(
+        "SELECT {},
+                COALESCE(SUM(m.token_input), 0),
+                COALESCE(SUM(m.token_output), 0),
+                COUNT(*)
+         FROM messages m
+         JOIN threads t ON t.id = m.thread_id
+         WHERE t.workspace_id = ?",
+        select.join(", ")
+    );
+    let mut args = vec![SqlValue::Text(workspace_id.to_string())];
+    // Bounds are only added when set so the `created_at` range stays
+    // usable by the index.
+    if let Some(since) = query.since {
+        sql.push_str(" AND m.created_at >= ?");
+        args.push(SqlValue::Text(since.format(BOUND_FORMAT).to_string()));
+    }
+    if let Some(until) = query.until {
+        sql.push_str(" AND m.created_at < ?");
+        args.push(SqlValue::Text(until.format(BOUND_FORMAT).to_string()));
+    }
+    if !query.include_archived {
+        sql.push_str(" AND t.archived_at IS NULL");
+    }
+    if !keys.is_empty() {
+        let keys = keys.join(", ");
+        sql.push_str(&format!(" GROUP BY {keys} ORDER BY {keys}"));
+    }
+
+    let conn = db.connect()?;
+    let mut stmt = conn
+        .prepare(&sql)
+        .context("failed to prepare usage query")?;
+    let rows = stmt.query_map(params_from_iter(args), |row| {
+        Ok(UsageSummaryRowDto {
+            day: row.get(0)?,
+            engine_id: row.get(1)?,
+            model_id: row.get(2)?,
+            input_tokens: row.get(3)?,
+            output_tokens: row.get(4)?,
+            message_count: row.get(5)?,
+        })
+    })?;
+    let rows = rows
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to load usage summary")?
+        .into_iter()
+        .filter(|row| row.message_count > 0)
+        .collect::<Vec<_>>();
+
+    let totals = rows
+        .iter()
+        .fold(UsageSummaryRowDto::default(), |mut totals, row| {
+            totals.input_tokens += row.input_tokens;
+            totals.output_tokens += row.output_tokens;
+            totals.message_count += row.message_count;
+            totals
+        });
+    Ok(UsageSummaryDto { rows, totals })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rusqlite::params;
+    use uuid::Uuid;
+
+    use crate::{
+        db::{messages, threads, workspaces, PoolLimits},
+        models::MessageStatusDto,
+    };
+
+    use super::*;
+
+    fn test_db() -> Database {
+        let path = std::env::temp_dir().join(format!("panes-usage-{}.db", Uuid::new_v4()));
+        let db = Database::unmigrated(path, PoolLimits::default());
+        db.run_migrations().expect("failed to run test migrations");
+        db
+    }
+
+    fn test_workspace(db: &Database) -> String {
+        let root = std::env::temp_dir().join(format!("panes-workspace-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("failed to create temp workspace root");
+        workspaces::upsert_workspace(db, root.to_string_lossy().as_ref(), Some(1))
+            .unwrap()
+            .id
+    }
+
+    fn seed_reply(
+        db: &Database,
+        thread_id: &str,
+        model_id: Option<&str>,
+        tokens: (u64, u64),
+        created_at: &str,
+    ) {
+        let message =
+            messages::insert_assistant_placeholder(db, thread_id, None, model_id, None).unwrap();
+        messages::complete_assistant_message(
+            db,
+            &message.id,
+            MessageStatusDto::Completed,
+            Some(tokens),
+            None,
+        )
+        .unwrap();
+        db.connect()
+            .unwrap()
+            .execute(
+                "UPDATE messages SET created_at = ?1 WHERE id = ?2",
+                params![created_at, message.id],
+            )
+            .unwrap();
+    }
+
+    fn at(raw: &str) -> DateTime<Utc> {
+        messages::parse_message_timestamp(raw).unwrap()
+    }
+
+    #[test]
+    fn usage_groups_by_day_and_model_within_the_window() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let other_workspace = test_workspace(&db);
+        let thread = threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.4", "a")
+            .unwrap()
+            .id;
+        let archived = threads::create_thread(&db, &workspace_id, None, "claude", "opus", "b")
+            .unwrap()
+            .id;
+        let elsewhere =
+            threads::create_thread(&db, &other_workspace, None, "codex", "gpt-5.4", "c")
+                .unwrap()
+                .id;
+
+        seed_reply(&db, &thread, None, (100, 10), "2026-03-01 09:00:00");
+        seed_reply(
+            &db,
+            &thread,
+            Some("gpt-5.4-mini"),
+            (50, 5),
+            "2026-03-01 18:00:00.250Z",
+        );
+        seed_reply(&db, &thread, None, (200, 20), "2026-03-02 08:00:00");
+        seed_reply(&db, &thread, None, (999, 99), "2026-02-27 08:00:00");
+        seed_reply(&db, &archived, None, (7, 1), "2026-03-02 10:00:00");
+        seed_reply(&db, &elsewhere, None, (5000, 500), "2026-03-01 10:00:00");
+        threads::archive_thread(&db, &archived).unwrap();
+
+        let query = UsageQuery {
+            since: Some(at("2026-03-01 00:00:00")),
+            until: Some(at("2026-03-03 00:00:00")),
+            group_by: vec![UsageGroupByDto::Model, UsageGroupByDto::Day],
+            include_archived: false,
+        };
+        let summary = usage_summary(&db, &workspace_id, &query).unwrap();
+
+        let row = |day: &str, model: &str, input, output| UsageSummaryRowDto {
+            day: Some(day.to_string()),
+            engine_id: None,
+            model_id: Some(model.to_string()),
+            input_tokens: input,
+            output_tokens: output,
+            message_count: 1,
+        };
+        assert_eq!(
+            summary.rows,
+            [
+                row("2026-03-01", "gpt-5.4", 100, 10),
+                row("2026-03-01", "gpt-5.4-mini", 50, 5),
+                row("2026-03-02", "gpt-5.4", 200, 20),
+            ]
+        );
+        assert_eq!(summary.totals.input_tokens, 350);
+        assert_eq!(summary.totals.message_count, 3);
+
+        let with_archived = UsageQuery {
+            group_by: vec![UsageGroupByDto::Engine],
+            include_archived: true,
+            ..query
+        };
+        let summary = usage_summary(&db, &workspace_id, &with_archived).unwrap();
+        let engines = summary
+            .rows
+            .iter()
+            .map(|row| (row.engine_id.as_deref().unwrap(), row.input_tokens))
+            .collect::<Vec<_>>();
+        assert_eq!(engines, [("claude", 7), ("codex", 350)]);
+    }
+
+    #[test]
+    fn ungrouped_usage_is_a_single_total_and_empty_workspaces_have_no_rows() {
+        let db = test_db();
+        let workspace_id = test_workspace(&db);
+        let summary = usage_summary(&db, &workspace_id, &UsageQuery::default()).unwrap();
+        assert!(summary.rows.is_empty());
+        assert_eq!(summary.totals, UsageSummaryRowDto::default());
+
+        let thread = threads::create_thread(&db, &workspace_id, None, "codex", "gpt-5.4", "a")
+            .unwrap()
+            .id;
+        seed_reply(&db, &thread, None, (3, 4), "2026-03-01 09:00:00");
+        seed_reply(&db, &thread, None, (5, 6), "2026-03-05 09:00:00");
+        let summary = usage_summary(&db, &workspace_id, &UsageQuery::default()).unwrap();
+        assert_eq!(summary.rows.len(), 1);
+        assert_eq!(summary.rows[0].day, None);
+        assert_eq!(summary.totals.output_tokens, 10);
+        assert_eq!(summary.totals.message_count, 2);
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{engines::events::UsageLimitsSnapshot, models::EngineUsageLimitsDto};
+
+use super::Database;
+
+/// Rate limits are account-wide, so they are kept once per engine; context
+/// usage belongs to a thread's engine session and stays on the thread.
+pub fn get_engine_usage_limits(
+    db: &Database,
+    engine_id: &str,
+) -> anyhow::Result<Option<EngineUsageLimitsDto>> {
+    let conn = db.connect()?;
+    get_engine_usage_limits_on(&conn, engine_id)
+}
+
+pub(crate) fn get_engine_usage_limits_on(
+    conn: &Connection,
+    engine_id: &str,
+) -> anyhow::Result<Option<EngineUsageLimitsDto>> {
+    let row = conn
+        .query_row(
+            "SELECT usage_limits_json, updated_at FROM engine_usage_limits WHERE engine_id = ?1",
+            params![engine_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()
+        .context("failed to read engine usage limits")?;
+    Ok(row.and_then(|(raw, updated_at)| {
+        let usage = serde_json::from_str(&raw).ok()?;
+        Some(EngineUsageLimitsDto {
+            engine_id: engine_id.to_string(),
+            usage,
+            updated_at,
+        })
+    }))
+}
+
+/// Folds the rate limit windows of `usage` into the engine's snapshot. An
+/// update carrying none leaves the snapshot and its timestamp alone.
+pub(crate) fn update_engine_usage_limits_on(
+    conn: &Connection,
+    engine_id: &str,
+    usage: &UsageLimitsSnapshot,
+) -> anyhow::Result<()> {
+    let update = rate_limit_fields(usage);
+    if !has_rate_limits(&update) {
+        return Ok(());
+    }
+    let merged = match get_engine_usage_limits_on(conn, engine_id)? {
+        Some(previous) => merge_usage_limits(previous.usage, &update),
+        None => update,
+    };
+    conn.execute(
+        "INSERT INTO engine_usage_limits (engine_id, usage_limits_json, updated_at)
+         VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(engine_id) DO UPDATE SET
+           usage_limits_json = excluded.usage_limits_json,
+           updated_at = excluded.updated_at",
+        params![engine_id, serde_json::to_string(&merged)?],
+    )
+    .context("failed to update engine usage limits")?;
+    Ok(())
+}
+
+/// The context window fields of `usage`, with the rate limits cleared.
+pub(crate) fn context_fields(usage: &UsageLimitsSnapshot) -> UsageLimitsSnapshot {
+    UsageLimitsSnapshot {
+        current_tokens: usage.current_tokens,
+        max_context_tokens: usage.max_context_tokens,
+        context_window_percent: usage.context_window_percent,
+        ..UsageLimitsSnapshot::default()
+    }
+}
+
+/// The rate limit windows of `usage`, with the context fields cleared.
+pub(crate) fn rate_limit_fields(usage: &UsageLimitsSnapshot) -> UsageLimitsSnapshot {
+    UsageLimitsSnapshot {
+        current_tokens: None,
+        max_context_tokens: None,
+        context_window_percent: None,
+        ..usage.clone()
+    }
+}
+
+fn has_rate_limits(usage: &UsageLimitsSnapshot) -> bool {
+    usage.five_hour_percent.is_some()
+        || usage.weekly_percent.is_some()
+        || usage.fable_weekly_percent.is_some()
+        || usage.opus_weekly_percent.is_some()
+        || usage.sonnet_weekly_percent.is_some()
+        || usage.five_hour_resets_at.is_some()
+        || usage.weekly_resets_at.is_some()
+        || usage.fable_weekly_resets_at.is_some()
+        || usage.opus_weekly_resets_at.is_some()
+        || usage.sonnet_weekly_resets_at.is_some()
+}
+
+/// Engines report rate limits and context usage in separate events, so
+/// fields the update leaves unset keep their last known value.
+pub(crate) fn merge_usage_limits(
+    previous: UsageLimitsSnapshot,
+    update: &UsageLimitsSnapshot,
+) -> UsageLimitsSnapshot {
+    UsageLimitsSnapshot {
+        current_tokens: update.current_tokens.or(previous.current_tokens),
+        max_context_tokens: update.max_context_tokens.or(previous.max_context_tokens),
+        context_window_percent: update
+            .context_window_percent
+            .or(previous.context_window_percent),
+        five_hour_percent: update.five_hour_percent.or(previous.five_hour_percent),
+        weekly_percent: update.weekly_percent.or(previous.weekly_percent),
+        fable_weekly_percent: update
+            .fable_weekly_percent
+            .or(previous.fable_weekly_percent),
+        opus_weekly_percent: update.opus_weekly_percent.or(previous.opus_weekly_percent),
+        sonnet_weekly_percent: update
+            .sonnet_weekly_percent
+            .or(previous.sonnet_weekly_percent),
+        five_hour_resets_at: update.five_hour_resets_at.or(previous.five_hour_resets_at),
+        weekly_resets_at: update.weekly_resets_at.or(previous.weekly_resets_at),
+        fable_weekly_resets_at: update
+            .fable_weekly_resets_at
+            .or(previous.fable_weekly_resets_at),
+        opus_weekly_resets_at: update
+            .opus_weekly_resets_at
+            .or(previous.opus_weekly_resets_at),
+        sonnet_weekly_resets_at: update
+            .sonnet_weekly_resets_at
+            .or(previous.sonnet_weekly_resets_at),
+    }
+}
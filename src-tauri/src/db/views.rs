@@ -0,0 +1,658 @@
+use anyhow::Context;
+use rusqlite::{params, Connection};
+
+use crate::models::{
+    ThreadTimelineCursorDto, ThreadTimelineDto, ThreadTimelineEntryDto, ThreadTimelineEntryKindDto,
+};
+
+use super::Database;
+
+const TIMELINE_EXCERPT_MAX_CHARS: usize = 160;
+// Only the head of a message is needed for the first-line excerpt.
+const TIMELINE_EXCERPT_SCAN_CHARS: i64 = 1_024;
+
+/// Builds one page of a thread's activity timeline, newest-first keyset
+/// pagination with entries returned in ascending order. Each entity type is
+/// read with its own indexed query (no blocks_json parsing) and the pages are
+/// merged in memory. Git operations show up when they were run from the
+/// thread.
+pub fn get_thread_timeline(
+    db: &Database,
+    thread_id: &str,
+    before: Option<&ThreadTimelineCursorDto>,
+    limit: usize,
+) -> anyhow::Result<ThreadTimelineDto> {
+    let conn = db.connect()?;
+    let page_limit = limit.max(1);
+    let query_limit = page_limit.saturating_add(1) as i64;
+    let cursor = TimelineCursor::from_dto(before);
+
+    let mut entries = Vec::new();
+    entries.extend(load_message_entries(
+        &conn,
+        thread_id,
+        &cursor,
+        query_limit,
+    )?);
+    entries.extend(load_action_entries(&conn, thread_id, &cursor, query_limit)?);
+    entries.extend(load_approval_entries(
+        &conn,
+        thread_id,
+        &cursor,
+        query_limit,
+    )?);
+    entries.extend(load_file_change_entries(
+        &conn,
+        thread_id,
+        &cursor,
+        query_limit,
+    )?);
+    entries.extend(load_git_operation_entries(
+        &conn,
+        thread_id,
+        &cursor,
+        query_limit,
+    )?);
+
+    entries.sort_by(|left, right| timeline_sort_key(right).cmp(&timeline_sort_key(left)));
+    let has_more = entries.len() > page_limit;
+    entries.truncate(page_limit);
+
+    let next_cursor = if has_more {
+        entries.last().map(|entry| ThreadTimelineCursorDto {
+            created_at: entry.created_at.clone(),
+            kind: entry.kind,
+            id: entry.id.clone(),
+        })
+    } else {
+        None
+    };
+
+    entries.reverse();
+    Ok(ThreadTimelineDto {
+        entries,
+        next_cursor,
+    })
+}
+
+struct TimelineCursor<'a> {
+    created_at: Option<&'a str>,
+    rank: Option<i64>,
+    id: Option<&'a str>,
+}
+
+impl<'a> TimelineCursor<'a> {
+    fn from_dto(cursor: Option<&'a ThreadTimelineCursorDto>) -> Self {
+        Self {
+            created_at: cursor.map(|value| value.created_at.as_str()),
+            rank: cursor.map(|value| value.kind.rank()),
+            id: cursor.map(|value| value.id.as_str()),
+        }
+    }
+}
+
+fn timeline_sort_key(entry: &ThreadTimelineEntryDto) -> (&str, i64, &str) {
+    (
+        entry.created_at.as_str(),
+        entry.kind.rank(),
+        entry.id.as_str(),
+    )
+}
+
+fn empty_entry(
+    id: String,
+    kind: ThreadTimelineEntryKindDto,
+    message_id: Option<String>,
+    created_at: String,
+) -> ThreadTimelineEntryDto {
+    ThreadTimelineEntryDto {
+        id,
+        kind,
+        message_id,
+        created_at,
+        excerpt: None,
+        action_type: None,
+        summary: None,
+        success: None,
+        duration_ms: None,
+        decision: None,
+        wait_ms: None,
+        file_change_count: None,
+        outcome: None,
+    }
+}
+
+fn load_message_entries(
+    conn: &Connection,
+    thread_id: &str,
+    cursor: &TimelineCursor<'_>,
+    limit: i64,
+) -> anyhow::Result<Vec<ThreadTimelineEntryDto>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, role, substr(content, 1, ?6), created_at,
+                CASE role WHEN 'user' THEN 0 ELSE 1 END AS rank
+             FROM messages
+             WHERE thread_id = ?1
+               AND role IN ('user', 'assistant')
+               AND (?2 IS NULL OR (created_at, rank, id) < (?2, ?3, ?4))
+             ORDER BY created_at DESC, rank DESC, id DESC
+             LIMIT ?5",
+        )
+        .context("failed to prepare timeline message query")?;
+    let rows = stmt.query_map(
+        params![
+            thread_id,
+            cursor.created_at,
+            cursor.rank,
+            cursor.id,
+            limit,
+            TIMELINE_EXCERPT_SCAN_CHARS
+        ],
+        |row| {
+            let id: String = row.get(0)?;
+            let role: String = row.get(1)?;
+            let content: Option<String> = row.get(2)?;
+            let created_at: String = row.get(3)?;
+            let kind = if role == "user" {
+                ThreadTimelineEntryKindDto::UserMessage
+            } else {
+                ThreadTimelineEntryKindDto::AssistantSummary
+            };
+            let mut entry = empty_entry(id.clone(), kind, Some(id), created_at);
+            entry.excerpt = content.as_deref().and_then(first_line_excerpt);
+            Ok(entry)
+        },
+    )?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load timeline messages")
+}
+
+fn load_action_entries(
+    conn: &Connection,
+    thread_id: &str,
+    cursor: &TimelineCursor<'_>,
+    limit: i64,
+) -> anyhow::Result<Vec<ThreadTimelineEntryDto>> {
+    let rank = ThreadTimelineEntryKindDto::Action.rank();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, message_id, action_type, summary, status, duration_ms, created_at
+             FROM actions
+             WHERE thread_id = ?1
+               AND (?2 IS NULL OR (created_at, ?6, id) < (?2, ?3, ?4))
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?5",
+        )
+        .context("failed to prepare timeline action query")?;
+    let rows = stmt.query_map(
+        params![
+            thread_id,
+            cursor.created_at,
+            cursor.rank,
+            cursor.id,
+            limit,
+            rank
+        ],
+        |row| {
+            let status: String = row.get(4)?;
+            let mut entry = empty_entry(
+                row.get(0)?,
+                ThreadTimelineEntryKindDto::Action,
+                row.get(1)?,
+                row.get(6)?,
+            );
+            entry.action_type = Some(row.get(2)?);
+            entry.summary = Some(row.get(3)?);
+            entry.success = match status.as_str() {
+                "done" => Some(true),
+                "error" => Some(false),
+                _ => None,
+            };
+            entry.duration_ms = row.get(5)?;
+            Ok(entry)
+        },
+    )?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load timeline actions")
+}
+
+fn load_approval_entries(
+    conn: &Connection,
+    thread_id: &str,
+    cursor: &TimelineCursor<'_>,
+    limit: i64,
+) -> anyhow::Result<Vec<ThreadTimelineEntryDto>> {
+    let rank = ThreadTimelineEntryKindDto::Approval.rank();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, message_id, action_type, summary, decision,
+                CAST(ROUND((julianday(answered_at) - julianday(created_at)) * 86400000) AS INTEGER),
+                created_at
+             FROM approvals
+             WHERE thread_id = ?1
+               AND (?2 IS NULL OR (created_at, ?6, id) < (?2, ?3, ?4))
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?5",
+        )
+        .context("failed to prepare timeline approval query")?;
+    let rows = stmt.query_map(
+        params![
+            thread_id,
+            cursor.created_at,
+            cursor.rank,
+            cursor.id,
+            limit,
+            rank
+        ],
+        |row| {
+            let mut entry = empty_entry(
+                row.get(0)?,
+                ThreadTimelineEntryKindDto::Approval,
+                row.get(1)?,
+                row.get(6)?,
+            );
+            entry.action_type = Some(row.get(2)?);
+            entry.summary = Some(row.get(3)?);
+            entry.decision = row.get(4)?;
+            entry.wait_ms = row.get(5)?;
+            Ok(entry)
+        },
+    )?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load timeline approvals")
+}
+
+/// Aggregates file write/edit/delete actions per turn (assistant message).
+fn load_file_change_entries(
+    conn: &Connection,
+    thread_id: &str,
+    cursor: &TimelineCursor<'_>,
+    limit: i64,
+) -> anyhow::Result<Vec<ThreadTimelineEntryDto>> {
+    let rank = ThreadTimelineEntryKindDto::FileChanges.rank();
+    let mut stmt = conn
+        .prepare(
+            "SELECT message_id, COUNT(*), MAX(created_at) AS last_created_at
+             FROM actions
+             WHERE thread_id = ?1
+               AND message_id IS NOT NULL
+               AND action_type IN ('file_write', 'file_edit', 'file_delete')
+             GROUP BY message_id
+             HAVING ?2 IS NULL OR (last_created_at, ?6, message_id) < (?2, ?3, ?4)
+             ORDER BY last_created_at DESC, message_id DESC
+             LIMIT ?5",
+        )
+        .context("failed to prepare timeline file change query")?;
+    let rows = stmt.query_map(
+        params![
+            thread_id,
+            cursor.created_at,
+            cursor.rank,
+            cursor.id,
+            limit,
+            rank
+        ],
+        |row| {
+            let message_id: String = row.get(0)?;
+            let mut entry = empty_entry(
+                message_id.clone(),
+                ThreadTimelineEntryKindDto::FileChanges,
+                Some(message_id),
+                row.get(2)?,
+            );
+            entry.file_change_count = Some(row.get(1)?);
+            Ok(entry)
+        },
+    )?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load timeline file changes")
+}
+
+fn load_git_operation_entries(
+    conn: &Connection,
+    thread_id: &str,
+    cursor: &TimelineCursor<'_>,
+    limit: i64,
+) -> anyhow::Result<Vec<ThreadTimelineEntryDto>> {
+    let rank = ThreadTimelineEntryKindDto::GitOperation.rank();
+    let mut stmt = conn
+        .prepare(
+            "SELECT CAST(id AS TEXT) AS entry_id, operation, target, outcome, created_at
+             FROM git_operation_logs
+             WHERE thread_id = ?1
+               AND (?2 IS NULL OR (created_at, ?6, entry_id) < (?2, ?3, ?4))
+             ORDER BY created_at DESC, entry_id DESC
+             LIMIT ?5",
+        )
+        .context("failed to prepare timeline git operation query")?;
+    let rows = stmt.query_map(
+        params![
+            thread_id,
+            cursor.created_at,
+            cursor.rank,
+            cursor.id,
+            limit,
+            rank
+        ],
+        |row| {
+            let outcome: String = row.get(3)?;
+            let mut entry = empty_entry(
+                row.get(0)?,
+                ThreadTimelineEntryKindDto::GitOperation,
+                None,
+                row.get(4)?,
+            );
+            entry.action_type = Some(row.get(1)?);
+            entry.summary = Some(row.get(2)?);
+            entry.success = Some(outcome == "completed");
+            entry.outcome = Some(outcome);
+            Ok(entry)
+        },
+    )?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .context("failed to load timeline git operations")
+}
+
+fn first_line_excerpt(content: &str) -> Option<String> {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    if line.chars().count() <= TIMELINE_EXCERPT_MAX_CHARS {
+        return Some(line.to_string());
+    }
+    let mut excerpt: String = line.chars().take(TIMELINE_EXCERPT_MAX_CHARS).collect();
+    excerpt.push('…');
+    Some(excerpt)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        db::{
+            actions,
+            git_operations::{self, GitOperationRecord},
+            messages, threads, workspaces, PoolLimits,
+        },
+        engines::events::{ActionResult, ActionType},
+        models::MessageStatusDto,
+    };
+
+    use super::*;
+
+    fn test_db() -> Database {
+        let path = std::env::temp_dir().join(format!("panes-views-{}.db", Uuid::new_v4()));
+        let db = Database::unmigrated(path, PoolLimits::default());
+        db.run_migrations().expect("failed to run test migrations");
+        db
+    }
+
+    fn test_thread(db: &Database) -> String {
+        let root = std::env::temp_dir().join(format!("panes-workspace-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("failed to create temp workspace root");
+        let workspace =
+            workspaces::upsert_workspace(db, root.to_string_lossy().as_ref(), Some(1)).unwrap();
+        threads::create_thread(db, &workspace.id, None, "codex", "gpt-5.3-codex", "test")
+            .unwrap()
+            .id
+    }
+
+    fn set_created_at(db: &Database, table: &str, id: &str, created_at: &str) {
+        let conn = db.connect().unwrap();
+        conn.execute(
+            &format!("UPDATE {table} SET created_at = ?1 WHERE id = ?2"),
+            params![created_at, id],
+        )
+        .unwrap();
+    }
+
+    /// One turn: user prompt, assistant reply with a thinking block ahead of
+    /// the text, a command, an answered approval, and two file edits.
+    fn seed_turn(db: &Database, thread_id: &str) -> (String, String) {
+        let user = messages::insert_user_message(
+            db,
+            thread_id,
+            "\n  Fix the flaky test\nand explain why",
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        set_created_at(db, "messages", &user.id, "2026-01-01 10:00:00");
+
+        let assistant =
+            messages::insert_assistant_placeholder(db, thread_id, None, None, None).unwrap();
+        let blocks = json!([
+            { "type": "thinking", "content": "internal planning" },
+            { "type": "text", "content": "The race is in setup.\nDetails follow." }
+        ]);
+        messages::update_assistant_blocks_json_on(
+            &db.connect().unwrap(),
+            &assistant.id,
+            &blocks.to_string(),
+            MessageStatusDto::Completed,
+            None,
+        )
+        .unwrap();
+        set_created_at(db, "messages", &assistant.id, "2026-01-01 10:00:01");
+
+        actions::insert_action_started_on(
+            &db.connect().unwrap(),
+            "action-cmd",
+            thread_id,
+            &assistant.id,
+            None,
+            &ActionType::Command,
+            "cargo test",
+            &json!({}),
+        )
+        .unwrap();
+        actions::update_action_completed_on(
+            &db.connect().unwrap(),
+            "action-cmd",
+            &ActionResult {
+                success: false,
+                output: None,
+                error: Some("failed".to_string()),
+                diff: None,
+                duration_ms: 1200,
+            },
+        )
+        .unwrap();
+        set_created_at(db, "actions", "action-cmd", "2026-01-01 10:00:02");
+
+        actions::insert_approval_on(
+            &db.connect().unwrap(),
+            "approval-1",
+            thread_id,
+            &assistant.id,
+            &ActionType::Command,
+            "Run migration",
+            &json!({}),
+        )
+        .unwrap();
+        actions::answer_approval(db, "approval-1", "accept").unwrap();
+        {
+            let conn = db.connect().unwrap();
+            conn.execute(
+                "UPDATE approvals
+                 SET created_at = '2026-01-01 10:00:03', answered_at = '2026-01-01 10:00:08'
+                 WHERE id = 'approval-1'",
+                [],
+            )
+            .unwrap();
+        }
+
+        for (id, at) in [
+            ("edit-1", "2026-01-01 10:00:04"),
+            ("edit-2", "2026-01-01 10:00:05"),
+        ] {
+            actions::insert_action_started_on(
+                &db.connect().unwrap(),
+                id,
+                thread_id,
+                &assistant.id,
+                None,
+                &ActionType::FileEdit,
+                "edit src/lib.rs",
+                &json!({}),
+            )
+            .unwrap();
+            set_created_at(db, "actions", id, at);
+        }
+
+        (user.id, assistant.id)
+    }
+
+    #[test]
+    fn timeline_orders_entries_across_entity_types() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let (user_id, assistant_id) = seed_turn(&db, &thread_id);
+
+        let timeline = get_thread_timeline(&db, &thread_id, None, 50).unwrap();
+        let kinds: Vec<_> = timeline
+            .entries
+            .iter()
+            .map(|entry| (entry.kind, entry.id.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ThreadTimelineEntryKindDto::UserMessage, user_id.as_str()),
+                (
+                    ThreadTimelineEntryKindDto::AssistantSummary,
+                    assistant_id.as_str()
+                ),
+                (ThreadTimelineEntryKindDto::Action, "action-cmd"),
+                (ThreadTimelineEntryKindDto::Approval, "approval-1"),
+                (ThreadTimelineEntryKindDto::Action, "edit-1"),
+                (ThreadTimelineEntryKindDto::Action, "edit-2"),
+                (
+                    ThreadTimelineEntryKindDto::FileChanges,
+                    assistant_id.as_str()
+                ),
+            ]
+        );
+        assert!(timeline.next_cursor.is_none());
+
+        let command = &timeline.entries[2];
+        assert_eq!(command.success, Some(false));
+        assert_eq!(command.duration_ms, Some(1200));
+        assert_eq!(command.message_id.as_deref(), Some(assistant_id.as_str()));
+
+        let approval = &timeline.entries[3];
+        assert_eq!(approval.decision.as_deref(), Some("accept"));
+        assert_eq!(approval.wait_ms, Some(5000));
+
+        assert_eq!(timeline.entries[6].file_change_count, Some(2));
+    }
+
+    #[test]
+    fn timeline_includes_git_operations_run_from_the_thread() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        let other_thread_id = test_thread(&db);
+        seed_turn(&db, &thread_id);
+
+        let record = |thread_id: Option<&str>, target: &str, outcome: &'static str| {
+            git_operations::record_git_operation(
+                &db,
+                &GitOperationRecord {
+                    repo_path: "/tmp/repo".to_string(),
+                    thread_id: thread_id.map(str::to_string),
+                    operation: "force_push",
+                    target: target.to_string(),
+                    force: false,
+                    force_with_lease: true,
+                    outcome,
+                    detail: None,
+                },
+            )
+            .unwrap();
+        };
+        record(Some(&thread_id), "origin/feature", "refused");
+        record(Some(&other_thread_id), "origin/other", "completed");
+        record(None, "origin/panel", "completed");
+        let conn = db.connect().unwrap();
+        conn.execute(
+            "UPDATE git_operation_logs SET created_at = '2026-01-01 10:00:09'",
+            [],
+        )
+        .unwrap();
+
+        let timeline = get_thread_timeline(&db, &thread_id, None, 50).unwrap();
+        let git_entries: Vec<_> = timeline
+            .entries
+            .iter()
+            .filter(|entry| entry.kind == ThreadTimelineEntryKindDto::GitOperation)
+            .collect();
+        assert_eq!(git_entries.len(), 1);
+        let entry = git_entries[0];
+        assert_eq!(entry.action_type.as_deref(), Some("force_push"));
+        assert_eq!(entry.summary.as_deref(), Some("origin/feature"));
+        assert_eq!(entry.outcome.as_deref(), Some("refused"));
+        assert_eq!(entry.success, Some(false));
+        assert!(entry.message_id.is_none());
+        assert_eq!(
+            timeline.entries.last().map(|entry| entry.kind),
+            Some(ThreadTimelineEntryKindDto::GitOperation)
+        );
+    }
+
+    #[test]
+    fn timeline_excerpts_come_from_first_text_line() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        seed_turn(&db, &thread_id);
+
+        let timeline = get_thread_timeline(&db, &thread_id, None, 50).unwrap();
+        assert_eq!(
+            timeline.entries[0].excerpt.as_deref(),
+            Some("Fix the flaky test")
+        );
+        // The thinking block precedes the text block but must not leak in.
+        assert_eq!(
+            timeline.entries[1].excerpt.as_deref(),
+            Some("The race is in setup.")
+        );
+    }
+
+    #[test]
+    fn timeline_pages_with_keyset_cursor() {
+        let db = test_db();
+        let thread_id = test_thread(&db);
+        seed_turn(&db, &thread_id);
+
+        let full = get_thread_timeline(&db, &thread_id, None, 50).unwrap();
+        let newest = get_thread_timeline(&db, &thread_id, None, 3).unwrap();
+        let cursor = newest.next_cursor.clone().expect("more entries remain");
+        let older = get_thread_timeline(&db, &thread_id, Some(&cursor), 10).unwrap();
+        assert!(older.next_cursor.is_none());
+
+        let stitched: Vec<_> = older
+            .entries
+            .iter()
+            .chain(newest.entries.iter())
+            .map(|entry| (entry.kind, entry.id.clone()))
+            .collect();
+        let expected: Vec<_> = full
+            .entries
+            .iter()
+            .map(|entry| (entry.kind, entry.id.clone()))
+            .collect();
+        assert_eq!(stitched, expected);
+    }
+}
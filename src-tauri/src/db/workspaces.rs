@@ -415,26 +415,17 @@ fn map_workspace_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<WorkspaceDto>
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        fs,
-        sync::{Arc, Mutex},
-    };
+    use std::fs;
 
     use uuid::Uuid;
 
-    use crate::db::{ConnectionPool, SQLITE_POOL_MAX_IDLE};
+    use crate::db::PoolLimits;
 
     use super::*;
 
     fn test_db() -> Database {
         let path = std::env::temp_dir().join(format!("panes-workspaces-{}.db", Uuid::new_v4()));
-        let db = Database {
-            path,
-            pool: Arc::new(ConnectionPool {
-                idle: Mutex::new(Vec::new()),
-                max_idle: SQLITE_POOL_MAX_IDLE,
-            }),
-        };
+        let db = Database::unmigrated(path, PoolLimits::default());
         db.run_migrations().expect("failed to run test migrations");
         db
     }
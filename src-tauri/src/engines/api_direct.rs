@@ -1 +1,876 @@
-// Reserved for future API-direct engine mode.
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use reqwest::{header::AUTHORIZATION, Url};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::{config::app_config::ApiDirectConfig, models::EngineHealthDto};
+
+use super::{
+    trim_action_output_delta_content, ActionResult, ActionType, ApprovalRequestRoute, Engine,
+    EngineEvent, EngineThread, ModelInfo, SandboxPolicy, ThreadScope, TokenUsage, TurnAttachment,
+    TurnCompletionStatus, TurnInput,
+};
+
+const API_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const API_MODELS_TIMEOUT: Duration = Duration::from_secs(4);
+const API_ERROR_BODY_MAX_CHARS: usize = 2_000;
+/// Text attachments are inlined into the prompt up to this size.
+const API_TEXT_ATTACHMENT_MAX_BYTES: u64 = 256 * 1024;
+const API_IMAGE_ATTACHMENT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const TOOL_CALLS_NOT_RUN: &str = "Panes does not run tools for the API engine";
+
+// ── SSE ────────────────────────────────────────────────────────────────
+
+/// Splits a `text/event-stream` body into the `data` payload of each event.
+#[derive(Default)]
+struct SseDecoder {
+    pending: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(newline) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line = self.pending.drain(..=newline).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(data) = line.strip_prefix("data:") {
+                self.data
+                    .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+        }
+        events
+    }
+
+    /// Returns the event left over when the body ends without a blank line.
+    fn finish(&mut self) -> Option<String> {
+        let tail = std::mem::take(&mut self.pending);
+        let tail = String::from_utf8_lossy(&tail);
+        if let Some(data) = tail.trim_end().strip_prefix("data:") {
+            self.data.push(data.trim_start().to_string());
+        }
+        (!self.data.is_empty()).then(|| std::mem::take(&mut self.data).join("\n"))
+    }
+}
+
+// ── Stream mapping ─────────────────────────────────────────────────────
+
+struct PendingToolCall {
+    action_id: String,
+    name: String,
+    arguments: String,
+    started_at: Instant,
+}
+
+/// Turns `chat.completion.chunk` payloads into `EngineEvent`s for one turn
+/// and keeps the reply text for the thread's history.
+#[derive(Default)]
+struct ChatStreamMapper {
+    text: String,
+    tool_calls: Vec<(u64, PendingToolCall)>,
+    usage: Option<TokenUsage>,
+    done: bool,
+}
+
+impl ChatStreamMapper {
+    fn map_data(&mut self, data: &str) -> Vec<EngineEvent> {
+        if data.trim() == "[DONE]" {
+            self.done = true;
+            return Vec::new();
+        }
+        let chunk = match serde_json::from_str::<Value>(data) {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                log::debug!("skipping unparseable chat completion chunk ({error}): {data}");
+                return Vec::new();
+            }
+        };
+
+        if let Some(error) = chunk.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            return vec![EngineEvent::Error {
+                message,
+                recoverable: false,
+            }];
+        }
+        if let Some(usage) = chunk.get("usage").filter(|usage| usage.is_object()) {
+            self.usage = Some(parse_usage(usage));
+        }
+
+        let mut events = Vec::new();
+        let Some(delta) = chunk.pointer("/choices/0/delta") else {
+            return events;
+        };
+        if let Some(thinking) = ["reasoning_content", "reasoning"]
+            .iter()
+            .find_map(|key| delta.get(*key).and_then(Value::as_str))
+            .filter(|thinking| !thinking.is_empty())
+        {
+            events.push(EngineEvent::ThinkingDelta {
+                content: thinking.to_string(),
+            });
+        }
+        if let Some(content) = delta
+            .get("content")
+            .and_then(Value::as_str)
+            .filter(|content| !content.is_empty())
+        {
+            self.text.push_str(content);
+            events.push(EngineEvent::TextDelta {
+                content: content.to_string(),
+            });
+        }
+        for call in delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            events.extend(self.map_tool_call_delta(call));
+        }
+        events
+    }
+
+    /// Tool calls stream in pieces keyed by `index`: the first piece carries
+    /// the id and name, later ones append to the arguments.
+    fn map_tool_call_delta(&mut self, call: &Value) -> Option<EngineEvent> {
+        let index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+        let name = call.pointer("/function/name").and_then(Value::as_str);
+        let arguments = call.pointer("/function/arguments").and_then(Value::as_str);
+
+        if let Some((_, pending)) = self.tool_calls.iter_mut().find(|(i, _)| *i == index) {
+            if let Some(name) = name {
+                pending.name.push_str(name);
+            }
+            if let Some(arguments) = arguments {
+                pending.arguments.push_str(arguments);
+            }
+            return None;
+        }
+
+        let engine_action_id = call.get("id").and_then(Value::as_str).map(str::to_string);
+        let action_id = engine_action_id
+            .clone()
+            .unwrap_or_else(|| format!("api-tool-{}", Uuid::new_v4()));
+        let name = name.unwrap_or_default().to_string();
+        self.tool_calls.push((
+            index,
+            PendingToolCall {
+                action_id: action_id.clone(),
+                name: name.clone(),
+                arguments: arguments.unwrap_or_default().to_string(),
+                started_at: Instant::now(),
+            },
+        ));
+        Some(EngineEvent::ActionStarted {
+            action_id,
+            engine_action_id,
+            action_type: ActionType::Other,
+            summary: if name.is_empty() {
+                "Tool call".to_string()
+            } else {
+                name.clone()
+            },
+            details: json!({ "toolName": name }),
+        })
+    }
+
+    /// Settles the turn. Tool calls the model asked for are reported as not
+    /// run, with the arguments it sent.
+    fn finish(&mut self, status: TurnCompletionStatus) -> Vec<EngineEvent> {
+        let mut events = self
+            .tool_calls
+            .drain(..)
+            .map(|(_, call)| EngineEvent::ActionCompleted {
+                action_id: call.action_id,
+                result: ActionResult {
+                    success: false,
+                    output: (!call.arguments.is_empty())
+                        .then(|| trim_action_output_delta_content(&call.arguments)),
+                    error: Some(TOOL_CALLS_NOT_RUN.to_string()),
+                    diff: None,
+                    duration_ms: call.started_at.elapsed().as_millis() as u64,
+                },
+            })
+            .collect::<Vec<_>>();
+        events.push(EngineEvent::TurnCompleted {
+            token_usage: self.usage.take(),
+            status,
+        });
+        events
+    }
+}
+
+fn parse_usage(usage: &Value) -> TokenUsage {
+    let count = |pointer: &str| usage.pointer(pointer).and_then(Value::as_u64);
+    TokenUsage {
+        input: count("/prompt_tokens").unwrap_or(0),
+        output: count("/completion_tokens").unwrap_or(0),
+        reasoning: count("/completion_tokens_details/reasoning_tokens"),
+        cache_read: count("/prompt_tokens_details/cached_tokens"),
+        cache_write: None,
+        cost_usd: None,
+    }
+}
+
+// ── Endpoint ───────────────────────────────────────────────────────────
+
+fn endpoint_url(base_url: &str, path: &str) -> anyhow::Result<Url> {
+    let base = base_url.trim().trim_end_matches('/');
+    Url::parse(&format!("{base}/{path}"))
+        .with_context(|| format!("invalid API engine base URL `{base_url}`"))
+}
+
+fn is_loopback_url(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|address| address.is_loopback())
+}
+
+fn api_model_info(id: &str, is_default: bool) -> ModelInfo {
+    ModelInfo {
+        id: id.to_string(),
+        display_name: id.to_string(),
+        description: "Served by the configured OpenAI-compatible endpoint".to_string(),
+        hidden: false,
+        is_default,
+        upgrade: None,
+        availability_nux: None,
+        upgrade_info: None,
+        input_modalities: vec!["text".to_string(), "image".to_string()],
+        attachment_modalities: vec!["text".to_string(), "image".to_string()],
+        limits: None,
+        supports_personality: false,
+        default_reasoning_effort: "medium".to_string(),
+        supported_reasoning_efforts: Vec::new(),
+    }
+}
+
+fn model_infos(ids: impl IntoIterator<Item = String>) -> Vec<ModelInfo> {
+    ids.into_iter()
+        .enumerate()
+        .map(|(index, id)| api_model_info(&id, index == 0))
+        .collect()
+}
+
+/// User message content: images become `image_url` parts, other files are
+/// inlined as text. Files that cannot be sent are reported back.
+async fn user_content(message: &str, attachments: &[TurnAttachment]) -> (Value, Vec<String>) {
+    if attachments.is_empty() {
+        return (Value::String(message.to_string()), Vec::new());
+    }
+
+    let mut parts = vec![json!({ "type": "text", "text": message })];
+    let mut skipped = Vec::new();
+    for attachment in attachments {
+        let is_image = attachment
+            .mime_type
+            .as_deref()
+            .is_some_and(|mime| mime.starts_with("image/"));
+        let limit = if is_image {
+            API_IMAGE_ATTACHMENT_MAX_BYTES
+        } else {
+            API_TEXT_ATTACHMENT_MAX_BYTES
+        };
+        if attachment.size_bytes > limit {
+            skipped.push(format!(
+                "{} is larger than {} KB",
+                attachment.file_name,
+                limit / 1024
+            ));
+            continue;
+        }
+        let bytes = match tokio::fs::read(&attachment.file_path).await {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                skipped.push(format!(
+                    "{} could not be read: {error}",
+                    attachment.file_name
+                ));
+                continue;
+            }
+        };
+        if is_image {
+            let mime = attachment.mime_type.as_deref().unwrap_or("image/png");
+            parts.push(json!({
+                "type": "image_url",
+                "image_url": { "url": format!("data:{mime};base64,{}", BASE64.encode(bytes)) },
+            }));
+        } else {
+            match String::from_utf8(bytes) {
+                Ok(text) => parts.push(json!({
+                    "type": "text",
+                    "text": format!("<file name=\"{}\">\n{text}\n</file>", attachment.file_name),
+                })),
+                Err(_) => skipped.push(format!("{} is not a text file", attachment.file_name)),
+            }
+        }
+    }
+    (Value::Array(parts), skipped)
+}
+
+// ── Engine ─────────────────────────────────────────────────────────────
+
+#[derive(Clone)]
+struct ApiThread {
+    model_id: String,
+    /// Conversation sent with every request; the endpoint keeps no state.
+    messages: Vec<Value>,
+}
+
+#[derive(Default)]
+struct ApiDirectState {
+    threads: HashMap<String, ApiThread>,
+    active_turns: HashMap<String, CancellationToken>,
+    runtime_models: Option<Vec<ModelInfo>>,
+}
+
+/// Talks to an OpenAI-compatible `/chat/completions` endpoint, e.g. Ollama.
+/// The endpoint is stateless, so each thread keeps its conversation in
+/// memory; after a restart a thread continues without its earlier turns.
+pub struct ApiDirectEngine {
+    config: RwLock<ApiDirectConfig>,
+    http: reqwest::Client,
+    state: Arc<Mutex<ApiDirectState>>,
+}
+
+pub struct ApiDirectHealthReport {
+    pub available: bool,
+    pub details: Option<String>,
+    pub warnings: Vec<String>,
+    pub checks: Vec<String>,
+    pub fixes: Vec<String>,
+}
+
+impl Default for ApiDirectEngine {
+    fn default() -> Self {
+        Self {
+            config: RwLock::new(ApiDirectConfig::default()),
+            http: reqwest::Client::builder()
+                .connect_timeout(API_CONNECT_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            state: Arc::new(Mutex::new(ApiDirectState::default())),
+        }
+    }
+}
+
+impl ApiDirectEngine {
+    pub fn set_config(&self, config: &ApiDirectConfig) {
+        *self
+            .config
+            .write()
+            .unwrap_or_else(|poison| poison.into_inner()) = config.clone();
+    }
+
+    fn config(&self) -> ApiDirectConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .clone()
+    }
+
+    fn request(&self, method: reqwest::Method, url: Url) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, url);
+        let token = self
+            .config()
+            .api_key_env
+            .and_then(|name| std::env::var(name).ok())
+            .filter(|token| !token.trim().is_empty());
+        match token {
+            Some(token) => request.header(AUTHORIZATION, format!("Bearer {}", token.trim())),
+            None => request,
+        }
+    }
+
+    async fn fetch_model_ids(&self) -> anyhow::Result<Vec<String>> {
+        let url = endpoint_url(&self.config().base_url, "models")?;
+        let response = self
+            .request(reqwest::Method::GET, url.clone())
+            .timeout(API_MODELS_TIMEOUT)
+            .send()
+            .await
+            .with_context(|| format!("failed to reach {url}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("{url} answered {status}");
+        }
+        let body = response
+            .json::<Value>()
+            .await
+            .context("model list is not JSON")?;
+        Ok(body
+            .get("data")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model.get("id").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect())
+    }
+
+    pub async fn list_models_runtime(&self) -> Vec<ModelInfo> {
+        match self.fetch_model_ids().await {
+            Ok(ids) if !ids.is_empty() => {
+                let models = model_infos(ids);
+                self.state.lock().await.runtime_models = Some(models.clone());
+                models
+            }
+            Ok(_) => self.runtime_model_fallback().await,
+            Err(error) => {
+                log::debug!("failed to list API engine models: {error:#}");
+                self.runtime_model_fallback().await
+            }
+        }
+    }
+
+    pub async fn runtime_model_fallback(&self) -> Vec<ModelInfo> {
+        match self.state.lock().await.runtime_models.clone() {
+            Some(models) => models,
+            None => self.models(),
+        }
+    }
+
+    pub async fn health_report(&self) -> ApiDirectHealthReport {
+        let base_url = self.config().base_url;
+        let checks = vec![format!("GET {}/models", base_url.trim_end_matches('/'))];
+        match self.fetch_model_ids().await {
+            Ok(ids) => {
+                let mut warnings = Vec::new();
+                if ids.is_empty() {
+                    warnings.push("The endpoint reported no models.".to_string());
+                }
+                ApiDirectHealthReport {
+                    available: true,
+                    details: Some(format!("{} models at {base_url}", ids.len())),
+                    warnings,
+                    checks,
+                    fixes: Vec::new(),
+                }
+            }
+            Err(error) => ApiDirectHealthReport {
+                available: false,
+                details: Some(format!("{error:#}")),
+                warnings: Vec::new(),
+                checks,
+                fixes: vec![
+                    "Start the server (for Ollama: `ollama serve`) or set `api_direct.base_url` in the Panes config.".to_string(),
+                ],
+            },
+        }
+    }
+
+    async fn finish_turn(&self, engine_thread_id: &str, reply: Option<(Value, String)>) {
+        let mut state = self.state.lock().await;
+        state.active_turns.remove(engine_thread_id);
+        let Some((user_message, text)) = reply else {
+            return;
+        };
+        if let Some(thread) = state.threads.get_mut(engine_thread_id) {
+            thread.messages.push(user_message);
+            thread
+                .messages
+                .push(json!({ "role": "assistant", "content": text }));
+        }
+    }
+
+    async fn stream_turn(
+        &self,
+        thread: &ApiThread,
+        user_message: &Value,
+        event_tx: &mpsc::Sender<EngineEvent>,
+        cancellation: &CancellationToken,
+        mapper: &mut ChatStreamMapper,
+    ) -> anyhow::Result<bool> {
+        let url = endpoint_url(&self.config().base_url, "chat/completions")?;
+        let mut messages = thread.messages.clone();
+        messages.push(user_message.clone());
+        let body = json!({
+            "model": thread.model_id,
+            "messages": messages,
+            "stream": true,
+            "stream_options": { "include_usage": true },
+        });
+
+        let mut response = tokio::select! {
+            _ = cancellation.cancelled() => return Ok(false),
+            response = self.request(reqwest::Method::POST, url.clone()).json(&body).send() => {
+                response.with_context(|| format!("failed to reach {url}"))?
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let body = body
+                .trim()
+                .chars()
+                .take(API_ERROR_BODY_MAX_CHARS)
+                .collect::<String>();
+            anyhow::bail!("{url} answered {status}: {body}");
+        }
+
+        let mut decoder = SseDecoder::default();
+        loop {
+            let chunk = tokio::select! {
+                _ = cancellation.cancelled() => return Ok(false),
+                chunk = response.chunk() => chunk.context("chat completion stream failed")?,
+            };
+            let payloads = match &chunk {
+                Some(bytes) => decoder.push(bytes),
+                None => decoder.finish().into_iter().collect(),
+            };
+            for payload in payloads {
+                for event in mapper.map_data(&payload) {
+                    event_tx.send(event).await.ok();
+                }
+            }
+            if chunk.is_none() || mapper.done {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Engine for ApiDirectEngine {
+    fn id(&self) -> &str {
+        "api"
+    }
+
+    fn name(&self) -> &str {
+        "Local API"
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        model_infos(self.config().models)
+    }
+
+    async fn is_available(&self) -> bool {
+        self.fetch_model_ids().await.is_ok()
+    }
+
+    /// Local models run no tools, so there is nothing for writable roots to
+    /// confine. Network access still matters: without it only an endpoint on
+    /// this machine may be used.
+    async fn start_thread(
+        &self,
+        _scope: ThreadScope,
+        resume_engine_thread_id: Option<&str>,
+        model: &str,
+        sandbox: SandboxPolicy,
+    ) -> Result<EngineThread, anyhow::Error> {
+        let url = endpoint_url(&self.config().base_url, "chat/completions")?;
+        if !sandbox.allow_network && !is_loopback_url(&url) {
+            anyhow::bail!(
+                "the API engine endpoint {url} is not on this machine and this thread does not allow network access"
+            );
+        }
+
+        let mut state = self.state.lock().await;
+        let engine_thread_id = resume_engine_thread_id
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("api-{}", Uuid::new_v4()));
+        state
+            .threads
+            .entry(engine_thread_id.clone())
+            .and_modify(|thread| thread.model_id = model.to_string())
+            .or_insert_with(|| ApiThread {
+                model_id: model.to_string(),
+                messages: Vec::new(),
+            });
+
+        Ok(EngineThread { engine_thread_id })
+    }
+
+    async fn send_message(
+        &self,
+        engine_thread_id: &str,
+        input: TurnInput,
+        event_tx: mpsc::Sender<EngineEvent>,
+        cancellation: CancellationToken,
+    ) -> Result<(), anyhow::Error> {
+        let turn_cancellation = cancellation.child_token();
+        let thread = {
+            let mut state = self.state.lock().await;
+            let thread = state
+                .threads
+                .get(engine_thread_id)
+                .cloned()
+                .context("no thread config found — was start_thread called?")?;
+            state
+                .active_turns
+                .insert(engine_thread_id.to_string(), turn_cancellation.clone());
+            thread
+        };
+
+        event_tx
+            .send(EngineEvent::TurnStarted {
+                client_turn_id: None,
+            })
+            .await
+            .ok();
+
+        let (content, skipped) = user_content(&input.message, &input.attachments).await;
+        for reason in skipped {
+            event_tx
+                .send(EngineEvent::Error {
+                    message: format!("Attachment skipped: {reason}"),
+                    recoverable: true,
+                })
+                .await
+                .ok();
+        }
+        let user_message = json!({ "role": "user", "content": content });
+
+        let mut mapper = ChatStreamMapper::default();
+        let outcome = self
+            .stream_turn(
+                &thread,
+                &user_message,
+                &event_tx,
+                &turn_cancellation,
+                &mut mapper,
+            )
+            .await;
+        let (status, reply) = match outcome {
+            Ok(true) => (
+                TurnCompletionStatus::Completed,
+                Some((user_message, mapper.text.clone())),
+            ),
+            Ok(false) => {
+                // An external cancellation settles the turn itself; an
+                // `interrupt` needs the completion reported here.
+                if cancellation.is_cancelled() {
+                    self.finish_turn(engine_thread_id, None).await;
+                    return Ok(());
+                }
+                (TurnCompletionStatus::Interrupted, None)
+            }
+            Err(error) => {
+                event_tx
+                    .send(EngineEvent::Error {
+                        message: format!("{error:#}"),
+                        recoverable: false,
+                    })
+                    .await
+                    .ok();
+                (TurnCompletionStatus::Failed, None)
+            }
+        };
+        for event in mapper.finish(status) {
+            event_tx.send(event).await.ok();
+        }
+        self.finish_turn(engine_thread_id, reply).await;
+        Ok(())
+    }
+
+    async fn steer_message(
+        &self,
+        _engine_thread_id: &str,
+        _input: TurnInput,
+    ) -> Result<(), anyhow::Error> {
+        anyhow::bail!("the API engine does not support mid-turn steering")
+    }
+
+    async fn respond_to_approval(
+        &self,
+        _approval_id: &str,
+        _response: serde_json::Value,
+        _route: Option<ApprovalRequestRoute>,
+    ) -> Result<(), anyhow::Error> {
+        anyhow::bail!("the API engine never requests approvals")
+    }
+
+    async fn interrupt(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
+        if let Some(token) = self.state.lock().await.active_turns.get(engine_thread_id) {
+            token.cancel();
+        }
+        Ok(())
+    }
+
+    async fn archive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
+        self.state.lock().await.threads.remove(engine_thread_id);
+        Ok(())
+    }
+
+    async fn unarchive_thread(&self, _engine_thread_id: &str) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn health(&self) -> EngineHealthDto {
+        let report = self.health_report().await;
+        EngineHealthDto {
+            id: "api".to_string(),
+            available: report.available,
+            version: None,
+            details: report.details,
+            warnings: report.warnings,
+            checks: report.checks,
+            fixes: report.fixes,
+            protocol_diagnostics: None,
+            timeouts: None,
+            auth_status: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(allow_network: bool) -> SandboxPolicy {
+        SandboxPolicy {
+            writable_roots: vec!["/elsewhere".to_string()],
+            allow_network,
+            approval_policy: None,
+            permission_profile: None,
+            approvals_reviewer: None,
+            reasoning_effort: None,
+            sandbox_mode: None,
+            service_tier: None,
+            personality: None,
+            output_schema: None,
+            opencode_agent: None,
+            executable_override: None,
+            custom_instructions: None,
+        }
+    }
+
+    fn scope() -> ThreadScope {
+        ThreadScope::Repo {
+            repo_path: "/repo".to_string(),
+        }
+    }
+
+    #[test]
+    fn sse_decoder_reassembles_events_split_across_chunks() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b"data: {\"a\":").is_empty());
+        assert_eq!(
+            decoder.push(b"1}\r\n\r\n: keep-alive\n\ndata: [DONE]\n"),
+            vec!["{\"a\":1}".to_string()]
+        );
+        assert_eq!(decoder.finish(), Some("[DONE]".to_string()));
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn maps_a_streamed_completion_into_engine_events() {
+        let mut mapper = ChatStreamMapper::default();
+        let chunks = [
+            r#"{"choices":[{"delta":{"role":"assistant","reasoning_content":"Thinking"}}]}"#,
+            r#"{"choices":[{"delta":{"content":"Hel"}}]}"#,
+            r#"{"choices":[{"delta":{"content":"lo"}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"read_file","arguments":"{\"pa"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"th\":\"a\"}"}}]}}]}"#,
+            r#"{"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":5,"prompt_tokens_details":{"cached_tokens":4}}}"#,
+            "[DONE]",
+        ];
+        let mut events = chunks
+            .iter()
+            .flat_map(|chunk| mapper.map_data(chunk))
+            .collect::<Vec<_>>();
+        assert!(mapper.done);
+        assert_eq!(mapper.text, "Hello");
+        events.extend(mapper.finish(TurnCompletionStatus::Completed));
+
+        assert!(
+            matches!(&events[0], EngineEvent::ThinkingDelta { content } if content == "Thinking")
+        );
+        assert!(matches!(&events[1], EngineEvent::TextDelta { content } if content == "Hel"));
+        assert!(matches!(&events[2], EngineEvent::TextDelta { content } if content == "lo"));
+        assert!(matches!(
+            &events[3],
+            EngineEvent::ActionStarted { action_id, summary, .. }
+                if action_id == "call_1" && summary == "read_file"
+        ));
+        match &events[4] {
+            EngineEvent::ActionCompleted { action_id, result } => {
+                assert_eq!(action_id, "call_1");
+                assert!(!result.success);
+                assert_eq!(result.output.as_deref(), Some(r#"{"path":"a"}"#));
+                assert_eq!(result.error.as_deref(), Some(TOOL_CALLS_NOT_RUN));
+            }
+            other => panic!("expected tool call completion, got {other:?}"),
+        }
+        match &events[5] {
+            EngineEvent::TurnCompleted {
+                token_usage: Some(usage),
+                status: TurnCompletionStatus::Completed,
+            } => {
+                assert_eq!((usage.input, usage.output), (12, 5));
+                assert_eq!(usage.cache_read, Some(4));
+            }
+            other => panic!("expected turn completion, got {other:?}"),
+        }
+        assert_eq!(events.len(), 6);
+    }
+
+    #[test]
+    fn error_chunks_fail_the_turn() {
+        let mut mapper = ChatStreamMapper::default();
+        let events = mapper.map_data(r#"{"error":{"message":"model not found"}}"#);
+        assert!(matches!(
+            &events[..],
+            [EngineEvent::Error { message, recoverable: false }] if message == "model not found"
+        ));
+    }
+
+    #[tokio::test]
+    async fn remote_endpoints_need_network_access_but_local_ones_do_not() {
+        let engine = ApiDirectEngine::default();
+        engine
+            .start_thread(scope(), None, "llama3.2", sandbox(false))
+            .await
+            .expect("localhost is allowed offline");
+
+        engine.set_config(&ApiDirectConfig {
+            base_url: "https://api.example.com/v1/".to_string(),
+            ..ApiDirectConfig::default()
+        });
+        let error = engine
+            .start_thread(scope(), None, "llama3.2", sandbox(false))
+            .await
+            .expect_err("remote endpoints need network access");
+        assert!(error.to_string().contains("does not allow network access"));
+
+        let thread = engine
+            .start_thread(scope(), Some("api-1"), "llama3.2", sandbox(true))
+            .await
+            .expect("network access allows remote endpoints");
+        assert_eq!(thread.engine_thread_id, "api-1");
+        assert_eq!(
+            endpoint_url("https://api.example.com/v1/", "models")
+                .unwrap()
+                .as_str(),
+            "https://api.example.com/v1/models"
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
     ffi::OsString,
     fs::{self, File},
@@ -21,15 +21,19 @@ use tokio::{
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::{process_utils, runtime_env};
+use crate::{
+    models::{EngineAuthStatusDto, EngineHealthDto},
+    process_utils, runtime_env,
+};
 
 use super::{
+    executable_resolution::{self, app_path_preview, ExecutableResolution},
     normalize_approval_response_for_engine, trim_action_output_delta_content, ActionResult,
-    ActionType, ApprovalRequestRoute, Engine, EngineEvent, EngineThread, ModelInfo, OutputStream,
-    ReasoningEffortOption, SandboxPolicy, ThreadScope, TurnCompletionStatus, TurnInput,
+    ActionType, ApprovalRequestRoute, Engine, EngineAuthStatus, EngineEvent, EngineThread,
+    ModelInfo, OutputStream, ReasoningEffortOption, SandboxPolicy, ThreadScope,
+    TurnCompletionStatus, TurnInput,
 };
 
-const LOGIN_SHELL_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
 const NODE_RUNTIME_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 const CLAUDE_MODEL_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(12);
 const CLAUDE_RUNTIME_INFO_TIMEOUT: Duration = Duration::from_secs(5);
@@ -569,6 +573,22 @@ struct ThreadConfig {
     sandbox: SandboxPolicy,
     agent_session_id: Option<String>,
     active_request_id: Option<String>,
+    /// Approvals the sidecar is blocked on for the active request. Cleared
+    /// whenever a request ends, since the sidecar declines whatever is still
+    /// outstanding when a query finishes or is cancelled.
+    pending_approval_ids: HashSet<String>,
+}
+
+impl ThreadConfig {
+    fn start_request(&mut self, request_id: String) {
+        self.active_request_id = Some(request_id);
+        self.pending_approval_ids.clear();
+    }
+
+    fn finish_request(&mut self) {
+        self.active_request_id = None;
+        self.pending_approval_ids.clear();
+    }
 }
 
 // ── Engine ─────────────────────────────────────────────────────────────
@@ -750,6 +770,11 @@ impl ClaudeSidecarEngine {
         }
     }
 
+    /// Whether a runtime model list has been discovered this session.
+    pub async fn has_runtime_models(&self) -> bool {
+        self.state.lock().await.runtime_model_cache.is_some()
+    }
+
     pub async fn runtime_model_fallback(&self) -> Vec<ModelInfo> {
         let state = self.state.lock().await;
         state
@@ -920,9 +945,13 @@ impl ClaudeSidecarEngine {
         let node_available = node_resolution.executable.is_some();
         let sidecar_exists = ClaudeTransport::resolve_sidecar_path(resource_dir.as_ref()).is_ok();
         let api_key_set = std::env::var("ANTHROPIC_API_KEY").is_ok();
-        let system_claude = resolve_system_claude_executable();
+        let claude_resolution = resolve_claude_executable().await;
+        let system_claude = claude_resolution
+            .as_ref()
+            .filter(|resolution| resolution.source != executable_resolution::SOURCE_LOGIN_SHELL)
+            .and_then(|resolution| resolution.executable.clone());
         let system_claude_version = match system_claude.as_deref() {
-            Some(executable) => probe_claude_version(executable).await,
+            Some(executable) => Some(probe_claude_version(executable).await),
             None => None,
         };
         let runtime_info = if node_available && sidecar_exists {
@@ -970,13 +999,15 @@ impl ClaudeSidecarEngine {
             warnings.push("Agent SDK sidecar script not found".to_string());
         }
 
-        if let Some(claude_path) = system_claude.as_ref() {
-            checks.push(format!(
-                "System Claude Code runtime resolved at `{}`",
-                claude_path.display()
-            ));
-        } else if sidecar_exists {
-            checks.push("Using the Claude Code runtime bundled with the Agent SDK".to_string());
+        if sidecar_exists || system_claude.is_some() {
+            let findings = system_claude_findings_for_platform(
+                runtime_env::platform_id(),
+                claude_resolution.as_ref(),
+                system_claude_version.as_ref(),
+            );
+            checks.extend(findings.checks);
+            warnings.extend(findings.warnings);
+            fixes.extend(findings.fixes);
         }
 
         if let Some(sdk_version) = runtime_info
@@ -986,18 +1017,44 @@ impl ClaudeSidecarEngine {
             checks.push(format!("Claude Agent SDK version: {sdk_version}"));
         }
 
-        if api_key_set {
+        let auth_status = if api_key_set {
             checks.push("ANTHROPIC_API_KEY is set".to_string());
+            EngineAuthStatus::SignedIn
         } else {
-            warnings.push(
-                "ANTHROPIC_API_KEY is not set. Claude may still work via Claude Code login or auth token."
-                    .to_string(),
-            );
-            fixes.push(
-                "Optional: set ANTHROPIC_API_KEY, or sign in with Claude Code so the SDK can use existing auth."
-                    .to_string(),
-            );
-        }
+            let auth_status = probe_claude_auth_status().await;
+            match auth_status {
+                EngineAuthStatus::SignedIn => {
+                    checks.push("Signed in with Claude Code".to_string());
+                }
+                EngineAuthStatus::NotRequired => {
+                    checks.push(
+                        "Claude Code is configured for Amazon Bedrock or Google Vertex AI"
+                            .to_string(),
+                    );
+                }
+                EngineAuthStatus::SignedOut => {
+                    warnings.push(
+                        "Claude is installed but not signed in. Turns will fail until you sign in or set ANTHROPIC_API_KEY."
+                            .to_string(),
+                    );
+                    fixes.push(
+                        "Run `claude` and use `/login` to sign in, or set ANTHROPIC_API_KEY."
+                            .to_string(),
+                    );
+                }
+                EngineAuthStatus::Unknown => {
+                    warnings.push(
+                        "ANTHROPIC_API_KEY is not set. Claude may still work via Claude Code login or auth token."
+                            .to_string(),
+                    );
+                    fixes.push(
+                        "Optional: set ANTHROPIC_API_KEY, or sign in with Claude Code so the SDK can use existing auth."
+                            .to_string(),
+                    );
+                }
+            }
+            auth_status
+        };
 
         let available = node_available && sidecar_exists;
         let runtime_source = runtime_info
@@ -1009,7 +1066,9 @@ impl ClaudeSidecarEngine {
                 "bundled"
             });
         let runtime_version = if runtime_source == "system" {
-            system_claude_version.clone()
+            system_claude_version
+                .as_ref()
+                .and_then(|version| version.as_ref().ok().cloned())
         } else {
             runtime_info
                 .as_ref()
@@ -1060,10 +1119,72 @@ impl ClaudeSidecarEngine {
             warnings,
             checks,
             fixes,
+            auth_status: Some(auth_status),
         }
     }
 }
 
+/// Claude Code records the signed-in account in `.claude.json`; the OAuth
+/// tokens live in `.credentials.json` except on macOS, which uses the
+/// Keychain. Both move under `CLAUDE_CONFIG_DIR` when it is set.
+async fn probe_claude_auth_status() -> EngineAuthStatus {
+    let env_is_set = |name: &str| env::var_os(name).is_some_and(|value| !value.is_empty());
+    if env_is_set("CLAUDE_CODE_USE_BEDROCK") || env_is_set("CLAUDE_CODE_USE_VERTEX") {
+        return EngineAuthStatus::NotRequired;
+    }
+    if env_is_set("ANTHROPIC_AUTH_TOKEN") || env_is_set("CLAUDE_CODE_OAUTH_TOKEN") {
+        return EngineAuthStatus::SignedIn;
+    }
+
+    let (config_path, credentials_path) = match env::var_os("CLAUDE_CONFIG_DIR")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+    {
+        Some(config_dir) => (
+            config_dir.join(".claude.json"),
+            config_dir.join(".credentials.json"),
+        ),
+        None => {
+            let Some(home) = runtime_env::home_dir() else {
+                return EngineAuthStatus::Unknown;
+            };
+            (
+                home.join(".claude.json"),
+                home.join(".claude").join(".credentials.json"),
+            )
+        }
+    };
+    let has_credentials = tokio::fs::try_exists(&credentials_path)
+        .await
+        .unwrap_or(false);
+    let config = match tokio::fs::read_to_string(&config_path).await {
+        Ok(config) => Some(config),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(_) => return EngineAuthStatus::Unknown,
+    };
+    claude_auth_status_from_files(config.as_deref(), has_credentials)
+}
+
+fn claude_auth_status_from_files(config: Option<&str>, has_credentials: bool) -> EngineAuthStatus {
+    if has_credentials {
+        return EngineAuthStatus::SignedIn;
+    }
+    let Some(config) = config else {
+        return EngineAuthStatus::SignedOut;
+    };
+    match serde_json::from_str::<serde_json::Value>(config) {
+        Ok(config)
+            if config
+                .get("oauthAccount")
+                .is_some_and(|account| !account.is_null()) =>
+        {
+            EngineAuthStatus::SignedIn
+        }
+        Ok(_) => EngineAuthStatus::SignedOut,
+        Err(_) => EngineAuthStatus::Unknown,
+    }
+}
+
 async fn resolve_node_executable() -> NodeExecutableResolution {
     let app_path = std::env::var("PATH").ok();
     let app_candidate = match runtime_env::resolve_executable("node") {
@@ -1079,7 +1200,7 @@ async fn resolve_node_executable() -> NodeExecutableResolution {
         return resolve_node_candidates(app_path, app_candidate, None);
     }
 
-    let login_shell_candidate = match detect_node_via_login_shell().await {
+    let login_shell_candidate = match executable_resolution::detect_via_login_shell("node").await {
         Some(path)
             if app_candidate
                 .as_ref()
@@ -1268,12 +1389,6 @@ fn node_health_checks_for_platform(platform: &str) -> Vec<String> {
     checks
 }
 
-fn app_path_preview(path: Option<&str>) -> String {
-    path.filter(|value| !value.trim().is_empty())
-        .unwrap_or("(empty)")
-        .to_string()
-}
-
 fn trim_sidecar_event_for_buffer(mut event: SidecarEvent) -> SidecarEvent {
     if let SidecarEvent::ActionOutputDelta { content, .. } = &mut event {
         *content = trim_action_output_delta_content(content);
@@ -1281,6 +1396,16 @@ fn trim_sidecar_event_for_buffer(mut event: SidecarEvent) -> SidecarEvent {
     event
 }
 
+fn pending_approval_thread(
+    threads: &HashMap<String, ThreadConfig>,
+    approval_id: &str,
+) -> Option<String> {
+    threads
+        .iter()
+        .find(|(_, config)| config.pending_approval_ids.contains(approval_id))
+        .map(|(engine_thread_id, _)| engine_thread_id.clone())
+}
+
 fn executable_augmented_path(executable: &Path) -> Option<OsString> {
     runtime_env::augmented_path_with_prepend(
         executable
@@ -1301,45 +1426,143 @@ fn paths_match(left: &Path, right: &Path) -> bool {
     }
 }
 
-fn resolve_system_claude_executable() -> Option<PathBuf> {
-    if std::env::var("PANES_CLAUDE_CODE_USE_BUNDLED")
+fn claude_code_bundled_forced() -> bool {
+    std::env::var("PANES_CLAUDE_CODE_USE_BUNDLED")
         .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
         .unwrap_or(false)
-    {
+}
+
+fn explicit_claude_executable() -> Option<PathBuf> {
+    std::env::var_os("PANES_CLAUDE_CODE_EXECUTABLE")
+        .map(PathBuf::from)
+        .filter(|explicit| runtime_env::is_executable_file(explicit))
+}
+
+/// Panes' terminal wrapper shims also answer to `claude`; they are not a
+/// runtime.
+fn is_panes_shim_dir(dir: &Path) -> bool {
+    paths_match(dir, &runtime_env::app_data_dir().join("bin"))
+}
+
+fn resolve_system_claude_executable() -> Option<PathBuf> {
+    if claude_code_bundled_forced() {
         return None;
     }
 
-    if let Some(explicit) = std::env::var_os("PANES_CLAUDE_CODE_EXECUTABLE") {
-        let explicit = PathBuf::from(explicit);
-        if runtime_env::is_executable_file(&explicit) {
-            return Some(explicit);
-        }
+    if let Some(explicit) = explicit_claude_executable() {
+        return Some(explicit);
     }
 
-    let shim_dir = runtime_env::app_data_dir().join("bin");
     let mut path_entries = runtime_env::augmented_path_entries();
-    path_entries.retain(|entry| !paths_match(entry, &shim_dir));
+    path_entries.retain(|entry| !is_panes_shim_dir(entry));
     let search_path = std::env::join_paths(path_entries).ok()?;
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     which::which_in("claude", Some(search_path), cwd).ok()
 }
 
-async fn probe_claude_version(executable: &Path) -> Option<String> {
+/// The health-report view of [`resolve_system_claude_executable`]: also
+/// asks the login shell, so a runtime Panes cannot see is still reported.
+/// `None` when the bundled runtime was explicitly requested.
+async fn resolve_claude_executable() -> Option<ExecutableResolution> {
+    if claude_code_bundled_forced() {
+        return None;
+    }
+
+    if let Some(explicit) = explicit_claude_executable() {
+        return Some(ExecutableResolution {
+            executable: Some(explicit),
+            source: "PANES_CLAUDE_CODE_EXECUTABLE",
+            app_path: std::env::var("PATH").ok(),
+            login_shell_executable: None,
+        });
+    }
+
+    Some(
+        executable_resolution::resolve_cli_executable_with("claude", |dir| !is_panes_shim_dir(dir))
+            .await,
+    )
+}
+
+async fn probe_claude_version(executable: &Path) -> Result<String, String> {
     let mut command = Command::new(executable);
     process_utils::configure_tokio_command(&mut command);
-    let output = timeout(Duration::from_secs(5), command.arg("--version").output())
-        .await
-        .ok()?
-        .ok()?;
-    if !output.status.success() {
-        return None;
+    runtime_env::apply_missing_login_shell_env(&mut command).await;
+    if let Some(augmented_path) = executable_augmented_path(executable) {
+        command.env("PATH", augmented_path);
     }
+    executable_resolution::probe_version(&mut command, &executable.to_string_lossy()).await
+}
 
-    String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(str::trim)
-        .find(|line| !line.is_empty())
-        .map(str::to_string)
+#[derive(Debug, Default)]
+struct SystemClaudeFindings {
+    checks: Vec<String>,
+    warnings: Vec<String>,
+    fixes: Vec<String>,
+}
+
+/// What the health report says about the system Claude Code runtime.
+/// `version` is the `claude --version` result for a runtime Panes will use.
+fn system_claude_findings_for_platform(
+    platform: &str,
+    resolution: Option<&ExecutableResolution>,
+    version: Option<&Result<String, String>>,
+) -> SystemClaudeFindings {
+    let mut findings = SystemClaudeFindings::default();
+    let Some(resolution) = resolution else {
+        findings.checks.push(
+            "PANES_CLAUDE_CODE_USE_BUNDLED is set; using the Claude Code runtime bundled with the Agent SDK"
+                .to_string(),
+        );
+        return findings;
+    };
+    let path_preview = app_path_preview(resolution.app_path.as_deref());
+
+    match (resolution.executable.as_ref(), resolution.source) {
+        (Some(shell_path), executable_resolution::SOURCE_LOGIN_SHELL) => {
+            findings.warnings.push(format!(
+                "Claude Code was found in your login shell at `{}`, but Panes does not see it in its app PATH, so the bundled runtime is used instead. App PATH: `{path_preview}`",
+                shell_path.display()
+            ));
+            findings
+                .fixes
+                .extend(executable_resolution::path_fix_commands_for_platform(
+                    platform,
+                    "claude",
+                    "@anthropic-ai/claude-code",
+                    &ExecutableResolution {
+                        executable: None,
+                        ..resolution.clone()
+                    },
+                    None,
+                ));
+        }
+        (Some(executable), source) => match version {
+            Some(Err(error)) => {
+                findings.warnings.push(format!(
+                    "Claude Code was found via {source} at `{}`, but Panes could not run it. App PATH: `{path_preview}`. Error: {error}",
+                    executable.display()
+                ));
+                findings
+                    .fixes
+                    .extend(executable_resolution::path_fix_commands_for_platform(
+                        platform,
+                        "claude",
+                        "@anthropic-ai/claude-code",
+                        resolution,
+                        Some(error),
+                    ));
+            }
+            _ => findings.checks.push(format!(
+                "System Claude Code runtime resolved via {source} at `{}`",
+                executable.display()
+            )),
+        },
+        (None, _) => findings.checks.push(
+            "Claude Code CLI not found; using the runtime bundled with the Agent SDK".to_string(),
+        ),
+    }
+
+    findings
 }
 
 fn effort_description(effort: &str) -> String {
@@ -1586,79 +1809,6 @@ fn map_claude_models(models: Vec<SidecarModelInfo>) -> Vec<ModelInfo> {
         .collect()
 }
 
-async fn detect_node_via_login_shell() -> Option<PathBuf> {
-    #[cfg(target_os = "windows")]
-    {
-        for powershell in runtime_env::windows_login_probe_shells() {
-            let mut cmd = Command::new(&powershell);
-            cmd.args([
-                "-NoLogo",
-                "-Command",
-                "(Get-Command node -ErrorAction SilentlyContinue | Select-Object -First 1).Source",
-            ]);
-            process_utils::configure_tokio_command(&mut cmd);
-
-            let Ok(Ok(output)) = timeout(Duration::from_secs(10), cmd.output()).await else {
-                continue;
-            };
-            if !output.status.success() {
-                continue;
-            }
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let Some(path) = runtime_env::parse_windows_single_path_output(&stdout) else {
-                continue;
-            };
-
-            let path = PathBuf::from(path);
-            if path.is_file() {
-                return Some(path);
-            }
-        }
-        None
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        for shell in runtime_env::login_probe_shells() {
-            let output = match timeout(
-                LOGIN_SHELL_PROBE_TIMEOUT,
-                Command::new(&shell)
-                    .args(runtime_env::login_probe_shell_args(
-                        &shell,
-                        "command -v node",
-                    ))
-                    .output(),
-            )
-            .await
-            {
-                Err(_) => {
-                    log::warn!(
-                        "timed out probing Node.js via login shell `{}`",
-                        shell.display()
-                    );
-                    continue;
-                }
-                Ok(Ok(output)) if output.status.success() => output,
-                _ => continue,
-            };
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(path) = stdout
-                .lines()
-                .map(str::trim)
-                .find(|line| line.starts_with('/'))
-                .map(PathBuf::from)
-                .filter(|path| runtime_env::is_executable_file(path))
-            {
-                return Some(path);
-            }
-        }
-
-        None
-    }
-}
-
 pub struct ClaudeHealthReport {
     pub available: bool,
     pub version: Option<String>,
@@ -1666,6 +1816,7 @@ pub struct ClaudeHealthReport {
     pub warnings: Vec<String>,
     pub checks: Vec<String>,
     pub fixes: Vec<String>,
+    pub auth_status: Option<EngineAuthStatus>,
 }
 
 #[async_trait]
@@ -1731,6 +1882,7 @@ impl Engine for ClaudeSidecarEngine {
             sandbox,
             agent_session_id: existing_session,
             active_request_id: None,
+            pending_approval_ids: HashSet::new(),
         };
 
         let mut state = self.state.lock().await;
@@ -1761,7 +1913,7 @@ impl Engine for ClaudeSidecarEngine {
         {
             let mut state = self.state.lock().await;
             if let Some(config) = state.threads.get_mut(engine_thread_id) {
-                config.active_request_id = Some(request_id.clone());
+                config.start_request(request_id.clone());
             }
         }
 
@@ -1803,6 +1955,7 @@ impl Engine for ClaudeSidecarEngine {
             "sandboxMode": thread_config.sandbox.sandbox_mode.clone(),
             "reasoningEffort": thread_config.sandbox.reasoning_effort.clone(),
             "planMode": plan_mode,
+            "appendSystemPrompt": thread_config.sandbox.custom_instructions.clone(),
         });
 
         if let Some(ref session_id) = thread_config.agent_session_id {
@@ -1834,7 +1987,7 @@ impl Engine for ClaudeSidecarEngine {
                     let _ = transport.send_command(&cancel_cmd).await;
                     let mut state = self.state.lock().await;
                     if let Some(config) = state.threads.get_mut(engine_thread_id) {
-                        config.active_request_id = None;
+                        config.finish_request();
                     }
                     return Ok(());
                 }
@@ -1950,6 +2103,12 @@ impl Engine for ClaudeSidecarEngine {
                                     details,
                                     ..
                                 } => {
+                                    {
+                                        let mut state = state_ref.lock().await;
+                                        if let Some(config) = state.threads.get_mut(&engine_thread_id_owned) {
+                                            config.pending_approval_ids.insert(approval_id.clone());
+                                        }
+                                    }
                                     event_tx
                                         .send(EngineEvent::ApprovalRequested {
                                             approval_id,
@@ -2011,7 +2170,7 @@ impl Engine for ClaudeSidecarEngine {
                                         .ok();
                                     let mut state = self.state.lock().await;
                                     if let Some(config) = state.threads.get_mut(engine_thread_id) {
-                                        config.active_request_id = None;
+                                        config.finish_request();
                                     }
                                     break;
                                 }
@@ -2124,7 +2283,7 @@ impl Engine for ClaudeSidecarEngine {
                                 .ok();
                             let mut state = state_ref.lock().await;
                             if let Some(config) = state.threads.get_mut(&engine_thread_id_owned) {
-                                config.active_request_id = None;
+                                config.finish_request();
                             }
                             break;
                         }
@@ -2149,7 +2308,7 @@ impl Engine for ClaudeSidecarEngine {
                             // Mark transport as dead so it restarts on next use
                             let mut state = state_ref.lock().await;
                             if let Some(config) = state.threads.get_mut(&engine_thread_id_owned) {
-                                config.active_request_id = None;
+                                config.finish_request();
                             }
                             state.transport = None;
                             break;
@@ -2161,7 +2320,7 @@ impl Engine for ClaudeSidecarEngine {
 
         let mut state = self.state.lock().await;
         if let Some(config) = state.threads.get_mut(engine_thread_id) {
-            config.active_request_id = None;
+            config.finish_request();
         }
 
         Ok(())
@@ -2183,23 +2342,35 @@ impl Engine for ClaudeSidecarEngine {
     ) -> Result<(), anyhow::Error> {
         let normalized_response = normalize_approval_response_for_engine("claude", response)
             .map_err(anyhow::Error::msg)?;
-        let state = self.state.lock().await;
-        if let Some(ref transport) = state.transport {
-            let approval_cmd = serde_json::json!({
-                "method": "approval_response",
-                "params": {
-                    "approvalId": approval_id,
-                    "response": normalized_response,
-                },
-            });
-            transport.send_command(&approval_cmd).await?;
+        let mut state = self.state.lock().await;
+        let Some(engine_thread_id) = pending_approval_thread(&state.threads, approval_id) else {
+            anyhow::bail!("Claude approval `{approval_id}` is no longer active.");
+        };
+        let Some(transport) = state.transport.clone() else {
+            if let Some(config) = state.threads.get_mut(&engine_thread_id) {
+                config.finish_request();
+            }
+            anyhow::bail!(
+                "Claude approval `{approval_id}` can no longer be answered because the sidecar was restarted. Re-run the request to create a fresh approval."
+            );
+        };
+        let approval_cmd = serde_json::json!({
+            "method": "approval_response",
+            "params": {
+                "approvalId": approval_id,
+                "response": normalized_response,
+            },
+        });
+        transport.send_command(&approval_cmd).await?;
+        if let Some(config) = state.threads.get_mut(&engine_thread_id) {
+            config.pending_approval_ids.remove(approval_id);
         }
         Ok(())
     }
 
     async fn interrupt(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
-        let state = self.state.lock().await;
-        let Some(ref transport) = state.transport else {
+        let mut state = self.state.lock().await;
+        let Some(transport) = state.transport.clone() else {
             return Ok(());
         };
         let request_id = state
@@ -2212,6 +2383,10 @@ impl Engine for ClaudeSidecarEngine {
                 "params": { "requestId": request_id },
             });
             transport.send_command(&cancel_cmd).await?;
+            // The sidecar declines outstanding approvals when it cancels.
+            if let Some(config) = state.threads.get_mut(engine_thread_id) {
+                config.pending_approval_ids.clear();
+            }
         }
         Ok(())
     }
@@ -2225,12 +2400,59 @@ impl Engine for ClaudeSidecarEngine {
     async fn unarchive_thread(&self, _engine_thread_id: &str) -> Result<(), anyhow::Error> {
         Ok(())
     }
+
+    async fn health(&self) -> EngineHealthDto {
+        let report = self.health_report().await;
+        EngineHealthDto {
+            id: "claude".to_string(),
+            available: report.available,
+            version: report.version,
+            details: Some(report.details),
+            warnings: report.warnings,
+            checks: report.checks,
+            fixes: report.fixes,
+            protocol_diagnostics: None,
+            timeouts: None,
+            auth_status: report.auth_status.map(EngineAuthStatusDto::from),
+        }
+    }
+
+    async fn prewarm(&self) -> Result<(), anyhow::Error> {
+        ClaudeSidecarEngine::prewarm(self).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn claude_auth_status_reads_the_account_and_credentials_files() {
+        assert_eq!(
+            claude_auth_status_from_files(None, true),
+            EngineAuthStatus::SignedIn
+        );
+        assert_eq!(
+            claude_auth_status_from_files(
+                Some(r#"{"oauthAccount":{"emailAddress":"dev@example.com"}}"#),
+                false
+            ),
+            EngineAuthStatus::SignedIn
+        );
+        assert_eq!(
+            claude_auth_status_from_files(Some(r#"{"numStartups":3,"oauthAccount":null}"#), false),
+            EngineAuthStatus::SignedOut
+        );
+        assert_eq!(
+            claude_auth_status_from_files(None, false),
+            EngineAuthStatus::SignedOut
+        );
+        assert_eq!(
+            claude_auth_status_from_files(Some("{not json"), false),
+            EngineAuthStatus::Unknown
+        );
+    }
+
     #[test]
     fn deserializes_action_output_delta_events() {
         let event: SidecarEvent = serde_json::from_value(serde_json::json!({
@@ -2458,6 +2680,83 @@ mod tests {
         assert!(model.supported_reasoning_efforts.is_empty());
     }
 
+    fn thread_config() -> ThreadConfig {
+        ThreadConfig {
+            scope: ThreadScope::Repo {
+                repo_path: "/repo".to_string(),
+            },
+            model_id: "default".to_string(),
+            sandbox: SandboxPolicy {
+                writable_roots: Vec::new(),
+                allow_network: false,
+                approval_policy: None,
+                permission_profile: None,
+                approvals_reviewer: None,
+                reasoning_effort: None,
+                sandbox_mode: None,
+                service_tier: None,
+                personality: None,
+                output_schema: None,
+                opencode_agent: None,
+                executable_override: None,
+                custom_instructions: None,
+            },
+            agent_session_id: None,
+            active_request_id: None,
+            pending_approval_ids: HashSet::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn approvals_are_only_answerable_while_their_request_is_pending() {
+        let engine = ClaudeSidecarEngine::default();
+        {
+            let mut state = engine.state.lock().await;
+            let mut config = thread_config();
+            config.start_request("req-1".to_string());
+            config
+                .pending_approval_ids
+                .insert("req-1:approval:1".to_string());
+            state.threads.insert("thread-1".to_string(), config);
+        }
+
+        let stale = engine
+            .respond_to_approval(
+                "req-0:approval:1",
+                serde_json::json!({ "decision": "accept" }),
+                None,
+            )
+            .await
+            .expect_err("stale approval");
+        assert!(stale.to_string().contains("no longer active"), "{stale}");
+
+        let orphaned = engine
+            .respond_to_approval(
+                "req-1:approval:1",
+                serde_json::json!({ "decision": "accept" }),
+                None,
+            )
+            .await
+            .expect_err("no sidecar running");
+        assert!(orphaned.to_string().contains("restarted"), "{orphaned}");
+        let state = engine.state.lock().await;
+        assert!(state.threads["thread-1"].pending_approval_ids.is_empty());
+        assert_eq!(state.threads["thread-1"].active_request_id, None);
+    }
+
+    #[test]
+    fn a_new_request_drops_approvals_left_by_the_previous_one() {
+        let mut config = thread_config();
+        config.start_request("req-1".to_string());
+        config
+            .pending_approval_ids
+            .insert("req-1:approval:1".to_string());
+        config.start_request("req-2".to_string());
+
+        assert!(config.pending_approval_ids.is_empty());
+        assert_eq!(config.active_request_id.as_deref(), Some("req-2"));
+    }
+
     #[tokio::test]
     async fn runtime_model_fallback_prefers_cached_catalog() {
         let engine = ClaudeSidecarEngine::default();
@@ -2477,7 +2776,9 @@ mod tests {
             default_reasoning_effort: "high".to_string(),
             supported_reasoning_efforts: Vec::new(),
         }];
+        assert!(!engine.has_runtime_models().await);
         engine.state.lock().await.runtime_model_cache = Some(cached.clone());
+        assert!(engine.has_runtime_models().await);
 
         assert_eq!(
             engine
@@ -2643,6 +2944,50 @@ mod tests {
         assert!(details.contains("/usr/local/bin/node"));
     }
 
+    #[test]
+    fn system_claude_findings_separate_login_shell_only_from_run_failures() {
+        let login_shell_only = ExecutableResolution {
+            executable: Some(PathBuf::from("/Users/me/.local/bin/claude")),
+            source: executable_resolution::SOURCE_LOGIN_SHELL,
+            app_path: Some("/usr/bin:/bin".to_string()),
+            login_shell_executable: Some(PathBuf::from("/Users/me/.local/bin/claude")),
+        };
+        let findings = system_claude_findings_for_platform("macos", Some(&login_shell_only), None);
+        assert!(findings.warnings[0].contains("found in your login shell"));
+        assert_eq!(
+            findings.fixes[0],
+            "launchctl setenv PATH \"/Users/me/.local/bin:/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin\""
+        );
+
+        let known_path = ExecutableResolution {
+            executable: Some(PathBuf::from(
+                "/Users/me/.nvm/versions/node/v22.1.0/bin/claude",
+            )),
+            source: executable_resolution::SOURCE_KNOWN_PATH,
+            app_path: Some("/usr/bin:/bin".to_string()),
+            login_shell_executable: None,
+        };
+        let failed = Err("env: node: No such file or directory".to_string());
+        let findings =
+            system_claude_findings_for_platform("macos", Some(&known_path), Some(&failed));
+        assert!(findings.checks.is_empty());
+        assert!(findings.warnings[0].contains("could not run it"));
+        assert!(findings.warnings[0].contains("env: node"));
+        assert!(findings.fixes[0].contains("/Users/me/.nvm/versions/node/v22.1.0/bin"));
+
+        let working = Ok("2.1.0 (Claude Code)".to_string());
+        let findings =
+            system_claude_findings_for_platform("linux", Some(&known_path), Some(&working));
+        assert_eq!(
+            findings.checks,
+            vec![
+                "System Claude Code runtime resolved via known-path at `/Users/me/.nvm/versions/node/v22.1.0/bin/claude`"
+                    .to_string()
+            ]
+        );
+        assert!(findings.warnings.is_empty() && findings.fixes.is_empty());
+    }
+
     #[test]
     fn node_unavailable_details_for_windows_mentions_path_guidance() {
         let details = node_unavailable_details_for_platform(
@@ -4,15 +4,18 @@ use std::{
     env,
     ffi::OsString,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
 use anyhow::Context;
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::{TimeZone, Utc};
 use serde::Deserialize;
-use tokio::time::timeout;
 use tokio::{
     fs as tokio_fs,
     process::Command,
@@ -26,16 +29,23 @@ use crate::models::{
     CodexMcpOauthCompletedDto, CodexMcpServerDto, CodexMethodAvailabilityDto, CodexPluginDto,
     CodexPluginMarketplaceDto, CodexProtocolDiagnosticsDto, CodexSkillDto,
     CodexThreadRealtimeEventDto, CodexWindowsSandboxSetupDto, CodexWindowsWorldWritableWarningDto,
-    RuntimeToastDto,
+    EngineAuthStatusDto, EngineHealthDto, EngineTimeoutsDto, RuntimeToastDto,
+    TurnDiagnosticSourceDto,
+};
+use crate::{
+    config::app_config::{AppConfig, EngineTimeoutsConfig},
+    process_utils, runtime_env,
 };
-use crate::{process_utils, runtime_env};
 
 use super::{
     codex_event_mapper::TurnEventMapper,
     codex_protocol::{raw_value_to_value, IncomingMessage},
-    codex_transport::CodexTransport,
-    ApprovalRequestRoute, CodexRemoteThreadSummary, Engine, EngineEvent, EngineThread,
-    ImportedThreadMessage, ModelAvailabilityNux, ModelInfo, ModelUpgradeInfo,
+    codex_server_requests::{classify_unmapped_request, BlockingRequestFamily},
+    codex_transport::{CodexTransport, TransportStderrLine},
+    executable_resolution::{self, app_path_preview, ExecutableResolution},
+    turn_diagnostics::TurnDiagnosticsSink,
+    ApprovalRequestRoute, CodexRemoteThreadSummary, Engine, EngineAuthStatus, EngineEvent,
+    EngineThread, ImportedThreadMessage, ModelAvailabilityNux, ModelInfo, ModelUpgradeInfo,
     ReasoningEffortOption, SandboxPolicy, ThreadScope, ThreadSyncSnapshot, TurnAttachment,
     TurnCompletionStatus, TurnInput, TurnInputItem, UsageLimitsSnapshot,
 };
@@ -61,6 +71,8 @@ const PLUGIN_LIST_METHODS: &[&str] = &["plugin/list"];
 const MCP_SERVER_STATUS_LIST_METHODS: &[&str] = &["mcpServerStatus/list"];
 const CONFIG_READ_METHODS: &[&str] = &["config/read"];
 const ACCOUNT_READ_METHODS: &[&str] = &["account/read"];
+// `getAuthStatus` is the pre-v2 name; both accept `refreshToken`.
+const AUTH_STATUS_METHODS: &[&str] = &["account/read", "getAuthStatus"];
 const TURN_START_METHODS: &[&str] = &["turn/start"];
 const TURN_STEER_METHODS: &[&str] = &["turn/steer"];
 const TURN_INTERRUPT_METHODS: &[&str] = &["turn/interrupt"];
@@ -69,23 +81,105 @@ const COMMAND_EXEC_METHODS: &[&str] = &["command/exec"];
 const MODEL_LIST_METHODS: &[&str] = &["model/list", "models/list"];
 const ACCOUNT_RATE_LIMITS_READ_METHODS: &[&str] = &["account/rateLimits/read"];
 
-const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
-const TURN_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+const CODEX_MODEL_CACHE_DEFAULT_TTL: Duration = Duration::from_secs(300);
 const HEALTH_APP_SERVER_TIMEOUT: Duration = Duration::from_secs(12);
-const LOGIN_SHELL_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+// Background capability probes; independent of the configured request timeout
+// so a generous setting doesn't keep a stalled probe alive.
+const PROTOCOL_DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(30);
 const TRANSPORT_RESTART_MAX_ATTEMPTS: usize = 3;
 const TRANSPORT_RESTART_BASE_BACKOFF: Duration = Duration::from_millis(250);
 const TRANSPORT_RESTART_MAX_BACKOFF: Duration = Duration::from_secs(2);
+const TRANSPORT_RESTART_NOTICE_KIND: &str = "transport_reconnecting";
+/// Lines of app-server stderr appended to startup and mid-turn failures.
+const STDERR_TAIL_CONTEXT_LINES: usize = 20;
 const CODEX_MISSING_DEFAULT_DETAILS: &str = "`codex` executable not found in PATH";
 const MAX_ATTACHMENTS_PER_TURN: usize = 10;
-const MAX_ATTACHMENT_BYTES: u64 = 10 * 1024 * 1024;
 const MAX_TEXT_ATTACHMENT_CHARS: usize = 40_000;
-const PLAN_MODE_PROMPT_PREFIX: &str = "Plan the solution first. Do not execute commands or edit files until the plan is complete. Reply with a structured plan using one line per step in the exact format `- [pending] Step`.";
+/// Image bytes sent inline as `data:` URLs in one turn; images past it are
+/// sent by path.
+const MAX_INLINE_IMAGE_BYTES_PER_TURN: u64 = 20 * 1024 * 1024;
+pub(crate) const PLAN_MODE_PROMPT_PREFIX: &str = "Plan the solution first. Do not execute commands or edit files until the plan is complete. Reply with a structured plan using one line per step in the exact format `- [pending] Step`.";
 
 pub struct CodexEngine {
     state: Arc<Mutex<CodexState>>,
-    transport_spawn_lock: Arc<Mutex<()>>,
+    timeouts: EngineTimeouts,
     runtime_events: broadcast::Sender<CodexRuntimeEvent>,
+    turn_diagnostics: TurnDiagnosticsSink,
+}
+
+/// How thread runtimes are mapped onto `codex app-server` processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodexTransportRouting {
+    /// Give each workspace root its own app-server instead of sharing one.
+    pub per_workspace: bool,
+    /// Upper bound on live per-workspace transports; idle ones are evicted
+    /// least-recently-used first.
+    pub max_workspace_transports: usize,
+}
+
+impl Default for CodexTransportRouting {
+    fn default() -> Self {
+        Self {
+            per_workspace: false,
+            max_workspace_transports: 4,
+        }
+    }
+}
+
+/// Request deadlines for `codex app-server`, fixed when the engine is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineTimeouts {
+    pub request: Duration,
+    /// `turn/start` and `turn/steer`; `None` waits indefinitely.
+    pub turn_request: Option<Duration>,
+    /// `None` waits on a running turn indefinitely.
+    pub turn_completion_inactivity: Option<Duration>,
+}
+
+impl EngineTimeouts {
+    /// Clamped config values; `PANES_CODEX_COMPLETION_INACTIVITY_TIMEOUT_SECS`
+    /// still applies when the config leaves the inactivity timeout off.
+    pub fn from_config(config: &EngineTimeoutsConfig) -> Self {
+        Self {
+            request: config.request_timeout(),
+            turn_request: config.turn_request_timeout(),
+            turn_completion_inactivity: config
+                .turn_completion_inactivity_timeout()
+                .or_else(env_completion_inactivity_timeout),
+        }
+    }
+
+    pub fn to_dto(self) -> EngineTimeoutsDto {
+        EngineTimeoutsDto {
+            request_timeout_secs: self.request.as_secs(),
+            turn_request_timeout_secs: self.turn_request.map(|timeout| timeout.as_secs()),
+            turn_completion_inactivity_timeout_secs: self
+                .turn_completion_inactivity
+                .map(|timeout| timeout.as_secs()),
+        }
+    }
+
+    /// Deadline handed to the transport for turn requests; tokio never
+    /// fires a `Duration::MAX` timeout.
+    fn turn_request_deadline(self) -> Duration {
+        self.turn_request.unwrap_or(Duration::MAX)
+    }
+}
+
+impl Default for EngineTimeouts {
+    fn default() -> Self {
+        Self::from_config(&EngineTimeoutsConfig::default())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum TransportKey {
+    Shared,
+    Workspace(String),
+    /// A thread pinned to a specific `codex` binary gets its own app-server,
+    /// shared by every thread pinned to the same path. These are not counted
+    /// against `max_workspace_transports`.
+    Executable(PathBuf),
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +200,9 @@ struct ThreadRuntime {
     service_tier: Option<String>,
     personality: Option<String>,
     output_schema: Option<serde_json::Value>,
+    /// Sent as `developerInstructions`, which the app-server only takes when
+    /// a thread is started or resumed.
+    developer_instructions: Option<String>,
     native_plan_mode_active: bool,
 }
 
@@ -121,39 +218,244 @@ struct TurnStartOutcome {
     native_plan_mode_active: bool,
 }
 
+/// One app-server process plus everything tied to its lifetime. Approval
+/// request ids and turn ids are only meaningful to the process that issued
+/// them, so they live here rather than on the engine.
 #[derive(Default)]
-struct CodexState {
+struct TransportSlot {
     transport: Option<Arc<CodexTransport>>,
     initialized: bool,
+    lifecycle_lock: Arc<Mutex<()>>,
     approval_requests: HashMap<String, PendingApproval>,
     active_turn_ids: HashMap<String, String>,
     thread_runtimes: HashMap<String, ThreadRuntime>,
-    runtime_model_cache: Option<Vec<ModelInfo>>,
+    runtime_monitor_transport_tag: Option<usize>,
+    last_used_at: Option<Instant>,
+    /// A live app-server was torn down and has not been replaced yet.
+    restart_pending: bool,
+}
+
+impl TransportSlot {
+    fn is_busy(&self) -> bool {
+        !self.active_turn_ids.is_empty() || !self.approval_requests.is_empty()
+    }
+}
+
+/// Inactivity deadline for a turn whose request has returned but whose
+/// `turn/completed` has not arrived yet.
+struct CompletionInactivity {
+    timeout: Option<Duration>,
+    last_progress_at: Option<Instant>,
+}
+
+impl CompletionInactivity {
+    fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            last_progress_at: None,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.timeout.is_some()
+    }
+
+    fn record_progress(&mut self) {
+        self.last_progress_at = Some(Instant::now());
+    }
+
+    /// Never true before the first progress is recorded.
+    fn expired_at(&self, now: Instant) -> bool {
+        match (self.timeout, self.last_progress_at) {
+            (Some(timeout), Some(last_progress_at)) => {
+                now.saturating_duration_since(last_progress_at) >= timeout
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Keeps a waiting turn informed while its transport is restarted, so the
+/// chat shows a notice instead of looking frozen. Notices share one kind and
+/// replace each other in the message.
+struct TransportRestartNotifier<'a> {
+    event_tx: &'a mpsc::Sender<EngineEvent>,
+    notified: AtomicBool,
+}
+
+impl<'a> TransportRestartNotifier<'a> {
+    fn new(event_tx: &'a mpsc::Sender<EngineEvent>) -> Self {
+        Self {
+            event_tx,
+            notified: AtomicBool::new(false),
+        }
+    }
+
+    /// The app-server died; the first respawn is about to start.
+    async fn transport_lost(&self) {
+        if !self.notified.load(Ordering::Relaxed) {
+            self.retrying(1, Duration::ZERO).await;
+        }
+    }
+
+    /// `attempt` is the upcoming attempt, started after `backoff`.
+    async fn retrying(&self, attempt: usize, backoff: Duration) {
+        self.notified.store(true, Ordering::Relaxed);
+        self.event_tx
+            .send(transport_reconnecting_notice(attempt, backoff))
+            .await
+            .ok();
+    }
+
+    async fn finish(&self) {
+        if self.notified.load(Ordering::Relaxed) {
+            self.event_tx
+                .send(EngineEvent::Notice {
+                    kind: TRANSPORT_RESTART_NOTICE_KIND.to_string(),
+                    level: "info".to_string(),
+                    title: "Reconnected to Codex".to_string(),
+                    message: "The Codex app-server was restarted.".to_string(),
+                })
+                .await
+                .ok();
+        }
+    }
+}
+
+fn transport_reconnecting_notice(attempt: usize, backoff: Duration) -> EngineEvent {
+    let attempt = format!("attempt {attempt} of {TRANSPORT_RESTART_MAX_ATTEMPTS}");
+    let message = if backoff.is_zero() {
+        format!("Restarting the Codex app-server ({attempt}).")
+    } else {
+        format!(
+            "Restarting the Codex app-server failed. Retrying in {:.2}s ({attempt}).",
+            backoff.as_secs_f64()
+        )
+    };
+    EngineEvent::Notice {
+        kind: TRANSPORT_RESTART_NOTICE_KIND.to_string(),
+        level: "warning".to_string(),
+        title: "Reconnecting to Codex…".to_string(),
+        message,
+    }
+}
+
+#[derive(Default)]
+struct CodexState {
+    routing: CodexTransportRouting,
+    transports: HashMap<TransportKey, TransportSlot>,
+    thread_transport_keys: HashMap<String, TransportKey>,
+    /// Last `model/list` result and when it was fetched.
+    runtime_model_cache: Option<(Instant, Vec<ModelInfo>)>,
+    /// Overrides [`CODEX_MODEL_CACHE_DEFAULT_TTL`].
+    model_cache_ttl: Option<Duration>,
+    /// Per-file attachment cap from the app config; the config default
+    /// until it is set.
+    max_attachment_bytes: Option<u64>,
+    model_refresh_in_flight: bool,
     sandbox_probe_completed: bool,
     force_external_sandbox: bool,
     protocol_diagnostics: Option<CodexProtocolDiagnosticsDto>,
-    runtime_monitor_transport_tag: Option<usize>,
+    /// Unmapped server requests seen since launch, keyed by method.
+    protocol_gap_counts: HashMap<String, u64>,
+}
+
+impl CodexState {
+    fn slot(&self, key: &TransportKey) -> Option<&TransportSlot> {
+        self.transports.get(key)
+    }
+
+    fn slot_mut(&mut self, key: &TransportKey) -> &mut TransportSlot {
+        self.transports.entry(key.clone()).or_default()
+    }
+
+    fn thread_key(&self, engine_thread_id: &str) -> TransportKey {
+        self.thread_transport_keys
+            .get(engine_thread_id)
+            .cloned()
+            .unwrap_or(TransportKey::Shared)
+    }
+
+    fn thread_slot(&self, engine_thread_id: &str) -> Option<&TransportSlot> {
+        self.slot(&self.thread_key(engine_thread_id))
+    }
+
+    fn thread_slot_mut(&mut self, engine_thread_id: &str) -> &mut TransportSlot {
+        let key = self.thread_key(engine_thread_id);
+        self.slot_mut(&key)
+    }
+
+    fn approval_key(&self, approval_id: &str) -> Option<TransportKey> {
+        self.transports
+            .iter()
+            .find(|(_, slot)| slot.approval_requests.contains_key(approval_id))
+            .map(|(key, _)| key.clone())
+    }
+
+    /// Drops least-recently-used idle workspace transports until at most
+    /// `max` remain. The shared transport, `keep`, and any transport with an
+    /// active turn or pending approval are never evicted.
+    fn evict_idle_transports(
+        &mut self,
+        max: usize,
+        keep: &TransportKey,
+    ) -> Vec<Arc<CodexTransport>> {
+        let mut evicted = Vec::new();
+        loop {
+            let live = self
+                .transports
+                .iter()
+                .filter(|(key, slot)| {
+                    matches!(key, TransportKey::Workspace(_)) && slot.transport.is_some()
+                })
+                .count();
+            if live <= max {
+                break;
+            }
+
+            let Some(victim) = self
+                .transports
+                .iter()
+                .filter(|(key, slot)| {
+                    matches!(key, TransportKey::Workspace(_))
+                        && *key != keep
+                        && slot.transport.is_some()
+                        && !slot.is_busy()
+                })
+                .min_by_key(|(_, slot)| slot.last_used_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+
+            // Thread bindings stay put so the next turn respawns the same
+            // workspace transport and resumes there.
+            if let Some(slot) = self.transports.remove(&victim) {
+                evicted.extend(slot.transport);
+            }
+        }
+        evicted
+    }
 }
 
 impl Default for CodexEngine {
     fn default() -> Self {
+        Self::with_timeouts(EngineTimeouts::default())
+    }
+}
+
+impl CodexEngine {
+    pub fn with_timeouts(timeouts: EngineTimeouts) -> Self {
         let (runtime_events, _) = broadcast::channel(256);
         Self {
             state: Arc::new(Mutex::new(CodexState::default())),
-            transport_spawn_lock: Arc::new(Mutex::new(())),
+            timeouts,
             runtime_events,
+            turn_diagnostics: TurnDiagnosticsSink::default(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct CodexExecutableResolution {
-    pub executable: Option<PathBuf>,
-    pub source: &'static str,
-    pub app_path: Option<String>,
-    pub login_shell_executable: Option<PathBuf>,
-}
-
 #[derive(Debug, Clone)]
 pub struct CodexHealthReport {
     pub available: bool,
@@ -163,6 +465,9 @@ pub struct CodexHealthReport {
     pub checks: Vec<String>,
     pub fixes: Vec<String>,
     pub protocol_diagnostics: Option<CodexProtocolDiagnosticsDto>,
+    pub timeouts: EngineTimeouts,
+    /// Only probed once the app-server is reachable.
+    pub auth_status: Option<EngineAuthStatus>,
 }
 
 #[derive(Debug, Clone)]
@@ -196,6 +501,13 @@ pub enum CodexRuntimeEvent {
     ThreadUnarchived {
         engine_thread_id: String,
     },
+    /// A server request Panes could not map; `fallback` names the family
+    /// whose safe default was sent, or is `None` for method-not-found.
+    ProtocolGap {
+        method: String,
+        count: u64,
+        fallback: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -407,10 +719,18 @@ impl Engine for CodexEngine {
             service_tier: sandbox.service_tier.clone(),
             personality: sandbox.personality.clone(),
             output_schema: sandbox.output_schema.clone(),
+            developer_instructions: sandbox.custom_instructions.clone(),
             native_plan_mode_active: false,
         };
 
-        let transport = self.ensure_ready_transport().await?;
+        let transport_key = match sandbox.executable_override.as_deref() {
+            Some(executable) => {
+                executable_resolution::validate_executable_override(executable)?;
+                TransportKey::Executable(executable.to_path_buf())
+            }
+            None => self.transport_key_for_cwd(&cwd).await,
+        };
+        let transport = self.ensure_ready_transport_for(&transport_key).await?;
 
         if !force_external_sandbox
             && self
@@ -426,15 +746,31 @@ impl Engine for CodexEngine {
         }
 
         if let Some(existing_thread_id) = resume_engine_thread_id {
-            if self.can_reuse_live_thread(existing_thread_id).await {
+            // Changed instructions only reach the app-server through
+            // `thread/resume`, so the live thread can't be reused as is.
+            let instructions_unchanged =
+                self.thread_runtime(existing_thread_id)
+                    .await
+                    .is_none_or(|runtime| {
+                        runtime.developer_instructions == requested_runtime.developer_instructions
+                    });
+            if instructions_unchanged
+                && self
+                    .can_reuse_live_thread(&transport_key, existing_thread_id)
+                    .await
+            {
                 // Codex applies model and effort per `turn/start`, so a live thread can stay put
                 // while we swap the requested runtime for the next turn.
                 requested_runtime = preserve_live_thread_runtime_flags(
                     requested_runtime,
                     self.thread_runtime(existing_thread_id).await.as_ref(),
                 );
-                self.store_thread_runtime(existing_thread_id, requested_runtime.clone())
-                    .await;
+                self.store_thread_runtime(
+                    &transport_key,
+                    existing_thread_id,
+                    requested_runtime.clone(),
+                )
+                .await;
                 return Ok(EngineThread {
                     engine_thread_id: existing_thread_id.to_string(),
                 });
@@ -452,13 +788,14 @@ impl Engine for CodexEngine {
                 sandbox.approvals_reviewer.as_deref(),
                 sandbox.service_tier.as_deref(),
                 sandbox.personality.as_deref(),
+                sandbox.custom_instructions.as_deref(),
             );
 
             match request_with_fallback(
                 transport.as_ref(),
                 THREAD_RESUME_METHODS,
                 resume_params,
-                DEFAULT_TIMEOUT,
+                self.timeouts.request,
             )
             .await
             {
@@ -466,7 +803,8 @@ impl Engine for CodexEngine {
                     let engine_thread_id = extract_thread_id(&result)
                         .unwrap_or_else(|| existing_thread_id.to_string());
                     let runtime = thread_runtime_from_resume_response(&result, &requested_runtime);
-                    self.store_thread_runtime(&engine_thread_id, runtime).await;
+                    self.store_thread_runtime(&transport_key, &engine_thread_id, runtime)
+                        .await;
 
                     return Ok(EngineThread { engine_thread_id });
                 }
@@ -483,7 +821,7 @@ impl Engine for CodexEngine {
             transport.as_ref(),
             THREAD_START_METHODS,
             start_params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await;
 
@@ -491,7 +829,8 @@ impl Engine for CodexEngine {
             Ok(result) => result,
             Err(error) => {
                 if is_auth_related_error(&error.to_string()) {
-                    self.invalidate_transport(
+                    self.invalidate_transport_for(
+                        &transport_key,
                         "resetting codex transport after auth failure while creating thread",
                     )
                     .await;
@@ -502,7 +841,7 @@ impl Engine for CodexEngine {
 
         let engine_thread_id = extract_thread_id(&result)
             .ok_or_else(|| anyhow::anyhow!("missing thread id in thread/start response"))?;
-        let runtime = thread_runtime_from_start_response(
+        let mut runtime = thread_runtime_from_start_response(
             &result,
             &requested_runtime.cwd,
             &requested_runtime.model_id,
@@ -515,7 +854,9 @@ impl Engine for CodexEngine {
             requested_runtime.personality.clone(),
             requested_runtime.output_schema.clone(),
         );
-        self.store_thread_runtime(&engine_thread_id, runtime).await;
+        runtime.developer_instructions = requested_runtime.developer_instructions;
+        self.store_thread_runtime(&transport_key, &engine_thread_id, runtime)
+            .await;
 
         Ok(EngineThread { engine_thread_id })
     }
@@ -527,20 +868,40 @@ impl Engine for CodexEngine {
         event_tx: mpsc::Sender<EngineEvent>,
         cancellation: CancellationToken,
     ) -> Result<(), anyhow::Error> {
-        let transport = self.ensure_ready_transport().await?;
+        let restart_notifier = TransportRestartNotifier::new(&event_tx);
+        let transport_key = self.thread_transport_key(engine_thread_id).await;
+        let transport = self
+            .ensure_ready_transport_notifying(&transport_key, Some(&restart_notifier))
+            .await?;
+        restart_notifier.finish().await;
         if let Some(message) = self.unsupported_external_auth_tokens_message().await {
             return Err(anyhow::anyhow!(message));
         }
 
         let mut mapper = TurnEventMapper::default();
         let mut subscription = transport.subscribe();
+        let mut stderr_subscription = transport.subscribe_stderr();
+        let mut stderr_open = true;
         let thread_id = engine_thread_id.to_string();
 
         let runtime = self.thread_runtime(&thread_id).await;
         let plan_mode_activation = self
             .resolve_turn_plan_mode_activation(runtime.as_ref(), &input)
             .await;
-        validate_turn_attachments(&input.attachments).await?;
+        let mut input = input;
+        for attachment in take_unsupported_attachments(&mut input) {
+            event_tx
+                .send(EngineEvent::Error {
+                    message: format!(
+                        "{} It was left out of this turn.",
+                        unsupported_attachment_message(&attachment)
+                    ),
+                    recoverable: true,
+                })
+                .await
+                .ok();
+        }
+        validate_turn_attachments(&input.attachments, self.max_attachment_bytes().await).await?;
 
         let transport_for_rate_limits = transport.clone();
         let rate_limits_task = tokio::spawn(async move {
@@ -558,6 +919,7 @@ impl Engine for CodexEngine {
         let runtime_for_turn = runtime.clone();
         let input_for_turn = input.clone();
         let plan_mode_activation_for_turn = plan_mode_activation;
+        let turn_request_timeout = self.timeouts.turn_request_deadline();
         let turn_task = tokio::spawn(async move {
             request_turn_start(
                 transport_for_turn.as_ref(),
@@ -565,6 +927,7 @@ impl Engine for CodexEngine {
                 runtime_for_turn,
                 input_for_turn,
                 plan_mode_activation_for_turn,
+                turn_request_timeout,
             )
             .await
         });
@@ -575,8 +938,8 @@ impl Engine for CodexEngine {
         let mut turn_request_done = false;
         let mut completion_seen = false;
         let mut expected_turn_id: Option<String> = None;
-        let mut completion_last_progress_at: Option<Instant> = None;
-        let completion_inactivity_timeout = completion_inactivity_timeout();
+        let mut completion_inactivity =
+            CompletionInactivity::new(self.timeouts.turn_completion_inactivity);
 
         while !completion_seen || !turn_request_done {
             tokio::select! {
@@ -596,6 +959,9 @@ impl Engine for CodexEngine {
                   }
                 }
               }
+              line = stderr_subscription.recv(), if stderr_open => {
+                stderr_open = self.record_stderr_diagnostic(&thread_id, line);
+              }
               _ = cancellation.cancelled() => {
                 turn_task.abort();
                 self
@@ -611,7 +977,8 @@ impl Engine for CodexEngine {
                   Ok(Err(error)) => {
                     if is_auth_related_error(&error.to_string()) {
                       self
-                        .invalidate_transport(
+                        .invalidate_transport_for(
+                          &transport_key,
                           "resetting codex transport after auth failure while starting turn",
                         )
                         .await;
@@ -646,7 +1013,8 @@ impl Engine for CodexEngine {
                   }
                   if event_indicates_auth_failure(&event) {
                     self
-                      .invalidate_transport(
+                      .invalidate_transport_for(
+                        &transport_key,
                         "resetting codex transport after auth failure during turn result",
                       )
                       .await;
@@ -659,7 +1027,7 @@ impl Engine for CodexEngine {
                 }
 
                 if !completion_seen {
-                  completion_last_progress_at = Some(Instant::now());
+                  completion_inactivity.record_progress();
                 }
               }
               incoming = subscription.recv() => {
@@ -670,8 +1038,17 @@ impl Engine for CodexEngine {
                     if let Some(error_message) =
                       transport_failure_message(normalized_method.as_str(), &params)
                     {
+                      if let Some(stderr_tail) = transport_stderr_tail(&transport) {
+                        event_tx
+                          .send(EngineEvent::Error {
+                            message: with_stderr_tail(&error_message, Some(&stderr_tail)),
+                            recoverable: true,
+                          })
+                          .await
+                          .ok();
+                      }
                       self.clear_active_turn(&thread_id).await;
-                      self.invalidate_transport(&error_message).await;
+                      self.invalidate_transport_for(&transport_key, &error_message).await;
                       if turn_request_done
                         && self
                           .try_emit_reconciled_turn_completion(
@@ -690,8 +1067,18 @@ impl Engine for CodexEngine {
                     }
 
                     if !belongs_to_thread(&params, &thread_id) {
+                      self.turn_diagnostics.record(
+                        &thread_id,
+                        TurnDiagnosticSourceDto::DroppedByThreadFilter,
+                        &format!("notification {method}"),
+                      );
                       continue;
                     }
+                    // Heartbeats and other unmapped notifications
+                    // for the thread still show the turn is alive.
+                    if turn_request_done && !completion_seen {
+                      completion_inactivity.record_progress();
+                    }
                     if normalized_method == "turn/started" {
                       if let Some(turn_id) = extract_turn_id(&params) {
                         rebind_expected_turn_id(
@@ -703,23 +1090,31 @@ impl Engine for CodexEngine {
                         self.set_active_turn(&thread_id, &turn_id).await;
                       }
                     } else if !belongs_to_turn(&params, expected_turn_id.as_deref()) {
+                      self.turn_diagnostics.record(
+                        &thread_id,
+                        TurnDiagnosticSourceDto::DroppedByTurnFilter,
+                        &format!("notification {method}"),
+                      );
                       continue;
                     }
 
                     if normalized_method == "turn/completed" {
                       self.clear_active_turn(&thread_id).await;
                     }
-                    if turn_request_done && !completion_seen {
-                      completion_last_progress_at = Some(Instant::now());
-                    }
 
                     let mapped_events = mapper.map_notification(&method, &params);
                     if mapped_events.is_empty()
                         && !is_known_codex_notification_method(&normalized_method)
                     {
+                        let params_keys =
+                            params.as_object().map(|object| object.keys().collect::<Vec<_>>());
                         log::debug!(
-                            "codex notification not mapped: method={method}, normalized={normalized_method}, params_keys={:?}",
-                            params.as_object().map(|object| object.keys().collect::<Vec<_>>())
+                            "codex notification not mapped: method={method}, normalized={normalized_method}, params_keys={params_keys:?}"
+                        );
+                        self.turn_diagnostics.record(
+                            &thread_id,
+                            TurnDiagnosticSourceDto::UnmappedNotification,
+                            &format!("{method} params_keys={params_keys:?}"),
                         );
                     }
 
@@ -729,7 +1124,8 @@ impl Engine for CodexEngine {
                       }
                       if event_indicates_auth_failure(&event) {
                         self
-                          .invalidate_transport(
+                          .invalidate_transport_for(
+                            &transport_key,
                             "resetting codex transport after auth failure during streamed turn event",
                           )
                           .await;
@@ -749,10 +1145,20 @@ impl Engine for CodexEngine {
                     );
                     if !belongs_to_thread(&params, &thread_id) {
                       log::warn!("codex server request dropped by belongs_to_thread: method={method}");
+                      self.turn_diagnostics.record(
+                        &thread_id,
+                        TurnDiagnosticSourceDto::DroppedByThreadFilter,
+                        &format!("server request {method}"),
+                      );
                       continue;
                     }
                     if !belongs_to_turn(&params, expected_turn_id.as_deref()) {
                       log::warn!("codex server request dropped by belongs_to_turn: method={method}");
+                      self.turn_diagnostics.record(
+                        &thread_id,
+                        TurnDiagnosticSourceDto::DroppedByTurnFilter,
+                        &format!("server request {method}"),
+                      );
                       continue;
                     }
                     let normalized_method = normalize_method(&method);
@@ -803,10 +1209,11 @@ impl Engine for CodexEngine {
                         approval.approval_id
                       );
                       if turn_request_done && !completion_seen {
-                        completion_last_progress_at = Some(Instant::now());
+                        completion_inactivity.record_progress();
                       }
                       self
                         .register_approval_request(
+                          &transport_key,
                           &approval.approval_id,
                           &raw_id,
                           &approval.server_method,
@@ -814,34 +1221,16 @@ impl Engine for CodexEngine {
                         .await;
                       event_tx.send(approval.event).await.ok();
                     } else {
-                      log::warn!(
-                        "codex server request not mapped: method={method}, normalized={normalized_method}"
-                      );
-                      let (message, recoverable) = (
-                        format!("Unsupported Codex server request method `{method}`"),
-                        true,
-                      );
-
-                      event_tx
-                        .send(EngineEvent::Error {
-                          message: message.clone(),
-                          recoverable,
-                        })
-                        .await
-                        .ok();
-
-                      transport
-                        .respond_error(
+                      self
+                        .answer_unmapped_server_request(
+                          &transport,
+                          &thread_id,
                           &raw_id,
-                          -32601,
-                          &message,
-                          Some(serde_json::json!({
-                            "method": method,
-                            "normalizedMethod": normalized_method,
-                          })),
+                          &method,
+                          &params,
+                          &event_tx,
                         )
-                        .await
-                        .ok();
+                        .await;
                     }
                   }
                   Ok(IncomingMessage::Response(_)) => {
@@ -852,7 +1241,7 @@ impl Engine for CodexEngine {
                         "codex transport lagged while waiting for turn events; skipped {skipped} messages"
                     );
                     self.clear_active_turn(&thread_id).await;
-                    self.invalidate_transport(&error_message).await;
+                    self.invalidate_transport_for(&transport_key, &error_message).await;
                     if turn_request_done
                         && self
                             .try_emit_reconciled_turn_completion(
@@ -872,7 +1261,7 @@ impl Engine for CodexEngine {
                   Err(broadcast::error::RecvError::Closed) => {
                     self.clear_active_turn(&thread_id).await;
                     self
-                      .invalidate_transport("codex transport subscription closed while waiting for turn events")
+                      .invalidate_transport_for(&transport_key, "codex transport subscription closed while waiting for turn events")
                       .await;
                     if turn_request_done
                         && self
@@ -894,16 +1283,12 @@ impl Engine for CodexEngine {
                   }
                 }
               }
-              _ = tokio::time::sleep(Duration::from_millis(200)), if turn_request_done && !completion_seen && completion_inactivity_timeout.is_some() => {
-                if let Some(last_progress_at) = completion_last_progress_at {
-                  if Instant::now().duration_since(last_progress_at)
-                    >= completion_inactivity_timeout.expect("guarded by is_some")
-                  {
-                    log::warn!(
-                      "codex turn completion inactivity timeout reached for thread {thread_id}; synthesizing completion"
-                    );
-                    break;
-                  }
+              _ = tokio::time::sleep(Duration::from_millis(200)), if turn_request_done && !completion_seen && completion_inactivity.is_enabled() => {
+                if completion_inactivity.expired_at(Instant::now()) {
+                  log::warn!(
+                    "codex turn completion inactivity timeout reached for thread {thread_id}; synthesizing completion"
+                  );
+                  break;
                 }
               }
             }
@@ -951,8 +1336,8 @@ impl Engine for CodexEngine {
         engine_thread_id: &str,
         input: TurnInput,
     ) -> Result<(), anyhow::Error> {
-        let transport = self.ensure_ready_transport().await?;
-        validate_turn_attachments(&input.attachments).await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
+        validate_turn_attachments(&input.attachments, self.max_attachment_bytes().await).await?;
 
         let expected_turn_id = self.active_turn_id(engine_thread_id).await.ok_or_else(|| {
             anyhow::anyhow!(
@@ -965,6 +1350,7 @@ impl Engine for CodexEngine {
             engine_thread_id,
             &expected_turn_id,
             &input,
+            self.timeouts.turn_request_deadline(),
         )
         .await
         .context("turn/steer request failed")?;
@@ -979,14 +1365,20 @@ impl Engine for CodexEngine {
         route: Option<ApprovalRequestRoute>,
     ) -> Result<(), anyhow::Error> {
         let pending = self.approval_request(approval_id).await;
-        let (raw_request_id, method) =
-            resolve_approval_response_target(pending.as_ref(), route.as_ref()).map_err(
-                |reason| {
-                    anyhow::anyhow!(approval_response_target_error_message(reason, approval_id))
-                },
-            )?;
+        let (raw_request_id, method) = resolve_approval_response_target(
+            pending.as_ref().map(|(_, pending)| pending),
+            route.as_ref(),
+        )
+        .map_err(|reason| {
+            anyhow::anyhow!(approval_response_target_error_message(reason, approval_id))
+        })?;
         let normalized_response = normalize_approval_response(Some(method), response);
-        let transport = self.ensure_ready_transport().await?;
+        // Request ids are scoped to the app-server that issued them.
+        let transport_key = pending
+            .as_ref()
+            .map(|(key, _)| key.clone())
+            .unwrap_or(TransportKey::Shared);
+        let transport = self.ensure_ready_transport_for(&transport_key).await?;
 
         log::info!(
             "sending approval response to codex: approval_id={approval_id}, raw_request_id={raw_request_id}"
@@ -1004,7 +1396,9 @@ impl Engine for CodexEngine {
     async fn interrupt(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
         let transport = {
             let state = self.state.lock().await;
-            state.transport.clone()
+            state
+                .thread_slot(engine_thread_id)
+                .and_then(|slot| slot.transport.clone())
         };
 
         let Some(transport) = transport else {
@@ -1040,7 +1434,7 @@ impl Engine for CodexEngine {
     }
 
     async fn archive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
-        let transport = self.ensure_ready_transport().await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
         let params = serde_json::json!({
             "threadId": engine_thread_id,
         });
@@ -1049,7 +1443,7 @@ impl Engine for CodexEngine {
             transport.as_ref(),
             THREAD_ARCHIVE_METHODS,
             params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to archive codex thread")?;
@@ -1058,7 +1452,7 @@ impl Engine for CodexEngine {
     }
 
     async fn unarchive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
-        let transport = self.ensure_ready_transport().await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
         let params = serde_json::json!({
             "threadId": engine_thread_id,
         });
@@ -1067,13 +1461,54 @@ impl Engine for CodexEngine {
             transport.as_ref(),
             THREAD_UNARCHIVE_METHODS,
             params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to unarchive codex thread")?;
 
         Ok(())
     }
+
+    async fn health(&self) -> EngineHealthDto {
+        let report = self.health_report().await;
+        EngineHealthDto {
+            id: "codex".to_string(),
+            available: report.available,
+            version: report.version,
+            details: report.details,
+            warnings: report.warnings,
+            checks: report.checks,
+            fixes: report.fixes,
+            protocol_diagnostics: report.protocol_diagnostics,
+            timeouts: Some(report.timeouts.to_dto()),
+            auth_status: report.auth_status.map(EngineAuthStatusDto::from),
+        }
+    }
+
+    async fn prewarm(&self) -> Result<(), anyhow::Error> {
+        CodexEngine::prewarm(self).await
+    }
+
+    async fn read_thread_preview(&self, engine_thread_id: &str) -> Option<String> {
+        CodexEngine::read_thread_preview(self, engine_thread_id).await
+    }
+
+    async fn set_thread_name(
+        &self,
+        engine_thread_id: &str,
+        name: &str,
+    ) -> Result<(), anyhow::Error> {
+        CodexEngine::set_thread_name(self, engine_thread_id, name).await
+    }
+
+    async fn read_thread_sync_snapshot(
+        &self,
+        engine_thread_id: &str,
+    ) -> Result<Option<ThreadSyncSnapshot>, anyhow::Error> {
+        CodexEngine::read_thread_sync_snapshot(self, engine_thread_id)
+            .await
+            .map(Some)
+    }
 }
 
 impl CodexEngine {
@@ -1092,6 +1527,87 @@ impl CodexEngine {
             _ => anyhow::bail!("Codex did not return account usage limits"),
         }
     }
+
+    /// Answers a server request the mapper does not know. Known-blocking
+    /// families get a safe refusal so the turn keeps going instead of stalling
+    /// until the inactivity timeout; anything else gets method-not-found. The
+    /// request is recorded with its params either way.
+    async fn answer_unmapped_server_request(
+        &self,
+        transport: &CodexTransport,
+        diagnostics_thread_id: &str,
+        raw_id: &serde_json::Value,
+        method: &str,
+        params: &serde_json::Value,
+        event_tx: &mpsc::Sender<EngineEvent>,
+    ) {
+        let normalized_method = normalize_method(method);
+        let family = classify_unmapped_request(&normalized_method);
+        log::warn!(
+            "codex server request not mapped: method={method}, normalized={normalized_method}, fallback={}",
+            family.map_or("method_not_found", BlockingRequestFamily::as_str)
+        );
+        self.turn_diagnostics.record(
+            diagnostics_thread_id,
+            TurnDiagnosticSourceDto::UnmappedServerRequest,
+            &format!("{method} params={params}"),
+        );
+        let count = {
+            let mut state = self.state.lock().await;
+            let count = state
+                .protocol_gap_counts
+                .entry(method.to_string())
+                .or_default();
+            *count += 1;
+            *count
+        };
+        let _ = self.runtime_events.send(CodexRuntimeEvent::ProtocolGap {
+            method: method.to_string(),
+            count,
+            fallback: family.map(|family| family.as_str().to_string()),
+        });
+
+        if let Some(family) = family {
+            event_tx
+                .send(EngineEvent::Notice {
+                    kind: "protocol_gap".to_string(),
+                    level: "warning".to_string(),
+                    title: "Unsupported Codex request".to_string(),
+                    message: format!(
+                        "Codex sent `{method}`, which Panes does not support yet. It was declined so the turn can continue."
+                    ),
+                })
+                .await
+                .ok();
+            transport
+                .respond_success(raw_id, family.fallback_response(method))
+                .await
+                .ok();
+            return;
+        }
+
+        let message = format!("Unsupported Codex server request method `{method}`");
+        event_tx
+            .send(EngineEvent::Error {
+                message: message.clone(),
+                recoverable: true,
+            })
+            .await
+            .ok();
+        transport
+            .respond_error(
+                raw_id,
+                -32601,
+                &message,
+                Some(serde_json::json!({
+                    "method": method,
+                    "normalizedMethod": normalized_method,
+                })),
+            )
+            .await
+            .ok();
+    }
+
     pub fn subscribe_runtime_events(&self) -> broadcast::Receiver<CodexRuntimeEvent> {
         self.runtime_events.subscribe()
     }
@@ -1109,7 +1625,7 @@ impl CodexEngine {
                 "cwds": [cwd],
                 "forceReload": false,
             }),
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to list codex skills")?;
@@ -1125,7 +1641,7 @@ impl CodexEngine {
 
     pub async fn list_apps(&self) -> anyhow::Result<Vec<CodexAppDto>> {
         let transport = self.ensure_ready_transport().await?;
-        match fetch_apps(transport.as_ref()).await {
+        match fetch_apps(transport.as_ref(), self.timeouts.request).await {
             MethodCallOutcome::Available(apps) => Ok(apps),
             MethodCallOutcome::Unsupported(detail) => {
                 anyhow::bail!("codex app/list unsupported: {}", detail.unwrap_or_default())
@@ -1141,7 +1657,8 @@ impl CodexEngine {
         model: &str,
         sandbox: SandboxPolicy,
     ) -> anyhow::Result<CodexForkedThread> {
-        let transport = self.ensure_ready_transport().await?;
+        let (transport_key, transport) =
+            self.ensure_ready_thread_transport(engine_thread_id).await?;
         let approval_policy = sandbox
             .approval_policy
             .clone()
@@ -1160,6 +1677,7 @@ impl CodexEngine {
             service_tier: sandbox.service_tier.clone(),
             personality: sandbox.personality.clone(),
             output_schema: sandbox.output_schema.clone(),
+            developer_instructions: sandbox.custom_instructions.clone(),
             native_plan_mode_active: false,
         };
 
@@ -1174,14 +1692,14 @@ impl CodexEngine {
                 &sandbox_mode,
                 &sandbox,
             ),
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to fork codex thread")?;
 
         let new_engine_thread_id = extract_thread_id(&response)
             .ok_or_else(|| anyhow::anyhow!("missing thread id in thread/fork response"))?;
-        let runtime = thread_runtime_from_start_response(
+        let mut runtime = thread_runtime_from_start_response(
             &response,
             &requested_runtime.cwd,
             &requested_runtime.model_id,
@@ -1194,7 +1712,8 @@ impl CodexEngine {
             requested_runtime.personality.clone(),
             requested_runtime.output_schema.clone(),
         );
-        self.store_thread_runtime(&new_engine_thread_id, runtime)
+        runtime.developer_instructions = requested_runtime.developer_instructions;
+        self.store_thread_runtime(&transport_key, &new_engine_thread_id, runtime)
             .await;
 
         Ok(CodexForkedThread {
@@ -1213,7 +1732,7 @@ impl CodexEngine {
         engine_thread_id: &str,
         num_turns: u32,
     ) -> anyhow::Result<ThreadSyncSnapshot> {
-        let transport = self.ensure_ready_transport().await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
         let response = request_with_fallback(
             transport.as_ref(),
             THREAD_ROLLBACK_METHODS,
@@ -1221,7 +1740,7 @@ impl CodexEngine {
                 "threadId": engine_thread_id,
                 "numTurns": num_turns,
             }),
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to rollback codex thread")?;
@@ -1236,14 +1755,14 @@ impl CodexEngine {
     }
 
     pub async fn compact_thread(&self, engine_thread_id: &str) -> anyhow::Result<()> {
-        let transport = self.ensure_ready_transport().await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
         request_with_fallback(
             transport.as_ref(),
             THREAD_COMPACT_START_METHODS,
             serde_json::json!({
                 "threadId": engine_thread_id,
             }),
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to start codex thread compaction")?;
@@ -1260,13 +1779,17 @@ impl CodexEngine {
         cancellation: CancellationToken,
         started_tx: oneshot::Sender<CodexReviewStarted>,
     ) -> Result<(), anyhow::Error> {
-        let transport = self.ensure_ready_transport().await?;
+        let (transport_key, transport) = self
+            .ensure_ready_thread_transport(source_engine_thread_id)
+            .await?;
         if let Some(message) = self.unsupported_external_auth_tokens_message().await {
             return Err(anyhow::anyhow!(message));
         }
 
         let mut mapper = TurnEventMapper::default();
         let mut subscription = transport.subscribe();
+        let mut stderr_subscription = transport.subscribe_stderr();
+        let mut stderr_open = true;
         let source_thread_id = source_engine_thread_id.to_string();
         let mut active_thread_id = source_thread_id.clone();
         let requested_delivery = delivery.map(str::to_string);
@@ -1285,6 +1808,7 @@ impl CodexEngine {
         let transport_for_review = transport.clone();
         let source_thread_id_for_review = source_thread_id.clone();
         let target_for_review = target.clone();
+        let review_request_timeout = self.timeouts.turn_request_deadline();
         let review_task = tokio::spawn(async move {
             request_with_fallback(
                 transport_for_review.as_ref(),
@@ -1294,7 +1818,7 @@ impl CodexEngine {
                     "target": target_for_review,
                     "delivery": requested_delivery,
                 }),
-                TURN_REQUEST_TIMEOUT,
+                review_request_timeout,
             )
             .await
         });
@@ -1305,9 +1829,9 @@ impl CodexEngine {
         let mut turn_request_done = false;
         let mut completion_seen = false;
         let mut expected_turn_id: Option<String> = None;
-        let mut completion_last_progress_at: Option<Instant> = None;
+        let mut completion_inactivity =
+            CompletionInactivity::new(self.timeouts.turn_completion_inactivity);
         let mut started_tx = Some(started_tx);
-        let completion_inactivity_timeout = completion_inactivity_timeout();
 
         while !completion_seen || !turn_request_done {
             tokio::select! {
@@ -1327,6 +1851,9 @@ impl CodexEngine {
                   }
                 }
               }
+              line = stderr_subscription.recv(), if stderr_open => {
+                stderr_open = self.record_stderr_diagnostic(&source_thread_id, line);
+              }
               _ = cancellation.cancelled() => {
                 review_task.abort();
                 drop(started_tx.take());
@@ -1343,7 +1870,8 @@ impl CodexEngine {
                   Ok(Err(error)) => {
                     if is_auth_related_error(&error.to_string()) {
                       self
-                        .invalidate_transport(
+                        .invalidate_transport_for(
+                          &transport_key,
                           "resetting codex transport after auth failure while starting review",
                         )
                         .await;
@@ -1365,6 +1893,9 @@ impl CodexEngine {
                     }
                 };
                 active_thread_id = review_thread_id.clone();
+                // Detached reviews run on a new thread that lives on the same
+                // transport as its source.
+                self.bind_thread_transport(&review_thread_id, &transport_key).await;
                 if let Some(started_tx) = started_tx.take() {
                     let _ = started_tx.send(CodexReviewStarted {
                         review_thread_id: review_thread_id.clone(),
@@ -1387,7 +1918,8 @@ impl CodexEngine {
                   }
                   if event_indicates_auth_failure(&event) {
                     self
-                      .invalidate_transport(
+                      .invalidate_transport_for(
+                        &transport_key,
                         "resetting codex transport after auth failure during review result",
                       )
                       .await;
@@ -1400,7 +1932,7 @@ impl CodexEngine {
                 }
 
                 if !completion_seen {
-                  completion_last_progress_at = Some(Instant::now());
+                  completion_inactivity.record_progress();
                 }
               }
               incoming = subscription.recv() => {
@@ -1411,8 +1943,17 @@ impl CodexEngine {
                     if let Some(error_message) =
                       transport_failure_message(normalized_method.as_str(), &params)
                     {
+                      if let Some(stderr_tail) = transport_stderr_tail(&transport) {
+                        event_tx
+                          .send(EngineEvent::Error {
+                            message: with_stderr_tail(&error_message, Some(&stderr_tail)),
+                            recoverable: true,
+                          })
+                          .await
+                          .ok();
+                      }
                       self.clear_active_turn(&active_thread_id).await;
-                      self.invalidate_transport(&error_message).await;
+                      self.invalidate_transport_for(&transport_key, &error_message).await;
                       if turn_request_done
                         && self
                           .try_emit_reconciled_turn_completion(
@@ -1432,8 +1973,18 @@ impl CodexEngine {
                     }
 
                     if !belongs_to_thread(&params, &active_thread_id) {
+                      self.turn_diagnostics.record(
+                        &source_thread_id,
+                        TurnDiagnosticSourceDto::DroppedByThreadFilter,
+                        &format!("notification {method}"),
+                      );
                       continue;
                     }
+                    // Heartbeats and other unmapped notifications
+                    // for the thread still show the turn is alive.
+                    if turn_request_done && !completion_seen {
+                      completion_inactivity.record_progress();
+                    }
                     if normalized_method == "turn/started" {
                       if let Some(turn_id) = extract_turn_id(&params) {
                         rebind_expected_turn_id(
@@ -1445,23 +1996,31 @@ impl CodexEngine {
                         self.set_active_turn(&active_thread_id, &turn_id).await;
                       }
                     } else if !belongs_to_turn(&params, expected_turn_id.as_deref()) {
+                      self.turn_diagnostics.record(
+                        &source_thread_id,
+                        TurnDiagnosticSourceDto::DroppedByTurnFilter,
+                        &format!("notification {method}"),
+                      );
                       continue;
                     }
 
                     if normalized_method == "turn/completed" {
                       self.clear_active_turn(&active_thread_id).await;
                     }
-                    if turn_request_done && !completion_seen {
-                      completion_last_progress_at = Some(Instant::now());
-                    }
 
                     let mapped_events = mapper.map_notification(&method, &params);
                     if mapped_events.is_empty()
                         && !is_known_codex_notification_method(&normalized_method)
                     {
+                        let params_keys =
+                            params.as_object().map(|object| object.keys().collect::<Vec<_>>());
                         log::debug!(
-                            "codex notification not mapped during review: method={method}, normalized={normalized_method}, params_keys={:?}",
-                            params.as_object().map(|object| object.keys().collect::<Vec<_>>())
+                            "codex notification not mapped during review: method={method}, normalized={normalized_method}, params_keys={params_keys:?}"
+                        );
+                        self.turn_diagnostics.record(
+                            &source_thread_id,
+                            TurnDiagnosticSourceDto::UnmappedNotification,
+                            &format!("{method} params_keys={params_keys:?}"),
                         );
                     }
 
@@ -1471,7 +2030,8 @@ impl CodexEngine {
                       }
                       if event_indicates_auth_failure(&event) {
                         self
-                          .invalidate_transport(
+                          .invalidate_transport_for(
+                            &transport_key,
                             "resetting codex transport after auth failure during streamed review event",
                           )
                           .await;
@@ -1491,10 +2051,20 @@ impl CodexEngine {
                     );
                     if !belongs_to_thread(&params, &active_thread_id) {
                       log::warn!("codex review server request dropped by belongs_to_thread: method={method}");
+                      self.turn_diagnostics.record(
+                        &source_thread_id,
+                        TurnDiagnosticSourceDto::DroppedByThreadFilter,
+                        &format!("server request {method}"),
+                      );
                       continue;
                     }
                     if !belongs_to_turn(&params, expected_turn_id.as_deref()) {
                       log::warn!("codex review server request dropped by belongs_to_turn: method={method}");
+                      self.turn_diagnostics.record(
+                        &source_thread_id,
+                        TurnDiagnosticSourceDto::DroppedByTurnFilter,
+                        &format!("server request {method}"),
+                      );
                       continue;
                     }
                     let normalized_method = normalize_method(&method);
@@ -1545,10 +2115,11 @@ impl CodexEngine {
                         approval.approval_id
                       );
                       if turn_request_done && !completion_seen {
-                        completion_last_progress_at = Some(Instant::now());
+                        completion_inactivity.record_progress();
                       }
                       self
                         .register_approval_request(
+                          &transport_key,
                           &approval.approval_id,
                           &raw_id,
                           &approval.server_method,
@@ -1556,34 +2127,16 @@ impl CodexEngine {
                         .await;
                       event_tx.send(approval.event).await.ok();
                     } else {
-                      log::warn!(
-                        "codex review server request not mapped: method={method}, normalized={normalized_method}"
-                      );
-                      let (message, recoverable) = (
-                        format!("Unsupported Codex server request method `{method}`"),
-                        true,
-                      );
-
-                      event_tx
-                        .send(EngineEvent::Error {
-                          message: message.clone(),
-                          recoverable,
-                        })
-                        .await
-                        .ok();
-
-                      transport
-                        .respond_error(
+                      self
+                        .answer_unmapped_server_request(
+                          &transport,
+                          &source_thread_id,
                           &raw_id,
-                          -32601,
-                          &message,
-                          Some(serde_json::json!({
-                            "method": method,
-                            "normalizedMethod": normalized_method,
-                          })),
+                          &method,
+                          &params,
+                          &event_tx,
                         )
-                        .await
-                        .ok();
+                        .await;
                     }
                   }
                   Ok(IncomingMessage::Response(_)) => {}
@@ -1592,7 +2145,7 @@ impl CodexEngine {
                         "codex transport lagged while waiting for review events; skipped {skipped} messages"
                     );
                     self.clear_active_turn(&active_thread_id).await;
-                    self.invalidate_transport(&error_message).await;
+                    self.invalidate_transport_for(&transport_key, &error_message).await;
                     if turn_request_done
                         && self
                             .try_emit_reconciled_turn_completion(
@@ -1613,7 +2166,7 @@ impl CodexEngine {
                   Err(broadcast::error::RecvError::Closed) => {
                     self.clear_active_turn(&active_thread_id).await;
                     self
-                      .invalidate_transport("codex transport subscription closed while waiting for review events")
+                      .invalidate_transport_for(&transport_key, "codex transport subscription closed while waiting for review events")
                       .await;
                     if turn_request_done
                         && self
@@ -1636,16 +2189,12 @@ impl CodexEngine {
                   }
                 }
               }
-              _ = tokio::time::sleep(Duration::from_millis(200)), if turn_request_done && !completion_seen && completion_inactivity_timeout.is_some() => {
-                if let Some(last_progress_at) = completion_last_progress_at {
-                  if Instant::now().duration_since(last_progress_at)
-                    >= completion_inactivity_timeout.expect("guarded by is_some")
-                  {
-                    log::warn!(
-                      "codex review completion inactivity timeout reached for thread {active_thread_id}; synthesizing completion"
-                    );
-                    break;
-                  }
+              _ = tokio::time::sleep(Duration::from_millis(200)), if turn_request_done && !completion_seen && completion_inactivity.is_enabled() => {
+                if completion_inactivity.expired_at(Instant::now()) {
+                  log::warn!(
+                    "codex review completion inactivity timeout reached for thread {active_thread_id}; synthesizing completion"
+                  );
+                  break;
                 }
               }
             }
@@ -1688,6 +2237,17 @@ impl CodexEngine {
         Ok(())
     }
 
+    /// Drops state derived from the previously resolved binary. The binary
+    /// itself is resolved on every health check, so a fresh install is picked
+    /// up there; this makes the next check also refetch diagnostics and models.
+    pub async fn invalidate_executable_caches(&self) {
+        let mut state = self.state.lock().await;
+        state.runtime_model_cache = None;
+        if let Some(diagnostics) = state.protocol_diagnostics.as_mut() {
+            diagnostics.stale = true;
+        }
+    }
+
     pub async fn health_report(&self) -> CodexHealthReport {
         let resolution = resolve_codex_executable().await;
         let version_result = self.probe_version_from_resolution(&resolution).await;
@@ -1707,9 +2267,24 @@ impl CodexEngine {
                 codex_unavailable_details(&resolution)
             }
         } else {
-            codex_unavailable_details(&resolution).or_else(|| codex_resolution_note(&resolution))
+            codex_unavailable_details(&resolution)
+                .or_else(|| executable_resolution::resolution_note("Codex", &resolution))
         };
 
+        let mut fixes = codex_fix_commands(&resolution, execution_error.as_deref());
+        let auth_status = if available {
+            Some(self.probe_auth_status().await)
+        } else {
+            None
+        };
+        if auth_status == Some(EngineAuthStatus::SignedOut) {
+            warnings.push(
+                "Codex is installed but not signed in. Turns will fail until you log in."
+                    .to_string(),
+            );
+            fixes.push("codex login".to_string());
+        }
+
         if available {
             if let Some(warning) = self.sandbox_preflight_warning().await {
                 warnings.push(warning);
@@ -1728,13 +2303,42 @@ impl CodexEngine {
             details,
             warnings,
             checks: codex_health_checks(),
-            fixes: codex_fix_commands(&resolution, execution_error.as_deref()),
+            fixes,
             protocol_diagnostics,
+            timeouts: self.timeouts,
+            auth_status,
         }
     }
 
+    /// Asks the app-server for the account, falling back to the credentials
+    /// file `codex login` writes.
+    async fn probe_auth_status(&self) -> EngineAuthStatus {
+        let response = match self.ensure_ready_transport().await {
+            Ok(transport) => request_with_fallback(
+                transport.as_ref(),
+                AUTH_STATUS_METHODS,
+                serde_json::json!({ "refreshToken": false }),
+                HEALTH_APP_SERVER_TIMEOUT,
+            )
+            .await
+            .ok(),
+            Err(_) => None,
+        };
+        if let Some(status) = response.as_ref().and_then(codex_auth_status_from_response) {
+            return status;
+        }
+        match codex_home_dir() {
+            Some(codex_home) => codex_auth_status_from_home(&codex_home).await,
+            None => EngineAuthStatus::Unknown,
+        }
+    }
+
+    /// Always asks the app-server; [`Self::cached_runtime_models`] is the
+    /// cheap path.
     pub async fn list_models_runtime(&self) -> Vec<ModelInfo> {
-        match self.fetch_models_from_server().await {
+        let fetched = self.fetch_models_from_server().await;
+        self.state.lock().await.model_refresh_in_flight = false;
+        match fetched {
             Ok(models) if !models.is_empty() => {
                 self.store_runtime_model_cache(models.clone()).await;
                 models
@@ -1747,6 +2351,38 @@ impl CodexEngine {
         }
     }
 
+    /// The cached catalog, if any, and whether the caller should refresh it.
+    /// Only one caller is told to refresh a stale entry at a time.
+    pub async fn cached_runtime_models(&self) -> Option<(Vec<ModelInfo>, bool)> {
+        let mut state = self.state.lock().await;
+        let ttl = state
+            .model_cache_ttl
+            .unwrap_or(CODEX_MODEL_CACHE_DEFAULT_TTL);
+        let (fetched_at, models) = state.runtime_model_cache.as_ref()?;
+        let models = models.clone();
+        let needs_refresh = fetched_at.elapsed() >= ttl && !state.model_refresh_in_flight;
+        if needs_refresh {
+            state.model_refresh_in_flight = true;
+        }
+        Some((models, needs_refresh))
+    }
+
+    pub fn set_model_cache_ttl(&self, ttl: Duration) {
+        self.state.blocking_lock().model_cache_ttl = Some(ttl);
+    }
+
+    pub fn set_max_attachment_bytes(&self, max_bytes: u64) {
+        self.state.blocking_lock().max_attachment_bytes = Some(max_bytes);
+    }
+
+    async fn max_attachment_bytes(&self) -> u64 {
+        self.state
+            .lock()
+            .await
+            .max_attachment_bytes
+            .unwrap_or_else(|| AppConfig::default().attachment_max_file_bytes())
+    }
+
     pub async fn runtime_model_fallback(&self) -> Vec<ModelInfo> {
         self.runtime_model_cache_snapshot()
             .await
@@ -1774,53 +2410,56 @@ impl CodexEngine {
         }
     }
 
+    /// Program and arguments that continue `engine_thread_id` in the
+    /// interactive codex CLI.
+    pub async fn cli_resume_command(
+        &self,
+        engine_thread_id: &str,
+    ) -> anyhow::Result<(PathBuf, Vec<String>)> {
+        let resolution = resolve_codex_executable().await;
+        let Some(executable) = resolution.executable.clone() else {
+            anyhow::bail!(codex_unavailable_details(&resolution)
+                .unwrap_or_else(|| CODEX_MISSING_DEFAULT_DETAILS.to_string()));
+        };
+        let version = self.probe_version_from_resolution(&resolution).await.ok();
+        let args = codex_cli_resume_args(version.as_deref(), engine_thread_id)?;
+        Ok((executable, args))
+    }
+
     async fn probe_version_from_resolution(
         &self,
-        resolution: &CodexExecutableResolution,
+        resolution: &ExecutableResolution,
     ) -> Result<String, String> {
         let executable = resolution
             .executable
             .as_ref()
             .ok_or_else(|| CODEX_MISSING_DEFAULT_DETAILS.to_string())?;
         let mut command = codex_command(executable).await;
-        let output = command.arg("--version").output().await.map_err(|error| {
-            format!(
-                "failed to execute `{}`: {error}",
-                executable.to_string_lossy()
-            )
-        })?;
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-            let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            let message = if !stderr.is_empty() {
-                stderr
-            } else if !stdout.is_empty() {
-                stdout
-            } else {
-                format!("process exited with status {}", output.status)
-            };
-            return Err(message);
-        }
-        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if version.is_empty() {
-            return Err("codex --version returned empty output".to_string());
-        }
-        Ok(version)
+        executable_resolution::probe_version(&mut command, &executable.to_string_lossy()).await
     }
 
     async fn probe_transport_ready(&self) -> Option<String> {
         match tokio::time::timeout(HEALTH_APP_SERVER_TIMEOUT, self.ensure_ready_transport()).await {
             Ok(Ok(_)) => None,
             Ok(Err(error)) => Some(format!("failed to initialize `codex app-server`: {error}")),
-            Err(_) => Some(format!(
-                "timed out initializing `codex app-server` after {}s",
-                HEALTH_APP_SERVER_TIMEOUT.as_secs()
-            )),
+            Err(_) => {
+                let stderr_tail = self.transport_stderr_tail_for(&TransportKey::Shared).await;
+                Some(with_stderr_tail(
+                    &format!(
+                        "timed out initializing `codex app-server` after {}s",
+                        HEALTH_APP_SERVER_TIMEOUT.as_secs()
+                    ),
+                    stderr_tail.as_deref(),
+                ))
+            }
         }
     }
 
     pub async fn read_thread_preview(&self, engine_thread_id: &str) -> Option<String> {
-        let transport = self.ensure_ready_transport().await.ok()?;
+        let (_, transport) = self
+            .ensure_ready_thread_transport(engine_thread_id)
+            .await
+            .ok()?;
 
         let params = serde_json::json!({
           "threadId": engine_thread_id,
@@ -1831,7 +2470,7 @@ impl CodexEngine {
             transport.as_ref(),
             THREAD_READ_METHODS,
             params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .ok()?;
@@ -1847,8 +2486,11 @@ impl CodexEngine {
         let transport = self.ensure_ready_transport().await?;
         let search_term = search_term.map(str::to_string);
 
-        let threads =
-            fetch_paginated_data(transport.as_ref(), THREAD_LIST_METHODS, move |cursor| {
+        let threads = fetch_paginated_data(
+            transport.as_ref(),
+            self.timeouts.request,
+            THREAD_LIST_METHODS,
+            move |cursor| {
                 serde_json::json!({
                   "cursor": cursor,
                   "limit": 100,
@@ -1857,9 +2499,10 @@ impl CodexEngine {
                   "sortKey": "updated_at",
                   "sourceKinds": ["appServer"],
                 })
-            })
-            .await
-            .context("failed to list codex threads")?;
+            },
+        )
+        .await
+        .context("failed to list codex threads")?;
 
         Ok(threads
             .iter()
@@ -1883,7 +2526,7 @@ impl CodexEngine {
             transport.as_ref(),
             THREAD_READ_METHODS,
             params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to read codex thread")?;
@@ -1900,7 +2543,7 @@ impl CodexEngine {
         &self,
         engine_thread_id: &str,
     ) -> anyhow::Result<ThreadSyncSnapshot> {
-        let transport = self.ensure_ready_transport().await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
         let params = serde_json::json!({
           "threadId": engine_thread_id,
           "includeTurns": false,
@@ -1910,7 +2553,7 @@ impl CodexEngine {
             transport.as_ref(),
             THREAD_READ_METHODS,
             params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to read codex thread metadata")?;
@@ -1931,14 +2574,19 @@ impl CodexEngine {
         transport: &CodexTransport,
         engine_thread_id: &str,
     ) -> anyhow::Result<Vec<ImportedThreadMessage>> {
-        let turns = match fetch_paginated_data(transport, THREAD_TURNS_LIST_METHODS, |cursor| {
-            serde_json::json!({
-              "threadId": engine_thread_id,
-              "cursor": cursor,
-              "limit": 100,
-              "sortDirection": "asc",
-            })
-        })
+        let turns = match fetch_paginated_data(
+            transport,
+            self.timeouts.request,
+            THREAD_TURNS_LIST_METHODS,
+            |cursor| {
+                serde_json::json!({
+                  "threadId": engine_thread_id,
+                  "cursor": cursor,
+                  "limit": 100,
+                  "sortDirection": "asc",
+                })
+            },
+        )
         .await
         {
             Ok(turns) => turns,
@@ -1969,7 +2617,7 @@ impl CodexEngine {
               "threadId": engine_thread_id,
               "includeTurns": true,
             }),
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to read codex thread turns")?;
@@ -1982,7 +2630,7 @@ impl CodexEngine {
         engine_thread_id: &str,
         expected_turn_id: Option<&str>,
     ) -> anyhow::Result<Option<ReconciledTurnCompletion>> {
-        let transport = self.ensure_ready_transport().await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
         let params = serde_json::json!({
           "threadId": engine_thread_id,
           "includeTurns": true,
@@ -1992,7 +2640,7 @@ impl CodexEngine {
             transport.as_ref(),
             THREAD_READ_METHODS,
             params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to read codex thread turns for reconciliation")?;
@@ -2051,7 +2699,7 @@ impl CodexEngine {
         engine_thread_id: &str,
         name: &str,
     ) -> Result<(), anyhow::Error> {
-        let transport = self.ensure_ready_transport().await?;
+        let (_, transport) = self.ensure_ready_thread_transport(engine_thread_id).await?;
 
         let params = serde_json::json!({
           "threadId": engine_thread_id,
@@ -2062,7 +2710,7 @@ impl CodexEngine {
             transport.as_ref(),
             THREAD_SET_NAME_METHODS,
             params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to set codex thread name")?;
@@ -2091,7 +2739,7 @@ impl CodexEngine {
                 transport.as_ref(),
                 MODEL_LIST_METHODS,
                 params,
-                DEFAULT_TIMEOUT,
+                self.timeouts.request,
             )
             .await?;
 
@@ -2112,28 +2760,130 @@ impl CodexEngine {
         Ok(output)
     }
 
-    async fn ensure_transport(&self) -> anyhow::Result<Arc<CodexTransport>> {
-        if let Some(transport) = self.live_transport().await {
+    /// Routes engine-wide requests (models, skills, account state) that are
+    /// not bound to a thread.
+    async fn ensure_ready_transport(&self) -> anyhow::Result<Arc<CodexTransport>> {
+        self.ensure_ready_transport_for(&TransportKey::Shared).await
+    }
+
+    /// Resolves the transport owning `engine_thread_id`, returning its key so
+    /// failures during the call only invalidate that transport.
+    async fn ensure_ready_thread_transport(
+        &self,
+        engine_thread_id: &str,
+    ) -> anyhow::Result<(TransportKey, Arc<CodexTransport>)> {
+        let key = self.thread_transport_key(engine_thread_id).await;
+        let transport = self.ensure_ready_transport_for(&key).await?;
+        Ok((key, transport))
+    }
+
+    async fn thread_transport_key(&self, engine_thread_id: &str) -> TransportKey {
+        self.state.lock().await.thread_key(engine_thread_id)
+    }
+
+    async fn transport_key_for_cwd(&self, cwd: &str) -> TransportKey {
+        let state = self.state.lock().await;
+        if state.routing.per_workspace {
+            TransportKey::Workspace(normalize_transport_workspace_root(cwd))
+        } else {
+            TransportKey::Shared
+        }
+    }
+
+    pub fn set_transport_routing(&self, routing: CodexTransportRouting) {
+        let mut state = self.state.blocking_lock();
+        state.routing = routing;
+    }
+
+    pub fn turn_diagnostics(&self) -> &TurnDiagnosticsSink {
+        &self.turn_diagnostics
+    }
+
+    /// Returns whether the stderr subscription is still worth polling.
+    fn record_stderr_diagnostic(
+        &self,
+        thread_id: &str,
+        line: Result<TransportStderrLine, broadcast::error::RecvError>,
+    ) -> bool {
+        match line {
+            Ok(line) => {
+                self.turn_diagnostics.record_at(
+                    thread_id,
+                    TurnDiagnosticSourceDto::Stderr,
+                    line.at,
+                    &line.line,
+                );
+                true
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                self.turn_diagnostics.record(
+                    thread_id,
+                    TurnDiagnosticSourceDto::Stderr,
+                    &format!("{skipped} stderr line(s) skipped"),
+                );
+                true
+            }
+            Err(broadcast::error::RecvError::Closed) => false,
+        }
+    }
+
+    async fn ensure_transport_for(
+        &self,
+        key: &TransportKey,
+        notifier: Option<&TransportRestartNotifier<'_>>,
+    ) -> anyhow::Result<Arc<CodexTransport>> {
+        if let Some(transport) = self.live_transport_for(key).await {
             return Ok(transport);
         }
 
-        let _spawn_guard = self.transport_spawn_lock.lock().await;
+        let lifecycle_lock = self.state.lock().await.slot_mut(key).lifecycle_lock.clone();
+        let _spawn_guard = lifecycle_lock.lock().await;
 
-        if let Some(transport) = self.live_transport().await {
+        if let Some(transport) = self.live_transport_for(key).await {
             return Ok(transport);
         }
 
-        let transport = self.spawn_transport_with_backoff().await?;
-        let mut state = self.state.lock().await;
-        state.transport = Some(transport.clone());
-        state.initialized = false;
+        if let Some(notifier) = notifier {
+            let restart_pending = self
+                .state
+                .lock()
+                .await
+                .slot(key)
+                .is_some_and(|slot| slot.restart_pending);
+            if restart_pending {
+                notifier.transport_lost().await;
+            }
+        }
+
+        let transport = self.spawn_transport_with_backoff(key, notifier).await?;
+        self.install_transport(key, transport.clone()).await;
         Ok(transport)
     }
 
-    async fn live_transport(&self) -> Option<Arc<CodexTransport>> {
+    async fn install_transport(&self, key: &TransportKey, transport: Arc<CodexTransport>) {
+        let evicted = {
+            let mut state = self.state.lock().await;
+            let slot = state.slot_mut(key);
+            slot.transport = Some(transport);
+            slot.initialized = false;
+            slot.restart_pending = false;
+            slot.last_used_at = Some(Instant::now());
+            let max = state.routing.max_workspace_transports;
+            state.evict_idle_transports(max, key)
+        };
+
+        for transport in evicted {
+            log::info!("evicting idle codex workspace transport");
+            transport.shutdown().await.ok();
+        }
+    }
+
+    async fn live_transport_for(&self, key: &TransportKey) -> Option<Arc<CodexTransport>> {
         let current = {
-            let state = self.state.lock().await;
-            state.transport.clone()
+            let mut state = self.state.lock().await;
+            let slot = state.transports.get_mut(key)?;
+            slot.last_used_at = Some(Instant::now());
+            slot.transport.clone()
         };
 
         if let Some(transport) = current {
@@ -2141,22 +2891,33 @@ impl CodexEngine {
                 return Some(transport);
             }
 
-            self.invalidate_transport("codex transport is not alive")
+            self.invalidate_transport_for(key, "codex transport is not alive")
                 .await;
         }
 
         None
     }
 
-    async fn ensure_ready_transport(&self) -> anyhow::Result<Arc<CodexTransport>> {
+    async fn ensure_ready_transport_for(
+        &self,
+        key: &TransportKey,
+    ) -> anyhow::Result<Arc<CodexTransport>> {
+        self.ensure_ready_transport_notifying(key, None).await
+    }
+
+    async fn ensure_ready_transport_notifying(
+        &self,
+        key: &TransportKey,
+        notifier: Option<&TransportRestartNotifier<'_>>,
+    ) -> anyhow::Result<Arc<CodexTransport>> {
         let mut backoff = TRANSPORT_RESTART_BASE_BACKOFF;
         let mut last_error: Option<anyhow::Error> = None;
 
         for attempt in 0..TRANSPORT_RESTART_MAX_ATTEMPTS {
-            let transport = self.ensure_transport().await?;
-            match self.ensure_initialized(&transport).await {
+            let transport = self.ensure_transport_for(key, notifier).await?;
+            match self.ensure_initialized(key, &transport).await {
                 Ok(()) => {
-                    self.ensure_runtime_monitor_started(&transport).await;
+                    self.ensure_runtime_monitor_started(key, &transport).await;
                     return Ok(transport);
                 }
                 Err(error) => {
@@ -2165,11 +2926,21 @@ impl CodexEngine {
                         attempt + 1,
                         TRANSPORT_RESTART_MAX_ATTEMPTS
                     );
+                    let error = match transport_stderr_tail(&transport) {
+                        Some(stderr_tail) => anyhow::anyhow!(with_stderr_tail(
+                            &format!("{error:#}"),
+                            Some(&stderr_tail)
+                        )),
+                        None => error,
+                    };
                     log::warn!("{message}: {error}");
                     last_error = Some(error);
-                    self.invalidate_transport(&message).await;
+                    self.invalidate_transport_for(key, &message).await;
 
                     if attempt + 1 < TRANSPORT_RESTART_MAX_ATTEMPTS {
+                        if let Some(notifier) = notifier {
+                            notifier.retrying(attempt + 2, backoff).await;
+                        }
                         tokio::time::sleep(backoff).await;
                         backoff =
                             std::cmp::min(backoff.saturating_mul(2), TRANSPORT_RESTART_MAX_BACKOFF);
@@ -2183,12 +2954,23 @@ impl CodexEngine {
         }))
     }
 
-    async fn spawn_transport_with_backoff(&self) -> anyhow::Result<Arc<CodexTransport>> {
-        let resolution = resolve_codex_executable().await;
-        let codex_executable = resolution.executable.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(codex_unavailable_details(&resolution)
-                .unwrap_or_else(|| CODEX_MISSING_DEFAULT_DETAILS.to_string()))
-        })?;
+    async fn spawn_transport_with_backoff(
+        &self,
+        key: &TransportKey,
+        notifier: Option<&TransportRestartNotifier<'_>>,
+    ) -> anyhow::Result<Arc<CodexTransport>> {
+        let codex_executable = if let TransportKey::Executable(executable) = key {
+            // The binary may have been removed or rebuilt since the thread
+            // was pinned to it.
+            executable_resolution::validate_executable_override(executable)?;
+            executable.clone()
+        } else {
+            let resolution = resolve_codex_executable().await;
+            resolution.executable.clone().ok_or_else(|| {
+                anyhow::anyhow!(codex_unavailable_details(&resolution)
+                    .unwrap_or_else(|| CODEX_MISSING_DEFAULT_DETAILS.to_string()))
+            })?
+        };
 
         let mut backoff = TRANSPORT_RESTART_BASE_BACKOFF;
         let mut last_error: Option<anyhow::Error> = None;
@@ -2204,6 +2986,9 @@ impl CodexEngine {
                     );
                     last_error = Some(error);
                     if attempt + 1 < TRANSPORT_RESTART_MAX_ATTEMPTS {
+                        if let Some(notifier) = notifier {
+                            notifier.retrying(attempt + 2, backoff).await;
+                        }
                         tokio::time::sleep(backoff).await;
                         backoff =
                             std::cmp::min(backoff.saturating_mul(2), TRANSPORT_RESTART_MAX_BACKOFF);
@@ -2216,20 +3001,39 @@ impl CodexEngine {
             .unwrap_or_else(|| anyhow::anyhow!("unable to spawn codex transport after retries")))
     }
 
-    async fn invalidate_transport(&self, reason: &str) {
+    /// Recent stderr of the transport in `key`'s slot, even if it is not
+    /// initialized yet.
+    async fn transport_stderr_tail_for(&self, key: &TransportKey) -> Option<String> {
+        let transport = self.state.lock().await.slot(key)?.transport.clone()?;
+        transport_stderr_tail(&transport)
+    }
+
+    /// Tears down one transport and the runtimes bound to it. Other
+    /// transports keep their threads, approvals, and active turns; the
+    /// engine-wide sandbox probe and model cache are only dropped with the
+    /// shared transport.
+    async fn invalidate_transport_for(&self, key: &TransportKey, reason: &str) {
         let transport = {
             let mut state = self.state.lock().await;
-            let transport = state.transport.take();
-            state.initialized = false;
-            state.approval_requests.clear();
-            state.active_turn_ids.clear();
-            state.thread_runtimes.clear();
-            state.sandbox_probe_completed = false;
-            state.force_external_sandbox = false;
-            if let Some(diagnostics) = state.protocol_diagnostics.as_mut() {
-                diagnostics.stale = true;
+            let transport = state.transports.get_mut(key).and_then(|slot| {
+                let transport = slot.transport.take();
+                slot.restart_pending |= transport.is_some();
+                slot.initialized = false;
+                slot.approval_requests.clear();
+                slot.active_turn_ids.clear();
+                slot.thread_runtimes.clear();
+                slot.runtime_monitor_transport_tag = None;
+                transport
+            });
+            if *key == TransportKey::Shared {
+                state.sandbox_probe_completed = false;
+                state.force_external_sandbox = false;
+                // A reset may come from a codex upgrade with a different catalog.
+                state.runtime_model_cache = None;
+                if let Some(diagnostics) = state.protocol_diagnostics.as_mut() {
+                    diagnostics.stale = true;
+                }
             }
-            state.runtime_monitor_transport_tag = None;
             transport
         };
 
@@ -2239,9 +3043,29 @@ impl CodexEngine {
         }
     }
 
-    async fn ensure_initialized(&self, transport: &CodexTransport) -> anyhow::Result<()> {
-        let mut state = self.state.lock().await;
-        if state.initialized {
+    async fn ensure_initialized(
+        &self,
+        key: &TransportKey,
+        transport: &Arc<CodexTransport>,
+    ) -> anyhow::Result<()> {
+        // Initialize under the slot's lifecycle lock rather than the engine
+        // state lock so a slow handshake in one workspace doesn't stall others.
+        let lifecycle_lock = {
+            let mut state = self.state.lock().await;
+            let slot = state.slot_mut(key);
+            if slot.initialized {
+                return Ok(());
+            }
+            slot.lifecycle_lock.clone()
+        };
+        let _guard = lifecycle_lock.lock().await;
+        if self
+            .state
+            .lock()
+            .await
+            .slot(key)
+            .is_some_and(|slot| slot.initialized)
+        {
             return Ok(());
         }
 
@@ -2257,10 +3081,10 @@ impl CodexEngine {
         });
 
         request_with_fallback(
-            transport,
+            transport.as_ref(),
             INITIALIZE_METHODS,
             initialize_params,
-            DEFAULT_TIMEOUT,
+            self.timeouts.request,
         )
         .await
         .context("failed to initialize codex app-server")?;
@@ -2270,7 +3094,15 @@ impl CodexEngine {
             .await
             .context("failed to send initialized notification to codex app-server")?;
 
-        state.initialized = true;
+        let mut state = self.state.lock().await;
+        let slot = state.slot_mut(key);
+        if slot
+            .transport
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, transport))
+        {
+            slot.initialized = true;
+        }
 
         Ok(())
     }
@@ -2400,16 +3232,22 @@ impl CodexEngine {
         }
     }
 
-    async fn ensure_runtime_monitor_started(&self, transport: &Arc<CodexTransport>) {
+    async fn ensure_runtime_monitor_started(
+        &self,
+        key: &TransportKey,
+        transport: &Arc<CodexTransport>,
+    ) {
         let transport_tag = Arc::as_ptr(transport) as usize;
         {
             let mut state = self.state.lock().await;
-            if state.runtime_monitor_transport_tag == Some(transport_tag) {
+            let slot = state.slot_mut(key);
+            if slot.runtime_monitor_transport_tag == Some(transport_tag) {
                 return;
             }
-            state.runtime_monitor_transport_tag = Some(transport_tag);
+            slot.runtime_monitor_transport_tag = Some(transport_tag);
         }
 
+        let key = key.clone();
         let transport = transport.clone();
         let state = self.state.clone();
         let runtime_events = self.runtime_events.clone();
@@ -2698,9 +3536,12 @@ impl CodexEngine {
                                     .or_else(|| params.get("request_id"))
                                     .cloned();
                                 if let Some(request_id) = request_id {
-                                    if let Some(approval_id) =
-                                        resolve_pending_approval_request(state.clone(), &request_id)
-                                            .await
+                                    if let Some(approval_id) = resolve_pending_approval_request(
+                                        state.clone(),
+                                        &key,
+                                        &request_id,
+                                    )
+                                    .await
                                     {
                                         log::debug!(
                                             "codex server request resolved approval: approval_id={approval_id}"
@@ -2848,7 +3689,11 @@ impl CodexEngine {
         self.set_force_external_sandbox(true).await;
 
         let mut state = self.state.lock().await;
-        if let Some(runtime) = state.thread_runtimes.get_mut(engine_thread_id) {
+        if let Some(runtime) = state
+            .thread_slot_mut(engine_thread_id)
+            .thread_runtimes
+            .get_mut(engine_thread_id)
+        {
             let allow_network = sandbox_policy_network_enabled(&runtime.sandbox_policy);
             runtime.sandbox_policy = serde_json::json!({
               "type": "externalSandbox",
@@ -2859,12 +3704,13 @@ impl CodexEngine {
 
     async fn register_approval_request(
         &self,
+        key: &TransportKey,
         approval_id: &str,
         raw_request_id: &serde_json::Value,
         method: &str,
     ) {
         let mut state = self.state.lock().await;
-        state.approval_requests.insert(
+        state.slot_mut(key).approval_requests.insert(
             approval_id.to_string(),
             PendingApproval {
                 raw_request_id: raw_request_id.clone(),
@@ -2873,69 +3719,128 @@ impl CodexEngine {
         );
     }
 
-    async fn approval_request(&self, approval_id: &str) -> Option<PendingApproval> {
+    /// Looks up a pending approval and the transport that must receive the
+    /// answer.
+    async fn approval_request(&self, approval_id: &str) -> Option<(TransportKey, PendingApproval)> {
         let state = self.state.lock().await;
-        state.approval_requests.get(approval_id).cloned()
+        let key = state.approval_key(approval_id)?;
+        let pending = state
+            .slot(&key)?
+            .approval_requests
+            .get(approval_id)?
+            .clone();
+        Some((key, pending))
     }
 
     async fn take_approval_request(&self, approval_id: &str) -> Option<PendingApproval> {
         let mut state = self.state.lock().await;
-        state.approval_requests.remove(approval_id)
+        let key = state.approval_key(approval_id)?;
+        state.slot_mut(&key).approval_requests.remove(approval_id)
     }
 
     async fn set_active_turn(&self, engine_thread_id: &str, turn_id: &str) {
         let mut state = self.state.lock().await;
         state
+            .thread_slot_mut(engine_thread_id)
             .active_turn_ids
             .insert(engine_thread_id.to_string(), turn_id.to_string());
     }
 
     async fn clear_active_turn(&self, engine_thread_id: &str) {
         let mut state = self.state.lock().await;
-        state.active_turn_ids.remove(engine_thread_id);
+        state
+            .thread_slot_mut(engine_thread_id)
+            .active_turn_ids
+            .remove(engine_thread_id);
     }
 
     async fn active_turn_id(&self, engine_thread_id: &str) -> Option<String> {
         let state = self.state.lock().await;
-        state.active_turn_ids.get(engine_thread_id).cloned()
+        state
+            .thread_slot(engine_thread_id)?
+            .active_turn_ids
+            .get(engine_thread_id)
+            .cloned()
     }
 
-    async fn store_thread_runtime(&self, engine_thread_id: &str, runtime: ThreadRuntime) {
+    /// Binds the thread to the transport at `key` and records its runtime
+    /// there, dropping any runtime left on a previously bound transport.
+    async fn store_thread_runtime(
+        &self,
+        key: &TransportKey,
+        engine_thread_id: &str,
+        runtime: ThreadRuntime,
+    ) {
         let mut state = self.state.lock().await;
+        let previous = state
+            .thread_transport_keys
+            .insert(engine_thread_id.to_string(), key.clone());
+        if let Some(previous) = previous.filter(|previous| previous != key) {
+            if let Some(slot) = state.transports.get_mut(&previous) {
+                slot.thread_runtimes.remove(engine_thread_id);
+            }
+        }
         state
+            .slot_mut(key)
             .thread_runtimes
             .insert(engine_thread_id.to_string(), runtime);
     }
 
+    async fn bind_thread_transport(&self, engine_thread_id: &str, key: &TransportKey) {
+        let mut state = self.state.lock().await;
+        state
+            .thread_transport_keys
+            .insert(engine_thread_id.to_string(), key.clone());
+    }
+
     async fn set_thread_native_plan_mode_active(&self, engine_thread_id: &str, active: bool) {
         let mut state = self.state.lock().await;
-        if let Some(runtime) = state.thread_runtimes.get_mut(engine_thread_id) {
+        if let Some(runtime) = state
+            .thread_slot_mut(engine_thread_id)
+            .thread_runtimes
+            .get_mut(engine_thread_id)
+        {
             runtime.native_plan_mode_active = active;
         }
     }
 
     async fn store_runtime_model_cache(&self, models: Vec<ModelInfo>) {
         let mut state = self.state.lock().await;
-        state.runtime_model_cache = Some(models);
+        state.runtime_model_cache = Some((Instant::now(), models));
     }
 
     async fn runtime_model_cache_snapshot(&self) -> Option<Vec<ModelInfo>> {
         let state = self.state.lock().await;
-        state.runtime_model_cache.clone()
+        state
+            .runtime_model_cache
+            .as_ref()
+            .map(|(_, models)| models.clone())
     }
 
     async fn thread_runtime(&self, engine_thread_id: &str) -> Option<ThreadRuntime> {
         let state = self.state.lock().await;
-        state.thread_runtimes.get(engine_thread_id).cloned()
+        state
+            .thread_slot(engine_thread_id)?
+            .thread_runtimes
+            .get(engine_thread_id)
+            .cloned()
     }
 
-    async fn can_reuse_live_thread(&self, engine_thread_id: &str) -> bool {
+    /// A live thread can only be reused on the transport that loaded it, so
+    /// a thread whose workspace now routes elsewhere is resumed instead.
+    async fn can_reuse_live_thread(&self, key: &TransportKey, engine_thread_id: &str) -> bool {
         let (transport, initialized, known_thread) = {
             let state = self.state.lock().await;
+            if state.thread_key(engine_thread_id) != *key {
+                return false;
+            }
+            let Some(slot) = state.slot(key) else {
+                return false;
+            };
             (
-                state.transport.clone(),
-                state.initialized,
-                state.thread_runtimes.contains_key(engine_thread_id),
+                slot.transport.clone(),
+                slot.initialized,
+                slot.thread_runtimes.contains_key(engine_thread_id),
             )
         };
 
@@ -3060,40 +3965,51 @@ fn map_codex_model(value: CodexModel) -> ModelInfo {
     }
 }
 
-pub async fn resolve_codex_executable() -> CodexExecutableResolution {
-    let app_path = std::env::var("PATH").ok();
-
-    if let Some(path) = runtime_env::resolve_executable("codex") {
-        return CodexExecutableResolution {
-            executable: Some(path),
-            source: "app-path",
-            app_path,
-            login_shell_executable: None,
-        };
-    }
+pub async fn resolve_codex_executable() -> ExecutableResolution {
+    executable_resolution::resolve_cli_executable("codex").await
+}
 
-    let login_shell_executable = detect_codex_via_login_shell().await;
-    let executable = login_shell_executable.clone();
+/// First CLI release with `codex resume <SESSION_ID>`.
+const CODEX_CLI_RESUME_MIN_VERSION: (u64, u64, u64) = (0, 39, 0);
 
-    CodexExecutableResolution {
-        executable,
-        source: if login_shell_executable.is_some() {
-            "login-shell"
-        } else {
-            "unavailable"
-        },
-        app_path,
-        login_shell_executable,
+/// Arguments that reopen `engine_thread_id` in the codex TUI. `version` is the
+/// raw `codex --version` output; when it is missing or unreadable the current
+/// syntax is assumed.
+fn codex_cli_resume_args(
+    version: Option<&str>,
+    engine_thread_id: &str,
+) -> anyhow::Result<Vec<String>> {
+    if let Some((major, minor, patch)) = version.and_then(parse_codex_cli_version) {
+        if (major, minor, patch) < CODEX_CLI_RESUME_MIN_VERSION {
+            let (min_major, min_minor, min_patch) = CODEX_CLI_RESUME_MIN_VERSION;
+            anyhow::bail!(
+                "Codex CLI {major}.{minor}.{patch} cannot resume a thread by id; update to {min_major}.{min_minor}.{min_patch} or later"
+            );
+        }
     }
+    Ok(vec!["resume".to_string(), engine_thread_id.to_string()])
 }
 
-fn codex_unavailable_details(resolution: &CodexExecutableResolution) -> Option<String> {
+/// Reads `codex-cli 0.46.0` (or a bare `0.46.0-alpha.1`) as a version triple.
+fn parse_codex_cli_version(output: &str) -> Option<(u64, u64, u64)> {
+    let token = output
+        .split_whitespace()
+        .find(|token| token.starts_with(|ch: char| ch.is_ascii_digit()))?;
+    let core = token.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+fn codex_unavailable_details(resolution: &ExecutableResolution) -> Option<String> {
     codex_unavailable_details_for_platform(runtime_env::platform_id(), resolution)
 }
 
 fn codex_unavailable_details_for_platform(
     platform: &str,
-    resolution: &CodexExecutableResolution,
+    resolution: &ExecutableResolution,
 ) -> Option<String> {
     if resolution.executable.is_some() {
         return None;
@@ -3123,13 +4039,13 @@ fn codex_unavailable_details_for_platform(
     }
 }
 
-fn codex_execution_failure_details(resolution: &CodexExecutableResolution, error: &str) -> String {
+fn codex_execution_failure_details(resolution: &ExecutableResolution, error: &str) -> String {
     codex_execution_failure_details_for_platform(runtime_env::platform_id(), resolution, error)
 }
 
 fn codex_execution_failure_details_for_platform(
     platform: &str,
-    resolution: &CodexExecutableResolution,
+    resolution: &ExecutableResolution,
     error: &str,
 ) -> String {
     let path_preview = app_path_preview(resolution.app_path.as_deref());
@@ -3165,19 +4081,6 @@ fn codex_execution_failure_details_for_platform(
     )
 }
 
-fn codex_resolution_note(resolution: &CodexExecutableResolution) -> Option<String> {
-    if resolution.source == "app-path" {
-        return None;
-    }
-
-    let executable = resolution.executable.as_ref()?;
-    Some(format!(
-        "Codex detected via {} at `{}`.",
-        resolution.source,
-        executable.display()
-    ))
-}
-
 fn codex_health_checks() -> Vec<String> {
     codex_health_checks_for_platform(runtime_env::platform_id())
 }
@@ -3211,8 +4114,44 @@ fn codex_health_checks_for_platform(platform: &str) -> Vec<String> {
     checks
 }
 
+/// Reads `account/read` (`account`) or `getAuthStatus` (`authMethod`)
+/// responses; `None` when the response has neither field.
+fn codex_auth_status_from_response(response: &serde_json::Value) -> Option<EngineAuthStatus> {
+    let credentials = response
+        .get("account")
+        .or_else(|| response.get("authMethod"))?;
+    if !credentials.is_null() {
+        return Some(EngineAuthStatus::SignedIn);
+    }
+    let requires_openai_auth = response
+        .get("requiresOpenaiAuth")
+        .or_else(|| response.get("requires_openai_auth"))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(true);
+    Some(if requires_openai_auth {
+        EngineAuthStatus::SignedOut
+    } else {
+        EngineAuthStatus::NotRequired
+    })
+}
+
+fn codex_home_dir() -> Option<PathBuf> {
+    env::var_os("CODEX_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| runtime_env::home_dir().map(|home| home.join(".codex")))
+}
+
+async fn codex_auth_status_from_home(codex_home: &Path) -> EngineAuthStatus {
+    match tokio_fs::try_exists(codex_home.join("auth.json")).await {
+        Ok(true) => EngineAuthStatus::SignedIn,
+        Ok(false) => EngineAuthStatus::SignedOut,
+        Err(_) => EngineAuthStatus::Unknown,
+    }
+}
+
 fn codex_fix_commands(
-    resolution: &CodexExecutableResolution,
+    resolution: &ExecutableResolution,
     execution_error: Option<&str>,
 ) -> Vec<String> {
     codex_fix_commands_for_platform(runtime_env::platform_id(), resolution, execution_error)
@@ -3220,76 +4159,16 @@ fn codex_fix_commands(
 
 fn codex_fix_commands_for_platform(
     platform: &str,
-    resolution: &CodexExecutableResolution,
+    resolution: &ExecutableResolution,
     execution_error: Option<&str>,
 ) -> Vec<String> {
-    if platform == "macos" {
-        let mut fixes = Vec::new();
-        if resolution.executable.is_none() {
-            if let Some(shell_path) = &resolution.login_shell_executable {
-                if let Some(bin_dir) = shell_path.parent() {
-                    fixes.push(format!(
-                        "launchctl setenv PATH \"{}:/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin\"",
-                        bin_dir.display()
-                    ));
-                    fixes.push("open -a Panes".to_string());
-                }
-            } else {
-                fixes.push("/bin/zsh -lic 'command -v codex && codex --version'".to_string());
-                fixes.push("open -a Panes".to_string());
-            }
-        } else if execution_error.is_some() {
-            if let Some(executable) = resolution.executable.as_ref() {
-                if let Some(bin_dir) = executable.parent() {
-                    fixes.push(format!(
-                        "launchctl setenv PATH \"{}:/opt/homebrew/bin:/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin\"",
-                        bin_dir.display()
-                    ));
-                }
-            }
-            fixes.push(
-                "/bin/zsh -lic 'command -v node && command -v codex && codex --version'"
-                    .to_string(),
-            );
-            fixes.push("open -a Panes".to_string());
-        }
-
-        return fixes;
-    }
-
-    if platform == "windows" {
-        let mut fixes = Vec::new();
-        if resolution.executable.is_none() {
-            fixes.push("npm install -g @openai/codex".to_string());
-            fixes.push("where codex".to_string());
-            fixes.push("echo %APPDATA%".to_string());
-            fixes.push(
-                "Ensure `%APPDATA%\\npm` is present in PATH, then restart Panes.".to_string(),
-            );
-            return fixes;
-        }
-
-        if execution_error.is_some() {
-            fixes.push("where node".to_string());
-            fixes.push("where codex".to_string());
-            fixes.push("echo %PATH%".to_string());
-            fixes.push(
-                "Ensure Node.js 20+ is installed and visible to Panes, then restart the app."
-                    .to_string(),
-            );
-        }
-        return fixes;
-    }
-
-    let _ = resolution;
-    let _ = execution_error;
-    Vec::new()
-}
-
-fn app_path_preview(path: Option<&str>) -> String {
-    path.filter(|value| !value.trim().is_empty())
-        .unwrap_or("(empty)")
-        .to_string()
+    executable_resolution::path_fix_commands_for_platform(
+        platform,
+        "codex",
+        "@openai/codex",
+        resolution,
+        execution_error,
+    )
 }
 
 fn codex_augmented_path(executable: &Path) -> Option<OsString> {
@@ -3311,86 +4190,13 @@ async fn codex_command(executable: &Path) -> Command {
     command
 }
 
-async fn detect_codex_via_login_shell() -> Option<PathBuf> {
-    #[cfg(target_os = "windows")]
-    {
-        for powershell in runtime_env::windows_login_probe_shells() {
-            let mut cmd = Command::new(&powershell);
-            cmd.args([
-                "-NoLogo",
-                "-Command",
-                "(Get-Command codex -ErrorAction SilentlyContinue | Select-Object -First 1).Source",
-            ]);
-            process_utils::configure_tokio_command(&mut cmd);
-
-            let Ok(Ok(output)) = timeout(Duration::from_secs(10), cmd.output()).await else {
-                continue;
-            };
-            if !output.status.success() {
-                continue;
-            }
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let Some(path) = runtime_env::parse_windows_single_path_output(&stdout) else {
-                continue;
-            };
-
-            let path = PathBuf::from(path);
-            if path.is_file() {
-                return Some(path);
-            }
-        }
-        None
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        for shell in runtime_env::login_probe_shells() {
-            let output = match timeout(
-                LOGIN_SHELL_PROBE_TIMEOUT,
-                Command::new(&shell)
-                    .args(runtime_env::login_probe_shell_args(
-                        &shell,
-                        "command -v codex",
-                    ))
-                    .output(),
-            )
-            .await
-            {
-                Err(_) => {
-                    log::warn!(
-                        "timed out probing Codex via login shell `{}`",
-                        shell.display()
-                    );
-                    continue;
-                }
-                Ok(Ok(output)) if output.status.success() => output,
-                Ok(Ok(_)) => continue,
-                Ok(Err(_)) => continue,
-            };
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(path) = stdout
-                .lines()
-                .map(str::trim)
-                .find(|line| line.starts_with('/'))
-                .map(PathBuf::from)
-                .filter(|path| runtime_env::is_executable_file(path))
-            {
-                return Some(path);
-            }
-        }
-
-        None
-    }
-}
-
 async fn request_turn_start(
     transport: &CodexTransport,
     thread_id: &str,
     runtime: Option<ThreadRuntime>,
     input: TurnInput,
     plan_mode_activation: PlanModeActivation,
+    timeout: Duration,
 ) -> anyhow::Result<TurnStartOutcome> {
     let runtime_ref = runtime.as_ref();
     let uses_native_collaboration_mode =
@@ -3398,14 +4204,7 @@ async fn request_turn_start(
 
     let primary_params =
         build_turn_start_params(thread_id, runtime_ref, &input, plan_mode_activation).await?;
-    match request_with_fallback(
-        transport,
-        TURN_START_METHODS,
-        primary_params,
-        TURN_REQUEST_TIMEOUT,
-    )
-    .await
-    {
+    match request_with_fallback(transport, TURN_START_METHODS, primary_params, timeout).await {
         Ok(result) => Ok(TurnStartOutcome {
             result,
             native_plan_mode_active: input.plan_mode && uses_native_collaboration_mode,
@@ -3430,14 +4229,10 @@ async fn request_turn_start(
             let fallback_params =
                 build_turn_start_params(thread_id, runtime_ref, &input, fallback_activation)
                     .await?;
-            let result = request_with_fallback(
-                transport,
-                TURN_START_METHODS,
-                fallback_params,
-                TURN_REQUEST_TIMEOUT,
-            )
-            .await
-            .context("codex turn/start request failed after plan-mode fallback")?;
+            let result =
+                request_with_fallback(transport, TURN_START_METHODS, fallback_params, timeout)
+                    .await
+                    .context("codex turn/start request failed after plan-mode fallback")?;
 
             Ok(TurnStartOutcome {
                 result,
@@ -3452,6 +4247,7 @@ async fn request_turn_steer(
     thread_id: &str,
     expected_turn_id: &str,
     input: &TurnInput,
+    timeout: Duration,
 ) -> anyhow::Result<serde_json::Value> {
     let params = serde_json::json!({
       "threadId": thread_id,
@@ -3459,7 +4255,7 @@ async fn request_turn_steer(
       "input": build_turn_input_items(input, false).await?,
     });
 
-    request_with_fallback(transport, TURN_STEER_METHODS, params, TURN_REQUEST_TIMEOUT)
+    request_with_fallback(transport, TURN_STEER_METHODS, params, timeout)
         .await
         .context("codex turn/steer request failed")
 }
@@ -3589,13 +4385,11 @@ async fn build_turn_input_items(
         }
     }
 
+    let mut inline_image_budget = MAX_INLINE_IMAGE_BYTES_PER_TURN;
     for attachment in &input.attachments {
         match attachment_input_kind(attachment) {
             Some(AttachmentInputKind::Image) => {
-                items.push(serde_json::json!({
-                  "type": "localImage",
-                  "path": attachment.file_path,
-                }));
+                items.push(image_input_item(attachment, &mut inline_image_budget).await?);
             }
             Some(AttachmentInputKind::Text) => {
                 let text_payload = read_text_attachment_for_turn_input(attachment).await?;
@@ -3605,12 +4399,7 @@ async fn build_turn_input_items(
                   "text_elements": [],
                 }));
             }
-            None => {
-                anyhow::bail!(
-                    "Attachment `{}` is not supported by Codex app-server. Only image and text attachments are currently supported.",
-                    attachment.file_name
-                );
-            }
+            None => anyhow::bail!(unsupported_attachment_message(attachment)),
         }
     }
 
@@ -3740,7 +4529,10 @@ fn is_plan_mode_protocol_error(error: &str) -> bool {
         || (value.contains("unknown field") && value.contains("plan"))
 }
 
-async fn validate_turn_attachments(attachments: &[TurnAttachment]) -> anyhow::Result<()> {
+async fn validate_turn_attachments(
+    attachments: &[TurnAttachment],
+    max_attachment_bytes: u64,
+) -> anyhow::Result<()> {
     if attachments.len() > MAX_ATTACHMENTS_PER_TURN {
         anyhow::bail!("You can attach at most {MAX_ATTACHMENTS_PER_TURN} files per turn.");
     }
@@ -3752,10 +4544,7 @@ async fn validate_turn_attachments(attachments: &[TurnAttachment]) -> anyhow::Re
         }
 
         if attachment_input_kind(attachment).is_none() {
-            anyhow::bail!(
-                "Attachment `{}` is not supported by Codex app-server. Only image and text attachments are currently supported.",
-                attachment.file_name
-            );
+            anyhow::bail!(unsupported_attachment_message(attachment));
         }
 
         let metadata = tokio_fs::metadata(path).await.with_context(|| {
@@ -3765,10 +4554,11 @@ async fn validate_turn_attachments(attachments: &[TurnAttachment]) -> anyhow::Re
             )
         })?;
         let size_bytes = std::cmp::max(metadata.len(), attachment.size_bytes);
-        if size_bytes > MAX_ATTACHMENT_BYTES {
+        if size_bytes > max_attachment_bytes {
             anyhow::bail!(
-                "Attachment `{}` exceeds the 10 MB per-file limit.",
-                attachment.file_name
+                "Attachment `{}` exceeds the {} MB per-file limit.",
+                attachment.file_name,
+                max_attachment_bytes.div_ceil(1024 * 1024)
             );
         }
     }
@@ -3776,6 +4566,23 @@ async fn validate_turn_attachments(attachments: &[TurnAttachment]) -> anyhow::Re
     Ok(())
 }
 
+/// Removes attachments Codex has no input item for and returns them, so the
+/// rest of the turn can still be sent.
+fn take_unsupported_attachments(input: &mut TurnInput) -> Vec<TurnAttachment> {
+    let (supported, unsupported) = std::mem::take(&mut input.attachments)
+        .into_iter()
+        .partition(|attachment| attachment_input_kind(attachment).is_some());
+    input.attachments = supported;
+    unsupported
+}
+
+fn unsupported_attachment_message(attachment: &TurnAttachment) -> String {
+    format!(
+        "Attachment `{}` is not supported by Codex app-server. Only image and text attachments are currently supported.",
+        attachment.file_name
+    )
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AttachmentInputKind {
     Image,
@@ -3874,6 +4681,76 @@ fn is_supported_text_extension(path: &str) -> bool {
     )
 }
 
+/// Raster images go inline as a `data:` URL so the app-server never has to
+/// read the file itself. Formats vision models don't take, and images past
+/// the turn's inline budget, are still sent by path.
+async fn image_input_item(
+    attachment: &TurnAttachment,
+    inline_budget: &mut u64,
+) -> anyhow::Result<serde_json::Value> {
+    let by_path = || {
+        serde_json::json!({
+          "type": "localImage",
+          "path": attachment.file_path,
+        })
+    };
+    let Some(media_type) = inline_image_media_type(attachment) else {
+        return Ok(by_path());
+    };
+    let unreadable = || {
+        format!(
+            "Attachment `{}` could not be read at `{}`",
+            attachment.file_name, attachment.file_path
+        )
+    };
+    // Images over the budget go by path without being read; the length is
+    // checked again after the read in case the file grew meanwhile.
+    let metadata = tokio_fs::metadata(attachment.file_path.trim())
+        .await
+        .with_context(unreadable)?;
+    if metadata.len() > *inline_budget {
+        return Ok(by_path());
+    }
+    let bytes = tokio_fs::read(attachment.file_path.trim())
+        .await
+        .with_context(unreadable)?;
+    let len = bytes.len() as u64;
+    if len > *inline_budget {
+        return Ok(by_path());
+    }
+    *inline_budget -= len;
+    Ok(serde_json::json!({
+      "type": "image",
+      "url": format!("data:{media_type};base64,{}", BASE64.encode(&bytes)),
+    }))
+}
+
+fn inline_image_media_type(attachment: &TurnAttachment) -> Option<&'static str> {
+    let from_mime = match attachment.mime_type.as_deref().map(str::to_lowercase) {
+        Some(mime) => match mime.as_str() {
+            "image/png" => Some("image/png"),
+            "image/jpeg" | "image/jpg" => Some("image/jpeg"),
+            "image/gif" => Some("image/gif"),
+            "image/webp" => Some("image/webp"),
+            _ => None,
+        },
+        None => None,
+    };
+    from_mime.or_else(|| {
+        let extension = Path::new(&attachment.file_name)
+            .extension()
+            .and_then(|value| value.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("png") => Some("image/png"),
+            Some("jpg") | Some("jpeg") => Some("image/jpeg"),
+            Some("gif") => Some("image/gif"),
+            Some("webp") => Some("image/webp"),
+            _ => None,
+        }
+    })
+}
+
 async fn read_text_attachment_for_turn_input(
     attachment: &TurnAttachment,
 ) -> anyhow::Result<String> {
@@ -3929,6 +4806,16 @@ async fn request_with_fallback(
     anyhow::bail!("all rpc methods failed: {}", errors.join(" | "))
 }
 
+fn normalize_transport_workspace_root(cwd: &str) -> String {
+    let trimmed = cwd.trim();
+    let without_trailing = trimmed.trim_end_matches(['/', '\\']);
+    if without_trailing.is_empty() {
+        trimmed.to_string()
+    } else {
+        without_trailing.to_string()
+    }
+}
+
 fn scope_cwd(scope: &ThreadScope) -> String {
     match scope {
         ThreadScope::Repo { repo_path } => repo_path.to_string(),
@@ -3936,6 +4823,7 @@ fn scope_cwd(scope: &ThreadScope) -> String {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_thread_resume_params(
     thread_id: &str,
     model: &str,
@@ -3946,6 +4834,7 @@ fn build_thread_resume_params(
     approvals_reviewer: Option<&str>,
     service_tier: Option<&str>,
     personality: Option<&str>,
+    developer_instructions: Option<&str>,
 ) -> serde_json::Value {
     let mut params = serde_json::Map::new();
     params.insert(
@@ -3965,6 +4854,7 @@ fn build_thread_resume_params(
     insert_optional_string(&mut params, "approvalsReviewer", approvals_reviewer);
     insert_optional_string(&mut params, "serviceTier", service_tier);
     insert_optional_string(&mut params, "personality", personality);
+    insert_optional_string(&mut params, "developerInstructions", developer_instructions);
     params.insert(
         "persistExtendedHistory".to_string(),
         serde_json::Value::Bool(false),
@@ -4001,6 +4891,11 @@ fn build_thread_start_params(
     );
     insert_optional_string(&mut params, "serviceTier", sandbox.service_tier.as_deref());
     insert_optional_string(&mut params, "personality", sandbox.personality.as_deref());
+    insert_optional_string(
+        &mut params,
+        "developerInstructions",
+        sandbox.custom_instructions.as_deref(),
+    );
     params.insert(
         "experimentalRawEvents".to_string(),
         serde_json::Value::Bool(false),
@@ -4046,6 +4941,11 @@ fn build_thread_fork_params(
     );
     insert_optional_string(&mut params, "serviceTier", sandbox.service_tier.as_deref());
     insert_optional_string(&mut params, "personality", sandbox.personality.as_deref());
+    insert_optional_string(
+        &mut params,
+        "developerInstructions",
+        sandbox.custom_instructions.as_deref(),
+    );
     serde_json::Value::Object(params)
 }
 
@@ -4505,6 +5405,7 @@ fn thread_runtime_from_start_response(
         service_tier: extract_any_string(response, &["serviceTier", "service_tier"]),
         personality: extract_any_string(response, &["personality"]),
         output_schema: fallback_output_schema,
+        developer_instructions: None,
         native_plan_mode_active: false,
     };
 
@@ -4551,6 +5452,7 @@ fn thread_runtime_from_resume_response(
     runtime.service_tier = requested_runtime.service_tier.clone();
     runtime.personality = requested_runtime.personality.clone();
     runtime.output_schema = requested_runtime.output_schema.clone();
+    runtime.developer_instructions = requested_runtime.developer_instructions.clone();
 
     runtime
 }
@@ -4800,7 +5702,12 @@ fn append_user_message_item(
             }
             "image" => {
                 if let Some(url) = extract_any_string(&input, &["url"]) {
-                    blocks.push(json_text_block(format!("[image: {url}]")));
+                    // Inline images are megabytes of base64; keep only the type.
+                    let label = match url.strip_prefix("data:") {
+                        Some(data) => data.split([';', ',']).next().unwrap_or("image"),
+                        None => url.as_str(),
+                    };
+                    blocks.push(json_text_block(format!("[image: {label}]")));
                 }
             }
             _ => {}
@@ -5258,6 +6165,7 @@ const PAGINATION_MAX_PAGES: usize = 50;
 
 async fn fetch_paginated_data(
     transport: &CodexTransport,
+    timeout: Duration,
     methods: &[&str],
     mut params_for_cursor: impl FnMut(Option<String>) -> serde_json::Value,
 ) -> Result<Vec<serde_json::Value>, anyhow::Error> {
@@ -5269,7 +6177,7 @@ async fn fetch_paginated_data(
             transport,
             methods,
             params_for_cursor(cursor.clone()),
-            DEFAULT_TIMEOUT,
+            timeout,
         )
         .await?;
         let Some(data) = response.get("data").and_then(serde_json::Value::as_array) else {
@@ -5306,18 +6214,22 @@ fn is_method_not_supported_error(message: &str) -> bool {
 async fn fetch_experimental_features(
     transport: &CodexTransport,
 ) -> MethodCallOutcome<Vec<CodexExperimentalFeatureDto>> {
-    let response =
-        match fetch_paginated_data(transport, EXPERIMENTAL_FEATURE_LIST_METHODS, |cursor| {
+    let response = match fetch_paginated_data(
+        transport,
+        PROTOCOL_DIAGNOSTICS_TIMEOUT,
+        EXPERIMENTAL_FEATURE_LIST_METHODS,
+        |cursor| {
             serde_json::json!({
                 "limit": 200,
                 "cursor": cursor,
             })
-        })
-        .await
-        {
-            Ok(data) => data,
-            Err(error) => return method_call_outcome_from_error(error),
-        };
+        },
+    )
+    .await
+    {
+        Ok(data) => data,
+        Err(error) => return method_call_outcome_from_error(error),
+    };
 
     MethodCallOutcome::Available(
         response
@@ -5348,7 +6260,7 @@ async fn fetch_collaboration_modes(transport: &CodexTransport) -> MethodCallOutc
         transport,
         COLLABORATION_MODE_LIST_METHODS,
         serde_json::Value::Null,
-        DEFAULT_TIMEOUT,
+        PROTOCOL_DIAGNOSTICS_TIMEOUT,
     )
     .await
     {
@@ -5372,8 +6284,11 @@ async fn fetch_collaboration_modes(transport: &CodexTransport) -> MethodCallOutc
     MethodCallOutcome::Available(modes.into_iter().collect())
 }
 
-async fn fetch_apps(transport: &CodexTransport) -> MethodCallOutcome<Vec<CodexAppDto>> {
-    let response = match fetch_paginated_data(transport, APP_LIST_METHODS, |cursor| {
+async fn fetch_apps(
+    transport: &CodexTransport,
+    timeout: Duration,
+) -> MethodCallOutcome<Vec<CodexAppDto>> {
+    let response = match fetch_paginated_data(transport, timeout, APP_LIST_METHODS, |cursor| {
         serde_json::json!({
             "limit": 200,
             "cursor": cursor,
@@ -5467,7 +6382,7 @@ async fn fetch_skills(transport: &CodexTransport) -> MethodCallOutcome<Vec<Codex
             "cwds": cwds,
             "forceReload": false,
         }),
-        DEFAULT_TIMEOUT,
+        PROTOCOL_DIAGNOSTICS_TIMEOUT,
     )
     .await
     {
@@ -5571,7 +6486,7 @@ async fn fetch_plugin_marketplaces(
         transport,
         PLUGIN_LIST_METHODS,
         serde_json::Value::Null,
-        DEFAULT_TIMEOUT,
+        PROTOCOL_DIAGNOSTICS_TIMEOUT,
     )
     .await
     {
@@ -5614,12 +6529,17 @@ fn map_mcp_servers(entries: &[serde_json::Value]) -> Vec<CodexMcpServerDto> {
 async fn fetch_mcp_servers(
     transport: &CodexTransport,
 ) -> MethodCallOutcome<Vec<CodexMcpServerDto>> {
-    let response = match fetch_paginated_data(transport, MCP_SERVER_STATUS_LIST_METHODS, |cursor| {
-        serde_json::json!({
-            "limit": 200,
-            "cursor": cursor,
-        })
-    })
+    let response = match fetch_paginated_data(
+        transport,
+        PROTOCOL_DIAGNOSTICS_TIMEOUT,
+        MCP_SERVER_STATUS_LIST_METHODS,
+        |cursor| {
+            serde_json::json!({
+                "limit": 200,
+                "cursor": cursor,
+            })
+        },
+    )
     .await
     {
         Ok(data) => data,
@@ -5651,7 +6571,7 @@ async fn fetch_account_state(
         transport,
         ACCOUNT_READ_METHODS,
         serde_json::Value::Null,
-        DEFAULT_TIMEOUT,
+        PROTOCOL_DIAGNOSTICS_TIMEOUT,
     )
     .await
     {
@@ -5767,7 +6687,7 @@ async fn fetch_config_state(transport: &CodexTransport) -> MethodCallOutcome<Cod
         transport,
         CONFIG_READ_METHODS,
         serde_json::Value::Null,
-        DEFAULT_TIMEOUT,
+        PROTOCOL_DIAGNOSTICS_TIMEOUT,
     )
     .await
     {
@@ -5795,7 +6715,7 @@ async fn refresh_protocol_diagnostics_via_transport(
     ) = tokio::join!(
         fetch_experimental_features(transport),
         fetch_collaboration_modes(transport),
-        fetch_apps(transport),
+        fetch_apps(transport, PROTOCOL_DIAGNOSTICS_TIMEOUT),
         fetch_skills(transport),
         fetch_plugin_marketplaces(transport),
         fetch_mcp_servers(transport),
@@ -6347,15 +7267,17 @@ fn build_windows_world_writable_warning_toast(
 
 async fn resolve_pending_approval_request(
     state: Arc<Mutex<CodexState>>,
+    key: &TransportKey,
     request_id: &serde_json::Value,
 ) -> Option<String> {
     let mut state = state.lock().await;
-    let approval_id = state
+    let slot = state.transports.get_mut(key)?;
+    let approval_id = slot
         .approval_requests
         .iter()
         .find(|(_, pending)| pending.raw_request_id == *request_id)
         .map(|(approval_id, _)| approval_id.clone())?;
-    state.approval_requests.remove(&approval_id);
+    slot.approval_requests.remove(&approval_id);
     Some(approval_id)
 }
 
@@ -6429,6 +7351,21 @@ fn belongs_to_thread(params: &serde_json::Value, thread_id: &str) -> bool {
     true
 }
 
+/// The last [`STDERR_TAIL_CONTEXT_LINES`] stderr lines of `transport`, or
+/// `None` when it has printed nothing.
+fn transport_stderr_tail(transport: &CodexTransport) -> Option<String> {
+    let lines = transport.recent_stderr();
+    let tail = &lines[lines.len().saturating_sub(STDERR_TAIL_CONTEXT_LINES)..];
+    (!tail.is_empty()).then(|| tail.join("\n"))
+}
+
+fn with_stderr_tail(message: &str, stderr_tail: Option<&str>) -> String {
+    match stderr_tail {
+        Some(stderr_tail) => format!("{message}\n\ncodex app-server stderr:\n{stderr_tail}"),
+        None => message.to_string(),
+    }
+}
+
 fn transport_failure_message(
     normalized_method: &str,
     params: &serde_json::Value,
@@ -6624,7 +7561,7 @@ fn parse_optional_timeout_seconds(raw: Option<&str>) -> Option<Duration> {
     }
 }
 
-fn completion_inactivity_timeout() -> Option<Duration> {
+fn env_completion_inactivity_timeout() -> Option<Duration> {
     parse_optional_timeout_seconds(
         env::var("PANES_CODEX_COMPLETION_INACTIVITY_TIMEOUT_SECS")
             .ok()
@@ -6722,6 +7659,32 @@ mod tests {
     use crate::engines::ActionResult;
     use serde_json::{json, Value};
 
+    #[test]
+    fn cli_resume_args_follow_the_installed_codex_version() {
+        let resume = vec!["resume".to_string(), "thr_123".to_string()];
+        assert_eq!(
+            codex_cli_resume_args(Some("codex-cli 0.46.0"), "thr_123").unwrap(),
+            resume
+        );
+        assert_eq!(
+            codex_cli_resume_args(Some("codex-cli 0.39.0-alpha.2\n"), "thr_123").unwrap(),
+            resume
+        );
+        assert_eq!(
+            codex_cli_resume_args(Some("codex 1.2"), "thr_123").unwrap(),
+            resume
+        );
+        assert_eq!(codex_cli_resume_args(None, "thr_123").unwrap(), resume);
+        assert_eq!(
+            codex_cli_resume_args(Some("unknown build"), "thr_123").unwrap(),
+            resume
+        );
+
+        let error = codex_cli_resume_args(Some("codex-cli 0.38.4"), "thr_123")
+            .expect_err("older CLIs cannot resume by id");
+        assert!(error.to_string().contains("0.38.4"), "{error}");
+    }
+
     #[test]
     fn normalize_modern_accept_with_execpolicy_from_top_level() {
         let response = json!({
@@ -6855,6 +7818,7 @@ mod tests {
             None,
             Some("fast"),
             Some("friendly"),
+            Some("Always use pnpm."),
         );
 
         assert_eq!(
@@ -6867,11 +7831,60 @@ mod tests {
                 "sandbox": "workspace-write",
                 "serviceTier": "fast",
                 "personality": "friendly",
+                "developerInstructions": "Always use pnpm.",
                 "persistExtendedHistory": false,
             })
         );
     }
 
+    #[tokio::test]
+    async fn transport_restart_notices_replace_each_other_and_settle_once_reconnected() {
+        let (event_tx, mut event_rx) = mpsc::channel(8);
+        let quiet = TransportRestartNotifier::new(&event_tx);
+        quiet.finish().await;
+        assert!(event_rx.try_recv().is_err());
+
+        let notifier = TransportRestartNotifier::new(&event_tx);
+        notifier.transport_lost().await;
+        notifier.retrying(2, Duration::from_millis(500)).await;
+        // Only the first loss of the transport is announced on its own.
+        notifier.transport_lost().await;
+        notifier.finish().await;
+
+        let notices = std::iter::from_fn(|| event_rx.try_recv().ok())
+            .map(|event| match event {
+                EngineEvent::Notice {
+                    kind,
+                    level,
+                    message,
+                    ..
+                } => {
+                    assert_eq!(kind, TRANSPORT_RESTART_NOTICE_KIND);
+                    (level, message)
+                }
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            notices,
+            [
+                (
+                    "warning".to_string(),
+                    "Restarting the Codex app-server (attempt 1 of 3).".to_string()
+                ),
+                (
+                    "warning".to_string(),
+                    "Restarting the Codex app-server failed. Retrying in 0.50s (attempt 2 of 3)."
+                        .to_string()
+                ),
+                (
+                    "info".to_string(),
+                    "The Codex app-server was restarted.".to_string()
+                ),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn build_turn_start_params_uses_native_plan_mode_for_codex_when_available() {
         let runtime = ThreadRuntime {
@@ -6889,6 +7902,7 @@ mod tests {
             service_tier: Some("fast".to_string()),
             personality: Some("friendly".to_string()),
             output_schema: Some(json!({"type":"object"})),
+            developer_instructions: None,
             native_plan_mode_active: false,
         };
         let input = TurnInput {
@@ -6952,6 +7966,7 @@ mod tests {
             service_tier: Some("fast".to_string()),
             personality: Some("friendly".to_string()),
             output_schema: Some(json!({"type":"object"})),
+            developer_instructions: None,
             native_plan_mode_active: true,
         };
         let input = TurnInput {
@@ -7012,6 +8027,7 @@ mod tests {
             service_tier: Some("fast".to_string()),
             personality: Some("friendly".to_string()),
             output_schema: Some(json!({"type":"object"})),
+            developer_instructions: None,
             native_plan_mode_active: false,
         };
         let input = TurnInput {
@@ -7178,6 +8194,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn completion_inactivity_starts_at_the_first_progress() {
+        let far_future = Instant::now() + Duration::from_secs(3_600);
+        assert!(!CompletionInactivity::new(None).expired_at(far_future));
+
+        let mut inactivity = CompletionInactivity::new(Some(Duration::from_secs(60)));
+        assert!(inactivity.is_enabled());
+        assert!(!inactivity.expired_at(far_future));
+
+        inactivity.record_progress();
+        let now = Instant::now();
+        assert!(!inactivity.expired_at(now + Duration::from_secs(59)));
+        assert!(inactivity.expired_at(now + Duration::from_secs(60)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn thread_heartbeats_from_the_transport_reset_completion_inactivity() {
+        let heartbeat =
+            r#"{"jsonrpc":"2.0","method":"thread/heartbeat","params":{"threadId":"thread-1"}}"#;
+        let other_thread =
+            r#"{"jsonrpc":"2.0","method":"thread/heartbeat","params":{"threadId":"thread-2"}}"#;
+        let transport = CodexTransport::spawn_mock_script(&format!(
+            "read _; i=0; while [ $i -lt 5 ]; do echo '{heartbeat}'; echo '{other_thread}'; sleep 0.05; i=$((i+1)); done; exec cat"
+        ))
+        .expect("mock transport");
+        let mut subscription = transport.subscribe();
+        transport
+            .notify("ping", json!({}))
+            .await
+            .expect("notify mock transport");
+
+        let mut inactivity = CompletionInactivity::new(Some(Duration::from_millis(500)));
+        let mut heartbeats = 0;
+        while heartbeats < 5 {
+            let incoming = tokio::time::timeout(Duration::from_secs(5), subscription.recv())
+                .await
+                .expect("heartbeat in time")
+                .expect("transport open");
+            let IncomingMessage::Notification { params, .. } = incoming else {
+                continue;
+            };
+            if belongs_to_thread(&raw_value_to_value(&params), "thread-1") {
+                assert!(!inactivity.expired_at(Instant::now()));
+                inactivity.record_progress();
+                heartbeats += 1;
+            }
+        }
+
+        let quiet_until = Instant::now() + Duration::from_millis(500);
+        assert!(inactivity.expired_at(quiet_until));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn transport_failures_carry_the_last_stderr_lines() {
+        let transport = CodexTransport::spawn_mock_script(
+            "i=1; while [ $i -le 25 ]; do echo \"auth error $i\" >&2; i=$((i+1)); done",
+        )
+        .expect("mock transport");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while transport.recent_stderr().len() < 25 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let stderr_tail = transport_stderr_tail(&transport).expect("stderr tail");
+        let lines = stderr_tail.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), STDERR_TAIL_CONTEXT_LINES);
+        assert_eq!(lines.first(), Some(&"auth error 6"));
+        assert_eq!(lines.last(), Some(&"auth error 25"));
+        assert_eq!(
+            with_stderr_tail("codex initialize failed", Some("auth error 25")),
+            "codex initialize failed\n\ncodex app-server stderr:\nauth error 25"
+        );
+        assert_eq!(
+            with_stderr_tail("codex initialize failed", None),
+            "codex initialize failed"
+        );
+    }
+
     #[test]
     fn rebind_expected_turn_id_replaces_prior_turn() {
         let mut expected_turn_id = Some("turn-plan".to_string());
@@ -7401,6 +8497,106 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn raster_images_are_inlined_until_the_turn_budget_runs_out() {
+        let dir = std::env::temp_dir().join(format!("panes-codex-images-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let attachment = |file_name: &str, mime_type: Option<&str>, bytes: &[u8]| {
+            let path = dir.join(file_name);
+            std::fs::write(&path, bytes).unwrap();
+            TurnAttachment {
+                file_name: file_name.to_string(),
+                file_path: path.to_string_lossy().into_owned(),
+                size_bytes: bytes.len() as u64,
+                mime_type: mime_type.map(str::to_string),
+            }
+        };
+        let png = attachment("shot.png", Some("image/png"), b"png-bytes");
+        let jpeg = attachment("photo.JPG", None, b"jpeg");
+        let svg = attachment("logo.svg", Some("image/svg+xml"), b"<svg/>");
+
+        let mut budget = MAX_INLINE_IMAGE_BYTES_PER_TURN;
+        assert_eq!(
+            image_input_item(&png, &mut budget).await.unwrap(),
+            json!({ "type": "image", "url": "data:image/png;base64,cG5nLWJ5dGVz" })
+        );
+        assert_eq!(budget, MAX_INLINE_IMAGE_BYTES_PER_TURN - 9);
+        assert_eq!(
+            image_input_item(&svg, &mut budget).await.unwrap(),
+            json!({ "type": "localImage", "path": svg.file_path })
+        );
+
+        let mut budget = 3;
+        assert_eq!(
+            image_input_item(&jpeg, &mut budget).await.unwrap(),
+            json!({ "type": "localImage", "path": jpeg.file_path })
+        );
+        budget = 4;
+        assert_eq!(
+            image_input_item(&jpeg, &mut budget).await.unwrap()["url"],
+            "data:image/jpeg;base64,anBlZw=="
+        );
+        assert_eq!(budget, 0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn unsupported_attachments_are_split_off_the_turn() {
+        let attachment = |file_name: &str, mime_type: Option<&str>| TurnAttachment {
+            file_name: file_name.to_string(),
+            file_path: format!("/tmp/{file_name}"),
+            size_bytes: 10,
+            mime_type: mime_type.map(str::to_string),
+        };
+        let mut input = TurnInput {
+            message: "Look at these".to_string(),
+            attachments: vec![
+                attachment("shot.webp", Some("image/webp")),
+                attachment("report.pdf", Some("application/pdf")),
+                attachment("notes.md", None),
+            ],
+            plan_mode: false,
+            input_items: Vec::new(),
+        };
+
+        let unsupported = take_unsupported_attachments(&mut input);
+
+        assert_eq!(
+            unsupported
+                .iter()
+                .map(|attachment| attachment.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["report.pdf"]
+        );
+        assert_eq!(
+            input
+                .attachments
+                .iter()
+                .map(|attachment| attachment.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["shot.webp", "notes.md"]
+        );
+    }
+
+    #[test]
+    fn engine_timeouts_report_clamped_config_values() {
+        let timeouts = EngineTimeouts::from_config(&EngineTimeoutsConfig {
+            request_timeout_secs: 0,
+            turn_request_timeout_secs: 900,
+            turn_completion_inactivity_timeout_secs: 10,
+            per_engine: Default::default(),
+        });
+
+        assert_eq!(
+            timeouts.to_dto(),
+            EngineTimeoutsDto {
+                request_timeout_secs: 5,
+                turn_request_timeout_secs: Some(900),
+                turn_completion_inactivity_timeout_secs: Some(60),
+            }
+        );
+    }
+
     #[test]
     fn normalize_legacy_snake_case_method_alias() {
         let response = json!({ "decision": "accept_for_session" });
@@ -7577,6 +8773,7 @@ mod tests {
             service_tier: Some("flex".to_string()),
             personality: Some("friendly".to_string()),
             output_schema: Some(json!({"type":"object"})),
+            developer_instructions: None,
             native_plan_mode_active: false,
         };
         let response = json!({
@@ -7611,6 +8808,7 @@ mod tests {
             service_tier: Some("default".to_string()),
             personality: Some("friendly".to_string()),
             output_schema: Some(json!({"type":"object"})),
+            developer_instructions: None,
             native_plan_mode_active: true,
         };
         let requested_runtime = ThreadRuntime {
@@ -7628,6 +8826,7 @@ mod tests {
             service_tier: Some("flex".to_string()),
             personality: Some("precise".to_string()),
             output_schema: None,
+            developer_instructions: None,
             native_plan_mode_active: false,
         };
 
@@ -7709,20 +8908,189 @@ mod tests {
         let state = Arc::new(Mutex::new(CodexState::default()));
         {
             let mut locked = state.lock().await;
-            locked.approval_requests.insert(
-                "approval-1".to_string(),
-                PendingApproval {
-                    raw_request_id: json!(42),
-                    method: "item/fileChange/requestApproval".to_string(),
-                },
-            );
+            locked
+                .slot_mut(&TransportKey::Shared)
+                .approval_requests
+                .insert(
+                    "approval-1".to_string(),
+                    PendingApproval {
+                        raw_request_id: json!(42),
+                        method: "item/fileChange/requestApproval".to_string(),
+                    },
+                );
         }
 
-        let approval_id = resolve_pending_approval_request(state.clone(), &json!(42)).await;
+        let approval_id =
+            resolve_pending_approval_request(state.clone(), &TransportKey::Shared, &json!(42))
+                .await;
 
         assert_eq!(approval_id.as_deref(), Some("approval-1"));
         let locked = state.lock().await;
-        assert!(locked.approval_requests.is_empty());
+        assert!(locked
+            .slot(&TransportKey::Shared)
+            .expect("shared slot")
+            .approval_requests
+            .is_empty());
+    }
+
+    fn workspace_runtime(cwd: &str) -> ThreadRuntime {
+        ThreadRuntime {
+            cwd: cwd.to_string(),
+            model_id: "gpt-5.4".to_string(),
+            approval_policy: json!("on-request"),
+            permission_profile: None,
+            approvals_reviewer: None,
+            sandbox_policy: json!({ "type": "workspaceWrite" }),
+            reasoning_effort: None,
+            service_tier: None,
+            personality: None,
+            output_schema: None,
+            developer_instructions: None,
+            native_plan_mode_active: false,
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn workspace_transport_crash_does_not_reset_other_workspaces() {
+        let engine = CodexEngine::default();
+        let key_a = TransportKey::Workspace("/work/a".to_string());
+        let key_b = TransportKey::Workspace("/work/b".to_string());
+        let transport_a = Arc::new(CodexTransport::spawn_mock().expect("mock transport a"));
+        let transport_b = Arc::new(CodexTransport::spawn_mock().expect("mock transport b"));
+        engine.install_transport(&key_a, transport_a.clone()).await;
+        engine.install_transport(&key_b, transport_b.clone()).await;
+
+        engine
+            .store_thread_runtime(&key_a, "thread-a", workspace_runtime("/work/a"))
+            .await;
+        engine
+            .store_thread_runtime(&key_b, "thread-b", workspace_runtime("/work/b"))
+            .await;
+        engine.set_active_turn("thread-a", "turn-a").await;
+        engine.set_active_turn("thread-b", "turn-b").await;
+        let approval_method = "item/commandExecution/requestApproval";
+        engine
+            .register_approval_request(&key_a, "approval-a", &json!(7), approval_method)
+            .await;
+        engine
+            .register_approval_request(&key_b, "approval-b", &json!(7), approval_method)
+            .await;
+
+        // Simulated crash of workspace A's app-server.
+        transport_a.shutdown().await.expect("kill mock transport");
+        assert!(engine.live_transport_for(&key_a).await.is_none());
+
+        assert!(engine.thread_runtime("thread-a").await.is_none());
+        assert!(engine.active_turn_id("thread-a").await.is_none());
+        assert!(engine.approval_request("approval-a").await.is_none());
+
+        assert!(engine.thread_runtime("thread-b").await.is_some());
+        assert_eq!(
+            engine.active_turn_id("thread-b").await.as_deref(),
+            Some("turn-b")
+        );
+        let (approval_key, _) = engine
+            .approval_request("approval-b")
+            .await
+            .expect("workspace B approval survives");
+        assert_eq!(approval_key, key_b);
+        let live_b = engine
+            .live_transport_for(&key_b)
+            .await
+            .expect("workspace B transport stays alive");
+        assert!(Arc::ptr_eq(&live_b, &transport_b));
+
+        // Request ids are per transport: resolving id 7 on A must not touch B.
+        assert_eq!(
+            resolve_pending_approval_request(engine.state.clone(), &key_a, &json!(7)).await,
+            None
+        );
+        assert!(engine.approval_request("approval-b").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn workspace_transport_reset_keeps_engine_wide_probe_and_caches() {
+        let engine = CodexEngine::default();
+        let key_a = TransportKey::Workspace("/work/a".to_string());
+        let key_b = TransportKey::Workspace("/work/b".to_string());
+        engine
+            .store_thread_runtime(&key_b, "thread-b", workspace_runtime("/work/b"))
+            .await;
+        engine.set_force_external_sandbox(true).await;
+        engine.store_runtime_model_cache(engine.models()).await;
+        engine.state.lock().await.protocol_diagnostics =
+            Some(CodexProtocolDiagnosticsDto::default());
+
+        engine.invalidate_transport_for(&key_a, "test").await;
+
+        {
+            let state = engine.state.lock().await;
+            assert!(state.sandbox_probe_completed);
+            assert!(state.force_external_sandbox);
+            assert!(state.runtime_model_cache.is_some());
+            assert!(state
+                .protocol_diagnostics
+                .as_ref()
+                .is_some_and(|diagnostics| !diagnostics.stale));
+        }
+        assert!(engine.thread_runtime("thread-b").await.is_some());
+
+        engine
+            .invalidate_transport_for(&TransportKey::Shared, "test")
+            .await;
+
+        let state = engine.state.lock().await;
+        assert!(!state.sandbox_probe_completed);
+        assert!(!state.force_external_sandbox);
+        assert!(state.runtime_model_cache.is_none());
+        assert!(state
+            .protocol_diagnostics
+            .as_ref()
+            .is_some_and(|diagnostics| diagnostics.stale));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn transport_eviction_skips_workspaces_with_active_turns() {
+        let engine = CodexEngine::default();
+        engine.state.lock().await.routing = CodexTransportRouting {
+            per_workspace: true,
+            max_workspace_transports: 1,
+        };
+        let key_a = TransportKey::Workspace("/work/a".to_string());
+        let key_b = TransportKey::Workspace("/work/b".to_string());
+        let key_c = TransportKey::Workspace("/work/c".to_string());
+        let transport_a = Arc::new(CodexTransport::spawn_mock().expect("mock transport a"));
+        let transport_b = Arc::new(CodexTransport::spawn_mock().expect("mock transport b"));
+        let transport_c = Arc::new(CodexTransport::spawn_mock().expect("mock transport c"));
+
+        engine.install_transport(&key_a, transport_a.clone()).await;
+        engine
+            .store_thread_runtime(&key_a, "thread-a", workspace_runtime("/work/a"))
+            .await;
+        engine.set_active_turn("thread-a", "turn-a").await;
+
+        // A is over the cap but busy, so installing B evicts nothing.
+        engine.install_transport(&key_b, transport_b.clone()).await;
+        assert!(transport_a.is_alive().await);
+        assert!(transport_b.is_alive().await);
+
+        // C pushes out the idle B, never the older-but-busy A.
+        engine.install_transport(&key_c, transport_c.clone()).await;
+        assert!(transport_a.is_alive().await);
+        assert!(!transport_b.is_alive().await);
+        assert!(transport_c.is_alive().await);
+        {
+            let state = engine.state.lock().await;
+            assert!(state.slot(&key_b).is_none());
+            assert!(state.slot(&key_a).is_some());
+        }
+        assert_eq!(
+            engine.active_turn_id("thread-a").await.as_deref(),
+            Some("turn-a")
+        );
+        assert_eq!(engine.thread_transport_key("thread-a").await, key_a);
     }
 
     #[tokio::test]
@@ -7766,6 +9134,45 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn cached_runtime_models_hand_out_one_refresh_once_stale() {
+        let engine = CodexEngine::default();
+        assert!(engine.cached_runtime_models().await.is_none());
+
+        engine.store_runtime_model_cache(engine.models()).await;
+        let (models, needs_refresh) = engine.cached_runtime_models().await.unwrap();
+        assert_eq!(models.len(), engine.models().len());
+        assert!(!needs_refresh);
+
+        engine.state.lock().await.model_cache_ttl = Some(Duration::ZERO);
+        assert!(engine.cached_runtime_models().await.unwrap().1);
+        assert!(!engine.cached_runtime_models().await.unwrap().1);
+
+        engine
+            .invalidate_transport_for(&TransportKey::Shared, "test")
+            .await;
+        assert!(engine.cached_runtime_models().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn invalidating_executable_caches_forces_fresh_diagnostics_and_models() {
+        let engine = CodexEngine::default();
+        engine.store_runtime_model_cache(Vec::new()).await;
+        engine.state.lock().await.protocol_diagnostics = Some(CodexProtocolDiagnosticsDto {
+            fetched_at: Some("2026-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        });
+
+        engine.invalidate_executable_caches().await;
+
+        let state = engine.state.lock().await;
+        assert!(state.runtime_model_cache.is_none());
+        assert!(state
+            .protocol_diagnostics
+            .as_ref()
+            .is_some_and(|diagnostics| diagnostics.stale));
+    }
+
     #[tokio::test]
     async fn update_protocol_diagnostics_with_config_warning_tracks_end_range() {
         let state = Arc::new(Mutex::new(CodexState::default()));
@@ -7963,7 +9370,7 @@ mod tests {
     fn codex_unavailable_details_for_windows_mentions_appdata_npm() {
         let details = codex_unavailable_details_for_platform(
             "windows",
-            &CodexExecutableResolution {
+            &ExecutableResolution {
                 executable: None,
                 source: "unavailable",
                 app_path: Some(r"C:\Windows\System32".to_string()),
@@ -7976,11 +9383,45 @@ mod tests {
         assert!(details.contains("App PATH"));
     }
 
+    #[test]
+    fn codex_auth_status_reads_account_and_legacy_auth_responses() {
+        assert_eq!(
+            codex_auth_status_from_response(&json!({
+                "account": { "type": "chatgpt", "email": "dev@example.com" },
+                "requiresOpenaiAuth": true
+            })),
+            Some(EngineAuthStatus::SignedIn)
+        );
+        assert_eq!(
+            codex_auth_status_from_response(&json!({
+                "account": null,
+                "requiresOpenaiAuth": true
+            })),
+            Some(EngineAuthStatus::SignedOut)
+        );
+        assert_eq!(
+            codex_auth_status_from_response(&json!({
+                "account": null,
+                "requiresOpenaiAuth": false
+            })),
+            Some(EngineAuthStatus::NotRequired)
+        );
+        assert_eq!(
+            codex_auth_status_from_response(&json!({ "authMethod": "apikey" })),
+            Some(EngineAuthStatus::SignedIn)
+        );
+        assert_eq!(
+            codex_auth_status_from_response(&json!({ "authMethod": null })),
+            Some(EngineAuthStatus::SignedOut)
+        );
+        assert_eq!(codex_auth_status_from_response(&json!({})), None);
+    }
+
     #[test]
     fn codex_fix_commands_for_windows_cover_install_and_path() {
         let fixes = codex_fix_commands_for_platform(
             "windows",
-            &CodexExecutableResolution {
+            &ExecutableResolution {
                 executable: None,
                 source: "unavailable",
                 app_path: Some(r"C:\Windows\System32".to_string()),
@@ -7998,7 +9439,7 @@ mod tests {
     fn codex_execution_failure_details_for_windows_mentions_node_path() {
         let details = codex_execution_failure_details_for_platform(
             "windows",
-            &CodexExecutableResolution {
+            &ExecutableResolution {
                 executable: Some(std::path::PathBuf::from(
                     r"C:\Users\panes\AppData\Roaming\npm\codex.cmd",
                 )),
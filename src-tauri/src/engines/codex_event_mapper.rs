@@ -369,7 +369,7 @@ impl TurnEventMapper {
         let approval_id = extract_any_string(params, &["approvalId", "itemId", "callId", "id"])
             .unwrap_or_else(|| request_id.to_string());
 
-        let mut details = params.clone();
+        let mut details = with_tool_arguments(params.clone());
         if let Some(object) = details.as_object_mut() {
             object.insert(
                 APPROVAL_DETAIL_SERVER_METHOD_KEY.to_string(),
@@ -449,10 +449,10 @@ impl TurnEventMapper {
                 let mut events = vec![EngineEvent::ActionStarted {
                     action_id,
                     engine_action_id: engine_item_id,
-                    action_type: ActionType::Other,
-                    summary: extract_any_string(item, &["name", "toolName"])
+                    action_type: ActionType::McpTool,
+                    summary: extract_any_string(item, &["tool", "name", "toolName"])
                         .unwrap_or_else(|| "Tool call".to_string()),
-                    details: item.clone(),
+                    details: with_tool_arguments(item.clone()),
                 }];
 
                 if let Some(engine_item_id) = extract_any_string(item, &["id"]) {
@@ -1118,6 +1118,51 @@ fn summarize_mcp_elicitation_request(params: &Value) -> String {
     }
 }
 
+const TOOL_ARGS_PREVIEW_MAX_CHARS: usize = 240;
+
+/// Tool calls report `arguments` as an object, a JSON-encoded string, or not
+/// at all. Rewrites them as an object and adds a one-line `argsPreview`, so
+/// action and approval blocks can show what a tool is about to receive.
+/// Payloads without `arguments` are returned unchanged.
+fn with_tool_arguments(mut details: Value) -> Value {
+    let Some(object) = details.as_object_mut() else {
+        return details;
+    };
+    let Some(raw) = object.get("arguments") else {
+        return details;
+    };
+
+    let arguments = match raw {
+        Value::Null => Value::Object(serde_json::Map::new()),
+        Value::String(text) => match serde_json::from_str::<Value>(text) {
+            Ok(parsed @ Value::Object(_)) => parsed,
+            _ if text.trim().is_empty() => Value::Object(serde_json::Map::new()),
+            _ => serde_json::json!({ "value": text }),
+        },
+        Value::Object(_) => raw.clone(),
+        other => serde_json::json!({ "value": other }),
+    };
+    let preview = args_preview(&arguments);
+
+    object.insert("arguments".to_string(), arguments);
+    object.insert("argsPreview".to_string(), Value::String(preview));
+    details
+}
+
+fn args_preview(arguments: &Value) -> String {
+    let rendered = serde_json::to_string(arguments).unwrap_or_default();
+    if rendered.chars().count() <= TOOL_ARGS_PREVIEW_MAX_CHARS {
+        return rendered;
+    }
+
+    let mut preview = rendered
+        .chars()
+        .take(TOOL_ARGS_PREVIEW_MAX_CHARS - 1)
+        .collect::<String>();
+    preview.push('…');
+    preview
+}
+
 fn summarize_hook_entries(entries: Option<&Vec<Value>>) -> Option<String> {
     let entries = entries?;
     let lines = entries
@@ -2261,6 +2306,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn map_notification_mcp_tool_call_exposes_server_tool_and_arguments() {
+        let mut mapper = TurnEventMapper::default();
+        let long_query = "x".repeat(400);
+        let events = mapper.map_notification(
+            "item/started",
+            &json!({
+                "item": {
+                    "id": "item_123",
+                    "type": "mcpToolCall",
+                    "server": "docs",
+                    "tool": "search_docs",
+                    "arguments": format!(r#"{{"query":"{long_query}"}}"#)
+                }
+            }),
+        );
+
+        match &events[0] {
+            EngineEvent::ActionStarted {
+                action_type,
+                summary,
+                details,
+                ..
+            } => {
+                assert!(matches!(action_type, ActionType::McpTool));
+                assert_eq!(summary, "search_docs");
+                assert_eq!(details["server"], "docs");
+                assert_eq!(details["arguments"]["query"], long_query.as_str());
+                let preview = details["argsPreview"].as_str().expect("argsPreview");
+                assert!(preview.starts_with(r#"{"query":"xxx"#));
+                assert_eq!(preview.chars().count(), TOOL_ARGS_PREVIEW_MAX_CHARS);
+                assert!(preview.ends_with('…'));
+            }
+            _ => panic!("expected action started event"),
+        }
+
+        assert_eq!(
+            with_tool_arguments(json!({ "arguments": null }))["argsPreview"],
+            "{}"
+        );
+        assert_eq!(
+            with_tool_arguments(json!({ "arguments": [1, 2] }))["arguments"],
+            json!({ "value": [1, 2] })
+        );
+        assert_eq!(
+            with_tool_arguments(json!({ "tool": "t" })),
+            json!({ "tool": "t" })
+        );
+    }
+
     #[test]
     fn map_notification_replays_latest_mcp_progress_when_item_starts() {
         let mut mapper = TurnEventMapper::default();
@@ -0,0 +1,131 @@
+use serde_json::{json, Value};
+
+/// Shape of a codex server request Panes has no mapping for, inferred from
+/// its normalized method name. Each family stalls the turn until answered,
+/// so it gets a safe refusal instead of a bare method-not-found error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockingRequestFamily {
+    /// `item/*/requestApproval` style; answered with `decline`.
+    Approval,
+    /// Pre-v2 `*Approval` methods; answered with `denied`.
+    LegacyApproval,
+    Elicitation,
+    UserInput,
+    DynamicToolCall,
+}
+
+// Matched in order against the method signature (normalized, no slashes), so
+// the more specific fragments come first.
+const BLOCKING_REQUEST_PATTERNS: &[(&str, BlockingRequestFamily)] = &[
+    ("elicitation", BlockingRequestFamily::Elicitation),
+    ("requestuserinput", BlockingRequestFamily::UserInput),
+    ("userinput", BlockingRequestFamily::UserInput),
+    ("requestapproval", BlockingRequestFamily::Approval),
+    ("approval", BlockingRequestFamily::LegacyApproval),
+    ("toolcall", BlockingRequestFamily::DynamicToolCall),
+];
+
+impl BlockingRequestFamily {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Approval => "approval",
+            Self::LegacyApproval => "legacy_approval",
+            Self::Elicitation => "elicitation",
+            Self::UserInput => "user_input",
+            Self::DynamicToolCall => "dynamic_tool_call",
+        }
+    }
+
+    /// Result that refuses the request while letting the turn continue.
+    pub fn fallback_response(self, method: &str) -> Value {
+        match self {
+            Self::Approval => json!({ "decision": "decline" }),
+            Self::LegacyApproval => json!({ "decision": "denied" }),
+            Self::Elicitation => json!({ "action": "decline", "content": null }),
+            Self::UserInput => json!({ "answers": {} }),
+            Self::DynamicToolCall => json!({
+                "success": false,
+                "contentItems": [{
+                    "type": "inputText",
+                    "text": format!("Panes does not support `{method}` yet; continue without it."),
+                }],
+            }),
+        }
+    }
+}
+
+/// Classifies an unmapped request by its normalized method (lowercase,
+/// `/`-separated, no `_` or `-`). `None` means the request is not known to
+/// block and can be answered with method-not-found.
+pub fn classify_unmapped_request(normalized_method: &str) -> Option<BlockingRequestFamily> {
+    let signature = normalized_method.replace('/', "");
+    let family = BLOCKING_REQUEST_PATTERNS
+        .iter()
+        .find(|(pattern, _)| signature.contains(pattern))
+        .map(|(_, family)| *family)?;
+    // A legacy-looking approval under a namespaced method is the v2 shape.
+    if family == BlockingRequestFamily::LegacyApproval && normalized_method.contains('/') {
+        return Some(BlockingRequestFamily::Approval);
+    }
+    Some(family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_blocking_families_and_leaves_the_rest_alone() {
+        assert_eq!(
+            classify_unmapped_request("item/networkaccess/requestapproval"),
+            Some(BlockingRequestFamily::Approval)
+        );
+        assert_eq!(
+            classify_unmapped_request("item/sandbox/approval"),
+            Some(BlockingRequestFamily::Approval)
+        );
+        assert_eq!(
+            classify_unmapped_request("mcpcommandapproval"),
+            Some(BlockingRequestFamily::LegacyApproval)
+        );
+        assert_eq!(
+            classify_unmapped_request("mcpserver/elicitation/create"),
+            Some(BlockingRequestFamily::Elicitation)
+        );
+        assert_eq!(
+            classify_unmapped_request("item/plan/requestuserinput"),
+            Some(BlockingRequestFamily::UserInput)
+        );
+        assert_eq!(
+            classify_unmapped_request("item/browser/toolcall"),
+            Some(BlockingRequestFamily::DynamicToolCall)
+        );
+        assert_eq!(classify_unmapped_request("account/usage/read"), None);
+        assert_eq!(classify_unmapped_request("thread/metadata/sync"), None);
+    }
+
+    #[test]
+    fn fallback_responses_refuse_in_each_family_shape() {
+        assert_eq!(
+            BlockingRequestFamily::Approval.fallback_response("m")["decision"],
+            "decline"
+        );
+        assert_eq!(
+            BlockingRequestFamily::LegacyApproval.fallback_response("m")["decision"],
+            "denied"
+        );
+        assert_eq!(
+            BlockingRequestFamily::Elicitation.fallback_response("m")["action"],
+            "decline"
+        );
+        assert_eq!(
+            BlockingRequestFamily::UserInput.fallback_response("m"),
+            json!({ "answers": {} })
+        );
+        let tool = BlockingRequestFamily::DynamicToolCall.fallback_response("item/x/toolCall");
+        assert_eq!(tool["success"], false);
+        assert!(tool["contentItems"][0]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("item/x/toolCall")));
+    }
+}
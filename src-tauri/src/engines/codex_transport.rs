@@ -1,5 +1,10 @@
 use std::{
-    collections::HashMap, ffi::OsString, path::Path, process::Stdio, sync::Arc, time::Duration,
+    collections::{HashMap, VecDeque},
+    ffi::OsString,
+    path::Path,
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Context;
@@ -16,11 +21,15 @@ use super::codex_protocol::{
     response_success_payload, IncomingMessage, RpcResponse,
 };
 use super::trim_action_output_delta_content;
+use super::turn_diagnostics::now_rfc3339;
 
 // This channel is only a live fan-out for active Codex subscribers. Tokio's
 // broadcast ring retains every slot until it is overwritten, so keeping a large
 // history here can pin already-delivered protocol payloads while Panes is idle.
 const INCOMING_EVENT_BUFFER_CAPACITY: usize = 64;
+const STDERR_LINE_BUFFER_CAPACITY: usize = 64;
+/// Bytes of recent stderr kept for error reports, independent of subscribers.
+const STDERR_TAIL_MAX_BYTES: usize = 64 * 1024;
 const TRANSPORT_ERROR_LINE_MAX_CHARS: usize = 16 * 1024;
 const TRANSPORT_ERROR_LINE_TRUNCATED_PREFIX: &str = "... [protocol line truncated; showing tail]\n";
 
@@ -29,9 +38,39 @@ pub struct CodexTransport {
     stdin: Mutex<ChildStdin>,
     pending: Arc<Mutex<HashMap<String, oneshot::Sender<RpcResponse>>>>,
     incoming_tx: broadcast::Sender<IncomingMessage>,
+    stderr_tx: broadcast::Sender<TransportStderrLine>,
+    stderr_tail: Arc<std::sync::Mutex<StderrTail>>,
     next_request_id: std::sync::atomic::AtomicU64,
 }
 
+/// One non-empty stderr line from the app-server, stamped when it was read.
+#[derive(Debug, Clone)]
+pub struct TransportStderrLine {
+    pub at: String,
+    pub line: String,
+}
+
+/// The latest stderr lines, dropped oldest-first once they exceed
+/// [`STDERR_TAIL_MAX_BYTES`].
+#[derive(Debug, Default)]
+struct StderrTail {
+    lines: VecDeque<String>,
+    bytes: usize,
+}
+
+impl StderrTail {
+    fn push(&mut self, line: String) {
+        self.bytes += line.len();
+        self.lines.push_back(line);
+        while self.bytes > STDERR_TAIL_MAX_BYTES {
+            let Some(dropped) = self.lines.pop_front() else {
+                break;
+            };
+            self.bytes -= dropped.len();
+        }
+    }
+}
+
 impl Drop for CodexTransport {
     fn drop(&mut self) {
         if let Ok(mut child) = self.child.try_lock() {
@@ -49,7 +88,7 @@ impl CodexTransport {
             command.env("PATH", augmented_path);
         }
 
-        let mut child = command
+        let child = command
             .arg("app-server")
             .arg("--listen")
             .arg("stdio://")
@@ -62,6 +101,34 @@ impl CodexTransport {
                 format!("failed to spawn `codex app-server` using `{codex_executable}`")
             })?;
 
+        Self::from_child(child)
+    }
+
+    /// Stand-in transport backed by `cat`: it stays alive until killed and
+    /// never answers requests, which is enough to exercise transport routing.
+    #[cfg(all(test, unix))]
+    pub(super) fn spawn_mock() -> anyhow::Result<Self> {
+        Self::spawn_mock_script("exec cat")
+    }
+
+    /// Mock transport running `script` under `sh`, for tests that need the
+    /// process to write specific stderr output.
+    #[cfg(all(test, unix))]
+    pub(super) fn spawn_mock_script(script: &str) -> anyhow::Result<Self> {
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("failed to spawn mock codex transport")?;
+
+        Self::from_child(child)
+    }
+
+    fn from_child(mut child: Child) -> anyhow::Result<Self> {
         let stdin = child
             .stdin
             .take()
@@ -76,6 +143,8 @@ impl CodexTransport {
             .ok_or_else(|| anyhow::anyhow!("codex app-server stderr not available"))?;
 
         let (incoming_tx, _) = broadcast::channel(INCOMING_EVENT_BUFFER_CAPACITY);
+        let (stderr_tx, _) = broadcast::channel(STDERR_LINE_BUFFER_CAPACITY);
+        let stderr_tail = Arc::new(std::sync::Mutex::new(StderrTail::default()));
         let pending = Arc::new(Mutex::new(
             HashMap::<String, oneshot::Sender<RpcResponse>>::new(),
         ));
@@ -134,6 +203,8 @@ impl CodexTransport {
         }
 
         {
+            let stderr_tx = stderr_tx.clone();
+            let stderr_tail = stderr_tail.clone();
             tokio::spawn(async move {
                 let mut lines = BufReader::new(stderr).lines();
                 loop {
@@ -141,6 +212,14 @@ impl CodexTransport {
                         Ok(Some(line)) => {
                             if !line.trim().is_empty() {
                                 log::debug!("codex stderr: {line}");
+                                let line = trim_transport_error_line(line.trim());
+                                if let Ok(mut tail) = stderr_tail.lock() {
+                                    tail.push(line.clone());
+                                }
+                                let _ = stderr_tx.send(TransportStderrLine {
+                                    at: now_rfc3339(),
+                                    line,
+                                });
                             }
                         }
                         Ok(None) => break,
@@ -158,6 +237,8 @@ impl CodexTransport {
             stdin: Mutex::new(stdin),
             pending,
             incoming_tx,
+            stderr_tx,
+            stderr_tail,
             next_request_id: std::sync::atomic::AtomicU64::new(1),
         })
     }
@@ -166,6 +247,21 @@ impl CodexTransport {
         self.incoming_tx.subscribe()
     }
 
+    /// Live stderr lines; like `subscribe`, nothing is replayed to late
+    /// subscribers.
+    pub fn subscribe_stderr(&self) -> broadcast::Receiver<TransportStderrLine> {
+        self.stderr_tx.subscribe()
+    }
+
+    /// Stderr lines kept since spawn, oldest first, up to
+    /// [`STDERR_TAIL_MAX_BYTES`]. Still readable after the process exits.
+    pub fn recent_stderr(&self) -> Vec<String> {
+        self.stderr_tail
+            .lock()
+            .map(|tail| tail.lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     pub async fn request(
         &self,
         method: &str,
@@ -410,4 +506,48 @@ mod tests {
             Some("bad json")
         );
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stderr_lines_reach_subscribers_with_timestamps() {
+        // Wait for stdin so the subscription exists before anything is written.
+        let transport = CodexTransport::spawn_mock_script("read _; echo '  boom  ' >&2; exec cat")
+            .expect("mock transport");
+        let mut stderr = transport.subscribe_stderr();
+        transport
+            .notify("ping", serde_json::json!({}))
+            .await
+            .expect("notify mock transport");
+
+        let line = tokio::time::timeout(std::time::Duration::from_secs(5), stderr.recv())
+            .await
+            .expect("stderr line in time")
+            .expect("stderr channel open");
+        assert_eq!(line.line, "boom");
+        assert!(chrono::DateTime::parse_from_rfc3339(&line.at).is_ok());
+        assert_eq!(transport.recent_stderr(), vec!["boom".to_string()]);
+    }
+
+    #[test]
+    fn stderr_tail_drops_the_oldest_lines_past_the_byte_cap() {
+        let mut tail = StderrTail::default();
+        let line = "e".repeat(1024);
+        for index in 0..100 {
+            tail.push(format!("{index:03}{line}"));
+        }
+
+        assert!(tail.bytes <= STDERR_TAIL_MAX_BYTES);
+        assert_eq!(
+            tail.bytes,
+            tail.lines.iter().map(String::len).sum::<usize>()
+        );
+        assert!(tail
+            .lines
+            .back()
+            .is_some_and(|last| last.starts_with("099")));
+        assert!(tail
+            .lines
+            .front()
+            .is_some_and(|first| !first.starts_with("000")));
+    }
 }
@@ -0,0 +1,523 @@
+use crate::{
+    config::app_config::ContextBudgetConfig,
+    models::{ContextContributorDto, ContextContributorKindDto, SendMessageErrorDto},
+};
+
+use super::{capabilities_for_engine, events::EngineEvent, TurnInput, TurnInputItem};
+
+/// Every estimate here uses the same chars/4 heuristic; it only has to be
+/// close enough to catch turns that are clearly oversized.
+const CHARS_PER_TOKEN: u64 = 4;
+/// Images are sent as files rather than inlined text, so their byte size says
+/// little about their token cost.
+const IMAGE_ATTACHMENT_TOKENS: u64 = 1_500;
+const MAX_LISTED_CONTRIBUTORS: usize = 5;
+/// Longest excerpt of a single message carried into a rolled-over session.
+const ROLLOVER_SEED_MESSAGE_MAX_CHARS: usize = 2_000;
+
+/// Context usage from the latest `UsageLimitsUpdated` snapshot for a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadContextUsage {
+    pub current_tokens: u64,
+    pub max_context_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextBudgetSource {
+    Config,
+    EngineReported,
+    Capabilities,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    pub max_input_tokens: u64,
+    /// Point past which a turn is refused: `max_input_tokens` scaled by the
+    /// configured threshold.
+    pub threshold_tokens: u64,
+    pub source: ContextBudgetSource,
+}
+
+/// Picks the input budget for `engine_id`: a configured override first, then
+/// the window the engine last reported for the thread, then the built-in
+/// capability estimate. Engines with none of these are not guarded.
+pub fn resolve_context_budget(
+    engine_id: &str,
+    config: &ContextBudgetConfig,
+    usage: Option<&ThreadContextUsage>,
+) -> Option<ContextBudget> {
+    let (max_input_tokens, source) =
+        if let Some(max) = config.engine_max_input_tokens.get(engine_id) {
+            (*max, ContextBudgetSource::Config)
+        } else if let Some(max) = usage.and_then(|usage| usage.max_context_tokens) {
+            (max, ContextBudgetSource::EngineReported)
+        } else {
+            (
+                capabilities_for_engine(engine_id).approx_max_input_tokens?,
+                ContextBudgetSource::Capabilities,
+            )
+        };
+    if max_input_tokens == 0 {
+        return None;
+    }
+    let percent = u64::from(config.threshold_percent.clamp(1, 100));
+    Some(ContextBudget {
+        max_input_tokens,
+        threshold_tokens: max_input_tokens.saturating_mul(percent) / 100,
+        source,
+    })
+}
+
+/// Share of the budget's input window, in percent, that `usage` fills.
+pub fn context_used_percent(usage: &ThreadContextUsage, budget: &ContextBudget) -> u64 {
+    usage.current_tokens.saturating_mul(100) / budget.max_input_tokens.max(1)
+}
+
+/// True when rollover is enabled and the thread's context usage reached the
+/// configured threshold.
+pub fn context_rollover_due(config: &ContextBudgetConfig, used_percent: u64) -> bool {
+    config.rollover_enabled
+        && used_percent >= u64::from(config.rollover_threshold_percent.clamp(1, 100))
+}
+
+/// Text put ahead of the first message of a rolled-over engine session:
+/// the thread's title and its most recent `(role, text)` messages, oldest
+/// first. Long messages are cut short.
+pub fn rollover_seed_prompt(title: &str, messages: &[(String, String)]) -> String {
+    let mut prompt = format!(
+        "This conversation continues the thread \"{}\", whose previous session ran out of context.",
+        title.trim()
+    );
+    if !messages.is_empty() {
+        prompt.push_str(" Its most recent messages were:\n");
+        for (role, text) in messages {
+            let speaker = if role == "user" { "User" } else { "Assistant" };
+            let text = text.trim();
+            let excerpt = match text.char_indices().nth(ROLLOVER_SEED_MESSAGE_MAX_CHARS) {
+                Some((cut, _)) => format!("{} [truncated]", &text[..cut]),
+                None => text.to_string(),
+            };
+            prompt.push_str(&format!("\n{speaker}: {excerpt}\n"));
+        }
+    }
+    prompt.push_str("\nContinue from there. The new message follows.\n\n---\n\n");
+    prompt
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextContributorKind {
+    Message,
+    Attachment,
+    ContextFile,
+    Skill,
+    Instructions,
+    ThreadHistory,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextContributor {
+    pub kind: ContextContributorKind,
+    pub label: String,
+    pub tokens: u64,
+}
+
+/// Estimated size of an outgoing turn; contributors are sorted largest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextEstimate {
+    pub total_tokens: u64,
+    pub contributors: Vec<ContextContributor>,
+}
+
+fn tokens_for_bytes(bytes: u64) -> u64 {
+    bytes.div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Estimates what a turn adds on top of the thread's current context.
+/// `file_size` resolves attachment, mention and skill paths; paths it cannot
+/// resolve count as empty.
+pub fn estimate_turn_context(
+    input: &TurnInput,
+    instructions_chars: usize,
+    usage: Option<&ThreadContextUsage>,
+    file_size: impl Fn(&str) -> Option<u64>,
+) -> ContextEstimate {
+    let mut contributors = vec![ContextContributor {
+        kind: ContextContributorKind::Message,
+        label: "message".to_string(),
+        tokens: tokens_for_bytes(input.message.len() as u64),
+    }];
+
+    for attachment in &input.attachments {
+        let is_image = attachment
+            .mime_type
+            .as_deref()
+            .is_some_and(|mime| mime.trim().to_lowercase().starts_with("image/"));
+        let tokens = if is_image {
+            IMAGE_ATTACHMENT_TOKENS
+        } else if attachment.size_bytes > 0 {
+            tokens_for_bytes(attachment.size_bytes)
+        } else {
+            tokens_for_bytes(file_size(&attachment.file_path).unwrap_or_default())
+        };
+        contributors.push(ContextContributor {
+            kind: ContextContributorKind::Attachment,
+            label: attachment.file_name.clone(),
+            tokens,
+        });
+    }
+
+    for item in &input.input_items {
+        let (kind, name, path) = match item {
+            TurnInputItem::Text { .. } => continue,
+            TurnInputItem::Mention { name, path } => {
+                (ContextContributorKind::ContextFile, name, path)
+            }
+            TurnInputItem::Skill { name, path } => (ContextContributorKind::Skill, name, path),
+        };
+        contributors.push(ContextContributor {
+            kind,
+            label: name.clone(),
+            tokens: tokens_for_bytes(file_size(path).unwrap_or_default()),
+        });
+    }
+
+    contributors.push(ContextContributor {
+        kind: ContextContributorKind::Instructions,
+        label: "instructions".to_string(),
+        tokens: tokens_for_bytes(instructions_chars as u64),
+    });
+    if let Some(usage) = usage {
+        contributors.push(ContextContributor {
+            kind: ContextContributorKind::ThreadHistory,
+            label: "thread history".to_string(),
+            tokens: usage.current_tokens,
+        });
+    }
+
+    contributors.retain(|contributor| contributor.tokens > 0);
+    contributors.sort_by(|a, b| b.tokens.cmp(&a.tokens).then_with(|| a.label.cmp(&b.label)));
+    ContextEstimate {
+        total_tokens: contributors
+            .iter()
+            .map(|contributor| contributor.tokens)
+            .sum(),
+        contributors,
+    }
+}
+
+/// The estimated turn would push the thread past its engine's input budget.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "This turn is too large for {engine_id}: ~{estimated_tokens} tokens against a budget of {} (max ~{}). Largest contributors: {}. Try: {}.",
+    .budget.threshold_tokens,
+    .budget.max_input_tokens,
+    format_contributors(.contributors),
+    .suggestions.join("; ")
+)]
+pub struct ContextBudgetExceededError {
+    pub engine_id: String,
+    pub estimated_tokens: u64,
+    pub budget: ContextBudget,
+    pub contributors: Vec<ContextContributor>,
+    pub suggestions: Vec<String>,
+}
+
+impl ContextBudgetExceededError {
+    /// Inline warning for a turn the user chose to send anyway.
+    pub fn forced_notice(&self) -> EngineEvent {
+        EngineEvent::Notice {
+            kind: "context_budget".to_string(),
+            level: "warning".to_string(),
+            title: "Context budget exceeded".to_string(),
+            message: format!(
+                "Sent anyway with ~{} tokens against a budget of {}. Largest contributors: {}.",
+                self.estimated_tokens,
+                self.budget.threshold_tokens,
+                format_contributors(&self.contributors)
+            ),
+        }
+    }
+}
+
+impl From<ContextBudgetExceededError> for SendMessageErrorDto {
+    fn from(error: ContextBudgetExceededError) -> Self {
+        let message = error.to_string();
+        Self::ContextBudgetExceeded {
+            engine_id: error.engine_id,
+            estimated_tokens: error.estimated_tokens,
+            threshold_tokens: error.budget.threshold_tokens,
+            max_input_tokens: error.budget.max_input_tokens,
+            contributors: error
+                .contributors
+                .into_iter()
+                .map(|contributor| ContextContributorDto {
+                    kind: contributor.kind.into(),
+                    label: contributor.label,
+                    tokens: contributor.tokens,
+                })
+                .collect(),
+            suggestions: error.suggestions,
+            message,
+        }
+    }
+}
+
+impl From<ContextContributorKind> for ContextContributorKindDto {
+    fn from(kind: ContextContributorKind) -> Self {
+        match kind {
+            ContextContributorKind::Message => Self::Message,
+            ContextContributorKind::Attachment => Self::Attachment,
+            ContextContributorKind::ContextFile => Self::ContextFile,
+            ContextContributorKind::Skill => Self::Skill,
+            ContextContributorKind::Instructions => Self::Instructions,
+            ContextContributorKind::ThreadHistory => Self::ThreadHistory,
+        }
+    }
+}
+
+pub fn check_context_budget(
+    engine_id: &str,
+    estimate: &ContextEstimate,
+    budget: &ContextBudget,
+) -> Result<(), ContextBudgetExceededError> {
+    if estimate.total_tokens <= budget.threshold_tokens {
+        return Ok(());
+    }
+    let contributors = estimate
+        .contributors
+        .iter()
+        .take(MAX_LISTED_CONTRIBUTORS)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut suggestions = contributors
+        .iter()
+        .filter_map(|contributor| suggestion_for(engine_id, contributor))
+        .collect::<Vec<_>>();
+    if suggestions.is_empty() {
+        suggestions.push("start a new thread".to_string());
+    }
+    Err(ContextBudgetExceededError {
+        engine_id: engine_id.to_string(),
+        estimated_tokens: estimate.total_tokens,
+        budget: *budget,
+        contributors,
+        suggestions,
+    })
+}
+
+fn suggestion_for(engine_id: &str, contributor: &ContextContributor) -> Option<String> {
+    let label = &contributor.label;
+    match contributor.kind {
+        ContextContributorKind::Message => Some("shorten the message".to_string()),
+        ContextContributorKind::Attachment => Some(format!("drop attachment `{label}`")),
+        ContextContributorKind::ContextFile => Some(format!("remove context file `{label}`")),
+        ContextContributorKind::Skill => Some(format!("drop skill `{label}`")),
+        ContextContributorKind::ThreadHistory if engine_id == "codex" => {
+            Some("compact the thread".to_string())
+        }
+        ContextContributorKind::ThreadHistory => Some("start a new thread".to_string()),
+        ContextContributorKind::Instructions => None,
+    }
+}
+
+fn format_contributors(contributors: &[ContextContributor]) -> String {
+    contributors
+        .iter()
+        .map(|contributor| format!("{} ~{}", contributor.label, contributor.tokens))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::engines::TurnAttachment;
+
+    fn turn_input() -> TurnInput {
+        TurnInput {
+            message: "x".repeat(400),
+            attachments: vec![
+                TurnAttachment {
+                    file_name: "dump.log".to_string(),
+                    file_path: "/tmp/dump.log".to_string(),
+                    size_bytes: 40_000,
+                    mime_type: Some("text/plain".to_string()),
+                },
+                TurnAttachment {
+                    file_name: "shot.png".to_string(),
+                    file_path: "/tmp/shot.png".to_string(),
+                    size_bytes: 4_000_000,
+                    mime_type: Some("image/png".to_string()),
+                },
+            ],
+            plan_mode: false,
+            input_items: vec![
+                TurnInputItem::Text {
+                    text: "x".repeat(400),
+                },
+                TurnInputItem::Mention {
+                    name: "schema.sql".to_string(),
+                    path: "/repo/schema.sql".to_string(),
+                },
+            ],
+        }
+    }
+
+    fn fake_size(path: &str) -> Option<u64> {
+        (path == "/repo/schema.sql").then_some(8_000)
+    }
+
+    #[test]
+    fn contributors_are_sorted_largest_first() {
+        let usage = ThreadContextUsage {
+            current_tokens: 5_000,
+            max_context_tokens: None,
+        };
+
+        let estimate = estimate_turn_context(&turn_input(), 40, Some(&usage), fake_size);
+
+        let breakdown = estimate
+            .contributors
+            .iter()
+            .map(|contributor| (contributor.label.as_str(), contributor.tokens))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            breakdown,
+            vec![
+                ("dump.log", 10_000),
+                ("thread history", 5_000),
+                ("schema.sql", 2_000),
+                ("shot.png", IMAGE_ATTACHMENT_TOKENS),
+                ("message", 100),
+                ("instructions", 10),
+            ]
+        );
+        assert_eq!(estimate.total_tokens, 18_610);
+    }
+
+    #[test]
+    fn budget_resolution_prefers_config_then_engine_report_then_capabilities() {
+        let mut config = ContextBudgetConfig::default();
+        let reported = ThreadContextUsage {
+            current_tokens: 0,
+            max_context_tokens: Some(100_000),
+        };
+
+        let codex = resolve_context_budget("codex", &config, None).expect("codex budget");
+        assert_eq!(codex.source, ContextBudgetSource::Capabilities);
+        assert_eq!(codex.max_input_tokens, 272_000);
+        assert_eq!(codex.threshold_tokens, 244_800);
+
+        let claude =
+            resolve_context_budget("claude", &config, Some(&reported)).expect("claude budget");
+        assert_eq!(claude.source, ContextBudgetSource::EngineReported);
+        assert_eq!(claude.max_input_tokens, 100_000);
+
+        assert!(resolve_context_budget("opencode", &config, None).is_none());
+
+        config.engine_max_input_tokens =
+            BTreeMap::from([("opencode".to_string(), 50_000), ("claude".to_string(), 0)]);
+        config.threshold_percent = 50;
+        let opencode = resolve_context_budget("opencode", &config, None).expect("opencode budget");
+        assert_eq!(opencode.source, ContextBudgetSource::Config);
+        assert_eq!(opencode.threshold_tokens, 25_000);
+        assert!(
+            resolve_context_budget("claude", &config, Some(&reported)).is_none(),
+            "a zero override disables the guard"
+        );
+    }
+
+    #[test]
+    fn rollover_is_due_only_when_enabled_and_past_the_threshold() {
+        let mut config = ContextBudgetConfig::default();
+        let budget = resolve_context_budget("codex", &config, None).expect("codex budget");
+        let usage = ThreadContextUsage {
+            current_tokens: 244_800,
+            max_context_tokens: None,
+        };
+        let used_percent = context_used_percent(&usage, &budget);
+        assert_eq!(used_percent, 90);
+        assert!(!context_rollover_due(&config, used_percent));
+
+        config.rollover_enabled = true;
+        assert!(context_rollover_due(&config, used_percent));
+        config.rollover_threshold_percent = 95;
+        assert!(!context_rollover_due(&config, used_percent));
+    }
+
+    #[test]
+    fn rollover_seed_names_the_thread_and_truncates_long_messages() {
+        let messages = vec![
+            ("user".to_string(), "Fix the parser".to_string()),
+            (
+                "assistant".to_string(),
+                "y".repeat(ROLLOVER_SEED_MESSAGE_MAX_CHARS + 10),
+            ),
+        ];
+
+        let seed = rollover_seed_prompt(" Parser work ", &messages);
+
+        assert!(seed.starts_with("This conversation continues the thread \"Parser work\""));
+        assert!(seed.contains("\nUser: Fix the parser\n"));
+        let truncated = format!(
+            "\nAssistant: {} [truncated]\n",
+            "y".repeat(ROLLOVER_SEED_MESSAGE_MAX_CHARS)
+        );
+        assert!(seed.contains(&truncated));
+        assert!(seed.ends_with("---\n\n"));
+    }
+
+    #[test]
+    fn oversized_turn_is_refused_with_suggestions_and_forced_turn_gets_a_warning() {
+        let budget = ContextBudget {
+            max_input_tokens: 10_000,
+            threshold_tokens: 9_000,
+            source: ContextBudgetSource::Config,
+        };
+        let usage = ThreadContextUsage {
+            current_tokens: 5_000,
+            max_context_tokens: None,
+        };
+        let estimate = estimate_turn_context(&turn_input(), 0, Some(&usage), fake_size);
+
+        let error = check_context_budget("codex", &estimate, &budget)
+            .expect_err("turn should exceed the budget");
+        assert_eq!(error.contributors[0].label, "dump.log");
+        assert_eq!(
+            error.suggestions[..3],
+            [
+                "drop attachment `dump.log`".to_string(),
+                "compact the thread".to_string(),
+                "remove context file `schema.sql`".to_string(),
+            ]
+        );
+        assert!(error
+            .to_string()
+            .contains("~18600 tokens against a budget of 9000"));
+
+        match error.forced_notice() {
+            EngineEvent::Notice { kind, level, .. } => {
+                assert_eq!(kind, "context_budget");
+                assert_eq!(level, "warning");
+            }
+            other => panic!("expected a notice, got {other:?}"),
+        }
+
+        let message = error.to_string();
+        let dto = serde_json::to_value(SendMessageErrorDto::from(error)).unwrap();
+        assert_eq!(dto["kind"], "context_budget_exceeded");
+        assert_eq!(dto["estimatedTokens"], 18_600);
+        assert_eq!(dto["thresholdTokens"], 9_000);
+        assert_eq!(dto["contributors"][0]["kind"], "attachment");
+        assert_eq!(dto["contributors"][0]["label"], "dump.log");
+        assert_eq!(dto["message"], message);
+
+        let small = ContextEstimate {
+            total_tokens: 9_000,
+            contributors: Vec::new(),
+        };
+        assert!(check_context_budget("codex", &small, &budget).is_ok());
+    }
+}
@@ -194,6 +194,7 @@ pub enum ActionType {
     Command,
     Git,
     Search,
+    McpTool,
     Other,
 }
 
@@ -207,6 +208,7 @@ impl ActionType {
             ActionType::Command => "command",
             ActionType::Git => "git",
             ActionType::Search => "search",
+            ActionType::McpTool => "mcp_tool",
             ActionType::Other => "other",
         }
     }
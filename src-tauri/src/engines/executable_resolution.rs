@@ -0,0 +1,415 @@
+//! Locates engine CLIs the way a user's terminal would. Apps launched from
+//! Finder or a desktop entry inherit a minimal PATH, so the lookup widens in
+//! steps and records which step found the binary.
+
+use std::path::{Path, PathBuf};
+
+use tokio::{
+    process::Command,
+    time::{timeout, Duration},
+};
+
+use crate::{process_utils, runtime_env};
+
+#[cfg(not(target_os = "windows"))]
+const LOGIN_SHELL_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Found on the PATH the app process was started with.
+pub const SOURCE_APP_PATH: &str = "app-path";
+/// Found in a well-known install location (`~/.local/bin`, npm global bin,
+/// nvm node versions, Homebrew, ...) missing from the app PATH.
+pub const SOURCE_KNOWN_PATH: &str = "known-path";
+/// Only the user's login shell could find it.
+pub const SOURCE_LOGIN_SHELL: &str = "login-shell";
+pub const SOURCE_UNAVAILABLE: &str = "unavailable";
+
+#[derive(Debug, Clone)]
+pub struct ExecutableResolution {
+    pub executable: Option<PathBuf>,
+    pub source: &'static str,
+    pub app_path: Option<String>,
+    pub login_shell_executable: Option<PathBuf>,
+}
+
+pub async fn resolve_cli_executable(binary: &str) -> ExecutableResolution {
+    resolve_cli_executable_with(binary, |_| true).await
+}
+
+/// Like [`resolve_cli_executable`], skipping directories `keep_dir` rejects
+/// (e.g. Panes' own wrapper shims).
+pub async fn resolve_cli_executable_with(
+    binary: &str,
+    keep_dir: impl Fn(&Path) -> bool,
+) -> ExecutableResolution {
+    let app_path = std::env::var("PATH").ok();
+    let app_entries = app_path
+        .as_deref()
+        .map(|path| std::env::split_paths(path).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let searches = [
+        (SOURCE_APP_PATH, app_entries),
+        (SOURCE_KNOWN_PATH, runtime_env::augmented_path_entries()),
+    ];
+    for (source, mut entries) in searches {
+        entries.retain(|entry| keep_dir(entry));
+        if let Some(executable) = which_in_entries(binary, entries) {
+            return ExecutableResolution {
+                executable: Some(executable),
+                source,
+                app_path,
+                login_shell_executable: None,
+            };
+        }
+    }
+
+    let login_shell_executable = detect_via_login_shell(binary)
+        .await
+        .filter(|path| path.parent().is_none_or(&keep_dir));
+    ExecutableResolution {
+        executable: login_shell_executable.clone(),
+        source: if login_shell_executable.is_some() {
+            SOURCE_LOGIN_SHELL
+        } else {
+            SOURCE_UNAVAILABLE
+        },
+        app_path,
+        login_shell_executable,
+    }
+}
+
+fn which_in_entries(binary: &str, entries: Vec<PathBuf>) -> Option<PathBuf> {
+    if entries.is_empty() {
+        return None;
+    }
+    let search_path = std::env::join_paths(entries).ok()?;
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    which::which_in(binary, Some(search_path), cwd).ok()
+}
+
+/// Checks a user-supplied engine binary path before Panes spawns it in place
+/// of the resolved CLI.
+pub fn validate_executable_override(path: &Path) -> anyhow::Result<()> {
+    if !path.is_absolute() {
+        anyhow::bail!(
+            "engine executable override `{}` must be an absolute path",
+            path.display()
+        );
+    }
+    if !path.is_file() {
+        anyhow::bail!(
+            "engine executable override `{}` does not exist or is not a file",
+            path.display()
+        );
+    }
+    if !runtime_env::is_executable_file(path) {
+        anyhow::bail!(
+            "engine executable override `{}` is not executable",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Asks the user's login shell where `binary` lives.
+pub async fn detect_via_login_shell(binary: &str) -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        for powershell in runtime_env::windows_login_probe_shells() {
+            let mut cmd = Command::new(&powershell);
+            cmd.args([
+                "-NoLogo".to_string(),
+                "-Command".to_string(),
+                format!(
+                    "(Get-Command {binary} -ErrorAction SilentlyContinue | Select-Object -First 1).Source"
+                ),
+            ]);
+            process_utils::configure_tokio_command(&mut cmd);
+
+            let Ok(Ok(output)) = timeout(Duration::from_secs(10), cmd.output()).await else {
+                continue;
+            };
+            if !output.status.success() {
+                continue;
+            }
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let Some(path) = runtime_env::parse_windows_single_path_output(&stdout) else {
+                continue;
+            };
+
+            let path = PathBuf::from(path);
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        for shell in runtime_env::login_probe_shells() {
+            let mut cmd = Command::new(&shell);
+            cmd.args(runtime_env::login_probe_shell_args(
+                &shell,
+                &format!("command -v {binary}"),
+            ));
+            process_utils::configure_tokio_command(&mut cmd);
+
+            let output = match timeout(LOGIN_SHELL_PROBE_TIMEOUT, cmd.output()).await {
+                Err(_) => {
+                    log::warn!(
+                        "timed out probing `{binary}` via login shell `{}`",
+                        shell.display()
+                    );
+                    continue;
+                }
+                Ok(Ok(output)) if output.status.success() => output,
+                _ => continue,
+            };
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if let Some(path) = stdout
+                .lines()
+                .map(str::trim)
+                .find(|line| line.starts_with('/'))
+                .map(PathBuf::from)
+                .filter(|path| runtime_env::is_executable_file(path))
+            {
+                return Some(path);
+            }
+        }
+
+        None
+    }
+}
+
+/// Runs `<executable> --version`. Errors describe why it could not run, as
+/// opposed to the executable being missing.
+pub async fn probe_version(command: &mut Command, label: &str) -> Result<String, String> {
+    let output = timeout(Duration::from_secs(10), command.arg("--version").output())
+        .await
+        .map_err(|_| format!("`{label} --version` timed out"))?
+        .map_err(|error| format!("failed to execute `{label}`: {error}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        return Err(if !stderr.is_empty() {
+            stderr
+        } else if !stdout.is_empty() {
+            stdout
+        } else {
+            format!("process exited with status {}", output.status)
+        });
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| format!("{label} --version returned empty output"))
+}
+
+/// One-line note naming how the executable was found, when that was not
+/// the app PATH.
+pub fn resolution_note(name: &str, resolution: &ExecutableResolution) -> Option<String> {
+    if resolution.source == SOURCE_APP_PATH {
+        return None;
+    }
+
+    let executable = resolution.executable.as_ref()?;
+    Some(format!(
+        "{name} detected via {} at `{}`.",
+        resolution.source,
+        executable.display()
+    ))
+}
+
+pub fn app_path_preview(path: Option<&str>) -> String {
+    path.filter(|value| !value.trim().is_empty())
+        .unwrap_or("(empty)")
+        .to_string()
+}
+
+/// Commands that make `binary` visible to Panes. `npm_package` is the
+/// package that installs it on Windows.
+pub fn path_fix_commands_for_platform(
+    platform: &str,
+    binary: &str,
+    npm_package: &str,
+    resolution: &ExecutableResolution,
+    execution_error: Option<&str>,
+) -> Vec<String> {
+    if platform == "macos" {
+        let mut fixes = Vec::new();
+        if resolution.executable.is_none() {
+            if let Some(shell_path) = &resolution.login_shell_executable {
+                if let Some(bin_dir) = shell_path.parent() {
+                    fixes.push(format!(
+                        "launchctl setenv PATH \"{}:/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin\"",
+                        bin_dir.display()
+                    ));
+                    fixes.push("open -a Panes".to_string());
+                }
+            } else {
+                fixes.push(format!(
+                    "/bin/zsh -lic 'command -v {binary} && {binary} --version'"
+                ));
+                fixes.push("open -a Panes".to_string());
+            }
+        } else if execution_error.is_some() {
+            if let Some(bin_dir) = resolution
+                .executable
+                .as_ref()
+                .and_then(|executable| executable.parent())
+            {
+                fixes.push(format!(
+                    "launchctl setenv PATH \"{}:/opt/homebrew/bin:/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin\"",
+                    bin_dir.display()
+                ));
+            }
+            fixes.push(format!(
+                "/bin/zsh -lic 'command -v node && command -v {binary} && {binary} --version'"
+            ));
+            fixes.push("open -a Panes".to_string());
+        }
+
+        return fixes;
+    }
+
+    if platform == "windows" {
+        let mut fixes = Vec::new();
+        if resolution.executable.is_none() {
+            fixes.push(format!("npm install -g {npm_package}"));
+            fixes.push(format!("where {binary}"));
+            fixes.push("echo %APPDATA%".to_string());
+            fixes.push(
+                "Ensure `%APPDATA%\\npm` is present in PATH, then restart Panes.".to_string(),
+            );
+            return fixes;
+        }
+
+        if execution_error.is_some() {
+            fixes.push("where node".to_string());
+            fixes.push(format!("where {binary}"));
+            fixes.push("echo %PATH%".to_string());
+            fixes.push(
+                "Ensure Node.js 20+ is installed and visible to Panes, then restart the app."
+                    .to_string(),
+            );
+        }
+        return fixes;
+    }
+
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolution(
+        executable: Option<&str>,
+        source: &'static str,
+        login_shell_executable: Option<&str>,
+    ) -> ExecutableResolution {
+        ExecutableResolution {
+            executable: executable.map(PathBuf::from),
+            source,
+            app_path: Some("/usr/bin:/bin".to_string()),
+            login_shell_executable: login_shell_executable.map(PathBuf::from),
+        }
+    }
+
+    #[test]
+    fn macos_fixes_export_the_login_shell_bin_dir_to_launchd() {
+        let fixes = path_fix_commands_for_platform(
+            "macos",
+            "claude",
+            "@anthropic-ai/claude-code",
+            &resolution(
+                None,
+                SOURCE_UNAVAILABLE,
+                Some("/Users/me/.local/bin/claude"),
+            ),
+            None,
+        );
+        assert_eq!(
+            fixes,
+            vec![
+                "launchctl setenv PATH \"/Users/me/.local/bin:/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin\"".to_string(),
+                "open -a Panes".to_string(),
+            ]
+        );
+
+        let fixes = path_fix_commands_for_platform(
+            "macos",
+            "claude",
+            "@anthropic-ai/claude-code",
+            &resolution(Some("/opt/homebrew/bin/claude"), SOURCE_KNOWN_PATH, None),
+            Some("env: node: No such file or directory"),
+        );
+        assert!(fixes[0].starts_with("launchctl setenv PATH \"/opt/homebrew/bin:"));
+        assert!(fixes
+            .iter()
+            .any(|fix| fix.contains("command -v node && command -v claude")));
+    }
+
+    #[test]
+    fn validate_executable_override_rejects_missing_and_non_executable_paths() {
+        let dir =
+            std::env::temp_dir().join(format!("panes-executable-override-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+
+        let relative = validate_executable_override(Path::new("bin/codex")).unwrap_err();
+        assert!(relative.to_string().contains("must be an absolute path"));
+
+        let missing = dir.join("codex");
+        let error = validate_executable_override(&missing).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("does not exist or is not a file"));
+
+        let error = validate_executable_override(&dir).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("does not exist or is not a file"));
+
+        std::fs::write(&missing, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let error = validate_executable_override(&missing).unwrap_err();
+            assert!(error.to_string().contains("is not executable"));
+
+            std::fs::set_permissions(&missing, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        assert!(validate_executable_override(&missing).is_ok());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn resolution_note_names_the_source_unless_it_is_the_app_path() {
+        assert_eq!(
+            resolution_note(
+                "Claude Code",
+                &resolution(Some("/usr/bin/claude"), SOURCE_APP_PATH, None)
+            ),
+            None
+        );
+        assert_eq!(
+            resolution_note(
+                "Claude Code",
+                &resolution(Some("/home/me/.local/bin/claude"), SOURCE_KNOWN_PATH, None)
+            )
+            .as_deref(),
+            Some("Claude Code detected via known-path at `/home/me/.local/bin/claude`.")
+        );
+        assert_eq!(
+            resolution_note("Claude Code", &resolution(None, SOURCE_UNAVAILABLE, None)),
+            None
+        );
+    }
+}
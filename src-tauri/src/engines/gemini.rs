@@ -0,0 +1,988 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::Command,
+    sync::{mpsc, Mutex},
+    time::timeout,
+};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::{models::EngineHealthDto, process_utils, runtime_env};
+
+use super::{
+    executable_resolution::{self, app_path_preview, ExecutableResolution},
+    trim_action_output_delta_content, ActionResult, ActionType, ApprovalRequestRoute, Engine,
+    EngineEvent, EngineThread, ModelInfo, SandboxPolicy, ThreadScope, TokenUsage, TurnAttachment,
+    TurnCompletionStatus, TurnInput,
+};
+
+const GEMINI_NPM_PACKAGE: &str = "@google/gemini-cli";
+const GEMINI_STDERR_TAIL_LINES: usize = 20;
+const GEMINI_STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+const GEMINI_AUTH_ENV_VARS: &[&str] = &["GEMINI_API_KEY", "GOOGLE_API_KEY"];
+/// Prefix for threads that have not run a turn yet. It keeps them from being
+/// mistaken for a CLI session id (a UUID) when restored after a restart.
+const PENDING_THREAD_PREFIX: &str = "pending-";
+
+// ── Stream events ─────────────────────────────────────────────────────
+
+/// One line of `gemini --output-format stream-json`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum GeminiStreamEvent {
+    Init {
+        session_id: Option<String>,
+    },
+    Message {
+        role: String,
+        #[serde(default)]
+        content: String,
+    },
+    ToolUse {
+        tool_name: String,
+        tool_id: String,
+        #[serde(default)]
+        parameters: Value,
+    },
+    ToolResult {
+        tool_id: String,
+        status: String,
+        output: Option<String>,
+        error: Option<GeminiStreamError>,
+    },
+    Error {
+        severity: Option<String>,
+        message: String,
+    },
+    Result {
+        status: String,
+        error: Option<GeminiStreamError>,
+        stats: Option<GeminiStreamStats>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiStreamError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiStreamStats {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    cached: Option<u64>,
+}
+
+/// Turns the CLI's stream into `EngineEvent`s for a single turn.
+#[derive(Default)]
+struct GeminiEventMapper {
+    session_id: Option<String>,
+    tool_started_at: HashMap<String, Instant>,
+    completed: bool,
+}
+
+impl GeminiEventMapper {
+    fn map(&mut self, event: GeminiStreamEvent) -> Vec<EngineEvent> {
+        match event {
+            GeminiStreamEvent::Init { session_id } => {
+                if session_id.is_some() {
+                    self.session_id = session_id;
+                }
+                Vec::new()
+            }
+            GeminiStreamEvent::Message { role, content } => {
+                if role != "assistant" || content.is_empty() {
+                    return Vec::new();
+                }
+                vec![EngineEvent::TextDelta { content }]
+            }
+            GeminiStreamEvent::ToolUse {
+                tool_name,
+                tool_id,
+                parameters,
+            } => {
+                self.tool_started_at.insert(tool_id.clone(), Instant::now());
+                vec![EngineEvent::ActionStarted {
+                    action_id: tool_id.clone(),
+                    engine_action_id: Some(tool_id),
+                    action_type: tool_action_type(&tool_name),
+                    summary: tool_summary(&tool_name, &parameters),
+                    details: json!({
+                        "toolName": tool_name,
+                        "parameters": parameters,
+                    }),
+                }]
+            }
+            GeminiStreamEvent::ToolResult {
+                tool_id,
+                status,
+                output,
+                error,
+            } => {
+                let duration_ms = self
+                    .tool_started_at
+                    .remove(&tool_id)
+                    .map(|started_at| started_at.elapsed().as_millis() as u64)
+                    .unwrap_or(0);
+                vec![EngineEvent::ActionCompleted {
+                    action_id: tool_id,
+                    result: ActionResult {
+                        success: status == "success",
+                        output: output.map(|output| trim_action_output_delta_content(&output)),
+                        error: error.map(|error| error.message),
+                        diff: None,
+                        duration_ms,
+                    },
+                }]
+            }
+            GeminiStreamEvent::Error { severity, message } => {
+                if severity.as_deref() == Some("warning") {
+                    vec![EngineEvent::Notice {
+                        kind: "gemini_warning".to_string(),
+                        level: "warning".to_string(),
+                        title: "Gemini warning".to_string(),
+                        message,
+                    }]
+                } else {
+                    vec![EngineEvent::Error {
+                        message,
+                        recoverable: true,
+                    }]
+                }
+            }
+            GeminiStreamEvent::Result {
+                status,
+                error,
+                stats,
+            } => {
+                self.completed = true;
+                let mut events = Vec::new();
+                let status = if status == "success" {
+                    TurnCompletionStatus::Completed
+                } else {
+                    events.push(EngineEvent::Error {
+                        message: error
+                            .map(|error| error.message)
+                            .unwrap_or_else(|| format!("Gemini turn ended with status {status}")),
+                        recoverable: false,
+                    });
+                    TurnCompletionStatus::Failed
+                };
+                events.push(EngineEvent::TurnCompleted {
+                    token_usage: stats.map(|stats| TokenUsage {
+                        input: stats.input_tokens,
+                        output: stats.output_tokens,
+                        reasoning: None,
+                        cache_read: stats.cached,
+                        cache_write: None,
+                        cost_usd: None,
+                    }),
+                    status,
+                });
+                events
+            }
+            GeminiStreamEvent::Unknown => Vec::new(),
+        }
+    }
+}
+
+fn tool_action_type(tool_name: &str) -> ActionType {
+    match tool_name {
+        "read_file" | "read_many_files" | "list_directory" => ActionType::FileRead,
+        "write_file" => ActionType::FileWrite,
+        "replace" | "edit" => ActionType::FileEdit,
+        "run_shell_command" => ActionType::Command,
+        "glob" | "search_file_content" | "grep" | "google_web_search" => ActionType::Search,
+        _ => ActionType::Other,
+    }
+}
+
+fn tool_summary(tool_name: &str, parameters: &Value) -> String {
+    [
+        "command",
+        "file_path",
+        "absolute_path",
+        "path",
+        "pattern",
+        "query",
+    ]
+    .iter()
+    .find_map(|key| parameters.get(key).and_then(Value::as_str))
+    .map(str::trim)
+    .filter(|value| !value.is_empty())
+    .map(str::to_string)
+    .unwrap_or_else(|| tool_name.to_string())
+}
+
+// ── Per-thread config ─────────────────────────────────────────────────
+
+#[derive(Clone)]
+struct GeminiThread {
+    scope: ThreadScope,
+    model_id: String,
+    sandbox: SandboxPolicy,
+    session_id: Option<String>,
+}
+
+// ── Engine ─────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct GeminiState {
+    threads: HashMap<String, GeminiThread>,
+    active_turns: HashMap<String, CancellationToken>,
+}
+
+/// Runs each turn as a headless `gemini` process fed the prompt on stdin and
+/// maps its `stream-json` output. Conversation state lives in the CLI's own
+/// session store; threads only remember the session id to `--resume`.
+#[derive(Default)]
+pub struct GeminiEngine {
+    state: Arc<Mutex<GeminiState>>,
+}
+
+pub struct GeminiHealthReport {
+    pub available: bool,
+    pub version: Option<String>,
+    pub details: Option<String>,
+    pub warnings: Vec<String>,
+    pub checks: Vec<String>,
+    pub fixes: Vec<String>,
+}
+
+impl GeminiEngine {
+    pub async fn runtime_model_fallback(&self) -> Vec<ModelInfo> {
+        self.models()
+    }
+
+    pub async fn health_report(&self) -> GeminiHealthReport {
+        let resolution = resolve_gemini_executable().await;
+        let Some(executable) = resolution.executable.clone() else {
+            return gemini_unavailable_report(runtime_env::platform_id(), &resolution, None);
+        };
+
+        let version = match probe_gemini_version(&executable).await {
+            Ok(version) => version,
+            Err(error) => {
+                return gemini_unavailable_report(
+                    runtime_env::platform_id(),
+                    &resolution,
+                    Some(&error),
+                );
+            }
+        };
+
+        let mut checks = vec![format!("Gemini CLI resolved at `{}`", executable.display())];
+        let mut warnings = Vec::new();
+        let mut fixes = Vec::new();
+        if let Some(var) = GEMINI_AUTH_ENV_VARS
+            .iter()
+            .find(|var| std::env::var_os(var).is_some())
+        {
+            checks.push(format!("{var} is set"));
+        } else if gemini_settings_dir().is_some_and(|dir| dir.is_dir()) {
+            checks.push("Using the Gemini CLI login from ~/.gemini".to_string());
+        } else {
+            warnings.push(
+                "No Gemini credentials found. Headless turns fail until the CLI is signed in."
+                    .to_string(),
+            );
+            fixes.push(
+                "Run `gemini` once in a terminal to sign in, or set GEMINI_API_KEY.".to_string(),
+            );
+        }
+
+        let details = executable_resolution::resolution_note("Gemini CLI", &resolution)
+            .unwrap_or_else(|| format!("Gemini CLI executable: {}", executable.display()));
+        GeminiHealthReport {
+            available: true,
+            version: Some(version),
+            details: Some(details),
+            warnings,
+            checks,
+            fixes,
+        }
+    }
+
+    async fn finish_turn(&self, engine_thread_id: &str, session_id: Option<String>) {
+        let mut state = self.state.lock().await;
+        state.active_turns.remove(engine_thread_id);
+        if let (Some(session_id), Some(thread)) =
+            (session_id, state.threads.get_mut(engine_thread_id))
+        {
+            thread.session_id = Some(session_id);
+        }
+    }
+}
+
+async fn resolve_gemini_executable() -> ExecutableResolution {
+    executable_resolution::resolve_cli_executable("gemini").await
+}
+
+fn gemini_unavailable_report(
+    platform: &str,
+    resolution: &ExecutableResolution,
+    execution_error: Option<&str>,
+) -> GeminiHealthReport {
+    let path_preview = app_path_preview(resolution.app_path.as_deref());
+    let details = match (resolution.executable.as_ref(), execution_error) {
+        (Some(executable), Some(error)) => format!(
+            "Gemini CLI was found at `{}`, but Panes could not run it. App PATH: `{path_preview}`. Error: {error}",
+            executable.display()
+        ),
+        _ => format!("`gemini` executable not found. App PATH: `{path_preview}`"),
+    };
+    let mut fixes = executable_resolution::path_fix_commands_for_platform(
+        platform,
+        "gemini",
+        GEMINI_NPM_PACKAGE,
+        resolution,
+        execution_error,
+    );
+    if resolution.executable.is_none() && platform != "windows" {
+        fixes.insert(0, format!("npm install -g {GEMINI_NPM_PACKAGE}"));
+    }
+
+    GeminiHealthReport {
+        available: false,
+        version: None,
+        details: Some(details),
+        warnings: vec![],
+        checks: vec![],
+        fixes,
+    }
+}
+
+fn gemini_settings_dir() -> Option<PathBuf> {
+    runtime_env::home_dir().map(|home| home.join(".gemini"))
+}
+
+async fn probe_gemini_version(executable: &Path) -> Result<String, String> {
+    let mut command = Command::new(executable);
+    process_utils::configure_tokio_command(&mut command);
+    runtime_env::apply_missing_login_shell_env(&mut command).await;
+    if let Some(path) = executable_augmented_path(executable) {
+        command.env("PATH", path);
+    }
+    executable_resolution::probe_version(&mut command, &executable.to_string_lossy()).await
+}
+
+fn executable_augmented_path(executable: &Path) -> Option<OsString> {
+    runtime_env::augmented_path_with_prepend(
+        executable
+            .parent()
+            .into_iter()
+            .map(|value| value.to_path_buf()),
+    )
+}
+
+fn scope_cwd(scope: &ThreadScope) -> &str {
+    match scope {
+        ThreadScope::Repo { repo_path } => repo_path,
+        ThreadScope::Workspace { root_path, .. } => root_path,
+    }
+}
+
+/// Headless runs cannot prompt, so the CLI's approval mode decides which
+/// tools run at all: `default` drops anything that would need confirmation,
+/// `auto_edit` allows file edits, `yolo` allows everything.
+fn approval_mode(sandbox: &SandboxPolicy, plan_mode: bool) -> &'static str {
+    if plan_mode || sandbox.sandbox_mode.as_deref() == Some("read-only") {
+        return "default";
+    }
+    match sandbox.approval_policy.as_ref().and_then(Value::as_str) {
+        Some("never") => "yolo",
+        Some("untrusted") => "default",
+        _ => "auto_edit",
+    }
+}
+
+/// Attachments are passed with the CLI's `@path` syntax, which inlines the
+/// file (text or image) into the prompt.
+fn prompt_with_attachments(message: &str, attachments: &[TurnAttachment]) -> String {
+    let mut prompt = message.to_string();
+    for attachment in attachments {
+        prompt.push_str("\n@");
+        prompt.push_str(&attachment.file_path.replace(' ', "\\ "));
+    }
+    prompt
+}
+
+/// The prompt itself goes on stdin, which runs the CLI headless: argv is
+/// capped by `ARG_MAX` and readable by other users through `ps`.
+fn gemini_args(thread: &GeminiThread, plan_mode: bool) -> Vec<String> {
+    let mut args = vec![
+        "--output-format".to_string(),
+        "stream-json".to_string(),
+        "--model".to_string(),
+        thread.model_id.clone(),
+        "--approval-mode".to_string(),
+        approval_mode(&thread.sandbox, plan_mode).to_string(),
+    ];
+    if let ThreadScope::Workspace {
+        root_path,
+        writable_roots,
+    } = &thread.scope
+    {
+        let extra_roots = writable_roots
+            .iter()
+            .filter(|root| *root != root_path)
+            .cloned()
+            .collect::<Vec<_>>();
+        if !extra_roots.is_empty() {
+            args.push("--include-directories".to_string());
+            args.push(extra_roots.join(","));
+        }
+    }
+    if let Some(session_id) = thread.session_id.as_deref() {
+        args.push("--resume".to_string());
+        args.push(session_id.to_string());
+    }
+    args
+}
+
+/// The CLI has no per-run thinking flag, so no reasoning efforts are
+/// advertised and the model picker hides the effort control.
+fn gemini_model_info(
+    id: &str,
+    display_name: &str,
+    description: &str,
+    is_default: bool,
+) -> ModelInfo {
+    ModelInfo {
+        id: id.to_string(),
+        display_name: display_name.to_string(),
+        description: description.to_string(),
+        hidden: false,
+        is_default,
+        upgrade: None,
+        availability_nux: None,
+        upgrade_info: None,
+        input_modalities: vec!["text".to_string(), "image".to_string()],
+        attachment_modalities: vec!["text".to_string(), "image".to_string()],
+        limits: None,
+        supports_personality: false,
+        default_reasoning_effort: String::new(),
+        supported_reasoning_efforts: Vec::new(),
+    }
+}
+
+async fn collect_stderr_tail(stderr: tokio::process::ChildStderr) -> String {
+    let mut lines = BufReader::new(stderr).lines();
+    let mut tail = VecDeque::with_capacity(GEMINI_STDERR_TAIL_LINES);
+    while let Ok(Some(line)) = lines.next_line().await {
+        if tail.len() == GEMINI_STDERR_TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+    tail.into_iter().collect::<Vec<_>>().join("\n")
+}
+
+#[async_trait]
+impl Engine for GeminiEngine {
+    fn id(&self) -> &str {
+        "gemini"
+    }
+
+    fn name(&self) -> &str {
+        "Gemini"
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        vec![
+            gemini_model_info(
+                "gemini-2.5-pro",
+                "Gemini 2.5 Pro",
+                "Most capable stable Gemini model for complex coding work",
+                true,
+            ),
+            gemini_model_info(
+                "gemini-3-pro-preview",
+                "Gemini 3 Pro (Preview)",
+                "Latest Gemini model, available to preview-enabled accounts",
+                false,
+            ),
+            gemini_model_info(
+                "gemini-2.5-flash",
+                "Gemini 2.5 Flash",
+                "Fast model for everyday coding tasks",
+                false,
+            ),
+            gemini_model_info(
+                "gemini-2.5-flash-lite",
+                "Gemini 2.5 Flash-Lite",
+                "Lowest-latency Gemini model for simple edits",
+                false,
+            ),
+        ]
+    }
+
+    async fn is_available(&self) -> bool {
+        resolve_gemini_executable().await.executable.is_some()
+    }
+
+    async fn start_thread(
+        &self,
+        scope: ThreadScope,
+        resume_engine_thread_id: Option<&str>,
+        model: &str,
+        sandbox: SandboxPolicy,
+    ) -> Result<EngineThread, anyhow::Error> {
+        let mut state = self.state.lock().await;
+        let known = resume_engine_thread_id
+            .and_then(|id| state.threads.remove(id).map(|thread| (id, thread)));
+        let session_id = match (&known, resume_engine_thread_id) {
+            (Some((_, thread)), _) => thread.session_id.clone(),
+            (None, Some(id)) => Uuid::parse_str(id).is_ok().then(|| id.to_string()),
+            (None, None) => None,
+        };
+        // Once the CLI has reported its session, the thread is re-keyed by
+        // that id so the persisted engine thread id can `--resume` it.
+        let engine_thread_id = session_id
+            .clone()
+            .or_else(|| known.map(|(id, _)| id.to_string()))
+            .unwrap_or_else(|| format!("{PENDING_THREAD_PREFIX}{}", Uuid::new_v4()));
+        state.threads.insert(
+            engine_thread_id.clone(),
+            GeminiThread {
+                scope,
+                model_id: model.to_string(),
+                sandbox,
+                session_id,
+            },
+        );
+
+        Ok(EngineThread { engine_thread_id })
+    }
+
+    async fn send_message(
+        &self,
+        engine_thread_id: &str,
+        input: TurnInput,
+        event_tx: mpsc::Sender<EngineEvent>,
+        cancellation: CancellationToken,
+    ) -> Result<(), anyhow::Error> {
+        let executable = resolve_gemini_executable()
+            .await
+            .executable
+            .context("`gemini` executable not found in PATH")?;
+        let turn_cancellation = cancellation.child_token();
+        let thread = {
+            let mut state = self.state.lock().await;
+            let thread = state
+                .threads
+                .get(engine_thread_id)
+                .cloned()
+                .context("no thread config found — was start_thread called?")?;
+            state
+                .active_turns
+                .insert(engine_thread_id.to_string(), turn_cancellation.clone());
+            thread
+        };
+
+        let prompt = prompt_with_attachments(&input.message, &input.attachments);
+        let mut command = Command::new(&executable);
+        process_utils::configure_tokio_command(&mut command);
+        runtime_env::apply_missing_login_shell_env(&mut command).await;
+        command
+            .args(gemini_args(&thread, input.plan_mode))
+            .current_dir(scope_cwd(&thread.scope))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(path) = executable_augmented_path(&executable) {
+            command.env("PATH", path);
+        }
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(error) => {
+                self.finish_turn(engine_thread_id, None).await;
+                return Err(error).with_context(|| {
+                    format!("failed to spawn Gemini CLI at {}", executable.display())
+                });
+            }
+        };
+        // Written from a task so a CLI that streams output before it has read
+        // the whole prompt cannot deadlock the turn; dropping stdin ends it.
+        if let Some(mut stdin) = child.stdin.take() {
+            tokio::spawn(async move {
+                if let Err(error) = stdin.write_all(prompt.as_bytes()).await {
+                    log::warn!("failed to write the prompt to Gemini CLI: {error}");
+                }
+            });
+        }
+        let Some(stdout) = child.stdout.take() else {
+            self.finish_turn(engine_thread_id, None).await;
+            anyhow::bail!("Gemini stdout not available");
+        };
+        let stderr_task = child
+            .stderr
+            .take()
+            .map(|stderr| tokio::spawn(collect_stderr_tail(stderr)));
+
+        event_tx
+            .send(EngineEvent::TurnStarted {
+                client_turn_id: None,
+            })
+            .await
+            .ok();
+
+        let mut mapper = GeminiEventMapper::default();
+        let mut lines = BufReader::new(stdout).lines();
+        loop {
+            tokio::select! {
+                _ = turn_cancellation.cancelled() => {
+                    let _ = child.kill().await;
+                    if let Some(task) = stderr_task {
+                        task.abort();
+                    }
+                    self.finish_turn(engine_thread_id, mapper.session_id).await;
+                    // An external cancellation settles the turn itself; an
+                    // `interrupt` needs the completion reported here.
+                    if !cancellation.is_cancelled() {
+                        event_tx
+                            .send(EngineEvent::TurnCompleted {
+                                token_usage: None,
+                                status: TurnCompletionStatus::Interrupted,
+                            })
+                            .await
+                            .ok();
+                    }
+                    return Ok(());
+                }
+                line = lines.next_line() => {
+                    let line = match line {
+                        Ok(Some(line)) => line,
+                        Ok(None) => break,
+                        Err(error) => {
+                            log::warn!("failed reading Gemini CLI output: {error}");
+                            break;
+                        }
+                    };
+                    let line = line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    let event = match serde_json::from_str::<GeminiStreamEvent>(line) {
+                        Ok(event) => event,
+                        Err(error) => {
+                            log::debug!("skipping unparseable Gemini CLI line ({error}): {line}");
+                            continue;
+                        }
+                    };
+                    for event in mapper.map(event) {
+                        event_tx.send(event).await.ok();
+                    }
+                    if mapper.completed {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let exit_status = child.wait().await.ok();
+        let stderr_tail = match stderr_task {
+            Some(task) => timeout(GEMINI_STDERR_DRAIN_TIMEOUT, task)
+                .await
+                .ok()
+                .and_then(Result::ok)
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+        if !mapper.completed {
+            let message = if stderr_tail.trim().is_empty() {
+                format!(
+                    "Gemini CLI exited before finishing the turn ({})",
+                    exit_status
+                        .map(|status| status.to_string())
+                        .unwrap_or_else(|| "unknown status".to_string())
+                )
+            } else {
+                stderr_tail.trim().to_string()
+            };
+            event_tx
+                .send(EngineEvent::Error {
+                    message,
+                    recoverable: false,
+                })
+                .await
+                .ok();
+            event_tx
+                .send(EngineEvent::TurnCompleted {
+                    token_usage: None,
+                    status: TurnCompletionStatus::Failed,
+                })
+                .await
+                .ok();
+        }
+        self.finish_turn(engine_thread_id, mapper.session_id).await;
+        Ok(())
+    }
+
+    async fn steer_message(
+        &self,
+        _engine_thread_id: &str,
+        _input: TurnInput,
+    ) -> Result<(), anyhow::Error> {
+        anyhow::bail!("Gemini does not support mid-turn steering")
+    }
+
+    async fn respond_to_approval(
+        &self,
+        _approval_id: &str,
+        _response: serde_json::Value,
+        _route: Option<ApprovalRequestRoute>,
+    ) -> Result<(), anyhow::Error> {
+        anyhow::bail!("Gemini runs headless and never requests approvals")
+    }
+
+    async fn interrupt(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
+        if let Some(token) = self.state.lock().await.active_turns.get(engine_thread_id) {
+            token.cancel();
+        }
+        Ok(())
+    }
+
+    async fn archive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
+        let mut state = self.state.lock().await;
+        state.threads.remove(engine_thread_id);
+        Ok(())
+    }
+
+    async fn unarchive_thread(&self, _engine_thread_id: &str) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn health(&self) -> EngineHealthDto {
+        let report = self.health_report().await;
+        EngineHealthDto {
+            id: "gemini".to_string(),
+            available: report.available,
+            version: report.version,
+            details: report.details,
+            warnings: report.warnings,
+            checks: report.checks,
+            fixes: report.fixes,
+            protocol_diagnostics: None,
+            timeouts: None,
+            auth_status: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(approval_policy: Option<&str>, sandbox_mode: Option<&str>) -> SandboxPolicy {
+        SandboxPolicy {
+            writable_roots: Vec::new(),
+            allow_network: false,
+            approval_policy: approval_policy.map(|value| json!(value)),
+            permission_profile: None,
+            approvals_reviewer: None,
+            reasoning_effort: None,
+            sandbox_mode: sandbox_mode.map(str::to_string),
+            service_tier: None,
+            personality: None,
+            output_schema: None,
+            opencode_agent: None,
+            executable_override: None,
+            custom_instructions: None,
+        }
+    }
+
+    fn map_lines(lines: &[&str]) -> (GeminiEventMapper, Vec<EngineEvent>) {
+        let mut mapper = GeminiEventMapper::default();
+        let events = lines
+            .iter()
+            .flat_map(|line| mapper.map(serde_json::from_str(line).expect("stream event")))
+            .collect();
+        (mapper, events)
+    }
+
+    #[test]
+    fn maps_a_streamed_turn_into_engine_events() {
+        let (mapper, events) = map_lines(&[
+            r#"{"type":"init","session_id":"1f7a","model":"gemini-2.5-pro"}"#,
+            r#"{"type":"message","role":"user","content":"list files"}"#,
+            r#"{"type":"tool_use","tool_name":"run_shell_command","tool_id":"t1","parameters":{"command":"ls"}}"#,
+            r#"{"type":"tool_result","tool_id":"t1","status":"success","output":"a.rs"}"#,
+            r#"{"type":"message","role":"assistant","content":"Found a.rs","delta":true}"#,
+            r#"{"type":"result","status":"success","stats":{"input_tokens":120,"output_tokens":8,"cached":40}}"#,
+        ]);
+
+        assert_eq!(mapper.session_id.as_deref(), Some("1f7a"));
+        assert!(mapper.completed);
+        assert_eq!(events.len(), 4);
+        match &events[0] {
+            EngineEvent::ActionStarted {
+                action_id,
+                action_type: ActionType::Command,
+                summary,
+                ..
+            } => assert_eq!((action_id.as_str(), summary.as_str()), ("t1", "ls")),
+            other => panic!("unexpected event {other:?}"),
+        }
+        match &events[1] {
+            EngineEvent::ActionCompleted { result, .. } => {
+                assert!(result.success);
+                assert_eq!(result.output.as_deref(), Some("a.rs"));
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+        assert!(
+            matches!(&events[2], EngineEvent::TextDelta { content } if content == "Found a.rs")
+        );
+        match &events[3] {
+            EngineEvent::TurnCompleted {
+                token_usage: Some(usage),
+                status: TurnCompletionStatus::Completed,
+            } => assert_eq!(
+                (usage.input, usage.output, usage.cache_read),
+                (120, 8, Some(40))
+            ),
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[test]
+    fn failed_results_report_the_error_before_completing() {
+        let (_, events) = map_lines(&[
+            r#"{"type":"error","severity":"warning","message":"loop detected"}"#,
+            r#"{"type":"result","status":"error","error":{"type":"FatalAuthenticationError","message":"not signed in"}}"#,
+            r#"{"type":"something_new"}"#,
+        ]);
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], EngineEvent::Notice { level, .. } if level == "warning"));
+        assert!(
+            matches!(&events[1], EngineEvent::Error { message, .. } if message == "not signed in")
+        );
+        assert!(matches!(
+            &events[2],
+            EngineEvent::TurnCompleted {
+                status: TurnCompletionStatus::Failed,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn approval_mode_follows_sandbox_and_policy() {
+        assert_eq!(approval_mode(&sandbox(Some("never"), None), false), "yolo");
+        assert_eq!(
+            approval_mode(&sandbox(Some("never"), None), true),
+            "default"
+        );
+        assert_eq!(
+            approval_mode(&sandbox(Some("never"), Some("read-only")), false),
+            "default"
+        );
+        assert_eq!(
+            approval_mode(&sandbox(Some("untrusted"), None), false),
+            "default"
+        );
+        assert_eq!(
+            approval_mode(&sandbox(Some("on-request"), Some("workspace-write")), false),
+            "auto_edit"
+        );
+    }
+
+    #[test]
+    fn args_resume_sessions_and_include_extra_workspace_roots() {
+        let thread = GeminiThread {
+            scope: ThreadScope::Workspace {
+                root_path: "/ws".to_string(),
+                writable_roots: vec!["/ws".to_string(), "/ws/a".to_string(), "/ws/b".to_string()],
+            },
+            model_id: "gemini-2.5-flash".to_string(),
+            sandbox: sandbox(Some("on-request"), None),
+            session_id: Some("1f7a".to_string()),
+        };
+        let prompt = prompt_with_attachments(
+            "-v explain",
+            &[TurnAttachment {
+                file_name: "my notes.md".to_string(),
+                file_path: "/tmp/my notes.md".to_string(),
+                size_bytes: 3,
+                mime_type: None,
+            }],
+        );
+
+        assert_eq!(prompt, "-v explain\n@/tmp/my\\ notes.md");
+        assert_eq!(
+            gemini_args(&thread, false),
+            [
+                "--output-format",
+                "stream-json",
+                "--model",
+                "gemini-2.5-flash",
+                "--approval-mode",
+                "auto_edit",
+                "--include-directories",
+                "/ws/a,/ws/b",
+                "--resume",
+                "1f7a",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn threads_are_rekeyed_by_the_cli_session_once_known() {
+        let engine = GeminiEngine::default();
+        let start = |resume: Option<String>| {
+            let engine = &engine;
+            async move {
+                engine
+                    .start_thread(
+                        ThreadScope::Repo {
+                            repo_path: "/repo".to_string(),
+                        },
+                        resume.as_deref(),
+                        "gemini-2.5-pro",
+                        sandbox(None, None),
+                    )
+                    .await
+                    .expect("start thread")
+                    .engine_thread_id
+            }
+        };
+
+        let pending = start(None).await;
+        assert!(pending.starts_with(PENDING_THREAD_PREFIX));
+        assert_eq!(start(Some(pending.clone())).await, pending);
+
+        let session_id = Uuid::new_v4().to_string();
+        engine.finish_turn(&pending, Some(session_id.clone())).await;
+        assert_eq!(start(Some(pending.clone())).await, session_id);
+
+        let restored = Uuid::new_v4().to_string();
+        assert_eq!(start(Some(restored.clone())).await, restored);
+        let state = engine.state.lock().await;
+        assert!(!state.threads.contains_key(&pending));
+        assert_eq!(
+            state.threads[&restored].session_id.as_deref(),
+            Some(restored.as_str())
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use anyhow::Context;
 use async_trait::async_trait;
@@ -9,14 +9,21 @@ use tokio::time::{timeout, Duration};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
+    config::app_config::{ApiDirectConfig, AppConfig, CodexConfig, EngineTimeoutsConfig},
     engines::{
+        api_direct::ApiDirectEngine,
         claude_sidecar::ClaudeSidecarEngine,
-        codex::{CodexEngine, CodexForkedThread, CodexReviewStarted},
+        codex::{
+            CodexEngine, CodexForkedThread, CodexReviewStarted, CodexTransportRouting,
+            EngineTimeouts,
+        },
+        gemini::GeminiEngine,
         opencode::OpenCodeEngine,
+        turn_diagnostics::TurnDiagnosticsSink,
     },
     models::{
-        CodexAppDto, CodexSkillDto, EngineCapabilitiesDto, EngineHealthDto, EngineInfoDto,
-        EngineModelAvailabilityNuxDto, EngineModelDto, EngineModelUpgradeInfoDto,
+        CodexAppDto, CodexSkillDto, EngineAuthStatusDto, EngineCapabilitiesDto, EngineHealthDto,
+        EngineInfoDto, EngineModelAvailabilityNuxDto, EngineModelDto, EngineModelUpgradeInfoDto,
         OpenCodeRuntimeCatalogDto, ReasoningEffortOptionDto, ThreadDto,
     },
 };
@@ -26,9 +33,15 @@ pub mod claude_sidecar;
 pub mod codex;
 pub mod codex_event_mapper;
 pub mod codex_protocol;
+pub mod codex_server_requests;
 pub mod codex_transport;
+pub mod context_budget;
 pub mod events;
+pub mod executable_resolution;
+pub mod gemini;
 pub mod opencode;
+pub mod turn_diagnostics;
+pub mod turn_event_log;
 
 pub use codex::CodexRuntimeEvent;
 pub use events::*;
@@ -63,6 +76,11 @@ pub struct SandboxPolicy {
     pub personality: Option<String>,
     pub output_schema: Option<Value>,
     pub opencode_agent: Option<String>,
+    /// Binary to run instead of the resolved engine CLI (Codex only).
+    pub executable_override: Option<PathBuf>,
+    /// Per-thread instructions layered on top of the engine's own system
+    /// prompt (Codex and Claude).
+    pub custom_instructions: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -114,39 +132,150 @@ pub struct EngineCapabilities {
     pub permission_modes: &'static [&'static str],
     pub sandbox_modes: &'static [&'static str],
     pub approval_decisions: &'static [&'static str],
+    /// Rough input budget used by the pre-send context guardrail; `None`
+    /// when it depends on the provider behind the engine.
+    pub approx_max_input_tokens: Option<u64>,
+    pub plan_mode: bool,
+    pub attachments: bool,
+    pub interrupt: bool,
+    pub reasoning_effort: bool,
+    /// Renames reach the engine's own thread title, not just Panes'.
+    pub thread_rename: bool,
+    /// Approvals still waiting when a turn ends are denied by the engine, so
+    /// they must not stay answerable afterwards.
+    pub approvals_end_with_turn: bool,
+}
+
+impl EngineCapabilities {
+    pub fn approvals(&self) -> bool {
+        !self.approval_decisions.is_empty()
+    }
 }
 
 const CODEX_CAPABILITIES: EngineCapabilities = EngineCapabilities {
     permission_modes: &["untrusted", "on-failure", "on-request", "never"],
     sandbox_modes: &["read-only", "workspace-write", "danger-full-access"],
     approval_decisions: &["accept", "decline", "cancel", "accept_for_session"],
+    approx_max_input_tokens: Some(272_000),
+    plan_mode: true,
+    attachments: true,
+    interrupt: true,
+    reasoning_effort: true,
+    thread_rename: true,
+    approvals_end_with_turn: false,
 };
 
 const CLAUDE_CAPABILITIES: EngineCapabilities = EngineCapabilities {
     permission_modes: &["restricted", "standard", "trusted"],
     sandbox_modes: &["read-only", "workspace-write"],
     approval_decisions: &["accept", "decline", "accept_for_session"],
+    approx_max_input_tokens: Some(200_000),
+    plan_mode: true,
+    attachments: true,
+    interrupt: true,
+    reasoning_effort: true,
+    thread_rename: false,
+    approvals_end_with_turn: true,
+};
+
+/// Gemini runs headless, so it never asks for approvals; the Panes policy
+/// picks the CLI's `--approval-mode` instead.
+const GEMINI_CAPABILITIES: EngineCapabilities = EngineCapabilities {
+    permission_modes: &["untrusted", "on-request", "never"],
+    sandbox_modes: &["read-only", "workspace-write"],
+    approval_decisions: &[],
+    approx_max_input_tokens: Some(1_000_000),
+    plan_mode: true,
+    attachments: true,
+    interrupt: true,
+    reasoning_effort: false,
+    thread_rename: false,
+    approvals_end_with_turn: false,
+};
+
+/// The API engine only streams chat completions: it runs no tools, so
+/// there is nothing to sandbox or approve.
+const API_CAPABILITIES: EngineCapabilities = EngineCapabilities {
+    permission_modes: &[],
+    sandbox_modes: &[],
+    approval_decisions: &[],
+    approx_max_input_tokens: None,
+    plan_mode: false,
+    attachments: true,
+    interrupt: true,
+    reasoning_effort: false,
+    thread_rename: false,
+    approvals_end_with_turn: false,
 };
 
 const OPENCODE_CAPABILITIES: EngineCapabilities = EngineCapabilities {
     permission_modes: &["ask", "allow", "deny"],
     sandbox_modes: &[],
     approval_decisions: &["accept", "decline", "cancel", "accept_for_session"],
+    approx_max_input_tokens: None,
+    plan_mode: false,
+    attachments: true,
+    interrupt: true,
+    reasoning_effort: true,
+    thread_rename: false,
+    approvals_end_with_turn: false,
 };
 
 pub fn capabilities_for_engine(engine_id: &str) -> EngineCapabilities {
     match engine_id {
         "claude" => CLAUDE_CAPABILITIES,
         "codex" => CODEX_CAPABILITIES,
+        "gemini" => GEMINI_CAPABILITIES,
+        "api" => API_CAPABILITIES,
         "opencode" => OPENCODE_CAPABILITIES,
         _ => EngineCapabilities {
             permission_modes: &[],
             sandbox_modes: &[],
             approval_decisions: &[],
+            approx_max_input_tokens: None,
+            plan_mode: false,
+            attachments: false,
+            interrupt: false,
+            reasoning_effort: false,
+            thread_rename: false,
+            approvals_end_with_turn: false,
         },
     }
 }
 
+/// Capabilities a thread was created with, falling back to the engine's
+/// current ones for threads created before snapshots were stored.
+pub fn capabilities_for_thread(thread: &ThreadDto) -> EngineCapabilitiesDto {
+    thread
+        .engine_capabilities
+        .clone()
+        .unwrap_or_else(|| engine_capabilities_dto(&thread.engine_id))
+}
+
+pub fn engine_capabilities_dto(engine_id: &str) -> EngineCapabilitiesDto {
+    map_engine_capabilities(capabilities_for_engine(engine_id))
+}
+
+/// Rejects turn options the thread's engine would silently ignore.
+pub fn validate_turn_capabilities(
+    engine_id: &str,
+    capabilities: &EngineCapabilitiesDto,
+    plan_mode: bool,
+    has_attachments: bool,
+) -> Result<(), String> {
+    if plan_mode && !capabilities.plan_mode {
+        return Err(format!(
+            "Plan mode is not supported by the `{engine_id}` engine. Turn it off and send again."
+        ));
+    }
+    if has_attachments && !capabilities.attachments {
+        return Err(format!(
+            "Attachments are not supported by the `{engine_id}` engine. Remove them and send again."
+        ));
+    }
+    Ok(())
+}
+
 pub fn engine_supports_sandbox_mode(engine_id: &str, sandbox_mode: &str) -> bool {
     capabilities_for_engine(engine_id)
         .sandbox_modes
@@ -327,6 +456,35 @@ fn map_engine_capabilities(capabilities: EngineCapabilities) -> EngineCapabiliti
             .iter()
             .map(|value| (*value).to_string())
             .collect(),
+        plan_mode: capabilities.plan_mode,
+        attachments: capabilities.attachments,
+        approvals: capabilities.approvals(),
+        interrupt: capabilities.interrupt,
+        reasoning_effort: capabilities.reasoning_effort,
+        thread_rename: capabilities.thread_rename,
+    }
+}
+
+/// Whether an installed engine has credentials to run turns, as reported by
+/// its health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineAuthStatus {
+    SignedIn,
+    SignedOut,
+    /// The configured provider authenticates some other way, e.g. an API key
+    /// or a cloud provider.
+    NotRequired,
+    Unknown,
+}
+
+impl From<EngineAuthStatus> for EngineAuthStatusDto {
+    fn from(status: EngineAuthStatus) -> Self {
+        match status {
+            EngineAuthStatus::SignedIn => Self::SignedIn,
+            EngineAuthStatus::SignedOut => Self::SignedOut,
+            EngineAuthStatus::NotRequired => Self::NotRequired,
+            EngineAuthStatus::Unknown => Self::Unknown,
+        }
     }
 }
 
@@ -335,6 +493,29 @@ pub struct EngineThread {
     pub engine_thread_id: String,
 }
 
+/// Why a one-shot prompt from [`EngineManager::run_ephemeral_prompt`]
+/// produced no answer.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EphemeralPromptError {
+    #[error("the `{engine_id}` engine is not available; check its install and sign-in")]
+    EngineUnavailable { engine_id: String },
+    #[error(
+        "the `{engine_id}` engine did not answer within {}",
+        display_timeout_ms(*.timeout_ms)
+    )]
+    TimedOut { engine_id: String, timeout_ms: u64 },
+    #[error("the `{engine_id}` engine failed: {message}")]
+    Failed { engine_id: String, message: String },
+}
+
+fn display_timeout_ms(timeout_ms: u64) -> String {
+    if timeout_ms.is_multiple_of(1000) {
+        format!("{}s", timeout_ms / 1000)
+    } else {
+        format!("{timeout_ms}ms")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ThreadSyncSnapshot {
     pub title: Option<String>,
@@ -413,6 +594,10 @@ pub trait Engine: Send + Sync {
     fn name(&self) -> &str;
     fn models(&self) -> Vec<ModelInfo>;
 
+    fn capabilities(&self) -> EngineCapabilities {
+        capabilities_for_engine(self.id())
+    }
+
     async fn is_available(&self) -> bool;
 
     async fn start_thread(
@@ -449,28 +634,162 @@ pub trait Engine: Send + Sync {
     async fn archive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error>;
 
     async fn unarchive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error>;
+
+    async fn health(&self) -> EngineHealthDto {
+        EngineHealthDto {
+            id: self.id().to_string(),
+            available: self.is_available().await,
+            version: None,
+            details: None,
+            warnings: Vec::new(),
+            checks: Vec::new(),
+            fixes: Vec::new(),
+            protocol_diagnostics: None,
+            timeouts: None,
+            auth_status: None,
+        }
+    }
+
+    async fn prewarm(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    /// Preview text the engine keeps for a thread, for engines that store
+    /// their own thread history.
+    async fn read_thread_preview(&self, _engine_thread_id: &str) -> Option<String> {
+        None
+    }
+
+    /// Engines without a thread title of their own accept the rename as a
+    /// no-op; Panes keeps the name in its database either way.
+    async fn set_thread_name(
+        &self,
+        _engine_thread_id: &str,
+        _name: &str,
+    ) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn read_thread_sync_snapshot(
+        &self,
+        _engine_thread_id: &str,
+    ) -> Result<Option<ThreadSyncSnapshot>, anyhow::Error> {
+        Ok(None)
+    }
 }
 
+const EPHEMERAL_EVENT_QUEUE_CAPACITY: usize = 128;
+
+/// Thread-level calls dispatch through `engines` by engine id. The typed
+/// handles stay for engine-specific features (Codex review, OpenCode
+/// sessions, ...) and the per-engine model loaders.
 pub struct EngineManager {
+    engines: HashMap<String, Arc<dyn Engine>>,
     codex: Arc<CodexEngine>,
     claude: Arc<ClaudeSidecarEngine>,
     opencode: Arc<OpenCodeEngine>,
+    gemini: Arc<GeminiEngine>,
+    api: Arc<ApiDirectEngine>,
+}
+
+impl Default for EngineManager {
+    fn default() -> Self {
+        Self::with_timeouts(&EngineTimeoutsConfig::default())
+    }
 }
 
 impl EngineManager {
-    pub fn new() -> Self {
-        Self {
-            codex: Arc::new(CodexEngine::default()),
-            claude: Arc::new(ClaudeSidecarEngine::default()),
-            opencode: Arc::new(OpenCodeEngine::default()),
-        }
+    pub fn with_timeouts(timeouts: &EngineTimeoutsConfig) -> Self {
+        let codex = Arc::new(CodexEngine::with_timeouts(EngineTimeouts::from_config(
+            &timeouts.for_engine("codex"),
+        )));
+        let claude = Arc::new(ClaudeSidecarEngine::default());
+        let opencode = Arc::new(OpenCodeEngine::default());
+        let gemini = Arc::new(GeminiEngine::default());
+        let api = Arc::new(ApiDirectEngine::default());
+
+        let mut manager = Self {
+            engines: HashMap::new(),
+            codex: codex.clone(),
+            claude: claude.clone(),
+            opencode: opencode.clone(),
+            gemini: gemini.clone(),
+            api: api.clone(),
+        };
+        manager.register(codex);
+        manager.register(claude);
+        manager.register(opencode);
+        manager.register(gemini);
+        manager.register(api);
+        manager
+    }
+
+    fn register(&mut self, engine: Arc<dyn Engine>) {
+        self.engines.insert(engine.id().to_string(), engine);
+    }
+
+    fn engine(&self, engine_id: &str) -> anyhow::Result<&Arc<dyn Engine>> {
+        self.engines
+            .get(engine_id)
+            .ok_or_else(|| self.unknown_engine_error(engine_id))
+    }
+
+    fn unknown_engine_error(&self, engine_id: &str) -> anyhow::Error {
+        let mut registered = self.engines.keys().map(String::as_str).collect::<Vec<_>>();
+        registered.sort_unstable();
+        anyhow::anyhow!(
+            "unknown engine_id `{engine_id}` (registered: {})",
+            registered.join(", ")
+        )
     }
 
     pub fn set_resource_dir(&self, resource_dir: Option<PathBuf>) {
         self.claude.set_resource_dir(resource_dir);
     }
 
+    /// Per-turn diagnostics collected by the Codex engine, keyed by engine
+    /// thread id.
+    pub fn turn_diagnostics(&self) -> &TurnDiagnosticsSink {
+        self.codex.turn_diagnostics()
+    }
+
+    pub fn set_codex_transport_routing(&self, config: &CodexConfig) {
+        self.codex.set_transport_routing(CodexTransportRouting {
+            per_workspace: config.transport_per_workspace,
+            max_workspace_transports: config.max_workspace_transports.max(1),
+        });
+    }
+
+    pub fn set_api_direct_config(&self, config: &ApiDirectConfig) {
+        self.api.set_config(config);
+    }
+
+    pub fn set_codex_model_cache_ttl(&self, config: &CodexConfig) {
+        self.codex
+            .set_model_cache_ttl(Duration::from_secs(config.model_cache_ttl_secs));
+    }
+
+    pub fn set_codex_attachment_limits(&self, config: &AppConfig) {
+        self.codex
+            .set_max_attachment_bytes(config.attachment_max_file_bytes());
+    }
+
+    /// Refetches the Codex catalog, bypassing the cache.
+    pub async fn refresh_codex_models(&self) -> Vec<ModelInfo> {
+        self.codex.list_models_runtime().await
+    }
+
     async fn load_codex_models(&self) -> Vec<ModelInfo> {
+        if let Some((models, needs_refresh)) = self.codex.cached_runtime_models().await {
+            if needs_refresh {
+                let codex = self.codex.clone();
+                tokio::spawn(async move {
+                    codex.list_models_runtime().await;
+                });
+            }
+            return models;
+        }
+
         match timeout(Duration::from_secs(4), self.codex.list_models_runtime()).await {
             Ok(models) => models,
             Err(_) => {
@@ -483,7 +802,15 @@ impl EngineManager {
     }
 
     async fn load_claude_models(&self) -> Vec<ModelInfo> {
-        match timeout(Duration::from_secs(12), self.claude.list_models_runtime()).await {
+        // The first discovery may have to start the sidecar, which is slow.
+        // Once a list is cached, a stalled runtime gets the same 4s budget as
+        // Codex before the cached list is served.
+        let budget = if self.claude.has_runtime_models().await {
+            Duration::from_secs(4)
+        } else {
+            Duration::from_secs(12)
+        };
+        match timeout(budget, self.claude.list_models_runtime()).await {
             Ok(models) => models,
             Err(_) => {
                 log::warn!(
@@ -504,6 +831,18 @@ impl EngineManager {
         }
     }
 
+    async fn load_api_models(&self) -> Vec<ModelInfo> {
+        match timeout(Duration::from_secs(4), self.api.list_models_runtime()).await {
+            Ok(models) => models,
+            Err(_) => {
+                log::warn!(
+                    "timed out loading API engine models; falling back to the configured list"
+                );
+                self.api.runtime_model_fallback().await
+            }
+        }
+    }
+
     pub async fn models_for_validation(
         &self,
         engine_id: &str,
@@ -513,7 +852,9 @@ impl EngineManager {
             "codex" => self.codex.runtime_model_fallback().await,
             "claude" => self.claude.runtime_model_fallback().await,
             "opencode" => self.opencode.runtime_model_fallback().await,
-            _ => anyhow::bail!("unsupported engine_id {engine_id}"),
+            "gemini" => self.gemini.runtime_model_fallback().await,
+            "api" => self.api.runtime_model_fallback().await,
+            _ => return Err(self.unknown_engine_error(engine_id)),
         };
 
         if cached_models
@@ -527,15 +868,18 @@ impl EngineManager {
             "codex" => self.load_codex_models().await,
             "claude" => self.load_claude_models().await,
             "opencode" => self.load_opencode_models().await,
+            "gemini" => self.gemini.models(),
+            "api" => self.load_api_models().await,
             _ => unreachable!(),
         })
     }
 
     pub async fn list_engines(&self) -> anyhow::Result<Vec<EngineInfoDto>> {
-        let (codex_models, claude_models, opencode_models) = tokio::join!(
+        let (codex_models, claude_models, opencode_models, api_models) = tokio::join!(
             self.load_codex_models(),
             self.load_claude_models(),
             self.load_opencode_models(),
+            self.load_api_models(),
         );
 
         Ok(vec![
@@ -543,19 +887,36 @@ impl EngineManager {
                 id: self.codex.id().to_string(),
                 name: self.codex.name().to_string(),
                 models: codex_models.into_iter().map(map_model_info).collect(),
-                capabilities: map_engine_capabilities(capabilities_for_engine(self.codex.id())),
+                capabilities: map_engine_capabilities(self.codex.capabilities()),
             },
             EngineInfoDto {
                 id: self.claude.id().to_string(),
                 name: self.claude.name().to_string(),
                 models: claude_models.into_iter().map(map_model_info).collect(),
-                capabilities: map_engine_capabilities(capabilities_for_engine(self.claude.id())),
+                capabilities: map_engine_capabilities(self.claude.capabilities()),
             },
             EngineInfoDto {
                 id: self.opencode.id().to_string(),
                 name: self.opencode.name().to_string(),
                 models: opencode_models.into_iter().map(map_model_info).collect(),
-                capabilities: map_engine_capabilities(capabilities_for_engine(self.opencode.id())),
+                capabilities: map_engine_capabilities(self.opencode.capabilities()),
+            },
+            EngineInfoDto {
+                id: self.gemini.id().to_string(),
+                name: self.gemini.name().to_string(),
+                models: self
+                    .gemini
+                    .models()
+                    .into_iter()
+                    .map(map_model_info)
+                    .collect(),
+                capabilities: map_engine_capabilities(self.gemini.capabilities()),
+            },
+            EngineInfoDto {
+                id: self.api.id().to_string(),
+                name: self.api.name().to_string(),
+                models: api_models.into_iter().map(map_model_info).collect(),
+                capabilities: map_engine_capabilities(self.api.capabilities()),
             },
         ])
     }
@@ -571,58 +932,17 @@ impl EngineManager {
         ]
     }
 
+    /// Called after the environment changed, e.g. a dependency was installed.
+    pub async fn invalidate_executable_caches(&self) {
+        self.codex.invalidate_executable_caches().await;
+    }
+
     pub async fn health(&self, engine_id: &str) -> anyhow::Result<EngineHealthDto> {
-        match engine_id {
-            "codex" => {
-                let report = self.codex.health_report().await;
-                Ok(EngineHealthDto {
-                    id: "codex".to_string(),
-                    available: report.available,
-                    version: report.version,
-                    details: report.details,
-                    warnings: report.warnings,
-                    checks: report.checks,
-                    fixes: report.fixes,
-                    protocol_diagnostics: report.protocol_diagnostics,
-                })
-            }
-            "claude" => {
-                let report = self.claude.health_report().await;
-                Ok(EngineHealthDto {
-                    id: "claude".to_string(),
-                    available: report.available,
-                    version: report.version,
-                    details: Some(report.details),
-                    warnings: report.warnings,
-                    checks: report.checks,
-                    fixes: report.fixes,
-                    protocol_diagnostics: None,
-                })
-            }
-            "opencode" => {
-                let report = self.opencode.health_report().await;
-                Ok(EngineHealthDto {
-                    id: "opencode".to_string(),
-                    available: report.available,
-                    version: report.version,
-                    details: report.details,
-                    warnings: report.warnings,
-                    checks: report.checks,
-                    fixes: report.fixes,
-                    protocol_diagnostics: None,
-                })
-            }
-            _ => anyhow::bail!("unknown engine: {engine_id}"),
-        }
+        Ok(self.engine(engine_id)?.health().await)
     }
 
     pub async fn prewarm(&self, engine_id: &str) -> anyhow::Result<()> {
-        match engine_id {
-            "codex" => self.codex.prewarm().await,
-            "claude" => self.claude.prewarm().await,
-            "opencode" => self.opencode.prewarm().await,
-            _ => anyhow::bail!("unknown engine: {engine_id}"),
-        }
+        self.engine(engine_id)?.prewarm().await
     }
 
     pub async fn list_codex_skills(&self, cwd: &str) -> anyhow::Result<Vec<CodexSkillDto>> {
@@ -666,6 +986,13 @@ impl EngineManager {
         self.codex.compact_thread(engine_thread_id).await
     }
 
+    pub async fn codex_cli_resume_command(
+        &self,
+        engine_thread_id: &str,
+    ) -> anyhow::Result<(PathBuf, Vec<String>)> {
+        self.codex.cli_resume_command(engine_thread_id).await
+    }
+
     pub async fn archive_codex_thread(&self, engine_thread_id: &str) -> anyhow::Result<()> {
         self.codex.archive_thread(engine_thread_id).await
     }
@@ -763,27 +1090,14 @@ impl EngineManager {
         scope: ThreadScope,
         sandbox: SandboxPolicy,
     ) -> anyhow::Result<String> {
+        let engine = self.engine(&thread.engine_id)?;
         let resume_id = thread.engine_thread_id.as_deref();
         let effective_model_id = model_id.unwrap_or(thread.model_id.as_str());
 
-        let result = match thread.engine_id.as_str() {
-            "codex" => self
-                .codex
-                .start_thread(scope, resume_id, effective_model_id, sandbox)
-                .await
-                .context("failed to start codex thread")?,
-            "claude" => self
-                .claude
-                .start_thread(scope, resume_id, effective_model_id, sandbox)
-                .await
-                .context("failed to start claude thread")?,
-            "opencode" => self
-                .opencode
-                .start_thread(scope, resume_id, effective_model_id, sandbox)
-                .await
-                .context("failed to start opencode thread")?,
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        };
+        let result = engine
+            .start_thread(scope, resume_id, effective_model_id, sandbox)
+            .await
+            .with_context(|| format!("failed to start {} thread", engine.id()))?;
 
         Ok(result.engine_thread_id)
     }
@@ -796,24 +1110,66 @@ impl EngineManager {
         event_tx: mpsc::Sender<EngineEvent>,
         cancellation: CancellationToken,
     ) -> anyhow::Result<()> {
-        match thread.engine_id.as_str() {
-            "codex" => self
-                .codex
-                .send_message(engine_thread_id, input, event_tx, cancellation)
-                .await
-                .context("codex send_message failed"),
-            "claude" => self
-                .claude
-                .send_message(engine_thread_id, input, event_tx, cancellation)
-                .await
-                .context("claude send_message failed"),
-            "opencode" => self
-                .opencode
-                .send_message(engine_thread_id, input, event_tx, cancellation)
-                .await
-                .context("opencode send_message failed"),
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
+        let engine = self.engine(&thread.engine_id)?;
+        engine
+            .send_message(engine_thread_id, input, event_tx, cancellation)
+            .await
+            .with_context(|| format!("{} send_message failed", engine.id()))
+    }
+
+    /// Runs `prompt` as a single turn on a throwaway engine thread scoped to
+    /// `repo_path` and returns the assistant's text. Nothing is stored in
+    /// the database, the turn runs read-only, and the engine thread is
+    /// archived afterwards.
+    pub async fn run_ephemeral_prompt(
+        &self,
+        engine_id: &str,
+        model_id: &str,
+        repo_path: &str,
+        prompt: String,
+        turn_timeout: Duration,
+    ) -> anyhow::Result<String> {
+        let engine = Arc::clone(self.engine(engine_id)?);
+        if !engine.is_available().await {
+            return Err(EphemeralPromptError::EngineUnavailable {
+                engine_id: engine_id.to_string(),
+            }
+            .into());
         }
+
+        let cancellation = CancellationToken::new();
+        let mut engine_thread_id = None;
+        let outcome = timeout(
+            turn_timeout,
+            run_ephemeral_turn(
+                &engine,
+                model_id,
+                repo_path,
+                prompt,
+                &cancellation,
+                &mut engine_thread_id,
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(EphemeralPromptError::TimedOut {
+                engine_id: engine_id.to_string(),
+                timeout_ms: u64::try_from(turn_timeout.as_millis()).unwrap_or(u64::MAX),
+            })
+        });
+
+        if let Some(engine_thread_id) = engine_thread_id {
+            if outcome.is_err() {
+                cancellation.cancel();
+                let _ = engine.interrupt(&engine_thread_id).await;
+            }
+            if let Err(error) = engine.archive_thread(&engine_thread_id).await {
+                log::warn!(
+                    "failed to archive ephemeral {engine_id} thread {engine_thread_id}: {error}"
+                );
+            }
+        }
+        outcome.map_err(Into::into)
     }
 
     pub async fn steer_message(
@@ -822,24 +1178,11 @@ impl EngineManager {
         engine_thread_id: &str,
         input: TurnInput,
     ) -> anyhow::Result<()> {
-        match thread.engine_id.as_str() {
-            "codex" => self
-                .codex
-                .steer_message(engine_thread_id, input)
-                .await
-                .context("codex steer_message failed"),
-            "claude" => self
-                .claude
-                .steer_message(engine_thread_id, input)
-                .await
-                .context("claude steer_message failed"),
-            "opencode" => self
-                .opencode
-                .steer_message(engine_thread_id, input)
-                .await
-                .context("opencode steer_message failed"),
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        }
+        let engine = self.engine(&thread.engine_id)?;
+        engine
+            .steer_message(engine_thread_id, input)
+            .await
+            .with_context(|| format!("{} steer_message failed", engine.id()))
     }
 
     pub async fn respond_to_approval(
@@ -849,34 +1192,16 @@ impl EngineManager {
         response: serde_json::Value,
         route: Option<ApprovalRequestRoute>,
     ) -> anyhow::Result<()> {
-        match thread.engine_id.as_str() {
-            "codex" => {
-                self.codex
-                    .respond_to_approval(approval_id, response, route)
-                    .await
-            }
-            "claude" => {
-                self.claude
-                    .respond_to_approval(approval_id, response, route)
-                    .await
-            }
-            "opencode" => {
-                self.opencode
-                    .respond_to_approval(approval_id, response, route)
-                    .await
-            }
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        }
+        self.engine(&thread.engine_id)?
+            .respond_to_approval(approval_id, response, route)
+            .await
     }
 
     pub async fn interrupt(&self, thread: &ThreadDto) -> anyhow::Result<()> {
         let engine_thread_id = thread.engine_thread_id.as_deref().unwrap_or("default");
-        match thread.engine_id.as_str() {
-            "codex" => self.codex.interrupt(engine_thread_id).await,
-            "claude" => self.claude.interrupt(engine_thread_id).await,
-            "opencode" => self.opencode.interrupt(engine_thread_id).await,
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        }
+        self.engine(&thread.engine_id)?
+            .interrupt(engine_thread_id)
+            .await
     }
 
     pub async fn archive_thread(&self, thread: &ThreadDto) -> anyhow::Result<()> {
@@ -884,12 +1209,9 @@ impl EngineManager {
             return Ok(());
         };
 
-        match thread.engine_id.as_str() {
-            "codex" => self.codex.archive_thread(engine_thread_id).await,
-            "claude" => self.claude.archive_thread(engine_thread_id).await,
-            "opencode" => self.opencode.archive_thread(engine_thread_id).await,
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        }
+        self.engine(&thread.engine_id)?
+            .archive_thread(engine_thread_id)
+            .await
     }
 
     pub async fn unarchive_thread(&self, thread: &ThreadDto) -> anyhow::Result<()> {
@@ -897,12 +1219,9 @@ impl EngineManager {
             return Ok(());
         };
 
-        match thread.engine_id.as_str() {
-            "codex" => self.codex.unarchive_thread(engine_thread_id).await,
-            "claude" => self.claude.unarchive_thread(engine_thread_id).await,
-            "opencode" => self.opencode.unarchive_thread(engine_thread_id).await,
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        }
+        self.engine(&thread.engine_id)?
+            .unarchive_thread(engine_thread_id)
+            .await
     }
 
     pub async fn codex_uses_external_sandbox(&self) -> bool {
@@ -914,10 +1233,10 @@ impl EngineManager {
         thread: &ThreadDto,
         engine_thread_id: &str,
     ) -> Option<String> {
-        match thread.engine_id.as_str() {
-            "codex" => self.codex.read_thread_preview(engine_thread_id).await,
-            _ => None,
-        }
+        self.engines
+            .get(&thread.engine_id)?
+            .read_thread_preview(engine_thread_id)
+            .await
     }
 
     pub async fn set_thread_name(
@@ -926,12 +1245,9 @@ impl EngineManager {
         engine_thread_id: &str,
         name: &str,
     ) -> anyhow::Result<()> {
-        match thread.engine_id.as_str() {
-            "codex" => self.codex.set_thread_name(engine_thread_id, name).await,
-            "claude" => Ok(()),
-            "opencode" => Ok(()),
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        }
+        self.engine(&thread.engine_id)?
+            .set_thread_name(engine_thread_id, name)
+            .await
     }
 
     pub fn subscribe_codex_runtime_events(&self) -> broadcast::Receiver<CodexRuntimeEvent> {
@@ -946,16 +1262,9 @@ impl EngineManager {
             return Ok(None);
         };
 
-        match thread.engine_id.as_str() {
-            "codex" => self
-                .codex
-                .read_thread_sync_snapshot(engine_thread_id)
-                .await
-                .map(Some),
-            "claude" => Ok(None),
-            "opencode" => Ok(None),
-            _ => anyhow::bail!("unsupported engine_id {}", thread.engine_id),
-        }
+        self.engine(&thread.engine_id)?
+            .read_thread_sync_snapshot(engine_thread_id)
+            .await
     }
 }
 
@@ -1013,6 +1322,110 @@ fn map_provider_usage(
     }
 }
 
+/// Read-only, approval-free settings for turns no user is watching.
+fn ephemeral_sandbox(engine_id: &str) -> SandboxPolicy {
+    SandboxPolicy {
+        writable_roots: Vec::new(),
+        allow_network: false,
+        approval_policy: None,
+        permission_profile: None,
+        approvals_reviewer: None,
+        reasoning_effort: None,
+        sandbox_mode: engine_supports_sandbox_mode(engine_id, "read-only")
+            .then(|| "read-only".to_string()),
+        service_tier: None,
+        personality: None,
+        output_schema: None,
+        opencode_agent: None,
+        executable_override: None,
+        custom_instructions: None,
+    }
+}
+
+/// Starts the throwaway thread, recording its id in `engine_thread_id` for
+/// cleanup, then collects the turn's text until it completes.
+async fn run_ephemeral_turn(
+    engine: &Arc<dyn Engine>,
+    model_id: &str,
+    repo_path: &str,
+    prompt: String,
+    cancellation: &CancellationToken,
+    engine_thread_id: &mut Option<String>,
+) -> Result<String, EphemeralPromptError> {
+    let failed = |message: String| EphemeralPromptError::Failed {
+        engine_id: engine.id().to_string(),
+        message,
+    };
+    let thread = engine
+        .start_thread(
+            ThreadScope::Repo {
+                repo_path: repo_path.to_string(),
+            },
+            None,
+            model_id,
+            ephemeral_sandbox(engine.id()),
+        )
+        .await
+        .map_err(|error| failed(format!("{error:#}")))?;
+    *engine_thread_id = Some(thread.engine_thread_id.clone());
+
+    let (event_tx, mut event_rx) = mpsc::channel(EPHEMERAL_EVENT_QUEUE_CAPACITY);
+    let send = tokio::spawn({
+        let engine = Arc::clone(engine);
+        let cancellation = cancellation.clone();
+        let input = TurnInput {
+            message: prompt,
+            attachments: Vec::new(),
+            plan_mode: false,
+            input_items: Vec::new(),
+        };
+        async move {
+            engine
+                .send_message(&thread.engine_thread_id, input, event_tx, cancellation)
+                .await
+        }
+    });
+
+    let mut text = String::new();
+    let mut completed = false;
+    while let Some(event) = event_rx.recv().await {
+        match event {
+            EngineEvent::TextDelta { content } => text.push_str(&content),
+            EngineEvent::Error {
+                message,
+                recoverable: false,
+            } => return Err(failed(message)),
+            EngineEvent::ApprovalRequested { summary, .. } => {
+                return Err(failed(format!("the turn asked for approval: {summary}")))
+            }
+            EngineEvent::TurnCompleted { status, .. } => match status {
+                TurnCompletionStatus::Completed => {
+                    completed = true;
+                    break;
+                }
+                TurnCompletionStatus::Interrupted => {
+                    return Err(failed("the turn was interrupted".to_string()))
+                }
+                TurnCompletionStatus::Failed => return Err(failed("the turn failed".to_string())),
+            },
+            _ => {}
+        }
+    }
+    if !completed {
+        match send.await {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => return Err(failed(format!("{error:#}"))),
+            Err(error) => return Err(failed(error.to_string())),
+        }
+    }
+
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(failed("the turn returned no text".to_string()));
+    }
+    Ok(text.to_string())
+}
+
 fn map_model_info(model: ModelInfo) -> EngineModelDto {
     EngineModelDto {
         id: model.id,
@@ -1058,6 +1471,382 @@ fn map_model_info(model: ModelInfo) -> EngineModelDto {
 mod tests {
     use super::*;
 
+    /// Records the calls that reach it so tests can check dispatch. A
+    /// message starting with `reply:` is answered with the rest of it, and
+    /// one starting with `hang` runs until cancelled.
+    #[derive(Default)]
+    struct MockEngine {
+        calls: std::sync::Mutex<Vec<String>>,
+        unavailable: std::sync::atomic::AtomicBool,
+    }
+
+    impl MockEngine {
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Engine for MockEngine {
+        fn id(&self) -> &str {
+            "mock"
+        }
+
+        fn name(&self) -> &str {
+            "Mock"
+        }
+
+        fn models(&self) -> Vec<ModelInfo> {
+            Vec::new()
+        }
+
+        async fn is_available(&self) -> bool {
+            !self.unavailable.load(std::sync::atomic::Ordering::SeqCst)
+        }
+
+        async fn start_thread(
+            &self,
+            _scope: ThreadScope,
+            resume_engine_thread_id: Option<&str>,
+            model: &str,
+            _sandbox: SandboxPolicy,
+        ) -> Result<EngineThread, anyhow::Error> {
+            self.record(format!("start_thread {resume_engine_thread_id:?} {model}"));
+            Ok(EngineThread {
+                engine_thread_id: "mock-thread".to_string(),
+            })
+        }
+
+        async fn send_message(
+            &self,
+            engine_thread_id: &str,
+            input: TurnInput,
+            event_tx: mpsc::Sender<EngineEvent>,
+            cancellation: CancellationToken,
+        ) -> Result<(), anyhow::Error> {
+            self.record(format!("send_message {engine_thread_id} {}", input.message));
+            if let Some(reply) = input.message.strip_prefix("reply:") {
+                for content in [reply, " "] {
+                    let _ = event_tx.try_send(EngineEvent::TextDelta {
+                        content: content.to_string(),
+                    });
+                }
+                let _ = event_tx.try_send(EngineEvent::TurnCompleted {
+                    token_usage: None,
+                    status: TurnCompletionStatus::Completed,
+                });
+            } else if input.message.starts_with("hang") {
+                cancellation.cancelled().await;
+            }
+            Ok(())
+        }
+
+        async fn steer_message(
+            &self,
+            _engine_thread_id: &str,
+            _input: TurnInput,
+        ) -> Result<(), anyhow::Error> {
+            anyhow::bail!("steering is not supported")
+        }
+
+        async fn respond_to_approval(
+            &self,
+            approval_id: &str,
+            _response: serde_json::Value,
+            _route: Option<ApprovalRequestRoute>,
+        ) -> Result<(), anyhow::Error> {
+            self.record(format!("respond_to_approval {approval_id}"));
+            Ok(())
+        }
+
+        async fn interrupt(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
+            self.record(format!("interrupt {engine_thread_id}"));
+            Ok(())
+        }
+
+        async fn archive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
+            self.record(format!("archive_thread {engine_thread_id}"));
+            Ok(())
+        }
+
+        async fn unarchive_thread(&self, engine_thread_id: &str) -> Result<(), anyhow::Error> {
+            self.record(format!("unarchive_thread {engine_thread_id}"));
+            Ok(())
+        }
+    }
+
+    fn thread_for(engine_id: &str, engine_thread_id: Option<&str>) -> ThreadDto {
+        ThreadDto {
+            id: "thread-1".to_string(),
+            workspace_id: "workspace-1".to_string(),
+            repo_id: None,
+            engine_id: engine_id.to_string(),
+            model_id: "mock-model".to_string(),
+            engine_thread_id: engine_thread_id.map(str::to_string),
+            engine_metadata: None,
+            reasoning_effort: None,
+            title: "Thread".to_string(),
+            status: crate::models::ThreadStatusDto::Idle,
+            message_count: 0,
+            total_tokens: 0,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            last_activity_at: "2026-01-01T00:00:00Z".to_string(),
+            engine_capabilities: None,
+        }
+    }
+
+    fn manager_with_mock() -> (EngineManager, Arc<MockEngine>) {
+        let mock = Arc::new(MockEngine::default());
+        let mut manager = EngineManager::default();
+        manager.register(mock.clone());
+        (manager, mock)
+    }
+
+    #[test]
+    fn engine_manager_registers_every_built_in_engine() {
+        let manager = EngineManager::default();
+        let mut ids = manager.engines.keys().cloned().collect::<Vec<_>>();
+        ids.sort();
+
+        assert_eq!(ids, ["api", "claude", "codex", "gemini", "opencode"]);
+    }
+
+    #[tokio::test]
+    async fn engine_manager_dispatches_thread_calls_to_the_registered_engine() {
+        let (manager, mock) = manager_with_mock();
+        let thread = thread_for("mock", None);
+        let sandbox = SandboxPolicy {
+            writable_roots: Vec::new(),
+            allow_network: false,
+            approval_policy: None,
+            permission_profile: None,
+            approvals_reviewer: None,
+            reasoning_effort: None,
+            sandbox_mode: None,
+            service_tier: None,
+            personality: None,
+            output_schema: None,
+            opencode_agent: None,
+            executable_override: None,
+            custom_instructions: None,
+        };
+
+        let engine_thread_id = manager
+            .ensure_engine_thread(
+                &thread,
+                None,
+                ThreadScope::Repo {
+                    repo_path: "/tmp/repo".to_string(),
+                },
+                sandbox,
+            )
+            .await
+            .unwrap();
+        assert_eq!(engine_thread_id, "mock-thread");
+
+        let (event_tx, _event_rx) = mpsc::channel(1);
+        manager
+            .send_message(
+                &thread,
+                &engine_thread_id,
+                TurnInput {
+                    message: "hello".to_string(),
+                    attachments: Vec::new(),
+                    plan_mode: false,
+                    input_items: Vec::new(),
+                },
+                event_tx,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        manager
+            .respond_to_approval(&thread, "approval-1", json!({}), None)
+            .await
+            .unwrap();
+        manager.interrupt(&thread).await.unwrap();
+        // Nothing to archive before the engine assigned a thread id.
+        manager.archive_thread(&thread).await.unwrap();
+        manager
+            .archive_thread(&thread_for("mock", Some("mock-thread")))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            mock.calls(),
+            [
+                "start_thread None mock-model",
+                "send_message mock-thread hello",
+                "respond_to_approval approval-1",
+                "interrupt default",
+                "archive_thread mock-thread",
+            ]
+        );
+
+        let steer_error = manager
+            .steer_message(
+                &thread,
+                "mock-thread",
+                TurnInput {
+                    message: "again".to_string(),
+                    attachments: Vec::new(),
+                    plan_mode: false,
+                    input_items: Vec::new(),
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(steer_error.to_string(), "mock steer_message failed");
+    }
+
+    #[tokio::test]
+    async fn ephemeral_prompts_return_the_reply_and_clean_up_their_thread() {
+        let (manager, mock) = manager_with_mock();
+        let reply = manager
+            .run_ephemeral_prompt(
+                "mock",
+                "mock-model",
+                "/tmp/repo",
+                "reply:Add tests".to_string(),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply, "Add tests");
+        assert_eq!(
+            mock.calls(),
+            [
+                "start_thread None mock-model",
+                "send_message mock-thread reply:Add tests",
+                "archive_thread mock-thread",
+            ]
+        );
+
+        let error = manager
+            .run_ephemeral_prompt(
+                "mock",
+                "mock-model",
+                "/tmp/repo",
+                "hang".to_string(),
+                Duration::from_millis(50),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<EphemeralPromptError>(),
+            Some(&EphemeralPromptError::TimedOut {
+                engine_id: "mock".to_string(),
+                timeout_ms: 50,
+            })
+        );
+        assert!(error.to_string().ends_with("did not answer within 50ms"));
+        assert_eq!(
+            mock.calls()[3..],
+            [
+                "start_thread None mock-model",
+                "send_message mock-thread hang",
+                "interrupt mock-thread",
+                "archive_thread mock-thread",
+            ]
+        );
+
+        let error = manager
+            .run_ephemeral_prompt(
+                "mock",
+                "mock-model",
+                "/tmp/repo",
+                "no answer".to_string(),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<EphemeralPromptError>(),
+            Some(EphemeralPromptError::Failed { .. })
+        ));
+
+        mock.unavailable
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        let error = manager
+            .run_ephemeral_prompt(
+                "mock",
+                "mock-model",
+                "/tmp/repo",
+                "reply:unused".to_string(),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<EphemeralPromptError>(),
+            Some(&EphemeralPromptError::EngineUnavailable {
+                engine_id: "mock".to_string(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn engine_manager_falls_back_to_default_engine_methods() {
+        let (manager, _mock) = manager_with_mock();
+        let thread = thread_for("mock", Some("mock-thread"));
+
+        let health = manager.health("mock").await.unwrap();
+        assert_eq!(health.id, "mock");
+        assert!(health.available);
+        assert!(manager.prewarm("mock").await.is_ok());
+        assert!(manager
+            .set_thread_name(&thread, "mock-thread", "Renamed")
+            .await
+            .is_ok());
+        assert_eq!(
+            manager.read_thread_preview(&thread, "mock-thread").await,
+            None
+        );
+        assert!(manager
+            .read_thread_sync_snapshot(&thread)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn engine_manager_rejects_unknown_engines_with_the_registered_ids() {
+        let (manager, _mock) = manager_with_mock();
+        let thread = thread_for("missing", Some("thread"));
+        let expected =
+            "unknown engine_id `missing` (registered: api, claude, codex, gemini, mock, opencode)";
+
+        assert_eq!(
+            manager.health("missing").await.unwrap_err().to_string(),
+            expected
+        );
+        assert_eq!(
+            manager.interrupt(&thread).await.unwrap_err().to_string(),
+            expected
+        );
+        assert_eq!(
+            manager
+                .set_thread_name(&thread, "thread", "Renamed")
+                .await
+                .unwrap_err()
+                .to_string(),
+            expected
+        );
+        assert_eq!(
+            manager
+                .models_for_validation("missing", "model")
+                .await
+                .unwrap_err()
+                .to_string(),
+            expected
+        );
+        assert_eq!(manager.read_thread_preview(&thread, "thread").await, None);
+    }
+
     #[test]
     fn claude_capabilities_expose_supported_contract() {
         let capabilities = capabilities_for_engine("claude");
@@ -1090,6 +1879,44 @@ mod tests {
         assert!(validate_engine_sandbox_mode("opencode", Some("workspace-write")).is_err());
     }
 
+    #[test]
+    fn gemini_capabilities_take_panes_policies_without_approval_prompts() {
+        let capabilities = capabilities_for_engine("gemini");
+
+        assert!(capabilities.approval_decisions.is_empty());
+        assert!(validate_engine_sandbox_mode("gemini", Some("workspace-write")).is_ok());
+        assert!(validate_engine_sandbox_mode("gemini", Some("danger-full-access")).is_err());
+    }
+
+    #[test]
+    fn turn_capabilities_reject_plan_mode_and_attachments_the_engine_lacks() {
+        let opencode = engine_capabilities_dto("opencode");
+        assert!(!opencode.plan_mode);
+        assert!(opencode.approvals);
+        let error = validate_turn_capabilities("opencode", &opencode, true, false).unwrap_err();
+        assert!(error.contains("Plan mode is not supported by the `opencode` engine"));
+        assert!(validate_turn_capabilities("opencode", &opencode, false, true).is_ok());
+
+        let unknown = engine_capabilities_dto("custom");
+        let error = validate_turn_capabilities("custom", &unknown, false, true).unwrap_err();
+        assert!(error.contains("Attachments are not supported by the `custom` engine"));
+
+        let codex = engine_capabilities_dto("codex");
+        assert!(codex.thread_rename);
+        assert!(validate_turn_capabilities("codex", &codex, true, true).is_ok());
+    }
+
+    #[test]
+    fn threads_keep_the_capabilities_they_were_created_with() {
+        let mut thread = thread_for("gemini", None);
+        assert!(capabilities_for_thread(&thread).plan_mode);
+
+        let mut snapshot = engine_capabilities_dto("gemini");
+        snapshot.plan_mode = false;
+        thread.engine_capabilities = Some(snapshot);
+        assert!(!capabilities_for_thread(&thread).plan_mode);
+    }
+
     #[test]
     fn validate_engine_sandbox_mode_rejects_unsupported_claude_full_access() {
         assert!(validate_engine_sandbox_mode("claude", Some("danger-full-access")).is_err());
@@ -27,11 +27,13 @@ use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use crate::models::{
-    OpenCodeAgentDto, OpenCodeCommandDto, OpenCodeMcpServerDto, OpenCodeRuntimeCatalogDto,
+    EngineHealthDto, OpenCodeAgentDto, OpenCodeCommandDto, OpenCodeMcpServerDto,
+    OpenCodeRuntimeCatalogDto,
 };
 use crate::{process_utils, runtime_env};
 
 use super::{
+    executable_resolution::{self, app_path_preview, ExecutableResolution},
     normalize_approval_response_for_engine, trim_action_output_delta_content, ActionResult,
     ActionType, ApprovalRequestRoute, DiffScope, Engine, EngineEvent, EngineThread, ModelInfo,
     ModelLimits, OpenCodeRemoteSessionSummary, OutputStream, ReasoningEffortOption, SandboxPolicy,
@@ -941,6 +943,26 @@ impl Engine for OpenCodeEngine {
     async fn unarchive_thread(&self, _engine_thread_id: &str) -> Result<()> {
         Ok(())
     }
+
+    async fn health(&self) -> EngineHealthDto {
+        let report = self.health_report().await;
+        EngineHealthDto {
+            id: "opencode".to_string(),
+            available: report.available,
+            version: report.version,
+            details: report.details,
+            warnings: report.warnings,
+            checks: report.checks,
+            fixes: report.fixes,
+            protocol_diagnostics: None,
+            timeouts: None,
+            auth_status: None,
+        }
+    }
+
+    async fn prewarm(&self) -> Result<()> {
+        OpenCodeEngine::prewarm(self).await
+    }
 }
 
 impl OpenCodeEngine {
@@ -1030,35 +1052,34 @@ impl OpenCodeEngine {
     }
 
     pub async fn health_report(&self) -> OpenCodeHealthReport {
-        let Some(executable) = resolve_opencode_executable() else {
-            return OpenCodeHealthReport {
-                available: false,
-                version: None,
-                details: Some("`opencode` executable not found in PATH".to_string()),
-                warnings: vec![],
-                checks: vec![],
-                fixes: vec!["npm install -g opencode-ai".to_string()],
-            };
+        let resolution = executable_resolution::resolve_cli_executable("opencode").await;
+        // Panes launches the binary from its own PATH, so one only the login
+        // shell knows about is reported but not used.
+        let Some(executable) = resolution
+            .executable
+            .clone()
+            .filter(|_| resolution.source != executable_resolution::SOURCE_LOGIN_SHELL)
+        else {
+            return opencode_unavailable_report(runtime_env::platform_id(), &resolution, None);
         };
 
         let version = match run_opencode_command(&executable, &["--version"]).await {
             Ok(output) => output.lines().next().map(str::trim).map(str::to_string),
             Err(error) => {
-                return OpenCodeHealthReport {
-                    available: false,
-                    version: None,
-                    details: Some(format!("failed to run opencode --version: {error}")),
-                    warnings: vec![],
-                    checks: vec![],
-                    fixes: vec![],
-                }
+                return opencode_unavailable_report(
+                    runtime_env::platform_id(),
+                    &resolution,
+                    Some(&format!("{error:#}")),
+                );
             }
         };
 
+        let details = executable_resolution::resolution_note("OpenCode", &resolution)
+            .unwrap_or_else(|| format!("OpenCode executable: {}", executable.display()));
         OpenCodeHealthReport {
             available: true,
             version,
-            details: Some(format!("OpenCode executable: {}", executable.display())),
+            details: Some(details),
             warnings: vec![],
             checks: vec!["opencode --version".to_string()],
             fixes: vec![],
@@ -2631,6 +2652,62 @@ fn resolve_opencode_executable() -> Option<PathBuf> {
     runtime_env::resolve_executable("opencode")
 }
 
+/// Health report for an `opencode` that is missing, only visible to the
+/// login shell, or present but failing to run (`execution_error`).
+fn opencode_unavailable_report(
+    platform: &str,
+    resolution: &ExecutableResolution,
+    execution_error: Option<&str>,
+) -> OpenCodeHealthReport {
+    let path_preview = app_path_preview(resolution.app_path.as_deref());
+    let details = match (resolution.executable.as_ref(), execution_error) {
+        (Some(executable), Some(error)) => format!(
+            "OpenCode was found at `{}`, but Panes could not run it. App PATH: `{path_preview}`. Error: {error}",
+            executable.display()
+        ),
+        (Some(shell_path), None) => format!(
+            "OpenCode was found in your login shell at `{}`, but Panes does not see it in its app PATH. App PATH: `{path_preview}`",
+            shell_path.display()
+        ),
+        (None, _) => format!("`opencode` executable not found. App PATH: `{path_preview}`"),
+    };
+
+    // The shared fixes read a missing `executable` as "not visible to Panes".
+    let visible_resolution = ExecutableResolution {
+        executable: resolution
+            .executable
+            .clone()
+            .filter(|_| execution_error.is_some()),
+        ..resolution.clone()
+    };
+    let mut fixes = executable_resolution::path_fix_commands_for_platform(
+        platform,
+        "opencode",
+        "opencode-ai",
+        &visible_resolution,
+        execution_error,
+    );
+    if resolution.executable.is_none() && platform != "windows" {
+        fixes.insert(0, "npm install -g opencode-ai".to_string());
+    }
+
+    OpenCodeHealthReport {
+        available: false,
+        version: None,
+        details: Some(details),
+        warnings: vec![],
+        checks: vec![
+            "opencode --version".to_string(),
+            if platform == "windows" {
+                "where opencode".to_string()
+            } else {
+                "command -v opencode".to_string()
+            },
+        ],
+        fixes,
+    }
+}
+
 async fn run_opencode_command(executable: &Path, args: &[&str]) -> Result<String> {
     let mut command = Command::new(executable);
     process_utils::configure_tokio_command(&mut command);
@@ -3460,6 +3537,40 @@ opencode/gpt-5-nano
         assert!(!is_user_message(&roles, "unknown"));
     }
 
+    #[test]
+    fn unavailable_report_tells_missing_apart_from_login_shell_only_and_failing() {
+        let mut resolution = ExecutableResolution {
+            executable: None,
+            source: executable_resolution::SOURCE_UNAVAILABLE,
+            app_path: Some("/usr/bin:/bin".to_string()),
+            login_shell_executable: None,
+        };
+        let report = opencode_unavailable_report("linux", &resolution, None);
+        assert!(report.details.unwrap().contains("not found"));
+        assert_eq!(report.fixes, vec!["npm install -g opencode-ai".to_string()]);
+
+        resolution.executable = Some(PathBuf::from("/Users/me/.opencode/bin/opencode"));
+        resolution.login_shell_executable = resolution.executable.clone();
+        resolution.source = executable_resolution::SOURCE_LOGIN_SHELL;
+        let report = opencode_unavailable_report("macos", &resolution, None);
+        assert!(report
+            .details
+            .unwrap()
+            .contains("found in your login shell"));
+        assert!(report.fixes[0].starts_with("launchctl setenv PATH \"/Users/me/.opencode/bin:"));
+
+        resolution.source = executable_resolution::SOURCE_KNOWN_PATH;
+        resolution.login_shell_executable = None;
+        let report = opencode_unavailable_report("macos", &resolution, Some("exit status 1"));
+        let details = report.details.unwrap();
+        assert!(details.contains("could not run it"));
+        assert!(details.contains("exit status 1"));
+        assert!(report
+            .fixes
+            .iter()
+            .any(|fix| fix.contains("command -v node && command -v opencode")));
+    }
+
     #[tokio::test]
     async fn mapper_ignores_prompt_user_text_parts() {
         let engine = OpenCodeEngine::default();
@@ -0,0 +1,152 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use chrono::{SecondsFormat, Utc};
+
+use crate::models::{TurnDiagnosticDto, TurnDiagnosticSourceDto};
+
+pub const TURN_DIAGNOSTICS_MAX_ENTRIES: usize = 200;
+const TURN_DIAGNOSTIC_MESSAGE_MAX_CHARS: usize = 2_000;
+
+/// Bounded per-thread buffers for engine output that would otherwise only hit
+/// the log: unmapped protocol messages, filtered-out events and app-server
+/// stderr. A buffer exists only between `begin` and `finish`, so nothing is
+/// retained for threads without an active turn.
+#[derive(Clone, Default)]
+pub struct TurnDiagnosticsSink {
+    buffers: Arc<Mutex<HashMap<String, TurnDiagnosticBuffer>>>,
+}
+
+#[derive(Default)]
+struct TurnDiagnosticBuffer {
+    entries: VecDeque<TurnDiagnosticDto>,
+    evicted: usize,
+}
+
+/// What a finished turn collected; `evicted` counts entries pushed out by the
+/// bound.
+#[derive(Debug, Default)]
+pub struct TurnDiagnostics {
+    pub entries: Vec<TurnDiagnosticDto>,
+    pub evicted: usize,
+}
+
+impl TurnDiagnosticsSink {
+    pub fn begin(&self, thread_id: &str) {
+        self.lock()
+            .insert(thread_id.to_string(), TurnDiagnosticBuffer::default());
+    }
+
+    pub fn record(&self, thread_id: &str, source: TurnDiagnosticSourceDto, message: &str) {
+        self.record_at(thread_id, source, now_rfc3339(), message);
+    }
+
+    /// Like [`Self::record`] with a timestamp taken when the line was read.
+    pub fn record_at(
+        &self,
+        thread_id: &str,
+        source: TurnDiagnosticSourceDto,
+        at: String,
+        message: &str,
+    ) {
+        let mut buffers = self.lock();
+        let Some(buffer) = buffers.get_mut(thread_id) else {
+            return;
+        };
+        let message = truncate_message(message);
+        if let Some(last) = buffer.entries.back_mut() {
+            if last.source == source && last.message == message {
+                last.count = last.count.saturating_add(1);
+                return;
+            }
+        }
+        if buffer.entries.len() >= TURN_DIAGNOSTICS_MAX_ENTRIES {
+            buffer.entries.pop_front();
+            buffer.evicted += 1;
+        }
+        buffer.entries.push_back(TurnDiagnosticDto {
+            source,
+            at,
+            message,
+            count: 1,
+        });
+    }
+
+    /// Ends the turn's buffer and hands back whatever it collected.
+    pub fn finish(&self, thread_id: &str) -> TurnDiagnostics {
+        self.lock()
+            .remove(thread_id)
+            .map(|buffer| TurnDiagnostics {
+                entries: buffer.entries.into(),
+                evicted: buffer.evicted,
+            })
+            .unwrap_or_default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, TurnDiagnosticBuffer>> {
+        self.buffers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn truncate_message(message: &str) -> String {
+    let message = message.trim_end();
+    match message
+        .char_indices()
+        .nth(TURN_DIAGNOSTIC_MESSAGE_MAX_CHARS)
+    {
+        Some((index, _)) => format!("{}…", &message[..index]),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_while_turn_is_active_and_stays_bounded() {
+        let sink = TurnDiagnosticsSink::default();
+        sink.record("thread-1", TurnDiagnosticSourceDto::Stderr, "before turn");
+        sink.begin("thread-1");
+        for index in 0..TURN_DIAGNOSTICS_MAX_ENTRIES + 5 {
+            sink.record(
+                "thread-1",
+                TurnDiagnosticSourceDto::Stderr,
+                &format!("line {index}"),
+            );
+        }
+        sink.record("thread-2", TurnDiagnosticSourceDto::Stderr, "other thread");
+
+        let diagnostics = sink.finish("thread-1");
+        assert_eq!(diagnostics.entries.len(), TURN_DIAGNOSTICS_MAX_ENTRIES);
+        assert_eq!(diagnostics.evicted, 5);
+        assert_eq!(diagnostics.entries[0].message, "line 5");
+        assert!(sink.finish("thread-1").entries.is_empty());
+        assert!(sink.finish("thread-2").entries.is_empty());
+    }
+
+    #[test]
+    fn folds_consecutive_duplicates() {
+        let sink = TurnDiagnosticsSink::default();
+        sink.begin("thread-1");
+        for _ in 0..3 {
+            sink.record(
+                "thread-1",
+                TurnDiagnosticSourceDto::DroppedByThreadFilter,
+                "item/agentMessage/delta",
+            );
+        }
+        sink.record("thread-1", TurnDiagnosticSourceDto::Stderr, "warn");
+
+        let entries = sink.finish("thread-1").entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].count, 3);
+        assert_eq!(entries[1].count, 1);
+    }
+}
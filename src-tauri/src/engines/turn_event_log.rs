@@ -0,0 +1,294 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::events::EngineEvent;
+use crate::runtime_env;
+
+const TURN_EVENT_LOG_DIR: [&str; 2] = ["logs", "turns"];
+const TURN_EVENT_LOG_FILE_EXTENSION: &str = "jsonl";
+/// Lines queued for the writer thread before new ones are dropped.
+const TURN_EVENT_LOG_QUEUE_CAPACITY: usize = 4096;
+
+pub fn turn_event_log_dir() -> PathBuf {
+    TURN_EVENT_LOG_DIR
+        .iter()
+        .fold(runtime_env::app_data_dir(), |dir, part| dir.join(part))
+}
+
+/// `<dir>/<thread_id>/<assistant_message_id>.jsonl`. Both ids are UUIDs;
+/// anything else is refused so a caller cannot point the path outside the
+/// log directory.
+pub fn turn_event_log_path(
+    dir: &Path,
+    thread_id: &str,
+    assistant_message_id: &str,
+) -> anyhow::Result<PathBuf> {
+    let thread_id = Uuid::parse_str(thread_id)
+        .map_err(|_| anyhow::anyhow!("invalid thread id: {thread_id}"))?;
+    let message_id = Uuid::parse_str(assistant_message_id)
+        .map_err(|_| anyhow::anyhow!("invalid message id: {assistant_message_id}"))?;
+    Ok(dir.join(thread_id.hyphenated().to_string()).join(format!(
+        "{}.{TURN_EVENT_LOG_FILE_EXTENSION}",
+        message_id.hyphenated()
+    )))
+}
+
+enum TurnEventLogEntry {
+    Line(String),
+    Flush,
+}
+
+/// Appends a turn's engine events to a JSONL file, one
+/// `{"at": ..., "event": ...}` object per line.
+///
+/// Like the terminal transcript, recording only queues: a dedicated thread
+/// does the file I/O and entries are dropped rather than stalling the turn
+/// when it falls behind. The thread flushes and exits once the log is
+/// dropped.
+pub struct TurnEventLog {
+    path: PathBuf,
+    sender: SyncSender<TurnEventLogEntry>,
+    overflowed: AtomicBool,
+}
+
+impl TurnEventLog {
+    pub fn start(dir: &Path, thread_id: &str, assistant_message_id: &str) -> anyhow::Result<Self> {
+        let path = turn_event_log_path(dir, thread_id, assistant_message_id)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let (sender, receiver) = mpsc::sync_channel(TURN_EVENT_LOG_QUEUE_CAPACITY);
+        thread::Builder::new()
+            .name("turn-event-log".to_string())
+            .spawn({
+                let path = path.clone();
+                move || run_writer(&path, BufWriter::new(file), receiver)
+            })?;
+        Ok(Self {
+            path,
+            sender,
+            overflowed: AtomicBool::new(false),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queues `event`; a `TurnCompleted` also flushes the file.
+    pub fn record(&self, event: &EngineEvent) {
+        let Ok(line) = render_entry(event, &Utc::now()) else {
+            return;
+        };
+        self.send(TurnEventLogEntry::Line(line));
+        if matches!(event, EngineEvent::TurnCompleted { .. }) {
+            self.send(TurnEventLogEntry::Flush);
+        }
+    }
+
+    fn send(&self, entry: TurnEventLogEntry) {
+        match self.sender.try_send(entry) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => {
+                if !self.overflowed.swap(true, Ordering::Relaxed) {
+                    log::warn!(
+                        "turn event log {} is falling behind; dropping events",
+                        self.path.display()
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn run_writer(path: &Path, mut file: BufWriter<File>, receiver: Receiver<TurnEventLogEntry>) {
+    for entry in receiver {
+        let written = match entry {
+            TurnEventLogEntry::Line(line) => file.write_all(line.as_bytes()),
+            TurnEventLogEntry::Flush => file.flush(),
+        };
+        if let Err(error) = written {
+            log::warn!("failed to write turn event log {}: {error}", path.display());
+            return;
+        }
+    }
+    if let Err(error) = file.flush() {
+        log::warn!("failed to flush turn event log {}: {error}", path.display());
+    }
+}
+
+fn render_entry(event: &EngineEvent, at: &DateTime<Utc>) -> serde_json::Result<String> {
+    let mut line = serde_json::to_string(&serde_json::json!({
+        "at": at.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "event": event,
+    }))?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// The last `max_lines` entries of a log, oldest first. A line that does
+/// not parse, such as one still being written, is skipped.
+pub fn read_turn_event_log_tail(path: &Path, max_lines: usize) -> io::Result<Vec<Value>> {
+    if max_lines == 0 {
+        return Ok(Vec::new());
+    }
+    let mut tail = VecDeque::with_capacity(max_lines.min(1024));
+    for line in BufReader::new(File::open(path)?).lines() {
+        if tail.len() == max_lines {
+            tail.pop_front();
+        }
+        tail.push_back(line?);
+    }
+    Ok(tail
+        .into_iter()
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// Deletes logs not written to for `retention_days` and the thread folders
+/// left empty. Zero keeps everything. Returns how many logs were removed.
+pub fn prune_turn_event_logs(dir: &Path, retention_days: u32) -> usize {
+    if retention_days == 0 {
+        return 0;
+    }
+    let max_age = Duration::from_secs(u64::from(retention_days) * 24 * 60 * 60);
+    match SystemTime::now().checked_sub(max_age) {
+        Some(cutoff) => prune_turn_event_logs_before(dir, cutoff),
+        None => 0,
+    }
+}
+
+fn prune_turn_event_logs_before(dir: &Path, cutoff: SystemTime) -> usize {
+    let Ok(thread_dirs) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for thread_dir in thread_dirs.flatten() {
+        let thread_dir = thread_dir.path();
+        let Ok(logs) = fs::read_dir(&thread_dir) else {
+            continue;
+        };
+        for log in logs.flatten() {
+            let path = log.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(TURN_EVENT_LOG_FILE_EXTENSION)
+            {
+                continue;
+            }
+            let stale = log
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified < cutoff);
+            if stale && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        // Only succeeds once the folder is empty.
+        let _ = fs::remove_dir(&thread_dir);
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::engines::events::TurnCompletionStatus;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("panes-turn-log-{}", Uuid::new_v4()))
+    }
+
+    fn wait_for_lines(path: &Path, expected: usize) -> Vec<Value> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let lines = read_turn_event_log_tail(path, 10).unwrap_or_default();
+            if lines.len() >= expected || Instant::now() >= deadline {
+                return lines;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn events_are_appended_as_timestamped_json_lines() {
+        let dir = temp_dir();
+        let thread_id = Uuid::new_v4().to_string();
+        let message_id = Uuid::new_v4().to_string();
+        let log = TurnEventLog::start(&dir, &thread_id, &message_id).expect("start");
+        let path = log.path().to_path_buf();
+        assert_eq!(path.parent(), Some(dir.join(&thread_id).as_path()));
+
+        log.record(&EngineEvent::TurnStarted {
+            client_turn_id: Some("client-1".to_string()),
+        });
+        log.record(&EngineEvent::TextDelta {
+            content: "hello".to_string(),
+        });
+        log.record(&EngineEvent::TurnCompleted {
+            token_usage: None,
+            status: TurnCompletionStatus::Completed,
+        });
+
+        let lines = wait_for_lines(&path, 3);
+        assert_eq!(lines.len(), 3, "{lines:?}");
+        assert_eq!(lines[0]["event"]["type"], "TurnStarted");
+        assert_eq!(lines[1]["event"]["content"], "hello");
+        assert!(lines[2]["at"].as_str().is_some_and(|at| at.ends_with('Z')));
+        assert_eq!(
+            read_turn_event_log_tail(&path, 1).expect("tail")[0]["event"]["type"],
+            "TurnCompleted"
+        );
+        assert!(turn_event_log_path(&dir, "../escape", &message_id).is_err());
+        drop(log);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn pruning_removes_stale_logs_and_empty_thread_folders() {
+        let dir = temp_dir();
+        let stale = turn_event_log_path(
+            &dir,
+            &Uuid::new_v4().to_string(),
+            &Uuid::new_v4().to_string(),
+        )
+        .expect("stale path");
+        let fresh = turn_event_log_path(
+            &dir,
+            &Uuid::new_v4().to_string(),
+            &Uuid::new_v4().to_string(),
+        )
+        .expect("fresh path");
+        for path in [&stale, &fresh] {
+            fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+            fs::write(path, "{}\n").expect("write log");
+        }
+        let old = SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(&stale)
+            .and_then(|file| file.set_modified(old))
+            .expect("backdate log");
+
+        assert_eq!(prune_turn_event_logs(&dir, 7), 1);
+        assert!(!stale.parent().expect("parent").exists());
+        assert!(fresh.exists());
+        assert_eq!(prune_turn_event_logs(&dir, 0), 0);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
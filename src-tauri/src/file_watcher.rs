@@ -0,0 +1,361 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex as StdMutex, Weak},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use notify::{event::EventKind, recommended_watcher, Event, RecursiveMode, Watcher};
+use tokio::sync::Mutex;
+
+/// How long a burst of writes to a watched file is collected before the
+/// change is reported.
+pub const FILE_CHANGE_DEBOUNCE: Duration = Duration::from_millis(200);
+
+pub type FileChangeCallback = Arc<dyn Fn(FileChange) + Send + Sync + 'static>;
+type BoxedWatcher = Box<dyn Watcher + Send>;
+
+/// A watched file changed on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// The path as it was passed to [`FileWatcherManager::watch_file`].
+    pub path: String,
+    /// Modification time in milliseconds since the Unix epoch; `None` when
+    /// the file is gone.
+    pub mtime_ms: Option<i64>,
+}
+
+struct WatchedFile {
+    _watcher: BoxedWatcher,
+    debouncer: Arc<FileChangeDebouncer>,
+    /// Open editors watching this file; the watcher stops at zero.
+    watchers: usize,
+}
+
+/// Watches individual files opened in the editor. Each file gets its own
+/// non-recursive watcher on its parent directory so editors and agents that
+/// save through a rename are still seen.
+#[derive(Default, Clone)]
+pub struct FileWatcherManager {
+    files: Arc<Mutex<HashMap<PathBuf, WatchedFile>>>,
+}
+
+impl FileWatcherManager {
+    /// Watches `path` and reports changes through `callback`, at most once
+    /// per `debounce` window. Watching an already watched file only counts
+    /// another watcher.
+    pub async fn watch_file(
+        &self,
+        path: String,
+        debounce: Duration,
+        callback: FileChangeCallback,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            Path::new(&path).is_absolute(),
+            "file watch path must be absolute: {path}"
+        );
+        let key = watch_key(Path::new(&path))?;
+        let mut files = self.files.lock().await;
+        if let Some(watched) = files.get_mut(&key) {
+            watched.watchers += 1;
+            return Ok(());
+        }
+
+        let parent = key.parent().context("cannot watch a filesystem root")?;
+        let debouncer = Arc::new(FileChangeDebouncer::new(
+            path.clone(),
+            key.clone(),
+            debounce,
+            callback,
+        ));
+        let candidates = vec![PathBuf::from(&path), key.clone()];
+        let handler = {
+            let debouncer = Arc::clone(&debouncer);
+            move |result: notify::Result<Event>| {
+                if let Ok(event) = result {
+                    if is_file_event(&event, &candidates) {
+                        debouncer.record();
+                    }
+                }
+            }
+        };
+        let mut watcher = recommended_watcher(handler)?;
+        watcher
+            .watch(parent, RecursiveMode::NonRecursive)
+            .with_context(|| format!("failed to watch {path}"))?;
+
+        files.insert(
+            key,
+            WatchedFile {
+                _watcher: Box::new(watcher),
+                debouncer,
+                watchers: 1,
+            },
+        );
+        Ok(())
+    }
+
+    /// Drops one watcher of `path`, stopping the watch once none is left.
+    /// True when the file was watched.
+    pub async fn unwatch_file(&self, path: &str) -> bool {
+        let Ok(key) = watch_key(Path::new(path)) else {
+            return false;
+        };
+        let mut files = self.files.lock().await;
+        let Some(watched) = files.get_mut(&key) else {
+            return false;
+        };
+        watched.watchers -= 1;
+        if watched.watchers == 0 {
+            files.remove(&key);
+        }
+        true
+    }
+
+    /// Stops every file watch under any of `roots`, returning the watched
+    /// paths.
+    pub async fn unwatch_within(&self, roots: &[PathBuf]) -> Vec<String> {
+        let roots = roots
+            .iter()
+            .flat_map(|root| [root.clone(), fs::canonicalize(root).unwrap_or_default()])
+            .filter(|root| !root.as_os_str().is_empty())
+            .collect::<Vec<_>>();
+        let mut files = self.files.lock().await;
+        let mut stopped = Vec::new();
+        files.retain(|key, watched| {
+            let inside = roots.iter().any(|root| key.starts_with(root));
+            if inside {
+                stopped.push(watched.debouncer.path.clone());
+            }
+            !inside
+        });
+        stopped
+    }
+
+    /// Marks the file's current contents as written by the app itself, so
+    /// the change it triggers is not reported back to the editor.
+    pub async fn suppress_own_write(&self, path: &Path) {
+        let Ok(key) = watch_key(path) else {
+            return;
+        };
+        if let Some(watched) = self.files.lock().await.get(&key) {
+            watched.debouncer.suppress_current();
+        }
+    }
+}
+
+/// The canonical path of a file, resolving its parent when the file itself
+/// is missing.
+fn watch_key(path: &Path) -> anyhow::Result<PathBuf> {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return Ok(canonical);
+    }
+    let name = path.file_name().context("invalid file path")?;
+    let parent = path.parent().context("invalid file path")?;
+    let parent = fs::canonicalize(parent)
+        .with_context(|| format!("failed to resolve {}", parent.display()))?;
+    Ok(parent.join(name))
+}
+
+fn is_file_event(event: &Event, candidates: &[PathBuf]) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event
+            .paths
+            .iter()
+            .any(|path| candidates.iter().any(|candidate| candidate == path))
+}
+
+/// Enough of a file's metadata to tell whether it changed since the app
+/// last wrote it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileFingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileFingerprint {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+
+    fn mtime_ms(self) -> Option<i64> {
+        let elapsed = self.modified?.duration_since(UNIX_EPOCH).ok()?;
+        i64::try_from(elapsed.as_millis()).ok()
+    }
+}
+
+/// Collects one file's change events and reports them together `window`
+/// after the first, skipping the report when the file still matches what
+/// the app itself last wrote.
+struct FileChangeDebouncer {
+    path: String,
+    key: PathBuf,
+    window: Duration,
+    callback: FileChangeCallback,
+    pending: StdMutex<bool>,
+    suppressed: StdMutex<Option<FileFingerprint>>,
+}
+
+impl FileChangeDebouncer {
+    fn new(path: String, key: PathBuf, window: Duration, callback: FileChangeCallback) -> Self {
+        Self {
+            path,
+            key,
+            window,
+            callback,
+            pending: StdMutex::new(false),
+            suppressed: StdMutex::new(None),
+        }
+    }
+
+    fn record(self: &Arc<Self>) {
+        {
+            let mut pending = self
+                .pending
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if *pending {
+                return;
+            }
+            *pending = true;
+        }
+
+        // The flush holds a weak handle so an unwatched file reports nothing.
+        let debouncer = Arc::downgrade(self);
+        let window = self.window;
+        let spawned = std::thread::Builder::new()
+            .name("file-watch-debounce".to_string())
+            .spawn(move || {
+                std::thread::sleep(window);
+                if let Some(debouncer) = Weak::upgrade(&debouncer) {
+                    debouncer.flush();
+                }
+            });
+        if let Err(error) = spawned {
+            log::warn!("failed to debounce changes to {}: {error}", self.path);
+            self.flush();
+        }
+    }
+
+    fn suppress_current(&self) {
+        *self
+            .suppressed
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = FileFingerprint::read(&self.key);
+    }
+
+    fn flush(&self) {
+        let was_pending = std::mem::take(
+            &mut *self
+                .pending
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+        if !was_pending {
+            return;
+        }
+
+        let current = FileFingerprint::read(&self.key);
+        let suppressed = self
+            .suppressed
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+        if suppressed.is_some() && suppressed == current {
+            return;
+        }
+        (self.callback)(FileChange {
+            path: self.path.clone(),
+            mtime_ms: current.and_then(FileFingerprint::mtime_ms),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn own_writes_are_suppressed_once_and_bursts_report_once() {
+        let dir = std::env::temp_dir().join(format!("panes-file-watch-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("notes.md");
+        fs::write(&file, "draft").unwrap();
+
+        let changes = Arc::new(StdMutex::new(Vec::new()));
+        let sink = Arc::clone(&changes);
+        let callback: FileChangeCallback =
+            Arc::new(move |change| sink.lock().unwrap().push(change));
+        let debouncer = Arc::new(FileChangeDebouncer::new(
+            "notes.md".to_string(),
+            file.clone(),
+            Duration::from_millis(50),
+            callback,
+        ));
+        let settle = || std::thread::sleep(Duration::from_millis(150));
+
+        // The editor saves: its own write is not echoed back.
+        fs::write(&file, "edited in the editor").unwrap();
+        debouncer.suppress_current();
+        debouncer.record();
+        debouncer.record();
+        settle();
+        assert!(changes.lock().unwrap().is_empty());
+
+        // An agent rewrites the file in a burst: reported once.
+        for contents in ["agent pass 1", "agent pass two", "agent pass three"] {
+            fs::write(&file, contents).unwrap();
+            debouncer.record();
+        }
+        settle();
+        let expected_mtime = FileFingerprint::read(&file).and_then(FileFingerprint::mtime_ms);
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![FileChange {
+                path: "notes.md".to_string(),
+                mtime_ms: expected_mtime,
+            }]
+        );
+
+        // Removing the file reports it without an mtime.
+        fs::remove_file(&file).unwrap();
+        debouncer.record();
+        settle();
+        assert_eq!(changes.lock().unwrap().last().unwrap().mtime_ms, None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn only_non_access_events_on_the_file_count() {
+        let candidates = vec![PathBuf::from("/tmp/ws/src/main.rs")];
+        let event = |kind, path: &str| Event {
+            kind,
+            paths: vec![PathBuf::from(path)],
+            attrs: Default::default(),
+        };
+        let modify = EventKind::Modify(notify::event::ModifyKind::Any);
+
+        assert!(is_file_event(
+            &event(modify, "/tmp/ws/src/main.rs"),
+            &candidates
+        ));
+        assert!(!is_file_event(
+            &event(modify, "/tmp/ws/src/lib.rs"),
+            &candidates
+        ));
+        assert!(!is_file_event(
+            &event(
+                EventKind::Access(notify::event::AccessKind::Any),
+                "/tmp/ws/src/main.rs"
+            ),
+            &candidates
+        ));
+    }
+}
@@ -1,14 +1,19 @@
 use std::{
     fs,
+    io::{Read, Write},
     path::{Component, Path, PathBuf},
 };
 
 use anyhow::Context;
+use git2::Repository;
 
-use crate::models::{FileTreeEntryDto, ReadFileResultDto};
+use crate::{
+    git::repo::decorate_file_tree_entries,
+    models::{FileTreeEntryDto, FileTreeGitStatusDto, ReadFileResultDto},
+};
 
-const READ_FILE_MAX_SIZE: u64 = 10 * 1024 * 1024; // 10 MB
 const BINARY_DETECT_SCAN_SIZE: usize = 8192;
+const READ_FILE_PREVIEW_SIZE: usize = 64 * 1024;
 
 pub fn validate_repo_relative_path(path: &str) -> anyhow::Result<&Path> {
     let mut has_component = false;
@@ -23,7 +28,15 @@ pub fn validate_repo_relative_path(path: &str) -> anyhow::Result<&Path> {
     Ok(Path::new(path))
 }
 
-pub fn list_dir(repo_path: &str, dir_path: &str) -> anyhow::Result<Vec<FileTreeEntryDto>> {
+/// Direct children of `dir_path`, directories first. When `repo_path` is a
+/// git repo root, gitignored entries (including `.git/info/exclude`) are
+/// left out unless `show_ignored` is set, and entries carry their git
+/// status.
+pub fn list_dir(
+    repo_path: &str,
+    dir_path: &str,
+    show_ignored: bool,
+) -> anyhow::Result<Vec<FileTreeEntryDto>> {
     let repo_root = PathBuf::from(repo_path)
         .canonicalize()
         .context("failed to canonicalize repo path")?;
@@ -37,6 +50,7 @@ pub fn list_dir(repo_path: &str, dir_path: &str) -> anyhow::Result<Vec<FileTreeE
     };
     anyhow::ensure!(target.starts_with(&repo_root), "path traversal not allowed");
     anyhow::ensure!(target.is_dir(), "path is not a directory");
+    let repo = Repository::open(&repo_root).ok();
 
     let mut entries = Vec::new();
     for entry in fs::read_dir(&target).context("failed to read directory")? {
@@ -54,7 +68,8 @@ pub fn list_dir(repo_path: &str, dir_path: &str) -> anyhow::Result<Vec<FileTreeE
         }
 
         // Skip symlinks pointing outside the repo
-        if path.is_symlink() {
+        let is_symlink = path.is_symlink();
+        if is_symlink {
             if let Ok(canonical) = path.canonicalize() {
                 if !canonical.starts_with(&repo_root) {
                     continue;
@@ -70,11 +85,23 @@ pub fn list_dir(repo_path: &str, dir_path: &str) -> anyhow::Result<Vec<FileTreeE
             .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_else(|_| path.to_string_lossy().to_string());
 
+        let ignored = repo
+            .as_ref()
+            .is_some_and(|repo| repo.is_path_ignored(&relative).unwrap_or(false));
+        if ignored && !show_ignored {
+            continue;
+        }
+
         entries.push(FileTreeEntryDto {
             path: relative,
             is_dir: path.is_dir(),
+            is_symlink,
+            git_status: ignored.then_some(FileTreeGitStatusDto::Ignored),
         });
     }
+    if let Some(repo) = &repo {
+        decorate_file_tree_entries(repo, dir_path, &mut entries);
+    }
 
     entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
         (true, false) => std::cmp::Ordering::Less,
@@ -85,7 +112,16 @@ pub fn list_dir(repo_path: &str, dir_path: &str) -> anyhow::Result<Vec<FileTreeE
     Ok(entries)
 }
 
-pub fn read_file(repo_path: &str, file_path: &str) -> anyhow::Result<ReadFileResultDto> {
+/// Reads a file for the editor. Binary files (NUL bytes or invalid UTF-8 in
+/// the first few KB) come back without content, and text files above
+/// `preview_threshold` as a preview of their first 64 KB. `force` returns
+/// the whole file either way.
+pub fn read_file(
+    repo_path: &str,
+    file_path: &str,
+    preview_threshold: u64,
+    force: bool,
+) -> anyhow::Result<ReadFileResultDto> {
     let repo_root = PathBuf::from(repo_path)
         .canonicalize()
         .context("failed to canonicalize repo path")?;
@@ -97,26 +133,64 @@ pub fn read_file(repo_path: &str, file_path: &str) -> anyhow::Result<ReadFileRes
         abs_path.starts_with(&repo_root),
         "path traversal not allowed"
     );
-    let metadata = fs::metadata(&abs_path).context("failed to read file metadata")?;
-    let size_bytes = metadata.len();
-    anyhow::ensure!(
-        size_bytes <= READ_FILE_MAX_SIZE,
-        "file too large to open in editor (max 10 MB)"
-    );
-    let raw = fs::read(&abs_path).context("failed to read file")?;
-    let is_binary = raw.iter().take(BINARY_DETECT_SCAN_SIZE).any(|&b| b == 0);
-    let content = if is_binary {
-        String::new()
+    let mut file = fs::File::open(&abs_path).context("failed to read file")?;
+    let size_bytes = file
+        .metadata()
+        .context("failed to read file metadata")?
+        .len();
+
+    let mut raw = Vec::new();
+    (&mut file)
+        .take(BINARY_DETECT_SCAN_SIZE as u64)
+        .read_to_end(&mut raw)
+        .context("failed to read file")?;
+    let is_binary = looks_binary(&raw, raw.len() as u64 >= size_bytes);
+    let truncated = !force && !is_binary && size_bytes > preview_threshold;
+    if is_binary && !force {
+        raw.clear();
     } else {
-        String::from_utf8_lossy(&raw).to_string()
-    };
+        let limit = if truncated {
+            READ_FILE_PREVIEW_SIZE.saturating_sub(raw.len()) as u64
+        } else {
+            u64::MAX
+        };
+        (&mut file)
+            .take(limit)
+            .read_to_end(&mut raw)
+            .context("failed to read file")?;
+    }
+    if truncated {
+        raw.truncate(complete_utf8_prefix_len(&raw));
+    }
+
     Ok(ReadFileResultDto {
-        content,
+        content: String::from_utf8_lossy(&raw).into_owned(),
         size_bytes,
         is_binary,
+        truncated,
     })
 }
 
+/// NUL bytes or invalid UTF-8 in `head`. A multi-byte character cut off at
+/// the end of `head` only counts when `head` is the whole file.
+fn looks_binary(head: &[u8], whole_file: bool) -> bool {
+    if head.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
+        Err(error) => error.error_len().is_some() || whole_file,
+    }
+}
+
+/// Length of `bytes` without a multi-byte character cut off at the end.
+fn complete_utf8_prefix_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Err(error) if error.error_len().is_none() => error.valid_up_to(),
+        _ => bytes.len(),
+    }
+}
+
 pub fn create_file(repo_path: &str, file_path: &str) -> anyhow::Result<()> {
     let repo_root = PathBuf::from(repo_path)
         .canonicalize()
@@ -266,14 +340,45 @@ pub fn delete_path(repo_path: &str, file_path: &str) -> anyhow::Result<()> {
     delete_existing_entry(&target)
 }
 
-pub fn write_file(repo_path: &str, file_path: &str, content: &str) -> anyhow::Result<()> {
+/// A file write the editor can recover from: offer to create the missing
+/// directory, or explain that the file is not writable.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FileWriteError {
+    #[error("parent directory not found: {path}")]
+    ParentMissing { path: String },
+    #[error("permission denied: {path}")]
+    PermissionDenied { path: String },
+}
+
+/// Writes `content` to a temp file next to the target and renames it over
+/// the target, so a crash mid-write never leaves a truncated file. An
+/// existing file keeps its permissions and, on Unix, its owner where the
+/// process may set it. With `create_backup` the previous contents are kept
+/// as `<name>.bak`. A read-only file is refused rather than replaced, since
+/// the rename only needs write access to the directory. The rename also
+/// gives the target a new inode, so other hard links to it keep the old
+/// contents.
+pub fn write_file(
+    repo_path: &str,
+    file_path: &str,
+    content: &str,
+    create_backup: bool,
+) -> anyhow::Result<()> {
     let repo_root = PathBuf::from(repo_path)
         .canonicalize()
         .context("failed to canonicalize repo path")?;
     let target = repo_root.join(file_path);
+    let parent_missing = || FileWriteError::ParentMissing {
+        path: Path::new(file_path)
+            .parent()
+            .map(|parent| parent.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default(),
+    };
 
-    // If the file already exists, canonicalize and verify the full path
-    if target.exists() {
+    // If the file already exists, canonicalize and verify the full path.
+    // Writing through the canonical path replaces a symlink's target rather
+    // than the link.
+    let destination = if target.exists() {
         let canonical = target
             .canonicalize()
             .context("failed to resolve file path")?;
@@ -281,27 +386,107 @@ pub fn write_file(repo_path: &str, file_path: &str, content: &str) -> anyhow::Re
             canonical.starts_with(&repo_root),
             "path traversal not allowed"
         );
+        canonical
     } else {
         // New file — verify the parent directory is inside the repo
         let parent = target.parent().context("invalid file path")?;
-        let parent_canonical = parent
-            .canonicalize()
-            .context("parent directory not found")?;
+        let parent_canonical = match parent.canonicalize() {
+            Ok(parent) => parent,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Err(parent_missing().into())
+            }
+            Err(error) => return Err(error).context("failed to resolve parent directory"),
+        };
         anyhow::ensure!(
             parent_canonical.starts_with(&repo_root),
             "path traversal not allowed"
         );
+        target
+    };
+
+    write_atomically(&destination, content.as_bytes(), create_backup).map_err(|error| {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => parent_missing().into(),
+            std::io::ErrorKind::PermissionDenied => FileWriteError::PermissionDenied {
+                path: file_path.to_string(),
+            }
+            .into(),
+            _ => anyhow::Error::new(error).context("failed to write file"),
+        }
+    })
+}
+
+fn write_atomically(target: &Path, content: &[u8], create_backup: bool) -> std::io::Result<()> {
+    let parent = target.parent().ok_or(std::io::ErrorKind::NotFound)?;
+    let name = target
+        .file_name()
+        .ok_or(std::io::ErrorKind::InvalidInput)?
+        .to_string_lossy();
+    let existing = fs::metadata(target).ok();
+    if existing
+        .as_ref()
+        .is_some_and(|existing| existing.permissions().readonly())
+    {
+        return Err(std::io::ErrorKind::PermissionDenied.into());
+    }
+    let temp = parent.join(format!(
+        ".{name}.{}.panes-tmp",
+        uuid::Uuid::new_v4().simple()
+    ));
+
+    let written = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)?;
+        file.write_all(content)?;
+        if let Some(existing) = &existing {
+            file.set_permissions(existing.permissions())?;
+            #[cfg(unix)]
+            preserve_owner(&file, existing);
+        }
+        file.sync_all()?;
+        if create_backup && existing.is_some() {
+            fs::copy(target, parent.join(format!("{name}.bak")))?;
+        }
+        fs::rename(&temp, target)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
     }
+    written?;
 
-    fs::write(&target, content).context("failed to write file")?;
+    // Persist the rename itself; not every platform can open a directory.
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(parent) {
+        let _ = dir.sync_all();
+    }
     Ok(())
 }
 
+/// Hands the replacement file to the original owner. Only privileged
+/// processes may do so, so a failure leaves it owned by the current user.
+#[cfg(unix)]
+fn preserve_owner(file: &fs::File, existing: &fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(current) = file.metadata() else {
+        return;
+    };
+    if (current.uid(), current.gid()) != (existing.uid(), existing.gid()) {
+        let _ = std::os::unix::fs::fchown(file, Some(existing.uid()), Some(existing.gid()));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, path::PathBuf};
 
-    use super::{create_dir, create_file, delete_path, rename_path};
+    use super::{
+        create_dir, create_file, delete_path, list_dir, read_file, rename_path, write_file,
+        FileWriteError, READ_FILE_PREVIEW_SIZE,
+    };
+    use crate::models::FileTreeGitStatusDto;
     use uuid::Uuid;
 
     #[cfg(unix)]
@@ -315,6 +500,111 @@ mod tests {
         result
     }
 
+    #[test]
+    fn list_dir_honors_gitignore_and_reports_git_status() {
+        with_temp_repo(|root| {
+            let repo = git2::Repository::init(&root).expect("init repo");
+            fs::write(root.join(".gitignore"), "node_modules/\n").expect("write gitignore");
+            fs::write(root.join("tracked.txt"), "one\n").expect("write tracked");
+            fs::create_dir_all(root.join("src")).expect("create src");
+            fs::write(root.join("src/lib.rs"), "one\n").expect("write lib");
+            let mut index = repo.index().expect("index");
+            for path in [".gitignore", "tracked.txt", "src/lib.rs"] {
+                index
+                    .add_path(std::path::Path::new(path))
+                    .expect("add path");
+            }
+            index.write().expect("write index");
+            let tree = repo
+                .find_tree(index.write_tree().expect("write tree"))
+                .expect("tree");
+            let signature = git2::Signature::now("Test", "test@example.com").expect("signature");
+            repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+                .expect("commit");
+
+            fs::write(root.join("tracked.txt"), "two\n").expect("modify tracked");
+            fs::write(root.join("src/lib.rs"), "two\n").expect("modify lib");
+            fs::write(root.join("fresh.txt"), "new\n").expect("write untracked");
+            fs::create_dir_all(root.join("newdir/nested")).expect("create untracked dir");
+            fs::write(root.join("newdir/nested/a.txt"), "a\n").expect("write nested");
+            fs::create_dir_all(root.join("node_modules/pkg")).expect("create node_modules");
+            fs::write(root.join("node_modules/pkg/index.js"), "x\n").expect("write dep");
+            fs::write(root.join(".git/info/exclude"), "secret.txt\n").expect("write exclude");
+            fs::write(root.join("secret.txt"), "s\n").expect("write excluded");
+            #[cfg(unix)]
+            symlink("tracked.txt", root.join("link.txt")).expect("symlink should exist");
+
+            let root_path = root.to_string_lossy().to_string();
+            let listed = |dir: &str, show_ignored: bool| {
+                list_dir(&root_path, dir, show_ignored)
+                    .expect("list dir")
+                    .into_iter()
+                    .map(|entry| (entry.path, entry.git_status))
+                    .collect::<Vec<_>>()
+            };
+
+            let top = listed("", false);
+            assert!(!top.iter().any(|(path, _)| path == "node_modules"));
+            assert!(!top.iter().any(|(path, _)| path == "secret.txt"));
+            let status_of = |entries: &[(String, Option<FileTreeGitStatusDto>)], path: &str| {
+                entries
+                    .iter()
+                    .find(|(entry, _)| entry == path)
+                    .unwrap_or_else(|| panic!("{path} should be listed"))
+                    .1
+            };
+            assert_eq!(
+                status_of(&top, "tracked.txt"),
+                Some(FileTreeGitStatusDto::Modified)
+            );
+            assert_eq!(status_of(&top, "src"), Some(FileTreeGitStatusDto::Modified));
+            assert_eq!(
+                status_of(&top, "fresh.txt"),
+                Some(FileTreeGitStatusDto::Untracked)
+            );
+            assert_eq!(
+                status_of(&top, "newdir"),
+                Some(FileTreeGitStatusDto::Untracked)
+            );
+            assert_eq!(status_of(&top, ".gitignore"), None);
+
+            let nested = listed("newdir", false);
+            assert_eq!(
+                status_of(&nested, "newdir/nested"),
+                Some(FileTreeGitStatusDto::Untracked)
+            );
+
+            let with_ignored = listed("", true);
+            assert_eq!(
+                status_of(&with_ignored, "node_modules"),
+                Some(FileTreeGitStatusDto::Ignored)
+            );
+            assert_eq!(
+                status_of(&with_ignored, "secret.txt"),
+                Some(FileTreeGitStatusDto::Ignored)
+            );
+
+            #[cfg(unix)]
+            {
+                let link = list_dir(&root_path, "", false)
+                    .expect("list dir")
+                    .into_iter()
+                    .find(|entry| entry.path == "link.txt")
+                    .expect("symlink should be listed");
+                assert!(link.is_symlink && !link.is_dir);
+            }
+
+            let page = crate::git::repo::get_file_tree_dir_page(&root_path, "src", 0, 10, false)
+                .expect("dir page");
+            assert_eq!(page.total, 1);
+            assert_eq!(page.entries[0].path, "src/lib.rs");
+            assert_eq!(
+                page.entries[0].git_status,
+                Some(FileTreeGitStatusDto::Modified)
+            );
+        });
+    }
+
     #[test]
     fn create_file_creates_missing_parent_directories() {
         with_temp_repo(|root| {
@@ -437,4 +727,136 @@ mod tests {
             assert!(fs::symlink_metadata(root.join("link.txt")).is_err());
         });
     }
+
+    #[test]
+    fn read_file_previews_large_text_and_withholds_binary_content() {
+        with_temp_repo(|root| {
+            let repo = root.to_str().expect("utf-8 path");
+            let threshold = 16 * 1024;
+
+            // A character split by the sniffing window is still text.
+            let small = format!("{}é tail", "a".repeat(8191));
+            fs::write(root.join("small.txt"), &small).expect("write small");
+            let result = read_file(repo, "small.txt", threshold, false).expect("read small");
+            assert_eq!(result.content, small);
+            assert!(!result.is_binary && !result.truncated);
+
+            let large = "éa".repeat(40_000);
+            fs::write(root.join("large.txt"), &large).expect("write large");
+            let result = read_file(repo, "large.txt", threshold, false).expect("read large");
+            assert!(result.truncated && !result.is_binary);
+            assert_eq!(result.size_bytes, large.len() as u64);
+            assert!(result.content.len() <= READ_FILE_PREVIEW_SIZE);
+            assert!(large.starts_with(&result.content));
+            assert!(!result.content.contains('\u{fffd}'));
+            let forced = read_file(repo, "large.txt", threshold, true).expect("force large");
+            assert_eq!(forced.content, large);
+            assert!(!forced.truncated);
+
+            fs::write(root.join("blob.bin"), b"PK\x03\x04\x00\x00data").expect("write blob");
+            fs::write(root.join("latin1.txt"), b"caf\xe9\n").expect("write latin1");
+            for name in ["blob.bin", "latin1.txt"] {
+                let result = read_file(repo, name, threshold, false).expect("read binary");
+                assert!(result.is_binary && !result.truncated);
+                assert!(result.content.is_empty());
+            }
+            let forced = read_file(repo, "latin1.txt", threshold, true).expect("force binary");
+            assert_eq!(forced.content, "caf\u{fffd}\n");
+        });
+    }
+
+    #[test]
+    fn write_file_replaces_atomically_keeps_permissions_and_backs_up() {
+        with_temp_repo(|root| {
+            let repo = root.to_str().expect("utf-8 path");
+            write_file(repo, "notes.md", "first\n", true).expect("create file");
+            assert_eq!(
+                fs::read_to_string(root.join("notes.md")).unwrap(),
+                "first\n"
+            );
+            assert!(!root.join("notes.md.bak").exists());
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(root.join("notes.md"), fs::Permissions::from_mode(0o750))
+                    .expect("chmod");
+            }
+            write_file(repo, "notes.md", "second\n", true).expect("overwrite file");
+            assert_eq!(
+                fs::read_to_string(root.join("notes.md")).unwrap(),
+                "second\n"
+            );
+            assert_eq!(
+                fs::read_to_string(root.join("notes.md.bak")).unwrap(),
+                "first\n"
+            );
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = fs::metadata(root.join("notes.md"))
+                    .unwrap()
+                    .permissions()
+                    .mode();
+                assert_eq!(mode & 0o777, 0o750);
+            }
+            let mut names = fs::read_dir(&root)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            names.sort();
+            assert_eq!(names, ["notes.md", "notes.md.bak"]);
+
+            let error = write_file(repo, "missing/dir/file.txt", "x", false).unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<FileWriteError>(),
+                Some(&FileWriteError::ParentMissing {
+                    path: "missing/dir".to_string(),
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn write_file_refuses_read_only_files() {
+        with_temp_repo(|root| {
+            let file = root.join("locked.md");
+            fs::write(&file, "keep\n").expect("write file");
+            let mut permissions = fs::metadata(&file).unwrap().permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(&file, permissions.clone()).expect("make read-only");
+
+            let error =
+                write_file(root.to_str().unwrap(), "locked.md", "replaced\n", true).unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<FileWriteError>(),
+                Some(&FileWriteError::PermissionDenied {
+                    path: "locked.md".to_string(),
+                })
+            );
+            assert_eq!(fs::read_to_string(&file).unwrap(), "keep\n");
+            assert_eq!(fs::read_dir(&root).unwrap().count(), 1);
+
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            fs::set_permissions(&file, permissions).expect("make writable");
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_file_through_symlink_replaces_the_target() {
+        with_temp_repo(|root| {
+            fs::write(root.join("target.txt"), "old").expect("write target");
+            symlink("target.txt", root.join("link.txt")).expect("symlink");
+
+            write_file(root.to_str().unwrap(), "link.txt", "new", false).expect("write link");
+
+            assert!(fs::symlink_metadata(root.join("link.txt"))
+                .unwrap()
+                .file_type()
+                .is_symlink());
+            assert_eq!(fs::read_to_string(root.join("target.txt")).unwrap(), "new");
+        });
+    }
 }
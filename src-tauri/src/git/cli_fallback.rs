@@ -1,28 +1,106 @@
-use std::process::Command;
+use std::{
+    io::Write,
+    ops::RangeInclusive,
+    process::{Command, Stdio},
+};
 
 use anyhow::Context;
 
+use super::preflight::{GitBinaryRequiredError, GitPreflightCache};
 use crate::process_utils;
 
 pub fn run_git(repo_path: &str, args: &[&str]) -> anyhow::Result<String> {
-    let mut command = Command::new("git");
+    run_git_with(GitPreflightCache::global(), repo_path, args)
+}
+
+/// Like [`run_git`], but writes `input` to git's stdin and returns stdout as
+/// raw bytes, so patches survive non-UTF-8 content and CRLF line endings.
+pub fn run_git_bytes(
+    repo_path: &str,
+    args: &[&str],
+    input: Option<&[u8]>,
+) -> anyhow::Result<Vec<u8>> {
+    run_git_bytes_with(GitPreflightCache::global(), repo_path, args, input, 0..=0)
+}
+
+/// Like [`run_git`], but also treats the exit codes in `accepted` as
+/// success, for commands such as `git merge-file` that report a count
+/// through their exit status.
+pub fn run_git_accepting(
+    repo_path: &str,
+    args: &[&str],
+    accepted: RangeInclusive<i32>,
+) -> anyhow::Result<String> {
+    let stdout = run_git_bytes_with(GitPreflightCache::global(), repo_path, args, None, accepted)?;
+    Ok(String::from_utf8_lossy(&stdout).to_string())
+}
+
+pub(crate) fn run_git_with(
+    preflight: &GitPreflightCache,
+    repo_path: &str,
+    args: &[&str],
+) -> anyhow::Result<String> {
+    let stdout = run_git_bytes_with(preflight, repo_path, args, None, 0..=0)?;
+    Ok(String::from_utf8_lossy(&stdout).to_string())
+}
+
+fn run_git_bytes_with(
+    preflight: &GitPreflightCache,
+    repo_path: &str,
+    args: &[&str],
+    input: Option<&[u8]>,
+    accepted: RangeInclusive<i32>,
+) -> anyhow::Result<Vec<u8>> {
+    let operation = args.first().copied().unwrap_or("git");
+    let binary = preflight.require_binary(operation)?;
+    let mut command = Command::new(&binary.path);
     process_utils::configure_std_command(&mut command);
-    let output = command
-        .arg("-C")
-        .arg(repo_path)
-        .args(args)
-        .output()
-        .with_context(|| {
-            format!(
-                "failed to run git command: git -C {repo_path} {}",
-                args.join(" ")
-            )
-        })?;
-
-    if !output.status.success() {
+    command.arg("-C").arg(repo_path).args(args);
+    let output = match input {
+        None => command.output(),
+        Some(input) => command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                // Write from a separate thread so a full stdout pipe can't
+                // stall git while we are still feeding it.
+                let mut stdin = child.stdin.take().expect("stdin is piped");
+                let input = input.to_vec();
+                let writer = std::thread::spawn(move || stdin.write_all(&input));
+                let output = child.wait_with_output()?;
+                writer
+                    .join()
+                    .map_err(|_| std::io::Error::other("git stdin writer panicked"))??;
+                Ok(output)
+            }),
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            // The cached executable vanished (uninstalled mid-session).
+            preflight.invalidate_binary();
+            return Err(GitBinaryRequiredError::new(operation).into());
+        }
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!(
+                    "failed to run git command: git -C {repo_path} {}",
+                    args.join(" ")
+                )
+            })
+        }
+    };
+
+    let accepted = output
+        .status
+        .code()
+        .is_some_and(|code| accepted.contains(&code));
+    if !output.status.success() && !accepted {
         let stderr = String::from_utf8_lossy(&output.stderr);
         anyhow::bail!("git command failed: {}", stderr.trim());
     }
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    Ok(output.stdout)
 }
@@ -0,0 +1,193 @@
+use anyhow::Context;
+use git2::{Delta, DiffFindOptions, DiffOptions, Patch, Repository};
+
+use crate::models::GitCommitMessageDto;
+
+const PROMPT_INSTRUCTIONS: &str = "\
+Write a git commit message for the staged changes below.
+
+Reply with the message only: a subject line of at most 72 characters in the \
+imperative mood, then, when the subject alone does not explain the change, a \
+blank line and a short body saying what changed and why. No code fences, \
+quotes or commentary.";
+
+/// The staged changes of a repo, trimmed to fit a prompt.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct StagedDiff {
+    /// One `<status> <path>` line per staged file.
+    pub files: Vec<String>,
+    /// Text patches, whole files only, up to the byte budget.
+    pub patch: String,
+    /// Text files whose patch did not fit the budget.
+    pub omitted: Vec<String>,
+}
+
+impl StagedDiff {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Collects the diff between HEAD and the index. Binary files are listed
+/// but never included, and text patches stop at `budget_bytes`.
+pub fn staged_diff(repo_path: &str, budget_bytes: usize) -> anyhow::Result<StagedDiff> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut options = DiffOptions::new();
+    options.context_lines(2);
+    let mut diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options))
+        .context("failed to diff the index")?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .context("failed to detect renames")?;
+
+    let mut staged = StagedDiff::default();
+    for index in 0..diff.deltas().len() {
+        let patch = Patch::from_diff(&diff, index).context("failed to load staged patch")?;
+        let delta = diff.get_delta(index).context("missing staged delta")?;
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|path| path.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let binary = delta.flags().is_binary()
+            || patch
+                .as_ref()
+                .is_some_and(|patch| patch.delta().flags().is_binary());
+        let status = match delta.status() {
+            Delta::Added => "A",
+            Delta::Deleted => "D",
+            Delta::Renamed => "R",
+            Delta::Copied => "C",
+            Delta::Typechange => "T",
+            _ => "M",
+        };
+        let suffix = if binary { " (binary, not shown)" } else { "" };
+        staged.files.push(format!("{status} {path}{suffix}"));
+        if binary {
+            continue;
+        }
+
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        let text = patch.to_buf().context("failed to render staged patch")?;
+        let text = String::from_utf8_lossy(&text);
+        if staged.patch.len() + text.len() <= budget_bytes {
+            staged.patch.push_str(&text);
+        } else {
+            staged.omitted.push(path);
+        }
+    }
+    Ok(staged)
+}
+
+/// The fixed prompt asking an engine for a commit message describing
+/// `diff`.
+pub fn build_prompt(diff: &StagedDiff) -> String {
+    let mut prompt = format!(
+        "{PROMPT_INSTRUCTIONS}\n\nStaged files:\n{}\n",
+        diff.files.join("\n")
+    );
+    if !diff.patch.is_empty() {
+        prompt.push_str("\nDiff:\n");
+        prompt.push_str(&diff.patch);
+    }
+    if !diff.omitted.is_empty() {
+        prompt.push_str(&format!(
+            "\nNot shown to stay within the size limit: {}\n",
+            diff.omitted.join(", ")
+        ));
+    }
+    prompt
+}
+
+/// Splits an engine reply into subject and body, dropping a code fence the
+/// model added anyway.
+pub fn parse_reply(reply: &str) -> Option<GitCommitMessageDto> {
+    let lines = reply
+        .trim()
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>();
+    let start = lines.iter().position(|line| !line.trim().is_empty())?;
+    let subject = lines[start].trim().trim_matches('"').to_string();
+    let body = lines[start + 1..].join("\n").trim().to_string();
+    Some(GitCommitMessageDto { subject, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use git2::{Repository, Signature};
+    use uuid::Uuid;
+
+    use super::{build_prompt, parse_reply, staged_diff};
+    use crate::models::GitCommitMessageDto;
+
+    #[test]
+    fn staged_diff_skips_binary_and_unstaged_changes_and_respects_the_budget() {
+        let root = std::env::temp_dir().join(format!("panes-commit-message-{}", Uuid::new_v4()));
+        let repo = Repository::init(&root).expect("init repo");
+        let stage = |paths: &[&str]| {
+            let mut index = repo.index().expect("index");
+            for path in paths {
+                index.add_path(Path::new(path)).expect("add path");
+            }
+            index.write().expect("write index");
+            index.write_tree().expect("write tree")
+        };
+        fs::write(root.join("lib.rs"), "fn one() {}\n").expect("write lib");
+        let tree = repo.find_tree(stage(&["lib.rs"])).expect("tree");
+        let signature = Signature::now("Test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .expect("commit");
+
+        fs::write(root.join("lib.rs"), "fn one() {}\nfn two() {}\n").expect("edit lib");
+        fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0, 1, 2]).expect("png");
+        fs::write(root.join("notes.md"), "staged notes\n".repeat(20)).expect("notes");
+        stage(&["lib.rs", "logo.png", "notes.md"]);
+        fs::write(root.join("notes.md"), "unstaged rewrite\n").expect("unstaged edit");
+
+        let diff = staged_diff(root.to_str().unwrap(), 64 * 1024).expect("staged diff");
+        assert_eq!(
+            diff.files,
+            ["M lib.rs", "A logo.png (binary, not shown)", "A notes.md"]
+        );
+        assert!(diff.patch.contains("+fn two() {}"));
+        assert!(diff.patch.contains("+staged notes"));
+        assert!(!diff.patch.contains("unstaged rewrite"));
+        assert!(!diff.patch.contains("logo.png"));
+        assert!(diff.omitted.is_empty());
+
+        let tight = staged_diff(root.to_str().unwrap(), 200).expect("tight diff");
+        assert!(tight.patch.contains("+fn two() {}"));
+        assert_eq!(tight.omitted, ["notes.md"]);
+        let prompt = build_prompt(&tight);
+        assert!(prompt.contains("A logo.png (binary, not shown)"));
+        assert!(prompt.ends_with("Not shown to stay within the size limit: notes.md\n"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn replies_split_into_subject_and_body() {
+        assert_eq!(
+            parse_reply("```\nAdd retry to uploads\n\nUploads now retry twice.\n```\n"),
+            Some(GitCommitMessageDto {
+                subject: "Add retry to uploads".to_string(),
+                body: "Uploads now retry twice.".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_reply("\n\"Fix typo\"\n"),
+            Some(GitCommitMessageDto {
+                subject: "Fix typo".to_string(),
+                body: String::new(),
+            })
+        );
+        assert_eq!(parse_reply("```\n```"), None);
+    }
+}
@@ -1,5 +1,10 @@
 pub mod cli_fallback;
+pub mod commit_message;
 pub mod multi_repo;
+pub mod overview;
+pub mod preflight;
 pub mod repo;
+pub mod safety;
+pub mod signing;
 pub mod watcher;
 pub mod worktree;
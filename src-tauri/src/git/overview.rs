@@ -0,0 +1,338 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use super::repo;
+use crate::models::{
+    GitMultiRepoResultDto, GitRepoFilesDto, GitStatusDto, RepoDto, WorkspaceGitFileDto,
+    WorkspaceGitOverviewDto, WorkspaceGitRepoSummaryDto,
+};
+
+pub const OVERVIEW_MAX_PARALLEL_REPOS: usize = 4;
+pub const OVERVIEW_REPO_TIMEOUT: Duration = Duration::from_secs(5);
+
+type StatusReader = fn(&str) -> anyhow::Result<GitStatusDto>;
+
+/// Reads `git status` for each repo with at most
+/// [`OVERVIEW_MAX_PARALLEL_REPOS`] running at once. A repo that exceeds
+/// [`OVERVIEW_REPO_TIMEOUT`] is reported with `timed_out` and no files.
+pub async fn workspace_git_overview(
+    workspace_id: &str,
+    repos: Vec<RepoDto>,
+) -> WorkspaceGitOverviewDto {
+    collect_overview(
+        workspace_id,
+        repos,
+        OVERVIEW_MAX_PARALLEL_REPOS,
+        OVERVIEW_REPO_TIMEOUT,
+        repo::get_git_status,
+    )
+    .await
+}
+
+async fn collect_overview(
+    workspace_id: &str,
+    repos: Vec<RepoDto>,
+    max_parallel: usize,
+    timeout: Duration,
+    read_status: StatusReader,
+) -> WorkspaceGitOverviewDto {
+    let semaphore = Arc::new(Semaphore::new(max_parallel.max(1)));
+    let mut tasks = JoinSet::new();
+    for (index, repo) in repos.into_iter().enumerate() {
+        let semaphore = Arc::clone(&semaphore);
+        tasks.spawn(async move {
+            let outcome = match semaphore.acquire_owned().await {
+                Ok(permit) => {
+                    // The permit lives with the blocking read, so a repo that
+                    // times out still occupies its slot until git returns.
+                    let path = repo.path.clone();
+                    let read = tokio::task::spawn_blocking(move || {
+                        let _permit = permit;
+                        read_status(&path)
+                    });
+                    match tokio::time::timeout(timeout, read).await {
+                        Ok(Ok(result)) => RepoStatusOutcome::Read(result),
+                        Ok(Err(error)) => RepoStatusOutcome::Read(Err(anyhow::anyhow!(
+                            "status task failed: {error}"
+                        ))),
+                        Err(_) => RepoStatusOutcome::TimedOut,
+                    }
+                }
+                Err(error) => RepoStatusOutcome::Read(Err(error.into())),
+            };
+            (index, repo, outcome)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(error) => log::warn!("git overview task failed: {error}"),
+        }
+    }
+    results.sort_by_key(|(index, _, _)| *index);
+
+    let mut overview = WorkspaceGitOverviewDto {
+        workspace_id: workspace_id.to_string(),
+        repos: Vec::with_capacity(results.len()),
+        files: Vec::new(),
+        partial: false,
+    };
+    for (_, repo, outcome) in results {
+        let mut summary = WorkspaceGitRepoSummaryDto {
+            repo_id: repo.id.clone(),
+            repo_name: repo.name.clone(),
+            repo_path: repo.path.clone(),
+            branch: None,
+            ahead: None,
+            behind: None,
+            dirty_count: 0,
+            staged_count: 0,
+            timed_out: false,
+            error: None,
+        };
+        match outcome {
+            RepoStatusOutcome::Read(Ok(status)) => {
+                summary.branch = Some(status.branch);
+                summary.ahead = Some(status.ahead);
+                summary.behind = Some(status.behind);
+                for file in status.files {
+                    if file.worktree_status.is_some() {
+                        summary.dirty_count += 1;
+                    }
+                    if file
+                        .index_status
+                        .as_deref()
+                        .is_some_and(|status| status != "conflicted")
+                    {
+                        summary.staged_count += 1;
+                    }
+                    overview.files.push(WorkspaceGitFileDto {
+                        repo_id: repo.id.clone(),
+                        repo_path: repo.path.clone(),
+                        path: file.path,
+                        index_status: file.index_status,
+                        worktree_status: file.worktree_status,
+                    });
+                }
+            }
+            RepoStatusOutcome::Read(Err(error)) => {
+                summary.error = Some(error.to_string());
+                overview.partial = true;
+            }
+            RepoStatusOutcome::TimedOut => {
+                summary.timed_out = true;
+                overview.partial = true;
+            }
+        }
+        overview.repos.push(summary);
+    }
+    overview
+}
+
+enum RepoStatusOutcome {
+    Read(anyhow::Result<GitStatusDto>),
+    TimedOut,
+}
+
+/// Stages files across repos; a failing repo does not stop the others.
+pub fn stage_files_multi(entries: &[GitRepoFilesDto]) -> Vec<GitMultiRepoResultDto> {
+    entries
+        .iter()
+        .map(|entry| {
+            repo_result(
+                &entry.repo_path,
+                repo::stage_files(&entry.repo_path, &entry.files).map(|_| None),
+            )
+        })
+        .collect()
+}
+
+/// Creates one commit per repo with the same message. Repos are committed
+/// independently, so earlier commits stay in place when a later repo fails.
+pub fn commit_multi(repo_paths: &[String], message: &str) -> Vec<GitMultiRepoResultDto> {
+    repo_paths
+        .iter()
+        .map(|repo_path| {
+            repo_result(
+                repo_path,
+                repo::commit(repo_path, message, repo::CommitOptions::default())
+                    .map(|result| Some(result.hash)),
+            )
+        })
+        .collect()
+}
+
+fn repo_result(repo_path: &str, result: anyhow::Result<Option<String>>) -> GitMultiRepoResultDto {
+    match result {
+        Ok(commit_hash) => GitMultiRepoResultDto {
+            repo_path: repo_path.to_string(),
+            ok: true,
+            commit_hash,
+            error: None,
+        },
+        Err(error) => GitMultiRepoResultDto {
+            repo_path: repo_path.to_string(),
+            ok: false,
+            commit_hash: None,
+            error: Some(error.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{git::cli_fallback::run_git, models::TrustLevelDto};
+
+    fn init_repo(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("panes-overview-{name}-{}", Uuid::new_v4()));
+        fs::create_dir_all(&path).expect("create repo dir");
+        let path_str = path.to_str().expect("utf-8 temp path");
+        run_git(path_str, &["init", "--initial-branch=main"]).expect("git init");
+        run_git(path_str, &["config", "user.email", "test@example.com"]).expect("config email");
+        run_git(path_str, &["config", "user.name", "Test"]).expect("config name");
+        fs::write(path.join("README.md"), "readme\n").expect("write readme");
+        run_git(path_str, &["add", "README.md"]).expect("add readme");
+        run_git(path_str, &["commit", "-m", "init"]).expect("initial commit");
+        path
+    }
+
+    fn repo_dto(name: &str, path: &str) -> RepoDto {
+        RepoDto {
+            id: format!("repo-{name}"),
+            workspace_id: "ws".to_string(),
+            name: name.to_string(),
+            path: path.to_string(),
+            default_branch: "main".to_string(),
+            is_active: true,
+            trust_level: TrustLevelDto::Standard,
+            detected_configs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn overview_aggregates_files_across_repos() {
+        let _env_guard = crate::process_utils::test_env_lock()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let api = init_repo("api");
+        let web = init_repo("web");
+        fs::write(api.join("README.md"), "changed\n").expect("modify api readme");
+        fs::write(web.join("staged.txt"), "new\n").expect("write web file");
+        run_git(web.to_str().unwrap(), &["add", "staged.txt"]).expect("stage web file");
+        fs::write(web.join("untracked.txt"), "new\n").expect("write untracked file");
+
+        let runtime = tokio::runtime::Runtime::new().expect("runtime should build");
+        let overview = runtime.block_on(workspace_git_overview(
+            "ws",
+            vec![
+                repo_dto("api", api.to_str().unwrap()),
+                repo_dto("web", web.to_str().unwrap()),
+            ],
+        ));
+
+        assert!(!overview.partial);
+        assert_eq!(overview.repos.len(), 2);
+        assert_eq!(overview.repos[0].repo_name, "api");
+        assert_eq!(overview.repos[0].branch.as_deref(), Some("main"));
+        assert_eq!(overview.repos[0].dirty_count, 1);
+        assert_eq!(overview.repos[0].staged_count, 0);
+        assert_eq!(overview.repos[1].dirty_count, 1);
+        assert_eq!(overview.repos[1].staged_count, 1);
+
+        let mut tagged = overview
+            .files
+            .iter()
+            .map(|file| (file.repo_id.as_str(), file.path.as_str()))
+            .collect::<Vec<_>>();
+        tagged.sort();
+        assert_eq!(
+            tagged,
+            vec![
+                ("repo-api", "README.md"),
+                ("repo-web", "staged.txt"),
+                ("repo-web", "untracked.txt"),
+            ]
+        );
+        let _ = fs::remove_dir_all(api);
+        let _ = fs::remove_dir_all(web);
+    }
+
+    #[tokio::test]
+    async fn slow_repo_is_flagged_without_blocking_the_rest() {
+        fn fake_status(path: &str) -> anyhow::Result<GitStatusDto> {
+            if path.contains("slow") {
+                std::thread::sleep(Duration::from_millis(500));
+            }
+            Ok(GitStatusDto {
+                branch: "main".to_string(),
+                files: Vec::new(),
+                upstream_branch: None,
+                ahead: 0,
+                behind: 0,
+                detached_head: false,
+                conflicts: Vec::new(),
+            })
+        }
+
+        let overview = collect_overview(
+            "ws",
+            vec![
+                repo_dto("fast", "/repos/fast"),
+                repo_dto("slow", "/repos/slow"),
+            ],
+            2,
+            Duration::from_millis(50),
+            fake_status,
+        )
+        .await;
+
+        assert!(overview.partial);
+        assert!(!overview.repos[0].timed_out);
+        assert_eq!(overview.repos[0].branch.as_deref(), Some("main"));
+        assert!(overview.repos[1].timed_out);
+        assert_eq!(overview.repos[1].branch, None);
+    }
+
+    #[test]
+    fn commit_multi_reports_partial_failures_per_repo() {
+        let _env_guard = crate::process_utils::test_env_lock()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let staged = init_repo("staged");
+        let clean = init_repo("clean");
+        fs::write(staged.join("feature.txt"), "feature\n").expect("write feature");
+
+        let staging = stage_files_multi(&[GitRepoFilesDto {
+            repo_path: staged.to_string_lossy().to_string(),
+            files: vec!["feature.txt".to_string()],
+        }]);
+        assert!(staging[0].ok, "{:?}", staging[0].error);
+
+        let results = commit_multi(
+            &[
+                staged.to_string_lossy().to_string(),
+                clean.to_string_lossy().to_string(),
+            ],
+            "Add feature",
+        );
+
+        assert!(results[0].ok);
+        assert_eq!(results[0].commit_hash.as_ref().map(String::len), Some(40));
+        assert!(!results[1].ok);
+        assert!(results[1].commit_hash.is_none());
+        assert!(results[1].error.is_some());
+        let log =
+            run_git(staged.to_str().unwrap(), &["log", "-1", "--format=%s"]).expect("read log");
+        assert_eq!(log.trim(), "Add feature");
+        let _ = fs::remove_dir_all(staged);
+        let _ = fs::remove_dir_all(clean);
+    }
+}
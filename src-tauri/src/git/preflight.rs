@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Mutex, OnceLock},
+};
+
+use git2::Repository;
+
+use crate::{models::GitPreflightDto, process_utils, runtime_env};
+
+/// Returned when an operation shells out to git but no `git` executable can be
+/// resolved. Callers can downcast the `anyhow::Error` to surface the install hint
+/// instead of a raw spawn failure.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("git binary required for `{operation}` but no git executable was found. {install_hint}")]
+pub struct GitBinaryRequiredError {
+    pub operation: String,
+    pub install_hint: &'static str,
+}
+
+impl GitBinaryRequiredError {
+    pub fn new(operation: impl Into<String>) -> Self {
+        Self {
+            operation: operation.into(),
+            install_hint: git_install_hint(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitBinaryInfo {
+    pub path: PathBuf,
+    pub version: Option<String>,
+}
+
+type GitBinaryResolver = fn() -> Option<GitBinaryInfo>;
+
+/// Session-wide cache of git binary resolution and per-repo preflight reports.
+/// Resolution runs once per app session; `refresh` forces a new probe after the
+/// user installs git or changes repo config.
+pub struct GitPreflightCache {
+    resolver: GitBinaryResolver,
+    binary: Mutex<Option<Option<GitBinaryInfo>>>,
+    reports: Mutex<HashMap<String, GitPreflightDto>>,
+}
+
+impl GitPreflightCache {
+    pub fn global() -> &'static Self {
+        static CACHE: OnceLock<GitPreflightCache> = OnceLock::new();
+        CACHE.get_or_init(|| Self::with_resolver(resolve_git_binary))
+    }
+
+    pub fn with_resolver(resolver: GitBinaryResolver) -> Self {
+        Self {
+            resolver,
+            binary: Mutex::new(None),
+            reports: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn binary(&self) -> Option<GitBinaryInfo> {
+        let mut binary = self
+            .binary
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        binary.get_or_insert_with(self.resolver).clone()
+    }
+
+    /// Resolves the git executable, returning a typed `GitBinaryRequiredError`
+    /// when it is missing.
+    pub fn require_binary(&self, operation: &str) -> anyhow::Result<GitBinaryInfo> {
+        self.binary()
+            .ok_or_else(|| GitBinaryRequiredError::new(operation).into())
+    }
+
+    /// Drops the cached binary so the next call probes again. Used when a
+    /// previously resolved executable disappears mid-session.
+    pub fn invalidate_binary(&self) {
+        *self
+            .binary
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+
+    pub fn report(&self, repo_path: &str, refresh: bool) -> GitPreflightDto {
+        if refresh {
+            self.invalidate_binary();
+        } else if let Some(report) = self
+            .reports
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(repo_path)
+        {
+            return report.clone();
+        }
+
+        let report = build_preflight_report(repo_path, self.binary());
+        self.reports
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(repo_path.to_string(), report.clone());
+        report
+    }
+}
+
+fn resolve_git_binary() -> Option<GitBinaryInfo> {
+    let path = runtime_env::resolve_executable("git")?;
+    let mut command = Command::new(&path);
+    process_utils::configure_std_command(&mut command);
+    let version = command
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| parse_git_version(&String::from_utf8_lossy(&output.stdout)));
+    Some(GitBinaryInfo { path, version })
+}
+
+fn parse_git_version(output: &str) -> Option<String> {
+    let trimmed = output.trim();
+    let version = trimmed.strip_prefix("git version ").unwrap_or(trimmed);
+    if version.is_empty() {
+        None
+    } else {
+        Some(version.to_string())
+    }
+}
+
+fn git_install_hint() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Install it with `xcode-select --install` or `brew install git`, then refresh."
+    } else if cfg!(target_os = "windows") {
+        "Install Git for Windows from https://git-scm.com/download/win, then refresh."
+    } else {
+        "Install it with your package manager (for example `sudo apt install git` or `sudo dnf install git`), then refresh."
+    }
+}
+
+fn build_preflight_report(repo_path: &str, binary: Option<GitBinaryInfo>) -> GitPreflightDto {
+    let libgit2 = git2::Version::get();
+    let (major, minor, patch) = libgit2.libgit2_version();
+    let repo = Repository::discover(repo_path).ok();
+    let config = match repo.as_ref() {
+        Some(repo) => repo.config().ok(),
+        None => git2::Config::open_default().ok(),
+    };
+    let config_string = |key: &str| {
+        config
+            .as_ref()
+            .and_then(|config| config.get_string(key).ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let credential_helper = config_string("credential.helper");
+    let signs_commits = config
+        .as_ref()
+        .and_then(|config| config.get_bool("commit.gpgsign").ok())
+        .unwrap_or(false);
+    let signing_program = if signs_commits {
+        match config_string("gpg.format").as_deref() {
+            Some("ssh") => config_string("gpg.ssh.program").or(Some("ssh-keygen".to_string())),
+            Some("x509") => config_string("gpg.x509.program").or(Some("gpgsm".to_string())),
+            _ => config_string("gpg.program").or(Some("gpg".to_string())),
+        }
+    } else {
+        None
+    };
+    let uses_lfs = repo
+        .as_ref()
+        .and_then(|repo| repo.workdir())
+        .is_some_and(repo_uses_lfs);
+
+    let mut binary_required_features = Vec::new();
+    if uses_lfs {
+        binary_required_features.push("lfs".to_string());
+    }
+    if signs_commits {
+        binary_required_features.push("commit_signing".to_string());
+    }
+    if repo.as_ref().is_some_and(repo_has_active_hooks) {
+        binary_required_features.push("hooks".to_string());
+    }
+
+    GitPreflightDto {
+        repo_path: repo_path.to_string(),
+        binary_found: binary.is_some(),
+        binary_path: binary
+            .as_ref()
+            .map(|binary| binary.path.display().to_string()),
+        install_hint: binary.is_none().then(|| git_install_hint().to_string()),
+        binary_version: binary.and_then(|binary| binary.version),
+        libgit2_version: format!("{major}.{minor}.{patch}"),
+        libgit2_https: libgit2.https(),
+        libgit2_ssh: libgit2.ssh(),
+        credential_helper,
+        uses_lfs,
+        signs_commits,
+        signing_program,
+        binary_required_features,
+    }
+}
+
+fn repo_uses_lfs(workdir: &Path) -> bool {
+    std::fs::read_to_string(workdir.join(".gitattributes"))
+        .map(|content| content.contains("filter=lfs"))
+        .unwrap_or(false)
+}
+
+fn repo_has_active_hooks(repo: &Repository) -> bool {
+    let Ok(entries) = std::fs::read_dir(repo.path().join("hooks")) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        !name.ends_with(".sample") && entry.path().is_file()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_git_version, GitBinaryInfo, GitBinaryRequiredError, GitPreflightCache};
+    use crate::git::cli_fallback::run_git_with;
+
+    fn missing_git() -> Option<GitBinaryInfo> {
+        None
+    }
+
+    fn fake_git() -> Option<GitBinaryInfo> {
+        Some(GitBinaryInfo {
+            path: "/opt/fake/git".into(),
+            version: Some("2.45.0".to_string()),
+        })
+    }
+
+    #[test]
+    fn binary_requiring_path_returns_typed_error_when_git_is_missing() {
+        let cache = GitPreflightCache::with_resolver(missing_git);
+        let error = run_git_with(&cache, ".", &["worktree", "list", "--porcelain"])
+            .expect_err("missing git must fail before spawning");
+
+        let typed = error
+            .downcast_ref::<GitBinaryRequiredError>()
+            .expect("error should be GitBinaryRequiredError");
+        assert_eq!(typed.operation, "worktree");
+        assert!(!typed.install_hint.is_empty());
+    }
+
+    #[test]
+    fn report_reflects_mocked_binary_resolution() {
+        let cache = GitPreflightCache::with_resolver(fake_git);
+        let dir = std::env::temp_dir();
+        let path = dir.to_str().expect("utf-8 temp dir");
+
+        let report = cache.report(path, false);
+        assert!(report.binary_found);
+        assert_eq!(report.binary_path.as_deref(), Some("/opt/fake/git"));
+        assert_eq!(report.binary_version.as_deref(), Some("2.45.0"));
+
+        let missing = GitPreflightCache::with_resolver(missing_git);
+        let report = missing.report(path, false);
+        assert!(!report.binary_found);
+        assert!(report.install_hint.is_some());
+    }
+
+    #[test]
+    fn parses_git_version_output() {
+        assert_eq!(
+            parse_git_version("git version 2.43.0\n").as_deref(),
+            Some("2.43.0")
+        );
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-146)").as_deref(),
+            Some("2.39.3 (Apple Git-146)")
+        );
+        assert_eq!(parse_git_version("  "), None);
+    }
+}
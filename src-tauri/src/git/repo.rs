@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
@@ -9,15 +9,25 @@ use std::{
 
 use anyhow::Context;
 use git2::{ErrorCode, ObjectType, Repository, Status, StatusOptions};
+use sha2::{Digest, Sha256};
 
 use crate::models::{
-    FileTreeEntryDto, FileTreePageDto, GitBranchDto, GitBranchPageDto, GitBranchScopeDto,
-    GitChangeTypeDto, GitCommitDto, GitCommitPageDto, GitCompareSourceDto, GitDiffPreviewDto,
-    GitFileCompareDto, GitFileStatusDto, GitInitRepoStatusDto, GitStashDto, GitStatusDto,
+    FileTreeEntryDto, FileTreeGitStatusDto, FileTreePageDto, GitBranchDto, GitBranchPageDto,
+    GitBranchScopeDto, GitChangeTypeDto, GitCommitDto, GitCommitPageDto, GitCommitResultDto,
+    GitCompareSourceDto, GitConflictDto, GitConflictKindDto, GitConflictResolutionDto,
+    GitDiffHunkDto, GitDiffPreviewDto, GitFileCompareDto, GitFileStatusDto, GitInitRepoStatusDto,
+    GitStashApplyResultDto, GitStashDto, GitStatusDto,
+};
+use crate::{fs_ops, path_utils};
+
+use super::{
+    cli_fallback::{run_git, run_git_accepting, run_git_bytes},
+    safety::{
+        self, DirtyWorktreeError, ForcePushLeaseError, PublishedHistoryError, PushedCommitError,
+        UnmergedBranchError,
+    },
+    signing::SigningConfig,
 };
-use crate::path_utils;
-
-use super::cli_fallback::run_git;
 
 const FILE_TREE_DEFAULT_PAGE_SIZE: usize = 2000;
 const FILE_TREE_MAX_PAGE_SIZE: usize = 5000;
@@ -150,22 +160,30 @@ fn get_git_status_via_cli(repo_path: &str) -> anyhow::Result<GitStatusDto> {
 }
 
 fn parse_porcelain_v1_status(output: &str) -> anyhow::Result<GitStatusDto> {
-    let mut branch = "detached".to_string();
-    let mut ahead = 0usize;
-    let mut behind = 0usize;
+    let mut header = PorcelainBranchHeader {
+        branch: "detached".to_string(),
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        detached: true,
+    };
     let mut files = Vec::new();
+    let mut conflicts = Vec::new();
     let mut records = output.split('\0').filter(|record| !record.is_empty());
 
     while let Some(record) = records.next() {
-        if let Some(header) = record.strip_prefix("## ") {
-            let parsed = parse_porcelain_branch_header(header);
-            branch = parsed.0;
-            ahead = parsed.1;
-            behind = parsed.2;
+        if let Some(branch_header) = record.strip_prefix("## ") {
+            header = parse_porcelain_branch_header(branch_header);
             continue;
         }
 
         if let Some((file, consumes_next_path)) = parse_porcelain_status_record(record) {
+            if let Some(kind) = porcelain_conflict_kind(record) {
+                conflicts.push(GitConflictDto {
+                    path: file.path.clone(),
+                    kind,
+                });
+            }
             files.push(file);
             if consumes_next_path {
                 let _ = records.next();
@@ -174,16 +192,30 @@ fn parse_porcelain_v1_status(output: &str) -> anyhow::Result<GitStatusDto> {
     }
 
     files.sort_by(|a, b| a.path.cmp(&b.path));
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
 
     Ok(GitStatusDto {
-        branch,
+        branch: header.branch,
         files,
-        ahead,
-        behind,
+        upstream_branch: header.upstream,
+        ahead: header.ahead,
+        behind: header.behind,
+        detached_head: header.detached,
+        conflicts,
     })
 }
 
-fn parse_porcelain_branch_header(header: &str) -> (String, usize, usize) {
+/// The `## ...` line of `git status --porcelain=v1 -b`.
+struct PorcelainBranchHeader {
+    branch: String,
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    detached: bool,
+}
+
+fn parse_porcelain_branch_header(header: &str) -> PorcelainBranchHeader {
+    let detached = header.starts_with("HEAD ");
     let branch = if let Some(branch) = header.strip_prefix("No commits yet on ") {
         branch
     } else if let Some(branch) = header.strip_prefix("Initial commit on ") {
@@ -205,8 +237,17 @@ fn parse_porcelain_branch_header(header: &str) -> (String, usize, usize) {
                 .map(|(track, _)| parse_upstream_track(track))
         })
         .unwrap_or((0, 0));
+    let upstream = header
+        .split_once("...")
+        .and_then(|(_, rest)| non_empty_string(rest.split(" [").next().unwrap_or(rest)));
 
-    (branch.trim().to_string(), ahead, behind)
+    PorcelainBranchHeader {
+        branch: branch.trim().to_string(),
+        upstream,
+        ahead,
+        behind,
+        detached,
+    }
 }
 
 fn parse_porcelain_status_record(record: &str) -> Option<(GitFileStatusDto, bool)> {
@@ -256,6 +297,21 @@ fn is_porcelain_conflicted(index_code: char, worktree_code: char) -> bool {
         || worktree_code == 'U'
 }
 
+/// The kind of conflict behind an unmerged porcelain record's `XY` codes.
+fn porcelain_conflict_kind(record: &str) -> Option<GitConflictKindDto> {
+    let mut chars = record.chars();
+    match (chars.next()?, chars.next()?) {
+        ('U', 'U') => Some(GitConflictKindDto::BothModified),
+        ('A', 'A') => Some(GitConflictKindDto::BothAdded),
+        ('D', 'D') => Some(GitConflictKindDto::BothDeleted),
+        ('A', 'U') => Some(GitConflictKindDto::AddedByUs),
+        ('U', 'A') => Some(GitConflictKindDto::AddedByThem),
+        ('D', 'U') => Some(GitConflictKindDto::DeletedByUs),
+        ('U', 'D') => Some(GitConflictKindDto::DeletedByThem),
+        _ => None,
+    }
+}
+
 fn porcelain_index_status_label(code: char) -> Option<String> {
     match code {
         'A' => Some("added".to_string()),
@@ -286,7 +342,8 @@ fn get_git_status_via_git2(repo_path: &str) -> anyhow::Result<GitStatusDto> {
         .and_then(|head| head.shorthand().map(ToOwned::to_owned))
         .unwrap_or_else(|| "detached".to_string());
 
-    let (ahead, behind) = resolve_branch_ahead_behind(&repo);
+    let (upstream_branch, ahead, behind) = resolve_branch_tracking(&repo);
+    let detached_head = repo.head_detached().unwrap_or(false);
 
     let mut options = StatusOptions::new();
     options
@@ -321,12 +378,19 @@ fn get_git_status_via_git2(repo_path: &str) -> anyhow::Result<GitStatusDto> {
     }
 
     files.sort_by(|a, b| a.path.cmp(&b.path));
+    let conflicts = index_conflicts(&repo)?
+        .iter()
+        .map(IndexConflictStages::to_dto)
+        .collect();
 
     Ok(GitStatusDto {
         branch,
         files,
+        upstream_branch,
         ahead,
         behind,
+        detached_head,
+        conflicts,
     })
 }
 
@@ -335,15 +399,360 @@ pub fn get_file_diff(
     file_path: &str,
     staged: bool,
 ) -> anyhow::Result<GitDiffPreviewDto> {
-    let mut args = vec!["diff"];
+    let raw = file_diff_bytes(repo_path, file_path, staged)?;
+    let hunks = diff_hunks(&raw)
+        .into_iter()
+        .map(|hunk| GitDiffHunkDto {
+            id: hunk.id,
+            index: hunk.index,
+            header: hunk.header,
+        })
+        .collect();
+    Ok(GitDiffPreviewDto {
+        hunks,
+        ..build_diff_preview(String::from_utf8_lossy(&raw).into_owned())
+    })
+}
+
+/// `git diff` arguments behind [`get_file_diff`]. A staged rename is diffed
+/// against its source, so its hunks are the edits made on top of the move.
+fn file_diff_args(repo_path: &str, file_path: &str, staged: bool) -> Vec<String> {
+    let mut args = vec!["diff".to_string()];
     if staged {
-        args.push("--staged");
+        args.push("--staged".to_string());
+    }
+    let renamed_from = if staged {
+        staged_rename_counterpart(repo_path, file_path)
+    } else {
+        None
+    };
+    if renamed_from.is_some() {
+        args.push("-M".to_string());
     }
-    args.push("--");
-    args.push(file_path);
+    args.push("--".to_string());
+    args.push(file_path.to_string());
+    args.extend(renamed_from);
+    args
+}
 
-    let raw = run_git(repo_path, &args)?;
-    Ok(build_diff_preview(raw))
+/// The other side of a staged rename involving `file_path`, if there is one.
+fn staged_rename_counterpart(repo_path: &str, file_path: &str) -> Option<String> {
+    let repo = Repository::open(repo_path).ok()?;
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).renames_head_to_index(true);
+    let statuses = repo.statuses(Some(&mut options)).ok()?;
+    statuses.iter().find_map(|entry| {
+        let delta = entry.head_to_index()?;
+        if delta.status() != git2::Delta::Renamed {
+            return None;
+        }
+        let old_path = delta.old_file().path()?.to_str()?;
+        let new_path = delta.new_file().path()?.to_str()?;
+        if new_path == file_path {
+            Some(old_path.to_string())
+        } else if old_path == file_path {
+            Some(new_path.to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// A hunk picked in an older diff of `path` that the current diff no longer
+/// has: the file changed on disk or in the index since the diff was shown.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("the diff of {path} changed since it was shown; refresh it and pick the hunks again")]
+pub struct StaleHunkError {
+    pub path: String,
+}
+
+/// Stages the hunk of `file_path`'s unstaged diff whose `@@` header (as
+/// shown by [`get_file_diff`]) matches `hunk_header`.
+pub fn stage_hunk(
+    repo_path: &str,
+    file_path: &str,
+    hunk_header: &str,
+) -> anyhow::Result<GitStatusDto> {
+    stage_file_hunks(repo_path, file_path, HunkSelection::Header(hunk_header))
+}
+
+/// Unstages the hunk of `file_path`'s staged diff whose `@@` header matches
+/// `hunk_header`, leaving the working tree alone.
+pub fn unstage_hunk(
+    repo_path: &str,
+    file_path: &str,
+    hunk_header: &str,
+) -> anyhow::Result<GitStatusDto> {
+    apply_file_hunks(
+        repo_path,
+        file_path,
+        HunkSelection::Header(hunk_header),
+        true,
+    )?;
+    get_git_status(repo_path)
+}
+
+/// Stages the hunks of `file_path`'s unstaged diff named by `hunk_ids`, the
+/// ids [`get_file_diff`] returned. Ids that are no longer in the diff fail
+/// the whole call with [`StaleHunkError`].
+pub fn stage_hunks(
+    repo_path: &str,
+    file_path: &str,
+    hunk_ids: &[String],
+) -> anyhow::Result<GitStatusDto> {
+    stage_file_hunks(repo_path, file_path, HunkSelection::Ids(hunk_ids))
+}
+
+/// Unstages the hunks of `file_path`'s staged diff named by `hunk_ids`,
+/// leaving the working tree alone.
+pub fn unstage_hunks(
+    repo_path: &str,
+    file_path: &str,
+    hunk_ids: &[String],
+) -> anyhow::Result<GitStatusDto> {
+    apply_file_hunks(repo_path, file_path, HunkSelection::Ids(hunk_ids), true)?;
+    get_git_status(repo_path)
+}
+
+/// Hunks to cut out of a file's diff.
+#[derive(Clone, Copy)]
+enum HunkSelection<'a> {
+    /// The hunk whose `@@` line has these ranges.
+    Header(&'a str),
+    /// Hunks by [`DiffHunk::id`].
+    Ids(&'a [String]),
+}
+
+/// An untracked file is marked intent-to-add first so its content shows up
+/// as a hunk, and unmarked again if staging fails.
+fn stage_file_hunks(
+    repo_path: &str,
+    file_path: &str,
+    selection: HunkSelection<'_>,
+) -> anyhow::Result<GitStatusDto> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let untracked = repo
+        .status_file(Path::new(file_path))
+        .is_ok_and(|status| status.contains(Status::WT_NEW));
+    if untracked {
+        run_git(repo_path, &["add", "--intent-to-add", "--", file_path])?;
+    }
+    if let Err(error) = apply_file_hunks(repo_path, file_path, selection, false) {
+        if untracked {
+            let _ = run_git(repo_path, &["rm", "--cached", "--quiet", "--", file_path]);
+        }
+        return Err(error);
+    }
+    get_git_status(repo_path)
+}
+
+fn apply_file_hunks(
+    repo_path: &str,
+    file_path: &str,
+    selection: HunkSelection<'_>,
+    staged: bool,
+) -> anyhow::Result<()> {
+    let diff = file_diff_bytes(repo_path, file_path, staged)?;
+    let patch = match selection {
+        HunkSelection::Header(hunk_header) => {
+            single_hunk_patch(&diff, hunk_header).with_context(|| {
+                format!(
+                    "hunk {} is no longer in the diff of {file_path}",
+                    hunk_header.trim()
+                )
+            })?
+        }
+        HunkSelection::Ids(hunk_ids) => {
+            if hunk_ids.is_empty() {
+                anyhow::bail!("no hunks selected");
+            }
+            let hunks = diff_hunks(&diff);
+            let mut selected = Vec::with_capacity(hunk_ids.len());
+            for hunk_id in hunk_ids {
+                let Some(hunk) = hunks.iter().find(|hunk| &hunk.id == hunk_id) else {
+                    return Err(StaleHunkError {
+                        path: file_path.to_string(),
+                    }
+                    .into());
+                };
+                selected.push(hunk);
+            }
+            hunks_patch(&diff, &selected)?
+        }
+    };
+    let mut apply_args = vec!["apply", "--cached"];
+    if staged {
+        apply_args.push("--reverse");
+    }
+    apply_args.push("-");
+    run_git_bytes(repo_path, &apply_args, Some(&patch))?;
+    Ok(())
+}
+
+/// Raw `git diff` of one file, the input for both the preview and patches
+/// built from its hunks.
+fn file_diff_bytes(repo_path: &str, file_path: &str, staged: bool) -> anyhow::Result<Vec<u8>> {
+    let mut diff_args = file_diff_args(repo_path, file_path, staged);
+    diff_args.splice(
+        1..1,
+        ["--no-color".to_string(), "--no-ext-diff".to_string()],
+    );
+    run_git_bytes(
+        repo_path,
+        &diff_args.iter().map(String::as_str).collect::<Vec<_>>(),
+        None,
+    )
+}
+
+/// One `@@` hunk of a one-file diff.
+struct DiffHunk {
+    /// `{index}:{digest}`, where the digest covers the hunk's header and
+    /// lines, so the id stops matching once the file changes under it.
+    id: String,
+    index: usize,
+    header: String,
+    /// Line range of the hunk, header included, in the diff's lines.
+    lines: std::ops::Range<usize>,
+}
+
+fn diff_lines(diff: &[u8]) -> Vec<&[u8]> {
+    diff.split_inclusive(|byte| *byte == b'\n').collect()
+}
+
+fn diff_hunks(diff: &[u8]) -> Vec<DiffHunk> {
+    let lines = diff_lines(diff);
+    let mut hunks = Vec::new();
+    let mut cursor = 0;
+    while let Some(offset) = lines[cursor..]
+        .iter()
+        .position(|line| line.starts_with(b"@@ "))
+    {
+        let start = cursor + offset;
+        let end = lines[start + 1..]
+            .iter()
+            .position(|line| line.starts_with(b"@@ ") || line.starts_with(b"diff --git "))
+            .map_or(lines.len(), |offset| start + 1 + offset);
+        let mut hasher = Sha256::new();
+        for line in &lines[start..end] {
+            hasher.update(line);
+        }
+        let digest = hasher
+            .finalize()
+            .iter()
+            .take(6)
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        let index = hunks.len();
+        hunks.push(DiffHunk {
+            id: format!("{index}:{digest}"),
+            index,
+            header: String::from_utf8_lossy(lines[start]).trim_end().to_string(),
+            lines: start..end,
+        });
+        cursor = end;
+    }
+    hunks
+}
+
+/// Cuts the hunk matching `hunk_header`'s line ranges out of a one-file
+/// `git diff`; see [`hunks_patch`].
+fn single_hunk_patch(diff: &[u8], hunk_header: &str) -> anyhow::Result<Vec<u8>> {
+    let wanted = parse_hunk_ranges(hunk_header.as_bytes())
+        .with_context(|| format!("invalid hunk header: {hunk_header}"))?;
+    let hunks = diff_hunks(diff);
+    anyhow::ensure!(!hunks.is_empty(), "diff has no hunks");
+    let hunk = hunks
+        .iter()
+        .find(|hunk| parse_hunk_ranges(hunk.header.as_bytes()) == Some(wanted))
+        .context("hunk not found")?;
+    hunks_patch(diff, &[hunk])
+}
+
+/// Builds a patch of `hunks` out of a one-file `git diff`, keeping the file
+/// header and the hunks' own line endings and "\ No newline" markers. A
+/// rename header becomes a plain edit of the new path so that applying the
+/// hunks never undoes the rename itself.
+fn hunks_patch(diff: &[u8], hunks: &[&DiffHunk]) -> anyhow::Result<Vec<u8>> {
+    let lines = diff_lines(diff);
+    let first_hunk = lines
+        .iter()
+        .position(|line| line.starts_with(b"@@ "))
+        .context("diff has no hunks")?;
+
+    let header = &lines[..first_hunk];
+    let renamed = header
+        .iter()
+        .any(|line| line.starts_with(b"rename from ") || line.starts_with(b"copy from "));
+    let mut patch = Vec::with_capacity(diff.len());
+    if renamed {
+        let new_path = header
+            .iter()
+            .find_map(|line| line.strip_prefix(b"+++ "))
+            .context("rename diff has no new path")?;
+        let new_path = new_path.trim_ascii_end();
+        let old_path = rename_side(new_path, b"b/", b"a/");
+        patch.extend_from_slice(b"diff --git ");
+        patch.extend_from_slice(&old_path);
+        patch.push(b' ');
+        patch.extend_from_slice(new_path);
+        patch.extend_from_slice(b"\n--- ");
+        patch.extend_from_slice(&old_path);
+        patch.extend_from_slice(b"\n+++ ");
+        patch.extend_from_slice(new_path);
+        patch.push(b'\n');
+    } else {
+        for line in header {
+            patch.extend_from_slice(line);
+        }
+    }
+    let mut hunks = hunks.to_vec();
+    hunks.sort_by_key(|hunk| hunk.index);
+    hunks.dedup_by_key(|hunk| hunk.index);
+    for hunk in hunks {
+        for line in &lines[hunk.lines.clone()] {
+            patch.extend_from_slice(line);
+        }
+        if !patch.ends_with(b"\n") {
+            patch.push(b'\n');
+        }
+    }
+    Ok(patch)
+}
+
+/// Swaps the `b/` prefix of a diff path for `a/`, inside quotes if git quoted it.
+fn rename_side(path: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let quoted = path.first() == Some(&b'"');
+    let body = if quoted { &path[1..] } else { path };
+    let mut side = Vec::with_capacity(path.len());
+    if quoted {
+        side.push(b'"');
+    }
+    match body.strip_prefix(from) {
+        Some(rest) => {
+            side.extend_from_slice(to);
+            side.extend_from_slice(rest);
+        }
+        None => side.extend_from_slice(body),
+    }
+    side
+}
+
+/// `(old_start, old_lines, new_start, new_lines)` from an `@@ -a,b +c,d @@`
+/// line; an omitted count is 1.
+fn parse_hunk_ranges(line: &[u8]) -> Option<(u32, u32, u32, u32)> {
+    let line = std::str::from_utf8(line.strip_prefix(b"@@ ")?).ok()?;
+    let (ranges, _) = line.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    let parse = |range: &str, sign: char| -> Option<(u32, u32)> {
+        let range = range.strip_prefix(sign)?;
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = parse(old, '-')?;
+    let (new_start, new_lines) = parse(new, '+')?;
+    Some((old_start, old_lines, new_start, new_lines))
 }
 
 pub fn get_git_file_compare(
@@ -467,10 +876,116 @@ pub fn discard_files(repo_path: &str, files: &[String]) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn commit(repo_path: &str, message: &str) -> anyhow::Result<String> {
-    run_git(repo_path, &["commit", "-m", message])?;
-    let hash = run_git(repo_path, &["rev-parse", "HEAD"])?;
-    Ok(hash.trim().to_string())
+/// How [`commit`] records the staged changes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitOptions {
+    /// Overrides `commit.gpgsign`.
+    pub sign: Option<bool>,
+    /// Replace HEAD instead of adding a commit; an empty message keeps
+    /// HEAD's message.
+    pub amend: bool,
+    /// Append a `Signed-off-by` trailer for the configured user.
+    pub signoff: bool,
+    /// Amend even when HEAD is already on the branch's upstream.
+    pub force: bool,
+}
+
+/// Commits the staged changes and returns the new commit with its stats. A
+/// signing failure comes back as a
+/// [`CommitSigningError`](super::signing::CommitSigningError), and amending
+/// a pushed commit without `force` as [`PushedCommitError`].
+pub fn commit(
+    repo_path: &str,
+    message: &str,
+    options: CommitOptions,
+) -> anyhow::Result<GitCommitResultDto> {
+    let signing = SigningConfig::read(repo_path)?;
+    let sign = options.sign.unwrap_or(signing.sign_by_default);
+    if sign {
+        signing.ensure_usable()?;
+    }
+    if options.amend && !options.force {
+        ensure_head_unpushed(repo_path)?;
+    }
+
+    let sign_flag = if sign { "--gpg-sign" } else { "--no-gpg-sign" };
+    let mut args = vec!["commit", sign_flag];
+    if options.amend {
+        args.push("--amend");
+    }
+    if options.signoff {
+        args.push("--signoff");
+    }
+    if options.amend && message.trim().is_empty() {
+        args.push("--no-edit");
+    } else {
+        args.extend(["-m", message]);
+    }
+    if let Err(error) = run_git(repo_path, &args) {
+        return Err(match signing.classify_failure(&error).filter(|_| sign) {
+            Some(signing_error) => signing_error.into(),
+            None => error,
+        });
+    }
+    head_commit_result(repo_path)
+}
+
+/// Fails with [`PushedCommitError`] when the current branch's upstream
+/// already contains HEAD.
+fn ensure_head_unpushed(repo_path: &str) -> anyhow::Result<()> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let Some(branch) = current_branch_name(&repo) else {
+        return Ok(());
+    };
+    let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) else {
+        return Ok(());
+    };
+    let Some(upstream) = repo
+        .find_branch(&branch, git2::BranchType::Local)
+        .ok()
+        .and_then(|branch| branch.upstream().ok())
+    else {
+        return Ok(());
+    };
+    let Some(upstream_tip) = upstream.get().target() else {
+        return Ok(());
+    };
+    if upstream_tip == head.id() || repo.graph_descendant_of(upstream_tip, head.id())? {
+        return Err(PushedCommitError {
+            commit: short_commit_id(head.id()),
+            upstream: upstream
+                .name()
+                .ok()
+                .flatten()
+                .unwrap_or("the upstream")
+                .to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn head_commit_result(repo_path: &str) -> anyhow::Result<GitCommitResultDto> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("failed to resolve HEAD commit")?;
+    let parent_tree = match head.parent(0) {
+        Ok(parent) => Some(parent.tree().context("failed to read parent tree")?),
+        Err(_) => None,
+    };
+    let tree = head.tree().context("failed to read commit tree")?;
+    let stats = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .and_then(|diff| diff.stats())
+        .context("failed to read commit stats")?;
+    Ok(GitCommitResultDto {
+        hash: head.id().to_string(),
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
 }
 
 pub fn soft_reset_last_commit(repo_path: &str) -> anyhow::Result<()> {
@@ -526,6 +1041,324 @@ pub fn push_repo(repo_path: &str) -> anyhow::Result<()> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForcePushMode {
+    /// Only overwrite the remote branch if it still matches the local
+    /// remote-tracking ref.
+    WithLease,
+    Unconditional,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForcePushOutcome {
+    pub remote: String,
+    pub branch: String,
+    pub previous_remote_tip: Option<String>,
+}
+
+/// Force-pushes the current branch. Protected branches are refused before
+/// anything touches the network. With a lease, the remote tip is checked via
+/// `ls-remote` first and the push itself carries the same expectation, so a
+/// remote that moves in between is still rejected by the server.
+pub fn force_push_repo(
+    repo_path: &str,
+    mode: ForcePushMode,
+    protected_branches: &[String],
+) -> anyhow::Result<ForcePushOutcome> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let branch_name = current_branch_name(&repo)
+        .ok_or_else(|| anyhow::anyhow!("detached HEAD; checkout a local branch before pushing"))?;
+    safety::ensure_branch_unprotected(&branch_name, protected_branches)?;
+
+    let (remote_name, remote_branch) = push_target(&repo, &branch_name)?;
+    let remote_ref = format!("refs/heads/{remote_branch}");
+    let refspec = format!("HEAD:{remote_ref}");
+    let expected = repo
+        .refname_to_id(&format!("refs/remotes/{remote_name}/{remote_branch}"))
+        .ok()
+        .map(|oid| oid.to_string());
+    let actual = remote_ref_tip(repo_path, &remote_name, &remote_ref)?;
+    let lease_error = |actual: Option<String>| ForcePushLeaseError {
+        remote: remote_name.clone(),
+        branch: remote_branch.clone(),
+        expected: expected.clone(),
+        actual,
+    };
+
+    match mode {
+        ForcePushMode::WithLease => {
+            if actual != expected {
+                return Err(lease_error(actual).into());
+            }
+            let lease = format!(
+                "--force-with-lease={remote_ref}:{}",
+                expected.as_deref().unwrap_or_default()
+            );
+            if let Err(error) = run_git(
+                repo_path,
+                &[
+                    "push",
+                    lease.as_str(),
+                    remote_name.as_str(),
+                    refspec.as_str(),
+                ],
+            ) {
+                if error.to_string().contains("stale info") {
+                    let actual = remote_ref_tip(repo_path, &remote_name, &remote_ref)
+                        .ok()
+                        .flatten();
+                    return Err(lease_error(actual).into());
+                }
+                return Err(error).context("failed to force push with lease");
+            }
+        }
+        ForcePushMode::Unconditional => {
+            run_git(
+                repo_path,
+                &["push", "--force", remote_name.as_str(), refspec.as_str()],
+            )
+            .context("failed to force push current branch")?;
+        }
+    }
+
+    Ok(ForcePushOutcome {
+        remote: remote_name,
+        branch: remote_branch,
+        previous_remote_tip: actual,
+    })
+}
+
+/// Replaces the last `count` commits of HEAD with a single commit holding
+/// the same tree, like `git reset --soft HEAD~count && git commit`. The
+/// oldest commit's author is kept. Returns the new commit id.
+///
+/// The new commit is built without touching the index or working tree and
+/// HEAD moves in one step, so a refusal or failure leaves the repo as it
+/// was; with `force`, uncommitted changes stay uncommitted.
+pub fn squash_commits(
+    repo_path: &str,
+    count: usize,
+    message: &str,
+    force: bool,
+    protected_branches: &[String],
+) -> anyhow::Result<String> {
+    let message = commit_message(message)?;
+    if count < 2 {
+        anyhow::bail!("squashing needs at least two commits");
+    }
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("failed to resolve HEAD commit")?;
+
+    let mut oldest = head.clone();
+    for index in 0..count {
+        if index > 0 {
+            oldest = oldest.parent(0)?;
+        }
+        match oldest.parent_count() {
+            1 => {}
+            0 => anyhow::bail!(
+                "cannot squash past the root commit; HEAD has only {} commit(s)",
+                index + 1
+            ),
+            _ => anyhow::bail!(
+                "cannot squash across merge commit {}",
+                short_commit_id(oldest.id())
+            ),
+        }
+    }
+    let base = oldest.parent(0)?;
+
+    ensure_clean_worktree(repo_path, force)?;
+    ensure_history_unpublished(&repo, oldest.id(), protected_branches)?;
+
+    let committer = repo
+        .signature()
+        .context("configure user.name and user.email before rewriting commits")?;
+    let tree = head.tree()?;
+    let squashed = repo
+        .commit(
+            None,
+            &oldest.author(),
+            &committer,
+            &message,
+            &tree,
+            &[&base],
+        )
+        .context("failed to create squashed commit")?;
+    move_head(
+        &repo,
+        head.id(),
+        squashed,
+        &format!("squash: {count} commits into one"),
+    )?;
+    Ok(squashed.to_string())
+}
+
+/// Changes the message of `commit_sha`, which must be HEAD. Like
+/// [`squash_commits`], staged changes are never folded into the commit.
+pub fn reword_commit(
+    repo_path: &str,
+    commit_sha: &str,
+    message: &str,
+    force: bool,
+    protected_branches: &[String],
+) -> anyhow::Result<String> {
+    let message = commit_message(message)?;
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("failed to resolve HEAD commit")?;
+    let target = repo
+        .revparse_single(commit_sha)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("commit not found: {commit_sha}"))?;
+    if target.id() != head.id() {
+        anyhow::bail!(
+            "only the HEAD commit can be reworded; {} is not HEAD",
+            short_commit_id(target.id())
+        );
+    }
+
+    ensure_clean_worktree(repo_path, force)?;
+    ensure_history_unpublished(&repo, head.id(), protected_branches)?;
+
+    let committer = repo
+        .signature()
+        .context("configure user.name and user.email before rewriting commits")?;
+    let reworded = head
+        .amend(None, None, Some(&committer), None, Some(&message), None)
+        .context("failed to create reworded commit")?;
+    move_head(&repo, head.id(), reworded, "reword: HEAD")?;
+    Ok(reworded.to_string())
+}
+
+fn commit_message(message: &str) -> anyhow::Result<String> {
+    let message = message.trim();
+    if message.is_empty() {
+        anyhow::bail!("commit message cannot be empty");
+    }
+    Ok(format!("{message}\n"))
+}
+
+fn short_commit_id(oid: git2::Oid) -> String {
+    oid.to_string().chars().take(7).collect()
+}
+
+/// Fails with [`DirtyWorktreeError`] when tracked files have staged or
+/// unstaged changes, unless `force` is set. Untracked files do not count.
+fn ensure_clean_worktree(repo_path: &str, force: bool) -> anyhow::Result<()> {
+    if force {
+        return Ok(());
+    }
+    let status = run_git(
+        repo_path,
+        &["status", "--porcelain=v1", "--untracked-files=no"],
+    )
+    .context("failed to check working tree before rewriting commits")?;
+    let changed_files = status
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count();
+    if changed_files > 0 {
+        return Err(DirtyWorktreeError { changed_files }.into());
+    }
+    Ok(())
+}
+
+/// Fails with [`PublishedHistoryError`] when the current branch is protected
+/// and `oldest_rewritten` is already on its remote-tracking branch. Branches
+/// that were never pushed, or are not protected, may be rewritten.
+fn ensure_history_unpublished(
+    repo: &Repository,
+    oldest_rewritten: git2::Oid,
+    protected_branches: &[String],
+) -> anyhow::Result<()> {
+    let Some(branch) = current_branch_name(repo) else {
+        return Ok(());
+    };
+    let Some(pattern) = protected_branches
+        .iter()
+        .find(|pattern| safety::branch_pattern_matches(pattern, &branch))
+    else {
+        return Ok(());
+    };
+    let Ok((remote, remote_branch)) = push_target(repo, &branch) else {
+        return Ok(());
+    };
+    let remote_ref = format!("refs/remotes/{remote}/{remote_branch}");
+    let Ok(remote_tip) = repo.refname_to_id(&remote_ref) else {
+        return Ok(());
+    };
+    if remote_tip == oldest_rewritten || repo.graph_descendant_of(remote_tip, oldest_rewritten)? {
+        return Err(PublishedHistoryError {
+            branch,
+            pattern: pattern.clone(),
+            remote_ref: format!("{remote}/{remote_branch}"),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Points HEAD (or the branch it is on) at `new_tip`, provided it still
+/// points at `expected`.
+fn move_head(
+    repo: &Repository,
+    expected: git2::Oid,
+    new_tip: git2::Oid,
+    reflog_message: &str,
+) -> anyhow::Result<()> {
+    let mut head = repo.head().context("failed to resolve HEAD")?;
+    if head.target() != Some(expected) {
+        anyhow::bail!("HEAD moved while rewriting commits; nothing was changed");
+    }
+    if head.is_branch() {
+        head.set_target(new_tip, reflog_message)
+            .context("failed to update branch")?;
+    } else {
+        repo.set_head_detached(new_tip)
+            .context("failed to update detached HEAD")?;
+    }
+    Ok(())
+}
+
+/// Remote and remote branch name `branch` pushes to: its configured upstream,
+/// otherwise the same name on the default remote.
+fn push_target(repo: &Repository, branch: &str) -> anyhow::Result<(String, String)> {
+    let config = repo.config().context("failed to read repository config")?;
+    let upstream_remote = config.get_string(&format!("branch.{branch}.remote")).ok();
+    let upstream_branch = config
+        .get_string(&format!("branch.{branch}.merge"))
+        .ok()
+        .and_then(|merge| merge.strip_prefix("refs/heads/").map(ToOwned::to_owned));
+    if let (Some(remote), Some(remote_branch)) = (upstream_remote, upstream_branch) {
+        if remote != "." {
+            return Ok((remote, remote_branch));
+        }
+    }
+
+    let remote = default_remote_name(repo)
+        .ok_or_else(|| anyhow::anyhow!("no git remote configured for this repository"))?;
+    Ok((remote, branch.to_string()))
+}
+
+fn remote_ref_tip(
+    repo_path: &str,
+    remote_name: &str,
+    remote_ref: &str,
+) -> anyhow::Result<Option<String>> {
+    let output = run_git(repo_path, &["ls-remote", remote_name, remote_ref])
+        .context("failed to read remote branch tip")?;
+    Ok(output.lines().find_map(|line| {
+        let (oid, name) = line.split_once('\t')?;
+        (name.trim() == remote_ref).then(|| oid.trim().to_string())
+    }))
+}
+
 pub fn list_git_branches(
     repo_path: &str,
     scope: GitBranchScopeDto,
@@ -687,11 +1520,35 @@ pub fn rename_git_branch(repo_path: &str, old_name: &str, new_name: &str) -> any
     Ok(())
 }
 
-pub fn delete_git_branch(repo_path: &str, branch_name: &str, force: bool) -> anyhow::Result<()> {
-    let delete_flag = if force { "-D" } else { "-d" };
-    run_git(repo_path, &["branch", delete_flag, branch_name])
-        .context("failed to delete git branch")?;
-    Ok(())
+/// Deletes a local branch. Commits reachable from neither HEAD nor the
+/// branch's upstream fail with [`UnmergedBranchError`] unless `force` is set;
+/// forced deletion of a protected branch is refused outright. Returns the
+/// number of unmerged commits that were dropped.
+pub fn delete_git_branch(
+    repo_path: &str,
+    branch_name: &str,
+    force: bool,
+    protected_branches: &[String],
+) -> anyhow::Result<usize> {
+    if force {
+        safety::ensure_branch_unprotected(branch_name, protected_branches)?;
+    }
+
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let unmerged_commits = safety::count_unmerged_commits(&repo, branch_name)
+        .context("failed to inspect branch before deletion")?;
+    if unmerged_commits > 0 && !force {
+        return Err(UnmergedBranchError {
+            branch: branch_name.to_string(),
+            unmerged_commits,
+        }
+        .into());
+    }
+
+    // Merge state was checked above against HEAD and the upstream, which is
+    // broader than `git branch -d`'s upstream-only rule once one is set.
+    run_git(repo_path, &["branch", "-D", branch_name]).context("failed to delete git branch")?;
+    Ok(unmerged_commits)
 }
 
 pub fn list_git_commits(
@@ -795,48 +1652,299 @@ pub fn list_git_stashes(repo_path: &str) -> anyhow::Result<Vec<GitStashDto>> {
             continue;
         }
 
-        let fields: Vec<&str> = trimmed.split(GIT_FIELD_SEPARATOR).collect();
-        if fields.len() < 3 {
-            continue;
+        let fields: Vec<&str> = trimmed.split(GIT_FIELD_SEPARATOR).collect();
+        if fields.len() < 3 {
+            continue;
+        }
+
+        let Some(index) = parse_stash_index(fields[0]) else {
+            continue;
+        };
+        let name = fields[1].trim().to_string();
+        let created_at = non_empty_string(fields[2]);
+
+        entries.push(GitStashDto {
+            index,
+            branch_hint: parse_branch_hint(&name),
+            message: parse_stash_message(&name),
+            name,
+            created_at,
+        });
+    }
+
+    entries.sort_by(|a, b| a.index.cmp(&b.index));
+    Ok(entries)
+}
+
+/// Stashes local changes, or only those under `paths` when it is non-empty.
+pub fn push_git_stash(
+    repo_path: &str,
+    message: Option<&str>,
+    include_untracked: bool,
+    paths: &[String],
+) -> anyhow::Result<()> {
+    let mut args = vec!["stash", "push"];
+    if let Some(msg) = message.filter(|m| !m.trim().is_empty()) {
+        args.extend(["-m", msg]);
+    }
+    if include_untracked {
+        args.push("--include-untracked");
+    }
+    if !paths.is_empty() {
+        args.push("--");
+        args.extend(paths.iter().map(String::as_str));
+    }
+    run_git(repo_path, &args).context("failed to create stash")?;
+    Ok(())
+}
+
+pub fn apply_git_stash(
+    repo_path: &str,
+    stash_index: usize,
+) -> anyhow::Result<GitStashApplyResultDto> {
+    run_stash_apply(repo_path, "apply", stash_index).context("failed to apply stash")
+}
+
+pub fn pop_git_stash(
+    repo_path: &str,
+    stash_index: usize,
+) -> anyhow::Result<GitStashApplyResultDto> {
+    run_stash_apply(repo_path, "pop", stash_index).context("failed to pop stash")
+}
+
+/// Runs `git stash apply|pop`. When git stops on merge conflicts that were
+/// not there before, the conflicted files are reported instead of an error;
+/// git keeps a popped stash in that case.
+fn run_stash_apply(
+    repo_path: &str,
+    subcommand: &str,
+    stash_index: usize,
+) -> anyhow::Result<GitStashApplyResultDto> {
+    let stash_ref = format!("stash@{{{stash_index}}}");
+    let conflicted_before = unmerged_paths(repo_path)?;
+    let error = match run_git(repo_path, &["stash", subcommand, stash_ref.as_str()]) {
+        Ok(_) => {
+            return Ok(GitStashApplyResultDto {
+                conflicted_files: Vec::new(),
+                dropped: subcommand == "pop",
+            })
+        }
+        Err(error) => error,
+    };
+    let conflicted_files = unmerged_paths(repo_path)?
+        .into_iter()
+        .filter(|path| !conflicted_before.contains(path))
+        .collect::<Vec<_>>();
+    if conflicted_files.is_empty() {
+        return Err(error);
+    }
+    Ok(GitStashApplyResultDto {
+        conflicted_files,
+        dropped: false,
+    })
+}
+
+fn unmerged_paths(repo_path: &str) -> anyhow::Result<Vec<String>> {
+    let output = run_git(repo_path, &["diff", "--name-only", "--diff-filter=U", "-z"])?;
+    Ok(output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Unmerged files left by a merge, pull, rebase or stash apply, sorted by
+/// path.
+pub fn list_conflicts(repo_path: &str) -> anyhow::Result<Vec<GitConflictDto>> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    Ok(index_conflicts(&repo)?
+        .iter()
+        .map(IndexConflictStages::to_dto)
+        .collect())
+}
+
+/// Settles the conflict on `file_path` and returns the refreshed status.
+/// Taking a side that deleted the file removes it; `Resolved` stages the
+/// working tree copy and refuses one that still has conflict markers.
+pub fn resolve_conflict(
+    repo_path: &str,
+    file_path: &str,
+    resolution: GitConflictResolutionDto,
+) -> anyhow::Result<GitStatusDto> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let stages = find_index_conflict(&repo, file_path)?;
+    match resolution {
+        GitConflictResolutionDto::Ours | GitConflictResolutionDto::Theirs => {
+            let (flag, side) = if resolution == GitConflictResolutionDto::Ours {
+                ("--ours", stages.ours)
+            } else {
+                ("--theirs", stages.theirs)
+            };
+            if side.is_some() {
+                run_git(repo_path, &["checkout", flag, "--", file_path])?;
+                run_git(repo_path, &["add", "--", file_path])?;
+            } else {
+                run_git(repo_path, &["rm", "--quiet", "--force", "--", file_path])?;
+            }
+        }
+        GitConflictResolutionDto::Resolved => {
+            match fs::read(Path::new(repo_path).join(file_path)) {
+                Ok(content) => {
+                    anyhow::ensure!(
+                        !has_conflict_markers(&content),
+                        "{file_path} still contains conflict markers"
+                    );
+                    run_git(repo_path, &["add", "--", file_path])?;
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    run_git(repo_path, &["rm", "--quiet", "--cached", "--", file_path])?;
+                }
+                Err(error) => {
+                    return Err(error).with_context(|| format!("failed to read {file_path}"))
+                }
+            }
+        }
+    }
+    get_git_status(repo_path)
+}
+
+/// The conflicted file merged again from its index stages, with diff3-style
+/// markers (`<<<<<<< ours`, `||||||| base`, `=======`, `>>>>>>> theirs`),
+/// leaving the working tree alone. `None` when the file is not conflicted.
+pub fn get_conflict_merge_view(
+    repo_path: &str,
+    file_path: &str,
+) -> anyhow::Result<Option<GitDiffPreviewDto>> {
+    let repo = Repository::open(repo_path).context("failed to open repository")?;
+    let Some(stages) = index_conflicts(&repo)?
+        .into_iter()
+        .find(|stages| stages.path == file_path)
+    else {
+        return Ok(None);
+    };
+
+    let scratch = std::env::temp_dir().join(format!("panes-merge-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&scratch).context("failed to create merge scratch directory")?;
+    let merged = (|| {
+        let mut sides = Vec::with_capacity(3);
+        for (name, oid) in [
+            ("ours", stages.ours),
+            ("base", stages.ancestor),
+            ("theirs", stages.theirs),
+        ] {
+            let content = match oid {
+                Some(oid) => repo.find_blob(oid)?.content().to_vec(),
+                None => Vec::new(),
+            };
+            let path = scratch.join(name);
+            fs::write(&path, content)?;
+            sides.push(path.to_string_lossy().to_string());
         }
+        // merge-file exits with the number of conflicts it left, up to 127.
+        run_git_accepting(
+            repo_path,
+            &[
+                "merge-file",
+                "-p",
+                "--diff3",
+                "-L",
+                "ours",
+                "-L",
+                "base",
+                "-L",
+                "theirs",
+                &sides[0],
+                &sides[1],
+                &sides[2],
+            ],
+            0..=127,
+        )
+    })();
+    let _ = fs::remove_dir_all(&scratch);
+    Ok(Some(build_diff_preview(merged.with_context(|| {
+        format!("failed to build conflict view for {file_path}")
+    })?)))
+}
 
-        let Some(index) = parse_stash_index(fields[0]) else {
-            continue;
-        };
-        let name = fields[1].trim().to_string();
-        let created_at = non_empty_string(fields[2]);
+/// The index stages of one unmerged path; a side that deleted or never
+/// had the file has no blob.
+struct IndexConflictStages {
+    path: String,
+    ancestor: Option<git2::Oid>,
+    ours: Option<git2::Oid>,
+    theirs: Option<git2::Oid>,
+}
 
-        entries.push(GitStashDto {
-            index,
-            branch_hint: parse_branch_hint(&name),
-            name,
-            created_at,
-        });
+impl IndexConflictStages {
+    fn kind(&self) -> GitConflictKindDto {
+        match (
+            self.ancestor.is_some(),
+            self.ours.is_some(),
+            self.theirs.is_some(),
+        ) {
+            (false, true, true) => GitConflictKindDto::BothAdded,
+            (true, false, false) => GitConflictKindDto::BothDeleted,
+            (false, true, false) => GitConflictKindDto::AddedByUs,
+            (false, false, true) => GitConflictKindDto::AddedByThem,
+            (true, false, true) => GitConflictKindDto::DeletedByUs,
+            (true, true, false) => GitConflictKindDto::DeletedByThem,
+            _ => GitConflictKindDto::BothModified,
+        }
     }
 
-    entries.sort_by(|a, b| a.index.cmp(&b.index));
-    Ok(entries)
+    fn to_dto(&self) -> GitConflictDto {
+        GitConflictDto {
+            path: self.path.clone(),
+            kind: self.kind(),
+        }
+    }
 }
 
-pub fn push_git_stash(repo_path: &str, message: Option<&str>) -> anyhow::Result<()> {
-    let mut args = vec!["stash", "push"];
-    if let Some(msg) = message.filter(|m| !m.trim().is_empty()) {
-        args.extend(["-m", msg]);
+fn index_conflicts(repo: &Repository) -> anyhow::Result<Vec<IndexConflictStages>> {
+    let index = repo.index().context("failed to read index")?;
+    let mut conflicts = Vec::new();
+    for conflict in index
+        .conflicts()
+        .context("failed to read index conflicts")?
+    {
+        let conflict = conflict.context("failed to read index conflict")?;
+        let Some(entry) = conflict
+            .our
+            .as_ref()
+            .or(conflict.their.as_ref())
+            .or(conflict.ancestor.as_ref())
+        else {
+            continue;
+        };
+        conflicts.push(IndexConflictStages {
+            path: String::from_utf8_lossy(&entry.path).to_string(),
+            ancestor: conflict.ancestor.as_ref().map(|entry| entry.id),
+            ours: conflict.our.as_ref().map(|entry| entry.id),
+            theirs: conflict.their.as_ref().map(|entry| entry.id),
+        });
     }
-    run_git(repo_path, &args).context("failed to create stash")?;
-    Ok(())
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(conflicts)
 }
 
-pub fn apply_git_stash(repo_path: &str, stash_index: usize) -> anyhow::Result<()> {
-    let stash_ref = format!("stash@{{{stash_index}}}");
-    run_git(repo_path, &["stash", "apply", stash_ref.as_str()]).context("failed to apply stash")?;
-    Ok(())
+fn find_index_conflict(repo: &Repository, file_path: &str) -> anyhow::Result<IndexConflictStages> {
+    index_conflicts(repo)?
+        .into_iter()
+        .find(|stages| stages.path == file_path)
+        .ok_or_else(|| anyhow::anyhow!("{file_path} has no merge conflict"))
 }
 
-pub fn pop_git_stash(repo_path: &str, stash_index: usize) -> anyhow::Result<()> {
-    let stash_ref = format!("stash@{{{stash_index}}}");
-    run_git(repo_path, &["stash", "pop", stash_ref.as_str()]).context("failed to pop stash")?;
-    Ok(())
+/// True when a line opens and another closes a conflict block.
+fn has_conflict_markers(content: &[u8]) -> bool {
+    let mut opened = false;
+    for line in content.split(|byte| *byte == b'\n') {
+        if line.starts_with(b"<<<<<<< ") || line == b"<<<<<<<" {
+            opened = true;
+        } else if opened && (line.starts_with(b">>>>>>> ") || line == b">>>>>>>") {
+            return true;
+        }
+    }
+    false
 }
 
 pub fn get_commit_diff(repo_path: &str, commit_hash: &str) -> anyhow::Result<GitDiffPreviewDto> {
@@ -856,6 +1964,7 @@ fn build_diff_preview(raw: String) -> GitDiffPreviewDto {
             truncated: false,
             original_bytes,
             returned_bytes: 0,
+            hunks: Vec::new(),
         };
     }
 
@@ -894,6 +2003,7 @@ fn build_diff_preview(raw: String) -> GitDiffPreviewDto {
             truncated: false,
             original_bytes,
             returned_bytes: original_bytes,
+            hunks: Vec::new(),
         };
     }
 
@@ -911,6 +2021,7 @@ fn build_diff_preview(raw: String) -> GitDiffPreviewDto {
         truncated,
         original_bytes,
         returned_bytes,
+        hunks: Vec::new(),
     }
 }
 
@@ -952,13 +2063,12 @@ pub fn get_file_tree(
     repo_path: &str,
     cache: &FileTreeCache,
 ) -> anyhow::Result<Vec<FileTreeEntryDto>> {
-    let cache_key = file_tree_cache_key(repo_path, FileTreeScanMode::Repo);
-    if let Some((entries, _)) = cache.get(&cache_key) {
-        return Ok((*entries).clone());
+    let (entries, _) = get_cached_file_tree_entries(repo_path, FileTreeScanMode::Repo, cache)?;
+    let mut entries = (*entries).clone();
+    if let Ok(repo) = Repository::open(repo_path) {
+        decorate_file_tree_entries(&repo, "", &mut entries);
     }
-    let scan = scan_file_tree(repo_path, FileTreeScanMode::Repo)?;
-    let arc = cache.insert(&cache_key, scan.entries, scan.truncated);
-    Ok((*arc).clone())
+    Ok(entries)
 }
 
 pub fn get_file_tree_page(
@@ -1046,7 +2156,14 @@ fn get_cached_file_tree_page(
     let total = all_entries.len();
     let offset = offset.min(total);
     let end = offset.saturating_add(limit).min(total);
-    let entries = all_entries[offset..end].to_vec();
+    let mut entries = all_entries[offset..end].to_vec();
+    // Statuses are read per page rather than cached with the scan, since
+    // working tree edits do not invalidate the cache.
+    if matches!(mode, FileTreeScanMode::Repo) {
+        if let Ok(repo) = Repository::open(root_path) {
+            decorate_file_tree_entries(&repo, "", &mut entries);
+        }
+    }
 
     Ok(FileTreePageDto {
         entries,
@@ -1058,6 +2175,114 @@ fn get_cached_file_tree_page(
     })
 }
 
+/// One page of the direct children of `dir_path`, listed on demand so the
+/// tree can expand a directory at a time; see [`fs_ops::list_dir`].
+pub fn get_file_tree_dir_page(
+    repo_path: &str,
+    dir_path: &str,
+    offset: usize,
+    limit: usize,
+    show_ignored: bool,
+) -> anyhow::Result<FileTreePageDto> {
+    let limit = limit.clamp(1, FILE_TREE_MAX_PAGE_SIZE);
+    let mut entries = fs_ops::list_dir(repo_path, dir_path, show_ignored)?;
+    let total = entries.len();
+    let offset = offset.min(total);
+    let end = offset.saturating_add(limit).min(total);
+    entries.truncate(end);
+    entries.drain(..offset);
+
+    Ok(FileTreePageDto {
+        entries,
+        offset,
+        limit,
+        total,
+        has_more: end < total,
+        scan_truncated: false,
+    })
+}
+
+/// Fills in `git_status` for entries given relative to the repo root, with
+/// `within` narrowing the status read to one directory. A directory is
+/// untracked when git reports it (or a parent) as new, and modified when
+/// anything under it changed. Entries already marked ignored keep that.
+pub(crate) fn decorate_file_tree_entries(
+    repo: &Repository,
+    within: &str,
+    entries: &mut [FileTreeEntryDto],
+) {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .include_ignored(false)
+        .exclude_submodules(true);
+    let within = within.replace('\\', "/");
+    let within = within.trim_matches('/');
+    if !within.is_empty() {
+        options.pathspec(within);
+    }
+    let statuses = match repo.statuses(Some(&mut options)) {
+        Ok(statuses) => statuses,
+        Err(error) => {
+            log::debug!("skipping file tree git status: {error}");
+            return;
+        }
+    };
+    let changes = statuses
+        .iter()
+        .filter_map(|entry| {
+            Some((
+                entry.path()?.to_string(),
+                file_tree_git_status(entry.status())?,
+            ))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let untracked_dirs = changes
+        .iter()
+        .filter(|(path, status)| path.ends_with('/') && **status == FileTreeGitStatusDto::Untracked)
+        .map(|(path, _)| path.as_str())
+        .collect::<Vec<_>>();
+
+    for entry in entries.iter_mut() {
+        if entry.git_status.is_some() {
+            continue;
+        }
+        let path = entry.path.replace('\\', "/");
+        let under_untracked_dir = untracked_dirs.iter().any(|dir| path.starts_with(dir));
+        entry.git_status = if under_untracked_dir {
+            Some(FileTreeGitStatusDto::Untracked)
+        } else if entry.is_dir {
+            let prefix = format!("{path}/");
+            changes.get(&prefix).copied().or_else(|| {
+                changes
+                    .range(prefix.clone()..)
+                    .next()
+                    .filter(|(changed, _)| changed.starts_with(&prefix))
+                    .map(|_| FileTreeGitStatusDto::Modified)
+            })
+        } else {
+            changes.get(&path).copied()
+        };
+    }
+}
+
+fn file_tree_git_status(status: Status) -> Option<FileTreeGitStatusDto> {
+    if status.is_conflicted() {
+        Some(FileTreeGitStatusDto::Conflicted)
+    } else if status.is_ignored() {
+        Some(FileTreeGitStatusDto::Ignored)
+    } else if status.is_index_new() {
+        Some(FileTreeGitStatusDto::Added)
+    } else if status.is_wt_new() {
+        Some(FileTreeGitStatusDto::Untracked)
+    } else if status.is_empty() {
+        None
+    } else {
+        Some(FileTreeGitStatusDto::Modified)
+    }
+}
+
 fn get_cached_file_tree_entries(
     root_path: &str,
     mode: FileTreeScanMode,
@@ -1261,7 +2486,8 @@ fn visit_dir(
             continue;
         }
 
-        if path.is_symlink() {
+        let is_symlink = path.is_symlink();
+        if is_symlink {
             let Ok(canonical) = path.canonicalize() else {
                 continue;
             };
@@ -1288,12 +2514,16 @@ fn visit_dir(
             context.entries.push(FileTreeEntryDto {
                 path: relative.clone(),
                 is_dir: true,
+                is_symlink,
+                git_status: None,
             });
             visit_dir(root, &path, repo, context)?;
         } else {
             context.entries.push(FileTreeEntryDto {
                 path: relative,
                 is_dir: false,
+                is_symlink,
+                git_status: None,
             });
         }
     }
@@ -1301,36 +2531,43 @@ fn visit_dir(
     Ok(())
 }
 
-fn resolve_branch_ahead_behind(repo: &Repository) -> (usize, usize) {
+/// Upstream name and ahead/behind counts of the checked-out branch; no
+/// upstream and detached HEAD both come back as `(None, 0, 0)`.
+fn resolve_branch_tracking(repo: &Repository) -> (Option<String>, usize, usize) {
     let head = match repo.head() {
         Ok(value) => value,
-        Err(_) => return (0, 0),
+        Err(_) => return (None, 0, 0),
     };
 
     if !head.is_branch() {
-        return (0, 0);
+        return (None, 0, 0);
     }
 
     let Some(local_oid) = head.target() else {
-        return (0, 0);
+        return (None, 0, 0);
     };
 
     let Some(local_name) = head.shorthand() else {
-        return (0, 0);
+        return (None, 0, 0);
     };
 
-    let upstream_oid = repo
+    let Some(upstream) = repo
         .find_branch(local_name, git2::BranchType::Local)
         .ok()
         .and_then(|branch| branch.upstream().ok())
-        .and_then(|branch| branch.get().target());
+    else {
+        return (None, 0, 0);
+    };
+    let upstream_name = upstream.name().ok().flatten().map(ToOwned::to_owned);
 
-    let Some(upstream_oid) = upstream_oid else {
-        return (0, 0);
+    let Some(upstream_oid) = upstream.get().target() else {
+        return (upstream_name, 0, 0);
     };
 
-    repo.graph_ahead_behind(local_oid, upstream_oid)
-        .unwrap_or((0, 0))
+    let (ahead, behind) = repo
+        .graph_ahead_behind(local_oid, upstream_oid)
+        .unwrap_or((0, 0));
+    (upstream_name, ahead, behind)
 }
 
 fn current_branch_name(repo: &Repository) -> Option<String> {
@@ -1393,6 +2630,13 @@ fn parse_stash_index(stash_ref: &str) -> Option<usize> {
         .ok()
 }
 
+/// The message given to `git stash push -m`; `None` for the default
+/// "WIP on <branch>: <commit>" subject.
+fn parse_stash_message(stash_name: &str) -> Option<String> {
+    let (_, message) = stash_name.trim().strip_prefix("On ")?.split_once(": ")?;
+    non_empty_string(message)
+}
+
 fn parse_branch_hint(stash_name: &str) -> Option<String> {
     let message = stash_name.trim();
 
@@ -1700,12 +2944,23 @@ mod tests {
     use std::fs;
 
     use super::{
-        build_diff_preview, checkout_git_branch, discard_files, get_workspace_file_tree_page,
-        is_diff_preview_metadata_line, parse_porcelain_v1_status, run_git, search_workspace_files,
-        truncate_utf8_prefix, FileTreeCache, GIT_DIFF_PREVIEW_MAX_BYTES,
+        build_diff_preview, checkout_git_branch, commit, delete_git_branch, discard_files,
+        force_push_repo, get_conflict_merge_view, get_file_diff, get_git_status,
+        get_git_status_via_cli, get_git_status_via_git2, get_workspace_file_tree_page,
+        is_diff_preview_metadata_line, list_conflicts, list_git_stashes, parse_hunk_ranges,
+        parse_porcelain_v1_status, parse_stash_message, pop_git_stash, push_git_stash,
+        resolve_conflict, reword_commit, run_git, search_workspace_files, single_hunk_patch,
+        squash_commits, stage_hunk, stage_hunks, truncate_utf8_prefix, unstage_hunk, unstage_hunks,
+        CommitOptions, FileTreeCache, ForcePushMode, StaleHunkError, GIT_DIFF_PREVIEW_MAX_BYTES,
         GIT_DIFF_PREVIEW_MAX_LINES,
     };
-    use crate::models::FileTreeEntryDto;
+    use crate::git::safety::{
+        default_protected_branches, DirtyWorktreeError, ForcePushLeaseError, ProtectedBranchError,
+        PublishedHistoryError, PushedCommitError, UnmergedBranchError,
+    };
+    use crate::models::{
+        FileTreeEntryDto, GitConflictKindDto, GitConflictResolutionDto, GitStatusDto,
+    };
     use uuid::Uuid;
 
     struct TempRepo {
@@ -1761,6 +3016,316 @@ mod tests {
         }
     }
 
+    /// Adds a bare repo as `origin` and pushes the current branch to it.
+    fn add_bare_origin(repo: &TempRepo) -> std::path::PathBuf {
+        let bare = repo.path.join(".origin.git");
+        fs::create_dir_all(&bare).expect("create bare dir");
+        let bare_str = bare.to_str().expect("utf-8 bare path");
+        run_git(bare_str, &["init", "--bare"]).expect("git init --bare");
+        run_git(repo.path_str(), &["remote", "add", "origin", bare_str]).expect("add remote");
+        bare
+    }
+
+    fn rev_parse(repo_path: &str, rev: &str) -> String {
+        run_git(repo_path, &["rev-parse", rev])
+            .expect("rev-parse")
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn single_hunk_patch_keeps_crlf_and_turns_renames_into_edits() {
+        let diff = b"diff --git a/old.txt b/new.txt\n\
+similarity index 80%\n\
+rename from old.txt\n\
+rename to new.txt\n\
+index 1111111..2222222 100644\n\
+--- a/old.txt\n\
++++ b/new.txt\n\
+@@ -1,2 +1,2 @@\n\
+-one\r\n\
++ONE\r\n\
+ two\r\n\
+@@ -9 +9 @@ eight\n\
+-nine\r\n\
++NINE\r\n";
+
+        let patch = single_hunk_patch(diff, "@@ -9 +9 @@").unwrap();
+        assert_eq!(
+            patch,
+            b"diff --git a/new.txt b/new.txt\n\
+--- a/new.txt\n\
++++ b/new.txt\n\
+@@ -9 +9 @@ eight\n\
+-nine\r\n\
++NINE\r\n"
+        );
+        assert!(single_hunk_patch(diff, "@@ -3,2 +3,2 @@").is_err());
+        assert_eq!(parse_hunk_ranges(b"@@ -0,0 +1 @@"), Some((0, 0, 1, 1)));
+        assert_eq!(parse_hunk_ranges(b"@@ nope @@"), None);
+    }
+
+    #[test]
+    fn hunks_stage_and_unstage_across_crlf_renames_and_new_files() {
+        let repo = TempRepo::init();
+        let lines = (1..=12).map(|n| format!("l{n}\r\n")).collect::<String>();
+        repo.write("crlf.txt", &lines);
+        repo.write("old.txt", "a\nb\nc\nd\n");
+        repo.commit_all("init");
+        let path = repo.path_str();
+        let hunk_headers = |file: &str, staged: bool| {
+            get_file_diff(path, file, staged)
+                .unwrap()
+                .content
+                .lines()
+                .filter(|line| line.starts_with("@@ "))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        repo.write(
+            "crlf.txt",
+            &lines.replace("l1\r\n", "L1\r\n").replace("l12\r", "L12\r"),
+        );
+        let unstaged = hunk_headers("crlf.txt", false);
+        assert_eq!(unstaged.len(), 2);
+        stage_hunk(path, "crlf.txt", &unstaged[1]).unwrap();
+        let staged = run_git(path, &["diff", "--cached"]).unwrap();
+        assert!(staged.contains("+L12\r\n") && !staged.contains("+L1\r\n"));
+        assert_eq!(hunk_headers("crlf.txt", false).len(), 1);
+
+        run_git(path, &["mv", "old.txt", "new.txt"]).unwrap();
+        repo.write("new.txt", "a\nb\nc\nd\ne\n");
+        run_git(path, &["add", "new.txt"]).unwrap();
+        let renamed = hunk_headers("new.txt", true);
+        assert_eq!(renamed, ["@@ -2,3 +2,4 @@ a"]);
+        unstage_hunk(path, "new.txt", &renamed[0]).unwrap();
+        let staged = run_git(
+            path,
+            &["diff", "--cached", "-M", "--", "old.txt", "new.txt"],
+        )
+        .unwrap();
+        assert!(staged.contains("rename to new.txt") && !staged.contains("+e"));
+        assert_eq!(repo.read("new.txt"), "a\nb\nc\nd\ne\n");
+
+        repo.write("fresh.txt", "x\ny\n");
+        let status = stage_hunk(path, "fresh.txt", "@@ -0,0 +1,2 @@").unwrap();
+        let fresh = status
+            .files
+            .iter()
+            .find(|file| file.path == "fresh.txt")
+            .unwrap();
+        assert_eq!(fresh.worktree_status, None);
+        assert!(fresh.index_status.is_some());
+
+        repo.write("other.txt", "z\n");
+        assert!(stage_hunk(path, "other.txt", "@@ -0,0 +1,5 @@").is_err());
+        let other = get_git_status(path).unwrap();
+        let other = other
+            .files
+            .iter()
+            .find(|file| file.path == "other.txt")
+            .unwrap();
+        assert_eq!(other.index_status, None);
+    }
+
+    #[test]
+    fn force_push_with_lease_aborts_when_remote_moved() {
+        let repo = TempRepo::init();
+        repo.write(".gitignore", ".origin.git/\n");
+        repo.write("a.txt", "one\n");
+        repo.commit_all("one");
+        let bare = add_bare_origin(&repo);
+        let bare_str = bare.to_str().unwrap();
+        run_git(repo.path_str(), &["checkout", "-b", "feature"]).expect("branch");
+        repo.write("a.txt", "two\n");
+        repo.commit_all("two");
+        run_git(repo.path_str(), &["push", "-u", "origin", "feature"]).expect("push");
+
+        // Someone else pushes to feature; our tracking ref is now stale.
+        repo.write("a.txt", "theirs\n");
+        repo.commit_all("theirs");
+        let theirs = rev_parse(repo.path_str(), "HEAD");
+        run_git(
+            repo.path_str(),
+            &["push", "origin", "HEAD:refs/heads/other"],
+        )
+        .expect("push");
+        run_git(
+            bare_str,
+            &["update-ref", "refs/heads/feature", theirs.as_str()],
+        )
+        .expect("move remote");
+        run_git(repo.path_str(), &["reset", "--hard", "HEAD~2"]).expect("reset");
+        repo.write("a.txt", "rewritten\n");
+        repo.commit_all("rewritten");
+
+        let protected = default_protected_branches();
+        let error = force_push_repo(repo.path_str(), ForcePushMode::WithLease, &protected)
+            .expect_err("stale lease must abort");
+        let typed = error
+            .downcast_ref::<ForcePushLeaseError>()
+            .expect("error should be ForcePushLeaseError");
+        assert_eq!(typed.actual.as_deref(), Some(theirs.as_str()));
+        assert_eq!(rev_parse(bare_str, "refs/heads/feature"), theirs);
+
+        // After fetching, the lease matches and the push goes through.
+        run_git(repo.path_str(), &["fetch", "origin"]).expect("fetch");
+        let pushed = force_push_repo(repo.path_str(), ForcePushMode::WithLease, &protected)
+            .expect("fresh lease should push");
+        assert_eq!(pushed.previous_remote_tip.as_deref(), Some(theirs.as_str()));
+        assert_eq!(
+            rev_parse(bare_str, "refs/heads/feature"),
+            rev_parse(repo.path_str(), "HEAD")
+        );
+    }
+
+    #[test]
+    fn squash_keeps_the_tree_and_leaves_uncommitted_changes_alone() {
+        let repo = TempRepo::init();
+        repo.write("a.txt", "base\n");
+        repo.commit_all("base");
+        for step in ["one", "two", "three"] {
+            repo.write("a.txt", &format!("{step}\n"));
+            repo.commit_all(step);
+        }
+        let tree_before = rev_parse(repo.path_str(), "HEAD^{tree}");
+        repo.write("a.txt", "staged\n");
+        run_git(repo.path_str(), &["add", "a.txt"]).expect("stage");
+
+        let error = squash_commits(repo.path_str(), 3, "combined", false, &[])
+            .expect_err("dirty tree must refuse");
+        assert!(error.is::<DirtyWorktreeError>());
+        assert!(squash_commits(repo.path_str(), 4, "combined", true, &[]).is_err());
+
+        let new_head =
+            squash_commits(repo.path_str(), 3, "combined", true, &[]).expect("forced squash");
+        assert_eq!(rev_parse(repo.path_str(), "HEAD"), new_head);
+        assert_eq!(rev_parse(repo.path_str(), "HEAD^{tree}"), tree_before);
+        assert_eq!(
+            run_git(repo.path_str(), &["log", "--format=%s"]).unwrap(),
+            "combined\nbase\n"
+        );
+        assert_eq!(
+            run_git(repo.path_str(), &["diff", "--cached", "--name-only"]).unwrap(),
+            "a.txt\n"
+        );
+        assert_eq!(repo.read("a.txt"), "staged\n");
+    }
+
+    #[test]
+    fn rewording_only_touches_head_and_refuses_pushed_protected_history() {
+        let repo = TempRepo::init();
+        repo.write(".gitignore", ".origin.git/\n");
+        repo.commit_all("base");
+        repo.write("a.txt", "one\n");
+        repo.commit_all("first");
+        repo.write("a.txt", "two\n");
+        repo.commit_all("second");
+        let first = rev_parse(repo.path_str(), "HEAD~1");
+
+        let error = reword_commit(repo.path_str(), &first, "renamed", false, &[])
+            .expect_err("non-HEAD commit");
+        assert!(error.to_string().contains("only the HEAD commit"));
+        reword_commit(repo.path_str(), "HEAD", "second, reworded", false, &[])
+            .expect("reword HEAD");
+        assert_eq!(
+            run_git(repo.path_str(), &["log", "-1", "--format=%s"]).unwrap(),
+            "second, reworded\n"
+        );
+
+        add_bare_origin(&repo);
+        run_git(repo.path_str(), &["push", "-u", "origin", "main"]).expect("push");
+        let head_before = rev_parse(repo.path_str(), "HEAD");
+        let protected = default_protected_branches();
+        let error = reword_commit(repo.path_str(), "HEAD", "again", false, &protected)
+            .expect_err("pushed protected history");
+        assert!(error.is::<PublishedHistoryError>());
+        let error = squash_commits(repo.path_str(), 2, "squashed", true, &protected)
+            .expect_err("force does not bypass published history");
+        assert!(error.is::<PublishedHistoryError>());
+        assert_eq!(rev_parse(repo.path_str(), "HEAD"), head_before);
+
+        // Commits made after the last push are still local.
+        repo.write("a.txt", "three\n");
+        repo.commit_all("third");
+        reword_commit(
+            repo.path_str(),
+            "HEAD",
+            "third, reworded",
+            false,
+            &protected,
+        )
+        .expect("unpushed commit can be reworded");
+    }
+
+    #[test]
+    fn force_push_refuses_protected_branches() {
+        let repo = TempRepo::init();
+        repo.write(".gitignore", ".origin.git/\n");
+        repo.write("a.txt", "one\n");
+        repo.commit_all("one");
+        let bare = add_bare_origin(&repo);
+        run_git(repo.path_str(), &["push", "-u", "origin", "main"]).expect("push");
+        let before = rev_parse(bare.to_str().unwrap(), "refs/heads/main");
+        repo.write("a.txt", "rewritten\n");
+        run_git(
+            repo.path_str(),
+            &["commit", "-a", "--amend", "-m", "rewritten"],
+        )
+        .expect("amend");
+
+        for mode in [ForcePushMode::WithLease, ForcePushMode::Unconditional] {
+            let error = force_push_repo(repo.path_str(), mode, &default_protected_branches())
+                .expect_err("protected branch must refuse force");
+            assert!(error.is::<ProtectedBranchError>());
+        }
+        assert_eq!(rev_parse(bare.to_str().unwrap(), "refs/heads/main"), before);
+
+        run_git(repo.path_str(), &["branch", "release/1.0"]).expect("branch");
+        let error = delete_git_branch(
+            repo.path_str(),
+            "release/1.0",
+            true,
+            &default_protected_branches(),
+        )
+        .expect_err("forced delete of protected branch must refuse");
+        assert!(error.is::<ProtectedBranchError>());
+    }
+
+    #[test]
+    fn delete_branch_detects_unmerged_commits() {
+        let repo = TempRepo::init();
+        repo.write("a.txt", "one\n");
+        repo.commit_all("one");
+        run_git(repo.path_str(), &["checkout", "-b", "feature"]).expect("branch");
+        repo.write("b.txt", "two\n");
+        repo.commit_all("two");
+        repo.write("c.txt", "three\n");
+        repo.commit_all("three");
+        run_git(repo.path_str(), &["checkout", "main"]).expect("checkout");
+        run_git(repo.path_str(), &["branch", "merged"]).expect("branch");
+        let protected = default_protected_branches();
+
+        let error = delete_git_branch(repo.path_str(), "feature", false, &protected)
+            .expect_err("unmerged branch must not be deleted");
+        let typed = error
+            .downcast_ref::<UnmergedBranchError>()
+            .expect("error should be UnmergedBranchError");
+        assert_eq!(typed.unmerged_commits, 2);
+        assert!(run_git(repo.path_str(), &["rev-parse", "--verify", "feature"]).is_ok());
+
+        assert_eq!(
+            delete_git_branch(repo.path_str(), "merged", false, &protected).unwrap(),
+            0
+        );
+        assert_eq!(
+            delete_git_branch(repo.path_str(), "feature", true, &protected).unwrap(),
+            2
+        );
+        assert!(run_git(repo.path_str(), &["rev-parse", "--verify", "feature"]).is_err());
+    }
+
     #[test]
     fn checkout_does_not_force_discard_for_dash_prefixed_branch() {
         let repo = TempRepo::init();
@@ -1824,8 +3389,10 @@ mod tests {
         let status = parse_porcelain_v1_status(output).expect("status should parse");
 
         assert_eq!(status.branch, "main");
+        assert_eq!(status.upstream_branch.as_deref(), Some("origin/main"));
         assert_eq!(status.ahead, 2);
         assert_eq!(status.behind, 1);
+        assert!(!status.detached_head);
         assert_eq!(
             status
                 .files
@@ -1854,6 +3421,8 @@ mod tests {
         let status = parse_porcelain_v1_status(output).expect("status should parse");
 
         assert_eq!(status.branch, "detached");
+        assert!(status.detached_head);
+        assert_eq!(status.upstream_branch, None);
         assert_eq!(status.files.len(), 1);
         assert_eq!(status.files[0].path, "src/conflict.ts");
         assert_eq!(status.files[0].index_status.as_deref(), Some("conflicted"));
@@ -1861,6 +3430,103 @@ mod tests {
             status.files[0].worktree_status.as_deref(),
             Some("conflicted")
         );
+        assert_eq!(status.conflicts.len(), 1);
+        assert_eq!(status.conflicts[0].kind, GitConflictKindDto::BothModified);
+    }
+
+    #[test]
+    fn status_reports_upstream_tracking_after_fetch_and_when_detached() {
+        let repo = TempRepo::init();
+        repo.write("file.txt", "base\n");
+        repo.commit_all("base");
+        let bare = add_bare_origin(&repo);
+        run_git(repo.path_str(), &["push", "-u", "origin", "main"]).expect("push main");
+
+        let other = repo.path.join(".other");
+        let other_str = other.to_str().expect("utf-8 clone path");
+        run_git(
+            repo.path_str(),
+            &[
+                "clone",
+                "--branch",
+                "main",
+                bare.to_str().expect("utf-8 bare path"),
+                other_str,
+            ],
+        )
+        .expect("clone origin");
+        for message in ["remote one", "remote two"] {
+            run_git(
+                other_str,
+                &[
+                    "-c",
+                    "user.email=other@example.com",
+                    "-c",
+                    "user.name=Other",
+                    "commit",
+                    "--allow-empty",
+                    "-m",
+                    message,
+                ],
+            )
+            .expect("commit in clone");
+        }
+        run_git(other_str, &["push", "origin", "main"]).expect("push from clone");
+        fs::remove_dir_all(&other).expect("remove clone");
+
+        repo.write("file.txt", "local\n");
+        run_git(repo.path_str(), &["commit", "-am", "local"]).expect("commit local");
+
+        let tracking = |status: &GitStatusDto| {
+            (
+                status.upstream_branch.clone(),
+                status.ahead,
+                status.behind,
+                status.detached_head,
+            )
+        };
+        let expected_before = (Some("origin/main".to_string()), 1, 0, false);
+        assert_eq!(
+            tracking(&get_git_status_via_cli(repo.path_str()).expect("cli status")),
+            expected_before
+        );
+        assert_eq!(
+            tracking(&get_git_status_via_git2(repo.path_str()).expect("git2 status")),
+            expected_before
+        );
+
+        run_git(repo.path_str(), &["fetch", "origin"]).expect("fetch");
+        let expected_after = (Some("origin/main".to_string()), 1, 2, false);
+        assert_eq!(
+            tracking(&get_git_status_via_cli(repo.path_str()).expect("cli status")),
+            expected_after
+        );
+        assert_eq!(
+            tracking(&get_git_status_via_git2(repo.path_str()).expect("git2 status")),
+            expected_after
+        );
+
+        run_git(repo.path_str(), &["checkout", "-b", "feature/local"]).expect("new branch");
+        let expected_untracked = (None, 0, 0, false);
+        assert_eq!(
+            tracking(&get_git_status_via_cli(repo.path_str()).expect("cli status")),
+            expected_untracked
+        );
+        assert_eq!(
+            tracking(&get_git_status_via_git2(repo.path_str()).expect("git2 status")),
+            expected_untracked
+        );
+
+        run_git(repo.path_str(), &["checkout", "--detach"]).expect("detach");
+        let expected_detached = (None, 0, 0, true);
+        assert_eq!(
+            tracking(&get_git_status_via_cli(repo.path_str()).expect("cli status")),
+            expected_detached
+        );
+        assert_eq!(
+            tracking(&get_git_status_via_git2(repo.path_str()).expect("git2 status")),
+            expected_detached
+        );
     }
 
     #[test]
@@ -1974,6 +3640,152 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hunk_ids_stage_a_subset_and_go_stale_when_the_file_changes() {
+        let repo = TempRepo::init();
+        let lines = (1..=12).map(|n| format!("l{n}\n")).collect::<String>();
+        let original = lines.trim_end_matches('\n');
+        repo.write("tail.txt", original);
+        repo.commit_all("init");
+        let path = repo.path_str();
+        let hunk_ids = |staged: bool| {
+            get_file_diff(path, "tail.txt", staged)
+                .unwrap()
+                .hunks
+                .into_iter()
+                .map(|hunk| hunk.id)
+                .collect::<Vec<_>>()
+        };
+
+        let edited = original.replace("l1\n", "L1\n").replace("l12", "L12");
+        repo.write("tail.txt", &edited);
+        let unstaged = get_file_diff(path, "tail.txt", false).unwrap();
+        assert_eq!(unstaged.hunks.len(), 2);
+        assert!(unstaged.hunks[0].header.starts_with("@@ -1,"));
+        assert_eq!(
+            hunk_ids(false),
+            unstaged
+                .hunks
+                .iter()
+                .map(|hunk| hunk.id.clone())
+                .collect::<Vec<_>>(),
+            "ids are stable while the file is unchanged"
+        );
+
+        stage_hunks(path, "tail.txt", &[unstaged.hunks[0].id.clone()]).unwrap();
+        let cached = run_git(path, &["diff", "--cached"]).unwrap();
+        assert!(cached.contains("+L1\n") && !cached.contains("+L12"));
+        let remaining = hunk_ids(false);
+        assert_eq!(remaining.len(), 1);
+
+        // The last hunk carries "\ No newline at end of file" markers.
+        stage_hunks(path, "tail.txt", &remaining).unwrap();
+        assert_eq!(
+            run_git(path, &["show", ":tail.txt"]).unwrap(),
+            edited,
+            "index matches the file without a trailing newline"
+        );
+
+        let staged = hunk_ids(true);
+        assert_eq!(staged.len(), 2);
+        repo.write("tail.txt", &edited.replace("L1\n", "M1\n"));
+        unstage_hunks(path, "tail.txt", &staged[1..]).unwrap();
+        let cached = run_git(path, &["diff", "--cached"]).unwrap();
+        assert!(cached.contains("+L1\n") && !cached.contains("+L12"));
+
+        let stale = hunk_ids(false);
+        repo.write("tail.txt", &edited.replace("L1\n", "N1\n"));
+        let error = stage_hunks(path, "tail.txt", &stale).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<StaleHunkError>(),
+            Some(&StaleHunkError {
+                path: "tail.txt".to_string()
+            })
+        );
+        assert!(stage_hunks(path, "tail.txt", &[]).is_err());
+    }
+
+    #[test]
+    fn amend_rewrites_head_with_staged_changes_and_refuses_pushed_commits() {
+        let repo = TempRepo::init();
+        let path = repo.path_str();
+        let unsigned = CommitOptions {
+            sign: Some(false),
+            ..CommitOptions::default()
+        };
+        let amend = CommitOptions {
+            amend: true,
+            ..unsigned
+        };
+        let message_of = |rev: &str| run_git(path, &["log", "-1", "--format=%B", rev]).unwrap();
+
+        repo.write("a.txt", "one\ntwo\n");
+        run_git(path, &["add", "a.txt"]).unwrap();
+        let first = commit(path, "first", unsigned).unwrap();
+        assert_eq!(
+            (first.files_changed, first.insertions, first.deletions),
+            (1, 2, 0)
+        );
+
+        // Amending the root commit keeps it a root commit and its message.
+        repo.write("b.txt", "b\n");
+        run_git(path, &["add", "b.txt"]).unwrap();
+        let amended = commit(
+            path,
+            "  ",
+            CommitOptions {
+                signoff: true,
+                ..amend
+            },
+        )
+        .unwrap();
+        assert_ne!(amended.hash, first.hash);
+        assert_eq!(rev_parse(path, "HEAD"), amended.hash);
+        assert!(run_git(path, &["rev-parse", "--verify", "--quiet", "HEAD~1"]).is_err());
+        assert_eq!(amended.files_changed, 2);
+        let message = message_of("HEAD");
+        assert!(message.starts_with("first\n"));
+        assert!(message.contains("Signed-off-by: Test <test@example.com>"));
+
+        // Only staged changes are folded in; unstaged edits stay behind.
+        repo.write("a.txt", "one\n2\n");
+        run_git(path, &["add", "a.txt"]).unwrap();
+        let second = commit(path, "second", unsigned).unwrap();
+        repo.write("c.txt", "c\n");
+        run_git(path, &["add", "c.txt"]).unwrap();
+        repo.write("a.txt", "unstaged\n");
+        let reworked = commit(path, "second, reworked", amend).unwrap();
+        assert_eq!(rev_parse(path, "HEAD~1"), amended.hash);
+        assert_eq!(
+            (
+                reworked.files_changed,
+                reworked.insertions,
+                reworked.deletions
+            ),
+            (2, 2, 1)
+        );
+        assert_ne!(reworked.hash, second.hash);
+        assert_eq!(message_of("HEAD").trim(), "second, reworked");
+        assert_eq!(repo.read("a.txt"), "unstaged\n");
+        assert_eq!(run_git(path, &["show", "HEAD:a.txt"]).unwrap(), "one\n2\n");
+
+        add_bare_origin(&repo);
+        run_git(path, &["push", "-u", "origin", "main"]).unwrap();
+        let error = commit(path, "", amend).unwrap_err();
+        assert!(error.downcast_ref::<PushedCommitError>().is_some());
+        assert_eq!(rev_parse(path, "HEAD"), reworked.hash);
+        let forced = commit(
+            path,
+            "",
+            CommitOptions {
+                force: true,
+                ..amend
+            },
+        )
+        .unwrap();
+        assert_ne!(forced.hash, reworked.hash);
+    }
+
     #[test]
     fn line_limit_preserves_hunks_after_metadata_heavy_prefix() {
         let mut raw = String::new();
@@ -2229,6 +4041,8 @@ mod tests {
             vec![FileTreeEntryDto {
                 path: "src/main.ts".to_string(),
                 is_dir: false,
+                is_symlink: false,
+                git_status: None,
             }],
             false,
         );
@@ -2237,6 +4051,8 @@ mod tests {
             vec![FileTreeEntryDto {
                 path: "apps/app/src/main.ts".to_string(),
                 is_dir: false,
+                is_symlink: false,
+                git_status: None,
             }],
             false,
         );
@@ -2245,6 +4061,8 @@ mod tests {
             vec![FileTreeEntryDto {
                 path: "README.md".to_string(),
                 is_dir: false,
+                is_symlink: false,
+                git_status: None,
             }],
             false,
         );
@@ -2264,6 +4082,8 @@ mod tests {
             vec![FileTreeEntryDto {
                 path: "src/main.ts".to_string(),
                 is_dir: false,
+                is_symlink: false,
+                git_status: None,
             }],
             false,
         );
@@ -2272,6 +4092,8 @@ mod tests {
             vec![FileTreeEntryDto {
                 path: "apps/app/src/main.ts".to_string(),
                 is_dir: false,
+                is_symlink: false,
+                git_status: None,
             }],
             false,
         );
@@ -2280,6 +4102,8 @@ mod tests {
             vec![FileTreeEntryDto {
                 path: "README.md".to_string(),
                 is_dir: false,
+                is_symlink: false,
+                git_status: None,
             }],
             false,
         );
@@ -2290,4 +4114,104 @@ mod tests {
         assert!(cache.get("workspace::/workspace").is_none());
         assert!(cache.get("/workspace/other").is_some());
     }
+
+    #[test]
+    fn parses_stash_message_only_when_one_was_given() {
+        assert_eq!(
+            parse_stash_message("On main: wip: half-done refactor").as_deref(),
+            Some("wip: half-done refactor")
+        );
+        assert_eq!(parse_stash_message("WIP on main: abc1234 Initial"), None);
+    }
+
+    #[test]
+    fn stashes_selected_paths_and_reports_conflicts_on_pop() {
+        let repo = TempRepo::init();
+        repo.write("a.txt", "one\n");
+        repo.write("b.txt", "one\n");
+        repo.commit_all("initial");
+
+        repo.write("a.txt", "stashed\n");
+        repo.write("b.txt", "kept\n");
+        repo.write("new.txt", "untracked\n");
+        push_git_stash(
+            repo.path_str(),
+            Some("only a"),
+            true,
+            &["a.txt".to_string(), "new.txt".to_string()],
+        )
+        .expect("stash selected paths");
+
+        assert_eq!(repo.read("a.txt"), "one\n");
+        assert_eq!(repo.read("b.txt"), "kept\n");
+        assert!(!repo.path.join("new.txt").exists());
+        let stashes = list_git_stashes(repo.path_str()).expect("list stashes");
+        assert_eq!(stashes.len(), 1);
+        assert_eq!(stashes[0].message.as_deref(), Some("only a"));
+
+        repo.write("a.txt", "conflicting\n");
+        repo.commit_all("touch a");
+        let result = pop_git_stash(repo.path_str(), 0).expect("pop with conflicts");
+
+        assert_eq!(result.conflicted_files, vec!["a.txt".to_string()]);
+        assert!(!result.dropped);
+        assert_eq!(list_git_stashes(repo.path_str()).expect("list").len(), 1);
+    }
+
+    #[test]
+    fn lists_merge_conflicts_with_a_three_way_view_and_resolves_them() {
+        let repo = TempRepo::init();
+        repo.write("a.txt", "base\n");
+        repo.write("b.txt", "keep\n");
+        repo.commit_all("base");
+        run_git(repo.path_str(), &["checkout", "-q", "-b", "other"]).expect("branch");
+        repo.write("a.txt", "theirs\n");
+        fs::remove_file(repo.path.join("b.txt")).expect("remove b.txt");
+        repo.commit_all("theirs");
+        run_git(repo.path_str(), &["checkout", "-q", "main"]).expect("checkout main");
+        repo.write("a.txt", "ours\n");
+        repo.write("b.txt", "changed\n");
+        repo.commit_all("ours");
+        assert!(run_git(repo.path_str(), &["merge", "other"]).is_err());
+
+        let conflicts = list_conflicts(repo.path_str()).expect("list conflicts");
+        let kinds = conflicts
+            .iter()
+            .map(|conflict| (conflict.path.as_str(), conflict.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("a.txt", GitConflictKindDto::BothModified),
+                ("b.txt", GitConflictKindDto::DeletedByThem),
+            ]
+        );
+        let status = get_git_status(repo.path_str()).expect("status");
+        assert_eq!(status.conflicts.len(), 2);
+
+        let view = get_conflict_merge_view(repo.path_str(), "a.txt")
+            .expect("merge view")
+            .expect("a.txt is conflicted");
+        assert_eq!(
+            view.content,
+            "<<<<<<< ours\nours\n||||||| base\nbase\n=======\ntheirs\n>>>>>>> theirs\n"
+        );
+        assert!(get_conflict_merge_view(repo.path_str(), "missing.txt")
+            .expect("merge view")
+            .is_none());
+
+        assert!(
+            resolve_conflict(repo.path_str(), "a.txt", GitConflictResolutionDto::Resolved).is_err(),
+            "conflict markers are still in the file"
+        );
+        repo.write("a.txt", "merged\n");
+        let status = resolve_conflict(repo.path_str(), "a.txt", GitConflictResolutionDto::Resolved)
+            .expect("resolve a.txt");
+        assert_eq!(status.conflicts.len(), 1);
+        let status = resolve_conflict(repo.path_str(), "b.txt", GitConflictResolutionDto::Theirs)
+            .expect("resolve b.txt");
+        assert!(status.conflicts.is_empty());
+        assert!(!repo.path.join("b.txt").exists());
+        assert_eq!(repo.read("a.txt"), "merged\n");
+    }
 }
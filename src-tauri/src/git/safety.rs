@@ -0,0 +1,183 @@
+use git2::{BranchType, Oid, Repository};
+
+/// Branch patterns that refuse force operations when a repo has not
+/// configured its own list.
+pub const DEFAULT_PROTECTED_BRANCHES: &[&str] = &["main", "master", "release/*"];
+
+/// A lease-protected force push found the remote branch somewhere other than
+/// the local remote-tracking ref, i.e. someone pushed since the last fetch.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "force push aborted: {remote}/{branch} moved from {} to {} since the last fetch; fetch and review before forcing",
+    short_oid(.expected.as_deref()),
+    short_oid(.actual.as_deref())
+)]
+pub struct ForcePushLeaseError {
+    pub remote: String,
+    pub branch: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// Deleting the branch would drop commits that are reachable from neither
+/// HEAD nor the branch's upstream.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "branch `{branch}` has {unmerged_commits} unmerged commit(s); pass force to delete it anyway"
+)]
+pub struct UnmergedBranchError {
+    pub branch: String,
+    pub unmerged_commits: usize,
+}
+
+/// History rewrites refuse to run over uncommitted work unless forced.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "working tree has {changed_files} uncommitted change(s); commit or stash them, or pass force"
+)]
+pub struct DirtyWorktreeError {
+    pub changed_files: usize,
+}
+
+/// Rewriting would replace commits that a protected branch already pushed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "branch `{branch}` is protected by `{pattern}` and these commits are already on {remote_ref}; rewriting them is not allowed"
+)]
+pub struct PublishedHistoryError {
+    pub branch: String,
+    pub pattern: String,
+    pub remote_ref: String,
+}
+
+/// Amending would replace a commit the branch's upstream already has.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "commit {commit} is already on {upstream}; amending it rewrites pushed history, pass force to amend anyway"
+)]
+pub struct PushedCommitError {
+    pub commit: String,
+    pub upstream: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("branch `{branch}` is protected by `{pattern}`; force operations are not allowed")]
+pub struct ProtectedBranchError {
+    pub branch: String,
+    pub pattern: String,
+}
+
+pub fn default_protected_branches() -> Vec<String> {
+    DEFAULT_PROTECTED_BRANCHES
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
+
+/// Fails with [`ProtectedBranchError`] when `branch` matches any pattern.
+pub fn ensure_branch_unprotected(branch: &str, patterns: &[String]) -> anyhow::Result<()> {
+    match patterns
+        .iter()
+        .find(|pattern| branch_pattern_matches(pattern, branch))
+    {
+        Some(pattern) => Err(ProtectedBranchError {
+            branch: branch.to_string(),
+            pattern: pattern.clone(),
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Glob match where `*` and `?` do not cross `/`, so `release/*` covers
+/// `release/1.2` but not `release/1.2/hotfix`.
+pub fn branch_pattern_matches(pattern: &str, branch: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return false;
+    }
+    glob_match(pattern.as_bytes(), branch.as_bytes())
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => {
+            let segment_end = text
+                .iter()
+                .position(|byte| *byte == b'/')
+                .unwrap_or(text.len());
+            (0..=segment_end).any(|skip| glob_match(rest, &text[skip..]))
+        }
+        Some((b'?', rest)) => {
+            matches!(text.split_first(), Some((byte, tail)) if *byte != b'/' && glob_match(rest, tail))
+        }
+        Some((expected, rest)) => {
+            matches!(text.split_first(), Some((byte, tail)) if byte == expected && glob_match(rest, tail))
+        }
+    }
+}
+
+/// Counts commits on local `branch` that are reachable from neither HEAD nor
+/// the branch's upstream.
+pub fn count_unmerged_commits(repo: &Repository, branch: &str) -> anyhow::Result<usize> {
+    let local = repo.find_branch(branch, BranchType::Local)?;
+    let Some(tip) = local.get().target() else {
+        return Ok(0);
+    };
+
+    let mut bases: Vec<Oid> = Vec::new();
+    if let Some(head) = repo.head().ok().and_then(|head| head.target()) {
+        bases.push(head);
+    }
+    if let Some(upstream) = local
+        .upstream()
+        .ok()
+        .and_then(|upstream| upstream.get().target())
+    {
+        bases.push(upstream);
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.push(tip)?;
+    for base in bases {
+        walk.hide(base)?;
+    }
+    Ok(walk.count())
+}
+
+fn short_oid(oid: Option<&str>) -> &str {
+    match oid {
+        Some(oid) => &oid[..oid.len().min(8)],
+        None => "(absent)",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{branch_pattern_matches, ensure_branch_unprotected, ProtectedBranchError};
+
+    #[test]
+    fn protected_patterns_match_single_path_segments() {
+        assert!(branch_pattern_matches("main", "main"));
+        assert!(!branch_pattern_matches("main", "main2"));
+        assert!(branch_pattern_matches("release/*", "release/1.2"));
+        assert!(!branch_pattern_matches("release/*", "release/1.2/hotfix"));
+        assert!(!branch_pattern_matches("release/*", "prerelease/1.2"));
+        assert!(branch_pattern_matches("hotfix-?", "hotfix-1"));
+        assert!(branch_pattern_matches("*", "feature"));
+        assert!(!branch_pattern_matches("  ", "feature"));
+    }
+
+    #[test]
+    fn protected_branch_refusal_reports_matching_pattern() {
+        let patterns = vec!["main".to_string(), "release/*".to_string()];
+        assert!(ensure_branch_unprotected("feature/x", &patterns).is_ok());
+
+        let error = ensure_branch_unprotected("release/2.0", &patterns).unwrap_err();
+        let typed = error
+            .downcast_ref::<ProtectedBranchError>()
+            .expect("error should be ProtectedBranchError");
+        assert_eq!(typed.pattern, "release/*");
+    }
+}
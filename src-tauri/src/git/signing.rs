@@ -0,0 +1,186 @@
+use anyhow::Context;
+use git2::Repository;
+
+/// How the repo asks commits to be signed, from `commit.gpgsign`,
+/// `gpg.format` and `user.signingkey`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningConfig {
+    pub sign_by_default: bool,
+    /// `openpgp` (git's default), `ssh` or `x509`.
+    pub format: String,
+    /// OpenPGP falls back to the committer identity when this is unset;
+    /// SSH signing cannot.
+    pub key: Option<String>,
+}
+
+impl SigningConfig {
+    pub fn read(repo_path: &str) -> anyhow::Result<Self> {
+        let repo = Repository::open(repo_path).context("failed to open repository")?;
+        let config = repo
+            .config()
+            .and_then(|mut config| config.snapshot())
+            .context("failed to read git config")?;
+        Ok(Self {
+            sign_by_default: config.get_bool("commit.gpgsign").unwrap_or(false),
+            format: config
+                .get_string("gpg.format")
+                .map(|format| format.trim().to_ascii_lowercase())
+                .ok()
+                .filter(|format| !format.is_empty())
+                .unwrap_or_else(|| "openpgp".to_string()),
+            key: config
+                .get_string("user.signingkey")
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+        })
+    }
+
+    /// Refuses up front what git would only reject after starting to
+    /// commit.
+    pub fn ensure_usable(&self) -> Result<(), CommitSigningError> {
+        if self.format == "ssh" && self.key.is_none() {
+            return Err(CommitSigningError::MissingKey {
+                format: self.label().to_string(),
+                detail: "user.signingkey is not set".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Turns a failed `git commit` into a [`CommitSigningError`] when the
+    /// signing program is what failed.
+    pub fn classify_failure(&self, error: &anyhow::Error) -> Option<CommitSigningError> {
+        let message = error.to_string();
+        let detail = message
+            .strip_prefix("git command failed: ")
+            .unwrap_or(&message)
+            .trim()
+            .to_string();
+        let lower = detail.to_ascii_lowercase();
+        if !SIGNING_FAILURE_MARKERS
+            .iter()
+            .any(|marker| lower.contains(marker))
+        {
+            return None;
+        }
+        let format = self.label().to_string();
+        Some(
+            if MISSING_KEY_MARKERS
+                .iter()
+                .any(|marker| lower.contains(marker))
+            {
+                CommitSigningError::MissingKey { format, detail }
+            } else if PASSPHRASE_MARKERS
+                .iter()
+                .any(|marker| lower.contains(marker))
+            {
+                CommitSigningError::PassphraseUnavailable { format, detail }
+            } else {
+                CommitSigningError::Failed { format, detail }
+            },
+        )
+    }
+
+    fn label(&self) -> &str {
+        match self.format.as_str() {
+            "ssh" => "SSH",
+            "x509" => "X.509",
+            _ => "GPG",
+        }
+    }
+}
+
+/// What git and the signing programs print when signing a commit fails.
+const SIGNING_FAILURE_MARKERS: &[&str] = &[
+    "failed to sign",
+    "signing failed",
+    "cannot run gpg",
+    "cannot run gpgsm",
+    "cannot run ssh-keygen",
+    "user.signingkey",
+    "couldn't load public key",
+    "couldn't sign message",
+];
+const MISSING_KEY_MARKERS: &[&str] = &[
+    "no secret key",
+    "unusable secret key",
+    "no default secret key",
+    "user.signingkey needs to be set",
+    "couldn't load public key",
+];
+const PASSPHRASE_MARKERS: &[&str] = &[
+    "inappropriate ioctl for device",
+    "no pinentry",
+    "operation cancelled",
+    "passphrase",
+    "/dev/tty",
+    "agent refused operation",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CommitSigningError {
+    #[error(
+        "commit signing is on but no {format} signing key is available; set user.signingkey or commit without signing ({detail})"
+    )]
+    MissingKey { format: String, detail: String },
+    #[error(
+        "the {format} signing key needs a passphrase and Panes cannot prompt for one; unlock it in gpg-agent or ssh-agent and retry ({detail})"
+    )]
+    PassphraseUnavailable { format: String, detail: String },
+    #[error("failed to sign the commit with {format}: {detail}")]
+    Failed { format: String, detail: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signing(format: &str) -> SigningConfig {
+        SigningConfig {
+            sign_by_default: true,
+            format: format.to_string(),
+            key: None,
+        }
+    }
+
+    #[test]
+    fn signing_failures_are_told_apart_from_other_commit_errors() {
+        let gpg = signing("openpgp");
+        let classify = |config: &SigningConfig, stderr: &str| {
+            config.classify_failure(&anyhow::anyhow!("git command failed: {stderr}"))
+        };
+
+        assert!(matches!(
+            classify(
+                &gpg,
+                "error: gpg failed to sign the data:\ngpg: signing failed: No secret key"
+            ),
+            Some(CommitSigningError::MissingKey { .. })
+        ));
+        assert!(matches!(
+            classify(
+                &gpg,
+                "error: gpg failed to sign the data:\ngpg: signing failed: Inappropriate ioctl for device"
+            ),
+            Some(CommitSigningError::PassphraseUnavailable { .. })
+        ));
+        assert!(matches!(
+            classify(&gpg, "error: cannot run gpg: No such file or directory"),
+            Some(CommitSigningError::Failed { .. })
+        ));
+        assert!(classify(&gpg, "nothing to commit, working tree clean").is_none());
+
+        let ssh = signing("ssh");
+        let error = ssh.ensure_usable().expect_err("ssh signing needs a key");
+        assert!(error
+            .to_string()
+            .starts_with("commit signing is on but no SSH signing key is available"));
+        assert!(SigningConfig {
+            key: Some("~/.ssh/id_ed25519.pub".to_string()),
+            ..ssh
+        }
+        .ensure_usable()
+        .is_ok());
+        assert!(gpg.ensure_usable().is_ok());
+    }
+}
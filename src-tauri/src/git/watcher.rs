@@ -3,7 +3,7 @@ use std::{
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex as StdMutex},
+    sync::{Arc, Mutex as StdMutex, Weak},
     time::{Duration, Instant},
 };
 
@@ -12,19 +12,64 @@ use notify::{
 };
 use tokio::sync::Mutex;
 
-pub type WatchCallback = Arc<dyn Fn(String) + Send + Sync + 'static>;
+use crate::agent_configs::{is_agent_config_path, AGENT_CONFIG_DIRS};
+
+pub type WatchCallback = Arc<dyn Fn(GitRepoChange) + Send + Sync + 'static>;
+/// Called with the repo path and the changed config path relative to it.
+pub type AgentConfigWatchCallback = Arc<dyn Fn(String, String) + Send + Sync + 'static>;
 type BoxedWatcher = Box<dyn Watcher + Send>;
 
+/// What moved in a repo's git metadata during one debounce window. Working
+/// tree edits are not watched; the Git panel polls for those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitRepoChange {
+    pub repo_path: String,
+    /// `HEAD` or a local branch moved: commits, checkouts, resets.
+    pub head: bool,
+    /// The staging area was rewritten.
+    pub index: bool,
+    /// Remote-tracking branches, tags, the stash or packed refs changed.
+    pub refs: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ChangeScope {
+    head: bool,
+    index: bool,
+    refs: bool,
+}
+
+impl ChangeScope {
+    const ALL: Self = Self {
+        head: true,
+        index: true,
+        refs: true,
+    };
+
+    fn is_empty(self) -> bool {
+        !(self.head || self.index || self.refs)
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.head |= other.head;
+        self.index |= other.index;
+        self.refs |= other.refs;
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct GitWatcherManager {
     watchers: Arc<Mutex<HashMap<String, BoxedWatcher>>>,
-    last_emit: Arc<StdMutex<HashMap<String, Instant>>>,
+    agent_config_watchers: Arc<Mutex<HashMap<String, BoxedWatcher>>>,
 }
 
 impl GitWatcherManager {
+    /// Watches the repo's git metadata and reports changes through
+    /// `callback`, at most once per `debounce` window.
     pub async fn watch_repo(
         &self,
         repo_path: String,
+        debounce: Duration,
         callback: WatchCallback,
     ) -> anyhow::Result<()> {
         let path = PathBuf::from(&repo_path);
@@ -36,41 +81,148 @@ impl GitWatcherManager {
             return Ok(());
         }
 
-        let callback_repo_path = repo_path.clone();
-        let callback_repo_root = path.clone();
-        let last_emit = self.last_emit.clone();
-        let debounce_window = Duration::from_millis(650);
-        let watcher = create_repo_watcher(
-            &path,
-            callback_repo_path.clone(),
-            callback_repo_root,
-            callback,
-            last_emit,
-            debounce_window,
-        )?;
+        let coalescer = Arc::new(ChangeCoalescer::new(repo_path.clone(), debounce, callback));
+        let watcher = create_repo_watcher(&path, coalescer)?;
 
         self.watchers.lock().await.insert(repo_path, watcher);
         Ok(())
     }
+
+    /// Watches the repo root and its agent config dirs, non-recursively, for
+    /// edits to known Codex/Claude project files. A config dir created after
+    /// the watch starts is only followed once the app restarts.
+    pub async fn watch_agent_configs(
+        &self,
+        repo_path: String,
+        callback: AgentConfigWatchCallback,
+    ) -> anyhow::Result<()> {
+        let root = PathBuf::from(&repo_path);
+        if !root.is_dir() {
+            return Ok(());
+        }
+
+        if self
+            .agent_config_watchers
+            .lock()
+            .await
+            .contains_key(&repo_path)
+        {
+            return Ok(());
+        }
+
+        let handler = make_agent_config_event_handler(
+            repo_path.clone(),
+            root.clone(),
+            callback,
+            Duration::from_millis(650),
+        );
+        let mut watcher = recommended_watcher(handler)?;
+        watcher.watch(&root, RecursiveMode::NonRecursive)?;
+        for dir in AGENT_CONFIG_DIRS {
+            let dir = root.join(dir);
+            if dir.is_dir() {
+                watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+            }
+        }
+
+        self.agent_config_watchers
+            .lock()
+            .await
+            .insert(repo_path, Box::new(watcher));
+        Ok(())
+    }
+
+    /// Stops the git and agent config watchers for `repo_path`; dropping a
+    /// watcher unregisters it. True when either was running.
+    pub async fn unwatch_repo(&self, repo_path: &str) -> bool {
+        let git = self.watchers.lock().await.remove(repo_path).is_some();
+        let configs = self
+            .agent_config_watchers
+            .lock()
+            .await
+            .remove(repo_path)
+            .is_some();
+        git || configs
+    }
+}
+
+/// Collects one repo's changes and reports them together `window` after
+/// the first change of a burst, so an agent turn touching many files or the
+/// `index.lock` dance of a commit reaches the UI as a single event.
+struct ChangeCoalescer {
+    repo_path: String,
+    window: Duration,
+    callback: WatchCallback,
+    pending: StdMutex<Option<ChangeScope>>,
+}
+
+impl ChangeCoalescer {
+    fn new(repo_path: String, window: Duration, callback: WatchCallback) -> Self {
+        Self {
+            repo_path,
+            window,
+            callback,
+            pending: StdMutex::new(None),
+        }
+    }
+
+    fn record(self: &Arc<Self>, scope: ChangeScope) {
+        {
+            let mut pending = self
+                .pending
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if let Some(pending) = pending.as_mut() {
+                pending.merge(scope);
+                return;
+            }
+            *pending = Some(scope);
+        }
+
+        // The flush holds a weak handle so an unwatched repo reports nothing.
+        let coalescer = Arc::downgrade(self);
+        let window = self.window;
+        let spawned = std::thread::Builder::new()
+            .name("git-watch-debounce".to_string())
+            .spawn(move || {
+                std::thread::sleep(window);
+                if let Some(coalescer) = Weak::upgrade(&coalescer) {
+                    coalescer.flush();
+                }
+            });
+        if let Err(error) = spawned {
+            log::warn!(
+                "failed to debounce git changes for {}: {error}",
+                self.repo_path
+            );
+            self.flush();
+        }
+    }
+
+    fn flush(&self) {
+        let scope = self
+            .pending
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+        if let Some(scope) = scope {
+            (self.callback)(GitRepoChange {
+                repo_path: self.repo_path.clone(),
+                head: scope.head,
+                index: scope.index,
+                refs: scope.refs,
+            });
+        }
+    }
 }
 
 fn create_repo_watcher(
     path: &PathBuf,
-    callback_repo_path: String,
-    callback_repo_root: PathBuf,
-    callback: WatchCallback,
-    last_emit: Arc<StdMutex<HashMap<String, Instant>>>,
-    debounce_window: Duration,
+    coalescer: Arc<ChangeCoalescer>,
 ) -> notify::Result<BoxedWatcher> {
     let watch_paths = git_watch_paths(path);
-    let event_handler = make_event_handler(
-        callback_repo_path.clone(),
-        callback_repo_root.clone(),
-        watch_paths.clone(),
-        Arc::clone(&callback),
-        last_emit.clone(),
-        debounce_window,
-    );
+    let event_handler =
+        make_event_handler(path.clone(), watch_paths.clone(), Arc::clone(&coalescer));
     let mut watcher = recommended_watcher(event_handler)?;
     match watch_git_paths(&mut watcher, &watch_paths) {
         Ok(()) => Ok(Box::new(watcher)),
@@ -80,14 +232,7 @@ fn create_repo_watcher(
                 format_watch_paths(&watch_paths),
                 error
             );
-            let poll_handler = make_event_handler(
-                callback_repo_path,
-                callback_repo_root,
-                watch_paths.clone(),
-                callback,
-                last_emit,
-                debounce_window,
-            );
+            let poll_handler = make_event_handler(path.clone(), watch_paths.clone(), coalescer);
             let mut poll_watcher = PollWatcher::new(
                 poll_handler,
                 Config::default().with_poll_interval(Duration::from_secs(2)),
@@ -193,39 +338,81 @@ fn git_watch_recursive_mode(path: &PathBuf) -> RecursiveMode {
 }
 
 fn make_event_handler(
-    callback_repo_path: String,
-    callback_repo_root: PathBuf,
+    repo_root: PathBuf,
     git_metadata_roots: Vec<PathBuf>,
-    callback: WatchCallback,
-    last_emit: Arc<StdMutex<HashMap<String, Instant>>>,
-    debounce_window: Duration,
+    coalescer: Arc<ChangeCoalescer>,
 ) -> impl Fn(notify::Result<Event>) + Send + 'static {
     move |result: notify::Result<Event>| {
         let Ok(event) = result else {
             return;
         };
 
-        if !should_emit_repo_change_event(&event, &callback_repo_root, &git_metadata_roots) {
-            return;
+        let scope = repo_change_scope(&event, &repo_root, &git_metadata_roots);
+        if !scope.is_empty() {
+            coalescer.record(scope);
         }
+    }
+}
 
-        let now = Instant::now();
-        let should_emit = if let Ok(mut guard) = last_emit.lock() {
-            match guard.get(&callback_repo_path) {
-                Some(previous) if now.duration_since(*previous) < debounce_window => false,
-                _ => {
-                    guard.insert(callback_repo_path.clone(), now);
-                    true
-                }
-            }
-        } else {
-            true
+fn make_agent_config_event_handler(
+    callback_repo_path: String,
+    repo_root: PathBuf,
+    callback: AgentConfigWatchCallback,
+    debounce_window: Duration,
+) -> impl Fn(notify::Result<Event>) + Send + 'static {
+    // Events may arrive with the resolved path (e.g. /private/var on macOS).
+    let roots = dedupe_paths(vec![
+        repo_root.clone(),
+        canonicalize_existing_path(repo_root),
+    ]);
+    let last_emit = StdMutex::new(HashMap::<String, Instant>::new());
+    move |result: notify::Result<Event>| {
+        let Ok(event) = result else {
+            return;
         };
 
-        if should_emit {
-            callback(callback_repo_path.clone());
+        for config_path in agent_config_changes(&event, &roots) {
+            let now = Instant::now();
+            let should_emit = match last_emit.lock() {
+                Ok(mut guard) => match guard.get(&config_path) {
+                    Some(previous) if now.duration_since(*previous) < debounce_window => false,
+                    _ => {
+                        guard.insert(config_path.clone(), now);
+                        true
+                    }
+                },
+                Err(_) => true,
+            };
+            if should_emit {
+                callback(callback_repo_path.clone(), config_path);
+            }
+        }
+    }
+}
+
+/// Known agent config paths touched by `event`, relative to the repo root.
+fn agent_config_changes(event: &Event, repo_roots: &[PathBuf]) -> Vec<String> {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return Vec::new();
+    }
+
+    let mut changes = Vec::new();
+    for path in &event.paths {
+        let Some(relative) = repo_roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+        else {
+            continue;
+        };
+        if !is_agent_config_path(relative) {
+            continue;
+        }
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if !changes.contains(&relative) {
+            changes.push(relative);
         }
     }
+    changes
 }
 
 fn should_fallback_to_polling(error: &notify::Error) -> bool {
@@ -251,7 +438,7 @@ fn should_fallback_to_polling(error: &notify::Error) -> bool {
     }
 }
 
-/// Returns true if a `.git/` sub-path is on the high-signal allowlist.
+/// Scope of a `.git/` sub-path on the high-signal allowlist.
 ///
 /// Allowed:
 ///   .git/HEAD              — branch pointer / detached head
@@ -263,111 +450,228 @@ fn should_fallback_to_polling(error: &notify::Error) -> bool {
 ///   .git/FETCH_HEAD        — fetch results for remote branch refresh
 ///   .git/packed-refs       — packed branch/tag refs after maintenance/fetch
 ///
-/// Everything else (objects/, logs/, ORIG_HEAD, config, hooks/, …)
-/// is dropped to avoid noisy refreshes during fetch/pull/push.
-fn is_allowed_git_internal_path(relative: &std::path::Path) -> bool {
+/// Everything else (objects/, logs/, ORIG_HEAD, config, hooks/, index.lock,
+/// …) is dropped to avoid noisy refreshes during fetch/pull/push/commit.
+fn git_internal_path_scope(relative: &std::path::Path) -> ChangeScope {
     let inside = match relative.strip_prefix(".git") {
         Ok(p) => p,
-        Err(_) => return false,
+        Err(_) => return ChangeScope::default(),
     };
 
     if inside.components().next().is_none() {
-        return true;
+        return ChangeScope::ALL;
     }
 
-    is_allowed_git_metadata_path(inside)
+    git_metadata_path_scope(inside)
 }
 
-fn is_allowed_git_metadata_path(relative: &std::path::Path) -> bool {
+fn git_metadata_path_scope(relative: &std::path::Path) -> ChangeScope {
+    let head = ChangeScope {
+        head: true,
+        ..ChangeScope::default()
+    };
+    let refs = ChangeScope {
+        refs: true,
+        ..ChangeScope::default()
+    };
     let mut components = relative.components();
     match components.next() {
         // .git/HEAD (exact)
         Some(std::path::Component::Normal(name)) if name == OsStr::new("HEAD") => {
-            components.next().is_none()
+            exact(components, head)
         }
         // .git/FETCH_HEAD (exact)
         Some(std::path::Component::Normal(name)) if name == OsStr::new("FETCH_HEAD") => {
-            components.next().is_none()
+            exact(components, refs)
         }
         // .git/index (exact)
-        Some(std::path::Component::Normal(name)) if name == OsStr::new("index") => {
-            components.next().is_none()
-        }
+        Some(std::path::Component::Normal(name)) if name == OsStr::new("index") => exact(
+            components,
+            ChangeScope {
+                index: true,
+                ..ChangeScope::default()
+            },
+        ),
         // .git/packed-refs (exact)
         Some(std::path::Component::Normal(name)) if name == OsStr::new("packed-refs") => {
-            components.next().is_none()
+            exact(components, refs)
         }
         // .git/refs/...
         Some(std::path::Component::Normal(name)) if name == OsStr::new("refs") => {
             match components.next() {
                 // .git/refs/heads/... (any depth)
-                Some(std::path::Component::Normal(n)) if n == OsStr::new("heads") => true,
+                Some(std::path::Component::Normal(n)) if n == OsStr::new("heads") => head,
                 // .git/refs/remotes/... (any depth)
-                Some(std::path::Component::Normal(n)) if n == OsStr::new("remotes") => true,
+                Some(std::path::Component::Normal(n)) if n == OsStr::new("remotes") => refs,
                 // .git/refs/tags/... (any depth)
-                Some(std::path::Component::Normal(n)) if n == OsStr::new("tags") => true,
+                Some(std::path::Component::Normal(n)) if n == OsStr::new("tags") => refs,
                 // .git/refs/stash (exact)
                 Some(std::path::Component::Normal(n)) if n == OsStr::new("stash") => {
-                    components.next().is_none()
+                    exact(components, refs)
                 }
-                _ => false,
+                _ => ChangeScope::default(),
             }
         }
-        _ => false,
+        _ => ChangeScope::default(),
     }
 }
 
-fn should_emit_repo_change_event(
+fn exact(mut rest: std::path::Components<'_>, scope: ChangeScope) -> ChangeScope {
+    if rest.next().is_none() {
+        scope
+    } else {
+        ChangeScope::default()
+    }
+}
+
+fn repo_change_scope(
     event: &Event,
     repo_root: &PathBuf,
     git_metadata_roots: &[PathBuf],
-) -> bool {
-    if event.paths.is_empty() {
-        return false;
-    }
+) -> ChangeScope {
+    let mut scope = ChangeScope::default();
 
     // Access-only events create noise and do not represent content changes.
     if matches!(event.kind, EventKind::Access(_)) {
-        return false;
+        return scope;
     }
 
-    // Emit only for high-signal git metadata changes. Working tree edits are
+    // Report only high-signal git metadata changes. Working tree edits are
     // picked up by the active Git-panel poller, which avoids recursively
     // watching large ignored trees like node_modules.
-    event.paths.iter().any(|path| {
+    for path in &event.paths {
         if let Ok(relative) = path.strip_prefix(repo_root) {
             let mut components = relative.components();
             if matches!(
                 components.next(),
                 Some(std::path::Component::Normal(name)) if name == OsStr::new(".git")
             ) {
-                return is_allowed_git_internal_path(relative);
+                scope.merge(git_internal_path_scope(relative));
+                continue;
             }
         }
 
         for git_metadata_root in git_metadata_roots {
             if let Ok(relative) = path.strip_prefix(git_metadata_root) {
                 if relative.components().next().is_none() {
-                    return true;
-                }
-                if is_allowed_git_metadata_path(relative) {
-                    return true;
+                    scope.merge(ChangeScope::ALL);
+                } else {
+                    scope.merge(git_metadata_path_scope(relative));
                 }
             }
         }
+    }
 
-        false
-    })
+    scope
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const DEBOUNCE: Duration = Duration::from_millis(300);
+
     fn standard_git_metadata_roots() -> Vec<PathBuf> {
         vec![PathBuf::from("/tmp/repo/.git")]
     }
 
+    fn modify_event(paths: &[&str]) -> Event {
+        Event {
+            kind: EventKind::Modify(notify::event::ModifyKind::Any),
+            paths: paths.iter().map(PathBuf::from).collect(),
+            attrs: Default::default(),
+        }
+    }
+
+    #[test]
+    fn bursts_of_changes_are_reported_once_with_their_combined_scope() {
+        let changes = Arc::new(StdMutex::new(Vec::new()));
+        let sink = Arc::clone(&changes);
+        let callback: WatchCallback = Arc::new(move |change| sink.lock().unwrap().push(change));
+        let coalescer = Arc::new(ChangeCoalescer::new(
+            "/tmp/repo".to_string(),
+            Duration::from_millis(50),
+            callback,
+        ));
+        let repo_root = PathBuf::from("/tmp/repo");
+        let roots = standard_git_metadata_roots();
+
+        // A commit: the index is rewritten through index.lock, then the
+        // branch ref advances.
+        for paths in [
+            &["/tmp/repo/.git/index.lock"][..],
+            &["/tmp/repo/.git/index.lock", "/tmp/repo/.git/index"],
+            &["/tmp/repo/.git/refs/heads/main.lock"],
+            &["/tmp/repo/.git/refs/heads/main"],
+            &["/tmp/repo/.git/objects/ab/cdef"],
+        ] {
+            let scope = repo_change_scope(&modify_event(paths), &repo_root, &roots);
+            if !scope.is_empty() {
+                coalescer.record(scope);
+            }
+        }
+        std::thread::sleep(Duration::from_millis(300));
+
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![GitRepoChange {
+                repo_path: "/tmp/repo".to_string(),
+                head: true,
+                index: true,
+                refs: false,
+            }]
+        );
+
+        coalescer.record(repo_change_scope(
+            &modify_event(&["/tmp/repo/.git/FETCH_HEAD"]),
+            &repo_root,
+            &roots,
+        ));
+        drop(coalescer);
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(
+            changes.lock().unwrap().len(),
+            1,
+            "unwatched repos stay quiet"
+        );
+    }
+
+    #[test]
+    fn index_lock_churn_alone_is_not_a_change() {
+        let scope = repo_change_scope(
+            &modify_event(&["/tmp/repo/.git/index.lock"]),
+            &PathBuf::from("/tmp/repo"),
+            &standard_git_metadata_roots(),
+        );
+        assert!(scope.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unwatched_repos_can_be_watched_again() {
+        let root = std::env::temp_dir().join(format!("panes-watcher-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join(".git")).expect("create repo");
+        let repo_path = root.to_string_lossy().to_string();
+        let manager = GitWatcherManager::default();
+        let callback: WatchCallback = Arc::new(|_| {});
+
+        manager
+            .watch_repo(repo_path.clone(), DEBOUNCE, Arc::clone(&callback))
+            .await
+            .expect("watch");
+        assert!(manager.watchers.lock().await.contains_key(&repo_path));
+
+        assert!(manager.unwatch_repo(&repo_path).await);
+        assert!(!manager.watchers.lock().await.contains_key(&repo_path));
+        assert!(!manager.unwatch_repo(&repo_path).await);
+
+        manager
+            .watch_repo(repo_path.clone(), DEBOUNCE, callback)
+            .await
+            .expect("watch again");
+        assert!(manager.watchers.lock().await.contains_key(&repo_path));
+        let _ = fs::remove_dir_all(root);
+    }
+
     #[cfg(target_os = "linux")]
     #[test]
     fn linux_inotify_limit_errors_enable_poll_fallback() {
@@ -379,6 +683,32 @@ mod tests {
         assert!(should_fallback_to_polling(&io_error));
     }
 
+    #[test]
+    fn agent_config_changes_only_report_known_files() {
+        let roots = vec![PathBuf::from("/tmp/repo")];
+        let event = Event {
+            kind: EventKind::Modify(notify::event::ModifyKind::Any),
+            paths: vec![
+                PathBuf::from("/tmp/repo/AGENTS.md"),
+                PathBuf::from("/tmp/repo/.claude/settings.json"),
+                PathBuf::from("/tmp/repo/README.md"),
+                PathBuf::from("/tmp/repo/docs/CLAUDE.md"),
+            ],
+            attrs: Default::default(),
+        };
+        assert_eq!(
+            agent_config_changes(&event, &roots),
+            vec!["AGENTS.md".to_string(), ".claude/settings.json".to_string()]
+        );
+
+        let access = Event {
+            kind: EventKind::Access(notify::event::AccessKind::Any),
+            paths: vec![PathBuf::from("/tmp/repo/AGENTS.md")],
+            attrs: Default::default(),
+        };
+        assert!(agent_config_changes(&access, &roots).is_empty());
+    }
+
     #[test]
     fn ignores_access_only_events() {
         let event = Event {
@@ -387,11 +717,12 @@ mod tests {
             attrs: Default::default(),
         };
 
-        assert!(!should_emit_repo_change_event(
+        assert!(repo_change_scope(
             &event,
             &PathBuf::from("/tmp/repo"),
             &standard_git_metadata_roots(),
-        ));
+        )
+        .is_empty());
     }
 
     #[test]
@@ -402,11 +733,12 @@ mod tests {
             attrs: Default::default(),
         };
 
-        assert!(should_emit_repo_change_event(
+        assert!(!repo_change_scope(
             &event,
             &PathBuf::from("/tmp/repo"),
             &standard_git_metadata_roots(),
-        ));
+        )
+        .is_empty());
     }
 
     #[test]
@@ -417,11 +749,12 @@ mod tests {
             attrs: Default::default(),
         };
 
-        assert!(!should_emit_repo_change_event(
+        assert!(repo_change_scope(
             &event,
             &PathBuf::from("/tmp/repo"),
             &standard_git_metadata_roots(),
-        ));
+        )
+        .is_empty());
     }
 
     #[test]
@@ -467,11 +800,10 @@ mod tests {
             attrs: Default::default(),
         };
 
-        assert!(should_emit_repo_change_event(
-            &event,
-            &PathBuf::from("/tmp/repo"),
-            &[git_metadata_root],
-        ));
+        assert!(
+            !repo_change_scope(&event, &PathBuf::from("/tmp/repo"), &[git_metadata_root],)
+                .is_empty()
+        );
     }
 
     #[test]
@@ -484,11 +816,12 @@ mod tests {
             attrs: Default::default(),
         };
 
-        assert!(should_emit_repo_change_event(
+        assert!(!repo_change_scope(
             &event,
             &PathBuf::from("/tmp/repo"),
             &[worktree_gitdir, common_gitdir],
-        ));
+        )
+        .is_empty());
     }
 
     #[test]
@@ -504,15 +837,17 @@ mod tests {
             attrs: Default::default(),
         };
 
-        assert!(should_emit_repo_change_event(
+        assert!(!repo_change_scope(
             &fetch_head,
             &PathBuf::from("/tmp/repo"),
             &standard_git_metadata_roots(),
-        ));
-        assert!(should_emit_repo_change_event(
+        )
+        .is_empty());
+        assert!(!repo_change_scope(
             &packed_refs,
             &PathBuf::from("/tmp/repo"),
             &standard_git_metadata_roots(),
-        ));
+        )
+        .is_empty());
     }
 }
@@ -1,30 +1,71 @@
 use anyhow::Context;
+use git2::{BranchType, Repository};
 use std::path::Path;
 
 use super::cli_fallback::run_git;
 use crate::models::GitWorktreeDto;
 
-/// Creates a new worktree at `worktree_path` on a new branch `branch_name`,
-/// branching from `base_ref` (defaults to HEAD if None).
+/// The branch is already checked out in another worktree; git allows a
+/// branch in only one worktree at a time.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("branch `{branch}` is already checked out at {worktree_path}; pick another branch or remove that worktree first")]
+pub struct WorktreeBranchInUseError {
+    pub branch: String,
+    pub worktree_path: String,
+}
+
+/// Creates a new worktree at `worktree_path` checked out on `branch_name`.
+/// An existing local branch is checked out as is; otherwise the branch is
+/// created from `base_ref` (defaults to HEAD if None).
 pub fn add_worktree(
     repo_path: &str,
     worktree_path: &str,
     branch_name: &str,
     base_ref: Option<&str>,
 ) -> anyhow::Result<GitWorktreeDto> {
-    let mut args = vec!["worktree", "add", "-b", branch_name, worktree_path];
-    if let Some(base) = base_ref {
-        args.push(base);
+    let branch_exists = Repository::open(repo_path)
+        .context("failed to open repository")?
+        .find_branch(branch_name, BranchType::Local)
+        .is_ok();
+    if branch_exists {
+        if let Some(existing) = list_worktrees(repo_path)?
+            .into_iter()
+            .find(|w| w.branch.as_deref() == Some(branch_name))
+        {
+            return Err(WorktreeBranchInUseError {
+                branch: branch_name.to_string(),
+                worktree_path: existing.path,
+            }
+            .into());
+        }
+    }
+
+    let mut args = vec!["worktree", "add"];
+    if branch_exists {
+        args.extend([worktree_path, branch_name]);
+    } else {
+        args.extend(["-b", branch_name, worktree_path]);
+        if let Some(base) = base_ref {
+            args.push(base);
+        }
     }
     run_git(repo_path, &args).context("failed to add worktree")?;
 
     // Return info about the newly created worktree
-    let all = list_worktrees(repo_path)?;
-    all.into_iter()
-        .find(|w| worktree_paths_match(&w.path, worktree_path))
+    find_worktree(repo_path, worktree_path)?
         .ok_or_else(|| anyhow::anyhow!("worktree created but not found in listing"))
 }
 
+/// Finds the worktree of `repo_path` checked out at `worktree_path`.
+pub fn find_worktree(
+    repo_path: &str,
+    worktree_path: &str,
+) -> anyhow::Result<Option<GitWorktreeDto>> {
+    Ok(list_worktrees(repo_path)?
+        .into_iter()
+        .find(|w| worktree_paths_match(&w.path, worktree_path)))
+}
+
 fn worktree_paths_match(listed_path: &str, requested_path: &str) -> bool {
     if listed_path == requested_path {
         return true;
@@ -141,3 +182,69 @@ pub fn prune_worktrees(repo_path: &str) -> anyhow::Result<()> {
     run_git(repo_path, &["worktree", "prune"]).context("failed to prune worktrees")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use uuid::Uuid;
+
+    use super::{add_worktree, find_worktree, run_git, WorktreeBranchInUseError};
+
+    #[test]
+    fn worktrees_reuse_existing_branches_and_refuse_checked_out_ones() {
+        let _env_guard = crate::process_utils::test_env_lock()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let root = std::env::temp_dir().join(format!("panes-worktree-test-{}", Uuid::new_v4()));
+        let repo = root.join("repo");
+        fs::create_dir_all(&repo).expect("create repo dir");
+        let repo_path = repo.to_str().expect("utf-8 temp path");
+        let git = |args: &[&str]| run_git(repo_path, args).expect("git");
+        git(&["init", "--initial-branch=main"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        fs::write(repo.join("README.md"), "hello\n").expect("write readme");
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "init"]);
+        git(&["branch", "feature"]);
+
+        let feature_path = root.join("feature");
+        let feature = feature_path.to_str().unwrap();
+        let created = add_worktree(repo_path, feature, "feature", None).expect("existing branch");
+        assert_eq!(created.branch.as_deref(), Some("feature"));
+        assert!(!created.is_main);
+        assert_eq!(
+            find_worktree(repo_path, feature)
+                .expect("list worktrees")
+                .map(|w| w.branch),
+            Some(Some("feature".to_string()))
+        );
+
+        let fresh_path = root.join("fresh");
+        let fresh = add_worktree(
+            repo_path,
+            fresh_path.to_str().unwrap(),
+            "fresh",
+            Some("main"),
+        )
+        .expect("new branch");
+        assert_eq!(fresh.branch.as_deref(), Some("fresh"));
+
+        let error = add_worktree(
+            repo_path,
+            root.join("again").to_str().unwrap(),
+            "main",
+            None,
+        )
+        .expect_err("main is checked out in the repo itself");
+        let in_use = error
+            .downcast_ref::<WorktreeBranchInUseError>()
+            .expect("typed error");
+        assert_eq!(in_use.branch, "main");
+        assert!(error.to_string().contains("already checked out at"));
+        assert!(!root.join("again").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,13 +1,19 @@
+mod agent_configs;
+mod blocks;
 mod commands;
 mod config;
+mod conversation_export;
+mod conversation_import;
 mod db;
 mod engines;
+mod file_watcher;
 mod fs_ops;
 mod git;
 #[cfg(any(target_os = "linux", test))]
 mod linux_appimage;
 mod linux_webkit;
 mod locale;
+mod metrics;
 mod models;
 mod path_utils;
 mod power;
@@ -31,7 +37,7 @@ use git::watcher::GitWatcherManager;
 #[cfg(target_os = "macos")]
 use locale::native_strings;
 use locale::resolve_app_locale;
-use models::{EngineRuntimeUpdatedDto, ThreadDto, ThreadStatusDto};
+use models::{EngineProtocolGapDto, EngineRuntimeUpdatedDto, ThreadDto, ThreadStatusDto};
 use power::KeepAwakeManager;
 use state::{AppState, TurnManager};
 #[cfg(target_os = "macos")]
@@ -48,14 +54,30 @@ pub fn run() {
     env_logger::init();
     linux_webkit::apply_webkit_display_workarounds();
 
-    let db = Database::init().expect("failed to initialize database");
+    let app_config = AppConfig::load_or_create().expect("failed to load config");
+    let db = Database::init(db::PoolLimits {
+        max_connections: app_config.database.max_connections,
+        acquire_timeout: std::time::Duration::from_millis(app_config.database.acquire_timeout_ms),
+    })
+    .expect("failed to initialize database");
     match db::threads::reconcile_runtime_state(&db) {
         Ok(report) => {
-            if report.messages_marked_interrupted > 0 || report.thread_status_updates > 0 {
+            if report.messages_marked_interrupted > 0
+                || report.thread_status_updates > 0
+                || report.queued_messages_held > 0
+            {
                 log::info!(
-                    "runtime recovery applied: interrupted_messages={}, thread_status_updates={}",
+                    "runtime recovery applied: interrupted_messages={}, thread_status_updates={}, queued_messages_held={}",
                     report.messages_marked_interrupted,
-                    report.thread_status_updates
+                    report.thread_status_updates,
+                    report.queued_messages_held
+                );
+            }
+            if !report.stale_empty_thread_ids.is_empty() {
+                log::info!(
+                    "runtime recovery found {} empty threads older than {}h; cleanup_empty_threads removes them",
+                    report.stale_empty_thread_ids.len(),
+                    db::threads::STALE_EMPTY_THREAD_HOURS
                 );
             }
         }
@@ -63,7 +85,6 @@ pub fn run() {
             log::warn!("runtime recovery failed, continuing startup: {error}");
         }
     }
-    let app_config = AppConfig::load_or_create().expect("failed to load config");
     let app_locale = resolve_app_locale(app_config.general.locale.as_deref());
     let keep_awake = Arc::new(KeepAwakeManager::new());
     if let Err(error) = keep_awake.reclaim_stale_helpers() {
@@ -80,12 +101,19 @@ pub fn run() {
     let _ =
         db::workspaces::ensure_default_workspace(&db).expect("failed to ensure default workspace");
 
+    let engines = Arc::new(EngineManager::with_timeouts(&app_config.engine_timeouts));
+    engines.set_codex_transport_routing(&app_config.codex);
+    engines.set_codex_model_cache_ttl(&app_config.codex);
+    engines.set_codex_attachment_limits(&app_config);
+    engines.set_api_direct_config(&app_config.api_direct);
+
     let app_state = AppState {
         db,
         config: Arc::new(app_config),
         config_write_lock: Arc::new(tokio::sync::Mutex::new(())),
-        engines: Arc::new(EngineManager::new()),
+        engines,
         git_watchers: Arc::new(GitWatcherManager::default()),
+        file_watchers: Arc::new(file_watcher::FileWatcherManager::default()),
         terminals: Arc::new(TerminalManager::default()),
         notifications: Arc::new(terminal_notifications::TerminalNotificationManager::default()),
         keep_awake,
@@ -162,6 +190,30 @@ pub fn run() {
             }
             state.engines.set_resource_dir(resource_dir);
             tauri::async_runtime::spawn(run_codex_runtime_bridge(handle.clone(), state.clone()));
+            tauri::async_runtime::spawn(run_blob_sweeper(state.db.clone()));
+            tauri::async_runtime::spawn(metrics::run_weekly_summary(
+                handle.clone(),
+                state.db.clone(),
+            ));
+            {
+                let terminals = state.terminals.clone();
+                tauri::async_runtime::spawn(async move {
+                    terminals.prune_stale_scrollback().await;
+                });
+                let retention_days = state.config.debug.turn_event_log_retention_days;
+                tauri::async_runtime::spawn_blocking(move || {
+                    let removed = engines::turn_event_log::prune_turn_event_logs(
+                        &engines::turn_event_log::turn_event_log_dir(),
+                        retention_days,
+                    );
+                    if removed > 0 {
+                        log::info!("pruned {removed} turn event log(s)");
+                    }
+                });
+                tauri::async_runtime::spawn(
+                    state.terminals.clone().run_idle_reaper(handle.clone()),
+                );
+            }
             app.on_menu_event(move |_app, event| {
                 let id = event.id().as_ref();
                 match id {
@@ -190,20 +242,37 @@ pub fn run() {
             commands::chat::save_pasted_image_attachment,
             commands::chat::read_attachment_preview,
             commands::chat::send_message,
+            commands::chat::send_message_multi,
+            commands::chat::queue_message,
+            commands::chat::list_queued_messages,
+            commands::chat::cancel_queued_message,
+            commands::chat::regenerate_message,
+            commands::chat::edit_user_message,
+            commands::chat::retry_last_turn,
+            commands::chat::delete_message,
             commands::chat::start_codex_review,
             commands::chat::steer_message,
             commands::chat::cancel_turn,
             commands::chat::respond_to_approval,
             commands::chat::get_thread_messages,
             commands::chat::get_thread_messages_window,
+            commands::chat::get_thread_messages_page,
             commands::chat::get_message_blocks,
+            commands::chat::get_message_storage_report,
+            commands::chat::get_turn_diagnostics,
+            commands::chat::get_turn_event_log,
+            commands::chat::get_thread_model_history,
             commands::chat::get_action_output,
             commands::chat::search_messages,
             commands::workspace::open_workspace,
             commands::workspace::list_workspaces,
             commands::workspace::list_archived_workspaces,
             commands::workspace::get_repos,
+            commands::workspace::detect_agent_configs,
+            commands::workspace::adopt_agent_config,
             commands::workspace::set_repo_trust_level,
+            commands::workspace::get_repo_protected_branches,
+            commands::workspace::set_repo_protected_branches,
             commands::workspace::set_repo_git_active,
             commands::workspace::set_workspace_git_active_repos,
             commands::workspace::has_workspace_git_selection,
@@ -221,13 +290,24 @@ pub fn run() {
             commands::workspace::list_workspace_dirs,
             commands::workspace::get_workspace_file_tree_page,
             commands::workspace::search_workspace_files,
+            commands::git::git_preflight,
             commands::git::get_git_status,
             commands::git::get_file_diff,
+            commands::git::list_conflicts,
+            commands::git::resolve_conflict,
             commands::git::get_git_file_compare,
             commands::git::stage_files,
             commands::git::unstage_files,
+            commands::git::stage_hunk,
+            commands::git::unstage_hunk,
+            commands::git::stage_hunks,
+            commands::git::unstage_hunks,
             commands::git::discard_files,
             commands::git::commit,
+            commands::git::generate_commit_message,
+            commands::git::get_workspace_git_overview,
+            commands::git::stage_files_multi,
+            commands::git::commit_multi,
             commands::git::soft_reset_last_commit,
             commands::git::fetch_git,
             commands::git::pull_git,
@@ -237,6 +317,9 @@ pub fn run() {
             commands::git::create_git_branch,
             commands::git::rename_git_branch,
             commands::git::delete_git_branch,
+            commands::git::squash_commits,
+            commands::git::reword_commit,
+            commands::git::list_git_operation_logs,
             commands::git::list_git_commits,
             commands::git::get_commit_diff,
             commands::git::list_git_stashes,
@@ -254,6 +337,7 @@ pub fn run() {
             commands::git::add_git_remote,
             commands::git::remove_git_remote,
             commands::git::rename_git_remote,
+            commands::app::get_database_pool_diagnostics,
             commands::app::get_terminal_accelerated_rendering,
             commands::app::set_terminal_accelerated_rendering,
             commands::app::get_terminal_font_size,
@@ -263,6 +347,11 @@ pub fn run() {
             commands::app::get_agent_notification_settings,
             commands::app::set_chat_notifications_enabled,
             commands::app::set_terminal_notifications_enabled,
+            commands::app::get_local_metrics,
+            commands::app::get_weekly_usage_summary_enabled,
+            commands::app::set_weekly_usage_summary_enabled,
+            commands::app::get_terminal_scrollback_persistence,
+            commands::app::set_terminal_scrollback_persistence,
             commands::app::install_terminal_notification_integration_command,
             commands::app::set_notification_sound,
             commands::app::preview_notification_sound,
@@ -271,6 +360,8 @@ pub fn run() {
             commands::files::read_file,
             commands::files::resolve_editor_file_reference,
             commands::files::write_file,
+            commands::files::watch_file,
+            commands::files::unwatch_file,
             commands::files::create_file,
             commands::files::create_dir,
             commands::files::rename_path,
@@ -289,39 +380,63 @@ pub fn run() {
             commands::engines::run_engine_check,
             commands::threads::list_threads,
             commands::threads::list_archived_threads,
+            commands::threads::get_thread_timeline,
+            commands::threads::get_thread_usage,
+            commands::threads::export_thread,
+            commands::threads::export_thread_markdown,
+            commands::usage::get_usage_summary,
+            commands::usage::get_usage_limits,
+            commands::threads::import_conversation,
             commands::threads::list_codex_remote_threads,
             commands::threads::attach_codex_remote_thread,
             commands::threads::list_opencode_remote_sessions,
             commands::threads::attach_opencode_remote_session,
             commands::threads::create_thread,
             commands::threads::rename_thread,
+            commands::threads::open_thread_in_cli,
+            commands::threads::detach_thread_from_cli,
             commands::threads::confirm_workspace_thread,
             commands::threads::set_thread_reasoning_effort,
             commands::threads::set_thread_execution_policy,
             commands::threads::set_thread_codex_config,
+            commands::threads::set_thread_custom_instructions,
             commands::threads::set_thread_opencode_config,
+            commands::threads::set_thread_worktree,
             commands::threads::archive_thread,
             commands::threads::restore_thread,
             commands::threads::sync_thread_from_engine,
             commands::threads::fork_codex_thread,
+            commands::threads::fork_thread,
             commands::threads::rollback_codex_thread,
             commands::threads::compact_codex_thread,
             commands::threads::delete_thread,
+            commands::threads::cleanup_empty_threads,
             commands::terminal::terminal_create_session,
             commands::terminal::terminal_write,
             commands::terminal::terminal_write_bytes,
             commands::terminal::terminal_resize,
+            commands::terminal::terminal_signal,
+            commands::terminal::terminal_inject_path,
             commands::terminal::terminal_close_session,
             commands::terminal::terminal_close_workspace_sessions,
+            commands::terminal::terminal_restore_scrollback,
+            commands::terminal::terminal_get_scrollback,
+            commands::terminal::terminal_search_output,
+            commands::terminal::terminal_get_transcript_path,
             commands::terminal::terminal_list_sessions,
+            commands::terminal::terminal_session_limits,
             commands::terminal::terminal_get_renderer_diagnostics,
+            commands::terminal::terminal_list_renderer_diagnostics,
             commands::terminal::terminal_resume_session,
+            commands::terminal::terminal_attach,
+            commands::terminal::terminal_detach,
             commands::terminal::terminal_drain_output,
             commands::terminal::terminal_list_notifications,
             commands::terminal::terminal_clear_notification,
             commands::terminal::terminal_set_notification_focus,
             commands::setup::check_dependencies,
             commands::setup::install_dependency,
+            commands::setup::refresh_environment,
             commands::harness::check_harnesses,
             commands::harness::install_harness,
             commands::harness::launch_harness,
@@ -333,6 +448,10 @@ pub fn run() {
 
     app.run(|app_handle, event| match event {
         RunEvent::ExitRequested { .. } | RunEvent::Exit => {
+            let active_turns = app_handle.state::<AppState>().turns.active_count();
+            if active_turns > 0 {
+                log::info!("exiting with {active_turns} agent turn(s) still running");
+            }
             let terminals = app_handle.state::<AppState>().terminals.clone();
             let keep_awake = app_handle.state::<AppState>().keep_awake.clone();
             tauri::async_runtime::block_on(async move {
@@ -368,6 +487,23 @@ async fn run_codex_runtime_bridge(app: tauri::AppHandle, state: AppState) {
     }
 }
 
+/// Periodically drops content blobs whose referencing messages are gone.
+async fn run_blob_sweeper(db: crate::db::Database) {
+    const SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+    loop {
+        match run_db(db.clone(), |db| {
+            db::blobs::sweep_unreferenced_blobs(db, db::blobs::BLOB_SWEEP_GRACE)
+        })
+        .await
+        {
+            Ok(0) => {}
+            Ok(removed) => log::info!("swept {removed} unreferenced content blobs"),
+            Err(error) => log::warn!("content blob sweep failed: {error}"),
+        }
+        tokio::time::sleep(SWEEP_INTERVAL).await;
+    }
+}
+
 async fn handle_codex_runtime_event(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -384,6 +520,21 @@ async fn handle_codex_runtime_event(
                 },
             );
         }
+        CodexRuntimeEvent::ProtocolGap {
+            method,
+            count,
+            fallback,
+        } => {
+            let _ = app.emit(
+                "engine-protocol-gap",
+                EngineProtocolGapDto {
+                    engine_id: "codex".to_string(),
+                    method,
+                    count,
+                    fallback,
+                },
+            );
+        }
         CodexRuntimeEvent::ApprovalResolved { approval_id } => {
             resolve_codex_runtime_approval(app, state, &approval_id).await;
         }
@@ -533,7 +684,7 @@ async fn resolve_codex_runtime_approval(
         return;
     };
 
-    let has_local_turn = state.turns.get(&thread_id).await.is_some();
+    let has_local_turn = state.turns.is_active(&thread_id);
     let updated_thread = match run_db(state.db.clone(), {
         let approval_id = approval_id.to_string();
         let thread_id = thread_id.clone();
@@ -544,14 +695,7 @@ async fn resolve_codex_runtime_approval(
                 .transaction()
                 .context("failed to start approval resolution transaction")?;
 
-            // Resolve the approval record.
-            tx.execute(
-                "UPDATE approvals
-                 SET status = 'answered', answered_at = COALESCE(answered_at, datetime('now'))
-                 WHERE id = ?1",
-                rusqlite::params![approval_id],
-            )
-            .context("failed to resolve approval")?;
+            db::actions::resolve_approval_on(&tx, &approval_id)?;
 
             // Update the approval block inside the message's blocks_json (best-effort).
             let raw_blocks: Option<String> = tx
@@ -689,7 +833,7 @@ async fn apply_codex_runtime_thread_update(
     .await
     .ok()??;
 
-    let has_local_turn = state.turns.get(&thread.id).await.is_some();
+    let has_local_turn = state.turns.is_active(&thread.id);
     let next_status = map_codex_runtime_status_to_local(raw_status, active_flags, has_local_turn);
     let metadata = merge_codex_runtime_metadata(
         thread.engine_metadata.clone(),
@@ -867,6 +1011,10 @@ where
     T: Send + 'static,
     F: FnOnce(&crate::db::Database) -> anyhow::Result<T> + Send + 'static,
 {
+    let db = db
+        .acquire_scope()
+        .await
+        .map_err(|error| error.to_string())?;
     tokio::task::spawn_blocking(move || operation(&db))
         .await
         .map_err(|error| error.to_string())?
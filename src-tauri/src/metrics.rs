@@ -0,0 +1,309 @@
+//! Local usage counters (turns, tokens, approvals, terminals, commits) kept
+//! in the `local_metrics` table. Nothing in this module or its storage ever
+//! leaves the machine: there is no network client here, and the test at the
+//! bottom keeps it that way.
+
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use tauri::AppHandle;
+
+use crate::{
+    config::app_config::AppConfig,
+    db::{local_metrics, Database},
+    models::{LocalMetricsDto, LocalMetricsPeriodDto, LocalTurnMetricDto},
+    terminal_notifications::show_agent_desktop_notification,
+};
+
+pub const TOKENS_TOTAL: &str = "tokens:total";
+pub const APPROVALS_ANSWERED: &str = "approvals:answered";
+pub const TERMINALS_OPENED: &str = "terminals:opened";
+pub const COMMITS_MADE: &str = "commits:made";
+const TURNS_PREFIX: &str = "turns:";
+
+const WEEKLY_SUMMARY_STATE_KEY: &str = "weekly_summary_last_day";
+const WEEKLY_SUMMARY_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const WEEKLY_SUMMARY_TITLE: &str = "Your week in Panes";
+
+/// Adds `by` to today's `key` counter. Failures are logged and swallowed:
+/// counting an action must never fail the action itself.
+pub fn incr(db: &Database, key: &str, by: i64) {
+    if by == 0 {
+        return;
+    }
+    if let Err(error) = local_metrics::incr(db, today(), key, by) {
+        log::warn!("failed to record local metric {key}: {error}");
+    }
+}
+
+/// `incr` for async callers: the writes run on a blocking task that is not
+/// awaited.
+pub fn spawn_incr(db: &Database, counters: Vec<(String, i64)>) {
+    let db = db.clone();
+    drop(tokio::task::spawn_blocking(move || {
+        for (key, by) in counters {
+            incr(&db, &key, by);
+        }
+    }));
+}
+
+/// Counters for one finished turn.
+pub fn turn_counters(
+    engine_id: &str,
+    model_id: &str,
+    token_usage: Option<(u64, u64)>,
+) -> Vec<(String, i64)> {
+    let (input, output) = token_usage.unwrap_or((0, 0));
+    vec![
+        (format!("{TURNS_PREFIX}{engine_id}:{model_id}"), 1),
+        (TOKENS_TOTAL.to_string(), (input + output) as i64),
+    ]
+}
+
+pub fn get_local_metrics(
+    db: &Database,
+    period: LocalMetricsPeriodDto,
+) -> anyhow::Result<LocalMetricsDto> {
+    aggregate(db, period, today())
+}
+
+fn aggregate(
+    db: &Database,
+    period: LocalMetricsPeriodDto,
+    today: NaiveDate,
+) -> anyhow::Result<LocalMetricsDto> {
+    let start = window_start(period, today);
+    let counters = local_metrics::sum_between(db, start, today)?;
+    Ok(summarize(period, start, today, counters))
+}
+
+fn window_start(period: LocalMetricsPeriodDto, today: NaiveDate) -> NaiveDate {
+    let days = match period {
+        LocalMetricsPeriodDto::Day => 0,
+        LocalMetricsPeriodDto::Week => 6,
+        LocalMetricsPeriodDto::Month => 29,
+    };
+    today - chrono::Days::new(days)
+}
+
+fn summarize(
+    period: LocalMetricsPeriodDto,
+    start: NaiveDate,
+    end: NaiveDate,
+    counters: Vec<(String, i64)>,
+) -> LocalMetricsDto {
+    let mut metrics = LocalMetricsDto {
+        period,
+        start_day: start.to_string(),
+        end_day: end.to_string(),
+        turns: 0,
+        tokens: 0,
+        approvals_answered: 0,
+        terminals_opened: 0,
+        commits: 0,
+        turns_by_model: Vec::new(),
+    };
+    for (key, value) in counters {
+        match key.as_str() {
+            TOKENS_TOTAL => metrics.tokens += value,
+            APPROVALS_ANSWERED => metrics.approvals_answered += value,
+            TERMINALS_OPENED => metrics.terminals_opened += value,
+            COMMITS_MADE => metrics.commits += value,
+            _ => {
+                let Some((engine_id, model_id)) = key
+                    .strip_prefix(TURNS_PREFIX)
+                    .and_then(|rest| rest.split_once(':'))
+                else {
+                    continue;
+                };
+                metrics.turns += value;
+                metrics.turns_by_model.push(LocalTurnMetricDto {
+                    engine_id: engine_id.to_string(),
+                    model_id: model_id.to_string(),
+                    turns: value,
+                });
+            }
+        }
+    }
+    metrics
+        .turns_by_model
+        .sort_by_key(|turn| std::cmp::Reverse(turn.turns));
+    metrics
+}
+
+/// Checks hourly whether the opt-in weekly summary is due and shows it as a
+/// desktop notification.
+pub async fn run_weekly_summary(app: AppHandle, db: Database) {
+    loop {
+        let db_for_check = db.clone();
+        let due = tokio::task::spawn_blocking(move || {
+            let enabled = AppConfig::load_or_create()
+                .map(|config| config.weekly_usage_summary_enabled())
+                .unwrap_or(false);
+            if !enabled {
+                return Ok(None);
+            }
+            due_weekly_summary(&db_for_check, today())
+        })
+        .await;
+        match due {
+            Ok(Ok(Some(body))) => {
+                if let Err(error) =
+                    show_agent_desktop_notification(&app, WEEKLY_SUMMARY_TITLE, &body)
+                {
+                    log::warn!("failed to show weekly usage summary: {error}");
+                }
+            }
+            Ok(Ok(None)) => {}
+            Ok(Err(error)) => log::warn!("weekly usage summary check failed: {error}"),
+            Err(error) => log::warn!("weekly usage summary task failed: {error}"),
+        }
+        tokio::time::sleep(WEEKLY_SUMMARY_CHECK_INTERVAL).await;
+    }
+}
+
+/// Body of the weekly summary when a week has passed since the last one and
+/// there was activity; marks it as sent.
+fn due_weekly_summary(db: &Database, today: NaiveDate) -> anyhow::Result<Option<String>> {
+    let last_sent = local_metrics::get_state(db, WEEKLY_SUMMARY_STATE_KEY)?
+        .and_then(|day| day.parse::<NaiveDate>().ok());
+    if last_sent.is_some_and(|day| (today - day).num_days() < 7) {
+        return Ok(None);
+    }
+    let week = aggregate(db, LocalMetricsPeriodDto::Week, today)?;
+    if week.turns == 0 && week.commits == 0 && week.terminals_opened == 0 {
+        return Ok(None);
+    }
+    local_metrics::set_state(db, WEEKLY_SUMMARY_STATE_KEY, &today.to_string())?;
+    Ok(Some(format_weekly_summary(&week)))
+}
+
+fn format_weekly_summary(week: &LocalMetricsDto) -> String {
+    format!(
+        "{} turns, {} tokens, {} commits",
+        week.turns,
+        compact_count(week.tokens),
+        week.commits
+    )
+}
+
+fn compact_count(value: i64) -> String {
+    match value {
+        value if value >= 1_000_000 => format!("{:.1}M", value as f64 / 1_000_000.0),
+        value if value >= 1_000 => format!("{:.1}K", value as f64 / 1_000.0),
+        value => value.to_string(),
+    }
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::db::PoolLimits;
+
+    fn test_db() -> Database {
+        let path = std::env::temp_dir().join(format!("panes-metrics-{}.db", Uuid::new_v4()));
+        Database::open(path, PoolLimits::default()).expect("open test db")
+    }
+
+    fn day(value: &str) -> NaiveDate {
+        value.parse().expect("valid day")
+    }
+
+    #[test]
+    fn windows_count_only_the_days_they_cover() {
+        let db = test_db();
+        let today = day("2026-03-31");
+        for (when, key, by) in [
+            ("2026-03-31", "turns:codex:gpt-5.4", 2),
+            ("2026-03-31", TOKENS_TOTAL, 1_000),
+            ("2026-03-25", "turns:claude:sonnet", 1),
+            ("2026-03-24", COMMITS_MADE, 3),
+            ("2026-03-02", TERMINALS_OPENED, 4),
+            ("2026-03-01", APPROVALS_ANSWERED, 5),
+        ] {
+            local_metrics::incr(&db, day(when), key, by).expect("incr");
+        }
+
+        let today_only = aggregate(&db, LocalMetricsPeriodDto::Day, today).expect("day");
+        assert_eq!((today_only.turns, today_only.tokens), (2, 1_000));
+        assert_eq!(today_only.start_day, "2026-03-31");
+
+        let week = aggregate(&db, LocalMetricsPeriodDto::Week, today).expect("week");
+        assert_eq!(week.start_day, "2026-03-25");
+        assert_eq!((week.turns, week.commits), (3, 0));
+        assert_eq!(week.turns_by_model[0].engine_id, "codex");
+        assert_eq!(week.turns_by_model[1].model_id, "sonnet");
+
+        let month = aggregate(&db, LocalMetricsPeriodDto::Month, today).expect("month");
+        assert_eq!(month.start_day, "2026-03-02");
+        assert_eq!((month.commits, month.terminals_opened), (3, 4));
+        assert_eq!(month.approvals_answered, 0);
+    }
+
+    #[test]
+    fn incr_swallows_storage_failures() {
+        let db = test_db();
+        db.connect()
+            .expect("connect")
+            .execute_batch("DROP TABLE local_metrics")
+            .expect("drop table");
+        incr(&db, COMMITS_MADE, 1);
+        assert!(local_metrics::sum_between(&db, today(), today()).is_err());
+    }
+
+    #[test]
+    fn weekly_summary_is_sent_once_per_week_when_there_was_activity() {
+        let db = test_db();
+        let today = day("2026-03-31");
+        assert_eq!(due_weekly_summary(&db, today).expect("check"), None);
+
+        local_metrics::incr(&db, today, "turns:codex:gpt-5.4", 42).expect("incr");
+        local_metrics::incr(&db, today, TOKENS_TOTAL, 1_234_567).expect("incr");
+        local_metrics::incr(&db, today, COMMITS_MADE, 9).expect("incr");
+        assert_eq!(
+            due_weekly_summary(&db, today).expect("check").as_deref(),
+            Some("42 turns, 1.2M tokens, 9 commits")
+        );
+        assert_eq!(
+            due_weekly_summary(&db, day("2026-04-06")).expect("check"),
+            None
+        );
+        local_metrics::incr(&db, day("2026-04-07"), COMMITS_MADE, 1).expect("incr");
+        assert!(due_weekly_summary(&db, day("2026-04-07"))
+            .expect("check")
+            .is_some());
+    }
+
+    #[test]
+    fn metrics_code_has_no_network_dependencies() {
+        // Local-only is a promise to users; a network client showing up in
+        // this module or its storage layer should fail the build's tests.
+        for source in [
+            include_str!("metrics.rs"),
+            include_str!("db/local_metrics.rs"),
+        ] {
+            let code = source.split("#[cfg(test)]").next().unwrap_or(source);
+            for forbidden in [
+                "reqwest",
+                "hyper",
+                "ureq",
+                "std::net",
+                "tokio::net",
+                "TcpStream",
+                "UdpSocket",
+                "tauri_plugin_http",
+            ] {
+                assert!(
+                    !code.contains(forbidden),
+                    "local metrics must not reference `{forbidden}`"
+                );
+            }
+        }
+    }
+}
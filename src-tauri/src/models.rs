@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -12,6 +14,16 @@ pub struct WorkspaceDto {
     pub last_opened_at: String,
 }
 
+/// What archiving a workspace shut down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceArchiveReportDto {
+    pub workspace_id: String,
+    pub stopped_watchers: Vec<String>,
+    pub closed_terminal_sessions: Vec<String>,
+    pub cancelled_threads: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RepoDto {
@@ -22,6 +34,31 @@ pub struct RepoDto {
     pub default_branch: String,
     pub is_active: bool,
     pub trust_level: TrustLevelDto,
+    /// Codex/Claude project files found in the repo; filled in by
+    /// `get_repos`, empty elsewhere.
+    #[serde(default)]
+    pub detected_configs: Vec<DetectedAgentConfigDto>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentConfigKindDto {
+    Instructions,
+    Config,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedAgentConfigDto {
+    /// Relative to the repo root, `/`-separated.
+    pub path: String,
+    pub engine_id: String,
+    pub kind: AgentConfigKindDto,
+    pub excerpt: String,
+    pub adopted: bool,
+    /// Adopted, but changed or removed since.
+    pub stale: bool,
+    pub note: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,12 +103,18 @@ pub struct ThreadDto {
     pub model_id: String,
     pub engine_thread_id: Option<String>,
     pub engine_metadata: Option<Value>,
+    /// Effort the next turn will request, lifted out of `engine_metadata`.
+    #[serde(default)]
+    pub reasoning_effort: Option<String>,
     pub title: String,
     pub status: ThreadStatusDto,
     pub message_count: i64,
     pub total_tokens: i64,
     pub created_at: String,
     pub last_activity_at: String,
+    /// Snapshot taken when the thread was created.
+    #[serde(default)]
+    pub engine_capabilities: Option<EngineCapabilitiesDto>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -165,6 +208,43 @@ pub struct MessageDto {
     pub status: MessageStatusDto,
     pub token_usage: Option<TokenUsageDto>,
     pub created_at: String,
+    /// Per-thread insertion order; the authoritative sort key.
+    pub seq: i64,
+    /// Assistant message this one was regenerated from.
+    pub regenerated_from: Option<String>,
+    /// Replaced by a regeneration; shown collapsed.
+    pub superseded: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadModelHistoryEntryDto {
+    pub message_id: String,
+    pub engine_id: String,
+    pub model_id: String,
+    pub reasoning_effort: Option<String>,
+    pub started_at: String,
+}
+
+/// Consecutive assistant turns that ran with the same engine, model and effort.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadModelRangeDto {
+    pub engine_id: String,
+    pub model_id: String,
+    pub reasoning_effort: Option<String>,
+    pub first_message_id: String,
+    pub last_message_id: String,
+    pub started_at: String,
+    pub turn_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadModelHistoryDto {
+    pub thread_id: String,
+    pub entries: Vec<ThreadModelHistoryEntryDto>,
+    pub ranges: Vec<ThreadModelRangeDto>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -173,6 +253,8 @@ pub struct MessageWindowCursorDto {
     pub created_at: String,
     pub id: String,
     pub row_id: Option<i64>,
+    #[serde(default)]
+    pub seq: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -180,6 +262,155 @@ pub struct MessageWindowCursorDto {
 pub struct MessageWindowDto {
     pub messages: Vec<MessageDto>,
     pub next_cursor: Option<MessageWindowCursorDto>,
+    pub has_more: bool,
+    /// Messages in the whole thread, not just this window.
+    pub total_count: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadExportFormatDto {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversationImportFormatDto {
+    ChatgptJson,
+    Markdown,
+}
+
+impl ConversationImportFormatDto {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ChatgptJson => "chatgpt_json",
+            Self::Markdown => "markdown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationImportDto {
+    pub threads: Vec<ThreadDto>,
+    pub imported_messages: usize,
+    pub skipped_items: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupByDto {
+    Day,
+    Engine,
+    Model,
+}
+
+/// Token and message totals for one group. Keys not grouped on are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummaryRowDto {
+    /// UTC day, `YYYY-MM-DD`.
+    pub day: Option<String>,
+    pub engine_id: Option<String>,
+    pub model_id: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub message_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummaryDto {
+    pub rows: Vec<UsageSummaryRowDto>,
+    pub totals: UsageSummaryRowDto,
+}
+
+/// A turn's JSONL event log. `tail` holds its last entries when asked for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnEventLogDto {
+    pub path: String,
+    pub exists: bool,
+    pub tail: Vec<Value>,
+}
+
+/// Last account-wide rate limits an engine reported, shared by all of its
+/// threads. `updated_at` is when they were reported, in UTC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineUsageLimitsDto {
+    pub engine_id: String,
+    pub usage: crate::engines::events::UsageLimitsSnapshot,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadTimelineEntryKindDto {
+    UserMessage,
+    AssistantSummary,
+    Action,
+    Approval,
+    FileChanges,
+    GitOperation,
+}
+
+impl ThreadTimelineEntryKindDto {
+    /// Tie-break order for entries sharing a timestamp; mirrors the order in
+    /// which a turn produces them.
+    pub fn rank(self) -> i64 {
+        match self {
+            Self::UserMessage => 0,
+            Self::AssistantSummary => 1,
+            Self::Action => 2,
+            Self::Approval => 3,
+            Self::FileChanges => 4,
+            Self::GitOperation => 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadTimelineEntryDto {
+    pub id: String,
+    pub kind: ThreadTimelineEntryKindDto,
+    pub message_id: Option<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excerpt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_change_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadTimelineCursorDto {
+    pub created_at: String,
+    pub kind: ThreadTimelineEntryKindDto,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadTimelineDto {
+    pub entries: Vec<ThreadTimelineEntryDto>,
+    pub next_cursor: Option<ThreadTimelineCursorDto>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -241,7 +472,35 @@ pub struct SearchResultDto {
     pub workspace_name: String,
     pub repo_id: Option<String>,
     pub message_id: String,
+    pub role: String,
     pub snippet: String,
+    /// Type of the block holding the first match (`text` for plain content).
+    pub block_type: Option<String>,
+    /// Index into the message's blocks for deep-linking; `None` when the match
+    /// came from the content column of a message without text blocks.
+    pub block_index: Option<usize>,
+    pub match_count: usize,
+    /// Up to three snippets, each cut from the text of a single matched block.
+    pub snippets: Vec<SearchSnippetDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSnippetDto {
+    pub text: String,
+    pub block_type: String,
+    pub block_index: Option<usize>,
+    /// Byte offsets into `text`, sorted and non-overlapping.
+    pub match_ranges: Vec<SearchMatchRangeDto>,
+    pub truncated_start: bool,
+    pub truncated_end: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatchRangeDto {
+    pub start: usize,
+    pub end: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -270,7 +529,7 @@ pub struct ChatProviderUsageWindowDto {
     pub resets_at: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EngineCapabilitiesDto {
     #[serde(default)]
@@ -279,6 +538,18 @@ pub struct EngineCapabilitiesDto {
     pub sandbox_modes: Vec<String>,
     #[serde(default)]
     pub approval_decisions: Vec<String>,
+    #[serde(default)]
+    pub plan_mode: bool,
+    #[serde(default)]
+    pub attachments: bool,
+    #[serde(default)]
+    pub approvals: bool,
+    #[serde(default)]
+    pub interrupt: bool,
+    #[serde(default)]
+    pub reasoning_effort: bool,
+    #[serde(default)]
+    pub thread_rename: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -354,6 +625,33 @@ pub struct EngineHealthDto {
     pub fixes: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub protocol_diagnostics: Option<CodexProtocolDiagnosticsDto>,
+    /// Effective request deadlines, after config minimums are applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeouts: Option<EngineTimeoutsDto>,
+    /// Absent when the engine has no sign-in step or was not probed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_status: Option<EngineAuthStatusDto>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineAuthStatusDto {
+    SignedIn,
+    SignedOut,
+    NotRequired,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineTimeoutsDto {
+    pub request_timeout_secs: u64,
+    /// Absent when turn requests wait indefinitely.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_request_timeout_secs: Option<u64>,
+    /// Absent when running turns are never failed for inactivity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_completion_inactivity_timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -659,6 +957,20 @@ pub struct EngineRuntimeUpdatedDto {
     pub toast: Option<RuntimeToastDto>,
 }
 
+/// Payload of `engine-protocol-gap`: a server request Panes could not map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineProtocolGapDto {
+    pub engine_id: String,
+    pub method: String,
+    /// Times this method was seen since launch.
+    pub count: u64,
+    /// Request family whose safe default was sent; absent when the request
+    /// was answered with method-not-found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EngineCheckResultDto {
@@ -675,8 +987,105 @@ pub struct EngineCheckResultDto {
 pub struct GitStatusDto {
     pub branch: String,
     pub files: Vec<GitFileStatusDto>,
+    /// Remote-tracking branch the current branch follows, e.g. `origin/main`.
+    pub upstream_branch: Option<String>,
     pub ahead: usize,
     pub behind: usize,
+    pub detached_head: bool,
+    /// Unmerged files, also listed in `files` as `conflicted`.
+    pub conflicts: Vec<GitConflictDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommitResultDto {
+    pub hash: String,
+    /// Diff stats of the commit against its first parent.
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommitMessageDto {
+    pub subject: String,
+    /// Empty when the subject says it all.
+    pub body: String,
+}
+
+/// Why no commit message was generated, tagged so the UI can tell a missing
+/// engine from a slow one. `message` is always ready to show.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum GitCommitMessageErrorDto {
+    NothingStaged {
+        message: String,
+    },
+    EngineUnavailable {
+        engine_id: String,
+        message: String,
+    },
+    TimedOut {
+        engine_id: String,
+        timeout_ms: u64,
+        message: String,
+    },
+    Failed {
+        engine_id: String,
+        message: String,
+    },
+    Other {
+        message: String,
+    },
+}
+
+/// Why hunks were not staged or unstaged. `stale_diff` means the picked hunks
+/// are no longer in the file's diff and the UI should refresh it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum GitHunkErrorDto {
+    StaleDiff { path: String, message: String },
+    Other { message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitConflictDto {
+    pub path: String,
+    pub kind: GitConflictKindDto,
+}
+
+/// Which sides of a merge touched an unmerged file, as `git status` names
+/// them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitConflictKindDto {
+    BothModified,
+    BothAdded,
+    BothDeleted,
+    AddedByUs,
+    AddedByThem,
+    DeletedByUs,
+    DeletedByThem,
+}
+
+/// How `resolve_conflict` settles a file: take one side's version, or mark
+/// the working tree copy the user edited as resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitConflictResolutionDto {
+    Ours,
+    Theirs,
+    Resolved,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -687,6 +1096,61 @@ pub struct GitFileStatusDto {
     pub worktree_status: Option<String>,
 }
 
+/// Status of every git-active repo in a workspace. `partial` is set when at
+/// least one repo timed out or failed and is missing its file list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceGitOverviewDto {
+    pub workspace_id: String,
+    pub repos: Vec<WorkspaceGitRepoSummaryDto>,
+    pub files: Vec<WorkspaceGitFileDto>,
+    pub partial: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceGitRepoSummaryDto {
+    pub repo_id: String,
+    pub repo_name: String,
+    pub repo_path: String,
+    pub branch: Option<String>,
+    pub ahead: Option<usize>,
+    pub behind: Option<usize>,
+    /// Files with unstaged or untracked changes.
+    pub dirty_count: usize,
+    pub staged_count: usize,
+    pub timed_out: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceGitFileDto {
+    pub repo_id: String,
+    pub repo_path: String,
+    pub path: String,
+    pub index_status: Option<String>,
+    pub worktree_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRepoFilesDto {
+    pub repo_path: String,
+    pub files: Vec<String>,
+}
+
+/// Outcome of one repo in a multi-repo operation; `commit_hash` is only set
+/// by commits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitMultiRepoResultDto {
+    pub repo_path: String,
+    pub ok: bool,
+    pub commit_hash: Option<String>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GitDiffPreviewDto {
@@ -694,6 +1158,20 @@ pub struct GitDiffPreviewDto {
     pub truncated: bool,
     pub original_bytes: usize,
     pub returned_bytes: usize,
+    /// Hunks of a single-file diff from `get_file_diff`, in order; empty
+    /// for other previews.
+    pub hunks: Vec<GitDiffHunkDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitDiffHunkDto {
+    /// Pass to `stage_hunks`/`unstage_hunks`; stops matching once the
+    /// file changes under the hunk.
+    pub id: String,
+    pub index: usize,
+    /// The hunk's `@@ -a,b +c,d @@` line.
+    pub header: String,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -808,9 +1286,21 @@ pub struct GitStashDto {
     pub index: usize,
     pub name: String,
     pub branch_hint: Option<String>,
+    /// Message given when the stash was created, if any.
+    pub message: Option<String>,
     pub created_at: Option<String>,
 }
 
+/// Result of applying or popping a stash. Non-empty `conflicted_files` means
+/// git stopped with conflict markers in those files and, for a pop, kept the
+/// stash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStashApplyResultDto {
+    pub conflicted_files: Vec<String>,
+    pub dropped: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GitWorktreeDto {
@@ -827,6 +1317,20 @@ pub struct GitWorktreeDto {
 pub struct FileTreeEntryDto {
     pub path: String,
     pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Filled in for repo trees; a directory reports the status of its
+    /// contents. `None` is clean, or unknown outside a repo.
+    pub git_status: Option<FileTreeGitStatusDto>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileTreeGitStatusDto {
+    Modified,
+    Added,
+    Untracked,
+    Ignored,
+    Conflicted,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -843,9 +1347,36 @@ pub struct FileTreePageDto {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReadFileResultDto {
+    /// Empty for binary files and only the start of the file when
+    /// `truncated`, unless the read was forced.
     pub content: String,
     pub size_bytes: u64,
     pub is_binary: bool,
+    pub truncated: bool,
+}
+
+/// Why `write_file` failed, tagged so the editor can offer to create a
+/// missing parent directory. `message` is always ready to show.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum FileWriteErrorDto {
+    /// `path` is the missing directory, relative to the repo.
+    ParentMissing {
+        path: String,
+        message: String,
+    },
+    PermissionDenied {
+        path: String,
+        message: String,
+    },
+    Other {
+        path: String,
+        message: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -859,6 +1390,15 @@ pub struct ResolvedEditorFileReferenceDto {
     pub column: Option<u32>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSessionLimitsDto {
+    pub workspace_id: String,
+    /// Open sessions, counting ones still spawning.
+    pub open: usize,
+    pub limit: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TerminalSessionDto {
@@ -866,9 +1406,36 @@ pub struct TerminalSessionDto {
     pub workspace_id: String,
     pub shell: String,
     pub cwd: String,
+    /// Last directory the shell reported through OSC 7, if any.
+    #[serde(default)]
+    pub current_cwd: Option<String>,
+    /// Latest window title set through OSC 0/2; empty after a reset.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Whether output events are emitted to the webview; detached sessions
+    /// keep buffering output for the next `terminal_attach`.
+    #[serde(default)]
+    pub attached: bool,
     pub created_at: String,
 }
 
+/// Session metadata plus the output a reattaching view missed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalAttachDto {
+    pub session: TerminalSessionDto,
+    pub replay: TerminalResumeSessionDto,
+}
+
+/// PATH assignment typed into a session by `terminal_inject_path`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalPathInjectionDto {
+    pub session_id: String,
+    pub path: String,
+    pub command: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TerminalNotificationDto {
@@ -889,6 +1456,45 @@ pub struct TerminalReplayChunkDto {
     pub data: String,
 }
 
+/// Recent decoded output of a live session. `seq` is the last output chunk
+/// included, so `terminal-output-*` events with `latest_seq <= seq` are
+/// already part of `data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalScrollbackDto {
+    pub seq: u64,
+    pub data: String,
+    /// Older output was dropped to stay within the scrollback cap.
+    pub truncated: bool,
+}
+
+/// Matches in a session's retained output. Offsets are bytes from the start
+/// of the buffer as it was when searched; `origin` is how many bytes had
+/// already been trimmed from the front, so `origin + offset` is stable across
+/// later trims.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalOutputSearchDto {
+    pub seq: u64,
+    pub origin: u64,
+    pub buffer_bytes: usize,
+    pub matches: Vec<TerminalOutputMatchDto>,
+    /// More matches exist than were returned.
+    pub limited: bool,
+}
+
+/// `snippet` is the match's line with escape sequences removed;
+/// `snippet_match_start..snippet_match_end` is the match within it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalOutputMatchDto {
+    pub offset: usize,
+    pub length: usize,
+    pub snippet: String,
+    pub snippet_match_start: usize,
+    pub snippet_match_end: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TerminalResumeSessionDto {
@@ -920,6 +1526,13 @@ pub struct TerminalEnvSnapshotDto {
     pub lc_all: Option<String>,
     pub lc_ctype: Option<String>,
     pub path: Option<String>,
+    /// Shell the session was spawned with and the arguments it was given.
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub shell_args: Vec<String>,
+    /// Variables the session was created with on top of the defaults.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -951,6 +1564,8 @@ pub struct TerminalIoCountersDto {
     pub stdin_writes: u64,
     pub stdin_bytes: u64,
     pub stdin_ctrl_c: u64,
+    #[serde(default)]
+    pub signals_sent: u64,
     pub last_stdin_write_duration_ms: Option<u64>,
     pub stdout_reads: u64,
     pub stdout_bytes: u64,
@@ -996,6 +1611,216 @@ pub struct GitInitRepoStatusDto {
     pub blocking_repo_path: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPreflightDto {
+    pub repo_path: String,
+    pub binary_found: bool,
+    pub binary_path: Option<String>,
+    pub binary_version: Option<String>,
+    pub install_hint: Option<String>,
+    pub libgit2_version: String,
+    pub libgit2_https: bool,
+    pub libgit2_ssh: bool,
+    pub credential_helper: Option<String>,
+    pub uses_lfs: bool,
+    pub signs_commits: bool,
+    pub signing_program: Option<String>,
+    /// Repo features that only work through the git binary ("lfs",
+    /// "commit_signing", "hooks").
+    pub binary_required_features: Vec<String>,
+}
+
+/// Why a turn was not started. A turn over its engine's context budget is
+/// tagged so the UI can list what to trim and offer to send it anyway;
+/// `message` is always ready to show.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum SendMessageErrorDto {
+    ContextBudgetExceeded {
+        engine_id: String,
+        estimated_tokens: u64,
+        threshold_tokens: u64,
+        max_input_tokens: u64,
+        /// Largest first.
+        contributors: Vec<ContextContributorDto>,
+        suggestions: Vec<String>,
+        message: String,
+    },
+    Other {
+        message: String,
+    },
+}
+
+impl SendMessageErrorDto {
+    pub fn into_message(self) -> String {
+        match self {
+            Self::ContextBudgetExceeded { message, .. } | Self::Other { message } => message,
+        }
+    }
+}
+
+impl From<String> for SendMessageErrorDto {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextContributorDto {
+    pub kind: ContextContributorKindDto,
+    pub label: String,
+    pub tokens: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextContributorKindDto {
+    Message,
+    Attachment,
+    ContextFile,
+    Skill,
+    Instructions,
+    ThreadHistory,
+}
+
+/// Outcome of sending one prompt to several threads, keyed by thread id.
+/// Each thread appears in exactly one of the maps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSendResultDto {
+    pub assistant_message_ids: BTreeMap<String, String>,
+    pub errors: BTreeMap<String, String>,
+}
+
+/// A message waiting for the thread's running turn to end.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedMessageDto {
+    pub id: String,
+    pub thread_id: String,
+    pub message: String,
+    pub model_id: Option<String>,
+    pub reasoning_effort: Option<String>,
+    pub attachments: Option<Value>,
+    pub input_items: Option<Value>,
+    pub plan_mode: bool,
+    /// Also sent when the turn before it ends in an error.
+    pub run_after_error: bool,
+    /// Queued before an app restart, or failed to send; shown but not sent
+    /// automatically.
+    pub held: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitOperationLogDto {
+    pub id: i64,
+    pub repo_path: String,
+    pub thread_id: Option<String>,
+    /// `force_push`, `delete_branch`, `squash_commits`, `reword_commit` or
+    /// `amend_commit`.
+    pub operation: String,
+    pub target: String,
+    pub force: bool,
+    pub force_with_lease: bool,
+    /// `completed`, `refused` or `failed`.
+    pub outcome: String,
+    pub detail: Option<String>,
+    pub created_at: String,
+}
+
+// ── Local metrics ───────────────────────────────────────────────────
+
+/// Rolling window ending today (local time): today only, the last 7 days
+/// or the last 30 days.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalMetricsPeriodDto {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalMetricsDto {
+    pub period: LocalMetricsPeriodDto,
+    /// First and last day of the window, `YYYY-MM-DD`.
+    pub start_day: String,
+    pub end_day: String,
+    pub turns: i64,
+    pub tokens: i64,
+    pub approvals_answered: i64,
+    pub terminals_opened: i64,
+    pub commits: i64,
+    pub turns_by_model: Vec<LocalTurnMetricDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalTurnMetricDto {
+    pub engine_id: String,
+    pub model_id: String,
+    pub turns: i64,
+}
+
+// ── Diagnostics ─────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabasePoolMetricsDto {
+    pub max_connections: usize,
+    pub acquire_timeout_ms: u64,
+    pub open: usize,
+    pub idle: usize,
+    pub in_use: usize,
+    pub waiters: usize,
+    pub peak_in_use: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnDiagnosticSourceDto {
+    UnmappedNotification,
+    UnmappedServerRequest,
+    DroppedByThreadFilter,
+    DroppedByTurnFilter,
+    Stderr,
+}
+
+/// Engine-side detail captured during a turn that never reached the chat,
+/// kept only when the turn fails.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnDiagnosticDto {
+    pub source: TurnDiagnosticSourceDto,
+    pub at: String,
+    pub message: String,
+    /// Consecutive identical entries are folded into one.
+    pub count: u32,
+}
+
+/// Savings from storing repeated large block payloads once. `inline_bytes`
+/// is what the referencing messages would occupy with the payload inlined.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobStorageReportDto {
+    pub category: String,
+    pub blob_count: u64,
+    pub unreferenced_blob_count: u64,
+    pub reference_count: u64,
+    pub stored_bytes: u64,
+    pub inline_bytes: u64,
+    pub saved_bytes: u64,
+}
+
 // ── Setup / Onboarding ──────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1023,6 +1848,24 @@ pub struct DepStatus {
 pub struct InstallResult {
     pub success: bool,
     pub message: String,
+    /// Whether the installed binary resolves after the environment refresh;
+    /// only set for successful dependency installs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolvable: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedExecutableDto {
+    pub name: String,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentRefreshDto {
+    pub path: Option<String>,
+    pub executables: Vec<ResolvedExecutableDto>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
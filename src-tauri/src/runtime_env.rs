@@ -3,19 +3,56 @@ use std::{
     env,
     ffi::{OsStr, OsString},
     fs,
+    future::Future,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use tokio::{
     process::Command,
-    sync::OnceCell,
+    sync::Mutex,
     time::{timeout, Duration},
 };
 
 const LOGIN_ENV_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
 const LOGIN_ENV_PROBE_MARKER: &str = "__PANES_LOGIN_ENV_START__";
 
-static LOGIN_SHELL_ENV: OnceCell<HashMap<OsString, OsString>> = OnceCell::const_new();
+static LOGIN_SHELL_ENV: LoginShellEnvCache = LoginShellEnvCache::new();
+
+type LoginShellEnv = Arc<HashMap<OsString, OsString>>;
+
+/// Login shell environment probed on first use. Unlike a `OnceCell` it can be
+/// dropped, so tools installed after launch show up on the next probe.
+struct LoginShellEnvCache {
+    value: Mutex<Option<LoginShellEnv>>,
+}
+
+impl LoginShellEnvCache {
+    const fn new() -> Self {
+        Self {
+            value: Mutex::const_new(None),
+        }
+    }
+
+    /// The lock is held while loading so concurrent callers share one probe.
+    async fn get_or_load<F, Fut>(&self, load: F) -> LoginShellEnv
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = HashMap<OsString, OsString>>,
+    {
+        let mut value = self.value.lock().await;
+        if let Some(env) = value.as_ref() {
+            return Arc::clone(env);
+        }
+        let env = Arc::new(load().await);
+        *value = Some(Arc::clone(&env));
+        env
+    }
+
+    async fn invalidate(&self) {
+        self.value.lock().await.take();
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ShellFlavor {
@@ -107,7 +144,7 @@ pub fn resolve_executable(binary: &str) -> Option<PathBuf> {
 
 pub async fn apply_missing_login_shell_env(command: &mut Command) {
     let shell_env = login_shell_environment().await;
-    for (key, value) in shell_env {
+    for (key, value) in shell_env.iter() {
         if !should_import_login_shell_env_var(key) || env::var_os(key).is_some() {
             continue;
         }
@@ -115,12 +152,18 @@ pub async fn apply_missing_login_shell_env(command: &mut Command) {
     }
 }
 
-async fn login_shell_environment() -> &'static HashMap<OsString, OsString> {
+async fn login_shell_environment() -> LoginShellEnv {
     LOGIN_SHELL_ENV
-        .get_or_init(load_login_shell_environment)
+        .get_or_load(load_login_shell_environment)
         .await
 }
 
+/// Drops the cached login shell environment. PATH lookups themselves are not
+/// cached, so after this every resolution reflects the current filesystem.
+pub async fn invalidate_environment_caches() {
+    LOGIN_SHELL_ENV.invalidate().await;
+}
+
 async fn load_login_shell_environment() -> HashMap<OsString, OsString> {
     #[cfg(target_os = "windows")]
     {
@@ -221,23 +264,39 @@ pub fn terminal_shell() -> PathBuf {
 }
 
 pub fn terminal_shell_args(shell: &Path) -> Vec<String> {
-    #[cfg(target_os = "windows")]
-    {
-        let _ = shell;
-        return Vec::new();
-    }
+    terminal_shell_args_for(shell, cfg!(target_os = "windows"))
+}
 
-    #[cfg(not(target_os = "windows"))]
+/// Sources the standard PowerShell profiles in PowerShell's own order. The
+/// shell is started with `-NoProfile` so this is the only place they load.
+const POWERSHELL_PROFILE_COMMAND: &str = concat!(
+    "foreach ($panesProfile in @(",
+    "$PROFILE.AllUsersAllHosts, $PROFILE.AllUsersCurrentHost, ",
+    "$PROFILE.CurrentUserAllHosts, $PROFILE.CurrentUserCurrentHost",
+    ")) { if (Test-Path $panesProfile) { . $panesProfile } }; ",
+    "Remove-Variable panesProfile"
+);
+
+fn terminal_shell_args_for(shell: &Path, is_windows: bool) -> Vec<String> {
+    if !is_windows {
+        return vec!["-l".to_string(), "-i".to_string()];
+    }
+    // ConPTY shells have no login mode; PowerShell gets its profiles
+    // sourced explicitly and everything else (cmd.exe included) runs bare.
     match shell_flavor(shell) {
+        ShellFlavor::PowerShell => vec![
+            "-NoLogo".to_string(),
+            "-NoProfile".to_string(),
+            "-NoExit".to_string(),
+            "-Command".to_string(),
+            POWERSHELL_PROFILE_COMMAND.to_string(),
+        ],
         ShellFlavor::Bash
         | ShellFlavor::Fish
         | ShellFlavor::Zsh
         | ShellFlavor::Sh
         | ShellFlavor::Cmd
-        | ShellFlavor::PowerShell
-        | ShellFlavor::Other => {
-            vec!["-l".to_string(), "-i".to_string()]
-        }
+        | ShellFlavor::Other => Vec::new(),
     }
 }
 
@@ -515,6 +574,18 @@ fn augmented_path_entries_for(
             entries.push(roaming_app_data.join("pnpm"));
             entries.push(roaming_app_data.join("nvm"));
         }
+        // Package manager shims outside their default locations.
+        if let Some(scoop) = env::var_os("SCOOP") {
+            entries.push(PathBuf::from(scoop).join("shims"));
+        }
+        match env::var_os("ChocolateyInstall") {
+            Some(chocolatey) => entries.push(PathBuf::from(chocolatey).join("bin")),
+            None => {
+                if let Some(program_data) = env::var_os("ProgramData") {
+                    entries.push(PathBuf::from(program_data).join("chocolatey").join("bin"));
+                }
+            }
+        }
         // nvm-windows default symlink directory
         if let Some(program_files) = env::var_os("ProgramFiles") {
             entries.push(PathBuf::from(&program_files).join("nodejs"));
@@ -649,6 +720,64 @@ fn command_shell_args_for(program: &Path, command: &str) -> Vec<String> {
     }
 }
 
+/// Command line that sets PATH to `path` inside an interactive `shell`. The
+/// value is quoted for that shell; the caller appends the line terminator.
+pub fn path_export_command(shell: &Path, path: &str) -> String {
+    match shell_flavor(shell) {
+        ShellFlavor::Fish => {
+            let entries = path
+                .split(':')
+                .filter(|entry| !entry.is_empty())
+                .map(fish_single_quote)
+                .collect::<Vec<_>>();
+            format!("set -gx PATH {}", entries.join(" "))
+        }
+        ShellFlavor::Cmd => format!("set \"PATH={path}\""),
+        ShellFlavor::PowerShell => format!("$env:PATH = '{}'", path.replace('\'', "''")),
+        _ => format!("export PATH='{}'", path.replace('\'', "'\\''")),
+    }
+}
+
+/// Command line that runs `program` with `args` when typed into an
+/// interactive `shell`. Words are quoted only when they need it; the caller
+/// appends the line terminator.
+pub fn shell_command_line(shell: &Path, program: &Path, args: &[String]) -> String {
+    let flavor = shell_flavor(shell);
+    let program = program.to_string_lossy();
+    let mut words = Vec::with_capacity(args.len() + 1);
+    words.push(quote_shell_word(flavor, &program));
+    words.extend(args.iter().map(|arg| quote_shell_word(flavor, arg)));
+    let line = words.join(" ");
+
+    // PowerShell treats a quoted first word as a string, not a command.
+    if flavor == ShellFlavor::PowerShell && words[0].starts_with('\'') {
+        format!("& {line}")
+    } else {
+        line
+    }
+}
+
+fn quote_shell_word(flavor: ShellFlavor, word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "-_./:=@%+,".contains(ch));
+    if plain {
+        return word.to_string();
+    }
+
+    match flavor {
+        ShellFlavor::Fish => fish_single_quote(word),
+        ShellFlavor::Cmd => format!("\"{word}\""),
+        ShellFlavor::PowerShell => format!("'{}'", word.replace('\'', "''")),
+        _ => format!("'{}'", word.replace('\'', "'\\''")),
+    }
+}
+
+fn fish_single_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
 fn shell_flavor(path: &Path) -> ShellFlavor {
     match path
         .file_name()
@@ -1034,34 +1163,22 @@ mod tests {
 
     #[test]
     fn terminal_shell_args_match_shell_type() {
-        #[cfg(target_os = "windows")]
-        {
-            assert_eq!(
-                terminal_shell_args(Path::new("cmd.exe")),
-                Vec::<String>::new()
-            );
-            assert_eq!(
-                terminal_shell_args(Path::new("pwsh.exe")),
-                Vec::<String>::new()
-            );
+        assert_eq!(
+            terminal_shell_args_for(Path::new("cmd.exe"), true),
+            Vec::<String>::new()
+        );
+        for powershell in [
+            "pwsh.exe",
+            r"C:\Windows\System32\WindowsPowerShell\v1.0\powershell.exe",
+        ] {
+            let args = terminal_shell_args_for(Path::new(powershell), true);
+            assert_eq!(args[..4], ["-NoLogo", "-NoProfile", "-NoExit", "-Command"]);
+            assert!(args[4].contains("$PROFILE.CurrentUserCurrentHost"));
         }
 
-        #[cfg(not(target_os = "windows"))]
-        {
+        for shell in ["/bin/bash", "/bin/zsh", "/bin/sh", "/usr/bin/fish"] {
             assert_eq!(
-                terminal_shell_args(Path::new("/bin/bash")),
-                vec!["-l".to_string(), "-i".to_string()]
-            );
-            assert_eq!(
-                terminal_shell_args(Path::new("/bin/zsh")),
-                vec!["-l".to_string(), "-i".to_string()]
-            );
-            assert_eq!(
-                terminal_shell_args(Path::new("/bin/sh")),
-                vec!["-l".to_string(), "-i".to_string()]
-            );
-            assert_eq!(
-                terminal_shell_args(Path::new("/usr/bin/fish")),
+                terminal_shell_args_for(Path::new(shell), false),
                 vec!["-l".to_string(), "-i".to_string()]
             );
         }
@@ -1092,6 +1209,97 @@ mod tests {
     }
 
     #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn path_export_command_quotes_for_each_shell() {
+        let path = "/opt/it's/bin:/usr/bin";
+        assert_eq!(
+            path_export_command(Path::new("/bin/zsh"), path),
+            "export PATH='/opt/it'\\''s/bin:/usr/bin'"
+        );
+        assert_eq!(
+            path_export_command(Path::new("/usr/bin/fish"), path),
+            "set -gx PATH '/opt/it\\'s/bin' '/usr/bin'"
+        );
+        assert_eq!(
+            path_export_command(Path::new("cmd.exe"), r"C:\Tools;C:\Windows"),
+            r#"set "PATH=C:\Tools;C:\Windows""#
+        );
+        assert_eq!(
+            path_export_command(Path::new("pwsh.exe"), r"C:\It's;C:\Windows"),
+            r"$env:PATH = 'C:\It''s;C:\Windows'"
+        );
+    }
+
+    #[test]
+    fn shell_command_line_quotes_only_words_that_need_it() {
+        let args = vec!["resume".to_string(), "thr_123".to_string()];
+        assert_eq!(
+            shell_command_line(
+                Path::new("/bin/zsh"),
+                Path::new("/usr/local/bin/codex"),
+                &args
+            ),
+            "/usr/local/bin/codex resume thr_123"
+        );
+        assert_eq!(
+            shell_command_line(
+                Path::new("/bin/bash"),
+                Path::new("/opt/it's bin/codex"),
+                &args
+            ),
+            "'/opt/it'\\''s bin/codex' resume thr_123"
+        );
+        assert_eq!(
+            shell_command_line(
+                Path::new("/usr/bin/fish"),
+                Path::new("/opt/it's/codex"),
+                &args
+            ),
+            "'/opt/it\\'s/codex' resume thr_123"
+        );
+        assert_eq!(
+            shell_command_line(
+                Path::new("cmd.exe"),
+                Path::new(r"C:\Program Files\codex.cmd"),
+                &args
+            ),
+            r#""C:\Program Files\codex.cmd" resume thr_123"#
+        );
+        assert_eq!(
+            shell_command_line(
+                Path::new("pwsh.exe"),
+                Path::new(r"C:\Users\O'Neil\codex.cmd"),
+                &args
+            ),
+            r"& 'C:\Users\O''Neil\codex.cmd' resume thr_123"
+        );
+    }
+
+    #[tokio::test]
+    async fn login_shell_env_cache_reloads_after_invalidation() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = LoginShellEnvCache::new();
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            let count = loads.fetch_add(1, Ordering::SeqCst) + 1;
+            HashMap::from([(OsString::from("LOADS"), OsString::from(count.to_string()))])
+        };
+
+        let first = cache.get_or_load(load).await;
+        let cached = cache.get_or_load(load).await;
+        assert!(Arc::ptr_eq(&first, &cached));
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        cache.invalidate().await;
+        let reloaded = cache.get_or_load(load).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            reloaded.get(OsStr::new("LOADS")),
+            Some(&OsString::from("2"))
+        );
+    }
+
     #[test]
     fn login_probe_shell_args_match_shell_type() {
         assert_eq!(
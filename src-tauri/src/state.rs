@@ -1,11 +1,22 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use arc_swap::ArcSwap;
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    config::app_config::AppConfig, db::Database, engines::EngineManager, git::repo::FileTreeCache,
-    git::watcher::GitWatcherManager, power::KeepAwakeManager, terminal::TerminalManager,
+    config::app_config::AppConfig,
+    db::Database,
+    engines::{context_budget::ThreadContextUsage, EngineManager},
+    file_watcher::FileWatcherManager,
+    git::repo::FileTreeCache,
+    git::watcher::GitWatcherManager,
+    power::KeepAwakeManager,
+    terminal::TerminalManager,
     terminal_notifications::TerminalNotificationManager,
 };
 
@@ -16,6 +27,7 @@ pub struct AppState {
     pub config_write_lock: Arc<tokio::sync::Mutex<()>>,
     pub engines: Arc<EngineManager>,
     pub git_watchers: Arc<GitWatcherManager>,
+    pub file_watchers: Arc<FileWatcherManager>,
     pub terminals: Arc<TerminalManager>,
     pub notifications: Arc<TerminalNotificationManager>,
     pub keep_awake: Arc<KeepAwakeManager>,
@@ -23,9 +35,18 @@ pub struct AppState {
     pub file_tree_cache: Arc<FileTreeCache>,
 }
 
+const TURN_FINISH_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Tracks the running turn per thread. Cancellation tokens live behind an
+/// async lock; status checks read `visible`, an immutable snapshot of the
+/// registered thread ids that every write republishes while still holding
+/// that lock, so they never wait on streaming registrations and never see a
+/// half-applied change.
 #[derive(Default)]
 pub struct TurnManager {
     active: RwLock<HashMap<String, CancellationToken>>,
+    visible: ArcSwap<HashSet<String>>,
+    context_usage: RwLock<HashMap<String, ThreadContextUsage>>,
 }
 
 impl TurnManager {
@@ -36,11 +57,16 @@ impl TurnManager {
         }
 
         active.insert(thread_id.to_string(), token);
+        self.publish(&active);
         true
     }
 
-    pub async fn get(&self, thread_id: &str) -> Option<CancellationToken> {
-        self.active.read().await.get(thread_id).cloned()
+    pub fn is_active(&self, thread_id: &str) -> bool {
+        self.visible.load().contains(thread_id)
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.visible.load().len()
     }
 
     pub async fn cancel(&self, thread_id: &str) {
@@ -50,6 +76,123 @@ impl TurnManager {
     }
 
     pub async fn finish(&self, thread_id: &str) {
-        self.active.write().await.remove(thread_id);
+        let mut active = self.active.write().await;
+        if active.remove(thread_id).is_some() {
+            self.publish(&active);
+        }
+    }
+
+    /// Waits for the thread's turn to call `finish`. False when it was still
+    /// registered after `timeout`.
+    pub async fn wait_until_finished(&self, thread_id: &str, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if !self.is_active(thread_id) {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(TURN_FINISH_POLL_INTERVAL).await;
+        }
+    }
+
+    fn publish(&self, active: &HashMap<String, CancellationToken>) {
+        self.visible
+            .store(Arc::new(active.keys().cloned().collect()));
+    }
+
+    /// Keeps the latest context usage an engine reported for a thread, which
+    /// the pre-send budget check adds to the size of the next turn.
+    pub async fn record_context_usage(&self, thread_id: &str, usage: ThreadContextUsage) {
+        self.context_usage
+            .write()
+            .await
+            .insert(thread_id.to_string(), usage);
+    }
+
+    pub async fn context_usage(&self, thread_id: &str) -> Option<ThreadContextUsage> {
+        self.context_usage.read().await.get(thread_id).copied()
+    }
+
+    /// Drops the thread's recorded usage, e.g. when it moves to a fresh
+    /// engine session.
+    pub async fn forget_context_usage(&self, thread_id: &str) {
+        self.context_usage.write().await.remove(thread_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn status_reads_track_registrations_without_locking() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let turns = Arc::new(TurnManager::default());
+        // Seqlock-style epochs: writer i bumps `epochs[i]` to odd before
+        // registering and back to even after `finish` returns. A reader that
+        // sees the same even epoch on both sides of `is_active` knows the
+        // thread had no turn in between, so it must not look active.
+        let epochs = Arc::new((0..8).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+        let thread_id = |index: usize| format!("thread-{index}");
+
+        let writers = (0..epochs.len())
+            .map(|index| {
+                let turns = Arc::clone(&turns);
+                let epochs = Arc::clone(&epochs);
+                let thread_id = thread_id(index);
+                tokio::spawn(async move {
+                    for _ in 0..200 {
+                        epochs[index].fetch_add(1, Ordering::SeqCst);
+                        assert!(
+                            turns
+                                .try_register(&thread_id, CancellationToken::new())
+                                .await
+                        );
+                        assert!(turns.is_active(&thread_id));
+                        assert!(
+                            !turns
+                                .try_register(&thread_id, CancellationToken::new())
+                                .await
+                        );
+                        turns.finish(&thread_id).await;
+                        assert!(!turns.is_active(&thread_id));
+                        epochs[index].fetch_add(1, Ordering::SeqCst);
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let reader = {
+            let turns = Arc::clone(&turns);
+            let epochs = Arc::clone(&epochs);
+            tokio::spawn(async move {
+                for _ in 0..2_000 {
+                    for (index, epoch) in epochs.iter().enumerate() {
+                        let before = epoch.load(Ordering::SeqCst);
+                        let active = turns.is_active(&thread_id(index));
+                        let after = epoch.load(Ordering::SeqCst);
+                        if before == after && before % 2 == 0 {
+                            assert!(!active, "thread-{index} visible outside its turn");
+                        }
+                    }
+                    assert!(turns.active_count() <= epochs.len());
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+
+        for writer in writers {
+            writer.await.expect("writer");
+        }
+        reader.await.expect("reader");
+        assert_eq!(turns.active_count(), 0);
+        assert!(
+            turns
+                .wait_until_finished("thread-0", Duration::from_millis(1))
+                .await
+        );
     }
 }
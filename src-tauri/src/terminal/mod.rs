@@ -11,20 +11,31 @@ use std::{
 };
 
 mod osc_notifications;
+mod scrollback;
+mod search;
+mod transcript;
 
 use anyhow::Context;
 use chrono::Utc;
 use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, MasterPty, PtySize};
 use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager};
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, RwLock};
 use uuid::Uuid;
 
 use self::osc_notifications::{TerminalOscNotification, TerminalOscNotificationParser};
+use self::scrollback::TerminalScrollbackFile;
+use self::transcript::TerminalTranscript;
+use crate::config::app_config::{
+    AppConfig, TerminalThrottleConfig, DEFAULT_TERMINAL_BUFFER_MAX_BYTES,
+    DEFAULT_TERMINAL_CLOSE_GRACE_PERIOD_MS, DEFAULT_TERMINAL_MAX_EMIT_BYTES,
+    DEFAULT_TERMINAL_MAX_SESSIONS_PER_WORKSPACE, DEFAULT_TERMINAL_SCROLLBACK_MAX_BYTES,
+};
 use crate::models::{
-    TerminalEnvSnapshotDto, TerminalIoCountersDto, TerminalLatencySnapshotDto,
-    TerminalOutputThrottleSnapshotDto, TerminalRendererDiagnosticsDto, TerminalReplayChunkDto,
-    TerminalResizeSnapshotDto, TerminalResumeSessionDto, TerminalSessionDto,
+    TerminalAttachDto, TerminalEnvSnapshotDto, TerminalIoCountersDto, TerminalLatencySnapshotDto,
+    TerminalOutputSearchDto, TerminalOutputThrottleSnapshotDto, TerminalPathInjectionDto,
+    TerminalRendererDiagnosticsDto, TerminalReplayChunkDto, TerminalResizeSnapshotDto,
+    TerminalResumeSessionDto, TerminalScrollbackDto, TerminalSessionDto, TerminalSessionLimitsDto,
 };
 #[cfg(target_os = "windows")]
 use crate::process_utils;
@@ -32,28 +43,54 @@ use crate::runtime_env;
 use crate::state::AppState;
 use crate::terminal_notifications::{TerminalNotificationManager, TerminalNotificationSessionEnv};
 
-const TERMINAL_OUTPUT_MIN_EMIT_INTERVAL_MS: u64 = 16;
-const TERMINAL_OUTPUT_MAX_EMIT_BYTES: usize = 256 * 1024;
-const TERMINAL_OUTPUT_BUFFER_MAX_BYTES: usize = 2 * 1024 * 1024;
 const TERMINAL_REPLAY_MAX_CHUNKS: usize = 4096;
 const TERMINAL_REPLAY_MAX_BYTES: usize = 4 * 1024 * 1024;
 const TERMINAL_COMPLETED_REPLAY_GRACE_MS: u64 = 60_000;
 const TERMINAL_COMPLETED_REPLAY_MAX_SESSIONS: usize = 32;
 const TERMINAL_COMPLETED_REPLAY_MAX_TOTAL_BYTES: usize = 16 * 1024 * 1024;
+const TERMINAL_SCROLLBACK_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const TERMINAL_CLOSE_POLL_INTERVAL: Duration = Duration::from_millis(25);
+const TERMINAL_IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+// Resizes are applied once requests pause this long, or at the latest
+// TERMINAL_RESIZE_MAX_DELAY after the first one, so a drag still repaints.
+const TERMINAL_RESIZE_DEBOUNCE: Duration = Duration::from_millis(40);
+const TERMINAL_RESIZE_MAX_DELAY: Duration = Duration::from_millis(200);
+const TERMINAL_IDLE_WARNING_LEAD: Duration = Duration::from_secs(60);
+// How long a shell whose PTY reader failed gets to be reaped before it is
+// treated as still running.
+const TERMINAL_READER_EXIT_WAIT: Duration = Duration::from_secs(1);
 
 #[derive(Default)]
 pub struct TerminalManager {
     workspaces: RwLock<HashMap<String, HashMap<String, Arc<TerminalSessionHandle>>>>,
+    /// Sessions per workspace that passed the limit check but are still
+    /// spawning. Only touched while holding the `workspaces` write lock.
+    pending_sessions: Mutex<HashMap<String, usize>>,
     completed_replays: RwLock<HashMap<String, HashMap<String, TerminalReplaySnapshot>>>,
+    /// Listeners waiting for a session to exit, keyed by session id.
+    exit_watchers: Mutex<HashMap<String, Vec<oneshot::Sender<()>>>>,
 }
 
 struct TerminalSessionHandle {
     meta: TerminalSessionDto,
     shell_pid: Option<u32>,
+    throttle: TerminalThrottleConfig,
     diagnostics: Mutex<TerminalSessionDiagnosticsState>,
     io_counters: TerminalSessionIoCounters,
     replay_seq: AtomicU64,
     replay_state: Mutex<TerminalReplayState>,
+    scrollback: Mutex<TerminalScrollbackBuffer>,
+    scrollback_max_bytes: usize,
+    scrollback_file: Mutex<Option<TerminalScrollbackFile>>,
+    transcript: Option<TerminalTranscript>,
+    current_cwd: Mutex<Option<String>>,
+    title: Mutex<Option<String>>,
+    title_throttle: Mutex<TitleEmitThrottle>,
+    attached: AtomicBool,
+    close_grace_period: Duration,
+    started_at_ms: u64,
+    idle_warning: Mutex<Option<IdleWarning>>,
+    pending_resize: Mutex<PendingResize>,
     // writer, master, and child each get their own lock: a write_all blocked on
     // a full PTY buffer must not wedge resize/kill/shutdown, and kill delivery
     // goes through the cloned killer so it never waits behind child.wait().
@@ -63,6 +100,192 @@ struct TerminalSessionHandle {
     child_killer: Mutex<Box<dyn ChildKiller + Send + Sync>>,
 }
 
+/// Signals `terminal_signal` can deliver to a session's foreground job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalSignal {
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
+impl TerminalSignal {
+    /// Accepts `SIGINT`/`INT`/`int` style names.
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        let name = raw.trim().to_ascii_uppercase();
+        match name.strip_prefix("SIG").unwrap_or(&name) {
+            "INT" => Ok(Self::Interrupt),
+            "TERM" => Ok(Self::Terminate),
+            "KILL" => Ok(Self::Kill),
+            _ => anyhow::bail!(
+                "unsupported terminal signal `{raw}`; expected SIGINT, SIGTERM or SIGKILL"
+            ),
+        }
+    }
+
+    #[cfg(unix)]
+    fn as_raw(self) -> libc::c_int {
+        match self {
+            Self::Interrupt => libc::SIGINT,
+            Self::Terminate => libc::SIGTERM,
+            Self::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+/// Decoded output tail kept for repainting, stamped with the seq of the last
+/// chunk it contains.
+#[derive(Default)]
+struct TerminalScrollbackBuffer {
+    data: String,
+    seq: u64,
+    truncated: bool,
+    /// Bytes trimmed from the front over the session's lifetime.
+    origin: u64,
+}
+
+impl TerminalScrollbackBuffer {
+    fn push(&mut self, seq: u64, data: &str, max_bytes: usize) {
+        self.data.push_str(data);
+        self.seq = seq;
+        let trimmed = trim_string_to_tail(&mut self.data, max_bytes);
+        if trimmed > 0 {
+            self.truncated = true;
+            self.origin += trimmed as u64;
+        }
+    }
+}
+
+const TITLE_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Rate limit for `terminal-title` events. The first change after a quiet
+/// period is emitted at once; changes inside the interval collapse into one
+/// trailing emit that carries whatever title is current when it fires.
+#[derive(Debug, Default)]
+struct TitleEmitThrottle {
+    last_emit_at: Option<Instant>,
+    flush_scheduled: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TitleEmitAction {
+    EmitNow,
+    FlushAfter(Duration),
+    /// A trailing flush is already pending and will pick this change up.
+    Coalesced,
+}
+
+impl TitleEmitThrottle {
+    fn on_change(&mut self, now: Instant) -> TitleEmitAction {
+        if self.flush_scheduled {
+            return TitleEmitAction::Coalesced;
+        }
+        match self
+            .last_emit_at
+            .map(|at| now.saturating_duration_since(at))
+        {
+            Some(elapsed) if elapsed < TITLE_EMIT_INTERVAL => {
+                self.flush_scheduled = true;
+                TitleEmitAction::FlushAfter(TITLE_EMIT_INTERVAL - elapsed)
+            }
+            _ => {
+                self.last_emit_at = Some(now);
+                TitleEmitAction::EmitNow
+            }
+        }
+    }
+
+    fn on_flush(&mut self, now: Instant) {
+        self.flush_scheduled = false;
+        self.last_emit_at = Some(now);
+    }
+}
+
+/// Per-session settings resolved from `AppConfig` when the session is
+/// created.
+#[derive(Debug, Clone)]
+pub struct TerminalSessionOptions {
+    pub throttle: TerminalThrottleConfig,
+    pub scrollback_max_bytes: usize,
+    pub persist_scrollback: bool,
+    pub close_grace_period: Duration,
+    pub max_sessions_per_workspace: usize,
+    pub env_overrides: TerminalEnvOverrides,
+    /// Write the session's decoded output and stdin to a transcript file.
+    pub record_transcript: bool,
+}
+
+/// Caller-supplied variables applied on top of the computed terminal
+/// environment. `PATH` is merged in front of the computed value unless
+/// `replace_path` is set.
+#[derive(Debug, Clone, Default)]
+pub struct TerminalEnvOverrides {
+    pub vars: HashMap<String, String>,
+    pub replace_path: bool,
+}
+
+impl TerminalEnvOverrides {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for key in self.vars.keys() {
+            if key.is_empty() {
+                anyhow::bail!("terminal environment variable names cannot be empty");
+            }
+            if key.contains('=') {
+                anyhow::bail!("terminal environment variable `{key}` cannot contain `=`");
+            }
+            if key.contains('\0') {
+                anyhow::bail!(
+                    "terminal environment variable `{}` cannot contain a NUL byte",
+                    key.escape_default()
+                );
+            }
+        }
+        for (key, value) in &self.vars {
+            if value.contains('\0') {
+                anyhow::bail!(
+                    "value of terminal environment variable `{key}` cannot contain a NUL byte"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for TerminalSessionOptions {
+    fn default() -> Self {
+        Self {
+            throttle: TerminalThrottleConfig::default(),
+            scrollback_max_bytes: DEFAULT_TERMINAL_SCROLLBACK_MAX_BYTES,
+            persist_scrollback: false,
+            close_grace_period: Duration::from_millis(DEFAULT_TERMINAL_CLOSE_GRACE_PERIOD_MS),
+            max_sessions_per_workspace: DEFAULT_TERMINAL_MAX_SESSIONS_PER_WORKSPACE,
+            env_overrides: TerminalEnvOverrides::default(),
+            record_transcript: false,
+        }
+    }
+}
+
+impl TerminalSessionOptions {
+    /// Options from the app config for sessions in `workspace_id`, falling
+    /// back to the defaults when the config cannot be read.
+    pub async fn load_for_workspace(workspace_id: &str) -> Self {
+        let workspace_id = workspace_id.to_string();
+        tokio::task::spawn_blocking(move || match AppConfig::load_or_create() {
+            Ok(config) => Self {
+                throttle: config.terminal_throttle(&workspace_id),
+                scrollback_max_bytes: config.terminal_scrollback_max_bytes(),
+                persist_scrollback: config.terminal_scrollback_persistence_enabled(),
+                close_grace_period: config.terminal_close_grace_period(),
+                max_sessions_per_workspace: config.terminal_max_sessions_per_workspace(),
+                env_overrides: TerminalEnvOverrides::default(),
+                record_transcript: config.debug.record_terminal_transcripts,
+            },
+            Err(_) => Self::default(),
+        })
+        .await
+        .unwrap_or_default()
+    }
+}
+
 #[derive(Default)]
 struct TerminalReplayState {
     entries: VecDeque<TerminalReplayChunkDto>,
@@ -89,6 +312,7 @@ impl TerminalReplaySnapshot {
 
 struct TerminalSessionDiagnosticsState {
     env_snapshot: TerminalEnvSnapshotDto,
+    env_overrides: TerminalEnvOverrides,
     last_resize: Option<TerminalResizeSnapshotDto>,
     last_zero_pixel_warning_at_ms: Option<i64>,
 }
@@ -98,6 +322,7 @@ struct TerminalSessionIoCounters {
     stdin_writes: AtomicU64,
     stdin_bytes: AtomicU64,
     stdin_ctrl_c: AtomicU64,
+    signals_sent: AtomicU64,
     last_stdin_write_duration_ms: AtomicU64,
     stdout_reads: AtomicU64,
     stdout_bytes: AtomicU64,
@@ -125,14 +350,16 @@ struct SharedTerminalOutputState {
 
 struct SharedTerminalOutput {
     buffer: Mutex<SharedTerminalOutputState>,
+    max_bytes: usize,
     ready: Condvar,
     done: AtomicBool,
 }
 
 impl SharedTerminalOutput {
-    fn new() -> Self {
+    fn new(max_bytes: usize) -> Self {
         Self {
             buffer: Mutex::new(SharedTerminalOutputState::default()),
+            max_bytes,
             ready: Condvar::new(),
             done: AtomicBool::new(false),
         }
@@ -147,8 +374,8 @@ impl SharedTerminalOutput {
             return (0, state.total_bytes);
         }
 
-        if chunk.len() > TERMINAL_OUTPUT_BUFFER_MAX_BYTES {
-            trim_string_to_tail(&mut chunk, TERMINAL_OUTPUT_BUFFER_MAX_BYTES);
+        if chunk.len() > self.max_bytes {
+            trim_string_to_tail(&mut chunk, self.max_bytes);
         }
 
         let mut state = self
@@ -159,7 +386,7 @@ impl SharedTerminalOutput {
         state.chunks.push_back(chunk);
 
         let mut trimmed = 0;
-        while state.total_bytes > TERMINAL_OUTPUT_BUFFER_MAX_BYTES {
+        while state.total_bytes > self.max_bytes {
             let Some(removed) = state.chunks.pop_front() else {
                 break;
             };
@@ -300,6 +527,7 @@ struct TerminalExitEvent {
     session_id: String,
     code: Option<i32>,
     signal: Option<i32>,
+    error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -310,13 +538,194 @@ struct TerminalForegroundChangedEvent {
     name: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalCwdChangedEvent {
+    session_id: String,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalIdleWarningEvent {
+    session_id: String,
+    closes_in_ms: u64,
+}
+
+/// Idle warning sent for a session, keyed by the activity stamp it was
+/// sent for: any newer input or output cancels it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IdleWarning {
+    activity_ms: u64,
+    warned_at_ms: u64,
+}
+
+/// Latest requested PTY size not yet applied, plus whether a flush task is
+/// already scheduled for it.
+#[derive(Debug, Default)]
+struct PendingResize {
+    size: Option<PtySize>,
+    first_requested_at: Option<Instant>,
+    last_requested_at: Option<Instant>,
+    flushing: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResizeStep {
+    Wait(Duration),
+    Apply(PtySize),
+    Idle,
+}
+
+impl PendingResize {
+    /// Records `size`, replacing any earlier pending one. True when no flush
+    /// is scheduled yet and the caller has to start one.
+    fn queue(&mut self, size: PtySize, now: Instant) -> bool {
+        self.size = Some(size);
+        self.first_requested_at.get_or_insert(now);
+        self.last_requested_at = Some(now);
+        !std::mem::replace(&mut self.flushing, true)
+    }
+
+    fn next(&mut self, now: Instant) -> ResizeStep {
+        let (Some(size), Some(first), Some(last)) =
+            (self.size, self.first_requested_at, self.last_requested_at)
+        else {
+            self.flushing = false;
+            return ResizeStep::Idle;
+        };
+        let due = (last + TERMINAL_RESIZE_DEBOUNCE).min(first + TERMINAL_RESIZE_MAX_DELAY);
+        if now < due {
+            return ResizeStep::Wait(due - now);
+        }
+        self.take();
+        ResizeStep::Apply(size)
+    }
+
+    fn take(&mut self) -> Option<PtySize> {
+        self.first_requested_at = None;
+        self.last_requested_at = None;
+        self.size.take()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IdleAction {
+    Keep,
+    Warn,
+    Close,
+}
+
+/// A session is warned once it is within the warning lead of the timeout,
+/// and closed only after the timeout passed and the warning for the same
+/// activity has been out for the full lead.
+fn idle_action(
+    now_ms: u64,
+    last_activity_ms: u64,
+    timeout_ms: u64,
+    warning: Option<IdleWarning>,
+) -> IdleAction {
+    let idle_ms = now_ms.saturating_sub(last_activity_ms);
+    let lead_ms = TERMINAL_IDLE_WARNING_LEAD.as_millis() as u64;
+    if idle_ms.saturating_add(lead_ms) < timeout_ms {
+        return IdleAction::Keep;
+    }
+    match warning {
+        Some(warning) if warning.activity_ms == last_activity_ms => {
+            if idle_ms >= timeout_ms && now_ms.saturating_sub(warning.warned_at_ms) >= lead_ms {
+                IdleAction::Close
+            } else {
+                IdleAction::Keep
+            }
+        }
+        _ => IdleAction::Warn,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalTitleChangedEvent {
+    session_id: String,
+    title: String,
+}
+
+/// How a shell ended. `code` is set for a normal exit and `signal` for one
+/// killed by a signal; when neither could be determined, `error` says why,
+/// so the UI does not mistake the session for a clean exit.
+#[derive(Debug, Clone, Default)]
 struct ExitPayload {
     code: Option<i32>,
     signal: Option<i32>,
+    error: Option<String>,
+}
+
+impl ExitPayload {
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Self::default()
+        }
+    }
 }
 
 impl TerminalManager {
+    /// Open (or still spawning) sessions in the workspace against `limit`.
+    pub async fn session_limits(
+        &self,
+        workspace_id: &str,
+        limit: usize,
+    ) -> TerminalSessionLimitsDto {
+        let sessions = self.workspaces.read().await;
+        let open =
+            sessions.get(workspace_id).map_or(0, HashMap::len) + self.pending_count(workspace_id);
+        TerminalSessionLimitsDto {
+            workspace_id: workspace_id.to_string(),
+            open,
+            limit,
+        }
+    }
+
+    /// Counts a new session against the workspace limit before it spawns.
+    /// The check and the reservation happen under the `workspaces` write
+    /// lock, so concurrent creates cannot overshoot the limit.
+    async fn reserve_session_slot(&self, workspace_id: &str, limit: usize) -> anyhow::Result<()> {
+        let sessions = self.workspaces.write().await;
+        let open =
+            sessions.get(workspace_id).map_or(0, HashMap::len) + self.pending_count(workspace_id);
+        if open >= limit {
+            anyhow::bail!("terminal session limit reached for this workspace ({limit} sessions)");
+        }
+        let mut pending = self
+            .pending_sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *pending.entry(workspace_id.to_string()).or_default() += 1;
+        Ok(())
+    }
+
+    /// Caller must hold the `workspaces` write lock.
+    fn release_session_slot(&self, workspace_id: &str) {
+        let mut pending = self
+            .pending_sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(count) = pending.get_mut(workspace_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                pending.remove(workspace_id);
+            }
+        }
+    }
+
+    fn pending_count(&self, workspace_id: &str) -> usize {
+        self.pending_sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(workspace_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
     pub async fn list_sessions(&self, workspace_id: &str) -> Vec<TerminalSessionDto> {
         let sessions = self.workspaces.read().await;
         let mut out = sessions
@@ -324,7 +733,7 @@ impl TerminalManager {
             .map(|items| {
                 items
                     .values()
-                    .map(|session| session.meta.clone())
+                    .map(|session| session.session_dto())
                     .collect::<Vec<_>>()
             })
             .unwrap_or_default();
@@ -344,6 +753,79 @@ impl TerminalManager {
         Ok(session.renderer_diagnostics())
     }
 
+    /// Snapshots every live session of the workspace, oldest first. Sessions
+    /// closing concurrently are simply absent from the result.
+    pub async fn workspace_renderer_diagnostics(
+        &self,
+        workspace_id: &str,
+    ) -> Vec<TerminalRendererDiagnosticsDto> {
+        let mut sessions = self
+            .workspaces
+            .read()
+            .await
+            .get(workspace_id)
+            .map(|items| items.values().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        sessions.sort_by(|a, b| a.meta.created_at.cmp(&b.meta.created_at));
+        sessions
+            .iter()
+            .map(|session| session.renderer_diagnostics())
+            .collect()
+    }
+
+    /// Buffered output of a live session for repainting a remounted view.
+    /// Resume live events after the returned `seq`; anything emitted between
+    /// the snapshot and the subscription is recoverable through
+    /// `drain_output` from that seq.
+    pub async fn scrollback(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+    ) -> anyhow::Result<TerminalScrollbackDto> {
+        let session = self
+            .get_session(workspace_id, session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("terminal session not found: {session_id}"))?;
+        Ok(session.scrollback_snapshot())
+    }
+
+    /// Searches the session's retained scrollback; see
+    /// [`TerminalOutputSearchDto`] for how offsets map onto the buffer.
+    pub async fn search_output(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+        query: String,
+        case_sensitive: bool,
+    ) -> anyhow::Result<TerminalOutputSearchDto> {
+        if query.is_empty() {
+            anyhow::bail!("search query cannot be empty");
+        }
+        let session = self
+            .get_session(workspace_id, session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("terminal session not found: {session_id}"))?;
+        tokio::task::spawn_blocking(move || session.search_output(&query, case_sensitive))
+            .await
+            .context("terminal search task failed")
+    }
+
+    /// Transcript file for a session: the live recorder's path, or a file
+    /// left behind by an earlier session. `None` when nothing was recorded.
+    pub async fn transcript_path(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+    ) -> anyhow::Result<Option<String>> {
+        if let Some(session) = self.get_session(workspace_id, session_id).await {
+            if let Some(transcript) = &session.transcript {
+                return Ok(Some(transcript.path().to_string_lossy().to_string()));
+            }
+        }
+        let path = transcript::transcript_path(&transcript::transcript_dir(), session_id)?;
+        Ok(path.is_file().then(|| path.to_string_lossy().to_string()))
+    }
+
     pub async fn resume_session(
         &self,
         workspace_id: &str,
@@ -362,6 +844,33 @@ impl TerminalManager {
         Err(anyhow::anyhow!("terminal session not found: {session_id}"))
     }
 
+    /// Reconnects a view to a live session, e.g. after a webview reload.
+    pub async fn attach(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+        from_seq: Option<u64>,
+    ) -> anyhow::Result<TerminalAttachDto> {
+        let session = self
+            .get_session(workspace_id, session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("terminal session not found: {session_id}"))?;
+        tokio::task::spawn_blocking(move || session.attach(from_seq))
+            .await
+            .context("terminal attach task failed")
+    }
+
+    /// Stops output events for a session whose view is hidden. The PTY is
+    /// still drained into the bounded replay buffer.
+    pub async fn detach(&self, workspace_id: &str, session_id: &str) -> anyhow::Result<()> {
+        let session = self
+            .get_session(workspace_id, session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("terminal session not found: {session_id}"))?;
+        session.detach();
+        Ok(())
+    }
+
     pub async fn drain_output(
         &self,
         workspace_id: &str,
@@ -369,10 +878,11 @@ impl TerminalManager {
         from_seq: Option<u64>,
         target_bytes: usize,
     ) -> anyhow::Result<TerminalResumeSessionDto> {
-        let target_bytes = target_bytes.max(TERMINAL_OUTPUT_MAX_EMIT_BYTES);
         if let Some(session) = self.get_session(workspace_id, session_id).await {
+            let target_bytes = target_bytes.max(session.throttle.max_emit_bytes);
             return Ok(session.replay_since_limited(from_seq, target_bytes));
         }
+        let target_bytes = target_bytes.max(DEFAULT_TERMINAL_MAX_EMIT_BYTES);
         if let Some(replay) = self
             .completed_replay_since(workspace_id, session_id, from_seq, target_bytes)
             .await
@@ -382,6 +892,7 @@ impl TerminalManager {
         Err(anyhow::anyhow!("terminal session not found: {session_id}"))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_session(
         self: &Arc<Self>,
         app: AppHandle,
@@ -390,7 +901,11 @@ impl TerminalManager {
         cwd: String,
         cols: u16,
         rows: u16,
+        options: TerminalSessionOptions,
     ) -> anyhow::Result<TerminalSessionDto> {
+        options.env_overrides.validate()?;
+        self.reserve_session_slot(&workspace_id, options.max_sessions_per_workspace)
+            .await?;
         let session_id = Uuid::new_v4().to_string();
         let notification_env = notifications.session_env(&workspace_id, &session_id).await;
         let workspace_for_spawn = workspace_id.clone();
@@ -403,10 +918,20 @@ impl TerminalManager {
                 cols,
                 rows,
                 notification_env,
+                options,
             )
         })
         .await
-        .context("terminal spawn task failed")??;
+        .context("terminal spawn task failed")
+        .and_then(|spawned| spawned);
+        let spawned = match spawned {
+            Ok(spawned) => spawned,
+            Err(error) => {
+                let _sessions = self.workspaces.write().await;
+                self.release_session_slot(&workspace_id);
+                return Err(error);
+            }
+        };
 
         let created = spawned.session.meta.clone();
 
@@ -416,6 +941,7 @@ impl TerminalManager {
                 .entry(workspace_id.clone())
                 .or_default()
                 .insert(created.id.clone(), Arc::clone(&spawned.session));
+            self.release_session_slot(&workspace_id);
         }
 
         self.spawn_reader(
@@ -428,6 +954,41 @@ impl TerminalManager {
         Ok(created)
     }
 
+    /// Resolves once the session has exited, whether its shell ended or it
+    /// was closed. Resolves at once when the session is already gone.
+    pub async fn watch_exit(&self, workspace_id: &str, session_id: &str) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        // Exits take the session out under the write lock before notifying,
+        // so holding the read lock here cannot miss one.
+        let sessions = self.workspaces.read().await;
+        let live = sessions
+            .get(workspace_id)
+            .is_some_and(|workspace| workspace.contains_key(session_id));
+        if live {
+            self.exit_watchers
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner())
+                .entry(session_id.to_string())
+                .or_default()
+                .push(sender);
+        } else {
+            let _ = sender.send(());
+        }
+        receiver
+    }
+
+    fn notify_exit(&self, session_id: &str) {
+        let watchers = self
+            .exit_watchers
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .remove(session_id)
+            .unwrap_or_default();
+        for watcher in watchers {
+            let _ = watcher.send(());
+        }
+    }
+
     pub async fn write(
         &self,
         workspace_id: &str,
@@ -473,12 +1034,55 @@ impl TerminalManager {
             .get_session(workspace_id, session_id)
             .await
             .ok_or_else(|| anyhow::anyhow!("terminal session not found: {session_id}"))?;
-        tokio::task::spawn_blocking(move || session.resize(cols, rows, pixel_width, pixel_height))
+        let size = PtySize {
+            rows: rows.max(1),
+            cols: cols.max(1),
+            pixel_width,
+            pixel_height,
+        };
+        let start_flush = session
+            .pending_resize
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .queue(size, Instant::now());
+        if start_flush {
+            tokio::spawn(flush_pending_resizes(session));
+        }
+        Ok(())
+    }
+
+    /// Signals the foreground job of a session, leaving the shell running.
+    pub async fn signal(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+        signal: TerminalSignal,
+    ) -> anyhow::Result<()> {
+        let session = self
+            .get_session(workspace_id, session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("terminal session not found: {session_id}"))?;
+        tokio::task::spawn_blocking(move || session.signal_foreground(signal))
             .await
-            .context("terminal resize task failed")??;
+            .context("terminal signal task failed")??;
         Ok(())
     }
 
+    /// Writes a PATH assignment for the current environment into the session.
+    pub async fn inject_path(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+    ) -> anyhow::Result<TerminalPathInjectionDto> {
+        let session = self
+            .get_session(workspace_id, session_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("terminal session not found: {session_id}"))?;
+        tokio::task::spawn_blocking(move || session.inject_path())
+            .await
+            .context("terminal inject path task failed")?
+    }
+
     pub async fn close_session(
         self: &Arc<Self>,
         app: AppHandle,
@@ -489,27 +1093,174 @@ impl TerminalManager {
             return Ok(());
         };
         let event_session_id = session.meta.id.clone();
-        let exit = tokio::task::spawn_blocking(move || session.kill_and_wait())
-            .await
-            .context("terminal close task failed")?;
+        let exit = tokio::task::spawn_blocking(move || {
+            let exit = session.kill_and_wait();
+            session.discard_scrollback();
+            exit
+        })
+        .await
+        .context("terminal close task failed")?;
         emit_exit(&app, workspace_id, &event_session_id, exit);
+        self.notify_exit(&event_session_id);
         Ok(())
     }
 
+    /// Closes every session in the workspace. Scrollback files survive only
+    /// when `keep_scrollback` is set, i.e. the workspace is being archived.
+    /// Returns the ids of the sessions that were closed.
     pub async fn close_workspace(
         self: &Arc<Self>,
         app: AppHandle,
         workspace_id: &str,
-    ) -> anyhow::Result<()> {
+        keep_scrollback: bool,
+    ) -> anyhow::Result<Vec<String>> {
         let sessions = self.take_workspace_sessions(workspace_id).await;
+        let mut closed = Vec::with_capacity(sessions.len());
         for session in sessions {
             let event_session_id = session.meta.id.clone();
-            let exit = tokio::task::spawn_blocking(move || session.kill_and_wait())
-                .await
-                .context("terminal workspace close task failed")?;
+            let exit = tokio::task::spawn_blocking(move || {
+                let exit = session.kill_and_wait();
+                if !keep_scrollback {
+                    session.discard_scrollback();
+                }
+                exit
+            })
+            .await
+            .context("terminal workspace close task failed")?;
             emit_exit(&app, workspace_id, &event_session_id, exit);
+            self.notify_exit(&event_session_id);
+            closed.push(event_session_id);
+        }
+        Ok(closed)
+    }
+
+    /// Tail of a session's output for repainting after a restart, at most
+    /// `max_bytes` (default: the output buffer cap). A live session answers
+    /// from its in-memory scrollback; otherwise the persisted scrollback file
+    /// is read, which is empty when persistence was off.
+    pub async fn restore_scrollback(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+        max_bytes: Option<usize>,
+    ) -> anyhow::Result<String> {
+        if let Some(session) = self.get_session(workspace_id, session_id).await {
+            let mut data = session.scrollback_snapshot().data;
+            if let Some(max_bytes) = max_bytes {
+                trim_string_to_tail(&mut data, max_bytes);
+            }
+            return Ok(data);
+        }
+
+        let max_bytes = max_bytes.unwrap_or(DEFAULT_TERMINAL_BUFFER_MAX_BYTES);
+        let session_id = session_id.to_string();
+        tokio::task::spawn_blocking(move || {
+            scrollback::read_scrollback_tail(&scrollback::scrollback_dir(), &session_id, max_bytes)
+        })
+        .await
+        .context("terminal scrollback restore task failed")?
+    }
+
+    /// Closes sessions with no input or output for the configured idle
+    /// timeout. A `terminal-idle-warning-{workspace_id}` event goes out a
+    /// minute ahead; writing to the session cancels the close. Sessions
+    /// running a foreground job are never closed.
+    pub async fn run_idle_reaper(self: Arc<Self>, app: AppHandle) {
+        loop {
+            tokio::time::sleep(TERMINAL_IDLE_CHECK_INTERVAL).await;
+            let timeout = tokio::task::spawn_blocking(|| {
+                AppConfig::load_or_create()
+                    .ok()
+                    .and_then(|config| config.terminal_idle_timeout())
+            })
+            .await
+            .ok()
+            .flatten();
+            if let Some(timeout) = timeout {
+                self.reap_idle_sessions(&app, timeout).await;
+            }
+        }
+    }
+
+    async fn reap_idle_sessions(self: &Arc<Self>, app: &AppHandle, timeout: Duration) {
+        let sessions = {
+            let workspaces = self.workspaces.read().await;
+            workspaces
+                .iter()
+                .flat_map(|(workspace_id, sessions)| {
+                    sessions
+                        .values()
+                        .map(|session| (workspace_id.clone(), Arc::clone(session)))
+                })
+                .collect::<Vec<_>>()
+        };
+        let now_ms = Utc::now().timestamp_millis().max(0) as u64;
+        let timeout_ms = timeout.as_millis() as u64;
+        for (workspace_id, session) in sessions {
+            let last_activity_ms = session.last_activity_ms();
+            let warning = *session
+                .idle_warning
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner());
+            let action = idle_action(now_ms, last_activity_ms, timeout_ms, warning);
+            if action == IdleAction::Keep {
+                continue;
+            }
+            let probe = Arc::clone(&session);
+            let busy = tokio::task::spawn_blocking(move || probe.has_foreground_job())
+                .await
+                .unwrap_or(true);
+            if busy {
+                continue;
+            }
+            match action {
+                IdleAction::Warn => {
+                    *session
+                        .idle_warning
+                        .lock()
+                        .unwrap_or_else(|poison| poison.into_inner()) = Some(IdleWarning {
+                        activity_ms: last_activity_ms,
+                        warned_at_ms: now_ms,
+                    });
+                    let idle_ms = now_ms.saturating_sub(last_activity_ms);
+                    let closes_in_ms = timeout_ms
+                        .saturating_sub(idle_ms)
+                        .max(TERMINAL_IDLE_WARNING_LEAD.as_millis() as u64);
+                    emit_idle_warning(app, &workspace_id, &session.meta.id, closes_in_ms);
+                }
+                IdleAction::Close => {
+                    log::info!(
+                        "closing idle terminal session: workspace_id={}, session_id={}",
+                        workspace_id,
+                        session.meta.id
+                    );
+                    if let Err(error) = self
+                        .close_session(app.clone(), &workspace_id, &session.meta.id)
+                        .await
+                    {
+                        log::warn!("failed to close idle terminal session: {error}");
+                    }
+                }
+                IdleAction::Keep => {}
+            }
+        }
+    }
+
+    /// Removes scrollback left by sessions that exited on their own and were
+    /// never restored.
+    pub async fn prune_stale_scrollback(&self) {
+        match tokio::task::spawn_blocking(|| {
+            scrollback::prune_scrollback_files(
+                &scrollback::scrollback_dir(),
+                TERMINAL_SCROLLBACK_MAX_AGE,
+            )
+        })
+        .await
+        {
+            Ok(0) => {}
+            Ok(removed) => log::info!("pruned {removed} stale terminal scrollback file(s)"),
+            Err(error) => log::warn!("terminal scrollback prune task failed: {error}"),
         }
-        Ok(())
     }
 
     pub async fn shutdown(&self) {
@@ -634,11 +1385,12 @@ impl TerminalManager {
             // output. In release builds the PTY reader can outpace the webview, flooding
             // IPC and making the terminal feel frozen (including Ctrl+C). We want to:
             // - Drain the PTY continuously (never sleep in the reader)
-            // - Coalesce and rate-limit IPC emissions (emit at most ~60Hz)
+            // - Coalesce and rate-limit IPC emissions (at most once per the session's
+            //   configured `min_emit_interval_ms`)
             //
             // Implementation: the reader thread appends into a shared buffer; an emitter
             // thread flushes it on a timer/condvar.
-            let shared = Arc::new(SharedTerminalOutput::new());
+            let shared = Arc::new(SharedTerminalOutput::new(session.throttle.buffer_max_bytes));
 
             let shared_for_emitter = Arc::clone(&shared);
             let app_for_emitter = app.clone();
@@ -647,8 +1399,9 @@ impl TerminalManager {
             let session_handle_for_emitter = Arc::clone(&session);
             let emitter_shell_pid = session.shell_pid;
             let emitter = thread::spawn(move || {
-                // 60Hz is enough for TUIs while keeping IPC overhead bounded.
-                let min_emit_interval = Duration::from_millis(TERMINAL_OUTPUT_MIN_EMIT_INTERVAL_MS);
+                let min_emit_interval =
+                    Duration::from_millis(session_handle_for_emitter.throttle.min_emit_interval_ms);
+                let max_emit_bytes = session_handle_for_emitter.throttle.max_emit_bytes;
                 let mut last_emit_at = Instant::now()
                     .checked_sub(min_emit_interval)
                     .unwrap_or_else(Instant::now);
@@ -700,8 +1453,7 @@ impl TerminalManager {
                         continue;
                     }
 
-                    let payload =
-                        take_output_chunks_head(&mut guard, TERMINAL_OUTPUT_MAX_EMIT_BYTES);
+                    let payload = take_output_chunks_head(&mut guard, max_emit_bytes);
                     session_handle_for_emitter
                         .io_counters
                         .output_buffer_bytes
@@ -711,27 +1463,32 @@ impl TerminalManager {
                         continue;
                     }
                     let replay_chunk = session_handle_for_emitter.record_replay_chunk(payload);
-                    let payload_len = replay_chunk.data.len() as u64;
-                    emit_output(
-                        &app_for_emitter,
-                        &workspace_for_emitter,
-                        &session_for_emitter,
-                        replay_chunk,
-                    );
-                    session_handle_for_emitter
-                        .io_counters
-                        .stdout_emits
-                        .fetch_add(1, Ordering::Relaxed);
-                    session_handle_for_emitter
-                        .io_counters
-                        .stdout_emit_bytes
-                        .fetch_add(payload_len, Ordering::Relaxed);
-                    let now_ms = Utc::now().timestamp_millis();
-                    if now_ms > 0 {
+                    session_handle_for_emitter.persist_scrollback(&replay_chunk.data);
+                    // Detached sessions keep filling the replay buffer but
+                    // stay quiet on IPC until the next attach.
+                    if session_handle_for_emitter.is_attached() {
+                        let payload_len = replay_chunk.data.len() as u64;
+                        emit_output(
+                            &app_for_emitter,
+                            &workspace_for_emitter,
+                            &session_for_emitter,
+                            replay_chunk,
+                        );
+                        session_handle_for_emitter
+                            .io_counters
+                            .stdout_emits
+                            .fetch_add(1, Ordering::Relaxed);
                         session_handle_for_emitter
                             .io_counters
-                            .last_stdout_emit_at_ms
-                            .store(now_ms as u64, Ordering::Relaxed);
+                            .stdout_emit_bytes
+                            .fetch_add(payload_len, Ordering::Relaxed);
+                        let now_ms = Utc::now().timestamp_millis();
+                        if now_ms > 0 {
+                            session_handle_for_emitter
+                                .io_counters
+                                .last_stdout_emit_at_ms
+                                .store(now_ms as u64, Ordering::Relaxed);
+                        }
                     }
                     last_emit_at = Instant::now();
 
@@ -761,6 +1518,7 @@ impl TerminalManager {
             let mut decode_buffer = Vec::new();
             let mut osc_notifications = TerminalOscNotificationParser::default();
             let mut pending = String::new();
+            let mut read_error = None;
             loop {
                 match reader.read(&mut buf) {
                     Ok(0) => break,
@@ -782,6 +1540,22 @@ impl TerminalManager {
                         }
 
                         let parsed = osc_notifications.consume(&buf[..n]);
+                        if let Some(cwd) = parsed.cwd_changes.last() {
+                            if session.update_current_cwd(cwd) {
+                                emit_cwd_changed(&app, &workspace_id, &session_id, cwd);
+                            }
+                        }
+                        if let Some(title) = parsed.title_changes.last() {
+                            if session.update_title(title) {
+                                schedule_title_emit(
+                                    &runtime,
+                                    &app,
+                                    &session,
+                                    &workspace_id,
+                                    &session_id,
+                                );
+                            }
+                        }
                         if !parsed.notifications.is_empty() {
                             emit_terminal_osc_notifications(
                                 &runtime,
@@ -802,6 +1576,7 @@ impl TerminalManager {
                         }
 
                         if !pending.is_empty() {
+                            session.record_transcript_output(&pending);
                             let (trimmed, total_bytes) =
                                 shared.push_chunk(std::mem::take(&mut pending));
                             if trimmed > 0 {
@@ -828,6 +1603,7 @@ impl TerminalManager {
                         if error.kind() == std::io::ErrorKind::Interrupted {
                             continue;
                         }
+                        read_error = Some(error.to_string());
                         break;
                     }
                 }
@@ -846,6 +1622,7 @@ impl TerminalManager {
             decode_buffer.extend_from_slice(&parsed.passthrough);
 
             if !pending.is_empty() {
+                session.record_transcript_output(&pending);
                 let (trimmed, total_bytes) = shared.push_chunk(std::mem::take(&mut pending));
                 if trimmed > 0 {
                     session
@@ -869,6 +1646,7 @@ impl TerminalManager {
             if !decode_buffer.is_empty() {
                 let trailing = String::from_utf8_lossy(&decode_buffer).to_string();
                 if !trailing.is_empty() {
+                    session.record_transcript_output(&trailing);
                     let (trimmed, total_bytes) = shared.push_chunk(trailing);
                     if trimmed > 0 {
                         session
@@ -905,6 +1683,7 @@ impl TerminalManager {
                         app_for_finalize,
                         workspace_for_finalize,
                         session_for_finalize,
+                        read_error,
                     )
                     .await;
             }));
@@ -916,6 +1695,7 @@ impl TerminalManager {
         app: AppHandle,
         workspace_id: String,
         session_id: String,
+        read_error: Option<String>,
     ) {
         let Some(session) = self.take_session(&workspace_id, &session_id).await else {
             return;
@@ -923,17 +1703,22 @@ impl TerminalManager {
         let event_session_id = session.meta.id.clone();
         self.store_completed_replay(&workspace_id, &event_session_id, session.replay_snapshot())
             .await;
-        let exit = match tokio::task::spawn_blocking(move || session.wait_for_exit()).await {
+        let exit = match tokio::task::spawn_blocking(move || {
+            session.wait_after_reader(read_error.as_deref())
+        })
+        .await
+        {
             Ok(payload) => payload,
             Err(error) => {
                 log::warn!(
                     "terminal wait task failed for session {}: {error}",
                     event_session_id
                 );
-                ExitPayload::default()
+                ExitPayload::failed(format!("terminal wait task failed: {error}"))
             }
         };
         emit_exit(&app, &workspace_id, &event_session_id, exit);
+        self.notify_exit(&event_session_id);
         let notifications = app.state::<AppState>().notifications.clone();
         notifications
             .clear_for_session(&app, &workspace_id, &event_session_id)
@@ -971,6 +1756,127 @@ fn emit_terminal_osc_notifications(
 }
 
 impl TerminalSessionHandle {
+    fn last_activity_ms(&self) -> u64 {
+        self.started_at_ms
+            .max(
+                self.io_counters
+                    .last_stdin_write_at_ms
+                    .load(Ordering::Relaxed),
+            )
+            .max(
+                self.io_counters
+                    .last_stdout_read_at_ms
+                    .load(Ordering::Relaxed),
+            )
+    }
+
+    /// Whether something other than the shell is running in the terminal.
+    fn has_foreground_job(&self) -> bool {
+        let Some(shell_pid) = self.shell_pid else {
+            return false;
+        };
+        #[cfg(unix)]
+        {
+            self.foreground_job(shell_pid as libc::pid_t).is_some()
+        }
+        #[cfg(not(unix))]
+        {
+            detect_foreground_process(shell_pid).is_some()
+        }
+    }
+
+    fn session_dto(&self) -> TerminalSessionDto {
+        TerminalSessionDto {
+            current_cwd: self
+                .current_cwd
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner())
+                .clone(),
+            title: self
+                .title
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner())
+                .clone(),
+            attached: self.is_attached(),
+            ..self.meta.clone()
+        }
+    }
+
+    fn is_attached(&self) -> bool {
+        self.attached.load(Ordering::SeqCst)
+    }
+
+    /// Resumes live output events and returns what was missed after
+    /// `from_seq`. The flag is set before the replay snapshot, so any chunk
+    /// recorded after the snapshot is also emitted live.
+    fn attach(&self, from_seq: Option<u64>) -> TerminalAttachDto {
+        self.attached.store(true, Ordering::SeqCst);
+        let replay = self.replay_since(from_seq);
+        if replay.gap {
+            // Output older than the replay window is gone; ask full-screen
+            // programs to repaint so the view is complete again.
+            self.request_redraw();
+        }
+        TerminalAttachDto {
+            session: self.session_dto(),
+            replay,
+        }
+    }
+
+    fn detach(&self) {
+        self.attached.store(false, Ordering::SeqCst);
+    }
+
+    /// Sends SIGWINCH to the foreground job; best effort.
+    #[cfg(unix)]
+    fn request_redraw(&self) {
+        let foreground = self
+            .master
+            .lock()
+            .ok()
+            .and_then(|master| master.process_group_leader());
+        if let Some(foreground) = foreground.filter(|pid| *pid > 0) {
+            let _ = signal_process_group(foreground, libc::SIGWINCH);
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn request_redraw(&self) {}
+
+    fn current_title(&self) -> String {
+        self.title
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .clone()
+            .unwrap_or_default()
+    }
+
+    fn update_title(&self, title: &str) -> bool {
+        let mut current = self
+            .title
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        if current.as_deref() == Some(title) {
+            return false;
+        }
+        *current = Some(title.to_string());
+        true
+    }
+
+    /// Records an OSC 7 report; returns false when the shell repeated the
+    /// directory it was already in.
+    fn update_current_cwd(&self, cwd: &str) -> bool {
+        let mut current = self
+            .current_cwd
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        if current.as_deref() == Some(cwd) {
+            return false;
+        }
+        *current = Some(cwd.to_string());
+        true
+    }
+
     fn renderer_diagnostics(&self) -> TerminalRendererDiagnosticsDto {
         let (env_snapshot, last_resize) = match self
             .diagnostics
@@ -1004,6 +1910,7 @@ impl TerminalSessionHandle {
             stdin_writes: self.io_counters.stdin_writes.load(Ordering::Relaxed),
             stdin_bytes: self.io_counters.stdin_bytes.load(Ordering::Relaxed),
             stdin_ctrl_c: self.io_counters.stdin_ctrl_c.load(Ordering::Relaxed),
+            signals_sent: self.io_counters.signals_sent.load(Ordering::Relaxed),
             last_stdin_write_duration_ms: non_zero_u64(
                 self.io_counters
                     .last_stdin_write_duration_ms
@@ -1041,10 +1948,10 @@ impl TerminalSessionHandle {
         };
 
         let output_throttle = TerminalOutputThrottleSnapshotDto {
-            min_emit_interval_ms: TERMINAL_OUTPUT_MIN_EMIT_INTERVAL_MS,
-            max_emit_bytes: TERMINAL_OUTPUT_MAX_EMIT_BYTES as u64,
+            min_emit_interval_ms: self.throttle.min_emit_interval_ms,
+            max_emit_bytes: self.throttle.max_emit_bytes as u64,
             buffer_bytes: self.io_counters.output_buffer_bytes.load(Ordering::Relaxed),
-            buffer_cap_bytes: TERMINAL_OUTPUT_BUFFER_MAX_BYTES as u64,
+            buffer_cap_bytes: self.throttle.buffer_max_bytes as u64,
             buffer_peak_bytes: self
                 .io_counters
                 .output_buffer_peak_bytes
@@ -1078,6 +1985,7 @@ impl TerminalSessionHandle {
             data,
         };
         let chunk_bytes = chunk.data.len();
+        self.append_scrollback(seq, &chunk.data);
 
         match self
             .replay_state
@@ -1104,6 +2012,80 @@ impl TerminalSessionHandle {
         chunk
     }
 
+    fn append_scrollback(&self, seq: u64, data: &str) {
+        let mut scrollback = self
+            .scrollback
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        scrollback.push(seq, data, self.scrollback_max_bytes);
+    }
+
+    fn scrollback_snapshot(&self) -> TerminalScrollbackDto {
+        let scrollback = self
+            .scrollback
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        TerminalScrollbackDto {
+            seq: scrollback.seq,
+            data: scrollback.data.clone(),
+            truncated: scrollback.truncated,
+        }
+    }
+
+    fn search_output(&self, query: &str, case_sensitive: bool) -> TerminalOutputSearchDto {
+        // Copy out so the reader thread is not held up while searching.
+        let (seq, origin, data) = {
+            let scrollback = self
+                .scrollback
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner());
+            (scrollback.seq, scrollback.origin, scrollback.data.clone())
+        };
+        let (matches, limited) =
+            search::search_output(&data, query, case_sensitive, search::SEARCH_MAX_MATCHES);
+        TerminalOutputSearchDto {
+            seq,
+            origin,
+            buffer_bytes: data.len(),
+            matches,
+            limited,
+        }
+    }
+
+    fn persist_scrollback(&self, data: &str) {
+        let mut scrollback = self
+            .scrollback_file
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        let Some(file) = scrollback.as_mut() else {
+            return;
+        };
+        if let Err(error) = file.append(data) {
+            log::warn!(
+                "disabling terminal scrollback persistence for session {}: {error}",
+                self.meta.id
+            );
+            *scrollback = None;
+        }
+    }
+
+    fn discard_scrollback(&self) {
+        let scrollback = self
+            .scrollback_file
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .take();
+        let path = match scrollback {
+            Some(file) => file.path().to_path_buf(),
+            None => match scrollback::scrollback_path(&scrollback::scrollback_dir(), &self.meta.id)
+            {
+                Ok(path) => path,
+                Err(_) => return,
+            },
+        };
+        scrollback::remove_scrollback_file(&path);
+    }
+
     fn replay_since(&self, from_seq: Option<u64>) -> TerminalResumeSessionDto {
         self.replay_since_limited(from_seq, usize::MAX)
     }
@@ -1159,6 +2141,88 @@ impl TerminalSessionHandle {
         }
     }
 
+    /// Sends `signal` to the PTY's foreground process group. Refuses when the
+    /// shell itself is in the foreground, so the shell is never the target.
+    #[cfg(unix)]
+    fn signal_foreground(&self, signal: TerminalSignal) -> anyhow::Result<()> {
+        let foreground = self
+            .master
+            .lock()
+            .map_err(|_| anyhow::anyhow!("terminal master lock poisoned"))?
+            .process_group_leader()
+            .ok_or_else(|| anyhow::anyhow!("unable to read the terminal's foreground process"))?;
+        let shell_pid = self
+            .shell_pid
+            .ok_or_else(|| anyhow::anyhow!("terminal shell pid is unknown"))?;
+        if foreground <= 0 || foreground as u32 == shell_pid {
+            anyhow::bail!("no foreground process is running in this terminal");
+        }
+        signal_process_group(foreground, signal.as_raw())
+            .with_context(|| format!("failed to signal process group {foreground}"))?;
+        self.io_counters
+            .signals_sent
+            .fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn signal_foreground(&self, _signal: TerminalSignal) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "terminal signals are not supported on Windows; send Ctrl+C with terminal_write instead"
+        )
+    }
+
+    /// Types an assignment of the freshly computed PATH at the shell prompt.
+    /// The PTY echoes the line, so the user sees exactly what ran. Refused
+    /// while a job holds the foreground, since the line would go to that job.
+    fn inject_path(&self) -> anyhow::Result<TerminalPathInjectionDto> {
+        #[cfg(unix)]
+        {
+            let foreground = self
+                .master
+                .lock()
+                .map_err(|_| anyhow::anyhow!("terminal master lock poisoned"))?
+                .process_group_leader();
+            if let (Some(foreground), Some(shell_pid)) = (foreground, self.shell_pid) {
+                if foreground > 0 && foreground as u32 != shell_pid {
+                    anyhow::bail!("a command is running in this terminal; wait for the prompt");
+                }
+            }
+        }
+
+        let overrides = self
+            .diagnostics
+            .lock()
+            .map_err(|_| anyhow::anyhow!("terminal diagnostics lock poisoned"))?
+            .env_overrides
+            .clone();
+        let path = refreshed_terminal_path(&overrides)
+            .ok_or_else(|| anyhow::anyhow!("unable to compute a PATH for this terminal"))?;
+        let command = runtime_env::path_export_command(Path::new(&self.meta.shell), &path);
+        self.write(&format!("{command}\r"))?;
+
+        if let Ok(mut diagnostics) = self.diagnostics.lock() {
+            diagnostics.env_snapshot.path = Some(path.clone());
+        }
+        Ok(TerminalPathInjectionDto {
+            session_id: self.meta.id.clone(),
+            path,
+            command,
+        })
+    }
+
+    fn record_transcript_output(&self, data: &str) {
+        if let Some(transcript) = &self.transcript {
+            transcript.record_output(data);
+        }
+    }
+
+    fn record_transcript_input(&self, data: &[u8]) {
+        if let Some(transcript) = &self.transcript {
+            transcript.record_input(data);
+        }
+    }
+
     fn write(&self, data: &str) -> anyhow::Result<()> {
         let started_at = Instant::now();
         let mut writer = self
@@ -1168,6 +2232,8 @@ impl TerminalSessionHandle {
         writer
             .write_all(data.as_bytes())
             .context("failed writing to terminal stdin")?;
+        drop(writer);
+        self.record_transcript_input(data.as_bytes());
         let write_duration_ms = started_at.elapsed().as_millis().min(u64::MAX as u128) as u64;
 
         self.io_counters
@@ -1203,6 +2269,8 @@ impl TerminalSessionHandle {
         writer
             .write_all(data)
             .context("failed writing bytes to terminal stdin")?;
+        drop(writer);
+        self.record_transcript_input(data);
         let write_duration_ms = started_at.elapsed().as_millis().min(u64::MAX as u128) as u64;
 
         self.io_counters
@@ -1229,24 +2297,34 @@ impl TerminalSessionHandle {
         Ok(())
     }
 
-    fn resize(
-        &self,
-        cols: u16,
-        rows: u16,
-        pixel_width: u16,
-        pixel_height: u16,
-    ) -> anyhow::Result<()> {
+    /// Applies a resize still waiting for its debounce right away, so a
+    /// session being closed (e.g. on app shutdown) never keeps a stale size.
+    fn flush_pending_resize(&self) {
+        let size = self
+            .pending_resize
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .take();
+        if let Some(size) = size {
+            if let Err(error) = self.apply_resize(size) {
+                log::warn!("failed applying pending terminal resize: {error}");
+            }
+        }
+    }
+
+    fn apply_resize(&self, size: PtySize) -> anyhow::Result<()> {
+        let PtySize {
+            rows,
+            cols,
+            pixel_width,
+            pixel_height,
+        } = size;
         let master = self
             .master
             .lock()
             .map_err(|_| anyhow::anyhow!("terminal master lock poisoned"))?;
         master
-            .resize(PtySize {
-                rows: rows.max(1),
-                cols: cols.max(1),
-                pixel_width,
-                pixel_height,
-            })
+            .resize(size)
             .context("failed resizing terminal pty")?;
         drop(master);
 
@@ -1257,8 +2335,8 @@ impl TerminalSessionHandle {
         {
             Ok(mut state) => {
                 state.last_resize = Some(TerminalResizeSnapshotDto {
-                    cols: cols.max(1),
-                    rows: rows.max(1),
+                    cols,
+                    rows,
                     pixel_width,
                     pixel_height,
                     recorded_at: Utc::now().to_rfc3339(),
@@ -1298,22 +2376,80 @@ impl TerminalSessionHandle {
             Ok(guard) => guard,
             Err(error) => {
                 log::warn!("unable to wait terminal exit: {error}");
-                return ExitPayload::default();
+                return ExitPayload::failed(error.to_string());
             }
         };
         match child.wait() {
-            Ok(status) => ExitPayload {
-                code: Some(status.exit_code() as i32),
-                signal: None,
-            },
+            Ok(status) => exit_payload(&status),
             Err(error) => {
                 log::warn!("failed waiting for terminal process exit: {error}");
-                ExitPayload::default()
+                ExitPayload::failed(format!("failed waiting for terminal process exit: {error}"))
+            }
+        }
+    }
+
+    /// Exit of a session whose output reader stopped. On Linux the PTY
+    /// reports a shell exit as a read error (EIO), so a failed read first
+    /// waits briefly for the shell to be reaped. A shell still running after
+    /// that has lost its output stream: it is closed like any other session
+    /// and the exit carries the read error.
+    fn wait_after_reader(&self, read_error: Option<&str>) -> ExitPayload {
+        let Some(read_error) = read_error else {
+            return self.wait_for_exit();
+        };
+        let deadline = Instant::now() + TERMINAL_READER_EXIT_WAIT;
+        loop {
+            let polled = match self.child.lock() {
+                Ok(mut child) => child.try_wait(),
+                Err(_) => return ExitPayload::failed("terminal child lock poisoned"),
+            };
+            match polled {
+                Ok(Some(status)) => return exit_payload(&status),
+                Ok(None) if Instant::now() < deadline => {
+                    thread::sleep(TERMINAL_CLOSE_POLL_INTERVAL)
+                }
+                Ok(None) => break,
+                Err(error) => {
+                    log::warn!("failed polling terminal process exit: {error}");
+                    break;
+                }
             }
         }
+        log::warn!(
+            "terminal session {} output failed while its shell was running: {read_error}",
+            self.meta.id
+        );
+        let mut exit = self.kill_and_wait();
+        exit.error = Some(format!("terminal output stream failed: {read_error}"));
+        exit
     }
 
     fn kill_and_wait(&self) -> ExitPayload {
+        self.flush_pending_resize();
+        #[cfg(unix)]
+        if let Some(shell_pid) = self
+            .shell_pid
+            .filter(|_| !self.close_grace_period.is_zero())
+        {
+            if let Some(exit) = self.terminate_gracefully(shell_pid as libc::pid_t) {
+                return exit;
+            }
+            log::warn!(
+                "terminal session {} outlived its {:?} close grace period; killing it",
+                self.meta.id,
+                self.close_grace_period
+            );
+            if let Some(foreground) = self.foreground_job(shell_pid as libc::pid_t) {
+                let _ = signal_process_group(foreground, libc::SIGKILL);
+            }
+            // SAFETY: kill only takes plain integers; the shell is not reaped
+            // until wait_for_exit, so its pid cannot have been reused.
+            unsafe {
+                libc::kill(shell_pid as libc::pid_t, libc::SIGKILL);
+            }
+            return self.wait_for_exit();
+        }
+
         // Kill through the cloned killer instead of the child handle: the child
         // lock may be held by wait_for_exit, and the writer may be blocked on a
         // full PTY buffer. The kill unblocks both.
@@ -1333,6 +2469,96 @@ impl TerminalSessionHandle {
         }
         self.wait_for_exit()
     }
+
+    /// Asks the foreground job, then the shell, to exit and waits up to the
+    /// close grace period. The job gets SIGTERM; the shell gets SIGHUP, as on
+    /// a closed terminal window, because interactive shells ignore SIGTERM.
+    /// `None` means the shell is still running.
+    #[cfg(unix)]
+    fn terminate_gracefully(&self, shell_pid: libc::pid_t) -> Option<ExitPayload> {
+        let deadline = Instant::now() + self.close_grace_period;
+        if let Some(foreground) = self.foreground_job(shell_pid) {
+            let _ = signal_process_group(foreground, libc::SIGTERM);
+            // Hanging up the shell now would hit the job with SIGHUP before it
+            // finished its SIGTERM cleanup.
+            while Instant::now() < deadline && self.foreground_job(shell_pid) == Some(foreground) {
+                thread::sleep(TERMINAL_CLOSE_POLL_INTERVAL);
+            }
+        }
+        // SAFETY: kill only takes plain integers; the shell is not reaped
+        // until we wait on it, so its pid cannot have been reused.
+        unsafe {
+            libc::kill(shell_pid, libc::SIGHUP);
+        }
+
+        let mut child = self.child.lock().ok()?;
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => return Some(exit_payload(&status)),
+                Ok(None) if Instant::now() < deadline => {
+                    thread::sleep(TERMINAL_CLOSE_POLL_INTERVAL)
+                }
+                Ok(None) => return None,
+                Err(error) => {
+                    log::warn!("failed polling terminal process exit: {error}");
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Process group holding the terminal's foreground, unless it is the shell.
+    #[cfg(unix)]
+    fn foreground_job(&self, shell_pid: libc::pid_t) -> Option<libc::pid_t> {
+        self.master
+            .lock()
+            .ok()?
+            .process_group_leader()
+            .filter(|pid| *pid > 0 && *pid != shell_pid)
+    }
+}
+
+/// portable_pty only exposes a terminating signal through its description
+/// (`strsignal` text), so it is mapped back to the signal number here.
+fn exit_payload(status: &portable_pty::ExitStatus) -> ExitPayload {
+    #[cfg(unix)]
+    if !status.success() {
+        if let Some(description) = status.to_string().strip_prefix("Terminated by ") {
+            return ExitPayload {
+                code: None,
+                signal: signal_from_description(description),
+                error: None,
+            };
+        }
+    }
+    ExitPayload {
+        code: Some(status.exit_code() as i32),
+        signal: None,
+        error: None,
+    }
+}
+
+#[cfg(unix)]
+fn signal_process_group(pgid: libc::pid_t, signal: libc::c_int) -> std::io::Result<()> {
+    // SAFETY: killpg only takes plain integers; a stale group id fails with ESRCH.
+    if unsafe { libc::killpg(pgid, signal) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn signal_from_description(description: &str) -> Option<i32> {
+    if let Some(number) = description.strip_prefix("Signal ") {
+        return number.trim().parse().ok();
+    }
+    (1..=64).find(|signal| {
+        // SAFETY: strsignal returns a NUL-terminated string that stays valid
+        // until the next strsignal call on this thread.
+        let name = unsafe { libc::strsignal(*signal) };
+        !name.is_null()
+            && unsafe { std::ffi::CStr::from_ptr(name) }.to_bytes() == description.as_bytes()
+    })
 }
 
 fn prune_completed_replays(replays: &mut HashMap<String, HashMap<String, TerminalReplaySnapshot>>) {
@@ -1395,6 +2621,7 @@ fn spawn_session(
     cols: u16,
     rows: u16,
     notification_env: Option<TerminalNotificationSessionEnv>,
+    options: TerminalSessionOptions,
 ) -> anyhow::Result<SpawnedSession> {
     let pty_system = native_pty_system();
     let pair = pty_system
@@ -1409,13 +2636,14 @@ fn spawn_session(
     let shell = default_shell();
     let mut cmd = CommandBuilder::new(shell.clone());
     cmd.cwd(PathBuf::from(&cwd));
-    let env_snapshot = configure_terminal_env(&mut cmd, notification_env.as_ref());
-    #[cfg(not(target_os = "windows"))]
-    {
-        for arg in runtime_env::terminal_shell_args(Path::new(&shell)) {
-            cmd.arg(arg);
-        }
+    let mut env_snapshot =
+        configure_terminal_env(&mut cmd, notification_env.as_ref(), &options.env_overrides);
+    let shell_args = runtime_env::terminal_shell_args(Path::new(&shell));
+    for arg in &shell_args {
+        cmd.arg(arg);
     }
+    env_snapshot.shell = Some(shell.clone());
+    env_snapshot.shell_args = shell_args;
     let child = pair
         .slave
         .spawn_command(cmd)
@@ -1435,23 +2663,65 @@ fn spawn_session(
         .take_writer()
         .context("failed to take terminal writer")?;
 
+    let scrollback_file = if options.persist_scrollback {
+        TerminalScrollbackFile::create(
+            &scrollback::scrollback_dir(),
+            &session_id,
+            options.throttle.buffer_max_bytes,
+        )
+        .map_err(|error| {
+            log::warn!(
+                "terminal scrollback persistence unavailable for session {session_id}: {error}"
+            );
+        })
+        .ok()
+    } else {
+        None
+    };
+    let transcript = if options.record_transcript {
+        TerminalTranscript::start(&transcript::transcript_dir(), &session_id)
+            .map_err(|error| {
+                log::warn!("terminal transcript unavailable for session {session_id}: {error}");
+            })
+            .ok()
+    } else {
+        None
+    };
+
     let session = Arc::new(TerminalSessionHandle {
         meta: TerminalSessionDto {
             id: session_id,
             workspace_id,
             shell,
             cwd,
+            current_cwd: None,
+            title: None,
+            attached: true,
             created_at: Utc::now().to_rfc3339(),
         },
         shell_pid,
+        throttle: options.throttle,
         diagnostics: Mutex::new(TerminalSessionDiagnosticsState {
             env_snapshot,
+            env_overrides: options.env_overrides,
             last_resize: None,
             last_zero_pixel_warning_at_ms: None,
         }),
         io_counters: TerminalSessionIoCounters::default(),
         replay_seq: AtomicU64::new(0),
         replay_state: Mutex::new(TerminalReplayState::default()),
+        scrollback: Mutex::new(TerminalScrollbackBuffer::default()),
+        scrollback_max_bytes: options.scrollback_max_bytes,
+        scrollback_file: Mutex::new(scrollback_file),
+        transcript,
+        current_cwd: Mutex::new(None),
+        title: Mutex::new(None),
+        title_throttle: Mutex::new(TitleEmitThrottle::default()),
+        attached: AtomicBool::new(true),
+        close_grace_period: options.close_grace_period,
+        started_at_ms: Utc::now().timestamp_millis().max(0) as u64,
+        idle_warning: Mutex::new(None),
+        pending_resize: Mutex::new(PendingResize::default()),
         writer: Mutex::new(writer),
         master: Mutex::new(pair.master),
         child: Mutex::new(child),
@@ -1513,13 +2783,74 @@ struct TerminalEnvConfig {
 fn configure_terminal_env(
     cmd: &mut CommandBuilder,
     notification_env: Option<&TerminalNotificationSessionEnv>,
+    overrides: &TerminalEnvOverrides,
 ) -> TerminalEnvSnapshotDto {
-    let config = build_terminal_env_config(notification_env);
+    let mut config = build_terminal_env_config(notification_env);
     apply_terminal_env(cmd, &config);
     apply_notification_env(cmd, notification_env);
+    apply_env_overrides(cmd, &mut config.snapshot, overrides);
     config.snapshot
 }
 
+fn apply_env_overrides(
+    cmd: &mut CommandBuilder,
+    snapshot: &mut TerminalEnvSnapshotDto,
+    overrides: &TerminalEnvOverrides,
+) {
+    for (key, value) in &overrides.vars {
+        if is_path_env_key(key) {
+            let path = override_path(value, overrides.replace_path, snapshot.path.as_deref());
+            cmd.env(key, &path);
+            snapshot.path = Some(path);
+        } else {
+            cmd.env(key, value);
+        }
+        snapshot.overrides.insert(key.clone(), value.clone());
+    }
+}
+
+fn is_path_env_key(key: &str) -> bool {
+    if cfg!(target_os = "windows") {
+        key.eq_ignore_ascii_case("PATH")
+    } else {
+        key == "PATH"
+    }
+}
+
+fn override_path(value: &str, replace_path: bool, computed: Option<&str>) -> String {
+    if replace_path {
+        value.to_string()
+    } else {
+        merge_path_override(value, computed)
+    }
+}
+
+/// PATH a session would get if spawned now, with its overrides re-applied.
+fn refreshed_terminal_path(overrides: &TerminalEnvOverrides) -> Option<String> {
+    let computed = build_terminal_path(None, &[]).or_else(|| read_non_empty_env("PATH"));
+    match overrides.vars.iter().find(|(key, _)| is_path_env_key(key)) {
+        Some((_, value)) => Some(override_path(
+            value,
+            overrides.replace_path,
+            computed.as_deref(),
+        )),
+        None => computed,
+    }
+}
+
+/// Puts the override's entries ahead of the computed PATH so they win
+/// lookups, dropping later duplicates.
+fn merge_path_override(override_value: &str, computed: Option<&str>) -> String {
+    let mut seen = std::collections::HashSet::new();
+    let entries = std::env::split_paths(override_value)
+        .chain(computed.map(std::env::split_paths).into_iter().flatten())
+        .filter(|entry| !entry.as_os_str().is_empty() && seen.insert(entry.clone()))
+        .collect::<Vec<_>>();
+    std::env::join_paths(entries)
+        .map(|joined| joined.to_string_lossy().to_string())
+        .unwrap_or_else(|_| override_value.to_string())
+}
+
 fn build_terminal_path(_home: Option<&str>, prepend: &[PathBuf]) -> Option<String> {
     let joined = if prepend.is_empty() {
         runtime_env::augmented_path()?
@@ -1644,6 +2975,9 @@ fn build_terminal_env_config_for(is_windows: bool, inputs: TerminalEnvInputs) ->
                 lc_all: None,
                 lc_ctype: None,
                 path,
+                shell: None,
+                shell_args: Vec::new(),
+                overrides: Default::default(),
             },
             user_profile,
             local_app_data,
@@ -1694,6 +3028,9 @@ fn build_terminal_env_config_for(is_windows: bool, inputs: TerminalEnvInputs) ->
             lc_all,
             lc_ctype,
             path,
+            shell: None,
+            shell_args: Vec::new(),
+            overrides: Default::default(),
         },
         user_profile: None,
         local_app_data: None,
@@ -1867,6 +3204,93 @@ fn emit_exit(app: &AppHandle, workspace_id: &str, session_id: &str, exit: ExitPa
         session_id: session_id.to_string(),
         code: exit.code,
         signal: exit.signal,
+        error: exit.error,
+    };
+    let _ = app.emit(&event_name, payload);
+}
+
+fn emit_cwd_changed(app: &AppHandle, workspace_id: &str, session_id: &str, cwd: &str) {
+    let event_name = format!("terminal-cwd-{workspace_id}");
+    let payload = TerminalCwdChangedEvent {
+        session_id: session_id.to_string(),
+        cwd: cwd.to_string(),
+    };
+    let _ = app.emit(&event_name, payload);
+}
+
+fn schedule_title_emit(
+    runtime: &tokio::runtime::Handle,
+    app: &AppHandle,
+    session: &Arc<TerminalSessionHandle>,
+    workspace_id: &str,
+    session_id: &str,
+) {
+    let action = session
+        .title_throttle
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .on_change(Instant::now());
+    match action {
+        TitleEmitAction::EmitNow => {
+            emit_title_changed(app, workspace_id, session_id, &session.current_title());
+        }
+        TitleEmitAction::FlushAfter(delay) => {
+            let app = app.clone();
+            let session = Arc::clone(session);
+            let workspace_id = workspace_id.to_string();
+            let session_id = session_id.to_string();
+            runtime.spawn(async move {
+                tokio::time::sleep(delay).await;
+                session
+                    .title_throttle
+                    .lock()
+                    .unwrap_or_else(|poison| poison.into_inner())
+                    .on_flush(Instant::now());
+                emit_title_changed(&app, &workspace_id, &session_id, &session.current_title());
+            });
+        }
+        TitleEmitAction::Coalesced => {}
+    }
+}
+
+/// Applies a session's queued resizes once they settle; exits when nothing
+/// is pending.
+async fn flush_pending_resizes(session: Arc<TerminalSessionHandle>) {
+    loop {
+        let step = session
+            .pending_resize
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .next(Instant::now());
+        match step {
+            ResizeStep::Wait(delay) => tokio::time::sleep(delay).await,
+            ResizeStep::Apply(size) => {
+                let session = Arc::clone(&session);
+                match tokio::task::spawn_blocking(move || session.apply_resize(size)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(error)) => log::warn!("failed applying terminal resize: {error}"),
+                    Err(error) => log::warn!("terminal resize task failed: {error}"),
+                }
+            }
+            ResizeStep::Idle => return,
+        }
+    }
+}
+
+fn emit_idle_warning(app: &AppHandle, workspace_id: &str, session_id: &str, closes_in_ms: u64) {
+    let event_name = format!("terminal-idle-warning-{workspace_id}");
+    let payload = TerminalIdleWarningEvent {
+        session_id: session_id.to_string(),
+        closes_in_ms,
+    };
+    let _ = app.emit(&event_name, payload);
+}
+
+fn emit_title_changed(app: &AppHandle, workspace_id: &str, session_id: &str, title: &str) {
+    let event_name = format!("terminal-title-{workspace_id}");
+    let payload = TerminalTitleChangedEvent {
+        session_id: session_id.to_string(),
+        title: title.to_string(),
     };
     let _ = app.emit(&event_name, payload);
 }
@@ -2335,6 +3759,47 @@ fn take_next_utf8_chunk(buffer: &mut Vec<u8>) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn scrollback_buffer_keeps_char_aligned_tail_and_last_seq() {
+        let mut scrollback = TerminalScrollbackBuffer::default();
+        scrollback.push(1, "prompt$ ", 12);
+        assert!(!scrollback.truncated);
+
+        scrollback.push(2, "ls\n→ a", 12);
+        assert_eq!(scrollback.seq, 2);
+        assert!(scrollback.truncated);
+        assert!(scrollback.data.len() <= 12);
+        assert_eq!(
+            scrollback.origin as usize + scrollback.data.len(),
+            "prompt$ ls\n→ a".len()
+        );
+        assert!(scrollback.data.ends_with("ls\n→ a"));
+    }
+
+    #[test]
+    fn title_throttle_emits_first_change_and_coalesces_the_burst() {
+        let mut throttle = TitleEmitThrottle::default();
+        let start = Instant::now();
+        assert_eq!(throttle.on_change(start), TitleEmitAction::EmitNow);
+
+        let soon = start + Duration::from_millis(50);
+        assert_eq!(
+            throttle.on_change(soon),
+            TitleEmitAction::FlushAfter(TITLE_EMIT_INTERVAL - Duration::from_millis(50))
+        );
+        assert_eq!(
+            throttle.on_change(soon + Duration::from_millis(10)),
+            TitleEmitAction::Coalesced
+        );
+
+        let flushed_at = start + TITLE_EMIT_INTERVAL;
+        throttle.on_flush(flushed_at);
+        assert_eq!(
+            throttle.on_change(flushed_at + TITLE_EMIT_INTERVAL),
+            TitleEmitAction::EmitNow
+        );
+    }
+
     fn normalize_path(path: &str) -> String {
         path.replace('\\', "/")
     }
@@ -2646,4 +4111,458 @@ mod tests {
             Some("C:/Users/panes/AppData/Local")
         );
     }
+
+    fn env_overrides(vars: &[(&str, &str)], replace_path: bool) -> TerminalEnvOverrides {
+        TerminalEnvOverrides {
+            vars: vars
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            replace_path,
+        }
+    }
+
+    #[test]
+    fn env_overrides_reject_invalid_keys() {
+        assert!(
+            env_overrides(&[("NODE_OPTIONS", "--max-old-space-size=4096")], false)
+                .validate()
+                .is_ok()
+        );
+        for key in ["", "A=B", "BAD\0KEY"] {
+            let error = env_overrides(&[(key, "1")], false)
+                .validate()
+                .expect_err("key should be rejected")
+                .to_string();
+            assert!(error.contains("terminal environment variable"), "{error}");
+        }
+    }
+
+    #[test]
+    fn env_overrides_merge_path_unless_replaced() {
+        let computed = std::env::join_paths(["/usr/local/bin", "/usr/bin"])
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let extra = std::env::join_paths(["/opt/tools/bin", "/usr/bin"])
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let base_snapshot = TerminalEnvSnapshotDto {
+            path: Some(computed),
+            ..TerminalEnvSnapshotDto::default()
+        };
+
+        let mut cmd = CommandBuilder::new("sh");
+        let mut snapshot = base_snapshot.clone();
+        apply_env_overrides(
+            &mut cmd,
+            &mut snapshot,
+            &env_overrides(
+                &[("PATH", &extra), ("GIT_SSH_COMMAND", "ssh -i key")],
+                false,
+            ),
+        );
+        let merged = std::env::join_paths(["/opt/tools/bin", "/usr/bin", "/usr/local/bin"])
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(snapshot.path.as_deref(), Some(merged.as_str()));
+        assert_eq!(
+            cmd.get_env("PATH").and_then(|value| value.to_str()),
+            Some(merged.as_str())
+        );
+        assert_eq!(
+            cmd.get_env("GIT_SSH_COMMAND")
+                .and_then(|value| value.to_str()),
+            Some("ssh -i key")
+        );
+        assert_eq!(
+            snapshot.overrides.get("PATH").map(String::as_str),
+            Some(extra.as_str())
+        );
+        assert_eq!(snapshot.overrides.len(), 2);
+
+        let mut cmd = CommandBuilder::new("sh");
+        let mut snapshot = base_snapshot;
+        apply_env_overrides(
+            &mut cmd,
+            &mut snapshot,
+            &env_overrides(&[("PATH", "/only/here")], true),
+        );
+        assert_eq!(snapshot.path.as_deref(), Some("/only/here"));
+    }
+
+    #[test]
+    fn terminal_signal_names_parse_with_or_without_prefix() {
+        assert_eq!(
+            TerminalSignal::parse("SIGINT").unwrap(),
+            TerminalSignal::Interrupt
+        );
+        assert_eq!(
+            TerminalSignal::parse("term").unwrap(),
+            TerminalSignal::Terminate
+        );
+        assert_eq!(
+            TerminalSignal::parse(" sigkill ").unwrap(),
+            TerminalSignal::Kill
+        );
+        assert!(TerminalSignal::parse("SIGHUP").is_err());
+    }
+
+    #[cfg(unix)]
+    fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_kills_foreground_job_and_leaves_the_shell_running() {
+        let _env_guard = crate::process_utils::test_env_lock()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let original_shell = std::env::var_os("SHELL");
+        std::env::set_var("SHELL", "/bin/sh");
+        let spawned = spawn_session(
+            Uuid::new_v4().to_string(),
+            "ws".to_string(),
+            std::env::temp_dir().to_string_lossy().to_string(),
+            80,
+            24,
+            None,
+            TerminalSessionOptions::default(),
+        );
+        match original_shell {
+            Some(shell) => std::env::set_var("SHELL", shell),
+            None => std::env::remove_var("SHELL"),
+        }
+        let SpawnedSession {
+            session,
+            mut reader,
+        } = spawned.expect("terminal session should spawn");
+        thread::spawn(move || {
+            let mut buf = [0_u8; 4096];
+            while matches!(reader.read(&mut buf), Ok(n) if n > 0) {}
+        });
+        let shell_pid = session.shell_pid.expect("shell pid") as libc::pid_t;
+        let foreground = || {
+            session
+                .master
+                .lock()
+                .expect("master lock")
+                .process_group_leader()
+        };
+
+        session.write("sleep 30\n").expect("start foreground job");
+        assert!(
+            wait_until(|| foreground().is_some_and(|group| group != shell_pid)),
+            "sleep should take over the foreground"
+        );
+
+        session
+            .signal_foreground(TerminalSignal::Kill)
+            .expect("signal foreground job");
+
+        assert!(
+            wait_until(|| foreground() == Some(shell_pid)),
+            "shell should regain the foreground once sleep dies"
+        );
+        assert!(session
+            .child
+            .lock()
+            .expect("child lock")
+            .try_wait()
+            .expect("poll shell")
+            .is_none());
+        assert_eq!(session.io_counters.signals_sent.load(Ordering::Relaxed), 1);
+        assert!(session
+            .signal_foreground(TerminalSignal::Interrupt)
+            .is_err());
+
+        session.kill_and_wait();
+    }
+
+    #[test]
+    fn rapid_resizes_coalesce_into_the_final_size() {
+        let size = |cols| PtySize {
+            rows: 40,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut pending = PendingResize::default();
+        assert!(pending.queue(size(80), at(0)));
+        assert!(!pending.queue(size(90), at(10)));
+        assert!(!pending.queue(size(100), at(20)));
+        assert_eq!(
+            pending.next(at(30)),
+            ResizeStep::Wait(Duration::from_millis(30))
+        );
+        assert_eq!(pending.next(at(60)), ResizeStep::Apply(size(100)));
+        assert_eq!(pending.next(at(60)), ResizeStep::Idle);
+        assert!(
+            pending.queue(size(110), at(70)),
+            "a new burst schedules a new flush"
+        );
+
+        // A continuous drag still applies once the max delay is reached.
+        let mut pending = PendingResize::default();
+        for step in 0..8 {
+            pending.queue(size(80 + step), at(step as u64 * 30));
+        }
+        assert_eq!(pending.next(at(200)), ResizeStep::Apply(size(87)));
+    }
+
+    #[test]
+    fn idle_sessions_are_warned_a_lead_ahead_and_closed_after() {
+        let minute = 60_000;
+        let timeout = 10 * minute;
+        assert_eq!(idle_action(8 * minute, 0, timeout, None), IdleAction::Keep);
+        assert_eq!(idle_action(9 * minute, 0, timeout, None), IdleAction::Warn);
+
+        let warning = Some(IdleWarning {
+            activity_ms: 0,
+            warned_at_ms: 9 * minute,
+        });
+        assert_eq!(
+            idle_action(9 * minute + 30_000, 0, timeout, warning),
+            IdleAction::Keep
+        );
+        assert_eq!(
+            idle_action(10 * minute, 0, timeout, warning),
+            IdleAction::Close
+        );
+
+        // Input after the warning moves the activity stamp and cancels it.
+        assert_eq!(
+            idle_action(10 * minute, 9 * minute + 30_000, timeout, warning),
+            IdleAction::Keep
+        );
+
+        // A session found long idle still gets the full lead after its warning.
+        let late = Some(IdleWarning {
+            activity_ms: 0,
+            warned_at_ms: 60 * minute,
+        });
+        assert_eq!(idle_action(60 * minute, 0, timeout, None), IdleAction::Warn);
+        assert_eq!(
+            idle_action(60 * minute + 15_000, 0, timeout, late),
+            IdleAction::Keep
+        );
+        assert_eq!(
+            idle_action(61 * minute, 0, timeout, late),
+            IdleAction::Close
+        );
+    }
+
+    #[tokio::test]
+    async fn exit_watchers_resolve_when_the_session_is_gone() {
+        let manager = TerminalManager::default();
+        manager
+            .watch_exit("ws-1", "missing")
+            .await
+            .await
+            .expect("a session that is already gone resolves at once");
+
+        let (sender, mut receiver) = oneshot::channel();
+        manager
+            .exit_watchers
+            .lock()
+            .unwrap()
+            .insert("session-1".to_string(), vec![sender]);
+        assert!(receiver.try_recv().is_err());
+        manager.notify_exit("session-2");
+        assert!(receiver.try_recv().is_err());
+        manager.notify_exit("session-1");
+        receiver.await.expect("exit notifies the watcher");
+        assert!(manager.exit_watchers.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn session_slots_are_capped_per_workspace() {
+        let manager = TerminalManager::default();
+        manager
+            .reserve_session_slot("ws-1", 2)
+            .await
+            .expect("first slot");
+        manager
+            .reserve_session_slot("ws-1", 2)
+            .await
+            .expect("second slot");
+        let error = manager
+            .reserve_session_slot("ws-1", 2)
+            .await
+            .expect_err("limit reached");
+        assert!(error
+            .to_string()
+            .contains("terminal session limit reached for this workspace"));
+        manager
+            .reserve_session_slot("ws-2", 2)
+            .await
+            .expect("other workspaces are unaffected");
+
+        {
+            let _sessions = manager.workspaces.write().await;
+            manager.release_session_slot("ws-1");
+        }
+        let limits = manager.session_limits("ws-1", 2).await;
+        assert_eq!((limits.open, limits.limit), (1, 2));
+        manager
+            .reserve_session_slot("ws-1", 2)
+            .await
+            .expect("released slot is reusable");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exit_payload_maps_signal_descriptions_back_to_numbers() {
+        // SAFETY: strsignal returns a valid C string for known signals.
+        let description = unsafe { std::ffi::CStr::from_ptr(libc::strsignal(libc::SIGTERM)) }
+            .to_string_lossy()
+            .into_owned();
+        let exit = exit_payload(&portable_pty::ExitStatus::with_signal(&description));
+        assert_eq!(exit.code, None);
+        assert_eq!(exit.signal, Some(libc::SIGTERM));
+
+        let exit = exit_payload(&portable_pty::ExitStatus::with_signal("Signal 42"));
+        assert_eq!(exit.signal, Some(42));
+
+        let exit = exit_payload(&portable_pty::ExitStatus::with_exit_code(3));
+        assert_eq!(exit.code, Some(3));
+        assert_eq!(exit.signal, None);
+    }
+
+    #[cfg(unix)]
+    fn run_in_pty(script: &str) -> ExitPayload {
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .expect("open pty");
+        let mut cmd = CommandBuilder::new("/bin/sh");
+        cmd.args(["-c", script]);
+        let mut child = pair.slave.spawn_command(cmd).expect("spawn sh");
+        drop(pair.slave);
+        exit_payload(&child.wait().expect("wait sh"))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_exit_codes_and_signals_are_reported_separately() {
+        let exit = run_in_pty("exit 3");
+        assert_eq!((exit.code, exit.signal), (Some(3), None));
+        assert!(exit.error.is_none());
+
+        let exit = run_in_pty("kill -TERM $$");
+        assert_eq!((exit.code, exit.signal), (None, Some(libc::SIGTERM)));
+        assert!(exit.error.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn killed_shell_reports_the_signal_instead_of_an_exit_code() {
+        let exit = run_in_pty("kill -9 $$");
+        assert_eq!(exit.code, None);
+        assert_eq!(exit.signal, Some(libc::SIGKILL));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn close_terminates_the_foreground_job_before_the_grace_period_ends() {
+        let _env_guard = crate::process_utils::test_env_lock()
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let original_shell = std::env::var_os("SHELL");
+        std::env::set_var("SHELL", "/bin/sh");
+        let grace = Duration::from_secs(5);
+        let spawned = spawn_session(
+            Uuid::new_v4().to_string(),
+            "ws".to_string(),
+            std::env::temp_dir().to_string_lossy().to_string(),
+            80,
+            24,
+            None,
+            TerminalSessionOptions {
+                close_grace_period: grace,
+                ..TerminalSessionOptions::default()
+            },
+        );
+        match original_shell {
+            Some(shell) => std::env::set_var("SHELL", shell),
+            None => std::env::remove_var("SHELL"),
+        }
+        let SpawnedSession {
+            session,
+            mut reader,
+        } = spawned.expect("terminal session should spawn");
+        thread::spawn(move || {
+            let mut buf = [0_u8; 4096];
+            while matches!(reader.read(&mut buf), Ok(n) if n > 0) {}
+        });
+        let shell_pid = session.shell_pid.expect("shell pid") as libc::pid_t;
+
+        session.write("sleep 30\n").expect("start foreground job");
+        assert!(
+            wait_until(|| session.foreground_job(shell_pid).is_some()),
+            "sleep should take over the foreground"
+        );
+
+        let started = Instant::now();
+        let exit = session.kill_and_wait();
+        assert!(
+            started.elapsed() < grace,
+            "close should not need the hard kill"
+        );
+        assert_eq!(exit.code, None);
+        assert_eq!(exit.signal, Some(libc::SIGHUP));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reattach_replays_output_recorded_while_detached() {
+        let SpawnedSession { session, reader } = spawn_session(
+            Uuid::new_v4().to_string(),
+            "ws".to_string(),
+            std::env::temp_dir().to_string_lossy().to_string(),
+            80,
+            24,
+            None,
+            TerminalSessionOptions::default(),
+        )
+        .expect("terminal session should spawn");
+        drop(reader);
+
+        let seen = session.record_replay_chunk("before reload\r\n".to_string());
+        session.detach();
+        assert!(!session.session_dto().attached);
+        session.record_replay_chunk("missed 1\r\n".to_string());
+        session.record_replay_chunk("missed 2\r\n".to_string());
+
+        let attached = session.attach(Some(seen.seq));
+        assert!(attached.session.attached);
+        assert!(!attached.replay.gap);
+        assert_eq!(
+            attached
+                .replay
+                .chunks
+                .iter()
+                .map(|chunk| chunk.data.as_str())
+                .collect::<Vec<_>>(),
+            vec!["missed 1\r\n", "missed 2\r\n"]
+        );
+
+        session.kill_and_wait();
+    }
 }
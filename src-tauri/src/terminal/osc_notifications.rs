@@ -10,6 +10,7 @@ const OSC_TITLE: &str = "Terminal";
 // emits ESC ] and never a terminator) would otherwise swallow all subsequent
 // output and grow the buffers without limit.
 const OSC_MAX_BUFFERED_BYTES: usize = 64 * 1024;
+const OSC_TITLE_MAX_CHARS: usize = 256;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TerminalOscNotification {
@@ -22,6 +23,12 @@ pub struct TerminalOscNotification {
 pub struct TerminalOscParseResult {
     pub passthrough: Vec<u8>,
     pub notifications: Vec<TerminalOscNotification>,
+    /// Working directories reported through OSC 7, oldest first. The
+    /// sequences themselves stay in `passthrough` for xterm.
+    pub cwd_changes: Vec<String>,
+    /// Window titles set through OSC 0/2, oldest first; also left in
+    /// `passthrough`.
+    pub title_changes: Vec<String>,
 }
 
 #[derive(Debug, Default)]
@@ -44,6 +51,8 @@ enum OscParseAction {
     Ignore,
     Strip,
     Notify(TerminalOscNotification),
+    Cwd(String),
+    Title(String),
 }
 
 impl TerminalOscNotificationParser {
@@ -51,6 +60,8 @@ impl TerminalOscNotificationParser {
         let mut result = TerminalOscParseResult {
             passthrough: Vec::with_capacity(bytes.len()),
             notifications: Vec::new(),
+            cwd_changes: Vec::new(),
+            title_changes: Vec::new(),
         };
         for &byte in bytes {
             self.consume_byte(byte, &mut result);
@@ -155,6 +166,14 @@ impl TerminalOscNotificationParser {
             OscParseAction::Notify(notification) => result.notifications.push(notification),
             OscParseAction::Strip => {}
             OscParseAction::Ignore => result.passthrough.extend(raw),
+            OscParseAction::Cwd(path) => {
+                result.cwd_changes.push(path);
+                result.passthrough.extend(raw);
+            }
+            OscParseAction::Title(title) => {
+                result.title_changes.push(title);
+                result.passthrough.extend(raw);
+            }
         }
     }
 
@@ -168,6 +187,10 @@ impl TerminalOscNotificationParser {
         let rest = parts.next().unwrap_or_default();
 
         match code {
+            "0" | "2" => OscParseAction::Title(parse_osc_title(rest)),
+            "7" => parse_osc_7(rest)
+                .map(OscParseAction::Cwd)
+                .unwrap_or(OscParseAction::Ignore),
             "9" => parse_osc_9(rest)
                 .map(OscParseAction::Notify)
                 .unwrap_or(OscParseAction::Ignore),
@@ -229,6 +252,47 @@ impl TerminalOscNotificationParser {
     }
 }
 
+/// Control characters are dropped so a title cannot smuggle escapes into the
+/// tab label; an empty title is kept and means "reset".
+fn parse_osc_title(rest: &str) -> String {
+    rest.chars()
+        .filter(|ch| !ch.is_control())
+        .take(OSC_TITLE_MAX_CHARS)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// `file://host/path` with a percent-encoded path. The host is ignored; a
+/// Windows drive path arrives as `/C:/...` and loses its leading slash.
+fn parse_osc_7(rest: &str) -> Option<String> {
+    let without_scheme = rest.trim().strip_prefix("file://")?;
+    let path_start = without_scheme.find('/')?;
+    let decoded = percent_decode(&without_scheme[path_start..])?;
+    let bytes = decoded.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return Some(decoded[1..].to_string());
+    }
+    Some(decoded)
+}
+
+fn percent_decode(raw: &str) -> Option<String> {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = raw.get(index + 1..index + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            out.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
 fn parse_osc_9(rest: &str) -> Option<TerminalOscNotification> {
     if rest.starts_with("4;") {
         return None;
@@ -301,6 +365,8 @@ mod tests {
         let tail = parser.finish();
         result.passthrough.extend(tail.passthrough);
         result.notifications.extend(tail.notifications);
+        result.cwd_changes.extend(tail.cwd_changes);
+        result.title_changes.extend(tail.title_changes);
         result
     }
 
@@ -410,6 +476,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reports_osc_7_cwd_without_stripping_it() {
+        let input = b"$ \x1b]7;file://devbox/home/me/My%20Project\x1b\\ok";
+        let result = parse_all(input);
+
+        assert_eq!(result.passthrough, input);
+        assert!(result.notifications.is_empty());
+        assert_eq!(result.cwd_changes, vec!["/home/me/My Project".to_string()]);
+    }
+
+    #[test]
+    fn reports_osc_7_split_across_reads() {
+        let mut parser = TerminalOscNotificationParser::default();
+        let first = parser.consume(b"a\x1b");
+        let second = parser.consume(b"]7;file:///C:/Users/me\x1b");
+        let third = parser.consume(b"\\b\x1b]7;file://host/tmp\x07");
+
+        assert_eq!(first.passthrough, b"a");
+        assert!(first.cwd_changes.is_empty());
+        assert!(second.passthrough.is_empty());
+        assert!(second.cwd_changes.is_empty());
+        assert_eq!(
+            third.passthrough,
+            b"\x1b]7;file:///C:/Users/me\x1b\\b\x1b]7;file://host/tmp\x07"
+        );
+        assert_eq!(
+            third.cwd_changes,
+            vec!["C:/Users/me".to_string(), "/tmp".to_string()]
+        );
+    }
+
+    #[test]
+    fn reports_osc_0_and_2_titles_without_stripping_them() {
+        let input = b"\x1b]0;zsh\x07\x1b]2;vim README.md\x1b\\\x1b]1;icon\x07";
+        let result = parse_all(input);
+
+        assert_eq!(result.passthrough, input);
+        assert_eq!(
+            result.title_changes,
+            vec!["zsh".to_string(), "vim README.md".to_string()]
+        );
+    }
+
+    #[test]
+    fn buffers_titles_split_across_reads() {
+        let mut parser = TerminalOscNotificationParser::default();
+        let first = parser.consume(b"out\x1b]2;vim ma");
+        let second = parser.consume(b"in.rs\x1b");
+        let third = parser.consume(b"\\");
+
+        assert_eq!(first.passthrough, b"out");
+        assert!(first.title_changes.is_empty());
+        assert!(second.title_changes.is_empty());
+        assert_eq!(third.title_changes, vec!["vim main.rs".to_string()]);
+        assert_eq!(third.passthrough, b"\x1b]2;vim main.rs\x1b\\");
+
+        let unfinished = parser.consume(b"\x1b]2;half");
+        let tail = parser.finish();
+        assert!(unfinished.title_changes.is_empty());
+        assert!(tail.title_changes.is_empty());
+    }
+
+    #[test]
+    fn ignores_malformed_osc_7_payloads() {
+        let result = parse_all(b"\x1b]7;not-a-url\x07\x1b]7;file://host/bad%zz\x07");
+
+        assert!(result.cwd_changes.is_empty());
+    }
+
     #[test]
     fn finish_flushes_incomplete_sequences() {
         let mut parser = TerminalOscNotificationParser::default();
@@ -0,0 +1,177 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use uuid::Uuid;
+
+use super::trim_string_to_tail;
+use crate::runtime_env;
+
+const SCROLLBACK_DIR_NAME: &str = "terminal-scrollback";
+const SCROLLBACK_FILE_EXTENSION: &str = "log";
+
+pub(super) fn scrollback_dir() -> PathBuf {
+    runtime_env::app_data_dir().join(SCROLLBACK_DIR_NAME)
+}
+
+/// Session ids are UUIDs; anything else is refused so a caller cannot point
+/// the path outside the scrollback directory.
+pub(super) fn scrollback_path(dir: &Path, session_id: &str) -> anyhow::Result<PathBuf> {
+    let session_id = Uuid::parse_str(session_id)
+        .map_err(|_| anyhow::anyhow!("invalid terminal session id: {session_id}"))?;
+    Ok(dir.join(format!(
+        "{}.{SCROLLBACK_FILE_EXTENSION}",
+        session_id.hyphenated()
+    )))
+}
+
+/// On-disk copy of a session's coalesced output, kept under `cap` bytes.
+///
+/// Appends go straight to the file. Once an append would cross the cap the
+/// file is rewritten with only its tail, trimmed to three quarters of the cap
+/// so that steady output does not rewrite the file on every flush.
+pub(super) struct TerminalScrollbackFile {
+    path: PathBuf,
+    file: File,
+    len: usize,
+    cap: usize,
+}
+
+impl TerminalScrollbackFile {
+    pub(super) fn create(dir: &Path, session_id: &str, cap: usize) -> anyhow::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = scrollback_path(dir, session_id)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            len: 0,
+            cap: cap.max(1),
+        })
+    }
+
+    pub(super) fn append(&mut self, data: &str) -> io::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        if self.len + data.len() <= self.cap {
+            self.file.write_all(data.as_bytes())?;
+            self.len += data.len();
+            return Ok(());
+        }
+
+        let mut retained = fs::read(&self.path)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        retained.push_str(data);
+        trim_string_to_tail(&mut retained, self.cap - self.cap / 4);
+
+        fs::write(&self.path, retained.as_bytes())?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.len = retained.len();
+        Ok(())
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Last `max_bytes` of a persisted session, cut on a char boundary. A missing
+/// file yields an empty string.
+pub(super) fn read_scrollback_tail(
+    dir: &Path,
+    session_id: &str,
+    max_bytes: usize,
+) -> anyhow::Result<String> {
+    let path = scrollback_path(dir, session_id)?;
+    let mut content = match fs::read(&path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(String::new()),
+        Err(error) => return Err(error.into()),
+    };
+    trim_string_to_tail(&mut content, max_bytes);
+    Ok(content)
+}
+
+pub(super) fn remove_scrollback_file(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => log::warn!(
+            "failed to remove terminal scrollback {}: {error}",
+            path.display()
+        ),
+    }
+}
+
+/// Deletes scrollback files untouched for longer than `max_age`; sessions
+/// that exited on their own leave their file behind for the next launch.
+pub(super) fn prune_scrollback_files(dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|value| value.to_str()) != Some(SCROLLBACK_FILE_EXTENSION) {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > max_age);
+        if stale && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("panes-scrollback-{}", Uuid::new_v4()))
+    }
+
+    #[test]
+    fn append_keeps_file_under_cap_and_on_char_boundaries() {
+        let dir = temp_dir();
+        let session_id = Uuid::new_v4().to_string();
+        let mut scrollback = TerminalScrollbackFile::create(&dir, &session_id, 16).expect("create");
+
+        scrollback.append("0123456789").expect("append");
+        scrollback.append("ééééé").expect("append");
+        scrollback.append("tail").expect("append");
+
+        let content = fs::read_to_string(scrollback.path()).expect("valid utf-8 on disk");
+        assert!(content.len() <= 16);
+        assert!(content.ends_with("ééééétail"));
+
+        let restored = read_scrollback_tail(&dir, &session_id, 6).expect("restore");
+        assert_eq!(restored, "étail");
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rejects_non_uuid_session_ids_and_missing_files_restore_empty() {
+        let dir = temp_dir();
+        assert!(scrollback_path(&dir, "../config").is_err());
+        assert_eq!(
+            read_scrollback_tail(&dir, &Uuid::new_v4().to_string(), 1024).expect("restore"),
+            ""
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::models::TerminalOutputMatchDto;
+
+pub(super) const SEARCH_MAX_MATCHES: usize = 500;
+// Context kept on each side of a match, cut back to the enclosing line.
+const SNIPPET_CONTEXT_BYTES: usize = 48;
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Non-overlapping matches of `query` in `data`, at most `max_matches`.
+/// Offsets are byte offsets into `data`. Case-insensitive matching folds
+/// with `char::to_lowercase` but reports the ranges of the original text.
+/// The second value is true when more matches were left out.
+pub(super) fn search_output(
+    data: &str,
+    query: &str,
+    case_sensitive: bool,
+    max_matches: usize,
+) -> (Vec<TerminalOutputMatchDto>, bool) {
+    let ranges = if case_sensitive {
+        data.match_indices(query)
+            .map(|(start, matched)| (start, start + matched.len()))
+            .take(max_matches + 1)
+            .collect::<Vec<_>>()
+    } else {
+        find_case_insensitive(data, query, max_matches + 1)
+    };
+    let limited = ranges.len() > max_matches;
+    let matches = ranges
+        .into_iter()
+        .take(max_matches)
+        .map(|(start, end)| match_with_snippet(data, start, end))
+        .collect();
+    (matches, limited)
+}
+
+fn find_case_insensitive(data: &str, query: &str, limit: usize) -> Vec<(usize, usize)> {
+    let needle = query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let mut ranges = Vec::new();
+    if needle.is_empty() {
+        return ranges;
+    }
+
+    let mut resume_at = 0;
+    for (start, _) in data.char_indices() {
+        if start < resume_at {
+            continue;
+        }
+        if let Some(end) = folded_match_end(data, start, &needle) {
+            ranges.push((start, end));
+            if ranges.len() >= limit {
+                break;
+            }
+            resume_at = end;
+        }
+    }
+    ranges
+}
+
+/// Byte end of the match starting at `start`, if the folded text there
+/// begins with `needle`. A char whose folding is only partly consumed does
+/// not match.
+fn folded_match_end(data: &str, start: usize, needle: &[char]) -> Option<usize> {
+    let mut matched = 0;
+    for (offset, ch) in data[start..].char_indices() {
+        for folded in ch.to_lowercase() {
+            if matched == needle.len() || folded != needle[matched] {
+                return None;
+            }
+            matched += 1;
+        }
+        if matched == needle.len() {
+            return Some(start + offset + ch.len_utf8());
+        }
+    }
+    None
+}
+
+fn match_with_snippet(data: &str, start: usize, end: usize) -> TerminalOutputMatchDto {
+    let line_start = data[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = data[end..]
+        .find('\n')
+        .map_or(data.len(), |index| end + index);
+    let before_start =
+        floor_char_boundary(data, start.saturating_sub(SNIPPET_CONTEXT_BYTES)).max(line_start);
+    let after_end = ceil_char_boundary(data, end + SNIPPET_CONTEXT_BYTES).min(line_end);
+
+    let before = strip_escape_sequences(&data[before_start..start]);
+    let matched = strip_escape_sequences(&data[start..end]);
+    let after = strip_escape_sequences(&data[end..after_end]);
+    TerminalOutputMatchDto {
+        offset: start,
+        length: end - start,
+        snippet_match_start: before.len(),
+        snippet_match_end: before.len() + matched.len(),
+        snippet: format!("{before}{matched}{after}"),
+    }
+}
+
+fn floor_char_boundary(value: &str, mut index: usize) -> usize {
+    while index > 0 && !value.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(value: &str, mut index: usize) -> usize {
+    if index >= value.len() {
+        return value.len();
+    }
+    while !value.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Drops CSI/OSC/two-byte escape sequences and other control characters so
+/// snippets read as plain text. Tabs become spaces.
+fn strip_escape_sequences(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ESC => match chars.next() {
+                Some('[') => {
+                    for next in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&next) {
+                            break;
+                        }
+                    }
+                }
+                Some(']') => {
+                    while let Some(next) = chars.next() {
+                        if next == BEL {
+                            break;
+                        }
+                        if next == ESC && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\t' => output.push(' '),
+            ch if ch.is_control() => {}
+            ch => output.push(ch),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_insensitive_offsets_point_into_the_original_text() {
+        let data = "ok\nÉrror: boom\nerror again\n";
+        let (matches, limited) = search_output(data, "érror", false, 10);
+        assert!(!limited);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&data[matches[0].offset..][..matches[0].length], "Érror");
+
+        let (matches, _) = search_output(data, "ERROR", false, 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].offset, data.find("error again").unwrap());
+        assert_eq!(matches[0].snippet, "error again");
+
+        let (matches, _) = search_output(data, "ERROR", true, 10);
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn snippets_drop_escape_sequences_and_stay_on_the_match_line() {
+        let data = "previous line\n\x1b[31mfatal\x1b[0m: disk full\x1b]0;title\x07\nnext";
+        let (matches, _) = search_output(data, "disk", true, 10);
+        let found = &matches[0];
+        assert_eq!(found.snippet, "fatal: disk full");
+        assert_eq!(
+            &found.snippet[found.snippet_match_start..found.snippet_match_end],
+            "disk"
+        );
+    }
+
+    #[test]
+    fn results_are_capped_and_flagged() {
+        let data = "x".repeat(20);
+        let (matches, limited) = search_output(&data, "x", true, 5);
+        assert_eq!(matches.len(), 5);
+        assert!(limited);
+
+        let (matches, limited) = search_output("aaaa", "aa", false, 5);
+        assert_eq!(
+            matches.iter().map(|found| found.offset).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert!(!limited);
+    }
+}
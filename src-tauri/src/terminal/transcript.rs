@@ -0,0 +1,252 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+    },
+    thread,
+};
+
+use chrono::{SecondsFormat, Utc};
+use uuid::Uuid;
+
+use crate::runtime_env;
+
+const TRANSCRIPT_DIR: [&str; 2] = ["logs", "terminal"];
+const TRANSCRIPT_FILE_EXTENSION: &str = "log";
+/// Size at which the live transcript is rotated; one rotated file is kept,
+/// so a session never uses more than twice this on disk.
+const TRANSCRIPT_MAX_BYTES: u64 = 20 * 1024 * 1024;
+/// Entries queued for the writer thread before new ones are dropped.
+const TRANSCRIPT_QUEUE_CAPACITY: usize = 1024;
+
+pub(super) fn transcript_dir() -> PathBuf {
+    TRANSCRIPT_DIR
+        .iter()
+        .fold(runtime_env::app_data_dir(), |dir, part| dir.join(part))
+}
+
+/// Session ids are UUIDs; anything else is refused so a caller cannot point
+/// the path outside the transcript directory.
+pub(super) fn transcript_path(dir: &Path, session_id: &str) -> anyhow::Result<PathBuf> {
+    let session_id = Uuid::parse_str(session_id)
+        .map_err(|_| anyhow::anyhow!("invalid terminal session id: {session_id}"))?;
+    Ok(dir.join(format!(
+        "{}.{TRANSCRIPT_FILE_EXTENSION}",
+        session_id.hyphenated()
+    )))
+}
+
+enum TranscriptEntry {
+    Output(String),
+    Input(Vec<u8>),
+}
+
+/// Records a session's decoded output and timestamped stdin writes to disk.
+///
+/// Recording only queues: a dedicated thread does the file I/O, and when it
+/// falls behind entries are dropped rather than stalling the PTY reader or
+/// the IPC emitter. The thread exits once the recorder is dropped.
+pub(super) struct TerminalTranscript {
+    path: PathBuf,
+    sender: SyncSender<TranscriptEntry>,
+    overflowed: AtomicBool,
+}
+
+impl TerminalTranscript {
+    pub(super) fn start(dir: &Path, session_id: &str) -> anyhow::Result<Self> {
+        Self::start_with_cap(dir, session_id, TRANSCRIPT_MAX_BYTES)
+    }
+
+    fn start_with_cap(dir: &Path, session_id: &str, max_bytes: u64) -> anyhow::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = transcript_path(dir, session_id)?;
+        let writer = TranscriptWriter::create(path.clone(), max_bytes)?;
+        let (sender, receiver) = mpsc::sync_channel(TRANSCRIPT_QUEUE_CAPACITY);
+        thread::Builder::new()
+            .name("terminal-transcript".to_string())
+            .spawn(move || run_writer(writer, receiver))?;
+        Ok(Self {
+            path,
+            sender,
+            overflowed: AtomicBool::new(false),
+        })
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(super) fn record_output(&self, data: &str) {
+        if !data.is_empty() {
+            self.send(TranscriptEntry::Output(data.to_string()));
+        }
+    }
+
+    pub(super) fn record_input(&self, data: &[u8]) {
+        if !data.is_empty() {
+            self.send(TranscriptEntry::Input(data.to_vec()));
+        }
+    }
+
+    fn send(&self, entry: TranscriptEntry) {
+        match self.sender.try_send(entry) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => {
+                if !self.overflowed.swap(true, Ordering::Relaxed) {
+                    log::warn!(
+                        "terminal transcript {} is falling behind; dropping entries",
+                        self.path.display()
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn run_writer(mut writer: TranscriptWriter, receiver: Receiver<TranscriptEntry>) {
+    for entry in receiver {
+        let rendered = match entry {
+            TranscriptEntry::Output(data) => render_output(&data),
+            TranscriptEntry::Input(data) => render_input(&data, &Utc::now()),
+        };
+        if let Err(error) = writer.append(&rendered) {
+            log::warn!(
+                "failed to write terminal transcript {}: {error}",
+                writer.path.display()
+            );
+            return;
+        }
+    }
+}
+
+struct TranscriptWriter {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+}
+
+impl TranscriptWriter {
+    fn create(path: PathBuf, max_bytes: u64) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            len,
+            max_bytes: max_bytes.max(1),
+        })
+    }
+
+    /// Appends `data`, first moving a full file to `<name>.1` (replacing the
+    /// previous rotation).
+    fn append(&mut self, data: &str) -> io::Result<()> {
+        if self.len > 0 && self.len + data.len() as u64 > self.max_bytes {
+            self.file.flush()?;
+            fs::rename(&self.path, rotated_path(&self.path))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?;
+            self.len = 0;
+        }
+        self.file.write_all(data.as_bytes())?;
+        self.len += data.len() as u64;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    path.with_file_name(name)
+}
+
+/// Output keeps its line breaks; every other control byte is escaped.
+fn render_output(data: &str) -> String {
+    escape_control_chars(&data.replace("\r\n", "\n"), true)
+}
+
+/// One line per stdin write, with every control byte escaped.
+fn render_input(data: &[u8], at: &chrono::DateTime<Utc>) -> String {
+    format!(
+        "\n[{}] stdin: {}\n",
+        at.to_rfc3339_opts(SecondsFormat::Millis, true),
+        escape_control_chars(&String::from_utf8_lossy(data), false)
+    )
+}
+
+/// Renders C0 controls and DEL in caret notation (`^C`, `^[`, `^?`) so the
+/// transcript stays readable as plain text.
+fn escape_control_chars(text: &str, keep_newlines: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\n' if keep_newlines => escaped.push('\n'),
+            '\t' => escaped.push('\t'),
+            '\u{7f}' => escaped.push_str("^?"),
+            ch if (ch as u32) < 0x20 => {
+                escaped.push('^');
+                escaped.push(char::from(ch as u8 + 0x40));
+            }
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("panes-transcript-{}", Uuid::new_v4()))
+    }
+
+    fn wait_for_content(path: &Path, expected: &str) -> String {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let content = fs::read_to_string(path).unwrap_or_default();
+            if content.contains(expected) || Instant::now() >= deadline {
+                return content;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn control_bytes_are_escaped_and_stdin_is_timestamped() {
+        assert_eq!(render_output("a\r\nb\x1b[0m\x07"), "a\nb^[[0m^G");
+        let at = "2026-04-01T10:00:00.5Z".parse().expect("timestamp");
+        assert_eq!(
+            render_input(b"ls\r\x03\x7f", &at),
+            "\n[2026-04-01T10:00:00.500Z] stdin: ls^M^C^?\n"
+        );
+    }
+
+    #[test]
+    fn recorder_writes_off_thread_and_rotates_past_the_cap() {
+        let dir = temp_dir();
+        let session_id = Uuid::new_v4().to_string();
+        let transcript = TerminalTranscript::start_with_cap(&dir, &session_id, 64).expect("start");
+        let path = transcript.path().to_path_buf();
+
+        transcript.record_output(&"x".repeat(60));
+        transcript.record_input(b"\x03");
+        let content = wait_for_content(&path, "stdin: ^C");
+        assert!(content.contains("stdin: ^C"), "{content:?}");
+        assert!(!content.contains('x'), "rotated out of the live file");
+
+        let rotated = fs::read_to_string(rotated_path(&path)).expect("rotated file");
+        assert_eq!(rotated, "x".repeat(60));
+        assert!(transcript_path(&dir, "../escape").is_err());
+        drop(transcript);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
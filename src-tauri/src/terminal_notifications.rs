@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::{BufRead, BufReader, Read, Write},
     net::{SocketAddr, TcpStream},
     path::{Path, PathBuf},
@@ -72,6 +72,7 @@ pub struct TerminalNotificationManager {
     runtime: RwLock<Option<NotificationIngressRuntime>>,
     notifications: RwLock<HashMap<String, HashMap<String, TerminalNotificationDto>>>,
     focus: RwLock<NotificationFocusState>,
+    archived_workspaces: RwLock<HashSet<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -316,6 +317,17 @@ impl TerminalNotificationManager {
         self.clear(app, workspace_id, None).await
     }
 
+    /// Archived workspaces get no new notifications, even from a session
+    /// that outlived the archive.
+    pub async fn set_workspace_archived(&self, workspace_id: &str, archived: bool) {
+        let mut archived_workspaces = self.archived_workspaces.write().await;
+        if archived {
+            archived_workspaces.insert(workspace_id.to_string());
+        } else {
+            archived_workspaces.remove(workspace_id);
+        }
+    }
+
     pub async fn set_focus(
         &self,
         window_focused: bool,
@@ -428,6 +440,14 @@ impl TerminalNotificationManager {
                 .await;
             return Ok(None);
         }
+        if self
+            .archived_workspaces
+            .read()
+            .await
+            .contains(&workspace_id)
+        {
+            return Ok(None);
+        }
 
         let title = normalize_notification_text(
             request.title.as_deref(),
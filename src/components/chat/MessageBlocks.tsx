@@ -32,6 +32,7 @@ import {
   Copy,
   Check,
   MessageSquare,
+  Plug,
 } from "lucide-react";
 import type {
   ActionBlock,
@@ -263,6 +264,7 @@ const actionIcons: Record<string, typeof Terminal> = {
   file_edit: FileCode2,
   file_read: FileCode2,
   file_delete: FileCode2,
+  mcp_tool: Plug,
 };
 
 /* ── Action Group Segmentation ── */
@@ -693,6 +695,12 @@ function ActionBlockView({
     actionDetails.progressKind === "mcp" && typeof actionDetails.progressMessage === "string"
       ? actionDetails.progressMessage
       : null;
+  const mcpToolLine =
+    block.actionType === "mcp_tool"
+      ? [actionDetails.server, actionDetails.argsPreview]
+          .filter((part): part is string => typeof part === "string" && part.length > 0)
+          .join(" · ")
+      : "";
   const [expanded, setExpanded] = useState(false);
   const [outputExpandedFully, setOutputExpandedFully] = useState(false);
   const [outputCopied, setOutputCopied] = useState(false);
@@ -763,6 +771,23 @@ function ActionBlockView({
         }
       />
 
+      {mcpToolLine && (
+        <div
+          style={{
+            padding: "0 12px 6px 30px",
+            fontSize: 11,
+            fontFamily: "var(--font-mono)",
+            color: "var(--text-3)",
+            lineHeight: 1.5,
+            overflow: "hidden",
+            textOverflow: "ellipsis",
+            whiteSpace: "nowrap",
+          }}
+        >
+          {mcpToolLine}
+        </div>
+      )}
+
       {progressMessage && (
         <div
           style={{
@@ -917,6 +942,7 @@ const actionTypeLabels: Record<string, string> = {
   file_delete: "file_delete",
   git: "git",
   search: "search",
+  mcp_tool: "mcp_tool",
   other: "other",
 };
 
@@ -1031,6 +1057,7 @@ const APPROVAL_INTERNAL_KEYS = new Set([
   "callId",
   "call_id",
   "arguments",
+  "argsPreview",
   "tool",
   "name",
   "permissions",
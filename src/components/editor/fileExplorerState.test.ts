@@ -7,6 +7,11 @@ import {
   pruneContainedPaths,
   remapDescendantPath,
 } from "./fileExplorerState";
+import type { FileTreeEntry } from "../../types";
+
+function entry(path: string, isDir: boolean): FileTreeEntry {
+  return { path, isDir, isSymlink: false, gitStatus: null };
+}
 
 describe("isCurrentExplorerLoad", () => {
   it("accepts loads for the current workspace root generation", () => {
@@ -93,16 +98,16 @@ describe("pruneDeletedSetPaths", () => {
 describe("pruneDeletedMapKeys", () => {
   it("removes cached directory contents for deleted subtrees", () => {
     const dirContents = new Map([
-      ["", [{ path: "src/components", isDir: true }]],
-      ["src/components", [{ path: "src/components/editor", isDir: true }]],
-      ["src/components/editor", [{ path: "src/components/editor/FileExplorer.tsx", isDir: false }]],
-      ["docs", [{ path: "docs/guide.md", isDir: false }]],
+      ["", [entry("src/components", true)]],
+      ["src/components", [entry("src/components/editor", true)]],
+      ["src/components/editor", [entry("src/components/editor/FileExplorer.tsx", false)]],
+      ["docs", [entry("docs/guide.md", false)]],
     ]);
 
     expect(pruneDeletedMapKeys(dirContents, ["src/components"])).toEqual(
       new Map([
-        ["", [{ path: "src/components", isDir: true }]],
-        ["docs", [{ path: "docs/guide.md", isDir: false }]],
+        ["", [entry("src/components", true)]],
+        ["docs", [entry("docs/guide.md", false)]],
       ]),
     );
   });
@@ -111,8 +116,8 @@ describe("pruneDeletedMapKeys", () => {
 describe("isKnownDirectoryPath", () => {
   it("recognizes collapsed directories from their parent listing", () => {
     const dirContents = new Map([
-      ["", [{ path: "src", isDir: true }]],
-      ["docs", [{ path: "docs/guide.md", isDir: false }]],
+      ["", [entry("src", true)]],
+      ["docs", [entry("docs/guide.md", false)]],
     ]);
 
     expect(isKnownDirectoryPath(dirContents, "src")).toBe(true);
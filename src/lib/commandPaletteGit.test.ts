@@ -23,8 +23,11 @@ function createStatus(files: GitStatus["files"] = []): GitStatus {
   return {
     branch: "main",
     files,
+    upstreamBranch: null,
     ahead: 0,
     behind: 0,
+    detachedHead: false,
+    conflicts: [],
   };
 }
 
@@ -7,27 +7,39 @@ import type {
   ApprovalResponse,
   ActionOutputPayload,
   AttachmentPreview,
+  BlobStorageReport,
   ChatAttachment,
   ChatEngineId,
   ChatInputItem,
+  ConversationImport,
+  ConversationImportFormat,
   ChatProviderUsage,
   CodexApprovalsReviewer,
   CodexReviewDelivery,
   CodexReviewTarget,
   CodexRemoteThreadPage,
   ContentBlock,
+  DatabasePoolMetrics,
   CodexApp,
   CodexSkill,
   DependencyReport,
+  DetectedAgentConfig,
+  EnvironmentRefresh,
   EngineCheckResult,
+  EngineProtocolGapEvent,
   EngineRuntimeUpdatedEvent,
   GitBranchPage,
   GitBranchScope,
+  GitCommitMessage,
   GitCommitPage,
+  GitCommitResult,
   GitInitRepoStatus,
+  GitOperationLog,
+  GitPreflight,
   GitCompareSource,
   GitFileCompare,
   GitStash,
+  GitStashApplyResult,
   GitRemote,
   GitWorktree,
   EngineHealth,
@@ -35,15 +47,21 @@ import type {
   FileTreeEntry,
   FileTreePage,
   GitDiffPreview,
+  GitConflict,
+  GitConflictResolution,
   GitStatus,
   HarnessReport,
   InstallProgressEvent,
   InstallResult,
   HelperStatus,
   KeepAwakeState,
+  LocalMetrics,
+  LocalMetricsPeriod,
   PowerSettings,
   PowerSettingsInput,
+  QueuedMessage,
   Message,
+  MultiSendResult,
   MessageWindow,
   MessageWindowCursor,
   OpenCodeRemoteSessionPage,
@@ -56,19 +74,41 @@ import type {
   TerminalNotificationClearedEvent,
   TerminalNotification,
   TerminalExitEvent,
+  TerminalCwdChangedEvent,
   TerminalForegroundChangedEvent,
+  TerminalTitleChangedEvent,
+  TerminalIdleWarningEvent,
   TerminalNotificationIntegrationId,
   TerminalNotificationSettings,
   TerminalOutputReadyEvent,
+  TerminalOutputSearch,
+  TerminalPathInjection,
   TerminalRendererDiagnostics,
   TerminalResumeSession,
+  TerminalAttach,
+  TerminalScrollback,
   TerminalSession,
+  TerminalSessionLimits,
   WorkspaceStartupPreset,
   WorkspaceStartupPresetFormat,
   Thread,
+  ThreadExportFormat,
   TrustLevel,
   WorkspaceGitSelectionStatus,
-  Workspace
+  Workspace,
+  WorkspaceArchiveReport,
+  ThreadModelHistory,
+  ThreadTimeline,
+  ThreadTimelineCursor,
+  TurnDiagnostic,
+  TurnEventLog,
+  UsageLimitsUpdatedEvent,
+  EngineUsageLimits,
+  UsageGroupBy,
+  UsageSummary,
+  GitRepoFiles,
+  GitMultiRepoResult,
+  WorkspaceGitOverview,
 } from "../types";
 
 export const ipc = {
@@ -97,6 +137,18 @@ export const ipc = {
     invoke<boolean>("set_chat_notifications_enabled", { enabled }),
   setTerminalNotificationsEnabled: (enabled: boolean) =>
     invoke<boolean>("set_terminal_notifications_enabled", { enabled }),
+  getLocalMetrics: (period: LocalMetricsPeriod) =>
+    invoke<LocalMetrics>("get_local_metrics", { period }),
+  getDatabasePoolDiagnostics: () =>
+    invoke<DatabasePoolMetrics>("get_database_pool_diagnostics"),
+  getWeeklyUsageSummaryEnabled: () =>
+    invoke<boolean>("get_weekly_usage_summary_enabled"),
+  setWeeklyUsageSummaryEnabled: (enabled: boolean) =>
+    invoke<boolean>("set_weekly_usage_summary_enabled", { enabled }),
+  getTerminalScrollbackPersistence: () =>
+    invoke<boolean>("get_terminal_scrollback_persistence"),
+  setTerminalScrollbackPersistence: (enabled: boolean) =>
+    invoke<boolean>("set_terminal_scrollback_persistence", { enabled }),
   installTerminalNotificationIntegration: (integration: TerminalNotificationIntegrationId) =>
     invoke<TerminalNotificationSettings>("install_terminal_notification_integration_command", { integration }),
   setNotificationSound: (sound: string) =>
@@ -112,14 +164,24 @@ export const ipc = {
       path,
       scanDepth: scanDepth ?? null,
     }),
-  archiveWorkspace: (workspaceId: string) => invoke<void>("archive_workspace", { workspaceId }),
+  archiveWorkspace: (workspaceId: string) =>
+    invoke<WorkspaceArchiveReport>("archive_workspace", { workspaceId }),
   restoreWorkspace: (workspaceId: string) => invoke<Workspace>("restore_workspace", { workspaceId }),
   deleteWorkspace: (workspaceId: string) => invoke<void>("delete_workspace", { workspaceId }),
   getRepos: (workspaceId: string) => invoke<Repo[]>("get_repos", { workspaceId }),
+  detectAgentConfigs: (repoId: string) =>
+    invoke<DetectedAgentConfig[]>("detect_agent_configs", { repoId }),
+  adoptAgentConfig: (repoId: string, path: string) =>
+    invoke<DetectedAgentConfig[]>("adopt_agent_config", { repoId, path }),
   setRepoTrustLevel: (repoId: string, trustLevel: TrustLevel) =>
     invoke<void>("set_repo_trust_level", { repoId, trustLevel }),
   setRepoGitActive: (repoId: string, isActive: boolean) =>
     invoke<void>("set_repo_git_active", { repoId, isActive }),
+  getRepoProtectedBranches: (repoPath: string) =>
+    invoke<string[]>("get_repo_protected_branches", { repoPath }),
+  /** Passing `null` restores the default protected branch patterns. */
+  setRepoProtectedBranches: (repoId: string, patterns: string[] | null) =>
+    invoke<void>("set_repo_protected_branches", { repoId, patterns }),
   setWorkspaceGitActiveRepos: (workspaceId: string, repoIds: string[]) =>
     invoke<void>("set_workspace_git_active_repos", { workspaceId, repoIds }),
   hasWorkspaceGitSelection: (workspaceId: string) =>
@@ -265,11 +327,50 @@ export const ipc = {
       reasoningEffort: reasoningEffort ?? null,
       serviceTier: serviceTier ?? null,
     }),
+  exportThread: (
+    threadId: string,
+    format: ThreadExportFormat,
+    outputPath: string,
+    options?: { includeThinking?: boolean; includeActions?: boolean },
+  ) =>
+    invoke<void>("export_thread", {
+      threadId,
+      format,
+      includeThinking: options?.includeThinking ?? true,
+      includeActions: options?.includeActions ?? true,
+      outputPath,
+      utcOffsetMinutes: -new Date().getTimezoneOffset(),
+    }),
+  exportThreadMarkdown: (threadId: string) =>
+    invoke<string | null>("export_thread_markdown", {
+      threadId,
+      utcOffsetMinutes: -new Date().getTimezoneOffset(),
+    }),
+  importConversation: (
+    workspaceId: string,
+    repoId: string | null,
+    sourcePath: string,
+    format: ConversationImportFormat,
+  ) =>
+    invoke<ConversationImport>("import_conversation", {
+      workspaceId,
+      repoId,
+      sourcePath,
+      format,
+    }),
   renameThread: (threadId: string, title: string) =>
     invoke<Thread>("rename_thread", {
       threadId,
       title,
     }),
+  openThreadInCli: (threadId: string, cols: number, rows: number) =>
+    invoke<TerminalSession>("open_thread_in_cli", {
+      threadId,
+      cols,
+      rows,
+    }),
+  detachThreadFromCli: (threadId: string) =>
+    invoke<Thread>("detach_thread_from_cli", { threadId }),
   confirmWorkspaceThread: (threadId: string, writableRoots: string[]) =>
     invoke<void>("confirm_workspace_thread", { threadId, writableRoots }),
   setThreadReasoningEffort: (
@@ -307,6 +408,7 @@ export const ipc = {
       personality?: string | null;
       serviceTier?: string | null;
       outputSchema?: unknown;
+      executableOverride?: string | null;
     },
   ) =>
     invoke<Thread>("set_thread_codex_config", {
@@ -317,7 +419,14 @@ export const ipc = {
       serviceTier: patch.serviceTier ?? null,
       updateOutputSchema: Object.prototype.hasOwnProperty.call(patch, "outputSchema"),
       outputSchema: patch.outputSchema ?? null,
+      updateExecutableOverride: Object.prototype.hasOwnProperty.call(
+        patch,
+        "executableOverride",
+      ),
+      executableOverride: patch.executableOverride ?? null,
     }),
+  setThreadCustomInstructions: (threadId: string, instructions: string | null) =>
+    invoke<Thread>("set_thread_custom_instructions", { threadId, instructions }),
   setThreadOpenCodeConfig: (
     threadId: string,
     patch: {
@@ -329,10 +438,18 @@ export const ipc = {
       updateAgent: Object.prototype.hasOwnProperty.call(patch, "agent"),
       agent: patch.agent ?? null,
     }),
+  setThreadWorktree: (threadId: string, worktreePath: string | null) =>
+    invoke<Thread>("set_thread_worktree", { threadId, worktreePath }),
   archiveThread: (threadId: string) => invoke<void>("archive_thread", { threadId }),
   restoreThread: (threadId: string) => invoke<Thread>("restore_thread", { threadId }),
   syncThreadFromEngine: (threadId: string) =>
     invoke<Thread>("sync_thread_from_engine", { threadId }),
+  forkThread: (threadId: string, upToMessageId: string, newTitle?: string | null) =>
+    invoke<Thread>("fork_thread", {
+      threadId,
+      upToMessageId,
+      newTitle: newTitle ?? null,
+    }),
   forkCodexThread: (threadId: string) =>
     invoke<Thread>("fork_codex_thread", { threadId }),
   rollbackCodexThread: (threadId: string, numTurns: number) =>
@@ -340,6 +457,8 @@ export const ipc = {
   compactCodexThread: (threadId: string) =>
     invoke<Thread>("compact_codex_thread", { threadId }),
   deleteThread: (threadId: string) => invoke<void>("delete_thread", { threadId }),
+  cleanupEmptyThreads: (workspaceId: string, olderThanHours?: number) =>
+    invoke<number>("cleanup_empty_threads", { workspaceId, olderThanHours }),
   listEngines: () => invoke<EngineInfo[]>("list_engines"),
   getChatProviderUsage: () =>
     invoke<ChatProviderUsage[]>("get_chat_provider_usage"),
@@ -372,6 +491,7 @@ export const ipc = {
     inputItems?: ChatInputItem[] | null,
     planMode?: boolean | null,
     clientTurnId?: string | null,
+    force?: boolean | null,
   ) =>
     invoke<string>("send_message", {
       threadId,
@@ -382,7 +502,70 @@ export const ipc = {
       inputItems: inputItems ?? null,
       planMode: planMode ?? null,
       clientTurnId: clientTurnId ?? null,
+      force: force ?? null,
+    }),
+  sendMessageMulti: (
+    threadIds: string[],
+    message: string,
+    options?: {
+      attachments?: ChatAttachment[] | null;
+      inputItems?: ChatInputItem[] | null;
+      planMode?: boolean | null;
+      force?: boolean | null;
+    },
+  ) =>
+    invoke<MultiSendResult>("send_message_multi", {
+      threadIds,
+      message,
+      attachments: options?.attachments ?? null,
+      inputItems: options?.inputItems ?? null,
+      planMode: options?.planMode ?? null,
+      force: options?.force ?? null,
+    }),
+  queueMessage: (
+    threadId: string,
+    message: string,
+    options?: {
+      modelId?: string | null;
+      reasoningEffort?: string | null;
+      attachments?: ChatAttachment[] | null;
+      inputItems?: ChatInputItem[] | null;
+      planMode?: boolean | null;
+      runAfterError?: boolean | null;
+    },
+  ) =>
+    invoke<QueuedMessage>("queue_message", {
+      threadId,
+      message,
+      modelId: options?.modelId ?? null,
+      reasoningEffort: options?.reasoningEffort ?? null,
+      attachments: options?.attachments ?? null,
+      inputItems: options?.inputItems ?? null,
+      planMode: options?.planMode ?? null,
+      runAfterError: options?.runAfterError ?? null,
+    }),
+  listQueuedMessages: (threadId: string) =>
+    invoke<QueuedMessage[]>("list_queued_messages", { threadId }),
+  cancelQueuedMessage: (threadId: string, queuedMessageId: string) =>
+    invoke<void>("cancel_queued_message", { threadId, queuedMessageId }),
+  /** Rejects with a `SendMessageError`, like `sendMessage`. */
+  regenerateMessage: (messageId: string, modelId?: string | null) =>
+    invoke<string>("regenerate_message", { messageId, modelId: modelId ?? null }),
+  /** Rejects with a `SendMessageError`, like `sendMessage`. */
+  retryLastTurn: (
+    threadId: string,
+    modelId?: string | null,
+    reasoningEffort?: string | null,
+  ) =>
+    invoke<string>("retry_last_turn", {
+      threadId,
+      modelId: modelId ?? null,
+      reasoningEffort: reasoningEffort ?? null,
     }),
+  editUserMessage: (threadId: string, messageId: string, newText: string) =>
+    invoke<Message[]>("edit_user_message", { threadId, messageId, newText }),
+  deleteMessage: (threadId: string, messageId: string) =>
+    invoke<void>("delete_message", { threadId, messageId }),
   steerMessage: (
     threadId: string,
     message: string,
@@ -407,11 +590,42 @@ export const ipc = {
       target,
       delivery,
     }),
-  cancelTurn: (threadId: string) => invoke<void>("cancel_turn", { threadId }),
+  cancelTurn: (threadId: string, clearQueue?: boolean) =>
+    invoke<void>("cancel_turn", { threadId, clearQueue: clearQueue ?? null }),
   respondApproval: (threadId: string, approvalId: string, response: ApprovalResponse) =>
     invoke<void>("respond_to_approval", { threadId, approvalId, response }),
   getThreadMessages: (threadId: string) =>
     invoke<Message[]>("get_thread_messages", { threadId }),
+  getThreadUsage: (threadId: string) =>
+    invoke<UsageLimitsUpdatedEvent["usage"] | null>("get_thread_usage", { threadId }),
+  getUsageLimits: (engineId: string) =>
+    invoke<EngineUsageLimits | null>("get_usage_limits", { engineId }),
+  getUsageSummary: (
+    workspaceId: string,
+    options?: {
+      since?: string | null;
+      until?: string | null;
+      groupBy?: UsageGroupBy[] | null;
+      includeArchived?: boolean | null;
+    },
+  ) =>
+    invoke<UsageSummary>("get_usage_summary", {
+      workspaceId,
+      since: options?.since ?? null,
+      until: options?.until ?? null,
+      groupBy: options?.groupBy ?? null,
+      includeArchived: options?.includeArchived ?? null,
+    }),
+  getThreadMessagesPage: (
+    threadId: string,
+    beforeMessageId?: string | null,
+    limit?: number | null,
+  ) =>
+    invoke<MessageWindow>("get_thread_messages_page", {
+      threadId,
+      beforeMessageId: beforeMessageId ?? null,
+      limit: limit ?? null,
+    }),
   getThreadMessagesWindow: (
     threadId: string,
     cursor?: MessageWindowCursor | null,
@@ -426,14 +640,58 @@ export const ipc = {
     invoke<ContentBlock[] | null>("get_message_blocks", { messageId }),
   getActionOutput: (messageId: string, actionId: string) =>
     invoke<ActionOutputPayload>("get_action_output", { messageId, actionId }),
-  searchMessages: (workspaceId: string, query: string) =>
+  getTurnDiagnostics: (messageId: string) =>
+    invoke<TurnDiagnostic[]>("get_turn_diagnostics", { messageId }),
+  getMessageStorageReport: () =>
+    invoke<BlobStorageReport>("get_message_storage_report"),
+  getTurnEventLog: (assistantMessageId: string, tailLines?: number) =>
+    invoke<TurnEventLog>("get_turn_event_log", { assistantMessageId, tailLines }),
+  getThreadModelHistory: (threadId: string) =>
+    invoke<ThreadModelHistory>("get_thread_model_history", { threadId }),
+  getThreadTimeline: (
+    threadId: string,
+    limit?: number | null,
+    before?: ThreadTimelineCursor | null,
+  ) =>
+    invoke<ThreadTimeline>("get_thread_timeline", {
+      threadId,
+      limit: limit ?? null,
+      before: before ?? null,
+    }),
+  searchMessages: (
+    workspaceId: string,
+    query: string,
+    page?: { limit?: number | null; offset?: number | null },
+  ) =>
     invoke<SearchResult[]>("search_messages", {
       workspaceId,
-      query
+      query,
+      limit: page?.limit ?? null,
+      offset: page?.offset ?? null,
     }),
   getGitStatus: (repoPath: string) => invoke<GitStatus>("get_git_status", { repoPath }),
-  getFileDiff: (repoPath: string, filePath: string, staged: boolean) =>
-    invoke<GitDiffPreview>("get_file_diff", { repoPath, filePath, staged }),
+  gitPreflight: (repoPath: string, refresh?: boolean) =>
+    invoke<GitPreflight>("git_preflight", { repoPath, refresh: refresh ?? null }),
+  getFileDiff: (
+    repoPath: string,
+    filePath: string,
+    staged: boolean,
+    conflictMarkers?: boolean,
+  ) =>
+    invoke<GitDiffPreview>("get_file_diff", {
+      repoPath,
+      filePath,
+      staged,
+      conflictMarkers,
+    }),
+  listConflicts: (repoPath: string) =>
+    invoke<GitConflict[]>("list_conflicts", { repoPath }),
+  resolveConflict: (
+    repoPath: string,
+    filePath: string,
+    resolution: GitConflictResolution,
+  ) =>
+    invoke<GitStatus>("resolve_conflict", { repoPath, filePath, resolution }),
   getGitFileCompare: (
     repoPath: string,
     filePath: string,
@@ -445,10 +703,22 @@ export const ipc = {
       source,
     }),
   getFileTree: (repoPath: string) => invoke<FileTreeEntry[]>("get_file_tree", { repoPath }),
-  getFileTreePage: (repoPath: string, offset?: number, limit?: number) =>
-    invoke<FileTreePage>("get_file_tree_page", { repoPath, offset: offset ?? null, limit: limit ?? null }),
-  listDir: (repoPath: string, dirPath: string) =>
-    invoke<FileTreeEntry[]>("list_dir", { repoPath, dirPath }),
+  getFileTreePage: (
+    repoPath: string,
+    offset?: number,
+    limit?: number,
+    dirPath?: string | null,
+    showIgnored?: boolean
+  ) =>
+    invoke<FileTreePage>("get_file_tree_page", {
+      repoPath,
+      offset: offset ?? null,
+      limit: limit ?? null,
+      dirPath: dirPath ?? null,
+      showIgnored: showIgnored ?? null,
+    }),
+  listDir: (repoPath: string, dirPath: string, showIgnored?: boolean) =>
+    invoke<FileTreeEntry[]>("list_dir", { repoPath, dirPath, showIgnored: showIgnored ?? null }),
   createFile: (repoPath: string, filePath: string, workspaceId?: string | null) =>
     invoke<void>("create_file", { repoPath, filePath, workspaceId: workspaceId ?? null }),
   createDir: (repoPath: string, dirPath: string, workspaceId?: string | null) =>
@@ -460,12 +730,49 @@ export const ipc = {
   stageFiles: (repoPath: string, files: string[]) => invoke<void>("stage_files", { repoPath, files }),
   unstageFiles: (repoPath: string, files: string[]) =>
     invoke<void>("unstage_files", { repoPath, files }),
+  stageHunk: (repoPath: string, filePath: string, hunkHeader: string) =>
+    invoke<GitStatus>("stage_hunk", { repoPath, filePath, hunkHeader }),
+  unstageHunk: (repoPath: string, filePath: string, hunkHeader: string) =>
+    invoke<GitStatus>("unstage_hunk", { repoPath, filePath, hunkHeader }),
+  /** Rejects with a `GitHunkError`. */
+  stageHunks: (repoPath: string, filePath: string, hunkIds: string[]) =>
+    invoke<GitStatus>("stage_hunks", { repoPath, filePath, hunkIds }),
+  /** Rejects with a `GitHunkError`. */
+  unstageHunks: (repoPath: string, filePath: string, hunkIds: string[]) =>
+    invoke<GitStatus>("unstage_hunks", { repoPath, filePath, hunkIds }),
   revealPath: (path: string) => invoke<void>("reveal_path", { path }),
   openPathWithDefaultApp: (path: string) =>
     invoke<void>("open_path_with_default_app", { path }),
   discardFiles: (repoPath: string, files: string[]) =>
     invoke<void>("discard_files", { repoPath, files }),
-  commit: (repoPath: string, message: string) => invoke<string>("commit", { repoPath, message }),
+  commit: (
+    repoPath: string,
+    message: string,
+    options?: {
+      sign?: boolean;
+      amend?: boolean;
+      signoff?: boolean;
+      force?: boolean;
+      threadId?: string | null;
+    },
+  ) =>
+    invoke<GitCommitResult>("commit", {
+      repoPath,
+      message,
+      sign: options?.sign ?? null,
+      amend: options?.amend ?? null,
+      signoff: options?.signoff ?? null,
+      force: options?.force ?? null,
+      threadId: options?.threadId ?? null,
+    }),
+  generateCommitMessage: (repoPath: string, engineId: string, modelId: string) =>
+    invoke<GitCommitMessage>("generate_commit_message", { repoPath, engineId, modelId }),
+  getWorkspaceGitOverview: (workspaceId: string) =>
+    invoke<WorkspaceGitOverview>("get_workspace_git_overview", { workspaceId }),
+  stageFilesMulti: (entries: GitRepoFiles[]) =>
+    invoke<GitMultiRepoResult[]>("stage_files_multi", { entries }),
+  commitMulti: (workspaceId: string, message: string, repoPaths: string[]) =>
+    invoke<GitMultiRepoResult[]>("commit_multi", { workspaceId, message, repoPaths }),
   softResetLastCommit: (repoPath: string) =>
     invoke<void>("soft_reset_last_commit", { repoPath }),
   fetchGit: (repoPath: string) => invoke<void>("fetch_git", { repoPath }),
@@ -485,8 +792,48 @@ export const ipc = {
     invoke<void>("create_git_branch", { repoPath, branchName, fromRef: fromRef ?? null }),
   renameGitBranch: (repoPath: string, oldName: string, newName: string) =>
     invoke<void>("rename_git_branch", { repoPath, oldName, newName }),
-  deleteGitBranch: (repoPath: string, branchName: string, force: boolean) =>
-    invoke<void>("delete_git_branch", { repoPath, branchName, force }),
+  deleteGitBranch: (
+    repoPath: string,
+    branchName: string,
+    force: boolean,
+    threadId?: string | null,
+  ) =>
+    invoke<void>("delete_git_branch", {
+      repoPath,
+      branchName,
+      force,
+      threadId: threadId ?? null,
+    }),
+  squashCommits: (
+    repoPath: string,
+    count: number,
+    message: string,
+    force?: boolean,
+    threadId?: string | null,
+  ) =>
+    invoke<string>("squash_commits", {
+      repoPath,
+      count,
+      message,
+      force: force ?? null,
+      threadId: threadId ?? null,
+    }),
+  rewordCommit: (
+    repoPath: string,
+    sha: string,
+    message: string,
+    force?: boolean,
+    threadId?: string | null,
+  ) =>
+    invoke<string>("reword_commit", {
+      repoPath,
+      sha,
+      message,
+      force: force ?? null,
+      threadId: threadId ?? null,
+    }),
+  listGitOperationLogs: (repoPath: string, limit?: number) =>
+    invoke<GitOperationLog[]>("list_git_operation_logs", { repoPath, limit: limit ?? null }),
   listGitCommits: (repoPath: string, offset?: number, limit?: number) =>
     invoke<GitCommitPage>("list_git_commits", {
       repoPath,
@@ -497,14 +844,23 @@ export const ipc = {
     invoke<GitDiffPreview>("get_commit_diff", { repoPath, commitHash }),
   listGitStashes: (repoPath: string) =>
     invoke<GitStash[]>("list_git_stashes", { repoPath }),
-  pushGitStash: (repoPath: string, message?: string) =>
-    invoke<void>("push_git_stash", { repoPath, message: message ?? null }),
+  pushGitStash: (
+    repoPath: string,
+    message?: string,
+    options?: { includeUntracked?: boolean; paths?: string[] },
+  ) =>
+    invoke<void>("push_git_stash", {
+      repoPath,
+      message: message ?? null,
+      includeUntracked: options?.includeUntracked ?? null,
+      paths: options?.paths ?? null,
+    }),
   applyGitStash: (repoPath: string, stashIndex: number) =>
-    invoke<void>("apply_git_stash", { repoPath, stashIndex }),
+    invoke<GitStashApplyResult>("apply_git_stash", { repoPath, stashIndex }),
   popGitStash: (repoPath: string, stashIndex: number) =>
-    invoke<void>("pop_git_stash", { repoPath, stashIndex }),
-  readFile: (repoPath: string, filePath: string) =>
-    invoke<ReadFileResult>("read_file", { repoPath, filePath }),
+    invoke<GitStashApplyResult>("pop_git_stash", { repoPath, stashIndex }),
+  readFile: (repoPath: string, filePath: string, force?: boolean) =>
+    invoke<ReadFileResult>("read_file", { repoPath, filePath, force: force ?? null }),
   resolveEditorFileReference: (
     workspaceId: string,
     rawReference: string,
@@ -517,8 +873,23 @@ export const ipc = {
       preferredRepoPath: preferredRepoPath ?? null,
       currentCwd: currentCwd ?? null,
     }),
-  writeFile: (repoPath: string, filePath: string, content: string, workspaceId?: string | null) =>
-    invoke<void>("write_file", { repoPath, filePath, content, workspaceId: workspaceId ?? null }),
+  /** Rejects with a `FileWriteError`. */
+  writeFile: (
+    repoPath: string,
+    filePath: string,
+    content: string,
+    workspaceId?: string | null,
+    createBackup?: boolean,
+  ) =>
+    invoke<void>("write_file", {
+      repoPath,
+      filePath,
+      content,
+      workspaceId: workspaceId ?? null,
+      createBackup: createBackup ?? null,
+    }),
+  watchFile: (path: string) => invoke<void>("watch_file", { path }),
+  unwatchFile: (path: string) => invoke<boolean>("unwatch_file", { path }),
   watchGitRepo: (repoPath: string) => invoke<void>("watch_git_repo", { repoPath }),
   addGitWorktree: (repoPath: string, worktreePath: string, branchName: string, baseRef?: string | null) =>
     invoke<GitWorktree>("add_git_worktree", { repoPath, worktreePath, branchName, baseRef: baseRef ?? null }),
@@ -553,12 +924,35 @@ export const ipc = {
     invoke<void>("remove_git_remote", { repoPath, name }),
   renameGitRemote: (repoPath: string, oldName: string, newName: string) =>
     invoke<void>("rename_git_remote", { repoPath, oldName, newName }),
-  terminalCreateSession: (workspaceId: string, cols: number, rows: number, cwd?: string | null) =>
-    invoke<TerminalSession>("terminal_create_session", { workspaceId, cols, rows, cwd: cwd ?? null }),
+  terminalCreateSession: (
+    workspaceId: string,
+    cols: number,
+    rows: number,
+    cwd?: string | null,
+    env?: Record<string, string> | null,
+    replacePath?: boolean | null,
+    record?: boolean | null,
+  ) =>
+    invoke<TerminalSession>("terminal_create_session", {
+      workspaceId,
+      cols,
+      rows,
+      cwd: cwd ?? null,
+      env: env ?? null,
+      replacePath: replacePath ?? null,
+      record: record ?? null,
+    }),
   terminalWrite: (workspaceId: string, sessionId: string, data: string) =>
     invoke<void>("terminal_write", { workspaceId, sessionId, data }),
   terminalWriteBytes: (workspaceId: string, sessionId: string, data: number[]) =>
     invoke<void>("terminal_write_bytes", { workspaceId, sessionId, data }),
+  terminalSignal: (
+    workspaceId: string,
+    sessionId: string,
+    signal: "SIGINT" | "SIGTERM" | "SIGKILL",
+  ) => invoke<void>("terminal_signal", { workspaceId, sessionId, signal }),
+  terminalInjectPath: (workspaceId: string, sessionId: string) =>
+    invoke<TerminalPathInjection>("terminal_inject_path", { workspaceId, sessionId }),
   terminalResize: (
     workspaceId: string,
     sessionId: string,
@@ -577,15 +971,39 @@ export const ipc = {
     }),
   terminalCloseSession: (workspaceId: string, sessionId: string) =>
     invoke<void>("terminal_close_session", { workspaceId, sessionId }),
-  terminalCloseWorkspaceSessions: (workspaceId: string) =>
-    invoke<void>("terminal_close_workspace_sessions", { workspaceId }),
+  terminalCloseWorkspaceSessions: (workspaceId: string, archiving?: boolean) =>
+    invoke<void>("terminal_close_workspace_sessions", { workspaceId, archiving }),
+  terminalRestoreScrollback: (workspaceId: string, sessionId: string, maxBytes?: number) =>
+    invoke<string>("terminal_restore_scrollback", { workspaceId, sessionId, maxBytes }),
   terminalListSessions: (workspaceId: string) =>
     invoke<TerminalSession[]>("terminal_list_sessions", { workspaceId }),
+  terminalSessionLimits: (workspaceId: string) =>
+    invoke<TerminalSessionLimits>("terminal_session_limits", { workspaceId }),
   terminalGetRendererDiagnostics: (workspaceId: string, sessionId: string) =>
     invoke<TerminalRendererDiagnostics>("terminal_get_renderer_diagnostics", {
       workspaceId,
       sessionId,
     }),
+  terminalListRendererDiagnostics: (workspaceId: string) =>
+    invoke<TerminalRendererDiagnostics[]>("terminal_list_renderer_diagnostics", {
+      workspaceId,
+    }),
+  terminalGetScrollback: (workspaceId: string, sessionId: string) =>
+    invoke<TerminalScrollback>("terminal_get_scrollback", { workspaceId, sessionId }),
+  terminalSearchOutput: (
+    workspaceId: string,
+    sessionId: string,
+    query: string,
+    caseSensitive = false,
+  ) =>
+    invoke<TerminalOutputSearch>("terminal_search_output", {
+      workspaceId,
+      sessionId,
+      query,
+      caseSensitive,
+    }),
+  terminalGetTranscriptPath: (workspaceId: string, sessionId: string) =>
+    invoke<string | null>("terminal_get_transcript_path", { workspaceId, sessionId }),
   terminalResumeSession: (
     workspaceId: string,
     sessionId: string,
@@ -596,6 +1014,14 @@ export const ipc = {
       sessionId,
       fromSeq: fromSeq ?? null,
     }),
+  terminalAttach: (workspaceId: string, sessionId: string, fromSeq?: number | null) =>
+    invoke<TerminalAttach>("terminal_attach", {
+      workspaceId,
+      sessionId,
+      fromSeq: fromSeq ?? null,
+    }),
+  terminalDetach: (workspaceId: string, sessionId: string) =>
+    invoke<void>("terminal_detach", { workspaceId, sessionId }),
   terminalDrainOutput: (
     workspaceId: string,
     sessionId: string,
@@ -628,6 +1054,7 @@ export const ipc = {
     ),
   installDependency: (dependency: string, method: string) =>
     invoke<InstallResult>("install_dependency", { dependency, method }),
+  refreshEnvironment: () => invoke<EnvironmentRefresh>("refresh_environment"),
   checkHarnesses: () => invoke<HarnessReport>("check_harnesses"),
   installHarness: (harnessId: string) =>
     invoke<InstallResult>("install_harness", { harnessId }),
@@ -654,6 +1081,9 @@ export async function listenThreadEvents(
 
 export interface GitRepoChangedEvent {
   repoPath: string;
+  head: boolean;
+  index: boolean;
+  refs: boolean;
 }
 
 export async function listenGitRepoChanged(
@@ -662,6 +1092,42 @@ export async function listenGitRepoChanged(
   return listen<GitRepoChangedEvent>("git-repo-changed", ({ payload }) => onEvent(payload));
 }
 
+export interface FileChangedEvent {
+  path: string;
+  mtimeMs: number | null;
+}
+
+export async function listenFileChanged(
+  onEvent: (event: FileChangedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<FileChangedEvent>("file-changed", ({ payload }) => onEvent(payload));
+}
+
+export interface GitRepoReadyEvent {
+  workspaceId: string;
+  repoPath: string;
+  status: GitStatus;
+}
+
+export async function listenGitRepoReady(
+  onEvent: (event: GitRepoReadyEvent) => void
+): Promise<UnlistenFn> {
+  return listen<GitRepoReadyEvent>("git-repo-ready", ({ payload }) => onEvent(payload));
+}
+
+export interface RepoAgentConfigChangedEvent {
+  repoPath: string;
+  path: string;
+}
+
+export async function listenRepoAgentConfigChanged(
+  onEvent: (event: RepoAgentConfigChangedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<RepoAgentConfigChangedEvent>("repo-agent-config-changed", ({ payload }) =>
+    onEvent(payload)
+  );
+}
+
 export interface ThreadUpdatedEvent {
   threadId: string;
   workspaceId: string;
@@ -683,6 +1149,19 @@ export async function listenThreadUpdated(
   return listen<ThreadUpdatedEvent>("thread-updated", ({ payload }) => onEvent(payload));
 }
 
+export interface QueuedMessagesUpdatedEvent {
+  threadId: string;
+  messages: QueuedMessage[];
+}
+
+export async function listenQueuedMessagesUpdated(
+  onEvent: (event: QueuedMessagesUpdatedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<QueuedMessagesUpdatedEvent>("queued-messages-updated", ({ payload }) =>
+    onEvent(payload)
+  );
+}
+
 export async function listenChatTurnFinished(
   onEvent: (event: ChatTurnFinishedEvent) => void
 ): Promise<UnlistenFn> {
@@ -715,6 +1194,15 @@ export async function listenEngineRuntimeUpdated(
   );
 }
 
+export async function listenEngineProtocolGap(
+  onEvent: (event: EngineProtocolGapEvent) => void
+): Promise<UnlistenFn> {
+  return listen<EngineProtocolGapEvent>(
+    "engine-protocol-gap",
+    ({ payload }) => onEvent(payload)
+  );
+}
+
 export async function listenMenuAction(
   onEvent: (action: string) => void
 ): Promise<UnlistenFn> {
@@ -747,6 +1235,36 @@ export async function listenTerminalExit(
   );
 }
 
+export async function listenTerminalCwdChanged(
+  workspaceId: string,
+  onEvent: (event: TerminalCwdChangedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<TerminalCwdChangedEvent>(
+    `terminal-cwd-${workspaceId}`,
+    ({ payload }) => onEvent(payload)
+  );
+}
+
+export async function listenTerminalTitleChanged(
+  workspaceId: string,
+  onEvent: (event: TerminalTitleChangedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<TerminalTitleChangedEvent>(
+    `terminal-title-${workspaceId}`,
+    ({ payload }) => onEvent(payload)
+  );
+}
+
+export async function listenTerminalIdleWarning(
+  workspaceId: string,
+  onEvent: (event: TerminalIdleWarningEvent) => void
+): Promise<UnlistenFn> {
+  return listen<TerminalIdleWarningEvent>(
+    `terminal-idle-warning-${workspaceId}`,
+    ({ payload }) => onEvent(payload)
+  );
+}
+
 export async function listenTerminalForegroundChanged(
   workspaceId: string,
   onEvent: (event: TerminalForegroundChangedEvent) => void
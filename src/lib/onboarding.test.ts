@@ -201,6 +201,23 @@ describe("onboarding helpers", () => {
     );
   });
 
+  it("flags an installed but signed-out Codex from the reported auth status", () => {
+    const signedOutHealth = {
+      id: "codex",
+      available: true,
+      warnings: [],
+      checks: [],
+      fixes: ["codex login"],
+      authStatus: "signed_out" as const,
+    };
+
+    expect(isCodexAuthDeferred(signedOutHealth)).toBe(true);
+    expect(isChatWorkflowReady(["codex"], readyDependencies, { codex: signedOutHealth })).toBe(
+      true,
+    );
+    expect(isCodexAuthDeferred({ ...signedOutHealth, authStatus: "signed_in" })).toBe(false);
+  });
+
   it("keeps non-auth Codex runtime failures blocking", () => {
     const runtimeFailure = {
       id: "codex",
@@ -232,6 +232,9 @@ export function onboardingStepIndex(step: OnboardingStep): number {
 }
 
 export function isCodexAuthDeferred(health?: EngineHealth): boolean {
+  if (health?.authStatus === "signed_out") {
+    return true;
+  }
   if (!health || health.available || !health.details) {
     return false;
   }
@@ -6,6 +6,7 @@ const mockIpc = vi.hoisted(() => ({
   steerMessage: vi.fn(),
   getThreadMessagesWindow: vi.fn(),
   getChatProviderUsage: vi.fn(),
+  getThreadUsage: vi.fn(),
   getActionOutput: vi.fn(),
   respondApproval: vi.fn(),
   syncThreadFromEngine: vi.fn(),
@@ -42,8 +43,11 @@ describe("chatStore send", () => {
     mockIpc.getThreadMessagesWindow.mockResolvedValue({
       messages: [],
       nextCursor: null,
+      hasMore: false,
+      totalCount: 0,
     });
     mockIpc.getChatProviderUsage.mockResolvedValue([]);
+    mockIpc.getThreadUsage.mockResolvedValue(null);
     mockIpc.getActionOutput.mockResolvedValue({
       found: true,
       outputChunks: [],
@@ -412,6 +416,8 @@ describe("chatStore send", () => {
         },
       ],
       nextCursor: null,
+      hasMore: false,
+      totalCount: 0,
     });
     useThreadStore.setState({
       threads: [
@@ -465,6 +471,25 @@ describe("chatStore send", () => {
     });
   });
 
+  it("restores the persisted usage snapshot when binding a thread", async () => {
+    mockIpc.getThreadUsage.mockResolvedValueOnce({
+      current_tokens: 50_000,
+      max_context_tokens: 200_000,
+      five_hour_percent: 25,
+    });
+
+    await useChatStore.getState().setActiveThread("thread-1");
+
+    await vi.waitFor(() => {
+      expect(useChatStore.getState().usageLimits).toMatchObject({
+        currentTokens: 50_000,
+        maxContextTokens: 200_000,
+        windowFiveHourPercent: 75,
+      });
+    });
+    expect(mockIpc.getThreadUsage).toHaveBeenCalledWith("thread-1");
+  });
+
   it("preserves stdin action output chunks from streamed events", async () => {
     vi.useFakeTimers();
 
@@ -632,6 +657,8 @@ describe("chatStore send", () => {
         },
       ],
       nextCursor: null,
+      hasMore: false,
+      totalCount: 0,
     });
 
     await useChatStore.getState().setActiveThread("thread-1");
@@ -1099,6 +1126,8 @@ describe("chatStore send", () => {
         },
       ],
       nextCursor: null,
+      hasMore: false,
+      totalCount: 0,
     });
 
     await useChatStore.getState().setActiveThread("thread-1");
@@ -1145,6 +1174,8 @@ describe("chatStore send", () => {
           id: "cursor-1",
           rowId: 1,
         },
+        hasMore: true,
+        totalCount: 0,
       })
       .mockResolvedValueOnce({
         messages: [
@@ -1178,6 +1209,8 @@ describe("chatStore send", () => {
           },
         ],
         nextCursor: null,
+        hasMore: false,
+        totalCount: 0,
       });
 
     await useChatStore.getState().setActiveThread("thread-1");
@@ -17,6 +17,7 @@ import type {
   MessageWindowCursor,
   NoticeBlock,
   SkillBlock,
+  SendMessageError,
   SteerBlock,
   StreamEvent,
   ThreadStatus
@@ -117,6 +118,14 @@ function isCodexThreadSyncRequired(metadata: Record<string, unknown> | undefined
   return metadata?.codexSyncRequired === true;
 }
 
+/** `sendMessage`, `regenerateMessage` and `retryLastTurn` reject with a tagged `SendMessageError`. */
+function sendMessageErrorText(error: unknown): string {
+  if (typeof error === "object" && error !== null && "kind" in error) {
+    return (error as SendMessageError).message;
+  }
+  return String(error);
+}
+
 function isThreadTurnActive(status: ThreadStatus): boolean {
   return status === "streaming" || status === "awaiting_approval";
 }
@@ -1978,6 +1987,19 @@ export const useChatStore = create<ChatState>((set, get) => ({
         usageLimitsLoading: shouldRefreshUsageLimits,
       });
 
+      void ipc
+        .getThreadUsage(threadId)
+        .then((usage) => {
+          if (!usage || bindSeq !== activeThreadBindSeq || get().threadId !== threadId) {
+            return;
+          }
+          const persisted = mapUsageLimitsFromEvent({ type: "UsageLimitsUpdated", usage });
+          // Anything already in state came from a live event or the provider
+          // refresh and is newer than the stored snapshot.
+          set((state) => ({ usageLimits: mergeUsageLimits(persisted, state.usageLimits) }));
+        })
+        .catch(() => {});
+
       if (shouldRefreshUsageLimits) {
         const refreshRestoredUsageLimits = async () => {
           try {
@@ -2160,7 +2182,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
         ),
         status: "error",
         streaming: false,
-        error: String(error),
+        error: sendMessageErrorText(error),
       }));
       return false;
     }
@@ -84,6 +84,7 @@ function makeReadFileResult(content: string): ReadFileResult {
     content,
     sizeBytes: content.length,
     isBinary: false,
+    truncated: false,
   };
 }
 
@@ -318,6 +319,23 @@ describe("fileStore", () => {
     );
   });
 
+  it("never saves a tab that only holds a preview of a large file", async () => {
+    mockIpc.readFile.mockResolvedValueOnce({
+      ...makeReadFileResult("head of a big log\n"),
+      sizeBytes: 50 * 1024 * 1024,
+      truncated: true,
+    });
+
+    await useFileStore.getState().openFile("/repo", "logs/big.log");
+
+    const tab = useFileStore.getState().tabs[0]!;
+    expect(tab.isTruncated).toBe(true);
+    useFileStore.getState().setTabContent(tab.id, "edited preview\n");
+    await useFileStore.getState().saveTab(tab.id);
+
+    expect(mockIpc.writeFile).not.toHaveBeenCalled();
+  });
+
   it("retargets git diff tabs when their repo root is renamed", async () => {
     mockWorkspaceState.activeRepoId = "repo-1";
     mockWorkspaceState.repos = [
@@ -17,10 +17,19 @@ import type {
   EditorRevealRequest,
   EditorRenderMode,
   EditorTab,
+  FileWriteError,
   GitCompareSource,
   GitFileCompare,
 } from "../types";
 
+/** `writeFile` rejects with a tagged `FileWriteError`. */
+function fileWriteErrorText(error: unknown): string {
+  if (typeof error === "object" && error !== null && "kind" in error) {
+    return (error as FileWriteError).message;
+  }
+  return String(error);
+}
+
 interface ResolvedFileContext {
   absolutePath: string;
   gitRepoPath: string | null;
@@ -256,6 +265,7 @@ export const useFileStore = create<FileStoreState>((set, get) => ({
                 content: result.content,
                 savedContent: result.content,
                 isBinary: result.isBinary,
+                isTruncated: result.truncated,
                 isLoading: false,
               }
             : t,
@@ -500,11 +510,11 @@ export const useFileStore = create<FileStoreState>((set, get) => ({
 
   saveTab: async (tabId) => {
     const tab = get().tabs.find((t) => t.id === tabId);
-    if (!tab || !tab.isDirty) return;
+    if (!tab || !tab.isDirty || tab.isTruncated) return;
 
     // Check if the file was modified externally since we loaded/last-saved it
     try {
-      const disk = await ipc.readFile(tab.rootPath, tab.filePath);
+      const disk = await ipc.readFile(tab.rootPath, tab.filePath, true);
       if (!disk.isBinary && disk.content !== tab.savedContent) {
         toast.warning(t("app:editor.toasts.modifiedExternally", { name: tab.fileName }));
         set((state) => ({
@@ -544,7 +554,7 @@ export const useFileStore = create<FileStoreState>((set, get) => ({
 
       toast.success(t("app:editor.toasts.saved", { name: tab.fileName }));
     } catch (err) {
-      toast.error(t("app:editor.toasts.saveFailed", { error: String(err) }));
+      toast.error(t("app:editor.toasts.saveFailed", { error: fileWriteErrorText(err) }));
     }
   },
 }));
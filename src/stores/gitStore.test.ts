@@ -39,8 +39,11 @@ function makeStatus(branch: string, files: GitStatus["files"] = []): GitStatus {
   return {
     branch,
     files,
+    upstreamBranch: null,
     ahead: 0,
     behind: 0,
+    detachedHead: false,
+    conflicts: [],
   };
 }
 
@@ -60,6 +63,7 @@ function makeDiffPreview(content = ""): GitDiffPreview {
     truncated: false,
     originalBytes: content.length,
     returnedBytes: content.length,
+    hunks: [],
   };
 }
 
@@ -3,9 +3,11 @@ import type {
   GitBranch,
   GitBranchScope,
   GitCommit,
+  GitCommitResult,
   GitDiffPreview,
   GitRemote,
   GitStash,
+  GitStashApplyResult,
   GitStatus,
   GitWorktree,
 } from "../types";
@@ -389,7 +391,7 @@ interface GitState {
   unstage: (repoPath: string, filePath: string) => Promise<void>;
   unstageMany: (repoPath: string, files: string[]) => Promise<void>;
   discardFiles: (repoPath: string, files: string[]) => Promise<void>;
-  commit: (repoPath: string, message: string) => Promise<string>;
+  commit: (repoPath: string, message: string) => Promise<GitCommitResult>;
   softResetLastCommit: (repoPath: string) => Promise<void>;
   fetchRemote: (repoPath: string) => Promise<void>;
   pullRemote: (repoPath: string) => Promise<void>;
@@ -410,8 +412,8 @@ interface GitState {
   pruneWorktrees: (repoPath: string) => Promise<void>;
   loadStashes: (repoPath: string) => Promise<void>;
   pushStash: (repoPath: string, message?: string) => Promise<void>;
-  applyStash: (repoPath: string, stashIndex: number) => Promise<void>;
-  popStash: (repoPath: string, stashIndex: number) => Promise<void>;
+  applyStash: (repoPath: string, stashIndex: number) => Promise<GitStashApplyResult>;
+  popStash: (repoPath: string, stashIndex: number) => Promise<GitStashApplyResult>;
   selectCommit: (repoPath: string, commitHash: string) => Promise<void>;
   clearCommitSelection: () => void;
   loadRemotes: (repoPath: string) => Promise<void>;
@@ -983,12 +985,10 @@ export const useGitStore = create<GitState>((set, get) => {
     pushStash: async (repoPath, message) => {
       await runRepoMutationWithRefresh(repoPath, () => ipc.pushGitStash(repoPath, message));
     },
-    applyStash: async (repoPath, stashIndex) => {
-      await runRepoMutationWithRefresh(repoPath, () => ipc.applyGitStash(repoPath, stashIndex));
-    },
-    popStash: async (repoPath, stashIndex) => {
-      await runRepoMutationWithRefresh(repoPath, () => ipc.popGitStash(repoPath, stashIndex));
-    },
+    applyStash: async (repoPath, stashIndex) =>
+      runRepoMutationWithRefresh(repoPath, () => ipc.applyGitStash(repoPath, stashIndex)),
+    popStash: async (repoPath, stashIndex) =>
+      runRepoMutationWithRefresh(repoPath, () => ipc.popGitStash(repoPath, stashIndex)),
     selectCommit: async (repoPath, commitHash) => {
       const current = get().selectedCommitHash;
       if (current === commitHash) {
@@ -9,6 +9,13 @@ export interface Workspace {
   lastOpenedAt: string;
 }
 
+export interface WorkspaceArchiveReport {
+  workspaceId: string;
+  stoppedWatchers: string[];
+  closedTerminalSessions: string[];
+  cancelledThreads: string[];
+}
+
 export interface KeepAwakeState {
   supported: boolean;
   enabled: boolean;
@@ -50,6 +57,38 @@ export interface HelperStatus {
   message?: string | null;
 }
 
+export type LocalMetricsPeriod = "day" | "week" | "month";
+
+export interface LocalTurnMetric {
+  engineId: string;
+  modelId: string;
+  turns: number;
+}
+
+/** Usage counters stored only on this machine. */
+export interface LocalMetrics {
+  period: LocalMetricsPeriod;
+  startDay: string;
+  endDay: string;
+  turns: number;
+  tokens: number;
+  approvalsAnswered: number;
+  terminalsOpened: number;
+  commits: number;
+  turnsByModel: LocalTurnMetric[];
+}
+
+/** Snapshot of the sqlite connection pool, for diagnostics. */
+export interface DatabasePoolMetrics {
+  maxConnections: number;
+  acquireTimeoutMs: number;
+  open: number;
+  idle: number;
+  inUse: number;
+  waiters: number;
+  peakInUse: number;
+}
+
 export type TerminalNotificationIntegrationId = "claude" | "codex";
 
 export interface TerminalNotificationIntegrationStatus {
@@ -77,6 +116,19 @@ export interface Repo {
   defaultBranch: string;
   isActive: boolean;
   trustLevel: TrustLevel;
+  detectedConfigs?: DetectedAgentConfig[];
+}
+
+export type AgentConfigKind = "instructions" | "config";
+
+export interface DetectedAgentConfig {
+  path: string;
+  engineId: string;
+  kind: AgentConfigKind;
+  excerpt: string;
+  adopted: boolean;
+  stale: boolean;
+  note: string | null;
 }
 
 export interface WorkspaceGitSelectionStatus {
@@ -160,12 +212,41 @@ export interface Thread {
   modelId: string;
   engineThreadId: string | null;
   engineMetadata?: Record<string, unknown>;
+  reasoningEffort?: string | null;
   title: string;
   status: ThreadStatus;
   messageCount: number;
   totalTokens: number;
   createdAt: string;
   lastActivityAt: string;
+  engineCapabilities?: EngineCapabilities | null;
+}
+
+export type ThreadExportFormat = "markdown" | "json";
+
+export type ConversationImportFormat = "chatgpt_json" | "markdown";
+
+export type UsageGroupBy = "day" | "engine" | "model";
+
+export interface UsageSummaryRow {
+  /** UTC day, `YYYY-MM-DD`. */
+  day?: string | null;
+  engineId?: string | null;
+  modelId?: string | null;
+  inputTokens: number;
+  outputTokens: number;
+  messageCount: number;
+}
+
+export interface UsageSummary {
+  rows: UsageSummaryRow[];
+  totals: UsageSummaryRow;
+}
+
+export interface ConversationImport {
+  threads: Thread[];
+  importedMessages: number;
+  skippedItems: number;
 }
 
 export interface CodexRemoteThread {
@@ -229,6 +310,9 @@ export interface Message {
   createdAt: string;
   hydration?: "full" | "summary";
   hasDeferredContent?: boolean;
+  regeneratedFrom?: string | null;
+  /** Replaced by a regenerated response; rendered collapsed. */
+  superseded?: boolean;
 }
 
 export interface MessageWindowCursor {
@@ -240,6 +324,9 @@ export interface MessageWindowCursor {
 export interface MessageWindow {
   messages: Message[];
   nextCursor: MessageWindowCursor | null;
+  hasMore: boolean;
+  /** Messages in the whole thread, not just this window. */
+  totalCount: number;
 }
 
 export type ActionType =
@@ -250,6 +337,7 @@ export type ActionType =
   | "command"
   | "git"
   | "search"
+  | "mcp_tool"
   | "other";
 
 export interface TextBlock {
@@ -306,6 +394,97 @@ export interface ActionOutputPayload {
   truncated: boolean;
 }
 
+export interface TurnDiagnostic {
+  source:
+    | "unmapped_notification"
+    | "unmapped_server_request"
+    | "dropped_by_thread_filter"
+    | "dropped_by_turn_filter"
+    | "stderr";
+  at: string;
+  message: string;
+  count: number;
+}
+
+/**
+ * Savings from storing repeated large block payloads once. `inlineBytes` is
+ * what the referencing messages would occupy with the payload inlined.
+ */
+export interface BlobStorageReport {
+  category: string;
+  blobCount: number;
+  unreferencedBlobCount: number;
+  referenceCount: number;
+  storedBytes: number;
+  inlineBytes: number;
+  savedBytes: number;
+}
+
+export interface TurnEventLog {
+  path: string;
+  exists: boolean;
+  tail: Array<{ at: string; event: Record<string, unknown> }>;
+}
+
+export interface ThreadModelHistoryEntry {
+  messageId: string;
+  engineId: string;
+  modelId: string;
+  reasoningEffort: string | null;
+  startedAt: string;
+}
+
+export interface ThreadModelRange {
+  engineId: string;
+  modelId: string;
+  reasoningEffort: string | null;
+  firstMessageId: string;
+  lastMessageId: string;
+  startedAt: string;
+  turnCount: number;
+}
+
+export interface ThreadModelHistory {
+  threadId: string;
+  entries: ThreadModelHistoryEntry[];
+  ranges: ThreadModelRange[];
+}
+
+export type ThreadTimelineEntryKind =
+  | "user_message"
+  | "assistant_summary"
+  | "action"
+  | "approval"
+  | "file_changes"
+  | "git_operation";
+
+export interface ThreadTimelineEntry {
+  id: string;
+  kind: ThreadTimelineEntryKind;
+  messageId: string | null;
+  createdAt: string;
+  excerpt?: string;
+  actionType?: string;
+  summary?: string;
+  success?: boolean;
+  durationMs?: number;
+  decision?: string;
+  waitMs?: number;
+  fileChangeCount?: number;
+  outcome?: "completed" | "refused" | "failed";
+}
+
+export interface ThreadTimelineCursor {
+  createdAt: string;
+  kind: ThreadTimelineEntryKind;
+  id: string;
+}
+
+export interface ThreadTimeline {
+  entries: ThreadTimelineEntry[];
+  nextCursor: ThreadTimelineCursor | null;
+}
+
 export interface ApprovalBlock {
   type: "approval";
   approvalId: string;
@@ -469,6 +648,12 @@ export interface EngineCapabilities {
   permissionModes: string[];
   sandboxModes: string[];
   approvalDecisions: string[];
+  planMode?: boolean;
+  attachments?: boolean;
+  approvals?: boolean;
+  interrupt?: boolean;
+  reasoningEffort?: boolean;
+  threadRename?: boolean;
 }
 
 export interface EngineModel {
@@ -519,6 +704,16 @@ export interface EngineHealth {
   checks?: string[];
   fixes?: string[];
   protocolDiagnostics?: CodexProtocolDiagnostics;
+  timeouts?: EngineTimeouts;
+  authStatus?: EngineAuthStatus;
+}
+
+export type EngineAuthStatus = "signed_in" | "signed_out" | "not_required" | "unknown";
+
+export interface EngineTimeouts {
+  requestTimeoutSecs: number;
+  turnRequestTimeoutSecs?: number;
+  turnCompletionInactivityTimeoutSecs?: number;
 }
 
 export interface CodexMethodAvailability {
@@ -716,6 +911,14 @@ export interface EngineRuntimeUpdatedEvent {
   toast?: RuntimeToast;
 }
 
+export interface EngineProtocolGapEvent {
+  engineId: string;
+  method: string;
+  count: number;
+  /** Request family whose safe default was sent; absent for method-not-found. */
+  fallback?: string;
+}
+
 export interface EngineCheckResult {
   command: string;
   success: boolean;
@@ -743,8 +946,67 @@ export interface GitFileStatus {
 export interface GitStatus {
   branch: string;
   files: GitFileStatus[];
+  upstreamBranch: string | null;
   ahead: number;
   behind: number;
+  detachedHead: boolean;
+  conflicts: GitConflict[];
+}
+
+export type GitConflictKind =
+  | "both_modified"
+  | "both_added"
+  | "both_deleted"
+  | "added_by_us"
+  | "added_by_them"
+  | "deleted_by_us"
+  | "deleted_by_them";
+
+export interface GitConflict {
+  path: string;
+  kind: GitConflictKind;
+}
+
+export type GitConflictResolution = "ours" | "theirs" | "resolved";
+
+export interface WorkspaceGitRepoSummary {
+  repoId: string;
+  repoName: string;
+  repoPath: string;
+  branch: string | null;
+  ahead: number | null;
+  behind: number | null;
+  dirtyCount: number;
+  stagedCount: number;
+  timedOut: boolean;
+  error: string | null;
+}
+
+export interface WorkspaceGitFile {
+  repoId: string;
+  repoPath: string;
+  path: string;
+  indexStatus?: string | null;
+  worktreeStatus?: string | null;
+}
+
+export interface WorkspaceGitOverview {
+  workspaceId: string;
+  repos: WorkspaceGitRepoSummary[];
+  files: WorkspaceGitFile[];
+  partial: boolean;
+}
+
+export interface GitRepoFiles {
+  repoPath: string;
+  files: string[];
+}
+
+export interface GitMultiRepoResult {
+  repoPath: string;
+  ok: boolean;
+  commitHash: string | null;
+  error: string | null;
 }
 
 export interface GitDiffPreview {
@@ -752,6 +1014,13 @@ export interface GitDiffPreview {
   truncated: boolean;
   originalBytes: number;
   returnedBytes: number;
+  hunks: GitDiffHunk[];
+}
+
+export interface GitDiffHunk {
+  id: string;
+  index: number;
+  header: string;
 }
 
 export type GitCompareSource = "changes" | "staged";
@@ -808,6 +1077,31 @@ export interface GitCommit {
   authoredAt: string;
 }
 
+export interface GitCommitResult {
+  hash: string;
+  filesChanged: number;
+  insertions: number;
+  deletions: number;
+}
+
+export interface GitCommitMessage {
+  subject: string;
+  body: string;
+}
+
+/** Rejection value of `generateCommitMessage`. */
+export type GitCommitMessageError =
+  | { kind: "nothing_staged"; message: string }
+  | { kind: "engine_unavailable"; engineId: string; message: string }
+  | { kind: "timed_out"; engineId: string; timeoutMs: number; message: string }
+  | { kind: "failed"; engineId: string; message: string }
+  | { kind: "other"; message: string };
+
+/** Rejection value of `stageHunks` and `unstageHunks`; refresh the diff on `stale_diff`. */
+export type GitHunkError =
+  | { kind: "stale_diff"; path: string; message: string }
+  | { kind: "other"; message: string };
+
 export interface GitCommitPage {
   entries: GitCommit[];
   offset: number;
@@ -820,9 +1114,15 @@ export interface GitStash {
   index: number;
   name: string;
   branchHint?: string;
+  message?: string;
   createdAt?: string;
 }
 
+export interface GitStashApplyResult {
+  conflictedFiles: string[];
+  dropped: boolean;
+}
+
 export interface GitWorktree {
   path: string;
   headSha: string | null;
@@ -842,15 +1142,56 @@ export interface GitInitRepoStatus {
   blockingRepoPath: string | null;
 }
 
+/** What git tooling a repo needs and what the machine provides. */
+export interface GitPreflight {
+  repoPath: string;
+  binaryFound: boolean;
+  binaryPath: string | null;
+  binaryVersion: string | null;
+  installHint: string | null;
+  libgit2Version: string;
+  libgit2Https: boolean;
+  libgit2Ssh: boolean;
+  credentialHelper: string | null;
+  usesLfs: boolean;
+  signsCommits: boolean;
+  signingProgram: string | null;
+  /** Repo features that only work through the git binary ("lfs", "commit_signing", "hooks"). */
+  binaryRequiredFeatures: string[];
+}
+
+/** Audit entry for a destructive git operation the app ran or refused. */
+export interface GitOperationLog {
+  id: number;
+  repoPath: string;
+  threadId: string | null;
+  operation:
+    | "force_push"
+    | "delete_branch"
+    | "squash_commits"
+    | "reword_commit"
+    | "amend_commit";
+  target: string;
+  force: boolean;
+  forceWithLease: boolean;
+  outcome: "completed" | "refused" | "failed";
+  detail: string | null;
+  createdAt: string;
+}
+
 export interface WorktreeSessionInfo {
   repoPath: string;
   worktreePath: string;
   branch: string;
 }
 
+export type FileTreeGitStatus = "modified" | "added" | "untracked" | "ignored" | "conflicted";
+
 export interface FileTreeEntry {
   path: string;
   isDir: boolean;
+  isSymlink: boolean;
+  gitStatus: FileTreeGitStatus | null;
 }
 
 export interface FileTreePage {
@@ -866,8 +1207,16 @@ export interface ReadFileResult {
   content: string;
   sizeBytes: number;
   isBinary: boolean;
+  /** `content` is only the start of a file above the preview threshold. */
+  truncated: boolean;
 }
 
+/** Rejection value of `writeFile`; `parent_missing` carries the missing directory. */
+export type FileWriteError =
+  | { kind: "parent_missing"; path: string; message: string }
+  | { kind: "permission_denied"; path: string; message: string }
+  | { kind: "other"; path: string; message: string };
+
 export interface ResolvedEditorFileReference {
   repoPath: string;
   filePath: string;
@@ -902,20 +1251,39 @@ export interface EditorTab {
   isDirty: boolean;
   isLoading: boolean;
   isBinary: boolean;
+  /** Shows a preview of a file too large to load; never saved. */
+  isTruncated?: boolean;
   renderMode: EditorRenderMode;
   gitContext: GitEditorContext | null;
   pendingReveal: EditorRevealRequest | null;
   loadError?: string;
 }
 
+export interface TerminalSessionLimits {
+  workspaceId: string;
+  /** Open sessions, counting ones still spawning. */
+  open: number;
+  limit: number;
+}
+
 export interface TerminalSession {
   id: string;
   workspaceId: string;
   shell: string;
   cwd: string;
+  currentCwd?: string | null;
+  title?: string | null;
+  /** False while output events are paused by `terminalDetach`. */
+  attached?: boolean;
   createdAt: string;
 }
 
+export interface TerminalPathInjection {
+  sessionId: string;
+  path: string;
+  command: string;
+}
+
 export interface TerminalNotification {
   id: string;
   workspaceId: string;
@@ -950,10 +1318,58 @@ export interface TerminalResumeSession {
   chunks: TerminalReplayChunk[];
 }
 
+export interface TerminalAttach {
+  session: TerminalSession;
+  replay: TerminalResumeSession;
+}
+
+/** Output events with `latestSeq <= seq` are already included in `data`. */
+export interface TerminalScrollback {
+  seq: number;
+  data: string;
+  truncated: boolean;
+}
+
+export interface TerminalOutputMatch {
+  /** Byte offset into the buffer as it was when searched. */
+  offset: number;
+  length: number;
+  snippet: string;
+  snippetMatchStart: number;
+  snippetMatchEnd: number;
+}
+
+export interface TerminalOutputSearch {
+  seq: number;
+  /** Bytes already trimmed from the front; `origin + offset` is stable. */
+  origin: number;
+  bufferBytes: number;
+  matches: TerminalOutputMatch[];
+  limited: boolean;
+}
+
 export interface TerminalExitEvent {
   sessionId: string;
   code: number | null;
   signal: number | null;
+  /** Set when the exit status could not be read or the output stream failed. */
+  error: string | null;
+}
+
+export interface TerminalCwdChangedEvent {
+  sessionId: string;
+  cwd: string;
+}
+
+export interface TerminalTitleChangedEvent {
+  sessionId: string;
+  title: string;
+}
+
+/** Sent a minute before an idle session is closed; any input cancels it. */
+export interface TerminalIdleWarningEvent {
+  sessionId: string;
+  closesInMs: number;
 }
 
 export interface TerminalForegroundChangedEvent {
@@ -982,6 +1398,9 @@ export interface TerminalEnvSnapshot {
   lcAll: string | null;
   lcCtype: string | null;
   path: string | null;
+  shell: string | null;
+  shellArgs: string[];
+  overrides?: Record<string, string>;
 }
 
 export interface TerminalResizeSnapshot {
@@ -996,6 +1415,7 @@ export interface TerminalIoCounters {
   stdinWrites: number;
   stdinBytes: number;
   stdinCtrlC: number;
+  signalsSent?: number;
   lastStdinWriteDurationMs: number | null;
   stdoutReads: number;
   stdoutBytes: number;
@@ -1098,6 +1518,18 @@ export interface DepStatus {
 export interface InstallResult {
   success: boolean;
   message: string;
+  /** Set after a successful dependency install. */
+  resolvable?: boolean;
+}
+
+export interface ResolvedExecutable {
+  name: string;
+  path: string | null;
+}
+
+export interface EnvironmentRefresh {
+  path: string | null;
+  executables: ResolvedExecutable[];
 }
 
 export interface InstallProgressEvent {
@@ -1240,6 +1672,12 @@ export interface UsageLimitsUpdatedEvent {
   };
 }
 
+export interface EngineUsageLimits {
+  engineId: string;
+  usage: UsageLimitsUpdatedEvent["usage"];
+  updatedAt: string;
+}
+
 export interface ModelReroutedEvent {
   type: "ModelRerouted";
   from_model: string;
@@ -1303,6 +1741,56 @@ export type ChatInputItem =
       path: string;
     };
 
+export type ContextContributorKind =
+  | "message"
+  | "attachment"
+  | "context_file"
+  | "skill"
+  | "instructions"
+  | "thread_history";
+
+export interface ContextContributor {
+  kind: ContextContributorKind;
+  label: string;
+  tokens: number;
+}
+
+/** Rejection value of `sendMessage`, `regenerateMessage` and `retryLastTurn`. */
+export type SendMessageError =
+  | {
+      kind: "context_budget_exceeded";
+      engineId: string;
+      estimatedTokens: number;
+      thresholdTokens: number;
+      maxInputTokens: number;
+      contributors: ContextContributor[];
+      suggestions: string[];
+      message: string;
+    }
+  | { kind: "other"; message: string };
+
+/** Outcome of sending one prompt to several threads, keyed by thread id. */
+export interface MultiSendResult {
+  assistantMessageIds: Record<string, string>;
+  errors: Record<string, string>;
+}
+
+/** A message waiting for the thread's running turn to end. */
+export interface QueuedMessage {
+  id: string;
+  threadId: string;
+  message: string;
+  modelId?: string | null;
+  reasoningEffort?: string | null;
+  attachments?: Omit<ChatAttachment, "id">[] | null;
+  inputItems?: ChatInputItem[] | null;
+  planMode: boolean;
+  runAfterError: boolean;
+  /** Queued before a restart or failed to send; not sent automatically. */
+  held: boolean;
+  createdAt: string;
+}
+
 // ── Context Usage ───────────────────────────────────────────────────
 
 export interface ContextUsage {
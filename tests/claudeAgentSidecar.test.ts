@@ -1,6 +1,8 @@
 import { afterEach, describe, expect, it } from "vitest";
 import { ChildProcessWithoutNullStreams, spawn } from "node:child_process";
+import { mkdtemp, rm, writeFile } from "node:fs/promises";
 import { createServer } from "node:http";
+import { tmpdir } from "node:os";
 import type { AddressInfo } from "node:net";
 import { fileURLToPath, pathToFileURL } from "node:url";
 import path from "node:path";
@@ -1266,4 +1268,89 @@ describe("claude-agent-sdk-server sidecar", () => {
     expect(firstCompletion?.actionId).not.toBe(secondStart?.actionId);
     expect(secondCompletion?.actionId).not.toBe(firstStart?.actionId);
   });
+
+  it("sends supported attachments and reports unsupported ones without failing the turn", async () => {
+    const attachmentDir = await mkdtemp(path.join(tmpdir(), "panes attachments "));
+    try {
+      const notesPath = path.join(attachmentDir, "meeting notes.md");
+      const imagePath = path.join(attachmentDir, "screen shot.png");
+      const archivePath = path.join(attachmentDir, "bundle.zip");
+      await writeFile(notesPath, "# Notes\nship it");
+      await writeFile(imagePath, Buffer.from([0x89, 0x50, 0x4e, 0x47]));
+      await writeFile(archivePath, "PK");
+
+      const harness = await spawnHarness({
+        steps: [],
+        emitObservationResult: true,
+        emitPromptContent: true,
+        sessionId: "session-attachments",
+      });
+
+      harness.send({
+        id: "query-attachments",
+        method: "query",
+        params: {
+          prompt: "review these",
+          cwd: repoRoot,
+          attachments: [
+            { fileName: "meeting notes.md", filePath: notesPath, sizeBytes: 15 },
+            { fileName: "screen shot.png", filePath: imagePath, sizeBytes: 4 },
+            { fileName: "bundle.zip", filePath: archivePath, sizeBytes: 2 },
+            {
+              fileName: "huge.txt",
+              filePath: notesPath,
+              sizeBytes: 11 * 1024 * 1024,
+            },
+          ],
+        },
+      });
+
+      const completed = await harness.waitFor(
+        (event) => event.id === "query-attachments" && event.type === "turn_completed",
+      );
+      expect(completed.status).toBe("completed");
+
+      const errors = harness.events.filter(
+        (event) => event.id === "query-attachments" && event.type === "error",
+      );
+      expect(errors.map((event) => event.recoverable)).toEqual([true, true]);
+      expect(errors[0]?.message).toContain('Attachment "bundle.zip" is not supported');
+      expect(errors[1]?.message).toContain('Attachment "huge.txt" exceeds the 10 MB');
+
+      const [observation] = parseObservationResults(harness, "query-attachments");
+      const content = observation?.result.content as Array<Record<string, unknown>>;
+      expect(content.map((block) => block.type)).toEqual(["text", "text", "image"]);
+      expect(content[1]?.text).toContain(`meeting notes.md (${notesPath})`);
+      expect(content[1]?.text).toContain("ship it");
+      expect(content[2]?.source).toMatchObject({ media_type: "image/png" });
+    } finally {
+      await rm(attachmentDir, { recursive: true, force: true });
+    }
+  });
+
+  it("fails the turn when no attachment or prompt can be sent", async () => {
+    const harness = await spawnHarness({ steps: [] });
+
+    harness.send({
+      id: "query-missing-attachment",
+      method: "query",
+      params: {
+        prompt: "",
+        cwd: repoRoot,
+        attachments: [{ fileName: "gone.txt", filePath: path.join(repoRoot, "gone.txt") }],
+      },
+    });
+
+    const completed = await harness.waitFor(
+      (event) =>
+        event.id === "query-missing-attachment" && event.type === "turn_completed",
+    );
+    const errors = harness.events.filter(
+      (event) => event.id === "query-missing-attachment" && event.type === "error",
+    );
+
+    expect(completed.status).toBe("failed");
+    expect(errors.map((event) => event.recoverable)).toEqual([true, false]);
+    expect(errors[0]?.message).toContain('Attachment "gone.txt" could not be read');
+  });
 });
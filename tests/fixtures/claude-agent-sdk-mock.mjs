@@ -40,13 +40,25 @@ async function runHooks(options, hookName, input) {
   }
 }
 
-export function query({ options }) {
+export function query({ prompt, options }) {
   const scenario = parseScenario();
   let closed = false;
 
   const iterator = (async function* () {
     const observations = [];
 
+    if (scenario.emitPromptContent) {
+      const content = [];
+      if (typeof prompt === "string") {
+        content.push({ type: "text", text: prompt });
+      } else {
+        for await (const message of prompt) {
+          content.push(...(message?.message?.content ?? []));
+        }
+      }
+      observations.push({ type: "prompt_content", result: { content } });
+    }
+
     if (scenario.emitQueryOptions) {
       observations.push({
         type: "query_options",